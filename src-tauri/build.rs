@@ -1,3 +1,251 @@
+// Declaring the app's commands makes each one a permission (`allow-<command>`),
+// so a window can only call the commands its capability in `capabilities/`
+// grants. A command added to `generate_handler!` must be listed here and
+// granted to the windows that call it.
+
+/// Every command registered with `generate_handler!` in `src/lib.rs`, in any build
+const COMMANDS: &[&str] = &[
+    "get_version",
+    "get_platform",
+    "list_profiles",
+    "switch_profile",
+    "start_task",
+    "cancel_task",
+    "interrupt_task",
+    "abort_all_tasks",
+    "await_task",
+    "get_task",
+    "open_observer_window",
+    "watch_task",
+    "get_task_messages",
+    "get_message_attachment",
+    "list_tasks",
+    "list_tasks_filtered",
+    "search_tasks",
+    "delete_task",
+    "clear_task_history",
+    "list_archived_tasks",
+    "restore_task",
+    "save_task_message",
+    "get_message_diff",
+    "get_message_sources",
+    "get_task_progress",
+    "list_task_artifacts",
+    "save_task_status",
+    "get_task_timeline",
+    "save_task_session",
+    "save_task_summary",
+    "complete_task",
+    "respond_to_permission",
+    "resume_session",
+    "send_follow_up",
+    "sync_session",
+    "get_session_context",
+    "get_interrupted_tasks",
+    "get_task_checkpoint",
+    "resume_from_checkpoint",
+    "create_checkpoint",
+    "list_checkpoints",
+    "delete_checkpoint",
+    "restore_checkpoint",
+    "get_task_plan",
+    "approve_task_plan",
+    "fork_task",
+    "get_task_forks",
+    "rerun_task",
+    "get_task_reruns",
+    "edit_message_and_rerun",
+    "get_message_versions",
+    "delete_task_message",
+    "redact_task_message",
+    "annotate_message",
+    "rate_message",
+    "list_message_ratings",
+    "get_task_notes",
+    "add_task_note",
+    "update_task_note",
+    "delete_task_note",
+    "list_follow_ups",
+    "list_projects",
+    "create_project",
+    "rename_project",
+    "delete_project",
+    "move_tasks_to_project",
+    "add_task_tag",
+    "remove_task_tag",
+    "list_tags",
+    "start_comparison_task",
+    "get_comparison",
+    "diff_tasks",
+    "get_api_keys",
+    "add_api_key",
+    "remove_api_key",
+    "get_debug_mode",
+    "set_debug_mode",
+    "get_app_settings",
+    "get_last_workspace",
+    "set_last_workspace",
+    "list_workspaces",
+    "create_workspace",
+    "select_workspace",
+    "detect_workspace",
+    "has_api_key",
+    "set_api_key",
+    "get_api_key",
+    "validate_api_key",
+    "validate_api_key_for_provider",
+    "clear_api_key",
+    "get_all_api_keys",
+    "has_any_api_key",
+    "run_provider_diagnostics",
+    "detect_available_providers",
+    "get_onboarding_complete",
+    "set_onboarding_complete",
+    "get_startup_health",
+    "check_claude_cli",
+    "get_claude_version",
+    "install_opencode_cli",
+    "get_cli_runtime",
+    "set_cli_runtime",
+    "get_selected_model",
+    "set_selected_model",
+    "get_summary_model",
+    "set_summary_model",
+    "test_ollama_connection",
+    "get_ollama_config",
+    "set_ollama_config",
+    "get_azure_foundry_config",
+    "set_azure_foundry_config",
+    "test_azure_foundry_connection",
+    "save_azure_foundry_config",
+    "fetch_openrouter_models",
+    "test_litellm_connection",
+    "fetch_litellm_models",
+    "get_litellm_config",
+    "set_litellm_config",
+    "validate_bedrock_credentials",
+    "save_bedrock_credentials",
+    "get_bedrock_credentials",
+    "fetch_bedrock_models",
+    "is_e2e_mode",
+    "is_safe_mode",
+    "exit_safe_mode",
+    // Only registered in debug builds and with the `e2e` feature
+    "seed_test_data",
+    "get_provider_settings",
+    "set_active_provider",
+    "get_connected_provider",
+    "set_connected_provider",
+    "remove_connected_provider",
+    "update_provider_model",
+    "list_model_parameters",
+    "set_model_parameters",
+    "set_provider_debug_mode",
+    "get_provider_debug_mode",
+    "log_event",
+    "get_log_level",
+    "set_log_level",
+    "query_logs",
+    "get_slow_commands",
+    "list_crash_reports",
+    "submit_crash_report",
+    "export_diagnostics",
+    "get_db_stats",
+    "get_storage_savings",
+    "dedup_attachments",
+    "get_backup_settings",
+    "set_backup_settings",
+    "create_backup",
+    "list_backups",
+    "restore_backup",
+    "get_downgrade_notice",
+    "import_downgraded_tasks",
+    "dismiss_downgrade_notice",
+    "export_task_html",
+    "export_task_pdf",
+    "export_task_shareable",
+    "export_task",
+    "set_github_token",
+    "has_github_token",
+    "remove_github_token",
+    "detect_github_repo",
+    "create_github_issue",
+    "create_github_draft_pr",
+    "get_api_server_status",
+    "set_api_server_enabled",
+    "set_api_server_websocket_enabled",
+    "set_api_server_metrics_enabled",
+    "get_api_server_token",
+    "regenerate_api_server_token",
+    "get_sync_folder",
+    "set_sync_folder",
+    "sync_history_folder",
+    "get_encrypted_sync_status",
+    "set_encrypted_sync_enabled",
+    "pair_device",
+    "sync_now",
+    "get_web_search_settings",
+    "set_web_search_settings",
+    "get_budget_limits",
+    "set_budget_limits",
+    "get_budget_status",
+    "get_usage_dashboard",
+    "get_task_usage",
+    "get_usage_summary",
+    "export_task_metrics_csv",
+    "import_claude_code_sessions",
+    "import_opencode_sessions",
+    "list_slash_commands",
+    "create_slash_command",
+    "update_slash_command",
+    "delete_slash_command",
+    "get_workspace_hooks",
+    "set_workspace_hooks",
+    "get_workspace_defaults",
+    "set_workspace_defaults",
+    "get_workspace_agent_settings",
+    "set_workspace_agent_settings",
+    "list_workspace_templates",
+    "create_workspace_from_template",
+    "export_workspace_template",
+    "preview_workspace_template",
+    "import_workspace_template",
+    "delete_workspace_template",
+    "get_workspace_postprocess",
+    "set_workspace_postprocess",
+    "get_workspace_sandbox",
+    "set_workspace_sandbox",
+    "index_project",
+    "search_project_files",
+    "list_ssh_hosts",
+    "create_ssh_host",
+    "delete_ssh_host",
+    "test_ssh_host",
+    "create_remote_workspace",
+    "read_remote_file",
+    "get_workspace_env",
+    "set_workspace_env",
+    "get_task_env",
+    "set_task_env",
+    "open_terminal",
+    "write_terminal",
+    "resize_terminal",
+    "close_terminal",
+    "add_attachment",
+    "add_attachment_data",
+    "attach_url",
+    "get_attachment",
+    "get_attachment_thumbnail",
+    "get_message_attachment_thumbnail",
+    "get_max_attachment_size",
+    "set_max_attachment_size",
+    "transcribe_audio",
+];
+
 fn main() {
-    tauri_build::build()
+    tauri_build::try_build(
+        tauri_build::Attributes::new()
+            .app_manifest(tauri_build::AppManifest::new().commands(COMMANDS)),
+    )
+    .expect("failed to run tauri build script");
 }
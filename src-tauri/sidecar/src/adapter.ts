@@ -6,11 +6,17 @@ import { spawnSync, spawn, type ChildProcessWithoutNullStreams } from 'child_pro
 import os from 'os';
 import { StreamParser } from './stream-parser';
 import { getOpenCodeCliPath, isOpenCodeAvailable, OpenCodeCliNotFoundError } from './cli-path';
+import { killProcessTree } from './process-tree';
 import {
   generateOpenCodeConfig,
   buildOpenCodeEnvironment,
+  applyWebSearchSettings,
+  applySandbox,
+  applyRemote,
+  applyAgentSettings,
   getOpenCodeConfigDir,
   ACCOMPLISH_AGENT_NAME,
+  PLAN_AGENT_NAME,
 } from './config-generator';
 import type {
   TaskConfig,
@@ -18,8 +24,12 @@ import type {
   TaskProgress,
   OpenCodeMessage,
   PermissionRequest,
+  TaskUsage,
   ApiKeys,
   OpenCodeToolUseMessage,
+  GenerateTextConfig,
+  TranscriptMessage,
+  SessionContext,
 } from './types';
 
 export interface OpenCodeAdapterEvents {
@@ -28,6 +38,7 @@ export interface OpenCodeAdapterEvents {
   'tool-result': [string];
   'permission-request': [PermissionRequest];
   progress: [TaskProgress];
+  usage: [TaskUsage];
   complete: [TaskResult];
   error: [Error];
 }
@@ -48,6 +59,12 @@ export class OpenCodeAdapter extends EventEmitter<OpenCodeAdapterEvents> {
   private lastWorkingDirectory: string | undefined;
   private currentModelId: string | null = null;
   private apiKeys: ApiKeys = {};
+  private planOnly: boolean = false;
+  private planText: string[] = [];
+  private expectsOutput: boolean = false;
+  private outputText: string[] = [];
+  private stepCount: number = 0;
+  private todos: { done: number; total: number; current?: string } | null = null;
 
   constructor(taskId?: string) {
     super();
@@ -77,6 +94,12 @@ export class OpenCodeAdapter extends EventEmitter<OpenCodeAdapterEvents> {
     this.lastWorkingDirectory = config.workingDirectory;
     this.apiKeys = config.apiKeys || {};
     this.currentModelId = config.modelId || null;
+    this.planOnly = Boolean(config.planOnly);
+    this.planText = [];
+    this.expectsOutput = Boolean(config.outputSchema);
+    this.outputText = [];
+    this.stepCount = 0;
+    this.todos = null;
     const modelId = this.currentModelId;
     const modelProvider = modelId ? modelId.split('/')[0] : null;
     const apiKeyFlags = {
@@ -96,6 +119,7 @@ export class OpenCodeAdapter extends EventEmitter<OpenCodeAdapterEvents> {
     const configPath = generateOpenCodeConfig({
       apiKeys: this.apiKeys,
       modelId: config.modelId,
+      modelParameters: config.modelParameters,
       workingDirectory: config.workingDirectory,
     });
 
@@ -104,13 +128,19 @@ export class OpenCodeAdapter extends EventEmitter<OpenCodeAdapterEvents> {
     const allArgs = [...baseArgs, ...cliArgs];
 
     // Build environment with API keys
-    const env = buildOpenCodeEnvironment(this.apiKeys);
+    const env = buildOpenCodeEnvironment(this.apiKeys, config.env);
     env.OPENCODE_CONFIG = configPath;
     env.OPENCODE_CONFIG_DIR = getOpenCodeConfigDir();
+    applyWebSearchSettings(env, config.webSearch, this.apiKeys);
+    // Pinned files are only read from a working directory on this machine
+    const localDirectory = config.remote ? undefined : config.workingDirectory;
+    applyAgentSettings(env, config.agentSettings, config.taskId, localDirectory);
+    applySandbox(env, config.sandbox, config.taskId, config.env);
+    applyRemote(env, config.remote, config.taskId);
     const authSync = syncApiKeysToOpenCodeAuth(this.apiKeys);
 
-    // Use temp directory as default cwd
-    const safeCwd = config.workingDirectory || os.tmpdir();
+    // Use temp directory as default cwd; a remote working directory is not on this machine
+    const safeCwd = (!config.remote && config.workingDirectory) || os.tmpdir();
 
     // Emit loading progress
     this.emit('progress', { stage: 'loading', message: 'Loading agent...' });
@@ -284,6 +314,18 @@ export class OpenCodeAdapter extends EventEmitter<OpenCodeAdapterEvents> {
     }
   }
 
+  /**
+   * Kill the OpenCode process and every process it started
+   */
+  abortTask(): void {
+    const pid = this.ptyProcess?.pid ?? this.childProcess?.pid;
+    if (pid) {
+      killProcessTree(pid);
+    }
+    this.ptyProcess = null;
+    this.childProcess = null;
+  }
+
   /**
    * Interrupt the current task (graceful Ctrl+C)
    */
@@ -365,7 +407,10 @@ export class OpenCodeAdapter extends EventEmitter<OpenCodeAdapterEvents> {
   }
 
   private buildCliArgs(config: TaskConfig): string[] {
-    const args = ['run', config.prompt, '--format', 'json'];
+    const prompt = config.outputSchema
+      ? config.prompt + structuredOutputInstructions(config.outputSchema)
+      : config.prompt;
+    const args = ['run', prompt, '--format', 'json'];
 
     if (config.modelId) {
       args.push('--model', config.modelId);
@@ -375,13 +420,41 @@ export class OpenCodeAdapter extends EventEmitter<OpenCodeAdapterEvents> {
       args.push('--session', config.sessionId);
     }
 
-    args.push('--agent', ACCOMPLISH_AGENT_NAME);
+    for (const attachment of config.attachments ?? []) {
+      args.push('--file', attachment.path);
+    }
+
+    args.push('--agent', config.planOnly ? PLAN_AGENT_NAME : config.agent || ACCOMPLISH_AGENT_NAME);
     // Enable CLI logs for debugging (stderr)
     args.push('--print-logs', '--log-level', 'DEBUG');
 
     return args;
   }
 
+  /**
+   * Count the agent's todo list, which OpenCode's todowrite tool rewrites as a whole
+   */
+  private trackTodos(toolName: string, input: unknown): void {
+    if (toolName !== 'todowrite') return;
+    const todos = (input as { todos?: { content?: string; status?: string }[] } | undefined)?.todos;
+    if (!Array.isArray(todos) || todos.length === 0) return;
+    this.todos = {
+      done: todos.filter((t) => t.status === 'completed').length,
+      total: todos.length,
+      current: todos.find((t) => t.status === 'in_progress')?.content,
+    };
+  }
+
+  /**
+   * Step fields of a progress update, from the todo list when the agent keeps one
+   */
+  private stepProgress(label: string): Pick<TaskProgress, 'step' | 'totalSteps' | 'label'> {
+    if (this.todos) {
+      return { step: this.todos.done, totalSteps: this.todos.total, label: this.todos.current ?? label };
+    }
+    return { step: this.stepCount, label };
+  }
+
   private setupStreamParsing(): void {
     this.streamParser.on('message', (message: OpenCodeMessage) => {
       this.handleMessage(message);
@@ -394,19 +467,31 @@ export class OpenCodeAdapter extends EventEmitter<OpenCodeAdapterEvents> {
 
   private handleMessage(message: OpenCodeMessage): void {
     switch (message.type) {
-      case 'step_start':
+      case 'step_start': {
         this.currentSessionId = message.part.sessionID;
+        // Only the last step's text is the final reply
+        this.outputText = [];
+        this.stepCount += 1;
+        const connecting = `Connecting to ${this.currentModelId || 'AI'}...`;
         this.emit('progress', {
           stage: 'connecting',
-          message: `Connecting to ${this.currentModelId || 'AI'}...`,
+          message: connecting,
           modelName: this.currentModelId || undefined,
+          ...this.stepProgress(connecting),
         });
         break;
+      }
 
       case 'text':
         if (!this.currentSessionId && message.part.sessionID) {
           this.currentSessionId = message.part.sessionID;
         }
+        if (this.planOnly && message.part.text) {
+          this.planText.push(message.part.text);
+        }
+        if (this.expectsOutput && message.part.text) {
+          this.outputText.push(message.part.text);
+        }
         this.emit('message', message);
         break;
 
@@ -415,9 +500,11 @@ export class OpenCodeAdapter extends EventEmitter<OpenCodeAdapterEvents> {
         const toolInput = message.part.input;
 
         this.emit('tool-use', toolName, toolInput);
+        this.trackTodos(toolName, toolInput);
         this.emit('progress', {
           stage: 'tool-use',
           message: `Using ${toolName}`,
+          ...this.stepProgress(`Using ${toolName}`),
         });
 
         // Check if this is AskUserQuestion
@@ -436,9 +523,11 @@ export class OpenCodeAdapter extends EventEmitter<OpenCodeAdapterEvents> {
 
         this.emit('message', message);
         this.emit('tool-use', toolUseName, toolUseInput);
+        this.trackTodos(toolUseName, toolUseInput);
         this.emit('progress', {
           stage: 'tool-use',
           message: `Using ${toolUseName}`,
+          ...this.stepProgress(`Using ${toolUseName}`),
         });
 
         if (toolUseStatus === 'completed' || toolUseStatus === 'error') {
@@ -456,6 +545,17 @@ export class OpenCodeAdapter extends EventEmitter<OpenCodeAdapterEvents> {
         break;
 
       case 'step_finish':
+        if (message.part.tokens || message.part.cost) {
+          this.emit('usage', {
+            modelId: this.currentModelId || undefined,
+            inputTokens: message.part.tokens?.input ?? 0,
+            outputTokens: message.part.tokens?.output ?? 0,
+            reasoningTokens: message.part.tokens?.reasoning ?? 0,
+            cacheReadTokens: message.part.tokens?.cache?.read ?? 0,
+            cacheWriteTokens: message.part.tokens?.cache?.write ?? 0,
+            cost: message.part.cost ?? 0,
+          });
+        }
         if (message.part.reason === 'error') {
           if (!this.hasCompleted) {
             this.hasCompleted = true;
@@ -473,6 +573,8 @@ export class OpenCodeAdapter extends EventEmitter<OpenCodeAdapterEvents> {
           this.emit('complete', {
             status: 'success',
             sessionId: this.currentSessionId || undefined,
+            plan: this.getPlan(),
+            output: this.getOutput(),
           });
         }
         break;
@@ -488,6 +590,20 @@ export class OpenCodeAdapter extends EventEmitter<OpenCodeAdapterEvents> {
     }
   }
 
+  /**
+   * Plan text collected during a plan-only run
+   */
+  private getPlan(): string | undefined {
+    return this.planOnly ? this.planText.join('\n\n').trim() || undefined : undefined;
+  }
+
+  /**
+   * Final reply of a run with an output schema
+   */
+  private getOutput(): string | undefined {
+    return this.expectsOutput ? this.outputText.join('').trim() || undefined : undefined;
+  }
+
   private handleAskUserQuestion(input: unknown): void {
     const typedInput = input as {
       questions?: Array<{
@@ -530,6 +646,8 @@ export class OpenCodeAdapter extends EventEmitter<OpenCodeAdapterEvents> {
         this.emit('complete', {
           status: 'success',
           sessionId: this.currentSessionId || undefined,
+          plan: this.getPlan(),
+          output: this.getOutput(),
         });
       } else if (code !== null) {
         this.emit('error', new Error(`OpenCode CLI exited with code ${code}`));
@@ -657,6 +775,217 @@ function syncApiKeysToOpenCodeAuth(apiKeys: ApiKeys): { updatedProviders: string
   return { updatedProviders };
 }
 
+/**
+ * Instructions appended to the prompt of runs with an output schema
+ */
+function structuredOutputInstructions(schema: object): string {
+  return `
+
+<output-format>
+When you are done, reply with a single JSON value that matches this JSON schema, and nothing else:
+${JSON.stringify(schema, null, 2)}
+</output-format>`;
+}
+
+/**
+ * Run a one-off prompt with the read-only plan agent and return its reply
+ *
+ * Used for short completions like history summaries, so it runs in a temp
+ * directory without a PTY and with no task session.
+ */
+export function generateText(config: GenerateTextConfig): Promise<string> {
+  const apiKeys = config.apiKeys || {};
+  const configPath = generateOpenCodeConfig({ apiKeys, modelId: config.modelId });
+  const env = buildOpenCodeEnvironment(apiKeys);
+  env.OPENCODE_CONFIG = configPath;
+  env.OPENCODE_CONFIG_DIR = getOpenCodeConfigDir();
+  syncApiKeysToOpenCodeAuth(apiKeys);
+
+  const args = ['run', config.prompt, '--format', 'json', '--agent', PLAN_AGENT_NAME];
+  if (config.modelId) {
+    args.push('--model', config.modelId);
+  }
+  const { command, args: baseArgs } = getOpenCodeCliPath();
+
+  return new Promise((resolve, reject) => {
+    const parser = new StreamParser();
+    const text: string[] = [];
+    let stderr = '';
+    parser.on('message', (message: OpenCodeMessage) => {
+      if (message.type === 'text') {
+        text.push(message.part.text);
+      }
+    });
+    parser.on('error', () => {
+      // Log lines mixed into stdout are not JSON
+    });
+
+    const child = spawn(command, [...baseArgs, ...args], {
+      cwd: os.tmpdir(),
+      env: env as { [key: string]: string },
+      stdio: 'pipe',
+      shell: process.platform === 'win32',
+    });
+    child.stdin.end();
+    child.stdout.on('data', (data: Buffer) => parser.feed(data.toString()));
+    child.stderr.on('data', (data: Buffer) => {
+      stderr = (stderr + data.toString()).slice(-2000);
+    });
+    child.on('error', reject);
+    child.on('exit', (code) => {
+      parser.flush();
+      const reply = text.join('').trim();
+      if (code === 0 && reply) {
+        resolve(reply);
+      } else {
+        reject(new Error(stderr.trim() || `opencode exited with code ${code}`));
+      }
+    });
+  });
+}
+
+/** OpenCode timestamps are milliseconds since the epoch */
+function isoTime(millis: unknown): string {
+  return typeof millis === 'number' ? new Date(millis).toISOString() : new Date().toISOString();
+}
+
+type ExportedPart = {
+  id: string;
+  type: string;
+  text?: string;
+  tool?: string;
+  synthetic?: boolean;
+  state?: { input?: unknown; output?: unknown };
+};
+
+type ExportedTokens = {
+  input?: number;
+  output?: number;
+  reasoning?: number;
+  cache?: { read?: number; write?: number };
+};
+
+type ExportedMessage = {
+  info: { role: string; time?: { created?: number }; tokens?: ExportedTokens };
+  parts?: ExportedPart[];
+};
+
+/** Run `opencode export` for a session and parse its messages */
+function readExport(sessionId: string): Promise<ExportedMessage[]> {
+  const { command, args: baseArgs } = getOpenCodeCliPath();
+
+  return new Promise((resolve, reject) => {
+    let stdout = '';
+    let stderr = '';
+    const child = spawn(command, [...baseArgs, 'export', sessionId], {
+      cwd: os.tmpdir(),
+      env: process.env as { [key: string]: string },
+      stdio: 'pipe',
+      shell: process.platform === 'win32',
+    });
+    child.stdin.end();
+    child.stdout.on('data', (data: Buffer) => {
+      stdout += data.toString();
+    });
+    child.stderr.on('data', (data: Buffer) => {
+      stderr = (stderr + data.toString()).slice(-2000);
+    });
+    child.on('error', reject);
+    child.on('exit', (code) => {
+      if (code !== 0) {
+        reject(new Error(stderr.trim() || `opencode exited with code ${code}`));
+        return;
+      }
+      try {
+        // Progress lines can come before the JSON
+        const exported = JSON.parse(stdout.slice(stdout.indexOf('{'))) as {
+          messages?: ExportedMessage[];
+        };
+        resolve(exported.messages ?? []);
+      } catch {
+        reject(new Error(`Unreadable export of session ${sessionId}`));
+      }
+    });
+  });
+}
+
+/**
+ * Read a session's messages as OpenCode stored them, with `opencode export`
+ *
+ * Text and tool parts become one message each, in order; synthetic parts
+ * (context OpenCode injected) are left out.
+ */
+export async function exportSession(sessionId: string): Promise<TranscriptMessage[]> {
+  const messages: TranscriptMessage[] = [];
+  for (const { info, parts } of await readExport(sessionId)) {
+    const timestamp = isoTime(info.time?.created);
+    for (const part of parts ?? []) {
+      if (part.synthetic) continue;
+      if (part.type === 'text' && part.text?.trim()) {
+        if (info.role === 'user' || info.role === 'assistant') {
+          messages.push({ id: part.id, type: info.role, content: part.text, timestamp });
+        }
+      } else if (part.type === 'tool') {
+        messages.push({
+          id: part.id,
+          type: 'tool',
+          content: '',
+          timestamp,
+          toolName: part.tool,
+          toolInput: part.state?.input,
+        });
+      }
+    }
+  }
+  return messages;
+}
+
+/** Rough token count of text, for when the provider reported none */
+function estimateTokens(text: string): number {
+  return Math.ceil(text.length / 4);
+}
+
+/**
+ * Measure what a resumed session starts with
+ *
+ * The estimate starts from the context of the last assistant reply as the
+ * provider counted it (input, cache, and output tokens); messages after it,
+ * or all of them if no count was reported, are estimated from the length of
+ * their parts, synthetic ones included.
+ */
+export async function measureSession(sessionId: string): Promise<SessionContext> {
+  const exported = await readExport(sessionId);
+  let tokenEstimate = 0;
+  let reported = false;
+  for (const { info, parts } of exported) {
+    const tokens = info.tokens;
+    if (info.role === 'assistant' && tokens && (tokens.input || tokens.output)) {
+      tokenEstimate =
+        (tokens.input ?? 0) +
+        (tokens.output ?? 0) +
+        (tokens.reasoning ?? 0) +
+        (tokens.cache?.read ?? 0) +
+        (tokens.cache?.write ?? 0);
+      reported = true;
+    } else {
+      for (const part of parts ?? []) {
+        const output = part.state?.output;
+        tokenEstimate += estimateTokens(part.text ?? '');
+        tokenEstimate += estimateTokens(typeof output === 'string' ? output : '');
+        if (part.state?.input !== undefined) {
+          tokenEstimate += estimateTokens(JSON.stringify(part.state.input));
+        }
+      }
+    }
+  }
+  return {
+    sessionId,
+    messageCount: exported.length,
+    tokenEstimate,
+    reported,
+  };
+}
+
 /**
  * Factory function to create a new adapter instance
  */
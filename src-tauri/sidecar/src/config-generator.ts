@@ -1,13 +1,36 @@
 import path from 'path';
 import fs from 'fs';
 import os from 'os';
-import type { ApiKeys } from './types';
+import type {
+  AgentSettings,
+  ApiKeys,
+  ModelParameters,
+  RemoteConfig,
+  SandboxConfig,
+  WebSearchConfig,
+} from './types';
 
 /**
  * Agent name used by Accomplish
  */
 export const ACCOMPLISH_AGENT_NAME = 'accomplish';
 
+/**
+ * Read-only agent used for plan-first tasks
+ */
+export const PLAN_AGENT_NAME = 'accomplish-plan';
+
+/**
+ * Instructions appended to the system prompt for plan-only runs
+ */
+const PLAN_MODE_INSTRUCTIONS = `
+<plan-mode>
+You are in planning mode. Investigate the request and the working directory, then reply with a
+step-by-step plan: which files you will change, what each change is, and how you will verify it.
+Do NOT modify files or run commands with side effects - the user reviews the plan before you act.
+</plan-mode>
+`;
+
 /**
  * Build platform-specific environment setup instructions
  */
@@ -207,6 +230,11 @@ interface AgentConfig {
   description?: string;
   prompt?: string;
   mode?: 'primary' | 'subagent' | 'all';
+  permission?: Record<string, string>;
+  temperature?: number;
+  top_p?: number;
+  /** Passed to the model provider with each request */
+  options?: Record<string, unknown>;
 }
 
 interface McpServerConfig {
@@ -249,12 +277,37 @@ interface OpenCodeConfig {
 export interface ConfigGeneratorOptions {
   apiKeys?: ApiKeys;
   modelId?: string;
+  modelParameters?: ModelParameters;
   skillsPath?: string;
   workingDirectory?: string;
   permissionApiPort?: number;
   questionApiPort?: number;
 }
 
+/**
+ * Agent settings for the model's generation parameters
+ *
+ * Temperature and top P are agent settings in OpenCode; the rest are provider
+ * options it forwards with each request.
+ */
+function modelParameterSettings(parameters?: ModelParameters): Partial<AgentConfig> {
+  if (!parameters) {
+    return {};
+  }
+  const options: Record<string, unknown> = {};
+  if (parameters.maxTokens !== undefined) {
+    options.maxOutputTokens = parameters.maxTokens;
+  }
+  if (parameters.reasoningEffort !== undefined) {
+    options.reasoningEffort = parameters.reasoningEffort;
+  }
+  return {
+    temperature: parameters.temperature,
+    top_p: parameters.topP,
+    options: Object.keys(options).length > 0 ? options : undefined,
+  };
+}
+
 /**
  * Get the default skills path
  */
@@ -406,6 +459,8 @@ export function generateOpenCodeConfig(options: ConfigGeneratorOptions = {}): st
     };
   }
 
+  const parameterSettings = modelParameterSettings(options.modelParameters);
+
   const config: OpenCodeConfig = {
     $schema: 'https://opencode.ai/config.json',
     default_agent: ACCOMPLISH_AGENT_NAME,
@@ -417,6 +472,14 @@ export function generateOpenCodeConfig(options: ConfigGeneratorOptions = {}): st
         description: 'Browser automation assistant using dev-browser',
         prompt: systemPrompt,
         mode: 'primary',
+        ...parameterSettings,
+      },
+      [PLAN_AGENT_NAME]: {
+        description: 'Proposes a plan without making changes',
+        prompt: systemPrompt + PLAN_MODE_INSTRUCTIONS,
+        mode: 'primary',
+        permission: { edit: 'deny', bash: 'deny' },
+        ...parameterSettings,
       },
     },
     mcp: Object.keys(mcpConfig).length > 0 ? mcpConfig : undefined,
@@ -433,10 +496,14 @@ export function generateOpenCodeConfig(options: ConfigGeneratorOptions = {}): st
  * Build environment variables for OpenCode CLI
  *
  * @param apiKeys - API keys from secure storage
+ * @param taskEnv - Workspace and task variables; API keys and OpenCode settings take precedence
  * @returns Environment variables object
  */
-export function buildOpenCodeEnvironment(apiKeys: ApiKeys = {}): NodeJS.ProcessEnv {
-  const env: NodeJS.ProcessEnv = { ...process.env };
+export function buildOpenCodeEnvironment(
+  apiKeys: ApiKeys = {},
+  taskEnv: Record<string, string> = {}
+): NodeJS.ProcessEnv {
+  const env: NodeJS.ProcessEnv = { ...process.env, ...taskEnv };
 
   // Set API keys as environment variables
   if (apiKeys.anthropic) {
@@ -466,3 +533,216 @@ export function buildOpenCodeEnvironment(apiKeys: ApiKeys = {}): NodeJS.ProcessE
 
   return env;
 }
+
+/**
+ * Apply a task's web search setting to the OpenCode environment
+ *
+ * The config file is shared between tasks, so per-task overrides go through
+ * `OPENCODE_CONFIG_CONTENT`, which OpenCode merges over the config file.
+ * Disabled tasks lose the `webfetch` and `websearch` tools; enabled tasks keep
+ * OpenCode's page fetching and, when chosen, get Exa (OpenCode's built-in
+ * `websearch`) or the Brave Search MCP server.
+ */
+export function applyWebSearchSettings(
+  env: NodeJS.ProcessEnv,
+  webSearch: WebSearchConfig | undefined,
+  apiKeys: ApiKeys = {}
+): void {
+  if (!webSearch) {
+    return;
+  }
+
+  if (!webSearch.enabled) {
+    env.OPENCODE_CONFIG_CONTENT = JSON.stringify({
+      tools: { webfetch: false, websearch: false },
+      permission: { webfetch: 'deny' },
+    });
+    return;
+  }
+
+  if (webSearch.backend === 'fetch') {
+    return;
+  }
+
+  if (webSearch.backend === 'brave') {
+    if (!apiKeys.brave) {
+      console.warn('[Config] Brave search selected but no Brave API key is configured');
+      return;
+    }
+    const mcp: Record<string, McpServerConfig> = {
+      'brave-search': {
+        type: 'local',
+        command: ['npx', '-y', '@modelcontextprotocol/server-brave-search'],
+        enabled: true,
+        environment: { BRAVE_API_KEY: apiKeys.brave },
+        timeout: 30000,
+      },
+    };
+    env.OPENCODE_CONFIG_CONTENT = JSON.stringify({ tools: { websearch: false }, mcp });
+    return;
+  }
+
+  env.OPENCODE_ENABLE_EXA = '1';
+}
+
+/**
+ * Apply a workspace's agent settings to the OpenCode environment
+ *
+ * Merged into `OPENCODE_CONFIG_CONTENT` under the task's own settings, so a
+ * task with web access turned off stays without it. The instructions are
+ * written to a file and, with the pinned files that exist, passed as OpenCode
+ * `instructions`. Pinned files are skipped when the working directory isn't on
+ * this machine.
+ */
+export function applyAgentSettings(
+  env: NodeJS.ProcessEnv,
+  settings: AgentSettings | undefined,
+  taskId: string,
+  workingDirectory: string | undefined
+): void {
+  if (!settings) {
+    return;
+  }
+
+  const instructions: string[] = [];
+  if (settings.instructions) {
+    const dir = path.join(getOpenCodeConfigDir(), 'instructions');
+    fs.mkdirSync(dir, { recursive: true });
+    const instructionsPath = path.join(dir, `${taskId}.md`);
+    fs.writeFileSync(instructionsPath, settings.instructions);
+    instructions.push(instructionsPath);
+  }
+  if (workingDirectory) {
+    for (const file of settings.pinnedFiles) {
+      const filePath = path.resolve(workingDirectory, file);
+      if (fs.existsSync(filePath)) {
+        instructions.push(filePath);
+      } else {
+        console.warn(`[Config] Pinned file not found: ${filePath}`);
+      }
+    }
+  }
+
+  const permission: Record<string, string | Record<string, string>> = {};
+  for (const rule of settings.permissionRules) {
+    const current = permission[rule.tool];
+    if (rule.pattern) {
+      const patterns = typeof current === 'object' ? current : { '*': current ?? 'allow' };
+      permission[rule.tool] = { ...patterns, [rule.pattern]: rule.action };
+    } else if (typeof current === 'object') {
+      permission[rule.tool] = { ...current, '*': rule.action };
+    } else {
+      permission[rule.tool] = rule.action;
+    }
+  }
+
+  const mcp: Record<string, McpServerConfig> = {};
+  for (const [name, server] of Object.entries(settings.mcpServers)) {
+    mcp[name] =
+      server.type === 'remote'
+        ? { type: 'remote', url: server.url, enabled: server.enabled }
+        : {
+            type: 'local',
+            command: server.command,
+            enabled: server.enabled,
+            environment: server.environment,
+          };
+  }
+
+  const content = env.OPENCODE_CONFIG_CONTENT ? JSON.parse(env.OPENCODE_CONFIG_CONTENT) : {};
+  if (instructions.length > 0) {
+    content.instructions = [...instructions, ...(content.instructions ?? [])];
+  }
+  content.permission = { ...permission, ...content.permission };
+  content.mcp = { ...mcp, ...content.mcp };
+  env.OPENCODE_CONFIG_CONTENT = JSON.stringify(content);
+}
+
+/**
+ * Turn off OpenCode's file tools, which work on the local disk directly, so
+ * the agent works on files through shell commands instead
+ */
+function disableFileTools(env: NodeJS.ProcessEnv): void {
+  const content = env.OPENCODE_CONFIG_CONTENT ? JSON.parse(env.OPENCODE_CONFIG_CONTENT) : {};
+  content.tools = {
+    ...content.tools,
+    read: false,
+    write: false,
+    edit: false,
+    multiedit: false,
+    patch: false,
+    glob: false,
+    grep: false,
+    list: false,
+  };
+  env.OPENCODE_CONFIG_CONTENT = JSON.stringify(content);
+}
+
+/**
+ * Run a task's shell commands in its sandbox container
+ *
+ * OpenCode runs commands through `$SHELL`, so it is pointed at a wrapper that
+ * forwards them to `docker exec` in the same directory (the backend mounts the
+ * working directory at the same path). Task environment variables are passed
+ * through by name. The file tools would reach the host's disk outside the
+ * container, so they are turned off as for a remote workspace.
+ */
+export function applySandbox(
+  env: NodeJS.ProcessEnv,
+  sandbox: SandboxConfig | undefined,
+  taskId: string,
+  taskEnv: Record<string, string> = {}
+): void {
+  if (!sandbox) {
+    return;
+  }
+
+  const dir = path.join(getOpenCodeConfigDir(), 'sandbox');
+  fs.mkdirSync(dir, { recursive: true });
+  const envFlags = Object.keys(taskEnv)
+    .map((name) => ` -e ${name}`)
+    .join('');
+  const wrapperPath = path.join(dir, `${taskId}.sh`);
+  fs.writeFileSync(
+    wrapperPath,
+    `#!/bin/sh\nexec docker exec -i${envFlags} -w "$PWD" '${sandbox.container}' /bin/sh "$@"\n`,
+    { mode: 0o755 }
+  );
+  env.SHELL = wrapperPath;
+  disableFileTools(env);
+}
+
+/** Quote a string for a POSIX shell */
+function shellQuote(value: string): string {
+  return `'${value.replace(/'/g, `'\\''`)}'`;
+}
+
+/**
+ * Run a task's shell commands on the SSH host of a remote workspace
+ *
+ * `$SHELL` points at a wrapper that runs each command over `ssh` in the remote
+ * directory. OpenCode's file tools only see the local disk, so they are turned
+ * off and the agent works on files through commands instead.
+ */
+export function applyRemote(
+  env: NodeJS.ProcessEnv,
+  remote: RemoteConfig | undefined,
+  taskId: string
+): void {
+  if (!remote) {
+    return;
+  }
+
+  const dir = path.join(getOpenCodeConfigDir(), 'remote');
+  fs.mkdirSync(dir, { recursive: true });
+  const sshArgs = remote.sshArgs.map(shellQuote).join(' ');
+  const prefix = shellQuote(`cd ${shellQuote(remote.directory)} && `);
+  const wrapperPath = path.join(dir, `${taskId}.sh`);
+  fs.writeFileSync(
+    wrapperPath,
+    `#!/bin/sh\n[ "$1" = "-c" ] && shift\nexec ssh ${sshArgs} ${prefix}"$1"\n`,
+    { mode: 0o755 }
+  );
+  env.SHELL = wrapperPath;
+  disableFileTools(env);
+}
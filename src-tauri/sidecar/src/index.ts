@@ -10,29 +10,52 @@
  *
  * Message Types:
  * Input:
- *   - start_task: { taskId, prompt, sessionId?, apiKeys?, workingDirectory?, modelId? }
+ *   - start_task: { taskId, prompt, sessionId?, apiKeys?, workingDirectory?, modelId?, modelParameters?, planOnly?, agent?, attachments?, webSearch?, outputSchema?, env?, sandbox?, remote?, agentSettings? }
  *   - cancel_task: { taskId }
  *   - interrupt_task: { taskId }
+ *   - abort_all: {} (kills every task's process tree; no task events follow)
  *   - send_response: { taskId, response }
+ *   - generate_text: { taskId, purpose, prompt, modelId?, apiKeys? }
+ *   - update_api_keys: { [provider]: key | null } (keys used when a command has no apiKeys)
+ *   - get_transcript: { taskId, sessionId }
+ *   - get_session_context: { requestId, sessionId }
  *
  * Output:
  *   - task_started: { taskId }
  *   - task_message: { taskId, message }
  *   - task_progress: { taskId, progress }
  *   - permission_request: { taskId, request }
- *   - task_complete: { taskId, result }
+ *   - task_usage: { taskId, usage }
+ *   - task_complete: { taskId, result } (result.plan is set for plan-only runs, result.output for runs with an outputSchema)
  *   - task_error: { taskId, error }
+ *   - text_generated: { taskId, purpose, text }
+ *   - text_error: { taskId, purpose, error }
+ *   - session_transcript: { taskId, sessionId, messages }
+ *   - session_transcript_error: { taskId, sessionId, error }
+ *   - session_context: { requestId, sessionId, messageCount, tokenEstimate, reported }
+ *   - session_context_error: { requestId, sessionId, error }
  *   - log: { level, message }
  */
 
 import * as readline from 'readline';
 import { TaskManager } from './task-manager';
+import { exportSession, generateText, measureSession } from './adapter';
 import { isOpenCodeAvailable, getOpenCodeVersion } from './cli-path';
-import type { TaskConfig, ApiKeys, SidecarMessage, SidecarCommand } from './types';
+import type {
+  TaskConfig,
+  ApiKeys,
+  ApiKeyChanges,
+  SidecarMessage,
+  SidecarCommand,
+  GenerateTextConfig,
+} from './types';
 
 // Initialize task manager
 const taskManager = new TaskManager();
 
+// API keys sent with update_api_keys; Rust only sends the ones that changed
+const apiKeys: ApiKeys = {};
+
 // Send a message to the parent (Tauri)
 function send(type: string, payload: unknown, taskId?: string): void {
   const message: SidecarMessage = { type, payload };
@@ -59,7 +82,7 @@ async function handleMessage(msg: SidecarCommand): Promise<void> {
     switch (type) {
       case 'start_task': {
         const config = payload as TaskConfig & { apiKeys?: ApiKeys };
-        await startTask(config);
+        await startTask({ ...config, apiKeys: config.apiKeys ?? { ...apiKeys } });
         break;
       }
 
@@ -77,6 +100,12 @@ async function handleMessage(msg: SidecarCommand): Promise<void> {
         break;
       }
 
+      case 'abort_all': {
+        const aborted = taskManager.abortAll();
+        log('warn', `Aborted ${aborted.length} task(s): ${aborted.join(', ')}`);
+        break;
+      }
+
       case 'send_response': {
         if (taskId) {
           const { response } = payload as { response: string };
@@ -85,6 +114,34 @@ async function handleMessage(msg: SidecarCommand): Promise<void> {
         break;
       }
 
+      case 'generate_text': {
+        if (taskId) {
+          // Runs alongside tasks; don't hold up the next command
+          const config = payload as GenerateTextConfig;
+          void runGenerateText(taskId, { ...config, apiKeys: config.apiKeys ?? { ...apiKeys } });
+        }
+        break;
+      }
+
+      case 'get_transcript': {
+        if (taskId) {
+          const { sessionId } = payload as { sessionId: string };
+          void runGetTranscript(taskId, sessionId);
+        }
+        break;
+      }
+
+      case 'get_session_context': {
+        const { requestId, sessionId } = payload as { requestId: string; sessionId: string };
+        void runGetSessionContext(requestId, sessionId);
+        break;
+      }
+
+      case 'update_api_keys': {
+        updateApiKeys(payload as ApiKeyChanges);
+        break;
+      }
+
       case 'ping': {
         send('pong', { timestamp: Date.now() });
         break;
@@ -106,6 +163,18 @@ async function handleMessage(msg: SidecarCommand): Promise<void> {
   }
 }
 
+// Apply changed API keys; null removes a key
+function updateApiKeys(changes: ApiKeyChanges): void {
+  for (const [provider, key] of Object.entries(changes)) {
+    if (key === null || key === undefined) {
+      delete apiKeys[provider as keyof ApiKeys];
+    } else {
+      Object.assign(apiKeys, { [provider]: key });
+    }
+  }
+  log('info', `Updated API keys: ${Object.keys(changes).join(', ')}`);
+}
+
 // Start a new task using TaskManager
 async function startTask(config: TaskConfig & { apiKeys?: ApiKeys }): Promise<void> {
   const { taskId } = config;
@@ -141,6 +210,9 @@ async function startTask(config: TaskConfig & { apiKeys?: ApiKeys }): Promise<vo
       onPermissionRequest: (request) => {
         send('permission_request', { request }, taskId);
       },
+      onUsage: (usage) => {
+        send('task_usage', { usage }, taskId);
+      },
       onComplete: (result) => {
         send('task_complete', { result }, taskId);
       },
@@ -154,6 +226,40 @@ async function startTask(config: TaskConfig & { apiKeys?: ApiKeys }): Promise<vo
   }
 }
 
+// Run a one-off completion for a task and report the text
+async function runGenerateText(taskId: string, config: GenerateTextConfig): Promise<void> {
+  const { purpose } = config;
+  try {
+    const text = await generateText(config);
+    send('text_generated', { purpose, text }, taskId);
+  } catch (error) {
+    const errorMessage = error instanceof Error ? error.message : String(error);
+    send('text_error', { purpose, error: errorMessage }, taskId);
+  }
+}
+
+// Read a task's session as OpenCode stored it and report the messages
+async function runGetTranscript(taskId: string, sessionId: string): Promise<void> {
+  try {
+    const messages = await exportSession(sessionId);
+    send('session_transcript', { sessionId, messages }, taskId);
+  } catch (error) {
+    const errorMessage = error instanceof Error ? error.message : String(error);
+    send('session_transcript_error', { sessionId, error: errorMessage }, taskId);
+  }
+}
+
+// Measure the context a session would resume with
+async function runGetSessionContext(requestId: string, sessionId: string): Promise<void> {
+  try {
+    const context = await measureSession(sessionId);
+    send('session_context', { requestId, ...context });
+  } catch (error) {
+    const errorMessage = error instanceof Error ? error.message : String(error);
+    send('session_context_error', { requestId, sessionId, error: errorMessage });
+  }
+}
+
 // Cancel a running task
 async function cancelTask(taskId: string): Promise<void> {
   log('info', `Cancelling task ${taskId}`);
@@ -0,0 +1,38 @@
+/**
+ * Process tree helpers
+ *
+ * OpenCode starts shells for the agent's commands and local MCP servers as
+ * its children, so killing OpenCode alone leaves them running.
+ */
+
+import { spawnSync } from 'child_process';
+
+/** PIDs of a process's descendants, parents before their children */
+function descendants(pid: number): number[] {
+  const result = spawnSync('pgrep', ['-P', String(pid)], { encoding: 'utf8' });
+  const children = (result.stdout || '')
+    .split('\n')
+    .map((line) => Number(line.trim()))
+    .filter((child) => child > 0);
+  return children.flatMap((child) => [child, ...descendants(child)]);
+}
+
+/**
+ * Kill a process and everything it started
+ *
+ * The whole tree is listed before anything is killed, since orphaned
+ * children are reparented and can't be found from the root afterwards.
+ */
+export function killProcessTree(pid: number): void {
+  if (process.platform === 'win32') {
+    spawnSync('taskkill', ['/pid', String(pid), '/T', '/F']);
+    return;
+  }
+  for (const target of [pid, ...descendants(pid)]) {
+    try {
+      process.kill(target, 'SIGKILL');
+    } catch {
+      // Already exited
+    }
+  }
+}
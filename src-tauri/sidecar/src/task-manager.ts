@@ -12,6 +12,7 @@ import type {
   TaskProgress,
   OpenCodeMessage,
   PermissionRequest,
+  TaskUsage,
   TaskCallbacks,
   ApiKeys,
 } from './types';
@@ -73,6 +74,10 @@ export class TaskManager {
       callbacks.onPermissionRequest(request);
     };
 
+    const onUsage = (usage: TaskUsage) => {
+      callbacks.onUsage(usage);
+    };
+
     const onComplete = (result: TaskResult) => {
       callbacks.onComplete(result);
       this.cleanupTask(taskId);
@@ -87,6 +92,7 @@ export class TaskManager {
     adapter.on('message', onMessage);
     adapter.on('progress', onProgress);
     adapter.on('permission-request', onPermissionRequest);
+    adapter.on('usage', onUsage);
     adapter.on('complete', onComplete);
     adapter.on('error', onError);
 
@@ -95,6 +101,7 @@ export class TaskManager {
       adapter.off('message', onMessage);
       adapter.off('progress', onProgress);
       adapter.off('permission-request', onPermissionRequest);
+      adapter.off('usage', onUsage);
       adapter.off('complete', onComplete);
       adapter.off('error', onError);
       adapter.dispose();
@@ -140,6 +147,21 @@ export class TaskManager {
     }
   }
 
+  /**
+   * Kill the process trees of all tasks without reporting them, returning their IDs
+   */
+  abortAll(): string[] {
+    const taskIds = this.getActiveTaskIds();
+    for (const taskId of taskIds) {
+      try {
+        this.activeTasks.get(taskId)?.adapter.abortTask();
+      } finally {
+        this.cleanupTask(taskId);
+      }
+    }
+    return taskIds;
+  }
+
   /**
    * Interrupt a running task (graceful Ctrl+C)
    */
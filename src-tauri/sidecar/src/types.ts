@@ -147,6 +147,7 @@ export interface ApiKeys {
   litellm?: string;
   ollama?: string;
   azureFoundry?: string;
+  brave?: string;
   bedrock?: {
     accessKeyId: string;
     secretAccessKey: string;
@@ -162,6 +163,84 @@ export interface TaskConfig {
   apiKeys?: ApiKeys;
   workingDirectory?: string;
   modelId?: string;
+  /** Generation parameters set for the model in the app */
+  modelParameters?: ModelParameters;
+  /** Run the read-only plan agent and return its plan instead of executing */
+  planOnly?: boolean;
+  /** OpenCode agent to run instead of the default agent */
+  agent?: string;
+  /** Files attached to the prompt (images for vision models) */
+  attachments?: AttachmentFile[];
+  /** Whether the agent may search and fetch the web; unset keeps OpenCode's defaults */
+  webSearch?: WebSearchConfig;
+  /** JSON schema the final reply must match (validated by the backend) */
+  outputSchema?: object;
+  /** Environment variables of the workspace and task, with secrets resolved */
+  env?: Record<string, string>;
+  /** Docker container the agent's shell commands run in */
+  sandbox?: SandboxConfig;
+  /** SSH host the agent's shell commands run on; workingDirectory is a path on it */
+  remote?: RemoteConfig;
+  /** Instructions, permission rules, pinned files, and MCP servers of the workspace */
+  agentSettings?: AgentSettings;
+}
+
+/** Per-model generation parameters; unset ones keep the model's defaults */
+export interface ModelParameters {
+  temperature?: number;
+  topP?: number;
+  maxTokens?: number;
+  reasoningEffort?: 'minimal' | 'low' | 'medium' | 'high';
+}
+
+/** Sandbox container started by the backend for a task */
+export interface SandboxConfig {
+  container: string;
+}
+
+/** SSH target of a remote workspace */
+export interface RemoteConfig {
+  /** Options and destination for `ssh` */
+  sshArgs: string[];
+  directory: string;
+}
+
+/** Allows or denies an OpenCode tool, or a `bash` command pattern */
+export interface PermissionRule {
+  tool: string;
+  pattern?: string;
+  action: 'allow' | 'deny';
+}
+
+/** MCP server of a workspace, in OpenCode's config format */
+export interface WorkspaceMcpServer {
+  type: 'local' | 'remote';
+  command: string[];
+  url?: string;
+  environment: Record<string, string>;
+  enabled: boolean;
+}
+
+/** What the agent is given in a workspace besides the prompt */
+export interface AgentSettings {
+  instructions?: string;
+  /** In order; later rules override earlier ones */
+  permissionRules: PermissionRule[];
+  /** Relative to the working directory */
+  pinnedFiles: string[];
+  mcpServers: Record<string, WorkspaceMcpServer>;
+}
+
+/** Web search setting for a task */
+export interface WebSearchConfig {
+  enabled: boolean;
+  backend: 'fetch' | 'exa' | 'brave';
+}
+
+/** File attached to a task prompt */
+export interface AttachmentFile {
+  path: string;
+  mimeType: string;
 }
 
 /** Task progress stages */
@@ -179,6 +258,12 @@ export interface TaskProgress {
   stage: TaskProgressStage;
   message?: string;
   modelName?: string;
+  /** Steps done: todos completed when the agent keeps a todo list, else agent steps started */
+  step?: number;
+  /** Steps in the agent's todo list; unset when there is none */
+  totalSteps?: number;
+  /** What the current step is doing */
+  label?: string;
 }
 
 /** Task completion result */
@@ -187,6 +272,21 @@ export interface TaskResult {
   sessionId?: string;
   summary?: string;
   error?: string;
+  /** Proposed plan, set on successful plan-only runs */
+  plan?: string;
+  /** Final reply, set on runs with an output schema */
+  output?: string;
+}
+
+/** Token usage and cost reported at the end of a step */
+export interface TaskUsage {
+  modelId?: string;
+  inputTokens: number;
+  outputTokens: number;
+  reasoningTokens: number;
+  cacheReadTokens: number;
+  cacheWriteTokens: number;
+  cost: number;
 }
 
 /** Permission request from OpenCode CLI */
@@ -200,6 +300,39 @@ export interface PermissionRequest {
   options?: string[];
 }
 
+/** Keys changed since the last update; null removes a key */
+export type ApiKeyChanges = { [K in keyof ApiKeys]?: ApiKeys[K] | null };
+
+/** One-off completion outside a task session, e.g. a history summary */
+export interface GenerateTextConfig {
+  /** What the text is for; echoed back with the result */
+  purpose: string;
+  prompt: string;
+  modelId?: string;
+  apiKeys?: ApiKeys;
+}
+
+/** A text or tool part of a session, as stored by OpenCode */
+export interface TranscriptMessage {
+  /** OpenCode part ID */
+  id: string;
+  type: 'user' | 'assistant' | 'tool';
+  content: string;
+  timestamp: string;
+  toolName?: string;
+  toolInput?: unknown;
+}
+
+/** Size of a session, as a task resuming it would start */
+export interface SessionContext {
+  sessionId: string;
+  /** User and assistant messages OpenCode stored */
+  messageCount: number;
+  tokenEstimate: number;
+  /** Whether the estimate builds on a token count the provider reported */
+  reported: boolean;
+}
+
 // ========== Sidecar IPC Protocol ==========
 
 /** Messages received from Rust via stdin */
@@ -208,6 +341,10 @@ export type SidecarInputMessage =
   | { type: 'cancel_task'; taskId: string }
   | { type: 'interrupt_task'; taskId: string }
   | { type: 'send_response'; taskId: string; payload: { response: string } }
+  | { type: 'generate_text'; taskId: string; payload: GenerateTextConfig }
+  | { type: 'update_api_keys'; payload: ApiKeyChanges }
+  | { type: 'get_transcript'; taskId: string; payload: { sessionId: string } }
+  | { type: 'get_session_context'; payload: { requestId: string; sessionId: string } }
   | { type: 'ping' };
 
 /** Messages sent to Rust via stdout */
@@ -220,6 +357,23 @@ export type SidecarOutputMessage =
   | { type: 'permission_request'; taskId: string; payload: PermissionRequest }
   | { type: 'task_complete'; taskId: string; payload: TaskResult }
   | { type: 'task_error'; taskId: string; payload: { error: string } }
+  | { type: 'text_generated'; taskId: string; payload: { purpose: string; text: string } }
+  | { type: 'text_error'; taskId: string; payload: { purpose: string; error: string } }
+  | {
+      type: 'session_transcript';
+      taskId: string;
+      payload: { sessionId: string; messages: TranscriptMessage[] };
+    }
+  | {
+      type: 'session_transcript_error';
+      taskId: string;
+      payload: { sessionId: string; error: string };
+    }
+  | { type: 'session_context'; payload: SessionContext & { requestId: string } }
+  | {
+      type: 'session_context_error';
+      payload: { requestId: string; sessionId: string; error: string };
+    }
   | { type: 'log'; payload: { level: 'info' | 'warn' | 'error'; message: string } };
 
 /** Task callbacks for event handling */
@@ -227,6 +381,7 @@ export interface TaskCallbacks {
   onMessage: (message: OpenCodeMessage) => void;
   onProgress: (progress: TaskProgress) => void;
   onPermissionRequest: (request: PermissionRequest) => void;
+  onUsage: (usage: TaskUsage) => void;
   onComplete: (result: TaskResult) => void;
   onError: (error: string) => void;
 }
@@ -0,0 +1,102 @@
+// src-tauri/src/abort.rs
+//! Abort-all panic button
+//!
+//! `abort_all_tasks` stops every task at once, for when the agent starts doing
+//! something alarming across several of them. The sidecar kills the process
+//! tree of each task it runs (OpenCode and the shells and MCP servers it
+//! started) without reporting them one by one, and every task still starting,
+//! queued, running, waiting for permission, or waiting for its plan to be
+//! approved is marked cancelled with a system message; a pending plan is
+//! discarded. One `tasks:aborted` event lists them all.
+
+use crate::db::tasks::{self, TaskMessageInput};
+use crate::db::{plans, session_locks, task_events, DbState};
+use crate::sidecar::{self, SidecarCommand, SidecarState};
+use rusqlite::{Connection, TransactionBehavior};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+/// Tasks stopped by `abort_all_tasks`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AbortSummary {
+    pub task_ids: Vec<String>,
+    pub aborted_at: String,
+}
+
+/// System message added to each aborted task, and the reason in its timeline
+const ABORT_NOTE: &str = "Aborted along with all other running tasks.";
+
+/// Mark the unfinished tasks cancelled, returning their IDs
+fn cancel_unfinished(conn: &mut Connection, now: &str) -> Result<Vec<String>, String> {
+    let unfinished = tasks::get_abortable_tasks(conn)?;
+    if unfinished.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    for task in &unfinished {
+        tasks::update_task_status(
+            &tx,
+            &task.task_id,
+            "cancelled",
+            Some(now),
+            task_events::SOURCE_ABORT,
+            Some(ABORT_NOTE),
+        )?;
+        let note = TaskMessageInput {
+            id: format!("msg_{}", uuid::Uuid::new_v4()),
+            msg_type: "system".to_string(),
+            content: ABORT_NOTE.to_string(),
+            timestamp: now.to_string(),
+            tool_name: None,
+            tool_input: None,
+            attachments: None,
+            note: None,
+            origin_id: None,
+        };
+        tasks::add_task_message(&tx, &task.task_id, &note)?;
+        session_locks::release_task(&tx, &task.task_id)?;
+        plans::discard_plan(&tx, &task.task_id)?;
+    }
+    tx.commit()
+        .map_err(|e| format!("Failed to commit aborted tasks: {}", e))?;
+    Ok(unfinished.into_iter().map(|t| t.task_id).collect())
+}
+
+/// Kill every running task, cancel the queued ones, and report them in one event
+pub async fn abort_all(app: &AppHandle) -> Result<AbortSummary, String> {
+    // Holding the manager keeps new tasks from starting until the database is updated
+    let sidecar_state = app.state::<SidecarState>();
+    let mut manager = sidecar_state.manager.lock().await;
+    if manager.is_running() {
+        manager.send_command(SidecarCommand::AbortAll).await?;
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let task_ids = {
+        let db_state = app.state::<DbState>();
+        let mut conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        cancel_unfinished(&mut conn, &now)?
+    };
+    drop(manager);
+
+    for task_id in &task_ids {
+        sidecar::finish_task(app, task_id, true);
+        let app = app.clone();
+        let task_id = task_id.clone();
+        std::thread::spawn(move || {
+            crate::sandbox::stop(&app.state::<crate::sandbox::SandboxState>(), &task_id)
+        });
+    }
+
+    tracing::warn!("[Abort] Aborted {} task(s)", task_ids.len());
+    let summary = AbortSummary {
+        task_ids,
+        aborted_at: now,
+    };
+    sidecar::forward_event(app, "tasks:aborted", serde_json::json!(summary));
+    Ok(summary)
+}
@@ -0,0 +1,485 @@
+// src-tauri/src/api_server.rs
+//! Local HTTP API server
+//!
+//! An opt-in server bound to `127.0.0.1` that lets editor plugins and scripts
+//! start and inspect tasks, wait for them to finish, answer permission
+//! requests, and stream task events over SSE. Every route except `/api/health`
+//! requires the bearer token kept in the OS keychain (`?token=` is accepted
+//! for clients that cannot set headers).
+//!
+//! When the WebSocket bridge is enabled, `/api/ws` relays the same events as
+//! JSON text frames for dashboards and companion apps. Rotating the token
+//! ends the event streams and closes the sockets opened with the old one,
+//! so clients reconnect with the new one. When the metrics
+//! endpoint is enabled, `/metrics` serves Prometheus metrics (see `metrics.rs`);
+//! scrapers pass the token with `authorization: { credentials: ... }`.
+
+use crate::db::DbState;
+use crate::sidecar::{ForwardedEvent, SidecarState};
+use crate::{PermissionResponse, Task, TaskConfig};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use futures_util::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use tauri::async_runtime::Mutex;
+use tauri::{AppHandle, Manager};
+use tokio::sync::{broadcast, oneshot, watch};
+
+/// Port used when none is configured
+pub const DEFAULT_PORT: u16 = 4317;
+
+/// Shared state handed to every route
+#[derive(Clone)]
+struct ApiContext {
+    app: AppHandle,
+    token: Arc<RwLock<String>>,
+    /// Changes when the token is rotated
+    token_rotated: watch::Receiver<()>,
+    websocket_enabled: Arc<AtomicBool>,
+    metrics_enabled: Arc<AtomicBool>,
+}
+
+/// A running server instance
+struct RunningServer {
+    port: u16,
+    token: Arc<RwLock<String>>,
+    token_rotated: watch::Sender<()>,
+    shutdown: oneshot::Sender<()>,
+}
+
+/// State for the local API server
+pub struct ApiServerState {
+    server: Mutex<Option<RunningServer>>,
+    websocket_enabled: Arc<AtomicBool>,
+    metrics_enabled: Arc<AtomicBool>,
+}
+
+impl ApiServerState {
+    pub fn new() -> Self {
+        Self {
+            server: Mutex::new(None),
+            websocket_enabled: Arc::new(AtomicBool::new(false)),
+            metrics_enabled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Allow or refuse WebSocket connections; takes effect immediately
+    pub fn set_websocket_enabled(&self, enabled: bool) {
+        self.websocket_enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Serve or hide `/metrics`; takes effect immediately
+    pub fn set_metrics_enabled(&self, enabled: bool) {
+        self.metrics_enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Start the server, returning the bound port
+    ///
+    /// Restarts it if it is already running on a different port.
+    pub async fn start(&self, app: &AppHandle, port: u16) -> Result<u16, String> {
+        let mut server = self.server.lock().await;
+        if let Some(running) = server.as_ref() {
+            if running.port == port {
+                return Ok(port);
+            }
+        }
+        if let Some(running) = server.take() {
+            let _ = running.shutdown.send(());
+        }
+
+        let token = Arc::new(RwLock::new(
+            crate::secure_storage::get_or_create_api_server_token()?,
+        ));
+
+        let listener = tokio::net::TcpListener::bind(("127.0.0.1", port))
+            .await
+            .map_err(|e| format!("Failed to bind API server to port {}: {}", port, e))?;
+        let bound_port = listener
+            .local_addr()
+            .map_err(|e| format!("Failed to read API server address: {}", e))?
+            .port();
+
+        let (token_rotated, token_rotated_rx) = watch::channel(());
+        let router = router(ApiContext {
+            app: app.clone(),
+            token: token.clone(),
+            token_rotated: token_rotated_rx,
+            websocket_enabled: self.websocket_enabled.clone(),
+            metrics_enabled: self.metrics_enabled.clone(),
+        });
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+        tauri::async_runtime::spawn(async move {
+            let result = axum::serve(listener, router)
+                .with_graceful_shutdown(async {
+                    let _ = shutdown_rx.await;
+                })
+                .await;
+            if let Err(e) = result {
+                tracing::error!("[ApiServer] Server error: {}", e);
+            }
+            tracing::info!("[ApiServer] Stopped");
+        });
+
+        tracing::info!("[ApiServer] Listening on http://127.0.0.1:{}", bound_port);
+        *server = Some(RunningServer {
+            port: bound_port,
+            token,
+            token_rotated,
+            shutdown: shutdown_tx,
+        });
+        Ok(bound_port)
+    }
+
+    /// Stop the server if it is running
+    pub async fn stop(&self) {
+        if let Some(running) = self.server.lock().await.take() {
+            let _ = running.shutdown.send(());
+        }
+    }
+
+    /// Port of the running server, if any
+    pub async fn running_port(&self) -> Option<u16> {
+        self.server.lock().await.as_ref().map(|s| s.port)
+    }
+
+    /// Apply a new token to the running server, closing WebSockets opened with the old one
+    pub async fn update_token(&self, token: &str) -> Result<(), String> {
+        if let Some(running) = self.server.lock().await.as_ref() {
+            *running.token.write().map_err(|e| e.to_string())? = token.to_string();
+            running.token_rotated.send_replace(());
+        }
+        Ok(())
+    }
+}
+
+impl Default for ApiServerState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Error returned from a route as `{ "error": message }`
+struct ApiError(StatusCode, String);
+
+impl ApiError {
+    fn internal(message: String) -> Self {
+        Self(StatusCode::INTERNAL_SERVER_ERROR, message)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PermissionBody {
+    allowed: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EventsQuery {
+    task_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListQuery {
+    sort: Option<crate::db::tasks::TaskSort>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AwaitQuery {
+    timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct HealthResponse {
+    status: &'static str,
+    version: &'static str,
+}
+
+fn router(context: ApiContext) -> Router {
+    let protected = Router::new()
+        .route("/api/tasks", get(list_tasks).post(start_task))
+        .route("/api/tasks/:task_id", get(get_task))
+        .route("/api/tasks/:task_id/await", get(await_task))
+        .route("/api/tasks/:task_id/cancel", post(cancel_task))
+        .route("/api/tasks/:task_id/permission", post(respond_to_permission))
+        .route("/api/events", get(stream_events))
+        .route("/api/ws", get(websocket))
+        .route("/metrics", get(metrics))
+        .route_layer(middleware::from_fn_with_state(
+            context.clone(),
+            require_token,
+        ));
+
+    Router::new()
+        .route("/api/health", get(health))
+        .merge(protected)
+        .with_state(context)
+}
+
+/// Reject requests that do not carry the API token
+async fn require_token(State(ctx): State<ApiContext>, req: Request, next: Next) -> Response {
+    let provided = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|v| v.trim().to_string())
+        .or_else(|| {
+            req.uri().query().and_then(|q| {
+                q.split('&')
+                    .find_map(|pair| pair.strip_prefix("token="))
+                    .map(|v| v.to_string())
+            })
+        });
+
+    let authorized = match (provided, ctx.token.read()) {
+        (Some(provided), Ok(expected)) => tokens_match(&provided, &expected),
+        _ => false,
+    };
+
+    if !authorized {
+        return ApiError(StatusCode::UNAUTHORIZED, "Invalid or missing API token".into())
+            .into_response();
+    }
+    next.run(req).await
+}
+
+/// Compare tokens without short-circuiting on the first mismatch
+fn tokens_match(provided: &str, expected: &str) -> bool {
+    provided.len() == expected.len()
+        && provided
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+async fn health() -> Json<HealthResponse> {
+    Json(HealthResponse {
+        status: "ok",
+        version: env!("CARGO_PKG_VERSION"),
+    })
+}
+
+async fn list_tasks(
+    State(ctx): State<ApiContext>,
+    Query(query): Query<ListQuery>,
+) -> ApiResult<Vec<Task>> {
+    let messages = ctx.app.state::<crate::message_buffer::MessageBufferState>();
+    crate::list_tasks(query.sort, ctx.app.state::<DbState>(), messages)
+        .await
+        .map(Json)
+        .map_err(ApiError::internal)
+}
+
+async fn start_task(
+    State(ctx): State<ApiContext>,
+    Json(config): Json<TaskConfig>,
+) -> Result<(StatusCode, Json<Task>), ApiError> {
+    if config.prompt.trim().is_empty() {
+        return Err(ApiError(StatusCode::BAD_REQUEST, "Prompt is required".into()));
+    }
+    let task = crate::start_task(
+        config,
+        ctx.app.clone(),
+        ctx.app.state::<SidecarState>(),
+        ctx.app.state::<DbState>(),
+    )
+    .await
+    .map_err(ApiError::internal)?;
+    Ok((StatusCode::CREATED, Json(task)))
+}
+
+async fn get_task(
+    State(ctx): State<ApiContext>,
+    Path(task_id): Path<String>,
+) -> ApiResult<Task> {
+    let messages = ctx.app.state::<crate::message_buffer::MessageBufferState>();
+    crate::get_task(task_id.clone(), Some(true), None, ctx.app.state::<DbState>(), messages)
+        .await
+        .map_err(ApiError::internal)?
+        .map(Json)
+        .ok_or_else(|| ApiError(StatusCode::NOT_FOUND, format!("Task not found: {}", task_id)))
+}
+
+/// Hold the request until the task ends or `timeoutMs` passes
+async fn await_task(
+    State(ctx): State<ApiContext>,
+    Path(task_id): Path<String>,
+    Query(query): Query<AwaitQuery>,
+) -> ApiResult<crate::task_wait::TaskOutcome> {
+    crate::await_task(
+        task_id.clone(),
+        query.timeout_ms,
+        ctx.app.state::<DbState>(),
+        ctx.app.state::<SidecarState>(),
+    )
+    .await
+    .map_err(ApiError::internal)?
+    .map(Json)
+    .ok_or_else(|| ApiError(StatusCode::NOT_FOUND, format!("Task not found: {}", task_id)))
+}
+
+async fn cancel_task(
+    State(ctx): State<ApiContext>,
+    Path(task_id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    crate::cancel_task(task_id, ctx.app.clone(), ctx.app.state::<SidecarState>())
+        .await
+        .map_err(ApiError::internal)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn respond_to_permission(
+    State(ctx): State<ApiContext>,
+    Path(task_id): Path<String>,
+    Json(body): Json<PermissionBody>,
+) -> Result<StatusCode, ApiError> {
+    crate::respond_to_permission(
+        PermissionResponse {
+            task_id,
+            allowed: body.allowed,
+        },
+        ctx.app.state::<SidecarState>(),
+    )
+    .await
+    .map_err(ApiError::internal)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Stream forwarded events as SSE, optionally filtered to one task, until the token rotates
+async fn stream_events(
+    State(ctx): State<ApiContext>,
+    Query(query): Query<EventsQuery>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let rx = ctx.app.state::<SidecarState>().subscribe();
+    let mut token_rotated = ctx.token_rotated.clone();
+    token_rotated.mark_unchanged();
+    let stream = event_stream(rx, query.task_id)
+        .map(|event| {
+            Ok(Event::default()
+                .event(&event.event)
+                .data(event.payload.to_string()))
+        })
+        .take_until(async move {
+            let _ = token_rotated.changed().await;
+        });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// Upgrade to a WebSocket relaying forwarded events, optionally filtered to one task
+async fn websocket(
+    State(ctx): State<ApiContext>,
+    Query(query): Query<EventsQuery>,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, ApiError> {
+    if !ctx.websocket_enabled.load(Ordering::Relaxed) {
+        return Err(ApiError(
+            StatusCode::NOT_FOUND,
+            "WebSocket bridge is disabled".into(),
+        ));
+    }
+
+    let rx = ctx.app.state::<SidecarState>().subscribe();
+    let mut token_rotated = ctx.token_rotated.clone();
+    token_rotated.mark_unchanged();
+    Ok(upgrade.on_upgrade(move |socket| relay_events(socket, rx, query.task_id, token_rotated)))
+}
+
+/// Serve metrics in Prometheus text format
+async fn metrics(State(ctx): State<ApiContext>) -> Result<Response, ApiError> {
+    if !ctx.metrics_enabled.load(Ordering::Relaxed) {
+        return Err(ApiError(
+            StatusCode::NOT_FOUND,
+            "Metrics endpoint is disabled".into(),
+        ));
+    }
+
+    let body = {
+        let db_state = ctx.app.state::<DbState>();
+        let conn = db_state.reader.lock().map_err(|e| ApiError::internal(e.to_string()))?;
+        crate::metrics::render(&conn).map_err(ApiError::internal)?
+    };
+    Ok(([(header::CONTENT_TYPE, crate::metrics::CONTENT_TYPE)], body).into_response())
+}
+
+/// Send events to the socket until either side goes away or the token is rotated
+async fn relay_events(
+    mut socket: WebSocket,
+    rx: broadcast::Receiver<ForwardedEvent>,
+    task_id: Option<String>,
+    mut token_rotated: watch::Receiver<()>,
+) {
+    let mut events = Box::pin(event_stream(rx, task_id));
+
+    loop {
+        tokio::select! {
+            event = events.next() => {
+                let Some(event) = event else { break };
+                let text = match serde_json::to_string(&event) {
+                    Ok(text) => text,
+                    Err(e) => {
+                        tracing::warn!("[ApiServer] Failed to serialize event: {}", e);
+                        continue;
+                    }
+                };
+                if socket.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+            _ = token_rotated.changed() => {
+                tracing::info!("[ApiServer] Closing WebSocket after the API token was rotated");
+                let _ = socket.send(Message::Close(None)).await;
+                break;
+            }
+            incoming = socket.recv() => {
+                // Clients only listen; anything but a close frame is ignored
+                match incoming {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                }
+            }
+        }
+    }
+}
+
+/// Turn a broadcast receiver into a stream of events, skipping over lag
+pub(crate) fn event_stream(
+    rx: broadcast::Receiver<ForwardedEvent>,
+    task_id: Option<String>,
+) -> impl Stream<Item = ForwardedEvent> {
+    futures_util::stream::unfold((rx, task_id), |(mut rx, task_id)| async move {
+        loop {
+            match rx.recv().await {
+                Ok(event) => {
+                    if task_id.is_none() || event.task_id() == task_id.as_deref() {
+                        return Some((event, (rx, task_id)));
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!("[ApiServer] Event subscriber lagged by {} events", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    })
+}
@@ -0,0 +1,423 @@
+// src-tauri/src/attachments.rs
+//! Task attachments
+//!
+//! Files added while composing a task are copied into `app_data/attachments`
+//! and recorded in the `attachments` table, so they outlive the original file
+//! and can be referenced by ID. Image attachments are passed to the agent as
+//! files when the selected model supports vision. Audio attachments can be
+//! transcribed through OpenAI's transcription API; the transcript is stored on
+//! the attachment so the frontend can append it to the prompt. Web pages added
+//! with `attach_url` are stored with their readable text, which is injected
+//! into the prompt when the task starts.
+//!
+//! Files larger than the `max_attachment_bytes` setting are refused. Images
+//! get a small JPEG thumbnail written next to the original, so lists can show
+//! them without loading the full file; screenshots in task messages get one
+//! stored with the message attachment (see `db::tasks`).
+
+use crate::db::attachments::Attachment;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+/// OpenAI audio transcription endpoint
+const TRANSCRIPTION_URL: &str = "https://api.openai.com/v1/audio/transcriptions";
+
+/// Transcription model
+const TRANSCRIPTION_MODEL: &str = "whisper-1";
+
+/// OpenAI rejects audio files larger than this
+const MAX_AUDIO_BYTES: u64 = 25 * 1024 * 1024;
+
+/// Largest page or document fetched by `attach_url`
+const MAX_FETCH_BYTES: usize = 20 * 1024 * 1024;
+
+/// Extracted text beyond this many characters is truncated
+const MAX_TEXT_CHARS: usize = 100_000;
+
+/// Fetched URLs are reused for this long instead of downloading again
+pub const URL_CACHE_HOURS: i64 = 24;
+
+/// Largest file that can be attached unless the setting says otherwise
+pub const DEFAULT_MAX_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Longest side of an image thumbnail, in pixels
+const THUMBNAIL_SIZE: u32 = 256;
+
+/// Images wider or taller than this aren't decoded for a thumbnail
+const MAX_IMAGE_SIDE: u32 = 16_384;
+
+/// Most memory decoding an image for a thumbnail may take
+const MAX_DECODE_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Directory attachments are copied into
+pub fn attachments_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = crate::profile::data_dir(app)?.join("attachments");
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create attachments directory: {}", e))?;
+    Ok(dir)
+}
+
+/// Kind and MIME type from a file extension
+pub fn classify(path: &Path) -> (&'static str, &'static str) {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "mp3" => ("audio", "audio/mpeg"),
+        "m4a" => ("audio", "audio/mp4"),
+        "wav" => ("audio", "audio/wav"),
+        "webm" => ("audio", "audio/webm"),
+        "ogg" | "oga" => ("audio", "audio/ogg"),
+        "flac" => ("audio", "audio/flac"),
+        "png" => ("image", "image/png"),
+        "jpg" | "jpeg" => ("image", "image/jpeg"),
+        "gif" => ("image", "image/gif"),
+        "webp" => ("image", "image/webp"),
+        "pdf" => ("document", "application/pdf"),
+        "txt" | "md" => ("document", "text/plain"),
+        _ => ("document", "application/octet-stream"),
+    }
+}
+
+fn new_attachment(app: &AppHandle, file_name: &str) -> Result<Attachment, String> {
+    let name = Path::new(file_name);
+    let (kind, mime_type) = classify(name);
+    let id = format!("att_{}", uuid::Uuid::new_v4());
+    let mut path = attachments_dir(app)?.join(&id);
+    if let Some(extension) = name.extension() {
+        path.set_extension(extension);
+    }
+
+    Ok(Attachment {
+        id,
+        kind: kind.to_string(),
+        file_name: file_name.to_string(),
+        mime_type: mime_type.to_string(),
+        path: path.to_string_lossy().to_string(),
+        text: None,
+        source_url: None,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+fn check_size(file_name: &str, size: u64, max_bytes: u64) -> Result<(), String> {
+    if size > max_bytes {
+        return Err(format!(
+            "{} is larger than the {:.1} MB attachment limit",
+            file_name,
+            max_bytes as f64 / (1024.0 * 1024.0)
+        ));
+    }
+    Ok(())
+}
+
+/// Thumbnail an image attachment once its file is written; a failure only loses the thumbnail
+fn add_thumbnail(attachment: &Attachment) {
+    if attachment.kind == "image" {
+        if let Err(e) = write_thumbnail(attachment) {
+            tracing::warn!("[Attachments] {}", e);
+        }
+    }
+}
+
+/// Copy a file of at most `max_bytes` into the attachments directory and describe it
+pub fn store_file(app: &AppHandle, source: &Path, max_bytes: u64) -> Result<Attachment, String> {
+    if !source.is_file() {
+        return Err(format!("File not found: {}", source.display()));
+    }
+    let file_name = source
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let size = std::fs::metadata(source)
+        .map_err(|e| format!("Failed to read {}: {}", source.display(), e))?
+        .len();
+    check_size(&file_name, size, max_bytes)?;
+    let attachment = new_attachment(app, &file_name)?;
+    std::fs::copy(source, &attachment.path)
+        .map_err(|e| format!("Failed to copy attachment: {}", e))?;
+    add_thumbnail(&attachment);
+    Ok(attachment)
+}
+
+/// Write base64 data (e.g. a pasted screenshot) of at most `max_bytes` into the
+/// attachments directory
+pub fn store_base64(
+    app: &AppHandle,
+    file_name: &str,
+    data: &str,
+    max_bytes: u64,
+) -> Result<Attachment, String> {
+    use base64::Engine;
+
+    // Accept data URLs as produced by the clipboard and canvas APIs
+    let encoded = data.split_once(";base64,").map(|(_, d)| d).unwrap_or(data);
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| format!("Invalid attachment data: {}", e))?;
+    check_size(file_name, bytes.len() as u64, max_bytes)?;
+    let attachment = new_attachment(app, file_name)?;
+    std::fs::write(&attachment.path, bytes)
+        .map_err(|e| format!("Failed to write attachment: {}", e))?;
+    add_thumbnail(&attachment);
+    Ok(attachment)
+}
+
+/// Where an image attachment's thumbnail is kept, next to the original
+fn thumbnail_path(attachment: &Attachment) -> PathBuf {
+    Path::new(&attachment.path).with_extension("thumb.jpg")
+}
+
+/// JPEG thumbnail, `THUMBNAIL_SIZE` on its longest side, of an encoded image
+///
+/// The decoder is limited, so an image claiming huge dimensions is refused
+/// rather than allocated.
+fn encode_thumbnail(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let mut reader = image::ImageReader::new(std::io::Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| format!("Failed to read image: {}", e))?;
+    let mut limits = image::Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_SIDE);
+    limits.max_image_height = Some(MAX_IMAGE_SIDE);
+    limits.max_alloc = Some(MAX_DECODE_BYTES);
+    reader.limits(limits);
+    let image = reader.decode().map_err(|e| format!("Failed to read image: {}", e))?;
+    // JPEG has no alpha channel
+    let thumbnail = image::DynamicImage::ImageRgb8(
+        image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).into_rgb8(),
+    );
+    let mut encoded = std::io::Cursor::new(Vec::new());
+    thumbnail
+        .write_to(&mut encoded, image::ImageFormat::Jpeg)
+        .map_err(|e| format!("Failed to encode thumbnail: {}", e))?;
+    Ok(encoded.into_inner())
+}
+
+/// Write a thumbnail next to an image attachment
+fn write_thumbnail(attachment: &Attachment) -> Result<PathBuf, String> {
+    let bytes = std::fs::read(&attachment.path)
+        .map_err(|e| format!("Failed to read attachment: {}", e))?;
+    let thumbnail = encode_thumbnail(&bytes)
+        .map_err(|e| format!("Failed to thumbnail {}: {}", attachment.file_name, e))?;
+
+    let path = thumbnail_path(attachment);
+    std::fs::write(&path, thumbnail).map_err(|e| format!("Failed to write thumbnail: {}", e))?;
+    Ok(path)
+}
+
+/// Thumbnail of a screenshot's base64 data or data URL, as a JPEG data URL
+pub fn screenshot_thumbnail(data: &str) -> Result<String, String> {
+    use base64::Engine;
+
+    let encoded = data.split_once(";base64,").map(|(_, d)| d).unwrap_or(data);
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| format!("Invalid screenshot data: {}", e))?;
+    let thumbnail = encode_thumbnail(&bytes)?;
+    Ok(format!(
+        "data:image/jpeg;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(thumbnail)
+    ))
+}
+
+/// Thumbnail of an image attachment as a JPEG data URL; `None` for other kinds
+///
+/// Attachments added before thumbnails existed get theirs now.
+pub fn thumbnail(attachment: &Attachment) -> Result<Option<String>, String> {
+    use base64::Engine;
+
+    if attachment.kind != "image" {
+        return Ok(None);
+    }
+    let mut path = thumbnail_path(attachment);
+    if !path.is_file() {
+        path = write_thumbnail(attachment)?;
+    }
+    let bytes = std::fs::read(&path).map_err(|e| format!("Failed to read thumbnail: {}", e))?;
+    Ok(Some(format!(
+        "data:image/jpeg;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    )))
+}
+
+/// Models in the provider catalog without image input
+///
+/// Mirrors `supportsVision: false` in `src/shared/types/provider.ts`.
+const NON_VISION_MODELS: &[&str] = &[
+    "grok-3",
+    "deepseek-chat",
+    "deepseek-reasoner",
+    "glm-4.7-flashx",
+    "glm-4.7",
+    "glm-4.7-flash",
+    "glm-4.6",
+    "glm-4.5-flash",
+];
+
+/// Whether a model accepts images; models outside the catalog are assumed to
+/// (dynamic providers like Ollama and OpenRouter do not report it)
+pub fn supports_vision(model_id: &str) -> bool {
+    let name = model_id.rsplit('/').next().unwrap_or(model_id);
+    !NON_VISION_MODELS.contains(&name)
+}
+
+/// Transcribe an audio attachment
+pub async fn transcribe(attachment: &Attachment) -> Result<String, String> {
+    if attachment.kind != "audio" {
+        return Err(format!("Attachment {} is not audio", attachment.id));
+    }
+    let api_key = crate::secure_storage::get_api_key("openai")?
+        .ok_or_else(|| "Transcription needs an OpenAI API key".to_string())?;
+
+    let size = std::fs::metadata(&attachment.path)
+        .map_err(|e| format!("Failed to read attachment: {}", e))?
+        .len();
+    if size > MAX_AUDIO_BYTES {
+        return Err("Audio is too long to transcribe (25 MB limit)".to_string());
+    }
+    let bytes = std::fs::read(&attachment.path)
+        .map_err(|e| format!("Failed to read attachment: {}", e))?;
+
+    let file = reqwest::multipart::Part::bytes(bytes)
+        .file_name(attachment.file_name.clone())
+        .mime_str(&attachment.mime_type)
+        .map_err(|e| format!("Failed to prepare audio: {}", e))?;
+    let form = reqwest::multipart::Form::new()
+        .text("model", TRANSCRIPTION_MODEL)
+        .part("file", file);
+
+    let response = reqwest::Client::new()
+        .post(TRANSCRIPTION_URL)
+        .bearer_auth(api_key)
+        .multipart(form)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to transcribe audio: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "Transcription API returned status: {}",
+            response.status()
+        ));
+    }
+
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse transcription: {}", e))?;
+    body.get("text")
+        .and_then(|t| t.as_str())
+        .map(|t| t.trim().to_string())
+        .ok_or_else(|| "Transcription response had no text".to_string())
+}
+
+fn truncate_text(text: &str) -> String {
+    if text.chars().count() <= MAX_TEXT_CHARS {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(MAX_TEXT_CHARS).collect();
+    truncated.push_str("\n… (truncated)");
+    truncated
+}
+
+/// File name for a fetched URL: the last path segment, or the host
+fn url_file_name(url: &reqwest::Url, mime_type: &str) -> String {
+    let segment = url
+        .path_segments()
+        .and_then(|mut s| s.next_back())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string());
+    let name = segment.unwrap_or_else(|| url.host_str().unwrap_or("page").to_string());
+    match mime_type {
+        "text/html" if !name.ends_with(".html") && !name.ends_with(".htm") => format!("{}.html", name),
+        "application/pdf" if !name.ends_with(".pdf") => format!("{}.pdf", name),
+        _ => name,
+    }
+}
+
+/// Fetch a web page or PDF and store it as an attachment
+///
+/// HTML is reduced to readable text; PDFs are kept as files for the agent to
+/// read. Requests go through the system proxy (`HTTPS_PROXY` / `HTTP_PROXY`).
+pub async fn fetch_url(app: &AppHandle, url: &str) -> Result<Attachment, String> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+    if parsed.scheme() != "http" && parsed.scheme() != "https" {
+        return Err(format!("Unsupported URL scheme: {}", parsed.scheme()));
+    }
+
+    let client = reqwest::Client::builder()
+        .user_agent(concat!("cowork-z/", env!("CARGO_PKG_VERSION")))
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let mut response = client
+        .get(parsed.clone())
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch {}: {}", url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("{} returned status: {}", url, response.status()));
+    }
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(|v| v.trim().to_lowercase())
+        .unwrap_or_default();
+    // Refuse an oversized body up front when its length is given, and count it
+    // as it arrives when it isn't
+    let too_large = || format!("{} is larger than 20 MB", url);
+    if response.content_length().is_some_and(|len| len > MAX_FETCH_BYTES as u64) {
+        return Err(too_large());
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", url, e))?
+    {
+        if bytes.len() + chunk.len() > MAX_FETCH_BYTES {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    let (mime_type, text) = match content_type.as_str() {
+        "text/html" | "application/xhtml+xml" => {
+            ("text/html", Some(html2text::from_read(&bytes[..], 100)))
+        }
+        "text/plain" | "text/markdown" | "application/json" => {
+            ("text/plain", Some(String::from_utf8_lossy(&bytes).to_string()))
+        }
+        "application/pdf" => ("application/pdf", None),
+        other => return Err(format!("Unsupported content type: {}", other)),
+    };
+
+    let mut attachment = new_attachment(app, &url_file_name(&parsed, mime_type))?;
+    std::fs::write(&attachment.path, &bytes)
+        .map_err(|e| format!("Failed to write attachment: {}", e))?;
+    attachment.kind = "document".to_string();
+    attachment.mime_type = mime_type.to_string();
+    attachment.text = text.map(|t| truncate_text(t.trim()));
+    attachment.source_url = Some(url.to_string());
+    Ok(attachment)
+}
+
+/// Prompt with the text of document and audio attachments appended as context
+pub fn prompt_with_context(prompt: &str, attachments: &[Attachment]) -> String {
+    let mut prompt = prompt.to_string();
+    for attachment in attachments {
+        let Some(text) = &attachment.text else {
+            continue;
+        };
+        let source = attachment.source_url.as_deref().unwrap_or(&attachment.file_name);
+        prompt.push_str(&format!(
+            "\n\n<attachment source=\"{}\">\n{}\n</attachment>",
+            source, text
+        ));
+    }
+    prompt
+}
@@ -0,0 +1,279 @@
+// src-tauri/src/backup.rs
+//! Database backups
+//!
+//! A backup is a `VACUUM INTO` copy of the database in `backups/` under the
+//! profile's data directory. It is taken through the read connection, so it is
+//! a consistent snapshot and doesn't hold up writes. Backups are made on demand
+//! or on the schedule in settings, which a background thread checks every few
+//! minutes; only automatic backups are pruned to the number to keep.
+//!
+//! The open database can't be replaced, so a restore is staged: the chosen
+//! backup is checked and copied next to the database, and the app restarts.
+//! Before the database is opened again the staged copy takes its place, and
+//! the replaced database is kept as a `pre-restore` backup.
+//!
+//! A database written by a newer version of the app is set aside at startup
+//! as a `pre-downgrade` backup and a new one is started, so an older build
+//! still opens. Its tasks can be imported into the new database, and the
+//! whole backup restored once the app is upgraded again.
+
+use crate::db::{self, DbState};
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager};
+
+/// Directory of backups in the profile's data directory
+const BACKUP_DIR: &str = "backups";
+
+/// Backup staged by `stage_restore`, next to the database
+const PENDING_RESTORE: &str = "restore-pending.db";
+
+/// How often the schedule is checked
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Kinds of backup, which are also their file name prefixes
+const KINDS: &[&str] = &["auto", "manual", "pre-restore", "pre-downgrade"];
+
+/// A backup file
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    /// File name, used to restore it
+    pub id: String,
+    /// `auto`, `manual`, `pre-restore`, or `pre-downgrade`
+    pub kind: String,
+    pub created_at: String,
+    pub bytes: u64,
+}
+
+fn backup_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = crate::profile::data_dir(app)?.join(BACKUP_DIR);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create backup directory: {}", e))?;
+    Ok(dir)
+}
+
+fn pending_restore_path(app: &AppHandle) -> PathBuf {
+    db::get_database_path(app).with_file_name(PENDING_RESTORE)
+}
+
+fn backup_info(path: &Path) -> Option<BackupInfo> {
+    let id = path.file_name()?.to_str()?.to_string();
+    let stem = id.strip_suffix(".db")?;
+    let kind = KINDS
+        .iter()
+        .filter(|kind| stem.starts_with(&format!("{}-", kind)))
+        .max_by_key(|kind| kind.len())?;
+    let metadata = std::fs::metadata(path).ok()?;
+    let modified: chrono::DateTime<chrono::Utc> =
+        metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH).into();
+    Some(BackupInfo {
+        id,
+        kind: kind.to_string(),
+        created_at: modified.to_rfc3339(),
+        bytes: metadata.len(),
+    })
+}
+
+/// Copy the database a connection has open into a new backup file
+fn write_backup(dir: &Path, conn: &Connection, kind: &str) -> Result<BackupInfo, String> {
+    let name = format!("{}-{}.db", kind, chrono::Utc::now().format("%Y%m%d-%H%M%S"));
+    let path = dir.join(&name);
+    if path.exists() {
+        return Err(format!("Backup already exists: {}", name));
+    }
+    conn.execute("VACUUM INTO ?1", [path.to_string_lossy()])
+        .map_err(|e| format!("Failed to back up database: {}", e))?;
+    // The copy can renumber rowids, which the search index is keyed by
+    let copy = Connection::open(&path)
+        .map_err(|e| format!("Failed to open backup {}: {}", name, e))?;
+    db::search::rebuild(&copy)?;
+    drop(copy);
+    backup_info(&path).ok_or_else(|| format!("Backup was not written: {}", name))
+}
+
+/// Back up the database now; `kind` is "auto" or "manual"
+pub fn create(app: &AppHandle, kind: &str) -> Result<BackupInfo, String> {
+    let dir = backup_dir(app)?;
+    let db_state = app.state::<DbState>();
+    let conn = db_state.reader.lock().map_err(|e| e.to_string())?;
+    let backup = write_backup(&dir, &conn, kind)?;
+    tracing::info!("[Backup] Wrote {} ({} bytes)", backup.id, backup.bytes);
+    Ok(backup)
+}
+
+/// Backups, newest first
+pub fn list(app: &AppHandle) -> Result<Vec<BackupInfo>, String> {
+    let entries = std::fs::read_dir(backup_dir(app)?)
+        .map_err(|e| format!("Failed to read backup directory: {}", e))?;
+    let mut backups: Vec<BackupInfo> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| backup_info(&entry.path()))
+        .collect();
+    backups.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id)));
+    Ok(backups)
+}
+
+/// Delete automatic backups beyond the newest `keep`
+fn prune(app: &AppHandle, keep: u32) -> Result<(), String> {
+    let dir = backup_dir(app)?;
+    let autos = list(app)?.into_iter().filter(|b| b.kind == "auto");
+    for backup in autos.skip(keep as usize) {
+        match std::fs::remove_file(dir.join(&backup.id)) {
+            Ok(()) => tracing::info!("[Backup] Pruned {}", backup.id),
+            Err(e) => tracing::warn!("[Backup] Failed to prune {}: {}", backup.id, e),
+        }
+    }
+    Ok(())
+}
+
+/// Take an automatic backup if the schedule says one is due
+fn run_schedule(app: &AppHandle) -> Result<(), String> {
+    let settings = {
+        let db_state = app.state::<DbState>();
+        let conn = db_state.reader.lock().map_err(|e| e.to_string())?;
+        db::settings::get_backup_settings(&conn)
+    };
+    if !settings.enabled {
+        return Ok(());
+    }
+
+    let interval = chrono::Duration::hours(settings.interval_hours as i64);
+    let last = list(app)?.into_iter().find(|b| b.kind == "auto");
+    let due = last
+        .and_then(|b| chrono::DateTime::parse_from_rfc3339(&b.created_at).ok())
+        .is_none_or(|created_at| {
+            chrono::Utc::now().signed_duration_since(created_at) >= interval
+        });
+    if !due {
+        return Ok(());
+    }
+
+    create(app, "auto")?;
+    prune(app, settings.keep)
+}
+
+/// Check the backup schedule in the background
+pub fn start_schedule(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        if let Err(e) = run_schedule(&app) {
+            tracing::warn!("[Backup] Scheduled backup failed: {}", e);
+        }
+        std::thread::sleep(SCHEDULE_CHECK_INTERVAL);
+    });
+}
+
+/// Path of a listed backup
+fn find(app: &AppHandle, backup_id: &str) -> Result<PathBuf, String> {
+    let backup = list(app)?
+        .into_iter()
+        .find(|b| b.id == backup_id)
+        .ok_or_else(|| format!("Backup not found: {}", backup_id))?;
+    Ok(backup_dir(app)?.join(&backup.id))
+}
+
+/// Check a backup and stage it to replace the database at the next start
+pub fn stage_restore(app: &AppHandle, backup_id: &str) -> Result<(), String> {
+    let path = find(app, backup_id)?;
+
+    let conn = Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Failed to open backup: {}", e))?;
+    let integrity: String = conn
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to check backup: {}", e))?;
+    if integrity != "ok" {
+        return Err(format!("Backup is damaged: {}", integrity));
+    }
+    let version = db::migrations::get_stored_version(&conn);
+    if version > db::migrations::CURRENT_VERSION {
+        return Err(format!(
+            "Backup is from a newer version of the app (schema v{})",
+            version
+        ));
+    }
+    drop(conn);
+
+    std::fs::copy(&path, pending_restore_path(app))
+        .map_err(|e| format!("Failed to stage backup: {}", e))?;
+    tracing::info!("[Backup] Staged {} to restore at restart", backup_id);
+    Ok(())
+}
+
+/// Remove the write-ahead log and shared memory files next to a database
+fn remove_wal_files(db_path: &Path) {
+    for suffix in ["-wal", "-shm"] {
+        let mut path = db_path.as_os_str().to_owned();
+        path.push(suffix);
+        let _ = std::fs::remove_file(PathBuf::from(path));
+    }
+}
+
+/// Replace the database with a staged backup; runs before the database is opened
+pub fn apply_pending_restore(app: &AppHandle) -> Result<(), String> {
+    let pending = pending_restore_path(app);
+    if !pending.exists() {
+        return Ok(());
+    }
+    let db_path = db::get_database_path(app);
+
+    // Keep the current database, including changes still in its WAL
+    if db_path.exists() {
+        let conn = Connection::open(&db_path)
+            .map_err(|e| format!("Failed to open database: {}", e))?;
+        let kept = write_backup(&backup_dir(app)?, &conn, "pre-restore")?;
+        drop(conn);
+        tracing::info!("[Backup] Kept the replaced database as {}", kept.id);
+    }
+
+    remove_wal_files(&db_path);
+    std::fs::rename(&pending, &db_path)
+        .map_err(|e| format!("Failed to restore backup: {}", e))?;
+    tracing::info!("[Backup] Restored database from backup");
+    Ok(())
+}
+
+/// Keep a database from a newer version of the app as a backup and remove it
+///
+/// Runs at startup, before the app has the database open, on the connection
+/// that found the newer schema.
+pub fn set_aside(app: &AppHandle, conn: Connection) -> Result<BackupInfo, String> {
+    let kept = write_backup(&backup_dir(app)?, &conn, "pre-downgrade")?;
+    drop(conn);
+
+    let db_path = db::get_database_path(app);
+    remove_wal_files(&db_path);
+    std::fs::remove_file(&db_path)
+        .map_err(|e| format!("Failed to remove newer database: {}", e))?;
+    tracing::info!("[Backup] Kept the newer database as {}", kept.id);
+    Ok(kept)
+}
+
+/// Copy a backup's tasks that the database doesn't have, with their messages
+///
+/// Backups from newer versions of the app work too, since migrations only add
+/// to the schema. Tasks keep their workspace by its path. Returns how many
+/// tasks were imported.
+pub fn import_tasks(app: &AppHandle, conn: &Connection, backup_id: &str) -> Result<usize, String> {
+    let path = find(app, backup_id)?;
+    let source = Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Failed to open backup: {}", e))?;
+
+    let mut imported = 0;
+    for mut task in db::tasks::get_tasks(&source) {
+        if db::tasks::task_exists(conn, &task.id) {
+            continue;
+        }
+        if let Some(workspace_id) = task.workspace_id.take() {
+            if let Some(workspace) = db::workspaces::get_workspace(&source, &workspace_id)? {
+                let local = db::workspaces::ensure_workspace_for_path(conn, &workspace.path)?;
+                task.workspace_id = Some(local.id);
+            }
+        }
+        db::tasks::save_stored_task(conn, &task)?;
+        imported += 1;
+    }
+    tracing::info!("[Backup] Imported {} tasks from {}", imported, backup_id);
+    Ok(imported)
+}
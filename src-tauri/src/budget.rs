@@ -0,0 +1,94 @@
+// src-tauri/src/budget.rs
+//! Spend limits enforced when tasks start
+//!
+//! Limits are daily or monthly, optionally scoped to one provider, and are
+//! measured against the cost recorded in `task_usage`. Periods follow the
+//! local calendar, so a daily limit resets at local midnight.
+
+use crate::db::{self, settings::BudgetLimit};
+use chrono::{Datelike, Local, TimeZone, Utc};
+use rusqlite::Connection;
+use serde::Serialize;
+
+/// Fraction of a limit at which a warning is raised
+pub const WARN_FRACTION: f64 = 0.8;
+
+/// Spend against one configured limit
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetStatus {
+    pub period: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_id: Option<String>,
+    pub amount: f64,
+    pub spent: f64,
+}
+
+impl BudgetStatus {
+    pub fn is_exceeded(&self) -> bool {
+        self.spent >= self.amount
+    }
+
+    pub fn is_near_limit(&self) -> bool {
+        self.spent >= self.amount * WARN_FRACTION
+    }
+}
+
+/// Start of the current period as a UTC RFC 3339 timestamp
+fn period_start(period: &str) -> Result<String, String> {
+    let today = Local::now().date_naive();
+    let start = match period {
+        "day" => today,
+        "month" => today.with_day(1).unwrap_or(today),
+        other => return Err(format!("Invalid budget period: {}", other)),
+    };
+    let local = Local
+        .from_local_datetime(&start.and_hms_opt(0, 0, 0).unwrap_or_default())
+        .earliest()
+        .ok_or("Failed to resolve start of budget period")?;
+    Ok(local.with_timezone(&Utc).to_rfc3339())
+}
+
+/// Validate limits before they are stored
+pub fn validate_limits(limits: &[BudgetLimit]) -> Result<(), String> {
+    for limit in limits {
+        period_start(&limit.period)?;
+        if !limit.amount.is_finite() || limit.amount <= 0.0 {
+            return Err(format!("Invalid budget amount: {}", limit.amount));
+        }
+    }
+    Ok(())
+}
+
+/// Spend against one limit in its current period
+fn limit_status(conn: &Connection, limit: BudgetLimit) -> Result<BudgetStatus, String> {
+    let since = period_start(&limit.period)?;
+    let spent = db::usage::get_spend_since(conn, &since, limit.provider_id.as_deref())?;
+    Ok(BudgetStatus {
+        period: limit.period,
+        provider_id: limit.provider_id,
+        amount: limit.amount,
+        spent,
+    })
+}
+
+/// Spend against every configured limit
+pub fn get_all_status(conn: &Connection) -> Result<Vec<BudgetStatus>, String> {
+    db::settings::get_budget_limits(conn)
+        .into_iter()
+        .map(|limit| limit_status(conn, limit))
+        .collect()
+}
+
+/// Spend against the limits that apply to a task on `provider_id`
+pub fn get_status(conn: &Connection, provider_id: Option<&str>) -> Result<Vec<BudgetStatus>, String> {
+    db::settings::get_budget_limits(conn)
+        .into_iter()
+        .filter(|limit| match (&limit.provider_id, provider_id) {
+            (Some(limited), Some(provider)) => limited == provider,
+            (Some(_), None) => false,
+            (None, _) => true,
+        })
+        .map(|limit| limit_status(conn, limit))
+        .collect()
+}
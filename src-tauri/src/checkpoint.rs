@@ -0,0 +1,148 @@
+// src-tauri/src/checkpoint.rs
+//! Checkpointing running tasks
+//!
+//! `observe` follows each task's `task_message` events for its OpenCode
+//! session ID and the files its write and edit tools touch. Every
+//! `CHECKPOINT_INTERVAL`, and when the sidecar terminates or the app exits,
+//! tasks that changed since their last checkpoint get a new one with the
+//! number of messages stored so far. A completed task's checkpoint is dropped;
+//! a failed or interrupted one keeps it, so `resume_from_checkpoint` can
+//! continue the session where it stopped.
+
+use crate::db::checkpoints::{self, Checkpoint};
+use crate::db::DbState;
+use crate::message_buffer::MessageBufferState;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// How often running tasks are checkpointed
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Tools whose `filePath` input is a file the agent changed
+const FILE_TOOLS: &[&str] = &["write", "edit", "multiedit", "patch"];
+
+/// What has been seen of a running task since it started
+#[derive(Default)]
+struct Tracked {
+    session_id: Option<String>,
+    files_touched: Vec<String>,
+    /// Seen something since the last checkpoint
+    dirty: bool,
+}
+
+static TRACKED: LazyLock<Mutex<HashMap<String, Tracked>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Note the session and touched file of a `task_message` payload
+pub fn observe(task_id: &str, payload: &serde_json::Value) {
+    let part = payload.pointer("/message/part");
+    let session_id = part.and_then(|p| p.get("sessionID")).and_then(|s| s.as_str());
+    let is_file_tool = part
+        .and_then(|p| p.get("tool"))
+        .and_then(|t| t.as_str())
+        .is_some_and(|tool| FILE_TOOLS.contains(&tool.to_ascii_lowercase().as_str()));
+    let file = part
+        .filter(|_| is_file_tool)
+        .and_then(|p| p.pointer("/state/input").or_else(|| p.get("input")))
+        .and_then(|input| input.get("filePath").or_else(|| input.get("file_path")))
+        .and_then(|f| f.as_str());
+
+    let Ok(mut tracked) = TRACKED.lock() else {
+        return;
+    };
+    let task = tracked.entry(task_id.to_string()).or_default();
+    if let Some(session_id) = session_id {
+        task.session_id = Some(session_id.to_string());
+    }
+    if let Some(file) = file {
+        if !task.files_touched.iter().any(|f| f == file) {
+            task.files_touched.push(file.to_string());
+        }
+    }
+    // New messages move the checkpoint's message count on
+    task.dirty = true;
+}
+
+/// Session a running task is using, as seen in its messages
+pub fn session_id(task_id: &str) -> Option<String> {
+    TRACKED.lock().ok()?.get(task_id)?.session_id.clone()
+}
+
+/// Checkpoint the tasks that changed since their last checkpoint
+pub fn save_all(app: &AppHandle) {
+    let due: Vec<Checkpoint> = {
+        let Ok(mut tracked) = TRACKED.lock() else {
+            return;
+        };
+        let now = chrono::Utc::now().to_rfc3339();
+        tracked
+            .iter_mut()
+            .filter(|(_, task)| task.dirty)
+            .map(|(task_id, task)| {
+                task.dirty = false;
+                Checkpoint {
+                    task_id: task_id.clone(),
+                    session_id: task.session_id.clone(),
+                    message_count: 0,
+                    files_touched: task.files_touched.clone(),
+                    updated_at: now.clone(),
+                }
+            })
+            .collect()
+    };
+    if due.is_empty() {
+        return;
+    }
+
+    let db_state = app.state::<DbState>();
+    let Ok(mut conn) = db_state.conn.lock() else {
+        return;
+    };
+    for mut checkpoint in due {
+        // Count the messages the UI saved so far, including buffered ones
+        app.state::<MessageBufferState>().flush_task(&mut conn, &checkpoint.task_id);
+        let saved = checkpoints::count_messages(&conn, &checkpoint.task_id).and_then(|count| {
+            checkpoint.message_count = count;
+            checkpoints::save_checkpoint(&conn, &checkpoint)
+        });
+        match saved {
+            Ok(()) => tracing::debug!(
+                "[Checkpoint] {} at {} messages",
+                checkpoint.task_id,
+                checkpoint.message_count
+            ),
+            Err(e) => tracing::warn!("[Checkpoint] {}: {}", checkpoint.task_id, e),
+        }
+    }
+}
+
+/// Stop following a task; a completed task's checkpoint is dropped
+pub fn finish(app: &AppHandle, task_id: &str, completed: bool) {
+    if completed {
+        let db_state = app.state::<DbState>();
+        let result = db_state
+            .conn
+            .lock()
+            .map_err(|e| e.to_string())
+            .and_then(|conn| checkpoints::delete_checkpoint(&conn, task_id));
+        if let Err(e) = result {
+            tracing::warn!("[Checkpoint] {}", e);
+        }
+    } else {
+        // Keep where a failed task got to
+        save_all(app);
+    }
+    if let Ok(mut tracked) = TRACKED.lock() {
+        tracked.remove(task_id);
+    }
+}
+
+/// Checkpoint running tasks in the background
+pub fn start(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(CHECKPOINT_INTERVAL);
+        save_all(&app);
+    });
+}
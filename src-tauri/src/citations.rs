@@ -0,0 +1,158 @@
+// src-tauri/src/citations.rs
+//! Source tracking for web content in a task's context
+//!
+//! Each piece of web content that enters a task's context is a chunk: a URL
+//! attachment injected into the prompt, a page read with `webfetch`, or a
+//! result list from `websearch` (or the Brave Search MCP server). The URLs of
+//! each chunk wait with the task until the next assistant text part, which is
+//! stored with them as its sources (`get_message_sources`). Transcript
+//! exports list them under the message.
+
+use crate::db::attachments::Attachment;
+use crate::db::citations::{self, MessageSource};
+use crate::db::DbState;
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use tauri::{AppHandle, Manager};
+
+/// URLs taken from one search result list at most
+const MAX_RESULT_URLS: usize = 20;
+
+static URL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"https?://[^\s<>()\[\]"'`]+"#).expect("valid URL pattern"));
+
+/// Sources seen since the task's last assistant text
+#[derive(Default)]
+struct Tracked {
+    pending: Vec<MessageSource>,
+    chunks: i64,
+}
+
+static TRACKED: LazyLock<Mutex<HashMap<String, Tracked>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Whether a URL is an http or https link, the only sources kept and linked
+pub(crate) fn is_web_url(url: &str) -> bool {
+    reqwest::Url::parse(url).is_ok_and(|u| u.scheme() == "http" || u.scheme() == "https")
+}
+
+/// Add a chunk's URLs to a task's pending sources
+fn add_chunk(task_id: &str, kind: &str, sources: Vec<(String, Option<String>)>) {
+    let Ok(mut tracked) = TRACKED.lock() else {
+        return;
+    };
+    let task = tracked.entry(task_id.to_string()).or_default();
+    // Tool parts are emitted again as they progress; their URLs are only added once
+    let new: Vec<_> = sources
+        .into_iter()
+        .filter(|(url, _)| !task.pending.iter().any(|s| &s.url == url))
+        .collect();
+    if new.is_empty() {
+        return;
+    }
+    task.chunks += 1;
+    let chunk = task.chunks;
+    task.pending.extend(new.into_iter().map(|(url, title)| MessageSource {
+        url,
+        title,
+        kind: kind.to_string(),
+        chunk,
+    }));
+}
+
+/// Note the web pages attached to a task's prompt
+pub fn note_attachments(task_id: &str, attachments: &[Attachment]) {
+    for attachment in attachments {
+        if let Some(url) = &attachment.source_url {
+            let title = Some(attachment.file_name.clone());
+            add_chunk(task_id, "attachment", vec![(url.clone(), title)]);
+        }
+    }
+}
+
+/// URLs in a tool's output, without trailing punctuation
+fn output_urls(output: &str) -> Vec<(String, Option<String>)> {
+    let mut urls: Vec<(String, Option<String>)> = Vec::new();
+    for found in URL.find_iter(output) {
+        let url = found.as_str().trim_end_matches(['.', ',', ';', ':', '!', '?']);
+        if !urls.iter().any(|(u, _)| u == url) {
+            urls.push((url.to_string(), None));
+        }
+        if urls.len() == MAX_RESULT_URLS {
+            break;
+        }
+    }
+    urls
+}
+
+/// Record the sources of a `task_message` payload, or store them with an assistant text
+pub fn observe(app: &AppHandle, task_id: &str, payload: &serde_json::Value) {
+    let Some(part) = payload.pointer("/message/part") else {
+        return;
+    };
+    let msg_type = payload.pointer("/message/type").and_then(|t| t.as_str());
+    if msg_type == Some("text") {
+        if let Some(origin_id) = part.get("id").and_then(|id| id.as_str()) {
+            attach_pending(app, task_id, origin_id);
+        }
+        return;
+    }
+
+    let Some(tool) = part.get("tool").and_then(|t| t.as_str()) else {
+        return;
+    };
+    let tool = tool.to_ascii_lowercase();
+    let state = part.get("state");
+    if tool == "webfetch" {
+        let url = state
+            .and_then(|s| s.get("input"))
+            .and_then(|input| input.get("url"))
+            .and_then(|u| u.as_str());
+        // The URL comes from the model's input, so it can be anything
+        if let Some(url) = url.filter(|u| is_web_url(u)) {
+            add_chunk(task_id, "webfetch", vec![(url.to_string(), None)]);
+        }
+    } else if tool == "websearch" || tool.starts_with("brave-search_") {
+        let output = state
+            .and_then(|s| s.get("output"))
+            .and_then(|o| o.as_str())
+            .unwrap_or_default();
+        add_chunk(task_id, "websearch", output_urls(output));
+    }
+}
+
+/// Store a task's pending sources with the assistant text part they precede
+fn attach_pending(app: &AppHandle, task_id: &str, origin_id: &str) {
+    let sources = {
+        let Ok(mut tracked) = TRACKED.lock() else {
+            return;
+        };
+        match tracked.get_mut(task_id) {
+            Some(task) if !task.pending.is_empty() => std::mem::take(&mut task.pending),
+            _ => return,
+        }
+    };
+    let db_state = app.state::<DbState>();
+    let saved = db_state
+        .conn
+        .lock()
+        .map_err(|e| e.to_string())
+        .and_then(|conn| citations::save_sources(&conn, task_id, origin_id, &sources));
+    match saved {
+        Ok(()) => tracing::debug!(
+            "[Citations] {} sources for {} of {}",
+            sources.len(),
+            origin_id,
+            task_id
+        ),
+        Err(e) => tracing::warn!("[Citations] {}", e),
+    }
+}
+
+/// Drop a finished task's pending sources
+pub fn finish(task_id: &str) {
+    if let Ok(mut tracked) = TRACKED.lock() {
+        tracked.remove(task_id);
+    }
+}
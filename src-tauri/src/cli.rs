@@ -0,0 +1,408 @@
+// src-tauri/src/cli.rs
+//! OpenCode CLI detection
+//!
+//! Apps launched from the Dock or Start menu don't get the user's shell PATH,
+//! so besides PATH this probes where npm, Volta, nvm, and Homebrew put global
+//! binaries. The resolved path is handed to the sidecar as `OPENCODE_CLI_PATH`
+//! so it runs the same binary that was checked. `install` runs the install
+//! from the app for users who don't have it yet.
+//!
+//! Builds can also ship a runtime in `resources/runtime/bin` (Node and the
+//! OpenCode binary, see `src-tauri/runtime/README.md`). It is used when the
+//! system has no usable CLI, or always when the `cli_runtime` setting is
+//! "bundled", so users without Node can still run tasks.
+
+use crate::db::{self, DbState};
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tauri::{AppHandle, Manager};
+
+/// Command that installs the CLI globally
+pub const INSTALL_COMMAND: &str = "npm install -g opencode-ai";
+
+/// Command that upgrades an outdated CLI
+pub const UPGRADE_COMMAND: &str = "npm install -g opencode-ai@latest";
+
+/// Oldest CLI version whose `run --format json` output the sidecar understands
+pub const MIN_VERSION: &str = "0.15.0";
+
+/// How long `opencode --version` and `npm prefix -g` get to answer
+const COMMAND_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Homebrew formula installed when npm is not available
+const BREW_FORMULA: &str = "sst/tap/opencode";
+
+/// File names the CLI can have on this platform
+fn binary_names() -> &'static [&'static str] {
+    if cfg!(windows) {
+        &["opencode.exe", "opencode.cmd"]
+    } else {
+        &["opencode"]
+    }
+}
+
+fn find_in(dir: &Path, names: &[&str]) -> Option<PathBuf> {
+    names
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// First line of a command's output, if it succeeded within `COMMAND_TIMEOUT`
+///
+/// The sidecar waits on these while starting, with its manager locked, so a
+/// CLI or npm that hangs is killed rather than holding up every task.
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let deadline = std::time::Instant::now() + COMMAND_TIMEOUT;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if std::time::Instant::now() < deadline => {
+                std::thread::sleep(std::time::Duration::from_millis(20));
+            }
+            _ => {
+                tracing::warn!("[CLI] {} {} did not finish, killing it", program, args.join(" "));
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    };
+    if !status.success() {
+        return None;
+    }
+    // Version and prefix output is a line or two, well under the pipe's buffer
+    let mut stdout = Vec::new();
+    child.stdout.take()?.read_to_end(&mut stdout).ok()?;
+    String::from_utf8_lossy(&stdout)
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+/// bin directories of the Node versions installed by nvm (or nvm-windows)
+fn nvm_bin_dirs(home: Option<&Path>) -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    if cfg!(windows) {
+        if let Some(nvm_home) = std::env::var_os("NVM_HOME") {
+            roots.push((PathBuf::from(nvm_home), None));
+        }
+    } else if let Some(nvm_dir) = std::env::var_os("NVM_DIR") {
+        roots.push((PathBuf::from(nvm_dir).join("versions").join("node"), Some("bin")));
+    } else if let Some(home) = home {
+        roots.push((home.join(".nvm").join("versions").join("node"), Some("bin")));
+    }
+
+    let mut dirs = Vec::new();
+    for (root, bin) in roots {
+        let Ok(entries) = std::fs::read_dir(&root) else {
+            continue;
+        };
+        let mut versions: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.is_dir())
+            .collect();
+        versions.sort();
+        versions.reverse();
+        dirs.extend(versions.into_iter().map(|v| match bin {
+            Some(bin) => v.join(bin),
+            None => v,
+        }));
+    }
+    dirs
+}
+
+/// Directories probed after PATH, in order
+fn candidate_dirs(home: Option<&Path>) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    // npm's global prefix (bin/ below it everywhere but Windows)
+    let npm = if cfg!(windows) { "npm.cmd" } else { "npm" };
+    if let Some(prefix) = command_output(npm, &["prefix", "-g"]) {
+        let prefix = PathBuf::from(prefix);
+        dirs.push(if cfg!(windows) { prefix } else { prefix.join("bin") });
+    }
+    if cfg!(windows) {
+        for var in ["APPDATA", "LOCALAPPDATA"] {
+            if let Some(dir) = std::env::var_os(var) {
+                dirs.push(PathBuf::from(dir).join("npm"));
+            }
+        }
+    }
+
+    // Volta
+    if let Some(volta_home) = std::env::var_os("VOLTA_HOME") {
+        dirs.push(PathBuf::from(volta_home).join("bin"));
+    } else if let Some(home) = home {
+        dirs.push(home.join(".volta").join("bin"));
+    }
+
+    dirs.extend(nvm_bin_dirs(home));
+
+    if !cfg!(windows) {
+        dirs.push(PathBuf::from("/opt/homebrew/bin"));
+        dirs.push(PathBuf::from("/usr/local/bin"));
+        dirs.push(PathBuf::from("/home/linuxbrew/.linuxbrew/bin"));
+        if let Some(home) = home {
+            dirs.push(home.join(".npm-global").join("bin"));
+            dirs.push(home.join(".local").join("bin"));
+        }
+    }
+    dirs
+}
+
+/// Resolve the OpenCode CLI binary, or `None` if it is not installed
+pub fn find_opencode(home: Option<&Path>) -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("OPENCODE_CLI_PATH").map(PathBuf::from) {
+        if path.is_file() {
+            return Some(path);
+        }
+    }
+
+    if let Some(path) = std::env::var_os("PATH")
+        .iter()
+        .flat_map(std::env::split_paths)
+        .find_map(|dir| find_in(&dir, binary_names()))
+    {
+        return Some(path);
+    }
+
+    if cfg!(windows) {
+        if let Some(path) = command_output("where.exe", &["opencode"]).map(PathBuf::from) {
+            return Some(path);
+        }
+    }
+
+    candidate_dirs(home)
+        .iter()
+        .find_map(|dir| find_in(dir, binary_names()))
+}
+
+/// bin directory of the runtime bundled with the app, if this build has one
+pub fn bundled_bin_dir(app: &AppHandle) -> Option<PathBuf> {
+    let dir = app.path().resource_dir().ok()?.join("runtime").join("bin");
+    dir.is_dir().then_some(dir)
+}
+
+/// CLI binary the sidecar runs
+#[derive(Debug, Clone)]
+pub struct ResolvedCli {
+    pub path: PathBuf,
+    pub version: Option<String>,
+    /// From the bundled runtime rather than the system
+    pub bundled: bool,
+}
+
+/// Pick the system or bundled CLI according to the `cli_runtime` setting
+///
+/// With "system", the bundled CLI is used only when the system one is missing
+/// or older than `MIN_VERSION`.
+pub fn resolve(app: &AppHandle) -> Option<ResolvedCli> {
+    // The read connection, so a write in progress doesn't hold up the sidecar's start
+    let prefer_bundled = app
+        .try_state::<DbState>()
+        .and_then(|state| {
+            let conn = state.reader.lock().ok()?;
+            Some(db::settings::get_cli_runtime(&conn))
+        })
+        .is_some_and(|runtime| runtime == "bundled");
+    let bundled = || {
+        let path = find_in(&bundled_bin_dir(app)?, binary_names())?;
+        Some(ResolvedCli {
+            version: version(&path),
+            path,
+            bundled: true,
+        })
+    };
+
+    if prefer_bundled {
+        if let Some(cli) = bundled() {
+            return Some(cli);
+        }
+    }
+    let home = app.path().home_dir().ok();
+    let system = find_opencode(home.as_deref()).map(|path| ResolvedCli {
+        version: version(&path),
+        path,
+        bundled: false,
+    });
+    match system {
+        Some(cli) if !cli.version.as_deref().is_some_and(is_outdated) => Some(cli),
+        system => bundled().or(system),
+    }
+}
+
+/// Version reported by `opencode --version` (e.g. "0.15.8")
+pub fn version(path: &Path) -> Option<String> {
+    // .cmd shims have to go through cmd.exe
+    let output = if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("cmd")) {
+        let path = path.to_string_lossy();
+        command_output("cmd", &["/C", path.as_ref(), "--version"])
+    } else {
+        command_output(&path.to_string_lossy(), &["--version"])
+    }?;
+    // Output is "opencode 1.0.0" or just "1.0.0"
+    Some(
+        output
+            .split_whitespace()
+            .find(|word| word.chars().next().is_some_and(|c| c.is_ascii_digit()))
+            .unwrap_or(&output)
+            .to_string(),
+    )
+}
+
+/// Numeric `major.minor.patch` of a version, ignoring a leading 'v' and any
+/// pre-release or build suffix
+fn version_parts(version: &str) -> Option<[u64; 3]> {
+    let core = version
+        .trim()
+        .trim_start_matches('v')
+        .split(['-', '+'])
+        .next()?;
+    let mut parts = [0; 3];
+    for (i, part) in core.split('.').take(3).enumerate() {
+        parts[i] = part.parse().ok()?;
+    }
+    Some(parts)
+}
+
+/// Whether a version is older than `MIN_VERSION`; unparseable versions are not
+pub fn is_outdated(version: &str) -> bool {
+    match (version_parts(version), version_parts(MIN_VERSION)) {
+        (Some(version), Some(min)) => version < min,
+        _ => false,
+    }
+}
+
+/// Fail with a `cli:outdated` event if the CLI is older than `MIN_VERSION`
+///
+/// A CLI that doesn't report its version is let through; the sidecar reports
+/// a broken one itself.
+pub fn ensure_supported(app: &AppHandle, cli: &ResolvedCli) -> Result<(), String> {
+    let Some(version) = cli.version.as_deref().filter(|v| is_outdated(v)) else {
+        return Ok(());
+    };
+
+    tracing::warn!("[CLI] OpenCode {} at {:?} is older than {}", version, cli.path, MIN_VERSION);
+    crate::sidecar::forward_event(
+        app,
+        "cli:outdated",
+        serde_json::json!({
+            "version": version,
+            "requiredVersion": MIN_VERSION,
+            "upgradeCommand": UPGRADE_COMMAND,
+            "path": cli.path.to_string_lossy(),
+        }),
+    );
+    Err(format!(
+        "OpenCode CLI {} is too old (version {} or later is required). Upgrade it with `{}`.",
+        version, MIN_VERSION, UPGRADE_COMMAND
+    ))
+}
+
+/// First of `names` on PATH or in the directories probed for the CLI
+fn find_tool(names: &[&str], home: Option<&Path>) -> Option<PathBuf> {
+    std::env::var_os("PATH")
+        .iter()
+        .flat_map(std::env::split_paths)
+        .find_map(|dir| find_in(&dir, names))
+        .or_else(|| candidate_dirs(home).iter().find_map(|dir| find_in(dir, names)))
+}
+
+fn progress(app: &AppHandle, stage: &str, message: &str) {
+    crate::sidecar::forward_event(
+        app,
+        "cli:install_progress",
+        serde_json::json!({ "stage": stage, "message": message }),
+    );
+}
+
+/// Forward each line of an install's output as progress
+fn forward_output(
+    app: &AppHandle,
+    output: impl Read + Send + 'static,
+) -> std::thread::JoinHandle<()> {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        for line in BufReader::new(output).lines().map_while(Result::ok) {
+            let line = line.trim();
+            if !line.is_empty() {
+                progress(&app, "output", line);
+            }
+        }
+    })
+}
+
+/// Install the CLI with npm, or Homebrew when npm is missing
+///
+/// Progress is emitted as `cli:install_progress` events with a `stage` of
+/// "installing", "output" (one per line the installer prints), "done", or
+/// "error".
+pub fn install(app: &AppHandle, home: Option<&Path>) -> Result<(), String> {
+    let npm_names: &[&str] = if cfg!(windows) { &["npm.cmd"] } else { &["npm"] };
+    let (program, args) = if let Some(npm) = find_tool(npm_names, home) {
+        (npm, vec!["install", "-g", "opencode-ai"])
+    } else if let Some(brew) = find_tool(&["brew"], home).filter(|_| !cfg!(windows)) {
+        (brew, vec!["install", BREW_FORMULA])
+    } else {
+        let message = "Neither npm nor Homebrew was found. Install Node.js and try again.";
+        progress(app, "error", message);
+        return Err(message.to_string());
+    };
+
+    let description = format!("{} {}", program.display(), args.join(" "));
+    tracing::info!("[CLI] Running {}", description);
+    progress(app, "installing", &format!("Running {}", description));
+
+    let mut cmd = Command::new(&program);
+    cmd.args(&args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // npm is a node script, so node (installed next to it) has to be on PATH
+    if let Some(dir) = program.parent() {
+        let path = std::env::var_os("PATH").unwrap_or_default();
+        let dirs = std::iter::once(dir.to_path_buf()).chain(std::env::split_paths(&path));
+        if let Ok(path) = std::env::join_paths(dirs) {
+            cmd.env("PATH", path);
+        }
+    }
+
+    let result = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", description, e))
+        .and_then(|mut child| {
+            let readers = [
+                child.stdout.take().map(|out| forward_output(app, out)),
+                child.stderr.take().map(|err| forward_output(app, err)),
+            ];
+            let status = child.wait();
+            for reader in readers.into_iter().flatten() {
+                let _ = reader.join();
+            }
+            match status {
+                Ok(status) if status.success() => Ok(()),
+                Ok(status) => Err(format!("{} failed ({})", description, status)),
+                Err(e) => Err(format!("Failed to wait for {}: {}", description, e)),
+            }
+        });
+
+    match &result {
+        Ok(()) => progress(app, "done", "OpenCode CLI installed"),
+        Err(e) => {
+            tracing::error!("[CLI] {}", e);
+            progress(app, "error", e);
+        }
+    }
+    result
+}
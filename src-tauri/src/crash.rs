@@ -0,0 +1,235 @@
+// src-tauri/src/crash.rs
+//! Crash reporting - panic hook and crash report storage
+//!
+//! A panic hook writes a JSON report (message, location, backtrace, recent log
+//! lines, app/sidecar versions) into `app_data/crashes`. Reports stay local
+//! until the user explicitly submits one.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use tauri::AppHandle;
+
+/// Number of recent log lines kept in memory for crash reports
+const MAX_RECENT_LOG_LINES: usize = 200;
+
+/// Environment variable holding the crash report upload endpoint
+const CRASH_REPORT_URL_ENV: &str = "COWORK_Z_CRASH_REPORT_URL";
+
+static CRASH_DIR: OnceLock<PathBuf> = OnceLock::new();
+static RECENT_LOGS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+static SIDECAR_VERSION: Mutex<Option<String>> = Mutex::new(None);
+
+/// Full crash report as written to disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    pub id: String,
+    pub created_at: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread: Option<String>,
+    pub backtrace: String,
+    pub app_version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sidecar_version: Option<String>,
+    pub os: String,
+    pub arch: String,
+    pub recent_logs: Vec<String>,
+    #[serde(default)]
+    pub submitted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submitted_at: Option<String>,
+}
+
+/// Crash report summary for listing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReportSummary {
+    pub id: String,
+    pub created_at: String,
+    pub message: String,
+    pub submitted: bool,
+}
+
+/// Get the crash report directory, if initialized
+fn crash_dir() -> Option<&'static PathBuf> {
+    CRASH_DIR.get()
+}
+
+/// Initialize the crash directory and install the panic hook
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    let dir = crate::profile::data_dir(app)?.join("crashes");
+
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create crash directory: {}", e))?;
+    let _ = CRASH_DIR.set(dir);
+
+    let previous_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = if let Some(s) = info.payload().downcast_ref::<&str>() {
+            s.to_string()
+        } else if let Some(s) = info.payload().downcast_ref::<String>() {
+            s.clone()
+        } else {
+            "Unknown panic payload".to_string()
+        };
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+
+        match write_report(message, location) {
+            Ok(path) => eprintln!("[Crash] Report written to {:?}", path),
+            Err(e) => eprintln!("[Crash] {}", e),
+        }
+
+        previous_hook(info);
+    }));
+
+    Ok(())
+}
+
+/// Record a log line so it can be attached to a crash report
+pub fn record_log_line(line: impl Into<String>) {
+    if let Ok(mut logs) = RECENT_LOGS.lock() {
+        if logs.len() >= MAX_RECENT_LOG_LINES {
+            logs.pop_front();
+        }
+        logs.push_back(line.into());
+    }
+}
+
+/// Remember the sidecar version reported in its `ready` event
+pub fn set_sidecar_version(version: Option<String>) {
+    if let Ok(mut current) = SIDECAR_VERSION.lock() {
+        *current = version;
+    }
+}
+
+/// Sidecar version reported in its last `ready` event
+pub fn sidecar_version() -> Option<String> {
+    SIDECAR_VERSION.lock().ok().and_then(|v| v.clone())
+}
+
+/// Build a crash report from the current process state and write it to disk
+fn write_report(message: String, location: Option<String>) -> Result<PathBuf, String> {
+    let dir = crash_dir().ok_or("Crash directory not initialized")?;
+
+    let now = chrono::Utc::now();
+    let id = format!(
+        "crash-{}-{}",
+        now.format("%Y%m%dT%H%M%S"),
+        &uuid::Uuid::new_v4().to_string()[..8]
+    );
+
+    // Never block inside the panic hook; a poisoned or held lock just yields no data
+    let recent_logs = RECENT_LOGS
+        .try_lock()
+        .map(|logs| logs.iter().cloned().collect())
+        .unwrap_or_default();
+    let sidecar_version = SIDECAR_VERSION
+        .try_lock()
+        .ok()
+        .and_then(|v| v.clone());
+
+    let report = CrashReport {
+        id: id.clone(),
+        created_at: now.to_rfc3339(),
+        message,
+        location,
+        thread: std::thread::current().name().map(|n| n.to_string()),
+        backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        sidecar_version,
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        recent_logs,
+        submitted: false,
+        submitted_at: None,
+    };
+
+    let path = dir.join(format!("{}.json", id));
+    save_report(&path, &report)?;
+    Ok(path)
+}
+
+/// Serialize a crash report to the given path
+fn save_report(path: &PathBuf, report: &CrashReport) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(report)
+        .map_err(|e| format!("Failed to serialize crash report: {}", e))?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write crash report: {}", e))
+}
+
+/// Resolve the on-disk path of a crash report, rejecting anything outside the crash directory
+fn report_path(id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || id.contains(['/', '\\']) || id.contains("..") {
+        return Err(format!("Invalid crash report ID: {}", id));
+    }
+    let dir = crash_dir().ok_or("Crash directory not initialized")?;
+    Ok(dir.join(format!("{}.json", id)))
+}
+
+/// Get a crash report by ID
+pub fn get_crash_report(id: &str) -> Result<CrashReport, String> {
+    let path = report_path(id)?;
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read crash report: {}", e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse crash report: {}", e))
+}
+
+/// List all crash reports, newest first
+pub fn list_crash_reports() -> Result<Vec<CrashReportSummary>, String> {
+    let dir = match crash_dir() {
+        Some(dir) => dir,
+        None => return Ok(vec![]),
+    };
+
+    let entries =
+        std::fs::read_dir(dir).map_err(|e| format!("Failed to read crash directory: {}", e))?;
+
+    let mut reports: Vec<CrashReportSummary> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| std::fs::read_to_string(entry.path()).ok())
+        .filter_map(|contents| serde_json::from_str::<CrashReport>(&contents).ok())
+        .map(|report| CrashReportSummary {
+            id: report.id,
+            created_at: report.created_at,
+            message: report.message,
+            submitted: report.submitted,
+        })
+        .collect();
+
+    reports.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Ok(reports)
+}
+
+/// Upload a crash report to the configured endpoint and mark it as submitted
+pub async fn submit_crash_report(id: &str) -> Result<(), String> {
+    let endpoint = std::env::var(CRASH_REPORT_URL_ENV)
+        .map_err(|_| "Crash report upload endpoint is not configured".to_string())?;
+
+    let mut report = get_crash_report(id)?;
+
+    let client = reqwest::Client::new();
+    let response = client
+        .post(&endpoint)
+        .json(&report)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to submit crash report: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!(
+            "Crash report endpoint returned status: {}",
+            response.status()
+        ));
+    }
+
+    report.submitted = true;
+    report.submitted_at = Some(chrono::Utc::now().to_rfc3339());
+    save_report(&report_path(id)?, &report)
+}
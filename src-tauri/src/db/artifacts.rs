@@ -0,0 +1,65 @@
+// src-tauri/src/db/artifacts.rs
+//! Code blocks extracted from agent messages
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+/// A fenced code block from an assistant message
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Artifact {
+    pub id: String,
+    pub task_id: String,
+    /// OpenCode part ID of the message the block came from
+    pub origin_id: Option<String>,
+    /// Canonical language from the fence's info string or the code, e.g. "rust"
+    pub language: Option<String>,
+    pub content: String,
+    pub created_at: String,
+}
+
+/// Store an artifact
+pub fn add_artifact(conn: &Connection, artifact: &Artifact) -> Result<(), String> {
+    super::with_retry(|| {
+        conn.execute(
+            "INSERT INTO task_artifacts (id, task_id, origin_id, language, content, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                artifact.id,
+                artifact.task_id,
+                artifact.origin_id,
+                artifact.language,
+                artifact.content,
+                artifact.created_at,
+            ],
+        )
+    })
+    .map_err(|e| format!("Failed to add artifact: {}", e))?;
+    Ok(())
+}
+
+/// A task's artifacts in the order they were produced
+pub fn get_task_artifacts(conn: &Connection, task_id: &str) -> Result<Vec<Artifact>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, task_id, origin_id, language, content, created_at
+             FROM task_artifacts WHERE task_id = ?1
+             ORDER BY created_at, rowid",
+        )
+        .map_err(|e| format!("Failed to prepare artifacts query: {}", e))?;
+    let artifacts = stmt
+        .query_map([task_id], |row| {
+            Ok(Artifact {
+                id: row.get(0)?,
+                task_id: row.get(1)?,
+                origin_id: row.get(2)?,
+                language: row.get(3)?,
+                content: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query artifacts: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(artifacts)
+}
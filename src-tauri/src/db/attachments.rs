@@ -0,0 +1,90 @@
+// src-tauri/src/db/attachments.rs
+//! Attachment repository
+//!
+//! Attachments are files the user adds while composing a task. The file itself
+//! is copied into the app data directory; this table records where it lives
+//! and any text derived from it (e.g. an audio transcription).
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+/// Stored attachment
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    pub id: String,
+    /// `audio`, `image`, or `document`
+    pub kind: String,
+    pub file_name: String,
+    pub mime_type: String,
+    pub path: String,
+    /// Transcription or extracted text
+    pub text: Option<String>,
+    pub source_url: Option<String>,
+    pub created_at: String,
+}
+
+pub fn insert_attachment(conn: &Connection, attachment: &Attachment) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO attachments (id, kind, file_name, mime_type, path, text, source_url, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            attachment.id,
+            attachment.kind,
+            attachment.file_name,
+            attachment.mime_type,
+            attachment.path,
+            attachment.text,
+            attachment.source_url,
+            attachment.created_at,
+        ],
+    )
+    .map_err(|e| format!("Failed to save attachment: {}", e))?;
+    Ok(())
+}
+
+fn row_to_attachment(row: &rusqlite::Row) -> rusqlite::Result<Attachment> {
+    Ok(Attachment {
+        id: row.get(0)?,
+        kind: row.get(1)?,
+        file_name: row.get(2)?,
+        mime_type: row.get(3)?,
+        path: row.get(4)?,
+        text: row.get(5)?,
+        source_url: row.get(6)?,
+        created_at: row.get(7)?,
+    })
+}
+
+pub fn get_attachment(conn: &Connection, id: &str) -> Result<Option<Attachment>, String> {
+    conn.query_row(
+        "SELECT id, kind, file_name, mime_type, path, text, source_url, created_at
+         FROM attachments WHERE id = ?1",
+        [id],
+        row_to_attachment,
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get attachment: {}", e))
+}
+
+/// Most recent attachment fetched from `url` since `since`
+pub fn get_cached_url(conn: &Connection, url: &str, since: &str) -> Result<Option<Attachment>, String> {
+    conn.query_row(
+        "SELECT id, kind, file_name, mime_type, path, text, source_url, created_at
+         FROM attachments WHERE source_url = ?1 AND created_at >= ?2
+         ORDER BY created_at DESC LIMIT 1",
+        params![url, since],
+        row_to_attachment,
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get attachment: {}", e))
+}
+
+pub fn set_text(conn: &Connection, id: &str, text: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE attachments SET text = ?1 WHERE id = ?2",
+        params![text, id],
+    )
+    .map_err(|e| format!("Failed to update attachment: {}", e))?;
+    Ok(())
+}
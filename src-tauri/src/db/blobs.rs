@@ -0,0 +1,125 @@
+// src-tauri/src/db/blobs.rs
+//! Content-addressed attachment data
+//!
+//! Message attachments (e.g. screenshots) keep their data in
+//! `attachment_blobs`, keyed by its SHA-256, and point at it with
+//! `task_attachments.blob_hash`, so the same screenshot attached to many
+//! messages is stored once. Triggers count the references and drop a blob
+//! with its last one. Rows written before blobs existed keep their data inline
+//! until `dedup_inline` moves it.
+
+use rusqlite::{params, Connection, TransactionBehavior};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// Inline rows moved per transaction by `dedup_inline`
+const DEDUP_BATCH: usize = 100;
+
+/// Space saved by storing attachment data once
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageSavings {
+    pub attachments: i64,
+    /// Attachments still holding their own data
+    pub inline_attachments: i64,
+    /// Distinct stored contents
+    pub blobs: i64,
+    /// Bytes the attachments would take with a copy each
+    pub referenced_bytes: i64,
+    pub stored_bytes: i64,
+    pub saved_bytes: i64,
+}
+
+/// Content address of attachment data
+pub fn hash(data: &str) -> String {
+    Sha256::digest(data.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Store data unless it is stored already, returning its hash
+///
+/// The blob has no references until an attachment row points at it.
+pub fn store(conn: &Connection, data: &str) -> Result<String, String> {
+    let hash = hash(data);
+    super::with_retry(|| {
+        conn.execute(
+            "INSERT OR IGNORE INTO attachment_blobs (hash, data, size) VALUES (?1, ?2, ?3)",
+            params![hash, data, data.len() as i64],
+        )
+    })
+    .map_err(|e| format!("Failed to store attachment data: {}", e))?;
+    Ok(hash)
+}
+
+/// Move the data of inline attachments into blobs, returning how many moved
+///
+/// Also drops blobs left without references, e.g. by a failed insert.
+pub fn dedup_inline(conn: &mut Connection) -> Result<usize, String> {
+    let mut moved = 0;
+    loop {
+        let tx = conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        let batch: Vec<(i64, String)> = tx
+            .prepare("SELECT id, data FROM task_attachments WHERE blob_hash IS NULL LIMIT ?1")
+            .and_then(|mut stmt| {
+                stmt.query_map([DEDUP_BATCH as i64], |row| Ok((row.get(0)?, row.get(1)?)))?
+                    .collect()
+            })
+            .map_err(|e| format!("Failed to read inline attachments: {}", e))?;
+        for (id, data) in &batch {
+            let hash = store(&tx, data)?;
+            tx.execute(
+                "UPDATE task_attachments SET blob_hash = ?1, data = '' WHERE id = ?2",
+                params![hash, id],
+            )
+            .map_err(|e| format!("Failed to move attachment data: {}", e))?;
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed to commit attachment data: {}", e))?;
+        moved += batch.len();
+        if batch.len() < DEDUP_BATCH {
+            break;
+        }
+    }
+
+    conn.execute("DELETE FROM attachment_blobs WHERE ref_count <= 0", [])
+        .map_err(|e| format!("Failed to drop unreferenced attachment data: {}", e))?;
+    Ok(moved)
+}
+
+/// How much storing attachment data once saves
+pub fn get_storage_savings(conn: &Connection) -> Result<StorageSavings, String> {
+    let (attachments, inline_attachments, inline_bytes): (i64, i64, i64) = conn
+        .query_row(
+            "SELECT COUNT(*),
+                    COUNT(blob_hash IS NULL OR NULL),
+                    COALESCE(SUM(CASE WHEN blob_hash IS NULL
+                                      THEN length(CAST(data AS BLOB)) END), 0)
+             FROM task_attachments",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|e| format!("Failed to count attachments: {}", e))?;
+    let (blobs, blob_bytes, referenced_blob_bytes): (i64, i64, i64) = conn
+        .query_row(
+            "SELECT COUNT(*), COALESCE(SUM(size), 0), COALESCE(SUM(size * ref_count), 0)
+             FROM attachment_blobs",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|e| format!("Failed to count attachment data: {}", e))?;
+
+    let referenced_bytes = inline_bytes + referenced_blob_bytes;
+    let stored_bytes = inline_bytes + blob_bytes;
+    Ok(StorageSavings {
+        attachments,
+        inline_attachments,
+        blobs,
+        referenced_bytes,
+        stored_bytes,
+        saved_bytes: referenced_bytes - stored_bytes,
+    })
+}
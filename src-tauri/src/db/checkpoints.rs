@@ -0,0 +1,212 @@
+// src-tauri/src/db/checkpoints.rs
+//! Checkpoints of running tasks
+//!
+//! A task's session ID is otherwise only stored when the task completes, so a
+//! checkpoint is what lets a task cut off by a sidecar crash or an app restart
+//! continue its session instead of starting over.
+//!
+//! Saved checkpoints are taken by the user instead, any number per task, to
+//! come back to with `restore_checkpoint`.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+/// Where a running task had got to
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Checkpoint {
+    pub task_id: String,
+    pub session_id: Option<String>,
+    /// Messages stored for the task when the checkpoint was taken
+    pub message_count: i64,
+    /// Files the agent wrote or edited, in the order first touched
+    pub files_touched: Vec<String>,
+    pub updated_at: String,
+}
+
+/// Store a task's checkpoint, replacing the previous one
+pub fn save_checkpoint(conn: &Connection, checkpoint: &Checkpoint) -> Result<(), String> {
+    let files = serde_json::to_string(&checkpoint.files_touched)
+        .map_err(|e| format!("Failed to serialize touched files: {}", e))?;
+    super::with_retry(|| {
+        conn.execute(
+            "INSERT OR REPLACE INTO task_checkpoints
+             (task_id, session_id, message_count, files_touched, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                checkpoint.task_id,
+                checkpoint.session_id,
+                checkpoint.message_count,
+                files,
+                checkpoint.updated_at,
+            ],
+        )
+    })
+    .map_err(|e| format!("Failed to save checkpoint: {}", e))?;
+    Ok(())
+}
+
+/// Get a task's checkpoint
+pub fn get_checkpoint(conn: &Connection, task_id: &str) -> Result<Option<Checkpoint>, String> {
+    conn.query_row(
+        "SELECT session_id, message_count, files_touched, updated_at
+         FROM task_checkpoints WHERE task_id = ?1",
+        [task_id],
+        |row| {
+            let files: String = row.get(2)?;
+            Ok(Checkpoint {
+                task_id: task_id.to_string(),
+                session_id: row.get(0)?,
+                message_count: row.get(1)?,
+                files_touched: serde_json::from_str(&files).unwrap_or_default(),
+                updated_at: row.get(3)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get checkpoint: {}", e))
+}
+
+/// Delete a task's checkpoint
+pub fn delete_checkpoint(conn: &Connection, task_id: &str) -> Result<(), String> {
+    super::with_retry(|| conn.execute("DELETE FROM task_checkpoints WHERE task_id = ?1", [task_id]))
+        .map_err(|e| format!("Failed to delete checkpoint: {}", e))?;
+    Ok(())
+}
+
+/// A point in a task's conversation the user saved to come back to
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedCheckpoint {
+    pub id: String,
+    pub task_id: String,
+    pub label: String,
+    pub session_id: Option<String>,
+    /// Messages the task had when the checkpoint was saved
+    pub message_count: i64,
+    pub created_at: String,
+}
+
+const SAVED_COLUMNS: &str = "id, task_id, label, session_id, message_count, created_at";
+
+fn row_to_saved(row: &rusqlite::Row) -> rusqlite::Result<SavedCheckpoint> {
+    Ok(SavedCheckpoint {
+        id: row.get(0)?,
+        task_id: row.get(1)?,
+        label: row.get(2)?,
+        session_id: row.get(3)?,
+        message_count: row.get(4)?,
+        created_at: row.get(5)?,
+    })
+}
+
+/// Save a checkpoint of a task at its current message count
+pub fn add_saved_checkpoint(
+    conn: &Connection,
+    task_id: &str,
+    label: &str,
+    session_id: Option<&str>,
+    message_count: i64,
+) -> Result<SavedCheckpoint, String> {
+    let label = label.trim();
+    if label.is_empty() {
+        return Err("Checkpoint label cannot be empty".to_string());
+    }
+    let checkpoint = SavedCheckpoint {
+        id: format!("checkpoint_{}", uuid::Uuid::new_v4()),
+        task_id: task_id.to_string(),
+        label: label.to_string(),
+        session_id: session_id.map(str::to_string),
+        message_count,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    super::with_retry(|| {
+        conn.execute(
+            "INSERT INTO saved_checkpoints
+             (id, task_id, label, session_id, message_count, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                checkpoint.id,
+                checkpoint.task_id,
+                checkpoint.label,
+                checkpoint.session_id,
+                checkpoint.message_count,
+                checkpoint.created_at,
+            ],
+        )
+    })
+    .map_err(|e| format!("Failed to save checkpoint: {}", e))?;
+    Ok(checkpoint)
+}
+
+/// Get a saved checkpoint
+pub fn get_saved_checkpoint(
+    conn: &Connection,
+    id: &str,
+) -> Result<Option<SavedCheckpoint>, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM saved_checkpoints WHERE id = ?1", SAVED_COLUMNS),
+        [id],
+        row_to_saved,
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get checkpoint: {}", e))
+}
+
+/// A task's saved checkpoints, earliest in the conversation first
+pub fn list_saved_checkpoints(
+    conn: &Connection,
+    task_id: &str,
+) -> Result<Vec<SavedCheckpoint>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM saved_checkpoints WHERE task_id = ?1
+             ORDER BY message_count ASC, created_at ASC",
+            SAVED_COLUMNS
+        ))
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let checkpoints = stmt
+        .query_map([task_id], row_to_saved)
+        .map_err(|e| format!("Failed to list checkpoints: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(checkpoints)
+}
+
+/// Delete a saved checkpoint
+pub fn delete_saved_checkpoint(conn: &Connection, id: &str) -> Result<(), String> {
+    let deleted = super::with_retry(|| {
+        conn.execute("DELETE FROM saved_checkpoints WHERE id = ?1", [id])
+    })
+    .map_err(|e| format!("Failed to delete checkpoint: {}", e))?;
+    if deleted == 0 {
+        return Err(format!("Checkpoint not found: {}", id));
+    }
+    Ok(())
+}
+
+/// Delete a task's saved checkpoints past `message_count` messages
+pub fn delete_saved_checkpoints_after(
+    conn: &Connection,
+    task_id: &str,
+    message_count: i64,
+) -> Result<(), String> {
+    super::with_retry(|| {
+        conn.execute(
+            "DELETE FROM saved_checkpoints WHERE task_id = ?1 AND message_count > ?2",
+            params![task_id, message_count],
+        )
+    })
+    .map_err(|e| format!("Failed to delete later checkpoints: {}", e))?;
+    Ok(())
+}
+
+/// Number of messages stored for a task
+pub fn count_messages(conn: &Connection, task_id: &str) -> Result<i64, String> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM task_messages WHERE task_id = ?1",
+        [task_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| format!("Failed to count messages: {}", e))
+}
@@ -0,0 +1,109 @@
+// src-tauri/src/db/citations.rs
+//! Web sources of assistant messages
+//!
+//! Sources are keyed by the OpenCode part ID of the assistant message they
+//! were in context for, since they are recorded from sidecar events before
+//! the UI stores the message; reads join on `task_messages.origin_id`.
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// A web page the agent had in context when it wrote a message
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageSource {
+    pub url: String,
+    pub title: Option<String>,
+    /// "attachment", "webfetch" or "websearch"
+    pub kind: String,
+    /// Order of the context chunk the URL came in within its task
+    pub chunk: i64,
+}
+
+/// Sources of a task's messages by message ID
+pub type TaskSources = HashMap<String, Vec<MessageSource>>;
+
+/// Store the sources of an assistant message; URLs it already has are kept
+pub fn save_sources(
+    conn: &Connection,
+    task_id: &str,
+    origin_id: &str,
+    sources: &[MessageSource],
+) -> Result<(), String> {
+    for source in sources {
+        super::with_retry(|| {
+            conn.execute(
+                "INSERT OR IGNORE INTO message_sources
+                 (task_id, origin_id, url, title, kind, chunk)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![task_id, origin_id, source.url, source.title, source.kind, source.chunk],
+            )
+        })
+        .map_err(|e| format!("Failed to save message source: {}", e))?;
+    }
+    Ok(())
+}
+
+fn row_to_source(row: &rusqlite::Row) -> rusqlite::Result<MessageSource> {
+    Ok(MessageSource {
+        url: row.get(0)?,
+        title: row.get(1)?,
+        kind: row.get(2)?,
+        chunk: row.get(3)?,
+    })
+}
+
+/// Sources of a message, in the order they entered the context
+pub fn get_message_sources(
+    conn: &Connection,
+    message_id: &str,
+) -> Result<Vec<MessageSource>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT s.url, s.title, s.kind, s.chunk
+             FROM message_sources s
+             JOIN task_messages m ON m.task_id = s.task_id AND m.origin_id = s.origin_id
+             WHERE m.id = ?1
+             ORDER BY s.chunk ASC, s.rowid ASC",
+        )
+        .map_err(|e| format!("Failed to prepare sources query: {}", e))?;
+    let sources = stmt
+        .query_map([message_id], row_to_source)
+        .map_err(|e| format!("Failed to query message sources: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(sources)
+}
+
+/// Sources of every message of a task that has some
+pub fn get_task_sources(conn: &Connection, task_id: &str) -> Result<TaskSources, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT m.id, s.url, s.title, s.kind, s.chunk
+             FROM message_sources s
+             JOIN task_messages m ON m.task_id = s.task_id AND m.origin_id = s.origin_id
+             WHERE s.task_id = ?1
+             ORDER BY s.chunk ASC, s.rowid ASC",
+        )
+        .map_err(|e| format!("Failed to prepare sources query: {}", e))?;
+    let rows = stmt
+        .query_map([task_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                MessageSource {
+                    url: row.get(1)?,
+                    title: row.get(2)?,
+                    kind: row.get(3)?,
+                    chunk: row.get(4)?,
+                },
+            ))
+        })
+        .map_err(|e| format!("Failed to query task sources: {}", e))?;
+
+    let mut sources = TaskSources::new();
+    for (message_id, source) in rows.filter_map(|r| r.ok()) {
+        sources.entry(message_id).or_default().push(source);
+    }
+    Ok(sources)
+}
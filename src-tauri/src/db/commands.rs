@@ -0,0 +1,204 @@
+// src-tauri/src/db/commands.rs
+//! Slash command repository
+//!
+//! User-defined commands invoked as `/name args` at the start of a prompt.
+//! `start_task` expands them with `slash_commands::expand`, so every window
+//! and the quick-entry UI share the same definitions.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// Stored slash command
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlashCommand {
+    pub id: String,
+    /// Invoked as `/name`
+    pub name: String,
+    pub description: Option<String>,
+    /// Prompt template; `$ARGUMENTS` is replaced with the text after the name
+    pub template: String,
+    /// Model used instead of the active provider's model
+    pub model_id: Option<String>,
+    /// OpenCode agent used instead of the default agent
+    pub agent: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Fields of a slash command set by the user
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlashCommandInput {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub template: String,
+    #[serde(default)]
+    pub model_id: Option<String>,
+    #[serde(default)]
+    pub agent: Option<String>,
+}
+
+fn row_to_command(row: &rusqlite::Row) -> rusqlite::Result<SlashCommand> {
+    Ok(SlashCommand {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        template: row.get(3)?,
+        model_id: row.get(4)?,
+        agent: row.get(5)?,
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+    })
+}
+
+/// Validate and normalize user input; a leading `/` on the name is dropped
+fn normalize(input: &SlashCommandInput) -> Result<SlashCommandInput, String> {
+    let name = input.name.trim().trim_start_matches('/').to_lowercase();
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Invalid command name '{}': use letters, digits, '-' and '_'",
+            input.name
+        ));
+    }
+    if input.template.trim().is_empty() {
+        return Err("Command template cannot be empty".to_string());
+    }
+    let non_empty = |v: &Option<String>| {
+        v.as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+    Ok(SlashCommandInput {
+        name,
+        description: non_empty(&input.description),
+        template: input.template.clone(),
+        model_id: non_empty(&input.model_id),
+        agent: non_empty(&input.agent),
+    })
+}
+
+/// All commands, by name
+pub fn list_commands(conn: &Connection) -> Result<Vec<SlashCommand>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, description, template, model_id, agent, created_at, updated_at
+             FROM commands ORDER BY name ASC",
+        )
+        .map_err(|e| format!("Failed to prepare commands query: {}", e))?;
+    let commands = stmt
+        .query_map([], row_to_command)
+        .map_err(|e| format!("Failed to query commands: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(commands)
+}
+
+/// Get a command by name (without the leading `/`)
+pub fn get_command_by_name(conn: &Connection, name: &str) -> Result<Option<SlashCommand>, String> {
+    conn.query_row(
+        "SELECT id, name, description, template, model_id, agent, created_at, updated_at
+         FROM commands WHERE name = ?1",
+        [name.to_lowercase()],
+        row_to_command,
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get command: {}", e))
+}
+
+fn get_command(conn: &Connection, id: &str) -> Result<Option<SlashCommand>, String> {
+    conn.query_row(
+        "SELECT id, name, description, template, model_id, agent, created_at, updated_at
+         FROM commands WHERE id = ?1",
+        [id],
+        row_to_command,
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get command: {}", e))
+}
+
+/// Create a command
+pub fn create_command(conn: &Connection, input: &SlashCommandInput) -> Result<SlashCommand, String> {
+    let input = normalize(input)?;
+    if get_command_by_name(conn, &input.name)?.is_some() {
+        return Err(format!("A command named /{} already exists", input.name));
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let command = SlashCommand {
+        id: format!("cmd_{}", uuid::Uuid::new_v4()),
+        name: input.name,
+        description: input.description,
+        template: input.template,
+        model_id: input.model_id,
+        agent: input.agent,
+        created_at: now.clone(),
+        updated_at: now,
+    };
+    conn.execute(
+        "INSERT INTO commands
+         (id, name, description, template, model_id, agent, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            command.id,
+            command.name,
+            command.description,
+            command.template,
+            command.model_id,
+            command.agent,
+            command.created_at,
+            command.updated_at,
+        ],
+    )
+    .map_err(|e| format!("Failed to create command: {}", e))?;
+    Ok(command)
+}
+
+/// Update a command
+pub fn update_command(
+    conn: &Connection,
+    id: &str,
+    input: &SlashCommandInput,
+) -> Result<SlashCommand, String> {
+    let input = normalize(input)?;
+    if let Some(existing) = get_command_by_name(conn, &input.name)? {
+        if existing.id != id {
+            return Err(format!("A command named /{} already exists", input.name));
+        }
+    }
+
+    let updated = conn
+        .execute(
+            "UPDATE commands
+             SET name = ?1, description = ?2, template = ?3, model_id = ?4, agent = ?5,
+                 updated_at = ?6
+             WHERE id = ?7",
+            params![
+                input.name,
+                input.description,
+                input.template,
+                input.model_id,
+                input.agent,
+                chrono::Utc::now().to_rfc3339(),
+                id,
+            ],
+        )
+        .map_err(|e| format!("Failed to update command: {}", e))?;
+    if updated == 0 {
+        return Err(format!("Command not found: {}", id));
+    }
+    get_command(conn, id)?.ok_or_else(|| format!("Command not found: {}", id))
+}
+
+/// Delete a command
+pub fn delete_command(conn: &Connection, id: &str) -> Result<(), String> {
+    conn.execute("DELETE FROM commands WHERE id = ?1", [id])
+        .map_err(|e| format!("Failed to delete command: {}", e))?;
+    Ok(())
+}
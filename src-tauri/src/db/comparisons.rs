@@ -0,0 +1,228 @@
+// src-tauri/src/db/comparisons.rs
+//! Model comparison runs
+//!
+//! A comparison starts the same prompt as one task per model. The tasks are
+//! ordinary tasks linked by a shared `comparison_id`; results, timing, and
+//! cost are read back from the tasks, their messages, and `task_usage`.
+//!
+//! Any two tasks can also be compared after the fact with `diff_tasks`, e.g. a
+//! rerun with a reworded prompt against the original.
+
+use crate::diff::{self, DiffHunk};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+/// One model's run within a comparison
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparisonEntry {
+    pub task_id: String,
+    pub model_id: String,
+    pub status: String,
+    pub started_at: Option<String>,
+    pub completed_at: Option<String>,
+    /// Wall-clock time from start to completion, once the task has finished
+    pub duration_ms: Option<i64>,
+    /// Cost in USD
+    pub cost: f64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    /// Last assistant message
+    pub result: Option<String>,
+}
+
+/// Tasks of a comparison in the order the models were given
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Comparison {
+    pub id: String,
+    pub prompt: String,
+    pub entries: Vec<ComparisonEntry>,
+}
+
+/// Link a task to a comparison
+pub fn add_task(
+    conn: &Connection,
+    comparison_id: &str,
+    task_id: &str,
+    model_id: &str,
+    position: usize,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO comparison_tasks (task_id, comparison_id, model_id, position)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![task_id, comparison_id, model_id, position as i64],
+    )
+    .map_err(|e| format!("Failed to link comparison task: {}", e))?;
+    Ok(())
+}
+
+fn duration_ms(started_at: Option<&str>, completed_at: Option<&str>) -> Option<i64> {
+    let started = chrono::DateTime::parse_from_rfc3339(started_at?).ok()?;
+    let completed = chrono::DateTime::parse_from_rfc3339(completed_at?).ok()?;
+    Some((completed - started).num_milliseconds())
+}
+
+/// Get a comparison, or `None` if no tasks are linked to it
+pub fn get_comparison(conn: &Connection, comparison_id: &str) -> Result<Option<Comparison>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT c.task_id, c.model_id, t.status, t.prompt, t.started_at, t.completed_at,
+                    COALESCE(SUM(u.cost), 0), COALESCE(SUM(u.input_tokens), 0),
+                    COALESCE(SUM(u.output_tokens), 0),
+                    (SELECT m.content FROM task_messages m
+                     WHERE m.task_id = c.task_id AND m.type = 'assistant' AND m.archived_at IS NULL
+                     ORDER BY m.sort_order DESC LIMIT 1)
+             FROM comparison_tasks c
+             JOIN tasks t ON t.id = c.task_id
+             LEFT JOIN task_usage u ON u.task_id = c.task_id
+             WHERE c.comparison_id = ?1
+             GROUP BY c.task_id
+             ORDER BY c.position",
+        )
+        .map_err(|e| format!("Failed to prepare comparison query: {}", e))?;
+
+    let rows: Vec<(String, ComparisonEntry)> = stmt
+        .query_map([comparison_id], |row| {
+            let started_at: Option<String> = row.get(4)?;
+            let completed_at: Option<String> = row.get(5)?;
+            Ok((
+                row.get(3)?,
+                ComparisonEntry {
+                    task_id: row.get(0)?,
+                    model_id: row.get(1)?,
+                    status: row.get(2)?,
+                    duration_ms: duration_ms(started_at.as_deref(), completed_at.as_deref()),
+                    started_at,
+                    completed_at,
+                    cost: row.get(6)?,
+                    input_tokens: row.get(7)?,
+                    output_tokens: row.get(8)?,
+                    result: row.get(9)?,
+                },
+            ))
+        })
+        .map_err(|e| format!("Failed to query comparison: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let Some(prompt) = rows.first().map(|(prompt, _)| prompt.clone()) else {
+        return Ok(None);
+    };
+    Ok(Some(Comparison {
+        id: comparison_id.to_string(),
+        prompt,
+        entries: rows.into_iter().map(|(_, entry)| entry).collect(),
+    }))
+}
+
+/// A task's prompt, models, timing, cost, and final output
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskRun {
+    pub task_id: String,
+    pub prompt: String,
+    pub status: String,
+    /// Models that reported usage, in the order they were first used
+    pub models: Vec<String>,
+    pub started_at: Option<String>,
+    pub completed_at: Option<String>,
+    pub duration_ms: Option<i64>,
+    /// Cost in USD
+    pub cost: f64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    /// Last assistant message
+    pub result: Option<String>,
+}
+
+/// Two runs side by side, with what changed from the first to the second
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskDiff {
+    pub a: TaskRun,
+    pub b: TaskRun,
+    /// Line diff from a's prompt to b's; empty when the prompts match
+    pub prompt_hunks: Vec<DiffHunk>,
+    pub same_models: bool,
+    /// b's duration minus a's, when both have finished
+    pub duration_delta_ms: Option<i64>,
+    /// b's cost minus a's, in USD
+    pub cost_delta: f64,
+    /// Line diff from a's result to b's
+    pub result_hunks: Vec<DiffHunk>,
+}
+
+fn get_task_run(conn: &Connection, task_id: &str) -> Result<Option<TaskRun>, String> {
+    let run = conn
+        .query_row(
+            "SELECT t.id, t.prompt, t.status, t.started_at, t.completed_at,
+                    (SELECT COALESCE(SUM(u.cost), 0) FROM task_usage u WHERE u.task_id = t.id),
+                    (SELECT COALESCE(SUM(u.input_tokens), 0) FROM task_usage u
+                     WHERE u.task_id = t.id),
+                    (SELECT COALESCE(SUM(u.output_tokens), 0) FROM task_usage u
+                     WHERE u.task_id = t.id),
+                    (SELECT m.content FROM task_messages m
+                     WHERE m.task_id = t.id AND m.type = 'assistant' AND m.archived_at IS NULL
+                     ORDER BY m.sort_order DESC LIMIT 1)
+             FROM tasks t
+             WHERE t.id = ?1",
+            [task_id],
+            |row| {
+                let started_at: Option<String> = row.get(3)?;
+                let completed_at: Option<String> = row.get(4)?;
+                Ok(TaskRun {
+                    task_id: row.get(0)?,
+                    prompt: row.get(1)?,
+                    status: row.get(2)?,
+                    models: Vec::new(),
+                    duration_ms: duration_ms(started_at.as_deref(), completed_at.as_deref()),
+                    started_at,
+                    completed_at,
+                    cost: row.get(5)?,
+                    input_tokens: row.get(6)?,
+                    output_tokens: row.get(7)?,
+                    result: row.get(8)?,
+                })
+            },
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get task run: {}", e))?;
+    let Some(mut run) = run else {
+        return Ok(None);
+    };
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT model_id FROM task_usage
+             WHERE task_id = ?1 AND model_id IS NOT NULL
+             GROUP BY model_id
+             ORDER BY MIN(id)",
+        )
+        .map_err(|e| format!("Failed to prepare task models query: {}", e))?;
+    run.models = stmt
+        .query_map([task_id], |row| row.get(0))
+        .map_err(|e| format!("Failed to query task models: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(Some(run))
+}
+
+/// Compare two tasks, from `task_a` to `task_b`
+pub fn diff_tasks(conn: &Connection, task_a: &str, task_b: &str) -> Result<TaskDiff, String> {
+    let a = get_task_run(conn, task_a)?.ok_or_else(|| format!("Task not found: {}", task_a))?;
+    let b = get_task_run(conn, task_b)?.ok_or_else(|| format!("Task not found: {}", task_b))?;
+
+    Ok(TaskDiff {
+        prompt_hunks: diff::hunks(&a.prompt, &b.prompt),
+        same_models: a.models == b.models,
+        duration_delta_ms: a.duration_ms.zip(b.duration_ms).map(|(a, b)| b - a),
+        cost_delta: b.cost - a.cost,
+        result_hunks: diff::hunks(
+            a.result.as_deref().unwrap_or(""),
+            b.result.as_deref().unwrap_or(""),
+        ),
+        a,
+        b,
+    })
+}
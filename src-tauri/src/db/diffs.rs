@@ -0,0 +1,61 @@
+// src-tauri/src/db/diffs.rs
+//! Structured diffs stored alongside file-editing tool messages
+
+use crate::diff::{self, MessageDiff};
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// Compute and store the diff for a tool message, if its tool edits files
+pub fn save_for_message(
+    conn: &Connection,
+    message_id: &str,
+    tool_name: &str,
+    tool_input: &serde_json::Value,
+) -> Result<Option<MessageDiff>, String> {
+    let Some(diff) = diff::from_tool(tool_name, tool_input) else {
+        return Ok(None);
+    };
+    let json = serde_json::to_string(&diff)
+        .map_err(|e| format!("Failed to serialize diff: {}", e))?;
+    conn.execute(
+        "INSERT OR REPLACE INTO message_diffs (message_id, diff) VALUES (?1, ?2)",
+        params![message_id, json],
+    )
+    .map_err(|e| format!("Failed to save diff: {}", e))?;
+    Ok(Some(diff))
+}
+
+/// Get the diff for a message
+///
+/// Messages stored before diffs were computed (or through imports and sync) are
+/// diffed from their tool input on first access.
+pub fn get_diff(conn: &Connection, message_id: &str) -> Result<Option<MessageDiff>, String> {
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT diff FROM message_diffs WHERE message_id = ?1",
+            [message_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get diff: {}", e))?;
+    if let Some(json) = stored {
+        return serde_json::from_str(&json)
+            .map(Some)
+            .map_err(|e| format!("Failed to parse diff: {}", e));
+    }
+
+    let tool: Option<(Option<String>, Option<String>)> = conn
+        .query_row(
+            "SELECT tool_name, tool_input FROM task_messages WHERE id = ?1",
+            [message_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get message: {}", e))?;
+    let Some((Some(tool_name), Some(tool_input))) = tool else {
+        return Ok(None);
+    };
+    let Ok(tool_input) = serde_json::from_str(&tool_input) else {
+        return Ok(None);
+    };
+    save_for_message(conn, message_id, &tool_name, &tool_input)
+}
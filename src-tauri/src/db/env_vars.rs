@@ -0,0 +1,60 @@
+// src-tauri/src/db/env_vars.rs
+//! Environment variable repository
+//!
+//! Variables belong to a workspace or a task, keyed by its ID. Only
+//! non-secret values are stored here; secret values live in the keychain
+//! (see `task_env`), so their rows just record the name.
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// Environment variable of a workspace or task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvVar {
+    pub name: String,
+    /// Value; never returned for secrets, and left unset to keep a stored secret
+    #[serde(default)]
+    pub value: Option<String>,
+    /// Stored in the keychain instead of the database
+    #[serde(default)]
+    pub secret: bool,
+}
+
+/// Variables of a workspace or task, by name
+pub fn list(conn: &Connection, owner_id: &str) -> Result<Vec<EnvVar>, String> {
+    let mut stmt = conn
+        .prepare("SELECT name, value, secret FROM env_vars WHERE owner_id = ?1 ORDER BY name ASC")
+        .map_err(|e| format!("Failed to prepare env vars query: {}", e))?;
+    let vars = stmt
+        .query_map([owner_id], |row| {
+            Ok(EnvVar {
+                name: row.get(0)?,
+                value: row.get(1)?,
+                secret: row.get::<_, i64>(2)? != 0,
+            })
+        })
+        .map_err(|e| format!("Failed to query env vars: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(vars)
+}
+
+/// Replace the variables of a workspace or task; secret values are not stored
+pub fn replace(conn: &mut Connection, owner_id: &str, vars: &[EnvVar]) -> Result<(), String> {
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Failed to begin env var transaction: {}", e))?;
+    tx.execute("DELETE FROM env_vars WHERE owner_id = ?1", [owner_id])
+        .map_err(|e| format!("Failed to clear env vars: {}", e))?;
+    for var in vars {
+        let value = if var.secret { None } else { var.value.as_deref() };
+        tx.execute(
+            "INSERT INTO env_vars (owner_id, name, value, secret) VALUES (?1, ?2, ?3, ?4)",
+            params![owner_id, var.name, value, var.secret as i64],
+        )
+        .map_err(|e| format!("Failed to save env var {}: {}", var.name, e))?;
+    }
+    tx.commit()
+        .map_err(|e| format!("Failed to commit env vars: {}", e))
+}
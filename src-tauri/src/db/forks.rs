@@ -0,0 +1,81 @@
+// src-tauri/src/db/forks.rs
+//! Task fork links
+//!
+//! `fork_task` copies a task's transcript up to a message into a new task with
+//! its own session. Each fork records the task and message it branched from,
+//! so the UI can navigate between a thread and its alternatives.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+/// Where a forked task branched from
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskFork {
+    pub task_id: String,
+    /// Unset once the parent task is deleted
+    pub parent_task_id: Option<String>,
+    pub parent_message_id: String,
+    pub created_at: String,
+}
+
+/// A task's parent (if it is a fork) and the forks made from it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskForks {
+    pub parent: Option<TaskFork>,
+    pub children: Vec<TaskFork>,
+}
+
+fn row_to_fork(row: &rusqlite::Row) -> rusqlite::Result<TaskFork> {
+    Ok(TaskFork {
+        task_id: row.get(0)?,
+        parent_task_id: row.get(1)?,
+        parent_message_id: row.get(2)?,
+        created_at: row.get(3)?,
+    })
+}
+
+/// Record that `task_id` was forked from `parent_task_id` at `parent_message_id`
+pub fn record_fork(
+    conn: &Connection,
+    task_id: &str,
+    parent_task_id: &str,
+    parent_message_id: &str,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO task_forks (task_id, parent_task_id, parent_message_id, created_at)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![task_id, parent_task_id, parent_message_id, chrono::Utc::now().to_rfc3339()],
+    )
+    .map_err(|e| format!("Failed to record fork: {}", e))?;
+    Ok(())
+}
+
+/// Get the fork links of a task
+pub fn get_forks(conn: &Connection, task_id: &str) -> Result<TaskForks, String> {
+    let parent = conn
+        .query_row(
+            "SELECT task_id, parent_task_id, parent_message_id, created_at
+             FROM task_forks WHERE task_id = ?1",
+            [task_id],
+            row_to_fork,
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get task fork: {}", e))?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT task_id, parent_task_id, parent_message_id, created_at
+             FROM task_forks WHERE parent_task_id = ?1
+             ORDER BY created_at ASC",
+        )
+        .map_err(|e| format!("Failed to prepare fork query: {}", e))?;
+    let children = stmt
+        .query_map([task_id], row_to_fork)
+        .map_err(|e| format!("Failed to query forks: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(TaskForks { parent, children })
+}
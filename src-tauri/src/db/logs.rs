@@ -0,0 +1,159 @@
+// src-tauri/src/db/logs.rs
+//! Log record repository
+
+use rusqlite::{params, params_from_iter, Connection, TransactionBehavior};
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of log records kept in the database
+const MAX_LOG_RECORDS: i64 = 20_000;
+
+/// Default number of records returned by a query
+const DEFAULT_QUERY_LIMIT: i64 = 500;
+
+/// Levels ordered from most to least severe
+const LEVELS: &[&str] = &["error", "warn", "info", "debug", "trace"];
+
+/// Stored log record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogRecord {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<String>,
+    pub timestamp: String,
+}
+
+/// Filter for querying log records
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogFilter {
+    /// Minimum severity (e.g. "warn" returns warn and error)
+    pub level: Option<String>,
+    pub task_id: Option<String>,
+    pub target: Option<String>,
+    /// Substring match on the message
+    pub search: Option<String>,
+    /// RFC 3339 lower bound (inclusive)
+    pub since: Option<String>,
+    /// RFC 3339 upper bound (inclusive)
+    pub until: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// Insert a batch of log records in a single transaction
+pub fn insert_logs(conn: &mut Connection, records: &[LogRecord]) -> Result<(), String> {
+    // Take the write lock up front so the busy timeout applies, rather than failing at commit
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| format!("Failed to begin log transaction: {}", e))?;
+
+    for record in records {
+        tx.execute(
+            "INSERT INTO logs (level, target, message, task_id, timestamp)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                record.level,
+                record.target,
+                record.message,
+                record.task_id,
+                record.timestamp,
+            ],
+        )
+        .map_err(|e| format!("Failed to insert log record: {}", e))?;
+    }
+
+    tx.commit()
+        .map_err(|e| format!("Failed to commit log records: {}", e))
+}
+
+/// Delete the oldest records beyond the retention cap
+pub fn prune_logs(conn: &Connection) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM logs WHERE id <= (SELECT MAX(id) FROM logs) - ?1",
+        [MAX_LOG_RECORDS],
+    )
+    .map_err(|e| format!("Failed to prune logs: {}", e))?;
+    Ok(())
+}
+
+/// Query log records, newest first
+pub fn query_logs(conn: &Connection, filter: &LogFilter) -> Result<Vec<LogRecord>, String> {
+    let mut clauses: Vec<String> = Vec::new();
+    let mut values: Vec<String> = Vec::new();
+
+    if let Some(level) = &filter.level {
+        let level = level.to_ascii_lowercase();
+        let position = LEVELS
+            .iter()
+            .position(|l| *l == level)
+            .ok_or_else(|| format!("Invalid log level: {}", level))?;
+        let placeholders: Vec<String> = LEVELS[..=position]
+            .iter()
+            .map(|l| {
+                values.push(l.to_string());
+                format!("?{}", values.len())
+            })
+            .collect();
+        clauses.push(format!("level IN ({})", placeholders.join(", ")));
+    }
+    if let Some(task_id) = &filter.task_id {
+        values.push(task_id.clone());
+        clauses.push(format!("task_id = ?{}", values.len()));
+    }
+    if let Some(target) = &filter.target {
+        values.push(format!("{}%", target));
+        clauses.push(format!("target LIKE ?{}", values.len()));
+    }
+    if let Some(search) = &filter.search {
+        values.push(format!("%{}%", search));
+        clauses.push(format!("message LIKE ?{}", values.len()));
+    }
+    if let Some(since) = &filter.since {
+        values.push(since.clone());
+        clauses.push(format!("timestamp >= ?{}", values.len()));
+    }
+    if let Some(until) = &filter.until {
+        values.push(until.clone());
+        clauses.push(format!("timestamp <= ?{}", values.len()));
+    }
+
+    let where_clause = if clauses.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", clauses.join(" AND "))
+    };
+    let limit = filter.limit.unwrap_or(DEFAULT_QUERY_LIMIT).clamp(1, MAX_LOG_RECORDS);
+
+    let sql = format!(
+        "SELECT id, level, target, message, task_id, timestamp
+         FROM logs {}
+         ORDER BY id DESC
+         LIMIT {}",
+        where_clause, limit
+    );
+
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|e| format!("Failed to prepare logs query: {}", e))?;
+
+    let records = stmt
+        .query_map(params_from_iter(values.iter()), |row| {
+            Ok(LogRecord {
+                id: row.get(0)?,
+                level: row.get(1)?,
+                target: row.get(2)?,
+                message: row.get(3)?,
+                task_id: row.get(4)?,
+                timestamp: row.get(5)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query logs: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(records)
+}
@@ -4,10 +4,19 @@
 use rusqlite::Connection;
 
 /// Current schema version supported by this app
-const CURRENT_VERSION: i32 = 2;
+pub const CURRENT_VERSION: i32 = 61;
+
+/// What `run_migrations` found the database to be
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaStatus {
+    /// At this app's version, migrated if it was older
+    Current,
+    /// Written by a newer version of the app (the stored version); left untouched
+    Newer(i32),
+}
 
 /// Get the stored schema version from the database
-fn get_stored_version(conn: &Connection) -> i32 {
+pub fn get_stored_version(conn: &Connection) -> i32 {
     // Check if schema_meta table exists
     let table_exists: bool = conn
         .query_row(
@@ -44,7 +53,7 @@ fn set_stored_version(conn: &Connection, version: i32) -> Result<(), String> {
 
 /// Migration v1: Initial schema
 fn migrate_v1(conn: &Connection) -> Result<(), String> {
-    println!("[Migrations] Running migration v1 (initial schema)");
+    tracing::info!("[Migrations] Running migration v1 (initial schema)");
 
     // Create schema_meta table
     conn.execute(
@@ -162,13 +171,13 @@ fn migrate_v1(conn: &Connection) -> Result<(), String> {
         .map_err(|e| format!("Failed to insert provider_meta default: {}", e))?;
 
     set_stored_version(conn, 1)?;
-    println!("[Migrations] Migration v1 complete");
+    tracing::info!("[Migrations] Migration v1 complete");
     Ok(())
 }
 
 /// Migration v2: Add Azure Foundry configuration column
 fn migrate_v2(conn: &Connection) -> Result<(), String> {
-    println!("[Migrations] Running migration v2 (Azure Foundry config)");
+    tracing::info!("[Migrations] Running migration v2 (Azure Foundry config)");
 
     conn.execute(
         "ALTER TABLE app_settings ADD COLUMN azure_foundry_config TEXT",
@@ -177,40 +186,1540 @@ fn migrate_v2(conn: &Connection) -> Result<(), String> {
     .map_err(|e| format!("Failed to add azure_foundry_config column: {}", e))?;
 
     set_stored_version(conn, 2)?;
-    println!("[Migrations] Migration v2 complete");
+    tracing::info!("[Migrations] Migration v2 complete");
     Ok(())
 }
 
-/// Run all pending migrations
-pub fn run_migrations(conn: &Connection) -> Result<(), String> {
-    let stored_version = get_stored_version(conn);
-    println!(
-        "[Migrations] Stored version: {}, App version: {}",
-        stored_version, CURRENT_VERSION
-    );
+/// Migration v3: Add runtime log level setting
+fn migrate_v3(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v3 (log level setting)");
 
-    // Block if database is from a newer app version
-    if stored_version > CURRENT_VERSION {
-        return Err(format!(
-            "Database schema version {} is newer than app version {}. Please upgrade the app.",
-            stored_version, CURRENT_VERSION
-        ));
-    }
+    conn.execute("ALTER TABLE app_settings ADD COLUMN log_level TEXT", [])
+        .map_err(|e| format!("Failed to add log_level column: {}", e))?;
 
-    // No migrations to run
-    if stored_version == CURRENT_VERSION {
-        println!("[Migrations] Database is up to date");
-        return Ok(());
-    }
+    set_stored_version(conn, 3)?;
+    tracing::info!("[Migrations] Migration v3 complete");
+    Ok(())
+}
 
-    // Run pending migrations
-    if stored_version < 1 {
-        migrate_v1(conn)?;
-    }
-    if stored_version < 2 {
-        migrate_v2(conn)?;
-    }
+/// Migration v4: Add log record store
+fn migrate_v4(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v4 (log store)");
+
+    conn.execute(
+        "CREATE TABLE logs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            level TEXT NOT NULL,
+            target TEXT NOT NULL,
+            message TEXT NOT NULL,
+            task_id TEXT,
+            timestamp TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create logs: {}", e))?;
+
+    conn.execute("CREATE INDEX idx_logs_task_id ON logs(task_id)", [])
+        .map_err(|e| format!("Failed to create logs task index: {}", e))?;
+
+    conn.execute("CREATE INDEX idx_logs_level ON logs(level)", [])
+        .map_err(|e| format!("Failed to create logs level index: {}", e))?;
+
+    set_stored_version(conn, 4)?;
+    tracing::info!("[Migrations] Migration v4 complete");
+    Ok(())
+}
+
+/// Migration v5: Add local API server settings
+fn migrate_v5(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v5 (API server settings)");
+
+    conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN api_server_enabled INTEGER NOT NULL DEFAULT 0",
+        [],
+    )
+    .map_err(|e| format!("Failed to add api_server_enabled column: {}", e))?;
+
+    conn.execute("ALTER TABLE app_settings ADD COLUMN api_server_port INTEGER", [])
+        .map_err(|e| format!("Failed to add api_server_port column: {}", e))?;
+
+    set_stored_version(conn, 5)?;
+    tracing::info!("[Migrations] Migration v5 complete");
+    Ok(())
+}
+
+/// Migration v6: Add WebSocket bridge toggle
+fn migrate_v6(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v6 (WebSocket bridge setting)");
+
+    conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN api_server_websocket_enabled INTEGER NOT NULL DEFAULT 0",
+        [],
+    )
+    .map_err(|e| format!("Failed to add api_server_websocket_enabled column: {}", e))?;
+
+    set_stored_version(conn, 6)?;
+    tracing::info!("[Migrations] Migration v6 complete");
+    Ok(())
+}
+
+/// Migration v7: Add cloud folder sync setting
+fn migrate_v7(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v7 (sync folder setting)");
+
+    conn.execute("ALTER TABLE app_settings ADD COLUMN sync_folder TEXT", [])
+        .map_err(|e| format!("Failed to add sync_folder column: {}", e))?;
+
+    set_stored_version(conn, 7)?;
+    tracing::info!("[Migrations] Migration v7 complete");
+    Ok(())
+}
+
+/// Migration v8: Add end-to-end encrypted sync settings and deletion tombstones
+fn migrate_v8(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v8 (encrypted sync)");
+
+    conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN e2e_sync_enabled INTEGER NOT NULL DEFAULT 0",
+        [],
+    )
+    .map_err(|e| format!("Failed to add e2e_sync_enabled column: {}", e))?;
+
+    conn.execute("ALTER TABLE app_settings ADD COLUMN e2e_sync_relay_url TEXT", [])
+        .map_err(|e| format!("Failed to add e2e_sync_relay_url column: {}", e))?;
+
+    conn.execute(
+        "CREATE TABLE sync_tombstones (
+            task_id TEXT PRIMARY KEY,
+            deleted_at TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create sync_tombstones: {}", e))?;
+
+    set_stored_version(conn, 8)?;
+    tracing::info!("[Migrations] Migration v8 complete");
+    Ok(())
+}
+
+/// Migration v9: Add workspaces and associate tasks with them
+fn migrate_v9(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v9 (workspaces)");
+
+    conn.execute(
+        "CREATE TABLE workspaces (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            path TEXT NOT NULL UNIQUE,
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create workspaces: {}", e))?;
+
+    conn.execute(
+        "ALTER TABLE tasks ADD COLUMN workspace_id TEXT REFERENCES workspaces(id) ON DELETE SET NULL",
+        [],
+    )
+    .map_err(|e| format!("Failed to add workspace_id column: {}", e))?;
+
+    conn.execute(
+        "CREATE INDEX idx_tasks_workspace ON tasks(workspace_id)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create workspace index: {}", e))?;
+
+    set_stored_version(conn, 9)?;
+    tracing::info!("[Migrations] Migration v9 complete");
+    Ok(())
+}
+
+/// Migration v10: Add per-step token usage and budget limits
+fn migrate_v10(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v10 (usage and budgets)");
+
+    conn.execute(
+        "CREATE TABLE task_usage (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task_id TEXT NOT NULL,
+            provider_id TEXT,
+            model_id TEXT,
+            input_tokens INTEGER NOT NULL DEFAULT 0,
+            output_tokens INTEGER NOT NULL DEFAULT 0,
+            reasoning_tokens INTEGER NOT NULL DEFAULT 0,
+            cache_read_tokens INTEGER NOT NULL DEFAULT 0,
+            cache_write_tokens INTEGER NOT NULL DEFAULT 0,
+            cost REAL NOT NULL DEFAULT 0,
+            recorded_at TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create task_usage: {}", e))?;
+
+    conn.execute(
+        "CREATE INDEX idx_task_usage_recorded_at ON task_usage(recorded_at)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create usage index: {}", e))?;
+
+    conn.execute(
+        "CREATE INDEX idx_task_usage_task ON task_usage(task_id)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create usage index: {}", e))?;
+
+    conn.execute("ALTER TABLE app_settings ADD COLUMN budget_limits TEXT", [])
+        .map_err(|e| format!("Failed to add budget_limits column: {}", e))?;
+
+    set_stored_version(conn, 10)?;
+    tracing::info!("[Migrations] Migration v10 complete");
+    Ok(())
+}
+
+/// Migration v11: Add per-workspace task hooks
+fn migrate_v11(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v11 (workspace hooks)");
+
+    conn.execute("ALTER TABLE workspaces ADD COLUMN hooks TEXT", [])
+        .map_err(|e| format!("Failed to add hooks column: {}", e))?;
+
+    set_stored_version(conn, 11)?;
+    tracing::info!("[Migrations] Migration v11 complete");
+    Ok(())
+}
+
+/// Migration v12: Add proposed plans for plan-first tasks
+fn migrate_v12(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v12 (task plans)");
+
+    conn.execute(
+        "CREATE TABLE task_plans (
+            task_id TEXT PRIMARY KEY REFERENCES tasks(id) ON DELETE CASCADE,
+            plan TEXT NOT NULL,
+            session_id TEXT,
+            status TEXT NOT NULL DEFAULT 'pending',
+            created_at TEXT NOT NULL,
+            approved_at TEXT
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create task_plans: {}", e))?;
+
+    set_stored_version(conn, 12)?;
+    tracing::info!("[Migrations] Migration v12 complete");
+    Ok(())
+}
+
+/// Migration v13: Add structured diffs for file-editing tool messages
+fn migrate_v13(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v13 (message diffs)");
+
+    conn.execute(
+        "CREATE TABLE message_diffs (
+            message_id TEXT PRIMARY KEY REFERENCES task_messages(id) ON DELETE CASCADE,
+            diff TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create message_diffs: {}", e))?;
 
-    println!("[Migrations] All migrations complete");
+    set_stored_version(conn, 13)?;
+    tracing::info!("[Migrations] Migration v13 complete");
     Ok(())
 }
+
+/// Migration v14: Add staged attachments (audio, images, fetched pages)
+fn migrate_v14(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v14 (attachments)");
+
+    conn.execute(
+        "CREATE TABLE attachments (
+            id TEXT PRIMARY KEY,
+            kind TEXT NOT NULL,
+            file_name TEXT NOT NULL,
+            mime_type TEXT NOT NULL,
+            path TEXT NOT NULL,
+            text TEXT,
+            source_url TEXT,
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create attachments: {}", e))?;
+
+    set_stored_version(conn, 14)?;
+    tracing::info!("[Migrations] Migration v14 complete");
+    Ok(())
+}
+
+/// Migration v15: Add per-task web search flag and search settings
+fn migrate_v15(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v15 (web search)");
+
+    conn.execute("ALTER TABLE tasks ADD COLUMN allow_web_search INTEGER", [])
+        .map_err(|e| format!("Failed to add allow_web_search column: {}", e))?;
+
+    conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN web_search_default INTEGER NOT NULL DEFAULT 1",
+        [],
+    )
+    .map_err(|e| format!("Failed to add web_search_default column: {}", e))?;
+
+    conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN web_search_backend TEXT NOT NULL DEFAULT 'exa'",
+        [],
+    )
+    .map_err(|e| format!("Failed to add web_search_backend column: {}", e))?;
+
+    set_stored_version(conn, 15)?;
+    tracing::info!("[Migrations] Migration v15 complete");
+    Ok(())
+}
+
+/// Migration v16: Link tasks started together by a model comparison
+fn migrate_v16(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v16 (model comparisons)");
+
+    conn.execute(
+        "CREATE TABLE comparison_tasks (
+            task_id TEXT PRIMARY KEY REFERENCES tasks(id) ON DELETE CASCADE,
+            comparison_id TEXT NOT NULL,
+            model_id TEXT NOT NULL,
+            position INTEGER NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create comparison_tasks: {}", e))?;
+
+    conn.execute(
+        "CREATE INDEX idx_comparison_tasks_comparison ON comparison_tasks(comparison_id)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create comparison index: {}", e))?;
+
+    set_stored_version(conn, 16)?;
+    tracing::info!("[Migrations] Migration v16 complete");
+    Ok(())
+}
+
+/// Migration v17: Record which task and message a forked task branched from
+fn migrate_v17(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v17 (task forks)");
+
+    conn.execute(
+        "CREATE TABLE task_forks (
+            task_id TEXT PRIMARY KEY REFERENCES tasks(id) ON DELETE CASCADE,
+            parent_task_id TEXT REFERENCES tasks(id) ON DELETE SET NULL,
+            parent_message_id TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create task_forks: {}", e))?;
+
+    conn.execute(
+        "CREATE INDEX idx_task_forks_parent ON task_forks(parent_task_id)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create fork index: {}", e))?;
+
+    set_stored_version(conn, 17)?;
+    tracing::info!("[Migrations] Migration v17 complete");
+    Ok(())
+}
+
+/// Migration v18: Message versions and soft-archived messages for edit-and-rerun
+fn migrate_v18(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v18 (message versions)");
+
+    conn.execute(
+        "ALTER TABLE task_messages ADD COLUMN version INTEGER NOT NULL DEFAULT 1",
+        [],
+    )
+    .map_err(|e| format!("Failed to add version column: {}", e))?;
+
+    conn.execute("ALTER TABLE task_messages ADD COLUMN archived_at TEXT", [])
+        .map_err(|e| format!("Failed to add archived_at column: {}", e))?;
+
+    conn.execute(
+        "CREATE TABLE task_message_versions (
+            message_id TEXT NOT NULL REFERENCES task_messages(id) ON DELETE CASCADE,
+            version INTEGER NOT NULL,
+            content TEXT NOT NULL,
+            replaced_at TEXT NOT NULL,
+            PRIMARY KEY (message_id, version)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create task_message_versions: {}", e))?;
+
+    set_stored_version(conn, 18)?;
+    tracing::info!("[Migrations] Migration v18 complete");
+    Ok(())
+}
+
+/// Migration v19: Add notes to task messages
+fn migrate_v19(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v19 (message notes)");
+
+    conn.execute("ALTER TABLE task_messages ADD COLUMN note TEXT", [])
+        .map_err(|e| format!("Failed to add note column: {}", e))?;
+
+    set_stored_version(conn, 19)?;
+    tracing::info!("[Migrations] Migration v19 complete");
+    Ok(())
+}
+
+/// Migration v20: Add user-defined slash commands
+fn migrate_v20(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v20 (slash commands)");
+
+    conn.execute(
+        "CREATE TABLE commands (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE,
+            description TEXT,
+            template TEXT NOT NULL,
+            model_id TEXT,
+            agent TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create commands: {}", e))?;
+
+    set_stored_version(conn, 20)?;
+    tracing::info!("[Migrations] Migration v20 complete");
+    Ok(())
+}
+
+/// Migration v21: Add JSON schemas and validated output of structured tasks
+fn migrate_v21(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v21 (structured output)");
+
+    conn.execute(
+        "CREATE TABLE task_structured_outputs (
+            task_id TEXT PRIMARY KEY REFERENCES tasks(id) ON DELETE CASCADE,
+            schema TEXT NOT NULL,
+            output TEXT,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            error TEXT
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create task_structured_outputs: {}", e))?;
+
+    set_stored_version(conn, 21)?;
+    tracing::info!("[Migrations] Migration v21 complete");
+    Ok(())
+}
+
+/// Migration v22: Add per-workspace default model and agent
+fn migrate_v22(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v22 (workspace defaults)");
+
+    conn.execute("ALTER TABLE workspaces ADD COLUMN model_id TEXT", [])
+        .map_err(|e| format!("Failed to add model_id column: {}", e))?;
+
+    conn.execute("ALTER TABLE workspaces ADD COLUMN agent TEXT", [])
+        .map_err(|e| format!("Failed to add agent column: {}", e))?;
+
+    set_stored_version(conn, 22)?;
+    tracing::info!("[Migrations] Migration v22 complete");
+    Ok(())
+}
+
+/// Migration v23: Add environment variables of workspaces and tasks
+fn migrate_v23(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v23 (environment variables)");
+
+    // owner_id is a workspace or task ID; secret values live in the keychain
+    conn.execute(
+        "CREATE TABLE env_vars (
+            owner_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            value TEXT,
+            secret INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (owner_id, name)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create env_vars: {}", e))?;
+
+    set_stored_version(conn, 23)?;
+    tracing::info!("[Migrations] Migration v23 complete");
+    Ok(())
+}
+
+/// Migration v24: Add the Docker sandbox image of workspaces
+fn migrate_v24(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v24 (sandbox image)");
+
+    conn.execute("ALTER TABLE workspaces ADD COLUMN sandbox_image TEXT", [])
+        .map_err(|e| format!("Failed to add sandbox_image column: {}", e))?;
+
+    set_stored_version(conn, 24)?;
+    tracing::info!("[Migrations] Migration v24 complete");
+    Ok(())
+}
+
+/// Migration v25: Add SSH hosts and remote workspaces
+fn migrate_v25(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v25 (SSH hosts)");
+
+    conn.execute(
+        "CREATE TABLE ssh_hosts (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            host TEXT NOT NULL,
+            port INTEGER,
+            user TEXT,
+            auth TEXT NOT NULL DEFAULT 'agent',
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create ssh_hosts: {}", e))?;
+
+    conn.execute(
+        "ALTER TABLE workspaces ADD COLUMN ssh_host_id TEXT REFERENCES ssh_hosts(id)",
+        [],
+    )
+    .map_err(|e| format!("Failed to add ssh_host_id column: {}", e))?;
+
+    set_stored_version(conn, 25)?;
+    tracing::info!("[Migrations] Migration v25 complete");
+    Ok(())
+}
+
+/// Migration v26: Add the CLI runtime preference
+fn migrate_v26(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v26 (CLI runtime)");
+
+    conn.execute("ALTER TABLE app_settings ADD COLUMN cli_runtime TEXT", [])
+        .map_err(|e| format!("Failed to add cli_runtime column: {}", e))?;
+
+    set_stored_version(conn, 26)?;
+    tracing::info!("[Migrations] Migration v26 complete");
+    Ok(())
+}
+
+/// Migration v27: Add the task summary model setting
+fn migrate_v27(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v27 (summary model)");
+
+    conn.execute("ALTER TABLE app_settings ADD COLUMN summary_model_id TEXT", [])
+        .map_err(|e| format!("Failed to add summary_model_id column: {}", e))?;
+
+    set_stored_version(conn, 27)?;
+    tracing::info!("[Migrations] Migration v27 complete");
+    Ok(())
+}
+
+/// Migration v28: Add generated task titles
+fn migrate_v28(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v28 (task titles)");
+
+    conn.execute("ALTER TABLE tasks ADD COLUMN title TEXT", [])
+        .map_err(|e| format!("Failed to add title column: {}", e))?;
+
+    set_stored_version(conn, 28)?;
+    tracing::info!("[Migrations] Migration v28 complete");
+    Ok(())
+}
+
+fn migrate_v29(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v29 (unique message order)");
+
+    // Renumber tasks whose concurrent appends got the same sort order,
+    // keeping ties in insertion order
+    conn.execute(
+        "UPDATE task_messages SET sort_order = (
+             SELECT n FROM (
+                 SELECT id, ROW_NUMBER() OVER (PARTITION BY task_id ORDER BY sort_order, rowid) - 1
+                        AS n
+                 FROM task_messages
+             ) numbered
+             WHERE numbered.id = task_messages.id
+         )
+         WHERE task_id IN (
+             SELECT task_id FROM task_messages GROUP BY task_id, sort_order HAVING COUNT(*) > 1
+         )",
+        [],
+    )
+    .map_err(|e| format!("Failed to renumber messages: {}", e))?;
+
+    conn.execute(
+        "CREATE UNIQUE INDEX idx_messages_task_order ON task_messages(task_id, sort_order)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create idx_messages_task_order: {}", e))?;
+
+    set_stored_version(conn, 29)?;
+    tracing::info!("[Migrations] Migration v29 complete");
+    Ok(())
+}
+
+fn migrate_v30(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v30 (message origins)");
+
+    conn.execute("ALTER TABLE task_messages ADD COLUMN origin_id TEXT", [])
+        .map_err(|e| format!("Failed to add origin_id column: {}", e))?;
+
+    conn.execute(
+        "CREATE UNIQUE INDEX idx_messages_origin ON task_messages(task_id, origin_id)
+         WHERE origin_id IS NOT NULL",
+        [],
+    )
+    .map_err(|e| format!("Failed to create idx_messages_origin: {}", e))?;
+
+    set_stored_version(conn, 30)?;
+    tracing::info!("[Migrations] Migration v30 complete");
+    Ok(())
+}
+
+fn migrate_v31(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v31 (task progress)");
+
+    conn.execute(
+        "CREATE TABLE task_progress (
+            task_id TEXT PRIMARY KEY REFERENCES tasks(id) ON DELETE CASCADE,
+            stage TEXT NOT NULL,
+            step INTEGER,
+            total_steps INTEGER,
+            label TEXT,
+            updated_at TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create task_progress: {}", e))?;
+
+    set_stored_version(conn, 31)?;
+    tracing::info!("[Migrations] Migration v31 complete");
+    Ok(())
+}
+
+fn migrate_v32(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v32 (window state)");
+
+    conn.execute("ALTER TABLE app_settings ADD COLUMN window_state TEXT", [])
+        .map_err(|e| format!("Failed to add window_state column: {}", e))?;
+    conn.execute("ALTER TABLE app_settings ADD COLUMN last_workspace_id TEXT", [])
+        .map_err(|e| format!("Failed to add last_workspace_id column: {}", e))?;
+
+    set_stored_version(conn, 32)?;
+    tracing::info!("[Migrations] Migration v32 complete");
+    Ok(())
+}
+
+fn migrate_v33(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v33 (backup schedule)");
+
+    conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN backup_enabled INTEGER NOT NULL DEFAULT 0",
+        [],
+    )
+    .map_err(|e| format!("Failed to add backup_enabled column: {}", e))?;
+    conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN backup_interval_hours INTEGER NOT NULL DEFAULT 24",
+        [],
+    )
+    .map_err(|e| format!("Failed to add backup_interval_hours column: {}", e))?;
+    conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN backup_keep INTEGER NOT NULL DEFAULT 7",
+        [],
+    )
+    .map_err(|e| format!("Failed to add backup_keep column: {}", e))?;
+
+    set_stored_version(conn, 33)?;
+    tracing::info!("[Migrations] Migration v33 complete");
+    Ok(())
+}
+
+fn migrate_v34(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v34 (project file index)");
+
+    conn.execute(
+        "CREATE TABLE project_files (
+            workspace_id TEXT NOT NULL REFERENCES workspaces(id) ON DELETE CASCADE,
+            path TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            modified_at INTEGER NOT NULL,
+            symbols TEXT NOT NULL DEFAULT '',
+            PRIMARY KEY (workspace_id, path)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create project_files: {}", e))?;
+    conn.execute(
+        "CREATE TABLE project_indexes (
+            workspace_id TEXT PRIMARY KEY REFERENCES workspaces(id) ON DELETE CASCADE,
+            indexed_at TEXT NOT NULL,
+            file_count INTEGER NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create project_indexes: {}", e))?;
+
+    set_stored_version(conn, 34)?;
+    tracing::info!("[Migrations] Migration v34 complete");
+    Ok(())
+}
+
+fn migrate_v35(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v35 (response post-processing)");
+
+    conn.execute("ALTER TABLE workspaces ADD COLUMN postprocess TEXT", [])
+        .map_err(|e| format!("Failed to add postprocess column: {}", e))?;
+    conn.execute(
+        "CREATE TABLE task_artifacts (
+            id TEXT PRIMARY KEY,
+            task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+            origin_id TEXT,
+            language TEXT,
+            content TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create task_artifacts: {}", e))?;
+    conn.execute(
+        "CREATE INDEX idx_task_artifacts_task ON task_artifacts(task_id, created_at)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create task_artifacts index: {}", e))?;
+
+    set_stored_version(conn, 35)?;
+    tracing::info!("[Migrations] Migration v35 complete");
+    Ok(())
+}
+
+fn migrate_v36(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v36 (task checkpoints)");
+
+    conn.execute(
+        "CREATE TABLE task_checkpoints (
+            task_id TEXT PRIMARY KEY REFERENCES tasks(id) ON DELETE CASCADE,
+            session_id TEXT,
+            message_count INTEGER NOT NULL,
+            files_touched TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create task_checkpoints: {}", e))?;
+
+    set_stored_version(conn, 36)?;
+    tracing::info!("[Migrations] Migration v36 complete");
+    Ok(())
+}
+
+fn migrate_v37(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v37 (model parameters)");
+
+    conn.execute(
+        "CREATE TABLE model_parameters (
+            model_id TEXT PRIMARY KEY,
+            temperature REAL,
+            top_p REAL,
+            max_tokens INTEGER,
+            reasoning_effort TEXT,
+            updated_at TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create model_parameters: {}", e))?;
+
+    set_stored_version(conn, 37)?;
+    tracing::info!("[Migrations] Migration v37 complete");
+    Ok(())
+}
+
+fn migrate_v38(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v38 (session locks)");
+
+    conn.execute(
+        "CREATE TABLE session_locks (
+            session_id TEXT PRIMARY KEY,
+            task_id TEXT NOT NULL,
+            holder TEXT NOT NULL,
+            acquired_at TEXT NOT NULL,
+            expires_at TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create session_locks: {}", e))?;
+
+    set_stored_version(conn, 38)?;
+    tracing::info!("[Migrations] Migration v38 complete");
+    Ok(())
+}
+
+fn migrate_v39(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v39 (metrics endpoint setting)");
+
+    conn.execute(
+        "ALTER TABLE app_settings ADD COLUMN api_server_metrics_enabled INTEGER NOT NULL DEFAULT 0",
+        [],
+    )
+    .map_err(|e| format!("Failed to add api_server_metrics_enabled column: {}", e))?;
+
+    set_stored_version(conn, 39)?;
+    tracing::info!("[Migrations] Migration v39 complete");
+    Ok(())
+}
+
+fn migrate_v40(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v40 (attachment blobs)");
+
+    conn.execute(
+        "CREATE TABLE attachment_blobs (
+            hash TEXT PRIMARY KEY,
+            data TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            ref_count INTEGER NOT NULL DEFAULT 0
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create attachment_blobs: {}", e))?;
+
+    conn.execute("ALTER TABLE task_attachments ADD COLUMN blob_hash TEXT", [])
+        .map_err(|e| format!("Failed to add blob_hash column: {}", e))?;
+
+    conn.execute(
+        "CREATE INDEX idx_task_attachments_blob ON task_attachments(blob_hash)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create blob_hash index: {}", e))?;
+
+    // References are counted by triggers, so every way an attachment row goes
+    // away (message or task deletion, history limits, cascades) releases it
+    conn.execute_batch(
+        "CREATE TRIGGER task_attachments_ref_blob AFTER INSERT ON task_attachments
+         WHEN NEW.blob_hash IS NOT NULL
+         BEGIN
+             UPDATE attachment_blobs SET ref_count = ref_count + 1 WHERE hash = NEW.blob_hash;
+         END;
+
+         CREATE TRIGGER task_attachments_swap_blob AFTER UPDATE OF blob_hash ON task_attachments
+         WHEN OLD.blob_hash IS NOT NEW.blob_hash
+         BEGIN
+             UPDATE attachment_blobs SET ref_count = ref_count + 1 WHERE hash = NEW.blob_hash;
+             UPDATE attachment_blobs SET ref_count = ref_count - 1 WHERE hash = OLD.blob_hash;
+             DELETE FROM attachment_blobs WHERE hash = OLD.blob_hash AND ref_count <= 0;
+         END;
+
+         CREATE TRIGGER task_attachments_unref_blob AFTER DELETE ON task_attachments
+         WHEN OLD.blob_hash IS NOT NULL
+         BEGIN
+             UPDATE attachment_blobs SET ref_count = ref_count - 1 WHERE hash = OLD.blob_hash;
+             DELETE FROM attachment_blobs WHERE hash = OLD.blob_hash AND ref_count <= 0;
+         END;",
+    )
+    .map_err(|e| format!("Failed to create attachment blob triggers: {}", e))?;
+
+    set_stored_version(conn, 40)?;
+    tracing::info!("[Migrations] Migration v40 complete");
+    Ok(())
+}
+
+fn migrate_v41(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v41 (workspace templates)");
+
+    conn.execute("ALTER TABLE workspaces ADD COLUMN agent_settings TEXT", [])
+        .map_err(|e| format!("Failed to add agent_settings column: {}", e))?;
+
+    conn.execute(
+        "CREATE TABLE workspace_templates (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            description TEXT,
+            template TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create workspace_templates: {}", e))?;
+
+    set_stored_version(conn, 41)?;
+    tracing::info!("[Migrations] Migration v41 complete");
+    Ok(())
+}
+
+fn migrate_v42(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v42 (message sources)");
+
+    conn.execute(
+        "CREATE TABLE message_sources (
+            task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+            origin_id TEXT NOT NULL,
+            url TEXT NOT NULL,
+            title TEXT,
+            kind TEXT NOT NULL,
+            chunk INTEGER NOT NULL,
+            PRIMARY KEY (task_id, origin_id, url)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create message_sources: {}", e))?;
+
+    set_stored_version(conn, 42)?;
+    tracing::info!("[Migrations] Migration v42 complete");
+    Ok(())
+}
+
+fn migrate_v43(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v43 (task notes)");
+
+    conn.execute(
+        "CREATE TABLE task_notes (
+            id TEXT PRIMARY KEY,
+            task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+            content TEXT NOT NULL,
+            follow_up_at TEXT,
+            reminded_at TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create task_notes: {}", e))?;
+
+    conn.execute(
+        "CREATE INDEX idx_task_notes_task ON task_notes(task_id, created_at)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create task notes index: {}", e))?;
+
+    conn.execute(
+        "CREATE INDEX idx_task_notes_follow_up ON task_notes(follow_up_at)
+         WHERE reminded_at IS NULL",
+        [],
+    )
+    .map_err(|e| format!("Failed to create follow-up index: {}", e))?;
+
+    set_stored_version(conn, 43)?;
+    tracing::info!("[Migrations] Migration v43 complete");
+    Ok(())
+}
+
+fn migrate_v44(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v44 (full-text search)");
+
+    // External-content indexes: the text stays in `tasks` and `task_messages`
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE task_search USING fts5(
+             prompt, summary, title,
+             content = 'tasks', tokenize = 'unicode61 remove_diacritics 2'
+         );
+         CREATE VIRTUAL TABLE message_search USING fts5(
+             content,
+             content = 'task_messages', tokenize = 'unicode61 remove_diacritics 2'
+         );",
+    )
+    .map_err(|e| format!("Failed to create search tables: {}", e))?;
+
+    // An external-content index must be told the old values of what it removes
+    conn.execute_batch(
+        "CREATE TRIGGER tasks_search_insert AFTER INSERT ON tasks
+         BEGIN
+             INSERT INTO task_search (rowid, prompt, summary, title)
+             VALUES (NEW.rowid, NEW.prompt, NEW.summary, NEW.title);
+         END;
+
+         CREATE TRIGGER tasks_search_update AFTER UPDATE OF prompt, summary, title ON tasks
+         BEGIN
+             INSERT INTO task_search (task_search, rowid, prompt, summary, title)
+             VALUES ('delete', OLD.rowid, OLD.prompt, OLD.summary, OLD.title);
+             INSERT INTO task_search (rowid, prompt, summary, title)
+             VALUES (NEW.rowid, NEW.prompt, NEW.summary, NEW.title);
+         END;
+
+         CREATE TRIGGER tasks_search_delete AFTER DELETE ON tasks
+         BEGIN
+             INSERT INTO task_search (task_search, rowid, prompt, summary, title)
+             VALUES ('delete', OLD.rowid, OLD.prompt, OLD.summary, OLD.title);
+         END;
+
+         CREATE TRIGGER task_messages_search_insert AFTER INSERT ON task_messages
+         BEGIN
+             INSERT INTO message_search (rowid, content) VALUES (NEW.rowid, NEW.content);
+         END;
+
+         CREATE TRIGGER task_messages_search_update AFTER UPDATE OF content ON task_messages
+         BEGIN
+             INSERT INTO message_search (message_search, rowid, content)
+             VALUES ('delete', OLD.rowid, OLD.content);
+             INSERT INTO message_search (rowid, content) VALUES (NEW.rowid, NEW.content);
+         END;
+
+         CREATE TRIGGER task_messages_search_delete AFTER DELETE ON task_messages
+         BEGIN
+             INSERT INTO message_search (message_search, rowid, content)
+             VALUES ('delete', OLD.rowid, OLD.content);
+         END;",
+    )
+    .map_err(|e| format!("Failed to create search triggers: {}", e))?;
+
+    super::search::rebuild(conn)?;
+
+    set_stored_version(conn, 44)?;
+    tracing::info!("[Migrations] Migration v44 complete");
+    Ok(())
+}
+
+fn migrate_v45(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v45 (downgrade notice)");
+
+    conn.execute("ALTER TABLE app_settings ADD COLUMN downgrade_notice TEXT", [])
+        .map_err(|e| format!("Failed to add downgrade_notice column: {}", e))?;
+
+    set_stored_version(conn, 45)?;
+    tracing::info!("[Migrations] Migration v45 complete");
+    Ok(())
+}
+
+fn migrate_v46(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v46 (message ratings)");
+
+    conn.execute(
+        "CREATE TABLE message_ratings (
+            message_id TEXT PRIMARY KEY REFERENCES task_messages(id) ON DELETE CASCADE,
+            rating TEXT NOT NULL CHECK (rating IN ('up', 'down')),
+            comment TEXT,
+            provider_id TEXT,
+            model_id TEXT,
+            rated_at TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create message_ratings: {}", e))?;
+
+    set_stored_version(conn, 46)?;
+    tracing::info!("[Migrations] Migration v46 complete");
+    Ok(())
+}
+
+fn migrate_v47(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v47 (task tags)");
+
+    conn.execute(
+        "CREATE TABLE task_tags (
+            task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+            tag TEXT NOT NULL COLLATE NOCASE,
+            created_at TEXT NOT NULL,
+            PRIMARY KEY (task_id, tag)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create task_tags: {}", e))?;
+
+    conn.execute("CREATE INDEX idx_task_tags_tag ON task_tags(tag)", [])
+        .map_err(|e| format!("Failed to create task tags index: {}", e))?;
+
+    set_stored_version(conn, 47)?;
+    tracing::info!("[Migrations] Migration v47 complete");
+    Ok(())
+}
+
+fn migrate_v48(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v48 (workspace repositories)");
+
+    conn.execute("ALTER TABLE workspaces ADD COLUMN repo TEXT", [])
+        .map_err(|e| format!("Failed to add repo column: {}", e))?;
+    conn.execute("CREATE INDEX idx_workspaces_repo ON workspaces(repo)", [])
+        .map_err(|e| format!("Failed to create workspace repository index: {}", e))?;
+
+    set_stored_version(conn, 48)?;
+    tracing::info!("[Migrations] Migration v48 complete");
+    Ok(())
+}
+
+fn migrate_v49(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v49 (task archive)");
+
+    conn.execute("ALTER TABLE tasks ADD COLUMN archived_at TEXT", [])
+        .map_err(|e| format!("Failed to add archived_at column: {}", e))?;
+    conn.execute("ALTER TABLE tasks ADD COLUMN restored_at TEXT", [])
+        .map_err(|e| format!("Failed to add restored_at column: {}", e))?;
+    conn.execute("CREATE INDEX idx_tasks_archived ON tasks(archived_at)", [])
+        .map_err(|e| format!("Failed to create task archive index: {}", e))?;
+
+    set_stored_version(conn, 49)?;
+    tracing::info!("[Migrations] Migration v49 complete");
+    Ok(())
+}
+
+/// Migration v50: Record each task's model and agent, and which task a re-run repeats
+fn migrate_v50(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v50 (task re-runs)");
+
+    conn.execute("ALTER TABLE tasks ADD COLUMN model_id TEXT", [])
+        .map_err(|e| format!("Failed to add model_id column: {}", e))?;
+    conn.execute("ALTER TABLE tasks ADD COLUMN agent TEXT", [])
+        .map_err(|e| format!("Failed to add agent column: {}", e))?;
+
+    conn.execute(
+        "CREATE TABLE task_reruns (
+            task_id TEXT PRIMARY KEY REFERENCES tasks(id) ON DELETE CASCADE,
+            parent_task_id TEXT REFERENCES tasks(id) ON DELETE SET NULL,
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create task_reruns: {}", e))?;
+    conn.execute(
+        "CREATE INDEX idx_task_reruns_parent ON task_reruns(parent_task_id)",
+        [],
+    )
+    .map_err(|e| format!("Failed to create re-run index: {}", e))?;
+
+    set_stored_version(conn, 50)?;
+    tracing::info!("[Migrations] Migration v50 complete");
+    Ok(())
+}
+
+/// Migration v51: Project folders for tasks
+fn migrate_v51(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v51 (projects)");
+
+    conn.execute(
+        "CREATE TABLE projects (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create projects: {}", e))?;
+    conn.execute(
+        "ALTER TABLE tasks ADD COLUMN project_id TEXT REFERENCES projects(id) ON DELETE SET NULL",
+        [],
+    )
+    .map_err(|e| format!("Failed to add project_id column: {}", e))?;
+    conn.execute("CREATE INDEX idx_tasks_project ON tasks(project_id)", [])
+        .map_err(|e| format!("Failed to create task project index: {}", e))?;
+
+    set_stored_version(conn, 51)?;
+    tracing::info!("[Migrations] Migration v51 complete");
+    Ok(())
+}
+
+/// Migration v52: How a task's last run ended, including its error
+fn migrate_v52(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v52 (task results)");
+
+    for column in ["result_status", "result_error", "result_session_id"] {
+        conn.execute(&format!("ALTER TABLE tasks ADD COLUMN {} TEXT", column), [])
+            .map_err(|e| format!("Failed to add {} column: {}", column, e))?;
+    }
+
+    set_stored_version(conn, 52)?;
+    tracing::info!("[Migrations] Migration v52 complete");
+    Ok(())
+}
+
+/// Migration v53: When each task last had activity
+fn migrate_v53(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v53 (task activity time)");
+
+    conn.execute("ALTER TABLE tasks ADD COLUMN updated_at TEXT", [])
+        .map_err(|e| format!("Failed to add updated_at column: {}", e))?;
+    // Existing tasks last changed when they ended, or when they started if they haven't
+    conn.execute(
+        "UPDATE tasks SET updated_at = COALESCE(completed_at, started_at, created_at)",
+        [],
+    )
+    .map_err(|e| format!("Failed to backfill updated_at: {}", e))?;
+    conn.execute("CREATE INDEX idx_tasks_updated ON tasks(updated_at)", [])
+        .map_err(|e| format!("Failed to create task activity index: {}", e))?;
+
+    set_stored_version(conn, 53)?;
+    tracing::info!("[Migrations] Migration v53 complete");
+    Ok(())
+}
+
+/// Migration v54: Attachment size limit setting
+fn migrate_v54(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v54 (attachment size limit)");
+
+    conn.execute("ALTER TABLE app_settings ADD COLUMN max_attachment_bytes INTEGER", [])
+        .map_err(|e| format!("Failed to add max_attachment_bytes column: {}", e))?;
+
+    set_stored_version(conn, 54)?;
+    tracing::info!("[Migrations] Migration v54 complete");
+    Ok(())
+}
+
+/// Migration v55: Checkpoints saved by the user
+fn migrate_v55(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v55 (saved checkpoints)");
+
+    conn.execute(
+        "CREATE TABLE saved_checkpoints (
+            id TEXT PRIMARY KEY,
+            task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+            label TEXT NOT NULL,
+            session_id TEXT,
+            message_count INTEGER NOT NULL,
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create saved_checkpoints: {}", e))?;
+
+    conn.execute("CREATE INDEX idx_saved_checkpoints_task ON saved_checkpoints(task_id)", [])
+        .map_err(|e| format!("Failed to create saved checkpoint index: {}", e))?;
+
+    set_stored_version(conn, 55)?;
+    tracing::info!("[Migrations] Migration v55 complete");
+    Ok(())
+}
+
+/// Migration v56: Task status timeline
+fn migrate_v56(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v56 (task events)");
+
+    conn.execute(
+        "CREATE TABLE task_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task_id TEXT NOT NULL,
+            from_status TEXT,
+            to_status TEXT NOT NULL,
+            source TEXT NOT NULL,
+            reason TEXT,
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create task_events: {}", e))?;
+
+    conn.execute("CREATE INDEX idx_task_events_task ON task_events(task_id)", [])
+        .map_err(|e| format!("Failed to create task events index: {}", e))?;
+
+    set_stored_version(conn, 56)?;
+    tracing::info!("[Migrations] Migration v56 complete");
+    Ok(())
+}
+
+/// Migration v57: Rebuild the task search index
+///
+/// `save_task` used to replace task rows, which left index entries for the old
+/// rowids behind without firing `tasks_search_delete`.
+fn migrate_v57(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v57 (task search rebuild)");
+
+    conn.execute("INSERT INTO task_search (task_search) VALUES ('rebuild')", [])
+        .map_err(|e| format!("Failed to rebuild task search: {}", e))?;
+
+    set_stored_version(conn, 57)?;
+    tracing::info!("[Migrations] Migration v57 complete");
+    Ok(())
+}
+
+/// Migration v58: Origins of deleted messages, so session sync doesn't bring them back
+fn migrate_v58(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v58 (deleted messages)");
+
+    conn.execute(
+        "CREATE TABLE deleted_messages (
+            task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+            origin_id TEXT NOT NULL,
+            deleted_at TEXT NOT NULL,
+            PRIMARY KEY (task_id, origin_id)
+        )",
+        [],
+    )
+    .map_err(|e| format!("Failed to create deleted_messages: {}", e))?;
+
+    set_stored_version(conn, 58)?;
+    tracing::info!("[Migrations] Migration v58 complete");
+    Ok(())
+}
+
+/// Migration v59: Directory a task runs in when it isn't its workspace's
+fn migrate_v59(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v59 (task working directory)");
+
+    conn.execute("ALTER TABLE tasks ADD COLUMN working_directory TEXT", [])
+        .map_err(|e| format!("Failed to add working_directory column: {}", e))?;
+
+    set_stored_version(conn, 59)?;
+    tracing::info!("[Migrations] Migration v59 complete");
+    Ok(())
+}
+
+/// Migration v60: Thumbnails of screenshots attached to messages
+fn migrate_v60(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v60 (message attachment thumbnails)");
+
+    conn.execute("ALTER TABLE task_attachments ADD COLUMN thumbnail TEXT", [])
+        .map_err(|e| format!("Failed to add thumbnail column: {}", e))?;
+
+    set_stored_version(conn, 60)?;
+    tracing::info!("[Migrations] Migration v60 complete");
+    Ok(())
+}
+
+/// Migration v61: Web search without a search engine by default
+///
+/// `exa` was the column's default rather than a choice, and turned on a
+/// third-party search service for every task.
+fn migrate_v61(conn: &Connection) -> Result<(), String> {
+    tracing::info!("[Migrations] Running migration v61 (web search default)");
+
+    conn.execute(
+        "UPDATE app_settings SET web_search_backend = 'fetch' WHERE web_search_backend = 'exa'",
+        [],
+    )
+    .map_err(|e| format!("Failed to reset web search backend: {}", e))?;
+
+    set_stored_version(conn, 61)?;
+    tracing::info!("[Migrations] Migration v61 complete");
+    Ok(())
+}
+
+/// Run all pending migrations
+///
+/// A database from a newer version of the app is reported rather than
+/// migrated, since this version can't know what the newer schema expects.
+pub fn run_migrations(conn: &Connection) -> Result<SchemaStatus, String> {
+    let stored_version = get_stored_version(conn);
+    tracing::info!(
+        "[Migrations] Stored version: {}, App version: {}",
+        stored_version, CURRENT_VERSION
+    );
+
+    // Leave a database from a newer app version for the caller to set aside
+    if stored_version > CURRENT_VERSION {
+        tracing::warn!(
+            "[Migrations] Database schema version {} is newer than app version {}",
+            stored_version, CURRENT_VERSION
+        );
+        return Ok(SchemaStatus::Newer(stored_version));
+    }
+
+    // No migrations to run
+    if stored_version == CURRENT_VERSION {
+        tracing::info!("[Migrations] Database is up to date");
+        return Ok(SchemaStatus::Current);
+    }
+
+    // Run pending migrations
+    if stored_version < 1 {
+        migrate_v1(conn)?;
+    }
+    if stored_version < 2 {
+        migrate_v2(conn)?;
+    }
+    if stored_version < 3 {
+        migrate_v3(conn)?;
+    }
+    if stored_version < 4 {
+        migrate_v4(conn)?;
+    }
+    if stored_version < 5 {
+        migrate_v5(conn)?;
+    }
+    if stored_version < 6 {
+        migrate_v6(conn)?;
+    }
+    if stored_version < 7 {
+        migrate_v7(conn)?;
+    }
+    if stored_version < 8 {
+        migrate_v8(conn)?;
+    }
+    if stored_version < 9 {
+        migrate_v9(conn)?;
+    }
+    if stored_version < 10 {
+        migrate_v10(conn)?;
+    }
+    if stored_version < 11 {
+        migrate_v11(conn)?;
+    }
+    if stored_version < 12 {
+        migrate_v12(conn)?;
+    }
+    if stored_version < 13 {
+        migrate_v13(conn)?;
+    }
+    if stored_version < 14 {
+        migrate_v14(conn)?;
+    }
+    if stored_version < 15 {
+        migrate_v15(conn)?;
+    }
+    if stored_version < 16 {
+        migrate_v16(conn)?;
+    }
+    if stored_version < 17 {
+        migrate_v17(conn)?;
+    }
+    if stored_version < 18 {
+        migrate_v18(conn)?;
+    }
+    if stored_version < 19 {
+        migrate_v19(conn)?;
+    }
+    if stored_version < 20 {
+        migrate_v20(conn)?;
+    }
+    if stored_version < 21 {
+        migrate_v21(conn)?;
+    }
+    if stored_version < 22 {
+        migrate_v22(conn)?;
+    }
+    if stored_version < 23 {
+        migrate_v23(conn)?;
+    }
+    if stored_version < 24 {
+        migrate_v24(conn)?;
+    }
+    if stored_version < 25 {
+        migrate_v25(conn)?;
+    }
+    if stored_version < 26 {
+        migrate_v26(conn)?;
+    }
+    if stored_version < 27 {
+        migrate_v27(conn)?;
+    }
+    if stored_version < 28 {
+        migrate_v28(conn)?;
+    }
+    if stored_version < 29 {
+        migrate_v29(conn)?;
+    }
+    if stored_version < 30 {
+        migrate_v30(conn)?;
+    }
+    if stored_version < 31 {
+        migrate_v31(conn)?;
+    }
+    if stored_version < 32 {
+        migrate_v32(conn)?;
+    }
+
+    if stored_version < 33 {
+        migrate_v33(conn)?;
+    }
+
+    if stored_version < 34 {
+        migrate_v34(conn)?;
+    }
+
+    if stored_version < 35 {
+        migrate_v35(conn)?;
+    }
+
+    if stored_version < 36 {
+        migrate_v36(conn)?;
+    }
+
+    if stored_version < 37 {
+        migrate_v37(conn)?;
+    }
+
+    if stored_version < 38 {
+        migrate_v38(conn)?;
+    }
+
+    if stored_version < 39 {
+        migrate_v39(conn)?;
+    }
+
+    if stored_version < 40 {
+        migrate_v40(conn)?;
+    }
+
+    if stored_version < 41 {
+        migrate_v41(conn)?;
+    }
+
+    if stored_version < 42 {
+        migrate_v42(conn)?;
+    }
+
+    if stored_version < 43 {
+        migrate_v43(conn)?;
+    }
+
+    if stored_version < 44 {
+        migrate_v44(conn)?;
+    }
+
+    if stored_version < 45 {
+        migrate_v45(conn)?;
+    }
+
+    if stored_version < 46 {
+        migrate_v46(conn)?;
+    }
+
+    if stored_version < 47 {
+        migrate_v47(conn)?;
+    }
+
+    if stored_version < 48 {
+        migrate_v48(conn)?;
+    }
+
+    if stored_version < 49 {
+        migrate_v49(conn)?;
+    }
+
+    if stored_version < 50 {
+        migrate_v50(conn)?;
+    }
+
+    if stored_version < 51 {
+        migrate_v51(conn)?;
+    }
+
+    if stored_version < 52 {
+        migrate_v52(conn)?;
+    }
+
+    if stored_version < 53 {
+        migrate_v53(conn)?;
+    }
+
+    if stored_version < 54 {
+        migrate_v54(conn)?;
+    }
+
+    if stored_version < 55 {
+        migrate_v55(conn)?;
+    }
+
+    if stored_version < 56 {
+        migrate_v56(conn)?;
+    }
+
+    if stored_version < 57 {
+        migrate_v57(conn)?;
+    }
+
+    if stored_version < 58 {
+        migrate_v58(conn)?;
+    }
+
+    if stored_version < 59 {
+        migrate_v59(conn)?;
+    }
+
+    if stored_version < 60 {
+        migrate_v60(conn)?;
+    }
+
+    if stored_version < 61 {
+        migrate_v61(conn)?;
+    }
+
+    tracing::info!("[Migrations] All migrations complete");
+    Ok(SchemaStatus::Current)
+}
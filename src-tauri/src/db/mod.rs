@@ -1,34 +1,70 @@
 // src-tauri/src/db/mod.rs
 //! Database module for Cowork Z
 //!
-//! Provides SQLite-based persistence for tasks, settings, and provider configurations.
+//! Provides SQLite-based persistence for tasks, task tags, task forks, task artifacts, task checkpoints, model comparisons, attachments, deduplicated attachment data, message diffs, message ratings, task plans, task progress, session locks, structured output, workspaces, project file indexes, SSH hosts, environment variables, slash commands, usage, settings, provider configurations, and logs.
+//!
+//! Writes go through `conn`. Under WAL, reads don't wait for writes, so
+//! commands that only query (task lists, diffs, logs, usage) use the
+//! read-only `reader` and aren't held up while streamed messages are written.
 
+pub mod artifacts;
+pub mod attachments;
+pub mod blobs;
+pub mod checkpoints;
+pub mod citations;
+pub mod commands;
+pub mod comparisons;
+pub mod diffs;
+pub mod env_vars;
+pub mod forks;
+pub mod logs;
 pub mod migrations;
+pub mod notes;
+pub mod plans;
+pub mod progress;
+pub mod projects;
+pub mod project_files;
 pub mod providers;
+pub mod ratings;
+pub mod reruns;
+pub mod search;
+pub mod session_locks;
 pub mod settings;
+pub mod ssh_hosts;
+pub mod stats;
+pub mod structured;
+pub mod tags;
+pub mod task_events;
 pub mod tasks;
+pub mod templates;
+pub mod usage;
+pub mod workspaces;
 
-use rusqlite::Connection;
-use std::path::PathBuf;
+use rusqlite::{Connection, ErrorCode, OpenFlags};
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
-use tauri::{AppHandle, Manager};
+use std::time::Duration;
+use tauri::AppHandle;
+
+use migrations::{run_migrations, SchemaStatus};
 
-use migrations::run_migrations;
+/// How long a statement waits for another connection's lock before failing
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
 
-/// App state containing the database connection
+/// Attempts of a write that keeps finding the database locked
+const WRITE_ATTEMPTS: u32 = 2;
+
+/// App state containing the database connections
 pub struct DbState {
     pub conn: Mutex<Connection>,
+    /// Read-only connection for query commands
+    pub reader: Mutex<Connection>,
 }
 
 /// Get the database file path based on environment
 pub fn get_database_path(app: &AppHandle) -> PathBuf {
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .expect("Failed to get app data directory");
-
-    // Ensure directory exists
-    std::fs::create_dir_all(&app_data_dir).expect("Failed to create app data directory");
+    // The profile's directory, created if missing
+    let app_data_dir = crate::profile::data_dir(app).expect("Failed to get app data directory");
 
     // Use different database for development vs production
     #[cfg(debug_assertions)]
@@ -39,12 +75,9 @@ pub fn get_database_path(app: &AppHandle) -> PathBuf {
     app_data_dir.join(db_name)
 }
 
-/// Initialize the database connection and run migrations
-pub fn init_database(app: &AppHandle) -> Result<DbState, String> {
-    let db_path = get_database_path(app);
-    println!("[DB] Opening database at: {:?}", db_path);
-
-    let conn = Connection::open(&db_path).map_err(|e| format!("Failed to open database: {}", e))?;
+/// Open the write connection
+fn open_database(db_path: &Path) -> Result<Connection, String> {
+    let conn = Connection::open(db_path).map_err(|e| format!("Failed to open database: {}", e))?;
 
     // Enable WAL mode for better concurrent read/write performance
     conn.pragma_update(None, "journal_mode", "WAL")
@@ -54,12 +87,105 @@ pub fn init_database(app: &AppHandle) -> Result<DbState, String> {
     conn.pragma_update(None, "foreign_keys", "ON")
         .map_err(|e| format!("Failed to enable foreign keys: {}", e))?;
 
+    // Wait for the log store's writes instead of failing with "database is locked"
+    conn.busy_timeout(BUSY_TIMEOUT)
+        .map_err(|e| format!("Failed to set busy timeout: {}", e))?;
+
+    Ok(conn)
+}
+
+/// Initialize the database connection and run migrations
+///
+/// A database from a newer version of the app is kept as a backup and a new
+/// one started, with a notice for the UI to offer importing its tasks.
+pub fn init_database(app: &AppHandle) -> Result<DbState, String> {
+    let db_path = get_database_path(app);
+    tracing::info!("[DB] Opening database at: {:?}", db_path);
+
+    let mut conn = open_database(&db_path)?;
+
     // Run migrations
-    run_migrations(&conn)?;
+    if let SchemaStatus::Newer(schema_version) = run_migrations(&conn)? {
+        let kept = crate::backup::set_aside(app, conn)?;
+        conn = open_database(&db_path)?;
+        run_migrations(&conn)?;
+        settings::set_downgrade_notice(
+            &conn,
+            Some(&settings::DowngradeNotice {
+                backup_id: kept.id,
+                schema_version,
+            }),
+        )?;
+    }
 
-    println!("[DB] Database initialized successfully");
+    // Opened after migrations so it sees the current schema
+    let reader = Connection::open_with_flags(
+        &db_path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .map_err(|e| format!("Failed to open read connection: {}", e))?;
+    reader
+        .busy_timeout(BUSY_TIMEOUT)
+        .map_err(|e| format!("Failed to set busy timeout: {}", e))?;
+
+    tracing::info!("[DB] Database initialized successfully");
 
     Ok(DbState {
         conn: Mutex::new(conn),
+        reader: Mutex::new(reader),
     })
 }
+
+/// Whether an error means another connection held the database lock
+fn is_busy(e: &rusqlite::Error) -> bool {
+    matches!(
+        e.sqlite_error_code(),
+        Some(ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked)
+    )
+}
+
+/// Run a write, retrying once more if the database is still locked
+///
+/// The retry goes out right away: callers hold the `DbState` mutex, so sleeping
+/// here would stall every other command, and the busy timeout already waits.
+/// It helps when SQLite reports busy without waiting, to avoid a deadlock.
+pub fn with_retry<T>(mut write: impl FnMut() -> rusqlite::Result<T>) -> rusqlite::Result<T> {
+    for _ in 1..WRITE_ATTEMPTS {
+        match write() {
+            Err(e) if is_busy(&e) => tracing::warn!("[DB] Database is locked, retrying"),
+            result => return result,
+        }
+    }
+    write()
+}
+
+/// Count the rows of every user table, sorted by table name
+pub fn get_table_row_counts(conn: &Connection) -> Result<Vec<(String, i64)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT name FROM sqlite_master
+             WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+             ORDER BY name",
+        )
+        .map_err(|e| format!("Failed to list tables: {}", e))?;
+
+    let tables: Vec<String> = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| format!("Failed to list tables: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    tables
+        .into_iter()
+        .map(|table| {
+            let count: i64 = conn
+                .query_row(
+                    &format!("SELECT COUNT(*) FROM \"{}\"", table.replace('"', "\"\"")),
+                    [],
+                    |row| row.get(0),
+                )
+                .map_err(|e| format!("Failed to count rows in {}: {}", table, e))?;
+            Ok((table, count))
+        })
+        .collect()
+}
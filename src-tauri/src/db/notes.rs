@@ -0,0 +1,211 @@
+// src-tauri/src/db/notes.rs
+//! Task notes and follow-ups
+//!
+//! Notes are the user's own, kept next to a task's transcript rather than in
+//! it. A note can carry a follow-up time; `reminders` emits it once when the
+//! time comes, and `reminded_at` is cleared again when the time is changed.
+//! Times are stored as UTC RFC 3339 so they compare as text.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+/// A note on a task
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskNote {
+    pub id: String,
+    pub task_id: String,
+    pub content: String,
+    pub follow_up_at: Option<String>,
+    /// When the follow-up reminder went out
+    pub reminded_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// A note with a follow-up, and the task it is about
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FollowUp {
+    #[serde(flatten)]
+    pub note: TaskNote,
+    /// The task's title, or its prompt if it has none
+    pub task_title: String,
+}
+
+const NOTE_COLUMNS: &str =
+    "n.id, n.task_id, n.content, n.follow_up_at, n.reminded_at, n.created_at, n.updated_at";
+
+fn row_to_note(row: &rusqlite::Row) -> rusqlite::Result<TaskNote> {
+    Ok(TaskNote {
+        id: row.get(0)?,
+        task_id: row.get(1)?,
+        content: row.get(2)?,
+        follow_up_at: row.get(3)?,
+        reminded_at: row.get(4)?,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+    })
+}
+
+fn row_to_follow_up(row: &rusqlite::Row) -> rusqlite::Result<FollowUp> {
+    Ok(FollowUp {
+        note: row_to_note(row)?,
+        task_title: row.get(7)?,
+    })
+}
+
+/// Check a note's content and bring its follow-up time to UTC
+fn normalize(
+    content: &str,
+    follow_up_at: Option<&str>,
+) -> Result<(String, Option<String>), String> {
+    let content = content.trim();
+    if content.is_empty() {
+        return Err("Note cannot be empty".to_string());
+    }
+    let follow_up_at = follow_up_at
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(|t| {
+            chrono::DateTime::parse_from_rfc3339(t)
+                .map(|time| time.with_timezone(&chrono::Utc).to_rfc3339())
+                .map_err(|e| format!("Invalid follow-up time {}: {}", t, e))
+        })
+        .transpose()?;
+    Ok((content.to_string(), follow_up_at))
+}
+
+/// Get a note
+pub fn get_note(conn: &Connection, id: &str) -> Result<Option<TaskNote>, String> {
+    conn.query_row(
+        &format!("SELECT {} FROM task_notes n WHERE n.id = ?1", NOTE_COLUMNS),
+        [id],
+        row_to_note,
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get note: {}", e))
+}
+
+/// Add a note to a task
+pub fn add_note(
+    conn: &Connection,
+    task_id: &str,
+    content: &str,
+    follow_up_at: Option<&str>,
+) -> Result<TaskNote, String> {
+    let (content, follow_up_at) = normalize(content, follow_up_at)?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let note = TaskNote {
+        id: format!("note_{}", uuid::Uuid::new_v4()),
+        task_id: task_id.to_string(),
+        content,
+        follow_up_at,
+        reminded_at: None,
+        created_at: now.clone(),
+        updated_at: now,
+    };
+    super::with_retry(|| {
+        conn.execute(
+            "INSERT INTO task_notes
+             (id, task_id, content, follow_up_at, reminded_at, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, NULL, ?5, ?6)",
+            params![
+                note.id,
+                note.task_id,
+                note.content,
+                note.follow_up_at,
+                note.created_at,
+                note.updated_at,
+            ],
+        )
+    })
+    .map_err(|e| format!("Failed to add note: {}", e))?;
+    Ok(note)
+}
+
+/// Change a note; a changed follow-up time will be reminded again
+pub fn update_note(
+    conn: &Connection,
+    id: &str,
+    content: &str,
+    follow_up_at: Option<&str>,
+) -> Result<TaskNote, String> {
+    let (content, follow_up_at) = normalize(content, follow_up_at)?;
+    let updated = super::with_retry(|| {
+        conn.execute(
+            "UPDATE task_notes SET content = ?2, updated_at = ?4,
+                 reminded_at = CASE WHEN follow_up_at IS ?3 THEN reminded_at ELSE NULL END,
+                 follow_up_at = ?3
+             WHERE id = ?1",
+            params![id, content, follow_up_at, chrono::Utc::now().to_rfc3339()],
+        )
+    })
+    .map_err(|e| format!("Failed to update note: {}", e))?;
+    if updated == 0 {
+        return Err(format!("Note not found: {}", id));
+    }
+    get_note(conn, id)?.ok_or_else(|| format!("Note not found: {}", id))
+}
+
+/// Delete a note
+pub fn delete_note(conn: &Connection, id: &str) -> Result<(), String> {
+    let deleted = conn
+        .execute("DELETE FROM task_notes WHERE id = ?1", [id])
+        .map_err(|e| format!("Failed to delete note: {}", e))?;
+    if deleted == 0 {
+        return Err(format!("Note not found: {}", id));
+    }
+    Ok(())
+}
+
+/// A task's notes, oldest first
+pub fn get_task_notes(conn: &Connection, task_id: &str) -> Result<Vec<TaskNote>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM task_notes n WHERE n.task_id = ?1 ORDER BY n.created_at ASC",
+            NOTE_COLUMNS
+        ))
+        .map_err(|e| format!("Failed to prepare notes query: {}", e))?;
+    let notes = stmt
+        .query_map([task_id], row_to_note)
+        .map_err(|e| format!("Failed to query notes: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(notes)
+}
+
+/// Follow-ups not reminded yet, soonest first; only those due by `due_by` if given
+pub fn get_pending_follow_ups(
+    conn: &Connection,
+    due_by: Option<&str>,
+) -> Result<Vec<FollowUp>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {}, COALESCE(t.title, t.prompt)
+             FROM task_notes n JOIN tasks t ON t.id = n.task_id
+             WHERE n.follow_up_at IS NOT NULL AND n.reminded_at IS NULL
+               AND (?1 IS NULL OR n.follow_up_at <= ?1)
+             ORDER BY n.follow_up_at ASC",
+            NOTE_COLUMNS
+        ))
+        .map_err(|e| format!("Failed to prepare follow-ups query: {}", e))?;
+    let follow_ups = stmt
+        .query_map([due_by], row_to_follow_up)
+        .map_err(|e| format!("Failed to query follow-ups: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(follow_ups)
+}
+
+/// Record that a follow-up was reminded; false if it already was
+pub fn mark_reminded(conn: &Connection, id: &str, now: &str) -> Result<bool, String> {
+    let updated = super::with_retry(|| {
+        conn.execute(
+            "UPDATE task_notes SET reminded_at = ?2 WHERE id = ?1 AND reminded_at IS NULL",
+            params![id, now],
+        )
+    })
+    .map_err(|e| format!("Failed to mark follow-up reminded: {}", e))?;
+    Ok(updated == 1)
+}
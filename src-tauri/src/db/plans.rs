@@ -0,0 +1,84 @@
+// src-tauri/src/db/plans.rs
+//! Proposed plans for plan-first tasks
+//!
+//! A plan-first task starts with a read-only planning run. The plan it produces
+//! is stored here as `pending` and the task waits until the user approves it,
+//! after which the same OpenCode session continues with execution.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+/// Stored plan for a task
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskPlan {
+    pub task_id: String,
+    pub plan: String,
+    pub session_id: Option<String>,
+    /// `pending`, `approved`, or `discarded` when its task was aborted
+    pub status: String,
+    pub created_at: String,
+    pub approved_at: Option<String>,
+}
+
+/// Store the proposed plan for a task, replacing any earlier proposal
+pub fn save_plan(
+    conn: &Connection,
+    task_id: &str,
+    plan: &str,
+    session_id: Option<&str>,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR REPLACE INTO task_plans (task_id, plan, session_id, status, created_at)
+         VALUES (?1, ?2, ?3, 'pending', ?4)",
+        params![task_id, plan, session_id, chrono::Utc::now().to_rfc3339()],
+    )
+    .map_err(|e| format!("Failed to save task plan: {}", e))?;
+    Ok(())
+}
+
+/// Get the plan for a task
+pub fn get_plan(conn: &Connection, task_id: &str) -> Result<Option<TaskPlan>, String> {
+    conn.query_row(
+        "SELECT task_id, plan, session_id, status, created_at, approved_at
+         FROM task_plans WHERE task_id = ?1",
+        [task_id],
+        |row| {
+            Ok(TaskPlan {
+                task_id: row.get(0)?,
+                plan: row.get(1)?,
+                session_id: row.get(2)?,
+                status: row.get(3)?,
+                created_at: row.get(4)?,
+                approved_at: row.get(5)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get task plan: {}", e))
+}
+
+/// Mark a pending plan as approved
+pub fn approve_plan(conn: &Connection, task_id: &str) -> Result<(), String> {
+    let updated = conn
+        .execute(
+            "UPDATE task_plans SET status = 'approved', approved_at = ?1
+             WHERE task_id = ?2 AND status = 'pending'",
+            params![chrono::Utc::now().to_rfc3339(), task_id],
+        )
+        .map_err(|e| format!("Failed to approve task plan: {}", e))?;
+    if updated == 0 {
+        return Err(format!("No pending plan for task: {}", task_id));
+    }
+    Ok(())
+}
+
+/// Mark a pending plan as discarded, so it can no longer be approved
+pub fn discard_plan(conn: &Connection, task_id: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE task_plans SET status = 'discarded' WHERE task_id = ?1 AND status = 'pending'",
+        [task_id],
+    )
+    .map_err(|e| format!("Failed to discard task plan: {}", e))?;
+    Ok(())
+}
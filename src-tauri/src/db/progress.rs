@@ -0,0 +1,82 @@
+// src-tauri/src/db/progress.rs
+//! Latest progress of each task
+//!
+//! The sidecar's `task_progress` events carry a stage and, when known, the
+//! current step out of the total; the last one of each task is kept here so
+//! progress can be shown again after the window reloads.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// Progress reported by the sidecar
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskProgress {
+    pub stage: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<i64>,
+    /// Known when the agent keeps a todo list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_steps: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Set when stored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+impl TaskProgress {
+    /// Share of the steps done, 0 to 100, when the total is known
+    pub fn percent(&self) -> Option<u64> {
+        match (self.step, self.total_steps) {
+            (Some(step), Some(total)) if total > 0 => {
+                Some((step.clamp(0, total) * 100 / total) as u64)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Store a task's latest progress
+pub fn set_progress(
+    conn: &Connection,
+    task_id: &str,
+    progress: &TaskProgress,
+) -> Result<(), String> {
+    super::with_retry(|| {
+        conn.execute(
+            "INSERT OR REPLACE INTO task_progress
+             (task_id, stage, step, total_steps, label, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                task_id,
+                progress.stage,
+                progress.step,
+                progress.total_steps,
+                progress.label,
+                chrono::Utc::now().to_rfc3339(),
+            ],
+        )
+    })
+    .map_err(|e| format!("Failed to save task progress: {}", e))?;
+    Ok(())
+}
+
+/// Get a task's latest progress
+pub fn get_progress(conn: &Connection, task_id: &str) -> Result<Option<TaskProgress>, String> {
+    conn.query_row(
+        "SELECT stage, step, total_steps, label, updated_at FROM task_progress WHERE task_id = ?1",
+        [task_id],
+        |row| {
+            Ok(TaskProgress {
+                stage: row.get(0)?,
+                step: row.get(1)?,
+                total_steps: row.get(2)?,
+                label: row.get(3)?,
+                updated_at: row.get(4)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get task progress: {}", e))
+}
@@ -0,0 +1,185 @@
+// src-tauri/src/db/project_files.rs
+//! Index of workspace files
+//!
+//! One row per file with its size, modification time (to skip unchanged files
+//! when reindexing), and the symbols declared in it, newline-separated.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Symbols returned with each search result
+const MATCHED_SYMBOLS: usize = 5;
+
+/// A file to store in the index
+pub struct ProjectFileInput {
+    /// Relative to the workspace, with `/` separators
+    pub path: String,
+    pub size: i64,
+    /// Seconds since the Unix epoch
+    pub modified_at: i64,
+    pub symbols: Vec<String>,
+}
+
+/// A file matching a search
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectFileMatch {
+    pub path: String,
+    /// Declared symbols matching the query
+    pub symbols: Vec<String>,
+}
+
+/// When a workspace was last indexed
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectIndexInfo {
+    pub workspace_id: String,
+    pub indexed_at: String,
+    pub file_count: i64,
+}
+
+/// Size and modification time of each indexed file, by path
+pub fn get_file_stamps(
+    conn: &Connection,
+    workspace_id: &str,
+) -> Result<HashMap<String, (i64, i64)>, String> {
+    let mut stmt = conn
+        .prepare("SELECT path, size, modified_at FROM project_files WHERE workspace_id = ?1")
+        .map_err(|e| format!("Failed to prepare file stamps query: {}", e))?;
+    let stamps = stmt
+        .query_map([workspace_id], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))
+        .map_err(|e| format!("Failed to query file stamps: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(stamps)
+}
+
+/// Add or replace a file
+pub fn upsert_file(
+    conn: &Connection,
+    workspace_id: &str,
+    file: &ProjectFileInput,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO project_files (workspace_id, path, size, modified_at, symbols)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT (workspace_id, path) DO UPDATE SET
+            size = excluded.size,
+            modified_at = excluded.modified_at,
+            symbols = excluded.symbols",
+        params![
+            workspace_id,
+            file.path,
+            file.size,
+            file.modified_at,
+            file.symbols.join("\n")
+        ],
+    )
+    .map_err(|e| format!("Failed to index {}: {}", file.path, e))?;
+    Ok(())
+}
+
+/// Remove a file that no longer exists
+pub fn delete_file(conn: &Connection, workspace_id: &str, path: &str) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM project_files WHERE workspace_id = ?1 AND path = ?2",
+        params![workspace_id, path],
+    )
+    .map_err(|e| format!("Failed to remove {} from the index: {}", path, e))?;
+    Ok(())
+}
+
+/// Record that a workspace was indexed
+pub fn set_indexed(
+    conn: &Connection,
+    workspace_id: &str,
+    indexed_at: &str,
+    file_count: i64,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR REPLACE INTO project_indexes (workspace_id, indexed_at, file_count)
+         VALUES (?1, ?2, ?3)",
+        params![workspace_id, indexed_at, file_count],
+    )
+    .map_err(|e| format!("Failed to record project index: {}", e))?;
+    Ok(())
+}
+
+/// When a workspace was last indexed, if it has been
+pub fn get_index_info(
+    conn: &Connection,
+    workspace_id: &str,
+) -> Result<Option<ProjectIndexInfo>, String> {
+    conn.query_row(
+        "SELECT workspace_id, indexed_at, file_count FROM project_indexes WHERE workspace_id = ?1",
+        [workspace_id],
+        |row| {
+            Ok(ProjectIndexInfo {
+                workspace_id: row.get(0)?,
+                indexed_at: row.get(1)?,
+                file_count: row.get(2)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get project index: {}", e))
+}
+
+/// Files whose path or symbols contain every term of the query, case-insensitively
+///
+/// Files whose path contains the whole query come first, then shorter paths.
+pub fn search(
+    conn: &Connection,
+    workspace_id: &str,
+    query: &str,
+    limit: i64,
+) -> Result<Vec<ProjectFileMatch>, String> {
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut sql = "SELECT path, symbols FROM project_files WHERE workspace_id = ?1".to_string();
+    let mut values: Vec<String> = vec![workspace_id.to_string()];
+    for term in &terms {
+        let escaped = term.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        values.push(format!("%{}%", escaped));
+        let n = values.len();
+        sql.push_str(&format!(
+            " AND (lower(path) LIKE ?{n} ESCAPE '\\' OR lower(symbols) LIKE ?{n} ESCAPE '\\')"
+        ));
+    }
+    values.push(query.trim().to_lowercase());
+    sql.push_str(&format!(
+        " ORDER BY instr(lower(path), ?{}) > 0 DESC, length(path), path LIMIT {}",
+        values.len(),
+        limit
+    ));
+
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|e| format!("Failed to prepare project file search: {}", e))?;
+    let matches = stmt
+        .query_map(rusqlite::params_from_iter(values.iter()), |row| {
+            let path: String = row.get(0)?;
+            let symbols: String = row.get(1)?;
+            Ok((path, symbols))
+        })
+        .map_err(|e| format!("Failed to search project files: {}", e))?
+        .filter_map(|r| r.ok())
+        .map(|(path, symbols)| ProjectFileMatch {
+            path,
+            symbols: symbols
+                .lines()
+                .filter(|symbol| {
+                    let symbol = symbol.to_lowercase();
+                    terms.iter().any(|term| symbol.contains(term))
+                })
+                .take(MATCHED_SYMBOLS)
+                .map(str::to_string)
+                .collect(),
+        })
+        .collect();
+    Ok(matches)
+}
@@ -0,0 +1,163 @@
+// src-tauri/src/db/projects.rs
+//! Project folders for tasks
+//!
+//! A project files tasks together, so histories from different codebases
+//! don't interleave in one list. It only groups: the directory, hooks and
+//! settings a task runs with still come from its workspace. A task is in at
+//! most one project, and deleting a project leaves its tasks unfiled.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+/// Longest project name accepted, in characters
+const MAX_NAME_LENGTH: usize = 80;
+
+/// A project and how many tasks in the history are filed under it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Project {
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+    pub task_count: i64,
+}
+
+/// Check a project name and trim it
+fn normalize_name(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Project name cannot be empty".to_string());
+    }
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(format!("Project name is longer than {} characters", MAX_NAME_LENGTH));
+    }
+    Ok(name)
+}
+
+/// Fail if another project already has the name; names compare without regard to case
+fn check_name_free(conn: &Connection, name: &str, except_id: Option<&str>) -> Result<(), String> {
+    let taken: bool = conn
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM projects
+             WHERE name = ?1 COLLATE NOCASE AND id IS NOT ?2",
+            params![name, except_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to check project name: {}", e))?;
+    if taken {
+        return Err(format!("A project named {} already exists", name));
+    }
+    Ok(())
+}
+
+/// Whether a project with the given ID exists
+pub fn project_exists(conn: &Connection, project_id: &str) -> bool {
+    conn.query_row(
+        "SELECT COUNT(*) > 0 FROM projects WHERE id = ?1",
+        [project_id],
+        |row| row.get(0),
+    )
+    .unwrap_or(false)
+}
+
+/// All projects by name, with their task counts (archived tasks left out)
+pub fn list_projects(conn: &Connection) -> Result<Vec<Project>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT p.id, p.name, p.created_at, COUNT(t.id)
+             FROM projects p
+             LEFT JOIN tasks t ON t.project_id = p.id AND t.archived_at IS NULL
+             GROUP BY p.id
+             ORDER BY p.name COLLATE NOCASE",
+        )
+        .map_err(|e| format!("Failed to prepare projects query: {}", e))?;
+    let projects = stmt
+        .query_map([], |row| {
+            Ok(Project {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                created_at: row.get(2)?,
+                task_count: row.get(3)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query projects: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(projects)
+}
+
+/// Create an empty project
+pub fn create_project(conn: &Connection, name: &str) -> Result<Project, String> {
+    let name = normalize_name(name)?;
+    check_name_free(conn, name, None)?;
+    let project = Project {
+        id: format!("proj_{}", uuid::Uuid::new_v4()),
+        name: name.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        task_count: 0,
+    };
+    super::with_retry(|| {
+        conn.execute(
+            "INSERT INTO projects (id, name, created_at) VALUES (?1, ?2, ?3)",
+            params![project.id, project.name, project.created_at],
+        )
+    })
+    .map_err(|e| format!("Failed to create project: {}", e))?;
+    Ok(project)
+}
+
+/// Rename a project
+pub fn rename_project(conn: &Connection, project_id: &str, name: &str) -> Result<(), String> {
+    let name = normalize_name(name)?;
+    check_name_free(conn, name, Some(project_id))?;
+    let updated = super::with_retry(|| {
+        conn.execute(
+            "UPDATE projects SET name = ?2 WHERE id = ?1",
+            params![project_id, name],
+        )
+    })
+    .map_err(|e| format!("Failed to rename project: {}", e))?;
+    if updated == 0 {
+        return Err(format!("Project not found: {}", project_id));
+    }
+    Ok(())
+}
+
+/// Delete a project; its tasks are kept, unfiled
+pub fn delete_project(conn: &Connection, project_id: &str) -> Result<(), String> {
+    super::with_retry(|| conn.execute("DELETE FROM projects WHERE id = ?1", [project_id]))
+        .map_err(|e| format!("Failed to delete project: {}", e))?;
+    Ok(())
+}
+
+/// The project a task is filed under
+pub fn get_task_project(conn: &Connection, task_id: &str) -> Result<Option<String>, String> {
+    conn.query_row("SELECT project_id FROM tasks WHERE id = ?1", [task_id], |row| row.get(0))
+        .optional()
+        .map(Option::flatten)
+        .map_err(|e| format!("Failed to get task project: {}", e))
+}
+
+/// File tasks under a project, or unfile them with `None`; returns how many were moved
+pub fn move_tasks(
+    conn: &Connection,
+    task_ids: &[String],
+    project_id: Option<&str>,
+) -> Result<usize, String> {
+    if let Some(project_id) = project_id {
+        if !project_exists(conn, project_id) {
+            return Err(format!("Project not found: {}", project_id));
+        }
+    }
+    let mut moved = 0;
+    for task_id in task_ids {
+        moved += super::with_retry(|| {
+            conn.execute(
+                "UPDATE tasks SET project_id = ?2 WHERE id = ?1",
+                params![task_id, project_id],
+            )
+        })
+        .map_err(|e| format!("Failed to move task: {}", e))?;
+    }
+    Ok(moved)
+}
@@ -1,7 +1,7 @@
 // src-tauri/src/db/providers.rs
 //! Provider settings repository
 
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -52,6 +52,52 @@ pub struct AvailableModel {
     pub name: String,
 }
 
+/// Reasoning effort levels accepted by `ModelParameters`
+pub const REASONING_EFFORTS: &[&str] = &["minimal", "low", "medium", "high"];
+
+/// Generation parameters overriding a model's defaults; unset ones keep the default
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelParameters {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    /// Most tokens the model may generate per response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<i64>,
+    /// One of `REASONING_EFFORTS`, for models that reason
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<String>,
+}
+
+impl ModelParameters {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Check the values are in range
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(temperature) = self.temperature.filter(|t| !(0.0..=2.0).contains(t)) {
+            return Err(format!("Temperature must be between 0 and 2, got {}", temperature));
+        }
+        if let Some(top_p) = self.top_p.filter(|p| !(0.0..=1.0).contains(p)) {
+            return Err(format!("Top P must be between 0 and 1, got {}", top_p));
+        }
+        if let Some(max_tokens) = self.max_tokens.filter(|t| *t <= 0) {
+            return Err(format!("Max tokens must be positive, got {}", max_tokens));
+        }
+        match self.reasoning_effort.as_deref() {
+            Some(effort) if !REASONING_EFFORTS.contains(&effort) => Err(format!(
+                "Unknown reasoning effort: {} (expected one of {})",
+                effort,
+                REASONING_EFFORTS.join(", ")
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
 /// Get all provider settings
 pub fn get_provider_settings(conn: &Connection) -> ProviderSettings {
     // Get provider meta
@@ -312,3 +358,81 @@ pub fn get_connected_provider_ids(conn: &Connection) -> Vec<String> {
         .filter_map(|r| r.ok())
         .collect()
 }
+
+/// Get the parameter overrides of a model
+pub fn get_model_parameters(
+    conn: &Connection,
+    model_id: &str,
+) -> Result<Option<ModelParameters>, String> {
+    conn.query_row(
+        "SELECT temperature, top_p, max_tokens, reasoning_effort
+         FROM model_parameters WHERE model_id = ?1",
+        [model_id],
+        |row| {
+            Ok(ModelParameters {
+                temperature: row.get(0)?,
+                top_p: row.get(1)?,
+                max_tokens: row.get(2)?,
+                reasoning_effort: row.get(3)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get model parameters: {}", e))
+}
+
+/// Get the parameter overrides of every model that has some, keyed by model ID
+pub fn list_model_parameters(
+    conn: &Connection,
+) -> Result<HashMap<String, ModelParameters>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT model_id, temperature, top_p, max_tokens, reasoning_effort
+             FROM model_parameters",
+        )
+        .map_err(|e| format!("Failed to prepare model parameters query: {}", e))?;
+    let parameters = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get(0)?,
+                ModelParameters {
+                    temperature: row.get(1)?,
+                    top_p: row.get(2)?,
+                    max_tokens: row.get(3)?,
+                    reasoning_effort: row.get(4)?,
+                },
+            ))
+        })
+        .map_err(|e| format!("Failed to query model parameters: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(parameters)
+}
+
+/// Replace a model's parameter overrides; empty overrides are removed
+pub fn set_model_parameters(
+    conn: &Connection,
+    model_id: &str,
+    parameters: &ModelParameters,
+) -> Result<(), String> {
+    if parameters.is_empty() {
+        conn.execute("DELETE FROM model_parameters WHERE model_id = ?1", [model_id])
+            .map_err(|e| format!("Failed to clear model parameters: {}", e))?;
+        return Ok(());
+    }
+    conn.execute(
+        "INSERT OR REPLACE INTO model_parameters
+         (model_id, temperature, top_p, max_tokens, reasoning_effort, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            model_id,
+            parameters.temperature,
+            parameters.top_p,
+            parameters.max_tokens,
+            parameters.reasoning_effort,
+            chrono::Utc::now().to_rfc3339(),
+        ],
+    )
+    .map_err(|e| format!("Failed to set model parameters: {}", e))?;
+    Ok(())
+}
@@ -0,0 +1,172 @@
+// src-tauri/src/db/ratings.rs
+//! Ratings of assistant messages
+//!
+//! A thumbs-up or thumbs-down, with an optional comment, on a message the
+//! agent wrote. The provider and model of the task's latest step are recorded
+//! with the rating, so ratings can be compared across models and prompts.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Values of a rating
+pub const RATINGS: &[&str] = &["up", "down"];
+
+/// A rating of an assistant message
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageRating {
+    pub message_id: String,
+    /// "up" or "down"
+    pub rating: String,
+    pub comment: Option<String>,
+    pub provider_id: Option<String>,
+    pub model_id: Option<String>,
+    pub rated_at: String,
+}
+
+/// A rating with the task the message belongs to
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RatedMessage {
+    #[serde(flatten)]
+    pub rating: MessageRating,
+    pub task_id: String,
+    pub task_prompt: String,
+    /// The rated message's content
+    pub content: String,
+}
+
+/// Ratings of a task's messages by message ID
+pub type TaskRatings = HashMap<String, MessageRating>;
+
+const RATING_COLUMNS: &str =
+    "r.message_id, r.rating, r.comment, r.provider_id, r.model_id, r.rated_at";
+
+fn row_to_rating(row: &rusqlite::Row) -> rusqlite::Result<MessageRating> {
+    Ok(MessageRating {
+        message_id: row.get(0)?,
+        rating: row.get(1)?,
+        comment: row.get(2)?,
+        provider_id: row.get(3)?,
+        model_id: row.get(4)?,
+        rated_at: row.get(5)?,
+    })
+}
+
+/// Rate an assistant message, replacing its rating; no rating removes it
+pub fn rate_message(
+    conn: &Connection,
+    message_id: &str,
+    rating: Option<&str>,
+    comment: Option<&str>,
+) -> Result<Option<MessageRating>, String> {
+    let message: Option<(String, String)> = conn
+        .query_row(
+            "SELECT task_id, type FROM task_messages WHERE id = ?1",
+            [message_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get message: {}", e))?;
+    let Some((task_id, msg_type)) = message else {
+        return Err(format!("Message not found: {}", message_id));
+    };
+    if msg_type != "assistant" {
+        return Err("Only assistant messages can be rated".to_string());
+    }
+
+    let Some(rating) = rating else {
+        super::with_retry(|| {
+            conn.execute("DELETE FROM message_ratings WHERE message_id = ?1", [message_id])
+        })
+        .map_err(|e| format!("Failed to remove rating: {}", e))?;
+        return Ok(None);
+    };
+    if !RATINGS.contains(&rating) {
+        return Err(format!("Unknown rating: {}", rating));
+    }
+
+    let (provider_id, model_id): (Option<String>, Option<String>) = conn
+        .query_row(
+            "SELECT provider_id, model_id FROM task_usage WHERE task_id = ?1
+             ORDER BY id DESC LIMIT 1",
+            [&task_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get task model: {}", e))?
+        .unwrap_or_default();
+    let rated = MessageRating {
+        message_id: message_id.to_string(),
+        rating: rating.to_string(),
+        comment: comment.map(str::trim).filter(|c| !c.is_empty()).map(str::to_string),
+        provider_id,
+        model_id,
+        rated_at: chrono::Utc::now().to_rfc3339(),
+    };
+    super::with_retry(|| {
+        conn.execute(
+            "INSERT OR REPLACE INTO message_ratings
+             (message_id, rating, comment, provider_id, model_id, rated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                rated.message_id,
+                rated.rating,
+                rated.comment,
+                rated.provider_id,
+                rated.model_id,
+                rated.rated_at,
+            ],
+        )
+    })
+    .map_err(|e| format!("Failed to rate message: {}", e))?;
+    Ok(Some(rated))
+}
+
+/// Ratings of a task's messages
+pub fn get_task_ratings(conn: &Connection, task_id: &str) -> Result<TaskRatings, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM message_ratings r
+             JOIN task_messages m ON m.id = r.message_id
+             WHERE m.task_id = ?1",
+            RATING_COLUMNS
+        ))
+        .map_err(|e| format!("Failed to prepare ratings query: {}", e))?;
+    let ratings = stmt
+        .query_map([task_id], row_to_rating)
+        .map_err(|e| format!("Failed to query ratings: {}", e))?
+        .filter_map(|r| r.ok())
+        .map(|rating| (rating.message_id.clone(), rating))
+        .collect();
+    Ok(ratings)
+}
+
+/// Every rating with its task, newest first; only those with `rating` if given
+pub fn list_ratings(conn: &Connection, rating: Option<&str>) -> Result<Vec<RatedMessage>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {}, m.task_id, t.prompt, m.content
+             FROM message_ratings r
+             JOIN task_messages m ON m.id = r.message_id
+             JOIN tasks t ON t.id = m.task_id
+             WHERE ?1 IS NULL OR r.rating = ?1
+             ORDER BY r.rated_at DESC",
+            RATING_COLUMNS
+        ))
+        .map_err(|e| format!("Failed to prepare ratings query: {}", e))?;
+    let ratings = stmt
+        .query_map([rating], |row| {
+            Ok(RatedMessage {
+                rating: row_to_rating(row)?,
+                task_id: row.get(6)?,
+                task_prompt: row.get(7)?,
+                content: row.get(8)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query ratings: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(ratings)
+}
@@ -0,0 +1,72 @@
+// src-tauri/src/db/reruns.rs
+//! Task re-run links
+//!
+//! `rerun_task` starts a fresh task with another task's prompt, directory,
+//! model and settings. Each re-run records the task it repeats, so results of
+//! the same prompt can be traced back and compared.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+/// Which task a re-run repeats
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskRerun {
+    pub task_id: String,
+    /// Unset once the parent task is deleted
+    pub parent_task_id: Option<String>,
+    pub created_at: String,
+}
+
+/// The task a task re-runs (if it is a re-run) and the re-runs made of it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskReruns {
+    pub parent: Option<TaskRerun>,
+    pub children: Vec<TaskRerun>,
+}
+
+fn row_to_rerun(row: &rusqlite::Row) -> rusqlite::Result<TaskRerun> {
+    Ok(TaskRerun {
+        task_id: row.get(0)?,
+        parent_task_id: row.get(1)?,
+        created_at: row.get(2)?,
+    })
+}
+
+/// Record that `task_id` re-runs `parent_task_id`
+pub fn record_rerun(conn: &Connection, task_id: &str, parent_task_id: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO task_reruns (task_id, parent_task_id, created_at) VALUES (?1, ?2, ?3)",
+        params![task_id, parent_task_id, chrono::Utc::now().to_rfc3339()],
+    )
+    .map_err(|e| format!("Failed to record re-run: {}", e))?;
+    Ok(())
+}
+
+/// Get the re-run links of a task
+pub fn get_reruns(conn: &Connection, task_id: &str) -> Result<TaskReruns, String> {
+    let parent = conn
+        .query_row(
+            "SELECT task_id, parent_task_id, created_at FROM task_reruns WHERE task_id = ?1",
+            [task_id],
+            row_to_rerun,
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get task re-run: {}", e))?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT task_id, parent_task_id, created_at
+             FROM task_reruns WHERE parent_task_id = ?1
+             ORDER BY created_at ASC",
+        )
+        .map_err(|e| format!("Failed to prepare re-run query: {}", e))?;
+    let children = stmt
+        .query_map([task_id], row_to_rerun)
+        .map_err(|e| format!("Failed to query re-runs: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(TaskReruns { parent, children })
+}
@@ -0,0 +1,117 @@
+// src-tauri/src/db/search.rs
+//! Full-text search over task history
+//!
+//! `task_search` indexes each task's prompt, summary, and title, and
+//! `message_search` the content of its messages. Both are FTS5 tables over
+//! the rows of `tasks` and `task_messages`, kept current by triggers and keyed
+//! by rowid; `VACUUM INTO` can renumber rowids, so backups rebuild theirs.
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+/// Hits returned when no limit is given
+const DEFAULT_LIMIT: u32 = 50;
+
+/// Tokens of context around the matched terms in a snippet
+const SNIPPET_TOKENS: i32 = 16;
+
+/// Marks the start of a matched term in a snippet; text never contains it
+pub const MATCH_START: &str = "\u{2}";
+
+/// Marks the end of a matched term in a snippet
+pub const MATCH_END: &str = "\u{3}";
+
+/// A task or message matching a search
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub task_id: String,
+    pub task_title: Option<String>,
+    pub task_status: String,
+    pub task_created_at: String,
+    /// The matching message; unset when the prompt, summary, or title matched
+    pub message_id: Option<String>,
+    pub message_type: Option<String>,
+    /// Text around the match, with matched terms between `MATCH_START` and `MATCH_END`
+    pub snippet: String,
+    /// BM25 score; lower is a better match
+    pub rank: f64,
+}
+
+/// Turn what the user typed into an FTS5 query: every word must appear, as a prefix
+///
+/// Words are quoted so FTS5 operators and punctuation are searched as text.
+fn match_expression(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// Tasks and messages matching a query, best first
+pub fn search_tasks(
+    conn: &Connection,
+    query: &str,
+    limit: Option<u32>,
+) -> Result<Vec<SearchHit>, String> {
+    let Some(expression) = match_expression(query) else {
+        return Ok(Vec::new());
+    };
+    let sql = format!(
+        "SELECT t.id, t.title, t.status, t.created_at, NULL, NULL,
+                snippet(task_search, -1, ?3, ?4, '…', {tokens}), bm25(task_search) AS rank
+         FROM task_search JOIN tasks t ON t.rowid = task_search.rowid
+         WHERE task_search MATCH ?1
+         UNION ALL
+         SELECT t.id, t.title, t.status, t.created_at, m.id, m.type,
+                snippet(message_search, 0, ?3, ?4, '…', {tokens}), bm25(message_search)
+         FROM message_search
+         JOIN task_messages m ON m.rowid = message_search.rowid
+         JOIN tasks t ON t.id = m.task_id
+         WHERE message_search MATCH ?1 AND m.archived_at IS NULL
+         ORDER BY rank ASC
+         LIMIT ?2",
+        tokens = SNIPPET_TOKENS
+    );
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|e| format!("Failed to prepare search: {}", e))?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+    let hits = stmt
+        .query_map(params![expression, limit, MATCH_START, MATCH_END], |row| {
+            Ok(SearchHit {
+                task_id: row.get(0)?,
+                task_title: row.get(1)?,
+                task_status: row.get(2)?,
+                task_created_at: row.get(3)?,
+                message_id: row.get(4)?,
+                message_type: row.get(5)?,
+                snippet: row.get(6)?,
+                rank: row.get(7)?,
+            })
+        })
+        .map_err(|e| format!("Failed to search tasks: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(hits)
+}
+
+/// Index the tasks and messages as they are now; nothing to do before the indexes exist
+pub fn rebuild(conn: &Connection) -> Result<(), String> {
+    let indexed: bool = conn
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'task_search'",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to look up search index: {}", e))?;
+    if !indexed {
+        return Ok(());
+    }
+    conn.execute_batch(
+        "INSERT INTO task_search (task_search) VALUES ('rebuild');
+         INSERT INTO message_search (message_search) VALUES ('rebuild');",
+    )
+    .map_err(|e| format!("Failed to rebuild search index: {}", e))
+}
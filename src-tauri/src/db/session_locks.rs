@@ -0,0 +1,97 @@
+// src-tauri/src/db/session_locks.rs
+//! Leases on OpenCode sessions
+//!
+//! Only one run may continue a session at a time. A lock is a lease: it names
+//! the task and app instance (`holder`) continuing the session and expires
+//! unless the holder renews it, so a lock left by a crashed instance frees
+//! itself. Times are RFC 3339 in UTC, which compare as strings.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+/// A session's current lease
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionLock {
+    pub session_id: String,
+    pub task_id: String,
+    pub holder: String,
+    pub acquired_at: String,
+    pub expires_at: String,
+}
+
+/// Get the lease on a session, expired or not
+pub fn get_lock(conn: &Connection, session_id: &str) -> Result<Option<SessionLock>, String> {
+    conn.query_row(
+        "SELECT session_id, task_id, holder, acquired_at, expires_at
+         FROM session_locks WHERE session_id = ?1",
+        [session_id],
+        |row| {
+            Ok(SessionLock {
+                session_id: row.get(0)?,
+                task_id: row.get(1)?,
+                holder: row.get(2)?,
+                acquired_at: row.get(3)?,
+                expires_at: row.get(4)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get session lock: {}", e))
+}
+
+/// Take the lease on a session unless someone holds an unexpired one
+///
+/// Returns the lease that blocked it, `None` when the lease was taken.
+pub fn try_acquire(
+    conn: &Connection,
+    session_id: &str,
+    task_id: &str,
+    holder: &str,
+    now: &str,
+    expires_at: &str,
+) -> Result<Option<SessionLock>, String> {
+    let taken = super::with_retry(|| {
+        conn.execute(
+            "INSERT INTO session_locks (session_id, task_id, holder, acquired_at, expires_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(session_id) DO UPDATE SET
+                 task_id = excluded.task_id,
+                 holder = excluded.holder,
+                 acquired_at = excluded.acquired_at,
+                 expires_at = excluded.expires_at
+             WHERE session_locks.expires_at <= excluded.acquired_at",
+            params![session_id, task_id, holder, now, expires_at],
+        )
+    })
+    .map_err(|e| format!("Failed to acquire session lock: {}", e))?;
+    if taken == 1 {
+        return Ok(None);
+    }
+    get_lock(conn, session_id)
+}
+
+/// Extend every lease a holder has
+pub fn renew(conn: &Connection, holder: &str, expires_at: &str) -> Result<usize, String> {
+    super::with_retry(|| {
+        conn.execute(
+            "UPDATE session_locks SET expires_at = ?1 WHERE holder = ?2",
+            params![expires_at, holder],
+        )
+    })
+    .map_err(|e| format!("Failed to renew session locks: {}", e))
+}
+
+/// Release the leases a task holds
+pub fn release_task(conn: &Connection, task_id: &str) -> Result<(), String> {
+    super::with_retry(|| conn.execute("DELETE FROM session_locks WHERE task_id = ?1", [task_id]))
+        .map_err(|e| format!("Failed to release session lock: {}", e))?;
+    Ok(())
+}
+
+/// Release every lease a holder has
+pub fn release_holder(conn: &Connection, holder: &str) -> Result<(), String> {
+    super::with_retry(|| conn.execute("DELETE FROM session_locks WHERE holder = ?1", [holder]))
+        .map_err(|e| format!("Failed to release session locks: {}", e))?;
+    Ok(())
+}
@@ -87,6 +87,114 @@ pub struct AzureFoundryConfig {
     pub last_validated: Option<u64>,
 }
 
+/// Local API server settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiServerSettings {
+    pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub websocket_enabled: bool,
+    /// Serve `/metrics` in Prometheus text format
+    #[serde(default)]
+    pub metrics_enabled: bool,
+}
+
+/// End-to-end encrypted sync settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct E2eSyncSettings {
+    pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relay_url: Option<String>,
+}
+
+/// A spend limit over a calendar period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetLimit {
+    /// "day" or "month"
+    pub period: String,
+    /// Limit in USD
+    pub amount: f64,
+    /// Provider the limit applies to (the model ID prefix, e.g. "anthropic");
+    /// `None` limits spend across all providers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_id: Option<String>,
+}
+
+/// Web search available to the agent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSearchSettings {
+    /// Used for tasks that don't set `allowWebSearch`
+    pub enabled_by_default: bool,
+    /// "fetch" (OpenCode's page fetching, no search engine), "exa" (OpenCode's
+    /// built-in search) or "brave" (Brave Search MCP server)
+    pub backend: String,
+}
+
+impl Default for WebSearchSettings {
+    fn default() -> Self {
+        Self {
+            enabled_by_default: true,
+            backend: "fetch".to_string(),
+        }
+    }
+}
+
+/// Schedule of automatic database backups
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupSettings {
+    pub enabled: bool,
+    /// Hours between automatic backups
+    pub interval_hours: u32,
+    /// Automatic backups kept; older ones are deleted
+    pub keep: u32,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_hours: 24,
+            keep: 7,
+        }
+    }
+}
+
+/// Main window bounds saved at exit, in physical pixels
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowState {
+    /// Inner size
+    pub width: u32,
+    pub height: u32,
+    /// Outer position
+    pub x: i32,
+    pub y: i32,
+    /// Bounds are the ones from before the window was maximized
+    pub maximized: bool,
+}
+
+/// A database from a newer version of the app that was set aside at startup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DowngradeNotice {
+    /// The backup it was kept as
+    pub backup_id: String,
+    /// Its schema version
+    pub schema_version: i32,
+}
+
+/// Values of the `cli_runtime` setting
+pub const CLI_RUNTIMES: &[&str] = &["system", "bundled"];
+
+/// Search backends the sidecar can configure
+pub const WEB_SEARCH_BACKENDS: &[&str] = &["fetch", "exa", "brave"];
+
 /// Get app settings
 pub fn get_app_settings(conn: &Connection) -> AppSettings {
     let result = conn.query_row(
@@ -274,3 +382,352 @@ pub fn set_azure_foundry_config(
     .map_err(|e| format!("Failed to set Azure Foundry config: {}", e))?;
     Ok(())
 }
+
+/// Get the stored log level
+pub fn get_log_level(conn: &Connection) -> Option<String> {
+    conn.query_row(
+        "SELECT log_level FROM app_settings WHERE id = 1",
+        [],
+        |row| row.get(0),
+    )
+    .ok()
+    .flatten()
+}
+
+/// Set the stored log level
+pub fn set_log_level(conn: &Connection, level: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE app_settings SET log_level = ?1 WHERE id = 1",
+        params![level],
+    )
+    .map_err(|e| format!("Failed to set log level: {}", e))?;
+    Ok(())
+}
+
+/// Which OpenCode CLI to prefer, "system" unless set
+pub fn get_cli_runtime(conn: &Connection) -> String {
+    conn.query_row(
+        "SELECT cli_runtime FROM app_settings WHERE id = 1",
+        [],
+        |row| row.get::<_, Option<String>>(0),
+    )
+    .ok()
+    .flatten()
+    .unwrap_or_else(|| "system".to_string())
+}
+
+/// Set which OpenCode CLI to prefer
+pub fn set_cli_runtime(conn: &Connection, runtime: &str) -> Result<(), String> {
+    if !CLI_RUNTIMES.contains(&runtime) {
+        return Err(format!("Unknown CLI runtime: {}", runtime));
+    }
+    conn.execute(
+        "UPDATE app_settings SET cli_runtime = ?1 WHERE id = 1",
+        params![runtime],
+    )
+    .map_err(|e| format!("Failed to set CLI runtime: {}", e))?;
+    Ok(())
+}
+
+/// Model used for task summaries; unset uses the task's model
+pub fn get_summary_model_id(conn: &Connection) -> Option<String> {
+    conn.query_row(
+        "SELECT summary_model_id FROM app_settings WHERE id = 1",
+        [],
+        |row| row.get(0),
+    )
+    .ok()
+    .flatten()
+}
+
+/// Set the model used for task summaries
+pub fn set_summary_model_id(conn: &Connection, model_id: Option<&str>) -> Result<(), String> {
+    conn.execute(
+        "UPDATE app_settings SET summary_model_id = ?1 WHERE id = 1",
+        params![model_id],
+    )
+    .map_err(|e| format!("Failed to set summary model: {}", e))?;
+    Ok(())
+}
+
+/// Largest file that can be attached, in bytes; unset uses the default
+pub fn get_max_attachment_bytes(conn: &Connection) -> Option<u64> {
+    conn.query_row(
+        "SELECT max_attachment_bytes FROM app_settings WHERE id = 1",
+        [],
+        |row| row.get::<_, Option<i64>>(0),
+    )
+    .ok()
+    .flatten()
+    .map(|bytes| bytes.max(0) as u64)
+}
+
+/// Set the largest file that can be attached; `None` restores the default
+pub fn set_max_attachment_bytes(conn: &Connection, bytes: Option<u64>) -> Result<(), String> {
+    conn.execute(
+        "UPDATE app_settings SET max_attachment_bytes = ?1 WHERE id = 1",
+        params![bytes.map(|b| b.min(i64::MAX as u64) as i64)],
+    )
+    .map_err(|e| format!("Failed to set attachment size limit: {}", e))?;
+    Ok(())
+}
+
+/// Get local API server settings
+pub fn get_api_server_settings(conn: &Connection) -> ApiServerSettings {
+    conn.query_row(
+        "SELECT api_server_enabled, api_server_port, api_server_websocket_enabled,
+                api_server_metrics_enabled
+         FROM app_settings WHERE id = 1",
+        [],
+        |row| {
+            let enabled: i32 = row.get(0)?;
+            let port: Option<u16> = row.get(1)?;
+            let websocket_enabled: i32 = row.get(2)?;
+            let metrics_enabled: i32 = row.get(3)?;
+            Ok(ApiServerSettings {
+                enabled: enabled == 1,
+                port,
+                websocket_enabled: websocket_enabled == 1,
+                metrics_enabled: metrics_enabled == 1,
+            })
+        },
+    )
+    .unwrap_or_default()
+}
+
+/// Set local API server settings
+pub fn set_api_server_settings(conn: &Connection, settings: &ApiServerSettings) -> Result<(), String> {
+    conn.execute(
+        "UPDATE app_settings
+         SET api_server_enabled = ?1, api_server_port = ?2, api_server_websocket_enabled = ?3,
+             api_server_metrics_enabled = ?4
+         WHERE id = 1",
+        params![
+            if settings.enabled { 1 } else { 0 },
+            settings.port,
+            if settings.websocket_enabled { 1 } else { 0 },
+            if settings.metrics_enabled { 1 } else { 0 },
+        ],
+    )
+    .map_err(|e| format!("Failed to set API server settings: {}", e))?;
+    Ok(())
+}
+
+/// Get the folder task history is mirrored into, if any
+pub fn get_sync_folder(conn: &Connection) -> Option<String> {
+    conn.query_row(
+        "SELECT sync_folder FROM app_settings WHERE id = 1",
+        [],
+        |row| row.get(0),
+    )
+    .ok()
+    .flatten()
+}
+
+/// Set or clear the sync folder
+pub fn set_sync_folder(conn: &Connection, folder: Option<&str>) -> Result<(), String> {
+    conn.execute(
+        "UPDATE app_settings SET sync_folder = ?1 WHERE id = 1",
+        params![folder],
+    )
+    .map_err(|e| format!("Failed to set sync folder: {}", e))?;
+    Ok(())
+}
+
+/// Get end-to-end encrypted sync settings
+pub fn get_e2e_sync_settings(conn: &Connection) -> E2eSyncSettings {
+    conn.query_row(
+        "SELECT e2e_sync_enabled, e2e_sync_relay_url FROM app_settings WHERE id = 1",
+        [],
+        |row| {
+            let enabled: i32 = row.get(0)?;
+            Ok(E2eSyncSettings {
+                enabled: enabled == 1,
+                relay_url: row.get(1)?,
+            })
+        },
+    )
+    .unwrap_or_default()
+}
+
+/// Set end-to-end encrypted sync settings
+pub fn set_e2e_sync_settings(conn: &Connection, settings: &E2eSyncSettings) -> Result<(), String> {
+    conn.execute(
+        "UPDATE app_settings SET e2e_sync_enabled = ?1, e2e_sync_relay_url = ?2 WHERE id = 1",
+        params![if settings.enabled { 1 } else { 0 }, settings.relay_url],
+    )
+    .map_err(|e| format!("Failed to set encrypted sync settings: {}", e))?;
+    Ok(())
+}
+
+/// Get configured budget limits
+pub fn get_budget_limits(conn: &Connection) -> Vec<BudgetLimit> {
+    conn.query_row(
+        "SELECT budget_limits FROM app_settings WHERE id = 1",
+        [],
+        |row| {
+            let json: Option<String> = row.get(0)?;
+            Ok(json.and_then(|s| serde_json::from_str(&s).ok()))
+        },
+    )
+    .ok()
+    .flatten()
+    .unwrap_or_default()
+}
+
+/// Replace the configured budget limits
+pub fn set_budget_limits(conn: &Connection, limits: &[BudgetLimit]) -> Result<(), String> {
+    let json = serde_json::to_string(limits)
+        .map_err(|e| format!("Failed to serialize budget limits: {}", e))?;
+    conn.execute(
+        "UPDATE app_settings SET budget_limits = ?1 WHERE id = 1",
+        params![json],
+    )
+    .map_err(|e| format!("Failed to set budget limits: {}", e))?;
+    Ok(())
+}
+
+/// Get web search settings
+pub fn get_web_search_settings(conn: &Connection) -> WebSearchSettings {
+    conn.query_row(
+        "SELECT web_search_default, web_search_backend FROM app_settings WHERE id = 1",
+        [],
+        |row| {
+            let enabled: i32 = row.get(0)?;
+            Ok(WebSearchSettings {
+                enabled_by_default: enabled == 1,
+                backend: row.get(1)?,
+            })
+        },
+    )
+    .unwrap_or_default()
+}
+
+/// Set web search settings
+pub fn set_web_search_settings(conn: &Connection, settings: &WebSearchSettings) -> Result<(), String> {
+    if !WEB_SEARCH_BACKENDS.contains(&settings.backend.as_str()) {
+        return Err(format!("Unknown web search backend: {}", settings.backend));
+    }
+    conn.execute(
+        "UPDATE app_settings SET web_search_default = ?1, web_search_backend = ?2 WHERE id = 1",
+        params![if settings.enabled_by_default { 1 } else { 0 }, settings.backend],
+    )
+    .map_err(|e| format!("Failed to set web search settings: {}", e))?;
+    Ok(())
+}
+
+/// Get the saved main window bounds
+pub fn get_window_state(conn: &Connection) -> Option<WindowState> {
+    conn.query_row(
+        "SELECT window_state FROM app_settings WHERE id = 1",
+        [],
+        |row| {
+            let json: Option<String> = row.get(0)?;
+            Ok(json.and_then(|s| serde_json::from_str(&s).ok()))
+        },
+    )
+    .ok()
+    .flatten()
+}
+
+/// Save the main window bounds
+pub fn set_window_state(conn: &Connection, state: &WindowState) -> Result<(), String> {
+    let json = serde_json::to_string(state)
+        .map_err(|e| format!("Failed to serialize window state: {}", e))?;
+    conn.execute(
+        "UPDATE app_settings SET window_state = ?1 WHERE id = 1",
+        params![json],
+    )
+    .map_err(|e| format!("Failed to set window state: {}", e))?;
+    Ok(())
+}
+
+/// Get the workspace open when the app last ran
+pub fn get_last_workspace_id(conn: &Connection) -> Option<String> {
+    conn.query_row(
+        "SELECT last_workspace_id FROM app_settings WHERE id = 1",
+        [],
+        |row| row.get(0),
+    )
+    .ok()
+    .flatten()
+}
+
+/// Set or clear the open workspace
+pub fn set_last_workspace_id(conn: &Connection, workspace_id: Option<&str>) -> Result<(), String> {
+    conn.execute(
+        "UPDATE app_settings SET last_workspace_id = ?1 WHERE id = 1",
+        params![workspace_id],
+    )
+    .map_err(|e| format!("Failed to set last workspace: {}", e))?;
+    Ok(())
+}
+
+/// Get the automatic backup schedule
+pub fn get_backup_settings(conn: &Connection) -> BackupSettings {
+    conn.query_row(
+        "SELECT backup_enabled, backup_interval_hours, backup_keep FROM app_settings WHERE id = 1",
+        [],
+        |row| {
+            let enabled: i32 = row.get(0)?;
+            Ok(BackupSettings {
+                enabled: enabled == 1,
+                interval_hours: row.get(1)?,
+                keep: row.get(2)?,
+            })
+        },
+    )
+    .unwrap_or_default()
+}
+
+/// Set the automatic backup schedule
+pub fn set_backup_settings(conn: &Connection, settings: &BackupSettings) -> Result<(), String> {
+    if settings.interval_hours == 0 {
+        return Err("Backup interval must be at least one hour".to_string());
+    }
+    if settings.keep == 0 {
+        return Err("At least one backup must be kept".to_string());
+    }
+    conn.execute(
+        "UPDATE app_settings SET backup_enabled = ?1, backup_interval_hours = ?2, backup_keep = ?3
+         WHERE id = 1",
+        params![
+            if settings.enabled { 1 } else { 0 },
+            settings.interval_hours,
+            settings.keep
+        ],
+    )
+    .map_err(|e| format!("Failed to set backup settings: {}", e))?;
+    Ok(())
+}
+
+/// Get the newer database set aside at startup, until its tasks are imported or it is dismissed
+pub fn get_downgrade_notice(conn: &Connection) -> Option<DowngradeNotice> {
+    conn.query_row(
+        "SELECT downgrade_notice FROM app_settings WHERE id = 1",
+        [],
+        |row| {
+            let json: Option<String> = row.get(0)?;
+            Ok(json.and_then(|s| serde_json::from_str(&s).ok()))
+        },
+    )
+    .ok()
+    .flatten()
+}
+
+/// Record or clear the newer database set aside at startup
+pub fn set_downgrade_notice(
+    conn: &Connection,
+    notice: Option<&DowngradeNotice>,
+) -> Result<(), String> {
+    let json = notice
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| format!("Failed to serialize downgrade notice: {}", e))?;
+    conn.execute(
+        "UPDATE app_settings SET downgrade_notice = ?1 WHERE id = 1",
+        params![json],
+    )
+    .map_err(|e| format!("Failed to set downgrade notice: {}", e))?;
+    Ok(())
+}
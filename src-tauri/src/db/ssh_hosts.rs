@@ -0,0 +1,139 @@
+// src-tauri/src/db/ssh_hosts.rs
+//! SSH host repository
+//!
+//! Hosts that remote workspaces live on. Authentication uses the user's SSH
+//! agent, or a private key kept in the keychain (see `remote`).
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// Stored SSH host
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SshHost {
+    pub id: String,
+    pub name: String,
+    /// Hostname, IP address, or alias from `~/.ssh/config`
+    pub host: String,
+    pub port: Option<u16>,
+    pub user: Option<String>,
+    /// "agent" (SSH agent and `~/.ssh` keys) or "key" (private key in the keychain)
+    pub auth: String,
+    pub created_at: String,
+}
+
+/// Fields of an SSH host set by the user
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SshHostInput {
+    pub name: String,
+    pub host: String,
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub user: Option<String>,
+    #[serde(default)]
+    pub auth: Option<String>,
+}
+
+fn row_to_host(row: &rusqlite::Row) -> rusqlite::Result<SshHost> {
+    Ok(SshHost {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        host: row.get(2)?,
+        port: row.get(3)?,
+        user: row.get(4)?,
+        auth: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+/// All hosts, by name
+pub fn list_hosts(conn: &Connection) -> Result<Vec<SshHost>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, host, port, user, auth, created_at
+             FROM ssh_hosts ORDER BY name ASC",
+        )
+        .map_err(|e| format!("Failed to prepare SSH hosts query: {}", e))?;
+    let hosts = stmt
+        .query_map([], row_to_host)
+        .map_err(|e| format!("Failed to query SSH hosts: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(hosts)
+}
+
+/// Get a host by ID
+pub fn get_host(conn: &Connection, id: &str) -> Result<Option<SshHost>, String> {
+    conn.query_row(
+        "SELECT id, name, host, port, user, auth, created_at FROM ssh_hosts WHERE id = ?1",
+        [id],
+        row_to_host,
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get SSH host: {}", e))
+}
+
+/// Create a host
+pub fn create_host(conn: &Connection, input: &SshHostInput) -> Result<SshHost, String> {
+    let host = input.host.trim();
+    // A leading '-' would be read as an ssh option
+    if host.is_empty() || host.starts_with('-') || host.contains(char::is_whitespace) {
+        return Err(format!("Invalid SSH host: '{}'", input.host));
+    }
+    let user = input.user.as_deref().map(str::trim).filter(|u| !u.is_empty());
+    if user.is_some_and(|u| u.starts_with('-') || u.contains(char::is_whitespace)) {
+        return Err(format!("Invalid SSH user: '{}'", user.unwrap_or_default()));
+    }
+    let auth = input.auth.as_deref().unwrap_or("agent");
+    if auth != "agent" && auth != "key" {
+        return Err(format!("Unknown SSH authentication: {}", auth));
+    }
+    let name = match input.name.trim() {
+        "" => host,
+        name => name,
+    };
+
+    let ssh_host = SshHost {
+        id: format!("ssh_{}", uuid::Uuid::new_v4()),
+        name: name.to_string(),
+        host: host.to_string(),
+        port: input.port,
+        user: user.map(str::to_string),
+        auth: auth.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+    conn.execute(
+        "INSERT INTO ssh_hosts (id, name, host, port, user, auth, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            ssh_host.id,
+            ssh_host.name,
+            ssh_host.host,
+            ssh_host.port,
+            ssh_host.user,
+            ssh_host.auth,
+            ssh_host.created_at,
+        ],
+    )
+    .map_err(|e| format!("Failed to create SSH host: {}", e))?;
+    Ok(ssh_host)
+}
+
+/// Delete a host that no workspace uses
+pub fn delete_host(conn: &Connection, id: &str) -> Result<(), String> {
+    let workspaces: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM workspaces WHERE ssh_host_id = ?1",
+            [id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to check SSH host workspaces: {}", e))?;
+    if workspaces > 0 {
+        return Err(format!("SSH host is used by {} workspace(s)", workspaces));
+    }
+    conn.execute("DELETE FROM ssh_hosts WHERE id = ?1", [id])
+        .map_err(|e| format!("Failed to delete SSH host: {}", e))?;
+    Ok(())
+}
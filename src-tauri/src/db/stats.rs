@@ -0,0 +1,115 @@
+// src-tauri/src/db/stats.rs
+//! Storage statistics
+//!
+//! Sizes of the database files and of what fills them, for the storage
+//! settings page.
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::path::Path;
+
+/// Tasks listed in `DbStats::largest_tasks`
+const LARGEST_TASKS: i64 = 10;
+
+/// Rows in one table
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableRows {
+    pub table: String,
+    pub rows: i64,
+}
+
+/// Bytes a task's messages take in the database
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskSize {
+    pub task_id: String,
+    /// Title, or the prompt when the task has none
+    pub title: String,
+    pub message_count: i64,
+    /// Message content and tool input
+    pub message_bytes: i64,
+    /// Inline message attachments
+    pub attachment_bytes: i64,
+}
+
+/// Database size and contents
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbStats {
+    pub path: String,
+    pub file_bytes: u64,
+    /// Write-ahead log not yet checkpointed into the file
+    pub wal_bytes: u64,
+    /// Free pages in the file, reclaimable with VACUUM
+    pub free_bytes: i64,
+    pub schema_version: i32,
+    pub tables: Vec<TableRows>,
+    /// Tasks taking the most bytes, largest first
+    pub largest_tasks: Vec<TaskSize>,
+}
+
+fn file_size(path: &Path) -> u64 {
+    std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+/// Tasks with the most message and attachment bytes
+fn largest_tasks(conn: &Connection, limit: i64) -> Result<Vec<TaskSize>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.id, COALESCE(t.title, t.prompt),
+                    (SELECT COUNT(*) FROM task_messages m WHERE m.task_id = t.id),
+                    (SELECT COALESCE(SUM(length(CAST(m.content AS BLOB))
+                                         + COALESCE(length(CAST(m.tool_input AS BLOB)), 0)), 0)
+                     FROM task_messages m WHERE m.task_id = t.id) AS message_bytes,
+                    (SELECT COALESCE(SUM(COALESCE(b.size, length(CAST(a.data AS BLOB)))), 0)
+                     FROM task_attachments a JOIN task_messages m ON m.id = a.message_id
+                     LEFT JOIN attachment_blobs b ON b.hash = a.blob_hash
+                     WHERE m.task_id = t.id) AS attachment_bytes
+             FROM tasks t
+             ORDER BY message_bytes + attachment_bytes DESC
+             LIMIT ?1",
+        )
+        .map_err(|e| format!("Failed to prepare task sizes query: {}", e))?;
+
+    let tasks = stmt
+        .query_map(params![limit], |row| {
+            Ok(TaskSize {
+                task_id: row.get(0)?,
+                title: row.get(1)?,
+                message_count: row.get(2)?,
+                message_bytes: row.get(3)?,
+                attachment_bytes: row.get(4)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query task sizes: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(tasks)
+}
+
+/// Sizes and row counts of the database at `db_path`
+pub fn get_db_stats(conn: &Connection, db_path: &Path) -> Result<DbStats, String> {
+    let free_pages: i64 = conn
+        .query_row("PRAGMA freelist_count", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to read free pages: {}", e))?;
+    let page_size: i64 = conn
+        .query_row("PRAGMA page_size", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to read page size: {}", e))?;
+
+    let mut wal_path = db_path.as_os_str().to_owned();
+    wal_path.push("-wal");
+
+    Ok(DbStats {
+        path: db_path.to_string_lossy().to_string(),
+        file_bytes: file_size(db_path),
+        wal_bytes: file_size(Path::new(&wal_path)),
+        free_bytes: free_pages * page_size,
+        schema_version: super::migrations::get_stored_version(conn),
+        tables: super::get_table_row_counts(conn)?
+            .into_iter()
+            .map(|(table, rows)| TableRows { table, rows })
+            .collect(),
+        largest_tasks: largest_tasks(conn, LARGEST_TASKS)?,
+    })
+}
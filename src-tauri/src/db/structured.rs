@@ -0,0 +1,76 @@
+// src-tauri/src/db/structured.rs
+//! Structured output repository
+//!
+//! Tasks started with an `outputSchema` keep the schema here. When the agent
+//! finishes, its final reply is validated against the schema and the parsed
+//! object (or the last validation error) is stored alongside it.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Schema and validated output of a structured task
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuredOutput {
+    pub task_id: String,
+    pub schema: Value,
+    /// Parsed reply, once one has passed validation
+    pub output: Option<Value>,
+    /// Validation attempts so far
+    pub attempts: i64,
+    /// Error from the last failed validation
+    pub error: Option<String>,
+}
+
+/// Store the schema a task's final reply must match
+pub fn save_schema(conn: &Connection, task_id: &str, schema: &Value) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR REPLACE INTO task_structured_outputs (task_id, schema) VALUES (?1, ?2)",
+        params![task_id, schema.to_string()],
+    )
+    .map_err(|e| format!("Failed to save output schema: {}", e))?;
+    Ok(())
+}
+
+/// Get the schema and output of a task, if it has a schema
+pub fn get(conn: &Connection, task_id: &str) -> Result<Option<StructuredOutput>, String> {
+    conn.query_row(
+        "SELECT task_id, schema, output, attempts, error
+         FROM task_structured_outputs WHERE task_id = ?1",
+        [task_id],
+        |row| {
+            let schema: String = row.get(1)?;
+            let output: Option<String> = row.get(2)?;
+            Ok(StructuredOutput {
+                task_id: row.get(0)?,
+                schema: serde_json::from_str(&schema).unwrap_or(Value::Null),
+                output: output.and_then(|o| serde_json::from_str(&o).ok()),
+                attempts: row.get(3)?,
+                error: row.get(4)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get structured output: {}", e))
+}
+
+/// Record a validation attempt: the parsed output on success, the error otherwise
+pub fn record_attempt(
+    conn: &Connection,
+    task_id: &str,
+    result: Result<&Value, &str>,
+) -> Result<(), String> {
+    let (output, error) = match result {
+        Ok(output) => (Some(output.to_string()), None),
+        Err(error) => (None, Some(error)),
+    };
+    conn.execute(
+        "UPDATE task_structured_outputs
+         SET output = ?1, error = ?2, attempts = attempts + 1
+         WHERE task_id = ?3",
+        params![output, error, task_id],
+    )
+    .map_err(|e| format!("Failed to record structured output: {}", e))?;
+    Ok(())
+}
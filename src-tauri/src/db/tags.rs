@@ -0,0 +1,108 @@
+// src-tauri/src/db/tags.rs
+//! Task tags
+//!
+//! Free-form labels the user puts on tasks to organize a long history by
+//! project or topic. Tags compare without regard to case, so "Backend" and
+//! "backend" are one tag.
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Longest tag accepted, in characters
+const MAX_TAG_LENGTH: usize = 50;
+
+/// A tag and how many tasks carry it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagCount {
+    pub tag: String,
+    pub task_count: i64,
+}
+
+/// Check a tag and trim it
+fn normalize(tag: &str) -> Result<&str, String> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return Err("Tag cannot be empty".to_string());
+    }
+    if tag.chars().count() > MAX_TAG_LENGTH {
+        return Err(format!("Tag is longer than {} characters", MAX_TAG_LENGTH));
+    }
+    Ok(tag)
+}
+
+/// A task's tags, alphabetically
+pub fn get_task_tags(conn: &Connection, task_id: &str) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT tag FROM task_tags WHERE task_id = ?1 ORDER BY tag")
+        .map_err(|e| format!("Failed to prepare tags query: {}", e))?;
+    let tags = stmt
+        .query_map([task_id], |row| row.get(0))
+        .map_err(|e| format!("Failed to query tags: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(tags)
+}
+
+/// Tags of every task that has some, by task ID
+pub fn get_all_task_tags(conn: &Connection) -> Result<HashMap<String, Vec<String>>, String> {
+    let mut stmt = conn
+        .prepare("SELECT task_id, tag FROM task_tags ORDER BY tag")
+        .map_err(|e| format!("Failed to prepare tags query: {}", e))?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+        .map_err(|e| format!("Failed to query tags: {}", e))?;
+
+    let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+    for (task_id, tag) in rows.filter_map(|r| r.ok()) {
+        tags.entry(task_id).or_default().push(tag);
+    }
+    Ok(tags)
+}
+
+/// Tag a task; returns its tags
+pub fn add_tag(conn: &Connection, task_id: &str, tag: &str) -> Result<Vec<String>, String> {
+    let tag = normalize(tag)?;
+    if !super::tasks::task_exists(conn, task_id) {
+        return Err(format!("Task not found: {}", task_id));
+    }
+    super::with_retry(|| {
+        conn.execute(
+            "INSERT OR IGNORE INTO task_tags (task_id, tag, created_at) VALUES (?1, ?2, ?3)",
+            params![task_id, tag, chrono::Utc::now().to_rfc3339()],
+        )
+    })
+    .map_err(|e| format!("Failed to add tag: {}", e))?;
+    get_task_tags(conn, task_id)
+}
+
+/// Take a tag off a task; returns its remaining tags
+pub fn remove_tag(conn: &Connection, task_id: &str, tag: &str) -> Result<Vec<String>, String> {
+    super::with_retry(|| {
+        conn.execute(
+            "DELETE FROM task_tags WHERE task_id = ?1 AND tag = ?2",
+            params![task_id, tag.trim()],
+        )
+    })
+    .map_err(|e| format!("Failed to remove tag: {}", e))?;
+    get_task_tags(conn, task_id)
+}
+
+/// Every tag in use, with how many tasks carry it, alphabetically
+pub fn list_tags(conn: &Connection) -> Result<Vec<TagCount>, String> {
+    let mut stmt = conn
+        .prepare("SELECT MIN(tag), COUNT(*) FROM task_tags GROUP BY tag ORDER BY tag")
+        .map_err(|e| format!("Failed to prepare tags query: {}", e))?;
+    let tags = stmt
+        .query_map([], |row| {
+            Ok(TagCount {
+                tag: row.get(0)?,
+                task_count: row.get(1)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query tags: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(tags)
+}
@@ -0,0 +1,87 @@
+// src-tauri/src/db/task_events.rs
+//! Task status changes
+//!
+//! Every status change made through `tasks::save_task` or
+//! `tasks::update_task_status` is recorded with what made it and why, so a
+//! task that got stuck or was cancelled without explanation can be traced
+//! with `get_task_timeline`. Like usage, rows aren't tied to the task by a
+//! foreign key; `tasks::delete_task` removes them with the task.
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+/// A command the user ran, such as a follow-up or a plan approval
+pub const SOURCE_USER: &str = "user";
+/// A status reported by the UI
+pub const SOURCE_UI: &str = "ui";
+/// An event of the agent run, such as it completing or failing
+pub const SOURCE_SIDECAR: &str = "sidecar";
+/// Aborting all tasks
+pub const SOURCE_ABORT: &str = "abort";
+/// Startup recovery of tasks a previous run left unfinished
+pub const SOURCE_RECOVERY: &str = "recovery";
+/// The whole task being saved: started, forked, imported, synced or restored from a backup
+pub const SOURCE_SAVE: &str = "save";
+
+/// A change of a task's status
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskEvent {
+    pub id: i64,
+    pub task_id: String,
+    /// None when the task was created with the status
+    pub from_status: Option<String>,
+    pub to_status: String,
+    /// What made the change, one of the `SOURCE_` values
+    pub source: String,
+    pub reason: Option<String>,
+    pub created_at: String,
+}
+
+/// Record a change of a task's status
+pub fn record(
+    conn: &Connection,
+    task_id: &str,
+    from_status: Option<&str>,
+    to_status: &str,
+    source: &str,
+    reason: Option<&str>,
+) -> Result<(), String> {
+    let now = chrono::Utc::now().to_rfc3339();
+    super::with_retry(|| {
+        conn.execute(
+            "INSERT INTO task_events
+             (task_id, from_status, to_status, source, reason, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![task_id, from_status, to_status, source, reason, now],
+        )
+    })
+    .map_err(|e| format!("Failed to record task event: {}", e))?;
+    Ok(())
+}
+
+/// A task's status changes, oldest first
+pub fn get_timeline(conn: &Connection, task_id: &str) -> Result<Vec<TaskEvent>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, task_id, from_status, to_status, source, reason, created_at
+             FROM task_events WHERE task_id = ?1 ORDER BY id ASC",
+        )
+        .map_err(|e| format!("Failed to prepare timeline query: {}", e))?;
+    let events = stmt
+        .query_map([task_id], |row| {
+            Ok(TaskEvent {
+                id: row.get(0)?,
+                task_id: row.get(1)?,
+                from_status: row.get(2)?,
+                to_status: row.get(3)?,
+                source: row.get(4)?,
+                reason: row.get(5)?,
+                created_at: row.get(6)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query timeline: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(events)
+}
@@ -1,11 +1,16 @@
 // src-tauri/src/db/tasks.rs
 //! Task history repository
 
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
+/// Tasks kept in the history; older ones are archived
 const MAX_HISTORY_ITEMS: i32 = 100;
 
+/// Most messages `get_message_window` returns at once
+const MAX_MESSAGE_WINDOW: u32 = 500;
+
 /// Stored task representation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -14,6 +19,9 @@ pub struct StoredTask {
     pub prompt: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub summary: Option<String>,
+    /// Short generated title shown in the sidebar
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
     pub status: String,
     pub messages: Vec<StoredTaskMessage>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -23,6 +31,14 @@ pub struct StoredTask {
     pub started_at: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub completed_at: Option<String>,
+    /// Last time a message was added or the status changed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<String>,
+    /// Whether the agent could search the web; unset for tasks from before the flag existed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_web_search: Option<bool>,
 }
 
 /// Stored task message representation
@@ -40,16 +56,63 @@ pub struct StoredTaskMessage {
     pub tool_input: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub attachments: Option<Vec<StoredAttachment>>,
+    /// Note added with `annotate_message`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
 }
 
 /// Stored attachment representation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StoredAttachment {
+    /// Row ID, for fetching the data of an attachment loaded without it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
     #[serde(rename = "type")]
     pub att_type: String,
+    /// Empty when loaded without attachment data
     pub data: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub label: Option<String>,
+    /// Length of the stored data
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<i64>,
+}
+
+/// Which tasks `get_tasks_filtered` returns; unset fields don't filter
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TaskFilter {
+    /// Tasks carrying every one of these tags
+    pub tags: Vec<String>,
+    /// Tasks with any of these statuses
+    pub statuses: Vec<String>,
+    /// Created at or after this date or RFC 3339 time
+    pub created_from: Option<String>,
+    /// Created at or before this RFC 3339 time, or on or before this date
+    pub created_to: Option<String>,
+    /// Tasks filed under this project
+    pub project_id: Option<String>,
+    pub sort: TaskSort,
+}
+
+/// Order of a task list, most recent first
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TaskSort {
+    /// By when the task was created
+    #[default]
+    Created,
+    /// By the task's last activity, so resumed tasks come first
+    Activity,
+}
+
+impl TaskSort {
+    fn column(self) -> &'static str {
+        match self {
+            TaskSort::Created => "created_at",
+            TaskSort::Activity => "COALESCE(updated_at, created_at)",
+        }
+    }
 }
 
 /// Input for saving a task
@@ -63,9 +126,19 @@ pub struct TaskInput {
     pub messages: Vec<TaskMessageInput>,
     pub session_id: Option<String>,
     pub summary: Option<String>,
+    /// Kept from the stored task when unset
+    #[serde(default)]
+    pub title: Option<String>,
     pub created_at: String,
     pub started_at: Option<String>,
     pub completed_at: Option<String>,
+    #[serde(default)]
+    pub workspace_id: Option<String>,
+    #[serde(default)]
+    pub allow_web_search: Option<bool>,
+    /// Kept from the stored task when unset, or the creation time for a new task
+    #[serde(default)]
+    pub updated_at: Option<String>,
 }
 
 /// Input for task message
@@ -80,6 +153,11 @@ pub struct TaskMessageInput {
     pub tool_name: Option<String>,
     pub tool_input: Option<serde_json::Value>,
     pub attachments: Option<Vec<AttachmentInput>>,
+    #[serde(default)]
+    pub note: Option<String>,
+    /// Sidecar event the message came from, unique per task
+    #[serde(default)]
+    pub origin_id: Option<String>,
 }
 
 /// Input for attachment
@@ -89,39 +167,68 @@ pub struct AttachmentInput {
     pub att_type: String,
     pub data: String,
     pub label: Option<String>,
+    /// A screenshot's thumbnail, when built before the write; built on insert otherwise
+    #[serde(skip)]
+    pub thumbnail: Option<String>,
+}
+
+/// Earlier content of an edited message
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageVersion {
+    pub version: i64,
+    pub content: String,
+    pub replaced_at: String,
 }
 
 /// Get messages for a task
-fn get_messages_for_task(conn: &Connection, task_id: &str) -> Vec<StoredTaskMessage> {
+fn get_messages_for_task(
+    conn: &Connection,
+    task_id: &str,
+    attachment_data: bool,
+) -> Vec<StoredTaskMessage> {
+    query_messages(conn, task_id, attachment_data, 0, -1)
+}
+
+/// Get `limit` of a task's messages from `offset` on; a negative limit takes the rest
+fn query_messages(
+    conn: &Connection,
+    task_id: &str,
+    attachment_data: bool,
+    offset: i64,
+    limit: i64,
+) -> Vec<StoredTaskMessage> {
     let mut stmt = conn
         .prepare(
-            "SELECT id, type, content, tool_name, tool_input, timestamp
+            "SELECT id, type, content, tool_name, tool_input, timestamp, note
              FROM task_messages
-             WHERE task_id = ?1
-             ORDER BY sort_order ASC",
+             WHERE task_id = ?1 AND archived_at IS NULL
+             ORDER BY sort_order ASC
+             LIMIT ?2 OFFSET ?3",
         )
         .expect("Failed to prepare messages query");
 
     let message_iter = stmt
-        .query_map([task_id], |row| {
+        .query_map(params![task_id, limit, offset], |row| {
             let id: String = row.get(0)?;
             let msg_type: String = row.get(1)?;
             let content: String = row.get(2)?;
             let tool_name: Option<String> = row.get(3)?;
             let tool_input_str: Option<String> = row.get(4)?;
             let timestamp: String = row.get(5)?;
+            let note: Option<String> = row.get(6)?;
 
             let tool_input = tool_input_str.and_then(|s| serde_json::from_str(&s).ok());
 
-            Ok((id, msg_type, content, tool_name, tool_input, timestamp))
+            Ok((id, msg_type, content, tool_name, tool_input, timestamp, note))
         })
         .expect("Failed to query messages");
 
     message_iter
         .filter_map(|r| r.ok())
-        .map(|(id, msg_type, content, tool_name, tool_input, timestamp)| {
+        .map(|(id, msg_type, content, tool_name, tool_input, timestamp, note)| {
             // Get attachments for this message
-            let attachments = get_attachments_for_message(conn, &id);
+            let attachments = get_attachments_for_message(conn, &id, attachment_data);
 
             StoredTaskMessage {
                 id,
@@ -135,39 +242,147 @@ fn get_messages_for_task(conn: &Connection, task_id: &str) -> Vec<StoredTaskMess
                 } else {
                     Some(attachments)
                 },
+                note,
             }
         })
         .collect()
 }
 
-/// Get attachments for a message
-fn get_attachments_for_message(conn: &Connection, message_id: &str) -> Vec<StoredAttachment> {
+fn row_to_attachment(row: &rusqlite::Row) -> rusqlite::Result<StoredAttachment> {
+    Ok(StoredAttachment {
+        id: row.get(0)?,
+        att_type: row.get(1)?,
+        data: row.get(2)?,
+        label: row.get(3)?,
+        size: row.get(4)?,
+    })
+}
+
+/// Get attachments for a message, leaving `data` empty unless `with_data`
+fn get_attachments_for_message(
+    conn: &Connection,
+    message_id: &str,
+    with_data: bool,
+) -> Vec<StoredAttachment> {
     let mut stmt = conn
-        .prepare("SELECT type, data, label FROM task_attachments WHERE message_id = ?1")
+        .prepare(
+            "SELECT a.id, a.type, CASE WHEN ?2 THEN COALESCE(b.data, a.data) ELSE '' END,
+                    a.label, COALESCE(b.size, length(a.data))
+             FROM task_attachments a LEFT JOIN attachment_blobs b ON b.hash = a.blob_hash
+             WHERE a.message_id = ?1",
+        )
         .expect("Failed to prepare attachments query");
 
     let att_iter = stmt
-        .query_map([message_id], |row| {
-            Ok(StoredAttachment {
-                att_type: row.get(0)?,
-                data: row.get(1)?,
-                label: row.get(2)?,
-            })
-        })
+        .query_map(params![message_id, with_data], row_to_attachment)
         .expect("Failed to query attachments");
 
     att_iter.filter_map(|r| r.ok()).collect()
 }
 
-/// Get all tasks (limited to MAX_HISTORY_ITEMS)
+/// Get one message attachment with its data
+pub fn get_message_attachment(
+    conn: &Connection,
+    attachment_id: i64,
+) -> Result<Option<StoredAttachment>, String> {
+    conn.query_row(
+        "SELECT a.id, a.type, COALESCE(b.data, a.data), a.label, COALESCE(b.size, length(a.data))
+         FROM task_attachments a LEFT JOIN attachment_blobs b ON b.hash = a.blob_hash
+         WHERE a.id = ?1",
+        [attachment_id],
+        row_to_attachment,
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get attachment: {}", e))
+}
+
+/// Get all tasks in the history, archived ones left out (limited to MAX_HISTORY_ITEMS)
 pub fn get_tasks(conn: &Connection) -> Vec<StoredTask> {
+    load_tasks(conn, true, TaskSort::Created)
+}
+
+/// Get all tasks with attachment metadata only, in the given order
+pub fn get_tasks_without_attachment_data(conn: &Connection, sort: TaskSort) -> Vec<StoredTask> {
+    load_tasks(conn, false, sort)
+}
+
+/// Tasks matching a filter, in the filter's order, without their messages
+/// (limited to MAX_HISTORY_ITEMS)
+pub fn get_tasks_filtered(
+    conn: &Connection,
+    filter: &TaskFilter,
+) -> Result<Vec<StoredTask>, String> {
+    let mut conditions = Vec::new();
+    let mut values: Vec<String> = Vec::new();
+    for tag in &filter.tags {
+        values.push(tag.trim().to_string());
+        conditions.push(format!(
+            "EXISTS (SELECT 1 FROM task_tags g WHERE g.task_id = t.id AND g.tag = ?{})",
+            values.len()
+        ));
+    }
+    if let Some(project_id) = &filter.project_id {
+        values.push(project_id.clone());
+        conditions.push(format!("t.project_id = ?{}", values.len()));
+    }
+    if !filter.statuses.is_empty() {
+        let first = values.len() + 1;
+        values.extend(filter.statuses.iter().cloned());
+        let placeholders: Vec<String> =
+            (first..=values.len()).map(|i| format!("?{}", i)).collect();
+        conditions.push(format!("t.status IN ({})", placeholders.join(", ")));
+    }
+    // julianday() reads both the app's and the frontend's timestamp formats
+    if let Some(from) = &filter.created_from {
+        values.push(from.clone());
+        conditions.push(format!("julianday(t.created_at) >= julianday(?{})", values.len()));
+    }
+    if let Some(to) = &filter.created_to {
+        values.push(to.clone());
+        // A date alone takes in the whole day
+        if chrono::NaiveDate::parse_from_str(to, "%Y-%m-%d").is_ok() {
+            conditions.push(format!(
+                "julianday(t.created_at) < julianday(?{}, '+1 day')",
+                values.len()
+            ));
+        } else {
+            conditions.push(format!("julianday(t.created_at) <= julianday(?{})", values.len()));
+        }
+    }
+    conditions.push("t.archived_at IS NULL".to_string());
+    let where_clause = format!("WHERE {}", conditions.join(" AND "));
+
+    values.push(MAX_HISTORY_ITEMS.to_string());
     let mut stmt = conn
-        .prepare(
-            "SELECT id, prompt, summary, status, session_id, created_at, started_at, completed_at
+        .prepare(&format!(
+            "SELECT t.id FROM tasks t {} ORDER BY {} DESC LIMIT ?{}",
+            where_clause,
+            filter.sort.column(),
+            values.len()
+        ))
+        .map_err(|e| format!("Failed to prepare filtered tasks query: {}", e))?;
+    let ids: Vec<String> = stmt
+        .query_map(rusqlite::params_from_iter(&values), |row| row.get(0))
+        .map_err(|e| format!("Failed to query filtered tasks: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(ids
+        .iter()
+        .filter_map(|id| load_task(conn, id, false, false))
+        .collect())
+}
+
+fn load_tasks(conn: &Connection, attachment_data: bool, sort: TaskSort) -> Vec<StoredTask> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT id, prompt, summary, status, session_id, created_at, started_at, completed_at,
+                    workspace_id, allow_web_search, title, updated_at
              FROM tasks
-             ORDER BY created_at DESC
+             WHERE archived_at IS NULL
+             ORDER BY {} DESC
              LIMIT ?1",
-        )
+            sort.column()
+        ))
         .expect("Failed to prepare tasks query");
 
     let task_iter = stmt
@@ -181,6 +396,10 @@ pub fn get_tasks(conn: &Connection) -> Vec<StoredTask> {
                 row.get::<_, String>(5)?,
                 row.get::<_, Option<String>>(6)?,
                 row.get::<_, Option<String>>(7)?,
+                row.get::<_, Option<String>>(8)?,
+                row.get::<_, Option<bool>>(9)?,
+                row.get::<_, Option<String>>(10)?,
+                row.get::<_, Option<String>>(11)?,
             ))
         })
         .expect("Failed to query tasks");
@@ -188,18 +407,35 @@ pub fn get_tasks(conn: &Connection) -> Vec<StoredTask> {
     task_iter
         .filter_map(|r| r.ok())
         .map(
-            |(id, prompt, summary, status, session_id, created_at, started_at, completed_at)| {
-                let messages = get_messages_for_task(conn, &id);
+            |(
+                id,
+                prompt,
+                summary,
+                status,
+                session_id,
+                created_at,
+                started_at,
+                completed_at,
+                workspace_id,
+                allow_web_search,
+                title,
+                updated_at,
+            )| {
+                let messages = get_messages_for_task(conn, &id, attachment_data);
                 StoredTask {
                     id,
                     prompt,
                     summary,
+                    title,
                     status,
                     messages,
                     session_id,
                     created_at,
                     started_at,
                     completed_at,
+                    updated_at,
+                    workspace_id,
+                    allow_web_search,
                 }
             },
         )
@@ -208,8 +444,50 @@ pub fn get_tasks(conn: &Connection) -> Vec<StoredTask> {
 
 /// Get a single task by ID
 pub fn get_task(conn: &Connection, task_id: &str) -> Option<StoredTask> {
+    load_task(conn, task_id, true, true)
+}
+
+/// Get a single task with attachment metadata only
+pub fn get_task_without_attachment_data(conn: &Connection, task_id: &str) -> Option<StoredTask> {
+    load_task(conn, task_id, true, false)
+}
+
+/// Get a single task with no messages, for loading them with `get_message_window`
+pub fn get_task_without_messages(conn: &Connection, task_id: &str) -> Option<StoredTask> {
+    load_task(conn, task_id, false, false)
+}
+
+/// Number of a task's messages, as `get_message_window` counts them
+pub fn count_messages(conn: &Connection, task_id: &str) -> Result<u32, String> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM task_messages WHERE task_id = ?1 AND archived_at IS NULL",
+        [task_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| format!("Failed to count messages: {}", e))
+}
+
+/// Up to `limit` of a task's messages from the `offset`th, in order, with attachment
+/// metadata only
+pub fn get_message_window(
+    conn: &Connection,
+    task_id: &str,
+    offset: u32,
+    limit: u32,
+) -> Vec<StoredTaskMessage> {
+    let limit = limit.min(MAX_MESSAGE_WINDOW);
+    query_messages(conn, task_id, false, offset.into(), limit.into())
+}
+
+fn load_task(
+    conn: &Connection,
+    task_id: &str,
+    messages: bool,
+    attachment_data: bool,
+) -> Option<StoredTask> {
     let result = conn.query_row(
-        "SELECT id, prompt, summary, status, session_id, created_at, started_at, completed_at
+        "SELECT id, prompt, summary, status, session_id, created_at, started_at, completed_at,
+                workspace_id, allow_web_search, title, updated_at
          FROM tasks WHERE id = ?1",
         [task_id],
         |row| {
@@ -222,36 +500,294 @@ pub fn get_task(conn: &Connection, task_id: &str) -> Option<StoredTask> {
                 row.get::<_, String>(5)?,
                 row.get::<_, Option<String>>(6)?,
                 row.get::<_, Option<String>>(7)?,
+                row.get::<_, Option<String>>(8)?,
+                row.get::<_, Option<bool>>(9)?,
+                row.get::<_, Option<String>>(10)?,
+                row.get::<_, Option<String>>(11)?,
             ))
         },
     );
 
     match result {
-        Ok((id, prompt, summary, status, session_id, created_at, started_at, completed_at)) => {
-            let messages = get_messages_for_task(conn, &id);
+        Ok((
+            id,
+            prompt,
+            summary,
+            status,
+            session_id,
+            created_at,
+            started_at,
+            completed_at,
+            workspace_id,
+            allow_web_search,
+            title,
+            updated_at,
+        )) => {
+            let messages = if messages {
+                get_messages_for_task(conn, &id, attachment_data)
+            } else {
+                Vec::new()
+            };
             Some(StoredTask {
                 id,
                 prompt,
                 summary,
+                title,
                 status,
                 messages,
                 session_id,
                 created_at,
                 started_at,
                 completed_at,
+                updated_at,
+                workspace_id,
+                allow_web_search,
             })
         }
         Err(_) => None,
     }
 }
 
-/// Save a task (upsert)
+/// A task left in progress by a previous run
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnfinishedTask {
+    pub task_id: String,
+    pub prompt: String,
+    /// Status the task was left in
+    pub status: String,
+    /// Session to resume the task with, if the agent got far enough to start one;
+    /// a running task's session is only known from its checkpoint
+    pub session_id: Option<String>,
+}
+
+/// Statuses of a task whose run is still starting, running, or waiting
+const UNFINISHED_STATUSES: &str =
+    "'pending', 'queued', 'starting', 'running', 'waiting_permission'";
+
+/// Tasks whose status says they are still starting, running, or waiting
+pub fn get_unfinished_tasks(conn: &Connection) -> Result<Vec<UnfinishedTask>, String> {
+    query_unfinished_tasks(conn, UNFINISHED_STATUSES)
+}
+
+/// Unfinished tasks plus those whose plan is awaiting approval, which could
+/// still be approved and run
+///
+/// A pending plan outlives a restart, so only aborting cancels these.
+pub fn get_abortable_tasks(conn: &Connection) -> Result<Vec<UnfinishedTask>, String> {
+    query_unfinished_tasks(conn, &format!("{}, 'awaiting_approval'", UNFINISHED_STATUSES))
+}
+
+fn query_unfinished_tasks(
+    conn: &Connection,
+    statuses: &str,
+) -> Result<Vec<UnfinishedTask>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT t.id, t.prompt, t.status, COALESCE(t.session_id, c.session_id)
+             FROM tasks t LEFT JOIN task_checkpoints c ON c.task_id = t.id
+             WHERE t.status IN ({})
+             ORDER BY t.created_at",
+            statuses
+        ))
+        .map_err(|e| format!("Failed to prepare unfinished tasks query: {}", e))?;
+    let tasks = stmt
+        .query_map([], |row| {
+            Ok(UnfinishedTask {
+                task_id: row.get(0)?,
+                prompt: row.get(1)?,
+                status: row.get(2)?,
+                session_id: row.get(3)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query unfinished tasks: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(tasks)
+}
+
+/// Whether any task already uses the given agent session
+pub fn has_task_for_session(conn: &Connection, session_id: &str) -> bool {
+    conn.query_row(
+        "SELECT COUNT(*) > 0 FROM tasks WHERE session_id = ?1",
+        [session_id],
+        |row| row.get(0),
+    )
+    .unwrap_or(false)
+}
+
+/// Most recent task using the given agent session
+pub fn get_task_id_for_session(conn: &Connection, session_id: &str) -> Option<String> {
+    conn.query_row(
+        "SELECT id FROM tasks WHERE session_id = ?1 ORDER BY created_at DESC LIMIT 1",
+        [session_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .ok()
+    .flatten()
+}
+
+/// A task that ran in an agent session
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionTask {
+    pub task_id: String,
+    pub title: Option<String>,
+    pub prompt: String,
+    pub status: String,
+    pub created_at: String,
+}
+
+/// Tasks that used the given agent session, oldest first
+pub fn get_session_tasks(conn: &Connection, session_id: &str) -> Result<Vec<SessionTask>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, title, prompt, status, created_at FROM tasks
+             WHERE session_id = ?1 ORDER BY created_at ASC",
+        )
+        .map_err(|e| format!("Failed to prepare session tasks query: {}", e))?;
+    let tasks = stmt
+        .query_map([session_id], |row| {
+            Ok(SessionTask {
+                task_id: row.get(0)?,
+                title: row.get(1)?,
+                prompt: row.get(2)?,
+                status: row.get(3)?,
+                created_at: row.get(4)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query session tasks: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(tasks)
+}
+
+/// IDs and origin IDs of a task's messages, archived and deleted ones included
+pub fn get_message_keys(conn: &Connection, task_id: &str) -> Result<HashSet<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT id, origin_id FROM task_messages WHERE task_id = ?1")
+        .map_err(|e| format!("Failed to prepare message keys query: {}", e))?;
+    let rows = stmt
+        .query_map([task_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+        })
+        .map_err(|e| format!("Failed to query message keys: {}", e))?;
+
+    let mut keys = HashSet::new();
+    for (id, origin_id) in rows.filter_map(|r| r.ok()) {
+        keys.insert(id);
+        keys.extend(origin_id);
+    }
+
+    // Deleted messages count as known
+    let mut stmt = conn
+        .prepare("SELECT origin_id FROM deleted_messages WHERE task_id = ?1")
+        .map_err(|e| format!("Failed to prepare deleted messages query: {}", e))?;
+    let deleted = stmt
+        .query_map([task_id], |row| row.get::<_, String>(0))
+        .map_err(|e| format!("Failed to query deleted messages: {}", e))?;
+    keys.extend(deleted.filter_map(|r| r.ok()));
+    Ok(keys)
+}
+
+/// Whether a task with the given ID is stored
+pub fn task_exists(conn: &Connection, task_id: &str) -> bool {
+    conn.query_row(
+        "SELECT COUNT(*) > 0 FROM tasks WHERE id = ?1",
+        [task_id],
+        |row| row.get(0),
+    )
+    .unwrap_or(false)
+}
+
+/// IDs and contents of a task's user messages that have no origin yet, oldest first
+///
+/// These are the prompts and follow-ups the app stored as it sent them.
+pub fn get_unmatched_user_messages(
+    conn: &Connection,
+    task_id: &str,
+) -> Result<Vec<(String, String)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, content FROM task_messages
+             WHERE task_id = ?1 AND type = 'user' AND origin_id IS NULL
+             ORDER BY sort_order ASC",
+        )
+        .map_err(|e| format!("Failed to prepare user messages query: {}", e))?;
+    let messages = stmt
+        .query_map([task_id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| format!("Failed to query user messages: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(messages)
+}
+
+/// Record the OpenCode part a stored message came from
+pub fn set_message_origin(
+    conn: &Connection,
+    message_id: &str,
+    origin_id: &str,
+) -> Result<(), String> {
+    conn.execute(
+        "UPDATE task_messages SET origin_id = ?2 WHERE id = ?1",
+        params![message_id, origin_id],
+    )
+    .map_err(|e| format!("Failed to set message origin: {}", e))?;
+    Ok(())
+}
+
+/// Whether a message with the given ID is stored
+pub fn message_exists(conn: &Connection, message_id: &str) -> bool {
+    conn.query_row(
+        "SELECT COUNT(*) > 0 FROM task_messages WHERE id = ?1",
+        [message_id],
+        |row| row.get(0),
+    )
+    .unwrap_or(false)
+}
+
+/// Save a task (upsert); an archived task stays archived
+///
+/// The task row is updated in place rather than replaced, so what hangs off it
+/// (notes, tags, plans, checkpoints, search entries, ...) is kept. Messages are
+/// matched by ID: stored ones keep their diffs, versions and attachments,
+/// missing ones are removed, and archived ones are left alone.
 pub fn save_task(conn: &Connection, task: &TaskInput) -> Result<(), String> {
-    // Use a transaction for atomicity
+    // A savepoint rather than a transaction, so callers may already be in one
+    conn.execute_batch("SAVEPOINT save_task")
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    match write_task(conn, task) {
+        Ok(()) => conn
+            .execute_batch("RELEASE save_task")
+            .map_err(|e| format!("Failed to commit task: {}", e)),
+        Err(e) => {
+            let _ = conn.execute_batch("ROLLBACK TO save_task; RELEASE save_task");
+            Err(e)
+        }
+    }
+}
+
+fn write_task(conn: &Connection, task: &TaskInput) -> Result<(), String> {
+    let previous_status = get_status(conn, &task.id)?;
+
     conn.execute(
-        "INSERT OR REPLACE INTO tasks
-         (id, prompt, summary, status, session_id, created_at, started_at, completed_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        "INSERT INTO tasks
+         (id, prompt, summary, status, session_id, created_at, started_at, completed_at,
+          workspace_id, allow_web_search, title, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, COALESCE(?12, ?6))
+         ON CONFLICT(id) DO UPDATE SET
+             prompt = excluded.prompt,
+             summary = excluded.summary,
+             status = excluded.status,
+             session_id = excluded.session_id,
+             created_at = excluded.created_at,
+             started_at = excluded.started_at,
+             completed_at = excluded.completed_at,
+             workspace_id = excluded.workspace_id,
+             allow_web_search = excluded.allow_web_search,
+             title = COALESCE(?11, title),
+             updated_at = COALESCE(?12, updated_at)",
         params![
             task.id,
             task.prompt,
@@ -261,127 +797,728 @@ pub fn save_task(conn: &Connection, task: &TaskInput) -> Result<(), String> {
             task.created_at,
             task.started_at,
             task.completed_at,
+            task.workspace_id,
+            task.allow_web_search,
+            task.title,
+            task.updated_at,
         ],
     )
     .map_err(|e| format!("Failed to save task: {}", e))?;
 
-    // Delete existing messages (cascade handles attachments)
-    conn.execute("DELETE FROM task_messages WHERE task_id = ?1", [&task.id])
-        .map_err(|e| format!("Failed to delete old messages: {}", e))?;
+    write_messages(conn, &task.id, &task.messages)?;
 
-    // Insert messages
-    for (sort_order, msg) in task.messages.iter().enumerate() {
-        conn.execute(
+    if previous_status.as_deref() != Some(task.status.as_str()) {
+        super::task_events::record(
+            conn,
+            &task.id,
+            previous_status.as_deref(),
+            &task.status,
+            super::task_events::SOURCE_SAVE,
+            None,
+        )?;
+    }
+
+    archive_old_tasks(conn)?;
+
+    Ok(())
+}
+
+/// Make a task's current messages the given ones, in order
+fn write_messages(
+    conn: &Connection,
+    task_id: &str,
+    messages: &[TaskMessageInput],
+) -> Result<(), String> {
+    let mut stmt = conn
+        .prepare("SELECT id FROM task_messages WHERE task_id = ?1 AND archived_at IS NULL")
+        .map_err(|e| format!("Failed to prepare messages query: {}", e))?;
+    let stored: HashSet<String> = stmt
+        .query_map([task_id], |row| row.get(0))
+        .map_err(|e| format!("Failed to query messages: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    let kept: HashSet<&str> = messages.iter().map(|m| m.id.as_str()).collect();
+
+    // Messages no longer in the task; attachments, diffs and versions cascade
+    for id in stored.iter().filter(|id| !kept.contains(id.as_str())) {
+        conn.execute("DELETE FROM task_messages WHERE id = ?1", [id])
+            .map_err(|e| format!("Failed to delete old message: {}", e))?;
+    }
+
+    // Clear the kept messages' positions so renumbering can't collide with them,
+    // and number after archived messages, which keep theirs
+    conn.execute(
+        "UPDATE task_messages SET sort_order = -1 - sort_order
+         WHERE task_id = ?1 AND archived_at IS NULL",
+        [task_id],
+    )
+    .map_err(|e| format!("Failed to reorder messages: {}", e))?;
+    let first: i64 = conn
+        .query_row(
+            "SELECT COALESCE(MAX(sort_order) + 1, 0) FROM task_messages
+             WHERE task_id = ?1 AND archived_at IS NOT NULL",
+            [task_id],
+            |row| row.get(0),
+        )
+        .map_err(|e| format!("Failed to reorder messages: {}", e))?;
+
+    for (position, msg) in messages.iter().enumerate() {
+        let sort_order = first + position as i64;
+        if stored.contains(&msg.id) {
+            conn.execute(
+                "UPDATE task_messages
+                 SET type = ?2, content = ?3, tool_name = ?4, tool_input = ?5, timestamp = ?6,
+                     sort_order = ?7, note = ?8, origin_id = COALESCE(?9, origin_id)
+                 WHERE id = ?1",
+                params![
+                    msg.id,
+                    msg.msg_type,
+                    msg.content,
+                    msg.tool_name,
+                    msg.tool_input.as_ref().map(|v| v.to_string()),
+                    msg.timestamp,
+                    sort_order,
+                    msg.note,
+                    msg.origin_id,
+                ],
+            )
+            .map_err(|e| format!("Failed to update message: {}", e))?;
+            continue;
+        }
+
+        // A message archived here, or already stored under its origin, stays as it is
+        let inserted = conn.execute(
             "INSERT INTO task_messages
-             (id, task_id, type, content, tool_name, tool_input, timestamp, sort_order)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+             (id, task_id, type, content, tool_name, tool_input, timestamp, sort_order, note,
+              origin_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+             ON CONFLICT DO NOTHING",
             params![
                 msg.id,
-                task.id,
+                task_id,
                 msg.msg_type,
                 msg.content,
                 msg.tool_name,
                 msg.tool_input.as_ref().map(|v| v.to_string()),
                 msg.timestamp,
-                sort_order as i32,
+                sort_order,
+                msg.note,
+                msg.origin_id,
             ],
         )
         .map_err(|e| format!("Failed to insert message: {}", e))?;
+        if inserted == 0 {
+            continue;
+        }
 
-        // Insert attachments
         if let Some(attachments) = &msg.attachments {
             for att in attachments {
-                conn.execute(
-                    "INSERT INTO task_attachments (message_id, type, data, label)
-                     VALUES (?1, ?2, ?3, ?4)",
-                    params![msg.id, att.att_type, att.data, att.label],
-                )
-                .map_err(|e| format!("Failed to insert attachment: {}", e))?;
+                insert_attachment(conn, &msg.id, att)?;
             }
         }
     }
+    Ok(())
+}
 
-    // Enforce max history limit
-    conn.execute(
-        "DELETE FROM tasks WHERE id NOT IN (
-             SELECT id FROM tasks ORDER BY created_at DESC LIMIT ?1
-         )",
-        [MAX_HISTORY_ITEMS],
+/// Archive the tasks beyond MAX_HISTORY_ITEMS, returning how many were archived
+///
+/// A restored task counts as new from when it was restored, so it isn't
+/// archived again by the next save.
+fn archive_old_tasks(conn: &Connection) -> Result<usize, String> {
+    let archived = conn
+        .execute(
+            "UPDATE tasks SET archived_at = ?2
+             WHERE archived_at IS NULL AND id NOT IN (
+                 SELECT id FROM tasks WHERE archived_at IS NULL
+                 ORDER BY julianday(COALESCE(restored_at, created_at)) DESC LIMIT ?1
+             )",
+            params![MAX_HISTORY_ITEMS, chrono::Utc::now().to_rfc3339()],
+        )
+        .map_err(|e| format!("Failed to archive old tasks: {}", e))?;
+    if archived > 0 {
+        tracing::info!("[Tasks] Archived {} tasks beyond the history limit", archived);
+    }
+    Ok(archived)
+}
+
+/// A task moved out of the history
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivedTask {
+    pub task_id: String,
+    pub title: Option<String>,
+    pub prompt: String,
+    pub status: String,
+    pub created_at: String,
+    pub archived_at: String,
+}
+
+/// Archived tasks, most recently archived first
+pub fn get_archived_tasks(conn: &Connection) -> Result<Vec<ArchivedTask>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, title, prompt, status, created_at, archived_at FROM tasks
+             WHERE archived_at IS NOT NULL
+             ORDER BY archived_at DESC, created_at DESC",
+        )
+        .map_err(|e| format!("Failed to prepare archived tasks query: {}", e))?;
+    let tasks = stmt
+        .query_map([], |row| {
+            Ok(ArchivedTask {
+                task_id: row.get(0)?,
+                title: row.get(1)?,
+                prompt: row.get(2)?,
+                status: row.get(3)?,
+                created_at: row.get(4)?,
+                archived_at: row.get(5)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query archived tasks: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(tasks)
+}
+
+/// Whether a task is archived
+pub fn is_archived(conn: &Connection, task_id: &str) -> bool {
+    conn.query_row(
+        "SELECT archived_at IS NOT NULL FROM tasks WHERE id = ?1",
+        [task_id],
+        |row| row.get(0),
     )
-    .map_err(|e| format!("Failed to enforce history limit: {}", e))?;
+    .unwrap_or(false)
+}
 
-    Ok(())
+/// Move an archived task back into the history, archiving the oldest task if it is full
+///
+/// Returns false when the task isn't archived.
+pub fn restore_task(conn: &Connection, task_id: &str) -> Result<bool, String> {
+    let restored = super::with_retry(|| {
+        conn.execute(
+            "UPDATE tasks SET archived_at = NULL, restored_at = ?2
+             WHERE id = ?1 AND archived_at IS NOT NULL",
+            params![task_id, chrono::Utc::now().to_rfc3339()],
+        )
+    })
+    .map_err(|e| format!("Failed to restore task: {}", e))?;
+    if restored == 0 {
+        return Ok(false);
+    }
+    archive_old_tasks(conn)?;
+    Ok(true)
 }
 
-/// Update task status
+/// Save a task read back from storage or an export (upsert)
+pub fn save_stored_task(conn: &Connection, task: &StoredTask) -> Result<(), String> {
+    let input = TaskInput {
+        id: task.id.clone(),
+        prompt: task.prompt.clone(),
+        status: task.status.clone(),
+        messages: task
+            .messages
+            .iter()
+            .map(|m| TaskMessageInput {
+                id: m.id.clone(),
+                msg_type: m.msg_type.clone(),
+                content: m.content.clone(),
+                timestamp: m.timestamp.clone(),
+                tool_name: m.tool_name.clone(),
+                tool_input: m.tool_input.clone(),
+                attachments: m.attachments.as_ref().map(|atts| {
+                    atts.iter()
+                        .map(|a| AttachmentInput {
+                            att_type: a.att_type.clone(),
+                            data: a.data.clone(),
+                            label: a.label.clone(),
+                            thumbnail: None,
+                        })
+                        .collect()
+                }),
+                note: m.note.clone(),
+                origin_id: None,
+            })
+            .collect(),
+        session_id: task.session_id.clone(),
+        summary: task.summary.clone(),
+        title: task.title.clone(),
+        created_at: task.created_at.clone(),
+        started_at: task.started_at.clone(),
+        completed_at: task.completed_at.clone(),
+        workspace_id: task.workspace_id.clone(),
+        allow_web_search: task.allow_web_search,
+        updated_at: task.updated_at.clone(),
+    };
+    save_task(conn, &input)
+}
+
+/// Current status of a task, if it exists
+fn get_status(conn: &Connection, task_id: &str) -> Result<Option<String>, String> {
+    conn.query_row("SELECT status FROM tasks WHERE id = ?1", [task_id], |row| row.get(0))
+        .optional()
+        .map_err(|e| format!("Failed to get task status: {}", e))
+}
+
+/// Update task status, recording the change with what made it (a `task_events::SOURCE_`
+/// value) and why
 pub fn update_task_status(
     conn: &Connection,
     task_id: &str,
     status: &str,
     completed_at: Option<&str>,
+    source: &str,
+    reason: Option<&str>,
 ) -> Result<(), String> {
+    let Some(previous_status) = get_status(conn, task_id)? else {
+        return Ok(());
+    };
+    let now = chrono::Utc::now().to_rfc3339();
     if let Some(completed) = completed_at {
-        conn.execute(
-            "UPDATE tasks SET status = ?1, completed_at = ?2 WHERE id = ?3",
-            params![status, completed, task_id],
-        )
+        super::with_retry(|| {
+            conn.execute(
+                "UPDATE tasks SET status = ?1, completed_at = ?2, updated_at = ?4 WHERE id = ?3",
+                params![status, completed, task_id, now],
+            )
+        })
         .map_err(|e| format!("Failed to update task status: {}", e))?;
     } else {
-        conn.execute(
-            "UPDATE tasks SET status = ?1 WHERE id = ?2",
-            params![status, task_id],
-        )
+        super::with_retry(|| {
+            conn.execute(
+                "UPDATE tasks SET status = ?1, updated_at = ?3 WHERE id = ?2",
+                params![status, task_id, now],
+            )
+        })
         .map_err(|e| format!("Failed to update task status: {}", e))?;
     }
+    if previous_status != status {
+        super::task_events::record(
+            conn,
+            task_id,
+            Some(&previous_status),
+            status,
+            source,
+            reason,
+        )?;
+    }
     Ok(())
 }
 
-/// Add a message to a task
+/// Add a message to a task, or update it when its ID is already stored
+///
+/// A streamed message is saved again as it grows, so a stored one takes the
+/// new content, tool input and attachments, as `save_task` does. Returns
+/// false, writing nothing, when another message with the same `origin_id` is
+/// already stored for the task, so replayed events are safe to add again.
 pub fn add_task_message(
     conn: &Connection,
     task_id: &str,
     message: &TaskMessageInput,
+) -> Result<bool, String> {
+    let stored = message_exists(conn, &message.id);
+    // The next sort_order is computed in the insert itself, so appends racing
+    // from another connection can't both take it (the unique index backs this up).
+    // An update keeps the message's place and leaves other tasks' messages alone.
+    let written = super::with_retry(|| {
+        conn.execute(
+            "INSERT INTO task_messages
+             (id, task_id, type, content, tool_name, tool_input, timestamp, sort_order, note,
+              origin_id)
+             SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7, COALESCE(MAX(sort_order) + 1, 0), ?8, ?9
+             FROM task_messages WHERE task_id = ?2
+             ON CONFLICT (id) DO UPDATE
+                 SET type = excluded.type, content = excluded.content,
+                     tool_name = excluded.tool_name, tool_input = excluded.tool_input
+                 WHERE task_messages.task_id = excluded.task_id
+             ON CONFLICT (task_id, origin_id) WHERE origin_id IS NOT NULL DO UPDATE
+                 SET type = excluded.type, content = excluded.content,
+                     tool_name = excluded.tool_name, tool_input = excluded.tool_input
+                 WHERE task_messages.id = excluded.id",
+            params![
+                message.id,
+                task_id,
+                message.msg_type,
+                message.content,
+                message.tool_name,
+                message.tool_input.as_ref().map(|v| v.to_string()),
+                message.timestamp,
+                message.note,
+                message.origin_id,
+            ],
+        )
+    })
+    .map_err(|e| format!("Failed to add message: {}", e))?;
+    if written == 0 {
+        tracing::debug!(
+            "[DB] Skipped message {} of task {}, its origin is already stored",
+            message.id,
+            task_id
+        );
+        return Ok(false);
+    }
+
+    if let Some(attachments) = &message.attachments {
+        if stored {
+            super::with_retry(|| {
+                conn.execute("DELETE FROM task_attachments WHERE message_id = ?1", [&message.id])
+            })
+            .map_err(|e| format!("Failed to replace attachments: {}", e))?;
+        }
+        for att in attachments {
+            insert_attachment(conn, &message.id, att)?;
+        }
+    }
+
+    super::with_retry(|| {
+        conn.execute(
+            "UPDATE tasks SET updated_at = ?2 WHERE id = ?1",
+            params![task_id, chrono::Utc::now().to_rfc3339()],
+        )
+    })
+    .map_err(|e| format!("Failed to update task activity: {}", e))?;
+
+    Ok(true)
+}
+
+/// Attach data to a message, storing the data itself once (see `blobs`)
+///
+/// Data over the `max_attachment_bytes` setting is left out, leaving an empty
+/// attachment whose label says so, and screenshots get their thumbnail stored
+/// with them for task lists.
+fn insert_attachment(
+    conn: &Connection,
+    message_id: &str,
+    att: &AttachmentInput,
+) -> Result<(), String> {
+    let max_bytes = super::settings::get_max_attachment_bytes(conn)
+        .unwrap_or(crate::attachments::DEFAULT_MAX_BYTES);
+    let oversized = att.data.len() as u64 > max_bytes;
+    let (data, label, thumbnail) = if oversized {
+        tracing::warn!(
+            "[Tasks] Left out a {} attachment of message {} over the size limit",
+            att.att_type,
+            message_id
+        );
+        let label = format!(
+            "{} (left out: over the {} byte limit)",
+            att.label.as_deref().unwrap_or(&att.att_type),
+            max_bytes
+        );
+        ("", Some(label), None)
+    } else if att.att_type == "screenshot" && att.thumbnail.is_none() {
+        let thumbnail = crate::attachments::screenshot_thumbnail(&att.data)
+            .map_err(|e| tracing::warn!("[Tasks] No thumbnail for message {}: {}", message_id, e))
+            .ok();
+        (att.data.as_str(), att.label.clone(), thumbnail)
+    } else {
+        (att.data.as_str(), att.label.clone(), att.thumbnail.clone())
+    };
+
+    let hash = super::blobs::store(conn, data)?;
+    super::with_retry(|| {
+        conn.execute(
+            "INSERT INTO task_attachments (message_id, type, data, label, blob_hash, thumbnail)
+             VALUES (?1, ?2, '', ?3, ?4, ?5)",
+            params![message_id, att.att_type, label, hash, thumbnail],
+        )
+    })
+    .map_err(|e| format!("Failed to insert attachment: {}", e))?;
+    Ok(())
+}
+
+/// Stored thumbnail of a screenshot attached to a message, as a JPEG data URL
+pub fn get_message_thumbnail(
+    conn: &Connection,
+    attachment_id: i64,
+) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT thumbnail FROM task_attachments WHERE id = ?1",
+        [attachment_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .map(Option::flatten)
+    .map_err(|e| format!("Failed to get thumbnail: {}", e))
+}
+
+/// Store the thumbnail of a screenshot attached before thumbnails existed
+pub fn set_message_thumbnail(
+    conn: &Connection,
+    attachment_id: i64,
+    thumbnail: &str,
 ) -> Result<(), String> {
-    // Get the next sort_order
-    let max_order: Option<i32> = conn
+    super::with_retry(|| {
+        conn.execute(
+            "UPDATE task_attachments SET thumbnail = ?1 WHERE id = ?2",
+            params![thumbnail, attachment_id],
+        )
+    })
+    .map_err(|e| format!("Failed to store thumbnail: {}", e))?;
+    Ok(())
+}
+
+/// Replace a user message's content and archive every message after it
+///
+/// The previous content is kept in `task_message_versions`. Archived messages
+/// stay in the table but are no longer returned with the task. Returns the
+/// time of the edit, which `undo_message_edit` takes.
+pub fn edit_message(
+    conn: &Connection,
+    task_id: &str,
+    message_id: &str,
+    content: &str,
+) -> Result<String, String> {
+    let (msg_type, old_content, version, sort_order): (String, String, i64, i64) = conn
         .query_row(
-            "SELECT MAX(sort_order) FROM task_messages WHERE task_id = ?1",
-            [task_id],
-            |row| row.get(0),
+            "SELECT type, content, version, sort_order FROM task_messages
+             WHERE id = ?1 AND task_id = ?2 AND archived_at IS NULL",
+            params![message_id, task_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
         )
-        .unwrap_or(None);
+        .map_err(|_| format!("Message not found: {}", message_id))?;
+    if msg_type != "user" {
+        return Err("Only user messages can be edited".to_string());
+    }
 
-    let sort_order = max_order.map(|m| m + 1).unwrap_or(0);
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO task_message_versions (message_id, version, content, replaced_at)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![message_id, version, old_content, now],
+    )
+    .map_err(|e| format!("Failed to save message version: {}", e))?;
 
     conn.execute(
-        "INSERT INTO task_messages
-         (id, task_id, type, content, tool_name, tool_input, timestamp, sort_order)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-        params![
-            message.id,
-            task_id,
-            message.msg_type,
-            message.content,
-            message.tool_name,
-            message.tool_input.as_ref().map(|v| v.to_string()),
-            message.timestamp,
-            sort_order,
-        ],
+        "UPDATE task_messages SET content = ?1, version = ?2, timestamp = ?3 WHERE id = ?4",
+        params![content, version + 1, now, message_id],
     )
-    .map_err(|e| format!("Failed to add message: {}", e))?;
+    .map_err(|e| format!("Failed to update message: {}", e))?;
 
-    // Insert attachments
-    if let Some(attachments) = &message.attachments {
-        for att in attachments {
-            conn.execute(
-                "INSERT INTO task_attachments (message_id, type, data, label)
-                 VALUES (?1, ?2, ?3, ?4)",
-                params![message.id, att.att_type, att.data, att.label],
-            )
-            .map_err(|e| format!("Failed to insert attachment: {}", e))?;
-        }
+    conn.execute(
+        "UPDATE task_messages SET archived_at = ?1
+         WHERE task_id = ?2 AND sort_order > ?3 AND archived_at IS NULL",
+        params![now, task_id, sort_order],
+    )
+    .map_err(|e| format!("Failed to archive messages: {}", e))?;
+
+    Ok(now)
+}
+
+/// Undo the `edit_message` made at `edited_at`, restoring the message's
+/// previous content and `timestamp` and the messages it archived
+pub fn undo_message_edit(
+    conn: &Connection,
+    task_id: &str,
+    message_id: &str,
+    timestamp: &str,
+    edited_at: &str,
+) -> Result<(), String> {
+    let (version, content): (i64, String) = conn
+        .query_row(
+            "SELECT version, content FROM task_message_versions
+             WHERE message_id = ?1 ORDER BY version DESC LIMIT 1",
+            [message_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|_| format!("No earlier version of message: {}", message_id))?;
+
+    conn.execute(
+        "UPDATE task_messages SET content = ?1, version = ?2, timestamp = ?3
+         WHERE id = ?4 AND task_id = ?5",
+        params![content, version, timestamp, message_id, task_id],
+    )
+    .map_err(|e| format!("Failed to restore message: {}", e))?;
+
+    conn.execute(
+        "DELETE FROM task_message_versions WHERE message_id = ?1 AND version = ?2",
+        params![message_id, version],
+    )
+    .map_err(|e| format!("Failed to remove message version: {}", e))?;
+
+    conn.execute(
+        "UPDATE task_messages SET archived_at = NULL WHERE task_id = ?1 AND archived_at = ?2",
+        params![task_id, edited_at],
+    )
+    .map_err(|e| format!("Failed to restore messages: {}", e))?;
+
+    Ok(())
+}
+
+/// Archive a task's messages past the first `keep`, returning how many were archived
+pub fn archive_messages_after(
+    conn: &Connection,
+    task_id: &str,
+    keep: u32,
+) -> Result<usize, String> {
+    let now = chrono::Utc::now().to_rfc3339();
+    conn.execute(
+        "UPDATE task_messages SET archived_at = ?1
+         WHERE task_id = ?2 AND archived_at IS NULL AND id NOT IN (
+             SELECT id FROM task_messages WHERE task_id = ?2 AND archived_at IS NULL
+             ORDER BY sort_order ASC LIMIT ?3
+         )",
+        params![now, task_id, keep],
+    )
+    .map_err(|e| format!("Failed to archive messages: {}", e))
+}
+
+/// Earlier versions of a message, oldest first
+pub fn get_message_versions(conn: &Connection, message_id: &str) -> Result<Vec<MessageVersion>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT version, content, replaced_at FROM task_message_versions
+             WHERE message_id = ?1 ORDER BY version ASC",
+        )
+        .map_err(|e| format!("Failed to prepare versions query: {}", e))?;
+    let versions = stmt
+        .query_map([message_id], |row| {
+            Ok(MessageVersion {
+                version: row.get(0)?,
+                content: row.get(1)?,
+                replaced_at: row.get(2)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query message versions: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(versions)
+}
+
+/// Content a redacted message is left with
+pub const REDACTED_CONTENT: &str = "[redacted]";
+
+/// Origin of a message of the task, failing if the task has no such message
+fn message_origin(
+    conn: &Connection,
+    task_id: &str,
+    message_id: &str,
+) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT origin_id FROM task_messages WHERE id = ?1 AND task_id = ?2",
+        params![message_id, task_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get message: {}", e))?
+    .ok_or_else(|| format!("Message not found: {}", message_id))
+}
+
+/// Remove the sources and artifacts taken from a message, which are keyed by its origin
+fn delete_message_extras(
+    conn: &Connection,
+    task_id: &str,
+    origin_id: Option<&str>,
+) -> Result<(), String> {
+    let Some(origin_id) = origin_id else {
+        return Ok(());
+    };
+    conn.execute(
+        "DELETE FROM message_sources WHERE task_id = ?1 AND origin_id = ?2",
+        params![task_id, origin_id],
+    )
+    .map_err(|e| format!("Failed to delete message sources: {}", e))?;
+    conn.execute(
+        "DELETE FROM task_artifacts WHERE task_id = ?1 AND origin_id = ?2",
+        params![task_id, origin_id],
+    )
+    .map_err(|e| format!("Failed to delete message artifacts: {}", e))?;
+    Ok(())
+}
+
+/// Delete a single message of a task; its attachments, diff, versions and rating
+/// cascade, and its sources and artifacts go with it
+///
+/// Its origin is kept in `deleted_messages`, so syncing the session doesn't add
+/// it back.
+pub fn delete_message(conn: &Connection, task_id: &str, message_id: &str) -> Result<(), String> {
+    let origin_id = message_origin(conn, task_id, message_id)?;
+    if let Some(origin_id) = &origin_id {
+        conn.execute(
+            "INSERT OR IGNORE INTO deleted_messages (task_id, origin_id, deleted_at)
+             VALUES (?1, ?2, ?3)",
+            params![task_id, origin_id, chrono::Utc::now().to_rfc3339()],
+        )
+        .map_err(|e| format!("Failed to record deleted message: {}", e))?;
+    }
+    conn.execute(
+        "DELETE FROM task_messages WHERE id = ?1 AND task_id = ?2",
+        params![message_id, task_id],
+    )
+    .map_err(|e| format!("Failed to delete message: {}", e))?;
+    delete_message_extras(conn, task_id, origin_id.as_deref())
+}
+
+/// Blank a message of a task, keeping its place in the conversation
+///
+/// The content becomes `REDACTED_CONTENT`; the tool input, note, attachments,
+/// diff, earlier versions, sources and artifacts are removed.
+pub fn redact_message(conn: &Connection, task_id: &str, message_id: &str) -> Result<(), String> {
+    let origin_id = message_origin(conn, task_id, message_id)?;
+    conn.execute(
+        "UPDATE task_messages SET content = ?3, tool_input = NULL, note = NULL
+         WHERE id = ?1 AND task_id = ?2",
+        params![message_id, task_id, REDACTED_CONTENT],
+    )
+    .map_err(|e| format!("Failed to redact message: {}", e))?;
+    for table in ["task_attachments", "message_diffs", "task_message_versions"] {
+        conn.execute(&format!("DELETE FROM {} WHERE message_id = ?1", table), [message_id])
+            .map_err(|e| format!("Failed to redact message: {}", e))?;
     }
+    delete_message_extras(conn, task_id, origin_id.as_deref())
+}
 
+/// Set or clear (`None`) the note on a message
+pub fn set_message_note(conn: &Connection, message_id: &str, note: Option<&str>) -> Result<(), String> {
+    let updated = conn
+        .execute(
+            "UPDATE task_messages SET note = ?1 WHERE id = ?2",
+            params![note, message_id],
+        )
+        .map_err(|e| format!("Failed to annotate message: {}", e))?;
+    if updated == 0 {
+        return Err(format!("Message not found: {}", message_id));
+    }
+    Ok(())
+}
+
+/// Record the directory a task runs in, for one started outside its workspace's directory
+pub fn set_working_directory(
+    conn: &Connection,
+    task_id: &str,
+    directory: &str,
+) -> Result<(), String> {
+    super::with_retry(|| {
+        conn.execute(
+            "UPDATE tasks SET working_directory = ?1 WHERE id = ?2",
+            params![directory, task_id],
+        )
+    })
+    .map_err(|e| format!("Failed to set working directory: {}", e))?;
+    Ok(())
+}
+
+/// Directory a task runs in: the one it was started in, otherwise its workspace's
+pub fn get_working_directory(conn: &Connection, task_id: &str) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT COALESCE(t.working_directory, w.path) FROM tasks t
+         LEFT JOIN workspaces w ON w.id = t.workspace_id
+         WHERE t.id = ?1",
+        [task_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .map(Option::flatten)
+    .map_err(|e| format!("Failed to get working directory: {}", e))
+}
+
+/// Forget a task's session and last result, for a rerun that starts a new session
+pub fn clear_task_session(conn: &Connection, task_id: &str) -> Result<(), String> {
+    super::with_retry(|| {
+        conn.execute(
+            "UPDATE tasks SET session_id = NULL, result_status = NULL, result_error = NULL,
+             result_session_id = NULL WHERE id = ?1",
+            params![task_id],
+        )
+    })
+    .map_err(|e| format!("Failed to clear session: {}", e))?;
     Ok(())
 }
 
@@ -391,28 +1528,128 @@ pub fn update_task_session_id(
     task_id: &str,
     session_id: &str,
 ) -> Result<(), String> {
-    conn.execute(
-        "UPDATE tasks SET session_id = ?1 WHERE id = ?2",
-        params![session_id, task_id],
-    )
+    super::with_retry(|| {
+        conn.execute(
+            "UPDATE tasks SET session_id = ?1 WHERE id = ?2",
+            params![session_id, task_id],
+        )
+    })
     .map_err(|e| format!("Failed to update session ID: {}", e))?;
     Ok(())
 }
 
+/// Set a task's generated title
+pub fn update_task_title(conn: &Connection, task_id: &str, title: &str) -> Result<(), String> {
+    super::with_retry(|| {
+        conn.execute(
+            "UPDATE tasks SET title = ?1 WHERE id = ?2",
+            params![title, task_id],
+        )
+    })
+    .map_err(|e| format!("Failed to update title: {}", e))?;
+    Ok(())
+}
+
 /// Update task summary
 pub fn update_task_summary(conn: &Connection, task_id: &str, summary: &str) -> Result<(), String> {
-    conn.execute(
-        "UPDATE tasks SET summary = ?1 WHERE id = ?2",
-        params![summary, task_id],
-    )
+    super::with_retry(|| {
+        conn.execute(
+            "UPDATE tasks SET summary = ?1 WHERE id = ?2",
+            params![summary, task_id],
+        )
+    })
     .map_err(|e| format!("Failed to update summary: {}", e))?;
     Ok(())
 }
 
+/// Model and agent a task was started with
+#[derive(Debug, Clone, Default)]
+pub struct TaskModel {
+    pub model_id: Option<String>,
+    pub agent: Option<String>,
+}
+
+/// Record the model and agent a task was started with
+pub fn set_task_model(conn: &Connection, task_id: &str, model: &TaskModel) -> Result<(), String> {
+    super::with_retry(|| {
+        conn.execute(
+            "UPDATE tasks SET model_id = ?2, agent = ?3 WHERE id = ?1",
+            params![task_id, model.model_id, model.agent],
+        )
+    })
+    .map_err(|e| format!("Failed to save task model: {}", e))?;
+    Ok(())
+}
+
+/// Get the model and agent a task was started with; unset for tasks started before they were kept
+pub fn get_task_model(conn: &Connection, task_id: &str) -> Result<TaskModel, String> {
+    conn.query_row(
+        "SELECT model_id, agent FROM tasks WHERE id = ?1",
+        [task_id],
+        |row| {
+            Ok(TaskModel {
+                model_id: row.get(0)?,
+                agent: row.get(1)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get task model: {}", e))
+    .map(Option::unwrap_or_default)
+}
+
+/// How a task's last run ended, as the sidecar reported it
+#[derive(Debug, Clone)]
+pub struct StoredResult {
+    /// `success`, `interrupted` or `error`
+    pub status: String,
+    pub error: Option<String>,
+    pub session_id: Option<String>,
+}
+
+/// Record how a task's last run ended
+pub fn set_task_result(
+    conn: &Connection,
+    task_id: &str,
+    result: &StoredResult,
+) -> Result<(), String> {
+    super::with_retry(|| {
+        conn.execute(
+            "UPDATE tasks SET result_status = ?2, result_error = ?3, result_session_id = ?4
+             WHERE id = ?1",
+            params![task_id, result.status, result.error, result.session_id],
+        )
+    })
+    .map_err(|e| format!("Failed to save task result: {}", e))?;
+    Ok(())
+}
+
+/// Get how a task's last run ended; unset until one ends, and for older tasks
+pub fn get_task_result(conn: &Connection, task_id: &str) -> Result<Option<StoredResult>, String> {
+    conn.query_row(
+        "SELECT result_status, result_error, result_session_id FROM tasks WHERE id = ?1",
+        [task_id],
+        |row| {
+            let status: Option<String> = row.get(0)?;
+            let (error, session_id) = (row.get(1)?, row.get(2)?);
+            Ok(status.map(|status| StoredResult {
+                status,
+                error,
+                session_id,
+            }))
+        },
+    )
+    .optional()
+    .map(Option::flatten)
+    .map_err(|e| format!("Failed to get task result: {}", e))
+}
+
 /// Delete a task
 pub fn delete_task(conn: &Connection, task_id: &str) -> Result<(), String> {
     conn.execute("DELETE FROM tasks WHERE id = ?1", [task_id])
         .map_err(|e| format!("Failed to delete task: {}", e))?;
+    conn.execute("DELETE FROM task_events WHERE task_id = ?1", [task_id])
+        .map_err(|e| format!("Failed to delete task events: {}", e))?;
     Ok(())
 }
 
@@ -420,5 +1657,197 @@ pub fn delete_task(conn: &Connection, task_id: &str) -> Result<(), String> {
 pub fn clear_history(conn: &Connection) -> Result<(), String> {
     conn.execute("DELETE FROM tasks", [])
         .map_err(|e| format!("Failed to clear history: {}", e))?;
+    conn.execute("DELETE FROM task_events", [])
+        .map_err(|e| format!("Failed to clear task events: {}", e))?;
     Ok(())
 }
+
+/// Remember that a task was deleted so sync does not bring it back
+pub fn record_tombstone(conn: &Connection, task_id: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR IGNORE INTO sync_tombstones (task_id, deleted_at) VALUES (?1, ?2)",
+        params![task_id, chrono::Utc::now().to_rfc3339()],
+    )
+    .map_err(|e| format!("Failed to record tombstone: {}", e))?;
+    Ok(())
+}
+
+/// IDs of all deleted tasks
+pub fn get_tombstones(conn: &Connection) -> Result<Vec<String>, String> {
+    let mut stmt = conn
+        .prepare("SELECT task_id FROM sync_tombstones")
+        .map_err(|e| format!("Failed to prepare tombstones query: {}", e))?;
+    let ids = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| format!("Failed to query tombstones: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(ids)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// A migrated database in a temporary file, removed when dropped
+    pub(crate) struct TestDb(PathBuf);
+
+    impl TestDb {
+        pub(crate) fn new() -> Self {
+            let path = std::env::temp_dir()
+                .join(format!("cowork-z-test-{}.db", uuid::Uuid::new_v4()));
+            let db = TestDb(path);
+            super::super::migrations::run_migrations(&db.open()).unwrap();
+            db
+        }
+
+        pub(crate) fn open(&self) -> Connection {
+            super::super::open_database(&self.0).unwrap()
+        }
+    }
+
+    impl Drop for TestDb {
+        fn drop(&mut self) {
+            for suffix in ["", "-wal", "-shm"] {
+                let mut path = self.0.clone().into_os_string();
+                path.push(suffix);
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+
+    pub(crate) fn save_test_task(conn: &Connection, task_id: &str) {
+        let now = chrono::Utc::now().to_rfc3339();
+        save_task(
+            conn,
+            &TaskInput {
+                id: task_id.to_string(),
+                prompt: "Test".to_string(),
+                status: "running".to_string(),
+                messages: Vec::new(),
+                session_id: None,
+                summary: None,
+                title: None,
+                created_at: now,
+                started_at: None,
+                completed_at: None,
+                workspace_id: None,
+                allow_web_search: None,
+                updated_at: None,
+            },
+        )
+        .unwrap();
+    }
+
+    fn message(id: &str, origin_id: Option<&str>) -> TaskMessageInput {
+        TaskMessageInput {
+            id: id.to_string(),
+            msg_type: "assistant".to_string(),
+            content: id.to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            tool_name: None,
+            tool_input: None,
+            attachments: None,
+            note: None,
+            origin_id: origin_id.map(str::to_string),
+        }
+    }
+
+    fn sort_orders(conn: &Connection, task_id: &str) -> Vec<i64> {
+        let mut stmt = conn
+            .prepare("SELECT sort_order FROM task_messages WHERE task_id = ?1 ORDER BY sort_order")
+            .unwrap();
+        stmt.query_map([task_id], |row| row.get(0))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+    }
+
+    #[test]
+    fn appends_take_consecutive_orders_per_task() {
+        let db = TestDb::new();
+        let conn = db.open();
+        save_test_task(&conn, "task_a");
+        save_test_task(&conn, "task_b");
+
+        for i in 0..3 {
+            assert!(add_task_message(&conn, "task_a", &message(&format!("a{}", i), None)).unwrap());
+        }
+        assert!(add_task_message(&conn, "task_b", &message("b0", None)).unwrap());
+
+        assert_eq!(sort_orders(&conn, "task_a"), vec![0, 1, 2]);
+        assert_eq!(sort_orders(&conn, "task_b"), vec![0]);
+    }
+
+    #[test]
+    fn repeated_origin_is_added_once() {
+        let db = TestDb::new();
+        let conn = db.open();
+        save_test_task(&conn, "task_a");
+
+        assert!(add_task_message(&conn, "task_a", &message("m1", Some("evt_1"))).unwrap());
+        assert!(!add_task_message(&conn, "task_a", &message("m2", Some("evt_1"))).unwrap());
+
+        assert_eq!(sort_orders(&conn, "task_a"), vec![0]);
+    }
+
+    #[test]
+    fn repeated_id_updates_the_message() {
+        let db = TestDb::new();
+        let conn = db.open();
+        save_test_task(&conn, "task_a");
+        save_test_task(&conn, "task_b");
+        add_task_message(&conn, "task_a", &message("m1", Some("evt_1"))).unwrap();
+
+        let mut updated = message("m1", Some("evt_1"));
+        updated.content = "updated".to_string();
+        assert!(add_task_message(&conn, "task_a", &updated).unwrap());
+        assert!(!add_task_message(&conn, "task_b", &updated).unwrap());
+
+        let content: String = conn
+            .query_row("SELECT content FROM task_messages WHERE id = 'm1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(content, "updated");
+        assert_eq!(sort_orders(&conn, "task_a"), vec![0]);
+        assert!(sort_orders(&conn, "task_b").is_empty());
+    }
+
+    #[test]
+    fn duplicate_order_is_rejected() {
+        let db = TestDb::new();
+        let conn = db.open();
+        save_test_task(&conn, "task_a");
+        add_task_message(&conn, "task_a", &message("m1", None)).unwrap();
+
+        let duplicate = conn.execute(
+            "INSERT INTO task_messages (id, task_id, type, content, timestamp, sort_order)
+             VALUES ('m2', 'task_a', 'assistant', '', '', 0)",
+            [],
+        );
+        assert!(duplicate.is_err());
+    }
+
+    #[test]
+    fn concurrent_appends_get_distinct_orders() {
+        let db = TestDb::new();
+        save_test_task(&db.open(), "task_a");
+
+        let writers: Vec<_> = (0..4)
+            .map(|writer| {
+                let conn = db.open();
+                std::thread::spawn(move || {
+                    for i in 0..25 {
+                        let id = format!("w{}_{}", writer, i);
+                        add_task_message(&conn, "task_a", &message(&id, None)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        assert_eq!(sort_orders(&db.open(), "task_a"), (0..100).collect::<Vec<i64>>());
+    }
+}
@@ -0,0 +1,126 @@
+// src-tauri/src/db/templates.rs
+//! Workspace template repository
+//!
+//! Templates imported by the user; the built-in ones live in `templates`.
+//! A template's defaults and agent settings are stored as one JSON column so
+//! new settings don't need a migration here.
+
+use super::workspaces::{AgentSettings, WorkspaceDefaults};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// Settings a new workspace starts with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// Shipped with the app; can't be deleted
+    pub builtin: bool,
+    pub defaults: WorkspaceDefaults,
+    pub settings: AgentSettings,
+}
+
+/// What the `template` column holds
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct Contents {
+    defaults: WorkspaceDefaults,
+    settings: AgentSettings,
+}
+
+fn row_to_template(row: &rusqlite::Row) -> rusqlite::Result<WorkspaceTemplate> {
+    let json: String = row.get(3)?;
+    let contents: Contents = serde_json::from_str(&json).unwrap_or_default();
+    Ok(WorkspaceTemplate {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        builtin: false,
+        defaults: contents.defaults,
+        settings: contents.settings,
+    })
+}
+
+/// Stored templates, by name
+pub fn list_templates(conn: &Connection) -> Result<Vec<WorkspaceTemplate>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, description, template FROM workspace_templates
+             ORDER BY name COLLATE NOCASE ASC",
+        )
+        .map_err(|e| format!("Failed to prepare templates query: {}", e))?;
+    let templates = stmt
+        .query_map([], row_to_template)
+        .map_err(|e| format!("Failed to query templates: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(templates)
+}
+
+/// Get a stored template
+pub fn get_template(conn: &Connection, id: &str) -> Result<Option<WorkspaceTemplate>, String> {
+    conn.query_row(
+        "SELECT id, name, description, template FROM workspace_templates WHERE id = ?1",
+        [id],
+        row_to_template,
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get template: {}", e))
+}
+
+/// Store a template under a new ID
+pub fn save_template(
+    conn: &Connection,
+    name: &str,
+    description: Option<&str>,
+    defaults: &WorkspaceDefaults,
+    settings: &AgentSettings,
+) -> Result<WorkspaceTemplate, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Template name cannot be empty".to_string());
+    }
+    let template = WorkspaceTemplate {
+        id: format!("tpl_{}", uuid::Uuid::new_v4()),
+        name: name.to_string(),
+        description: description
+            .map(str::trim)
+            .filter(|d| !d.is_empty())
+            .map(str::to_string),
+        builtin: false,
+        defaults: defaults.clone(),
+        settings: super::workspaces::normalize_agent_settings(settings)?,
+    };
+    let json = serde_json::to_string(&Contents {
+        defaults: template.defaults.clone(),
+        settings: template.settings.clone(),
+    })
+    .map_err(|e| format!("Failed to serialize template: {}", e))?;
+
+    conn.execute(
+        "INSERT INTO workspace_templates (id, name, description, template, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            template.id,
+            template.name,
+            template.description,
+            json,
+            chrono::Utc::now().to_rfc3339(),
+        ],
+    )
+    .map_err(|e| format!("Failed to save template: {}", e))?;
+    Ok(template)
+}
+
+/// Delete a stored template
+pub fn delete_template(conn: &Connection, id: &str) -> Result<(), String> {
+    let deleted = conn
+        .execute("DELETE FROM workspace_templates WHERE id = ?1", [id])
+        .map_err(|e| format!("Failed to delete template: {}", e))?;
+    if deleted == 0 {
+        return Err(format!("Template not found: {}", id));
+    }
+    Ok(())
+}
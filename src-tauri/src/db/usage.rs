@@ -0,0 +1,488 @@
+// src-tauri/src/db/usage.rs
+//! Token usage and cost repository
+//!
+//! One row per agent step, as reported by the sidecar's `task_usage` event.
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// Usage reported for one agent step
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UsageInput {
+    pub model_id: Option<String>,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub reasoning_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_write_tokens: i64,
+    /// Cost in USD
+    pub cost: f64,
+}
+
+/// Provider part of an OpenCode model ID ("anthropic/claude-..." -> "anthropic")
+pub fn provider_of_model(model_id: &str) -> Option<&str> {
+    model_id.split_once('/').map(|(provider, _)| provider)
+}
+
+/// Record usage for a task step
+pub fn insert_usage(conn: &Connection, task_id: &str, usage: &UsageInput) -> Result<(), String> {
+    let provider_id = usage.model_id.as_deref().and_then(provider_of_model);
+    super::with_retry(|| {
+        conn.execute(
+            "INSERT INTO task_usage
+             (task_id, provider_id, model_id, input_tokens, output_tokens, reasoning_tokens,
+              cache_read_tokens, cache_write_tokens, cost, recorded_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                task_id,
+                provider_id,
+                usage.model_id,
+                usage.input_tokens,
+                usage.output_tokens,
+                usage.reasoning_tokens,
+                usage.cache_read_tokens,
+                usage.cache_write_tokens,
+                usage.cost,
+                chrono::Utc::now().to_rfc3339(),
+            ],
+        )
+    })
+    .map_err(|e| format!("Failed to record usage: {}", e))?;
+    Ok(())
+}
+
+/// Total cost recorded since an RFC 3339 timestamp, optionally for one provider
+pub fn get_spend_since(
+    conn: &Connection,
+    since: &str,
+    provider_id: Option<&str>,
+) -> Result<f64, String> {
+    conn.query_row(
+        "SELECT COALESCE(SUM(cost), 0) FROM task_usage
+         WHERE recorded_at >= ?1 AND (?2 IS NULL OR provider_id = ?2)",
+        params![since, provider_id],
+        |row| row.get(0),
+    )
+    .map_err(|e| format!("Failed to query spend: {}", e))
+}
+
+/// Inclusive RFC 3339 bounds; either side may be open
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DateRange {
+    pub since: Option<String>,
+    pub until: Option<String>,
+}
+
+/// Tasks created on one (UTC) day
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TasksPerDay {
+    pub date: String,
+    pub total: i64,
+    pub completed: i64,
+    pub failed: i64,
+}
+
+/// Tokens and cost recorded on one (UTC) day
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokensPerDay {
+    pub date: String,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cost: f64,
+}
+
+/// Spend attributed to one model
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelCost {
+    pub model_id: String,
+    pub tasks: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cost: f64,
+}
+
+/// Pre-aggregated series for the usage dashboard
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageDashboard {
+    pub tasks_per_day: Vec<TasksPerDay>,
+    pub tokens_per_day: Vec<TokensPerDay>,
+    pub cost_by_model: Vec<ModelCost>,
+    pub total_tasks: i64,
+    pub completed_tasks: i64,
+    pub failed_tasks: i64,
+    /// Completed share of tasks that finished as completed or failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub success_rate: Option<f64>,
+}
+
+/// Aggregate task and usage history for the dashboard
+pub fn get_usage_dashboard(conn: &Connection, range: &DateRange) -> Result<UsageDashboard, String> {
+    let tasks_per_day: Vec<TasksPerDay> = conn
+        .prepare(
+            "SELECT substr(created_at, 1, 10) AS day,
+                    COUNT(*),
+                    SUM(status = 'completed'),
+                    SUM(status = 'failed')
+             FROM tasks
+             WHERE (?1 IS NULL OR created_at >= ?1) AND (?2 IS NULL OR created_at <= ?2)
+             GROUP BY day
+             ORDER BY day",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map(params![range.since, range.until], |row| {
+                Ok(TasksPerDay {
+                    date: row.get(0)?,
+                    total: row.get(1)?,
+                    completed: row.get(2)?,
+                    failed: row.get(3)?,
+                })
+            })?
+            .collect()
+        })
+        .map_err(|e| format!("Failed to aggregate tasks: {}", e))?;
+
+    let tokens_per_day: Vec<TokensPerDay> = conn
+        .prepare(
+            "SELECT substr(recorded_at, 1, 10) AS day,
+                    SUM(input_tokens),
+                    SUM(output_tokens),
+                    SUM(cost)
+             FROM task_usage
+             WHERE (?1 IS NULL OR recorded_at >= ?1) AND (?2 IS NULL OR recorded_at <= ?2)
+             GROUP BY day
+             ORDER BY day",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map(params![range.since, range.until], |row| {
+                Ok(TokensPerDay {
+                    date: row.get(0)?,
+                    input_tokens: row.get(1)?,
+                    output_tokens: row.get(2)?,
+                    cost: row.get(3)?,
+                })
+            })?
+            .collect()
+        })
+        .map_err(|e| format!("Failed to aggregate tokens: {}", e))?;
+
+    let cost_by_model: Vec<ModelCost> = conn
+        .prepare(
+            "SELECT COALESCE(model_id, 'unknown') AS model,
+                    COUNT(DISTINCT task_id),
+                    SUM(input_tokens),
+                    SUM(output_tokens),
+                    SUM(cost)
+             FROM task_usage
+             WHERE (?1 IS NULL OR recorded_at >= ?1) AND (?2 IS NULL OR recorded_at <= ?2)
+             GROUP BY model
+             ORDER BY SUM(cost) DESC",
+        )
+        .and_then(|mut stmt| {
+            stmt.query_map(params![range.since, range.until], |row| {
+                Ok(ModelCost {
+                    model_id: row.get(0)?,
+                    tasks: row.get(1)?,
+                    input_tokens: row.get(2)?,
+                    output_tokens: row.get(3)?,
+                    cost: row.get(4)?,
+                })
+            })?
+            .collect()
+        })
+        .map_err(|e| format!("Failed to aggregate cost by model: {}", e))?;
+
+    let total_tasks = tasks_per_day.iter().map(|d| d.total).sum();
+    let completed_tasks: i64 = tasks_per_day.iter().map(|d| d.completed).sum();
+    let failed_tasks: i64 = tasks_per_day.iter().map(|d| d.failed).sum();
+    let finished = completed_tasks + failed_tasks;
+
+    Ok(UsageDashboard {
+        tasks_per_day,
+        tokens_per_day,
+        cost_by_model,
+        total_tasks,
+        completed_tasks,
+        failed_tasks,
+        success_rate: (finished > 0).then(|| completed_tasks as f64 / finished as f64),
+    })
+}
+
+/// One task's outcome, usage, and ratings, for spreadsheet export
+#[derive(Debug, Clone)]
+pub struct TaskMetrics {
+    pub task_id: String,
+    pub created_at: String,
+    pub title: Option<String>,
+    pub status: String,
+    pub workspace: Option<String>,
+    /// Model of the task's latest step
+    pub model_id: Option<String>,
+    /// Seconds from start to completion, for tasks that finished
+    pub duration_seconds: Option<f64>,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub reasoning_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_write_tokens: i64,
+    pub cost: f64,
+    /// Assistant messages rated up and down
+    pub rated_up: i64,
+    pub rated_down: i64,
+    pub tags: Vec<String>,
+}
+
+/// Every task created in a range, oldest first, with its usage totals and ratings
+pub fn get_task_metrics(conn: &Connection, range: &DateRange) -> Result<Vec<TaskMetrics>, String> {
+    conn.prepare(
+        "SELECT t.id, t.created_at, t.title, t.status, w.name,
+                (SELECT model_id FROM task_usage
+                 WHERE task_id = t.id AND model_id IS NOT NULL
+                 ORDER BY id DESC LIMIT 1),
+                CASE WHEN t.started_at IS NOT NULL AND t.completed_at IS NOT NULL
+                     THEN (julianday(t.completed_at) - julianday(t.started_at)) * 86400 END,
+                COALESCE(u.input_tokens, 0),
+                COALESCE(u.output_tokens, 0),
+                COALESCE(u.reasoning_tokens, 0),
+                COALESCE(u.cache_read_tokens, 0),
+                COALESCE(u.cache_write_tokens, 0),
+                COALESCE(u.cost, 0),
+                (SELECT COUNT(*) FROM message_ratings r JOIN task_messages m ON m.id = r.message_id
+                 WHERE m.task_id = t.id AND r.rating = 'up'),
+                (SELECT COUNT(*) FROM message_ratings r JOIN task_messages m ON m.id = r.message_id
+                 WHERE m.task_id = t.id AND r.rating = 'down'),
+                (SELECT group_concat(tag, char(10))
+                 FROM (SELECT tag FROM task_tags WHERE task_id = t.id ORDER BY tag))
+         FROM tasks t
+         LEFT JOIN workspaces w ON w.id = t.workspace_id
+         LEFT JOIN (
+             SELECT task_id,
+                    SUM(input_tokens) AS input_tokens,
+                    SUM(output_tokens) AS output_tokens,
+                    SUM(reasoning_tokens) AS reasoning_tokens,
+                    SUM(cache_read_tokens) AS cache_read_tokens,
+                    SUM(cache_write_tokens) AS cache_write_tokens,
+                    SUM(cost) AS cost
+             FROM task_usage
+             GROUP BY task_id
+         ) u ON u.task_id = t.id
+         WHERE (?1 IS NULL OR t.created_at >= ?1) AND (?2 IS NULL OR t.created_at <= ?2)
+         ORDER BY t.created_at",
+    )
+    .and_then(|mut stmt| {
+        stmt.query_map(params![range.since, range.until], |row| {
+            let tags: Option<String> = row.get(15)?;
+            Ok(TaskMetrics {
+                task_id: row.get(0)?,
+                created_at: row.get(1)?,
+                title: row.get(2)?,
+                status: row.get(3)?,
+                workspace: row.get(4)?,
+                model_id: row.get(5)?,
+                duration_seconds: row.get(6)?,
+                input_tokens: row.get(7)?,
+                output_tokens: row.get(8)?,
+                reasoning_tokens: row.get(9)?,
+                cache_read_tokens: row.get(10)?,
+                cache_write_tokens: row.get(11)?,
+                cost: row.get(12)?,
+                rated_up: row.get(13)?,
+                rated_down: row.get(14)?,
+                tags: tags.map(|t| t.lines().map(str::to_string).collect()).unwrap_or_default(),
+            })
+        })?
+        .collect()
+    })
+    .map_err(|e| format!("Failed to query task metrics: {}", e))
+}
+
+/// Tasks in one status, with the run time of those that finished
+#[derive(Debug, Clone)]
+pub struct StatusTotals {
+    pub status: String,
+    pub tasks: i64,
+    /// Tasks with both a start and a completion time
+    pub timed: i64,
+    pub duration_seconds: f64,
+}
+
+/// Token and cost totals of one model
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelTotals {
+    pub model_id: String,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub reasoning_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_write_tokens: i64,
+    pub cost: f64,
+}
+
+/// Task counts and run times by status, for the metrics endpoint
+pub fn get_status_totals(conn: &Connection) -> Result<Vec<StatusTotals>, String> {
+    conn.prepare(
+        "SELECT status,
+                COUNT(*),
+                COUNT(started_at IS NOT NULL AND completed_at IS NOT NULL OR NULL),
+                COALESCE(SUM((julianday(completed_at) - julianday(started_at)) * 86400), 0)
+         FROM tasks
+         GROUP BY status
+         ORDER BY status",
+    )
+    .and_then(|mut stmt| {
+        stmt.query_map([], |row| {
+            Ok(StatusTotals {
+                status: row.get(0)?,
+                tasks: row.get(1)?,
+                timed: row.get(2)?,
+                duration_seconds: row.get(3)?,
+            })
+        })?
+        .collect()
+    })
+    .map_err(|e| format!("Failed to aggregate task statuses: {}", e))
+}
+
+/// Lifetime token and cost totals by model, for the metrics endpoint
+pub fn get_model_totals(conn: &Connection) -> Result<Vec<ModelTotals>, String> {
+    model_totals(conn, "1", [])
+}
+
+/// Token and cost totals by model of the usage records matching `filter`
+fn model_totals(
+    conn: &Connection,
+    filter: &str,
+    values: impl rusqlite::Params,
+) -> Result<Vec<ModelTotals>, String> {
+    conn.prepare(&format!(
+        "SELECT COALESCE(model_id, 'unknown') AS model,
+                SUM(input_tokens),
+                SUM(output_tokens),
+                SUM(reasoning_tokens),
+                SUM(cache_read_tokens),
+                SUM(cache_write_tokens),
+                SUM(cost)
+         FROM task_usage
+         WHERE {}
+         GROUP BY model
+         ORDER BY model",
+        filter
+    ))
+    .and_then(|mut stmt| {
+        stmt.query_map(values, |row| {
+            Ok(ModelTotals {
+                model_id: row.get(0)?,
+                input_tokens: row.get(1)?,
+                output_tokens: row.get(2)?,
+                reasoning_tokens: row.get(3)?,
+                cache_read_tokens: row.get(4)?,
+                cache_write_tokens: row.get(5)?,
+                cost: row.get(6)?,
+            })
+        })?
+        .collect()
+    })
+    .map_err(|e| format!("Failed to aggregate usage by model: {}", e))
+}
+
+/// Token and cost sums over usage records
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageTotals {
+    /// Agent steps usage was recorded for
+    pub steps: i64,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub reasoning_tokens: i64,
+    pub cache_read_tokens: i64,
+    pub cache_write_tokens: i64,
+    /// Estimated cost in USD
+    pub cost: f64,
+}
+
+/// Totals of the usage records matching `filter`, and how many tasks they belong to
+fn usage_totals(
+    conn: &Connection,
+    filter: &str,
+    values: impl rusqlite::Params,
+) -> Result<(UsageTotals, i64), String> {
+    conn.query_row(
+        &format!(
+            "SELECT COUNT(*),
+                    COALESCE(SUM(input_tokens), 0),
+                    COALESCE(SUM(output_tokens), 0),
+                    COALESCE(SUM(reasoning_tokens), 0),
+                    COALESCE(SUM(cache_read_tokens), 0),
+                    COALESCE(SUM(cache_write_tokens), 0),
+                    COALESCE(SUM(cost), 0),
+                    COUNT(DISTINCT task_id)
+             FROM task_usage
+             WHERE {}",
+            filter
+        ),
+        values,
+        |row| {
+            let totals = UsageTotals {
+                steps: row.get(0)?,
+                input_tokens: row.get(1)?,
+                output_tokens: row.get(2)?,
+                reasoning_tokens: row.get(3)?,
+                cache_read_tokens: row.get(4)?,
+                cache_write_tokens: row.get(5)?,
+                cost: row.get(6)?,
+            };
+            Ok((totals, row.get(7)?))
+        },
+    )
+    .map_err(|e| format!("Failed to total usage: {}", e))
+}
+
+/// A task's token and cost totals, overall and by model
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskUsage {
+    pub task_id: String,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+    pub by_model: Vec<ModelTotals>,
+}
+
+/// Usage recorded for a task; all zero when none was
+pub fn get_task_usage(conn: &Connection, task_id: &str) -> Result<TaskUsage, String> {
+    let (totals, _) = usage_totals(conn, "task_id = ?1", [task_id])?;
+    let by_model = model_totals(conn, "task_id = ?1", [task_id])?;
+    Ok(TaskUsage {
+        task_id: task_id.to_string(),
+        totals,
+        by_model,
+    })
+}
+
+/// Token and cost totals of the usage recorded in a range, overall and by model
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageSummary {
+    /// Tasks with usage recorded in the range
+    pub tasks: i64,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+    pub by_model: Vec<ModelTotals>,
+}
+
+/// Total the usage recorded in a range
+pub fn get_usage_summary(conn: &Connection, range: &DateRange) -> Result<UsageSummary, String> {
+    let filter = "(?1 IS NULL OR recorded_at >= ?1) AND (?2 IS NULL OR recorded_at <= ?2)";
+    let (totals, tasks) = usage_totals(conn, filter, params![range.since, range.until])?;
+    let by_model = model_totals(conn, filter, params![range.since, range.until])?;
+    Ok(UsageSummary {
+        tasks,
+        totals,
+        by_model,
+    })
+}
@@ -0,0 +1,625 @@
+// src-tauri/src/db/workspaces.rs
+//! Workspace repository
+//!
+//! A workspace is a directory tasks run in. Tasks reference their workspace by
+//! ID; paths are unique so the same folder always maps to the same workspace.
+//! A workspace in a git repository also records the repository's remote
+//! (`repo`), so other checkouts of it map to the same workspace.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Component, Path};
+
+/// MCP servers the app configures itself; a workspace can't replace them
+const RESERVED_MCP_SERVERS: &[&str] = &[
+    "file-permission",
+    "ask-user-question",
+    "dev-browser-mcp",
+    "complete-task",
+    "brave-search",
+];
+
+/// Stored workspace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Workspace {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    pub created_at: String,
+}
+
+/// Shell commands run around tasks in a workspace
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WorkspaceHooks {
+    /// Run before the task is sent to the agent
+    pub pre_task: Vec<String>,
+    /// Run after the task completes successfully
+    pub post_task: Vec<String>,
+}
+
+/// Processors run over a workspace's agent messages before they are emitted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PostprocessSettings {
+    /// Remove ANSI escape codes from tool output
+    pub strip_ansi: bool,
+    /// Link file paths that exist in the workspace
+    pub linkify_paths: bool,
+    /// Save fenced code blocks as task artifacts
+    pub extract_code_blocks: bool,
+}
+
+impl Default for PostprocessSettings {
+    fn default() -> Self {
+        Self {
+            strip_ansi: true,
+            linkify_paths: true,
+            extract_code_blocks: true,
+        }
+    }
+}
+
+/// Model and agent used for tasks in a workspace instead of the global selection
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WorkspaceDefaults {
+    /// OpenCode model ID (`provider/model`)
+    pub model_id: Option<String>,
+    /// OpenCode agent name
+    pub agent: Option<String>,
+}
+
+/// Allows or denies an OpenCode tool, or a command pattern of `bash`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionRule {
+    /// OpenCode permission name, e.g. `edit`, `bash` or `webfetch`
+    pub tool: String,
+    /// Command pattern such as `git push*`; only `bash` rules take one
+    #[serde(default)]
+    pub pattern: Option<String>,
+    /// "allow" or "deny"
+    pub action: String,
+}
+
+/// MCP server started for a workspace's tasks, in OpenCode's config format
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpServer {
+    /// "local" (a command) or "remote" (a URL)
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub command: Vec<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub environment: BTreeMap<String, String>,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+/// What the agent is given in a workspace besides the prompt
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AgentSettings {
+    /// Added to the agent's instructions
+    pub instructions: Option<String>,
+    /// Applied in order, so later rules override earlier ones
+    pub permission_rules: Vec<PermissionRule>,
+    /// Files relative to the workspace directory, included as instructions
+    pub pinned_files: Vec<String>,
+    /// By server name
+    pub mcp_servers: BTreeMap<String, McpServer>,
+}
+
+impl AgentSettings {
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_none()
+            && self.permission_rules.is_empty()
+            && self.pinned_files.is_empty()
+            && self.mcp_servers.is_empty()
+    }
+}
+
+/// Validate and normalize agent settings set by the user
+pub fn normalize_agent_settings(settings: &AgentSettings) -> Result<AgentSettings, String> {
+    let instructions = settings
+        .instructions
+        .as_deref()
+        .map(str::trim)
+        .filter(|i| !i.is_empty())
+        .map(str::to_string);
+
+    let mut permission_rules = Vec::new();
+    for rule in &settings.permission_rules {
+        let tool = rule.tool.trim().to_lowercase();
+        if tool.is_empty() || !tool.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("Invalid permission name: {}", rule.tool));
+        }
+        if rule.action != "allow" && rule.action != "deny" {
+            return Err(format!("Expected 'allow' or 'deny' for {}: {}", tool, rule.action));
+        }
+        let pattern = rule
+            .pattern
+            .as_deref()
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(str::to_string);
+        if pattern.is_some() && tool != "bash" {
+            return Err(format!("Only bash permissions take a command pattern, not {}", tool));
+        }
+        permission_rules.push(PermissionRule {
+            tool,
+            pattern,
+            action: rule.action.clone(),
+        });
+    }
+
+    let mut pinned_files: Vec<String> = Vec::new();
+    for file in &settings.pinned_files {
+        let file = file.trim();
+        let path = Path::new(file);
+        let inside = path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+        if file.is_empty() || !inside {
+            return Err(format!("Pinned files must be relative to the workspace: {}", file));
+        }
+        if !pinned_files.iter().any(|f| f == file) {
+            pinned_files.push(file.to_string());
+        }
+    }
+
+    let mut mcp_servers = BTreeMap::new();
+    for (name, server) in &settings.mcp_servers {
+        let name = name.trim();
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!(
+                "Invalid MCP server name '{}': use letters, digits, '-' and '_'",
+                name
+            ));
+        }
+        if RESERVED_MCP_SERVERS.contains(&name) {
+            return Err(format!("The MCP server name '{}' is used by the app", name));
+        }
+        let has_url = server
+            .url
+            .as_deref()
+            .is_some_and(|url| url.starts_with("http://") || url.starts_with("https://"));
+        match server.kind.as_str() {
+            "local" if server.command.iter().all(|arg| arg.trim().is_empty()) => {
+                return Err(format!("MCP server '{}' needs a command", name));
+            }
+            "remote" if !has_url => {
+                return Err(format!("MCP server '{}' needs an http(s) URL", name));
+            }
+            "local" | "remote" => {}
+            other => {
+                return Err(format!("Expected a local or remote MCP server, got '{}'", other));
+            }
+        }
+        mcp_servers.insert(name.to_string(), server.clone());
+    }
+
+    Ok(AgentSettings {
+        instructions,
+        permission_rules,
+        pinned_files,
+        mcp_servers,
+    })
+}
+
+fn row_to_workspace(row: &rusqlite::Row) -> rusqlite::Result<Workspace> {
+    Ok(Workspace {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        path: row.get(2)?,
+        created_at: row.get(3)?,
+    })
+}
+
+/// Get a workspace by ID
+pub fn get_workspace(conn: &Connection, id: &str) -> Result<Option<Workspace>, String> {
+    conn.query_row(
+        "SELECT id, name, path, created_at FROM workspaces WHERE id = ?1",
+        [id],
+        row_to_workspace,
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get workspace: {}", e))
+}
+
+/// Get the workspace for a directory
+pub fn get_workspace_by_path(conn: &Connection, path: &str) -> Result<Option<Workspace>, String> {
+    conn.query_row(
+        "SELECT id, name, path, created_at FROM workspaces WHERE path = ?1",
+        [path],
+        row_to_workspace,
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get workspace: {}", e))
+}
+
+/// Get the workspace associated with a git repository, the first made if there are several
+pub fn get_workspace_by_repo(conn: &Connection, repo: &str) -> Result<Option<Workspace>, String> {
+    conn.query_row(
+        "SELECT id, name, path, created_at FROM workspaces WHERE repo = ?1
+         ORDER BY created_at ASC LIMIT 1",
+        [repo],
+        row_to_workspace,
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get workspace: {}", e))
+}
+
+/// The git repository a workspace is associated with
+pub fn get_repo(conn: &Connection, workspace_id: &str) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT repo FROM workspaces WHERE id = ?1",
+        [workspace_id],
+        |row| row.get(0),
+    )
+    .optional()
+    .map(Option::flatten)
+    .map_err(|e| format!("Failed to get workspace repository: {}", e))
+}
+
+/// Associate a workspace with a git repository
+pub fn set_repo(conn: &Connection, workspace_id: &str, repo: &str) -> Result<(), String> {
+    conn.execute(
+        "UPDATE workspaces SET repo = ?2 WHERE id = ?1",
+        params![workspace_id, repo],
+    )
+    .map_err(|e| format!("Failed to set workspace repository: {}", e))?;
+    Ok(())
+}
+
+/// Directory a session's task ran in and the ID of its workspace
+pub fn get_session_directory(
+    conn: &Connection,
+    session_id: &str,
+) -> Result<Option<(String, String)>, String> {
+    conn.query_row(
+        "SELECT COALESCE(t.working_directory, w.path), w.id
+         FROM tasks t JOIN workspaces w ON w.id = t.workspace_id
+         WHERE t.session_id = ?1
+         ORDER BY t.created_at DESC
+         LIMIT 1",
+        [session_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get session workspace: {}", e))
+}
+
+/// Create a workspace
+pub fn create_workspace(conn: &Connection, name: &str, path: &str) -> Result<Workspace, String> {
+    let workspace = Workspace {
+        id: format!("ws_{}", uuid::Uuid::new_v4()),
+        name: name.to_string(),
+        path: path.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    conn.execute(
+        "INSERT INTO workspaces (id, name, path, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![workspace.id, workspace.name, workspace.path, workspace.created_at],
+    )
+    .map_err(|e| format!("Failed to create workspace: {}", e))?;
+
+    Ok(workspace)
+}
+
+/// Name of a directory's folder, or the whole path if it has none
+pub fn folder_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// Create a workspace for a local directory, named after the folder unless a name is given
+pub fn create_local_workspace(
+    conn: &Connection,
+    path: &str,
+    name: Option<&str>,
+) -> Result<Workspace, String> {
+    if !Path::new(path).is_dir() {
+        return Err(format!("Not a directory: {}", path));
+    }
+    if get_workspace_by_path(conn, path)?.is_some() {
+        return Err(format!("A workspace already exists for {}", path));
+    }
+    let name = name
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| folder_name(path));
+    create_workspace(conn, &name, path)
+}
+
+/// Get the workspace for a directory, creating one named after the folder if needed
+pub fn ensure_workspace_for_path(conn: &Connection, path: &str) -> Result<Workspace, String> {
+    if let Some(workspace) = get_workspace_by_path(conn, path)? {
+        return Ok(workspace);
+    }
+    create_workspace(conn, &folder_name(path), path)
+}
+
+/// All workspaces, by name
+pub fn list_workspaces(conn: &Connection) -> Result<Vec<Workspace>, String> {
+    let mut stmt = conn
+        .prepare("SELECT id, name, path, created_at FROM workspaces ORDER BY name COLLATE NOCASE")
+        .map_err(|e| format!("Failed to prepare workspaces query: {}", e))?;
+    let workspaces = stmt
+        .query_map([], row_to_workspace)
+        .map_err(|e| format!("Failed to query workspaces: {}", e))?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(workspaces)
+}
+
+/// Get a workspace's hooks
+pub fn get_hooks(conn: &Connection, workspace_id: &str) -> Result<WorkspaceHooks, String> {
+    let json: Option<String> = conn
+        .query_row(
+            "SELECT hooks FROM workspaces WHERE id = ?1",
+            [workspace_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get workspace hooks: {}", e))?
+        .ok_or_else(|| format!("Workspace not found: {}", workspace_id))?;
+
+    Ok(json
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default())
+}
+
+/// Replace a workspace's hooks
+pub fn set_hooks(conn: &Connection, workspace_id: &str, hooks: &WorkspaceHooks) -> Result<(), String> {
+    let json = serde_json::to_string(hooks)
+        .map_err(|e| format!("Failed to serialize workspace hooks: {}", e))?;
+    let updated = conn
+        .execute(
+            "UPDATE workspaces SET hooks = ?1 WHERE id = ?2",
+            params![json, workspace_id],
+        )
+        .map_err(|e| format!("Failed to set workspace hooks: {}", e))?;
+    if updated == 0 {
+        return Err(format!("Workspace not found: {}", workspace_id));
+    }
+    Ok(())
+}
+
+/// Get a workspace's post-processing settings
+pub fn get_postprocess(
+    conn: &Connection,
+    workspace_id: &str,
+) -> Result<PostprocessSettings, String> {
+    let json: Option<String> = conn
+        .query_row(
+            "SELECT postprocess FROM workspaces WHERE id = ?1",
+            [workspace_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get post-processing settings: {}", e))?
+        .ok_or_else(|| format!("Workspace not found: {}", workspace_id))?;
+
+    Ok(json
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default())
+}
+
+/// Replace a workspace's post-processing settings
+pub fn set_postprocess(
+    conn: &Connection,
+    workspace_id: &str,
+    settings: &PostprocessSettings,
+) -> Result<(), String> {
+    let json = serde_json::to_string(settings)
+        .map_err(|e| format!("Failed to serialize post-processing settings: {}", e))?;
+    let updated = conn
+        .execute(
+            "UPDATE workspaces SET postprocess = ?1 WHERE id = ?2",
+            params![json, workspace_id],
+        )
+        .map_err(|e| format!("Failed to set post-processing settings: {}", e))?;
+    if updated == 0 {
+        return Err(format!("Workspace not found: {}", workspace_id));
+    }
+    Ok(())
+}
+
+/// Get a workspace's default model and agent
+pub fn get_defaults(conn: &Connection, workspace_id: &str) -> Result<WorkspaceDefaults, String> {
+    conn.query_row(
+        "SELECT model_id, agent FROM workspaces WHERE id = ?1",
+        [workspace_id],
+        |row| {
+            Ok(WorkspaceDefaults {
+                model_id: row.get(0)?,
+                agent: row.get(1)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get workspace defaults: {}", e))?
+    .ok_or_else(|| format!("Workspace not found: {}", workspace_id))
+}
+
+/// Replace a workspace's default model and agent; empty values clear them
+pub fn set_defaults(
+    conn: &Connection,
+    workspace_id: &str,
+    defaults: &WorkspaceDefaults,
+) -> Result<(), String> {
+    let non_empty = |v: &Option<String>| {
+        v.as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+    let model_id = non_empty(&defaults.model_id);
+    if let Some(model_id) = &model_id {
+        if !model_id.contains('/') {
+            return Err(format!("Expected a provider/model ID: {}", model_id));
+        }
+    }
+    let updated = conn
+        .execute(
+            "UPDATE workspaces SET model_id = ?1, agent = ?2 WHERE id = ?3",
+            params![model_id, non_empty(&defaults.agent), workspace_id],
+        )
+        .map_err(|e| format!("Failed to set workspace defaults: {}", e))?;
+    if updated == 0 {
+        return Err(format!("Workspace not found: {}", workspace_id));
+    }
+    Ok(())
+}
+
+/// Get a workspace's agent settings
+pub fn get_agent_settings(conn: &Connection, workspace_id: &str) -> Result<AgentSettings, String> {
+    let json: Option<String> = conn
+        .query_row(
+            "SELECT agent_settings FROM workspaces WHERE id = ?1",
+            [workspace_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to get agent settings: {}", e))?
+        .ok_or_else(|| format!("Workspace not found: {}", workspace_id))?;
+
+    Ok(json
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default())
+}
+
+/// Replace a workspace's agent settings
+pub fn set_agent_settings(
+    conn: &Connection,
+    workspace_id: &str,
+    settings: &AgentSettings,
+) -> Result<(), String> {
+    let settings = normalize_agent_settings(settings)?;
+    let json = if settings.is_empty() {
+        None
+    } else {
+        Some(
+            serde_json::to_string(&settings)
+                .map_err(|e| format!("Failed to serialize agent settings: {}", e))?,
+        )
+    };
+    let updated = conn
+        .execute(
+            "UPDATE workspaces SET agent_settings = ?1 WHERE id = ?2",
+            params![json, workspace_id],
+        )
+        .map_err(|e| format!("Failed to set agent settings: {}", e))?;
+    if updated == 0 {
+        return Err(format!("Workspace not found: {}", workspace_id));
+    }
+    Ok(())
+}
+
+/// Agent settings sent with a task, `None` outside a workspace or when none are set
+pub fn task_agent_settings(
+    conn: &Connection,
+    workspace_id: Option<&str>,
+) -> Result<Option<AgentSettings>, String> {
+    let Some(workspace_id) = workspace_id else {
+        return Ok(None);
+    };
+    let settings = get_agent_settings(conn, workspace_id)?;
+    Ok((!settings.is_empty()).then_some(settings))
+}
+
+/// Docker image a workspace's tasks run in, or `None` to run on the host
+pub fn get_sandbox_image(conn: &Connection, workspace_id: &str) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT sandbox_image FROM workspaces WHERE id = ?1",
+        [workspace_id],
+        |row| row.get::<_, Option<String>>(0),
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get workspace sandbox: {}", e))?
+    .ok_or_else(|| format!("Workspace not found: {}", workspace_id))
+}
+
+/// Set the Docker image a workspace's tasks run in; an empty image turns the sandbox off
+pub fn set_sandbox_image(
+    conn: &Connection,
+    workspace_id: &str,
+    image: Option<&str>,
+) -> Result<(), String> {
+    let image = image.map(str::trim).filter(|i| !i.is_empty());
+    let updated = conn
+        .execute(
+            "UPDATE workspaces SET sandbox_image = ?1 WHERE id = ?2",
+            params![image, workspace_id],
+        )
+        .map_err(|e| format!("Failed to set workspace sandbox: {}", e))?;
+    if updated == 0 {
+        return Err(format!("Workspace not found: {}", workspace_id));
+    }
+    Ok(())
+}
+
+/// Whether any workspace runs its tasks in a sandbox
+pub fn has_sandboxes(conn: &Connection) -> bool {
+    conn.query_row(
+        "SELECT 1 FROM workspaces WHERE sandbox_image IS NOT NULL LIMIT 1",
+        [],
+        |_| Ok(()),
+    )
+    .is_ok()
+}
+
+/// SSH host a remote workspace lives on, or `None` for a local workspace
+pub fn get_ssh_host_id(conn: &Connection, workspace_id: &str) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT ssh_host_id FROM workspaces WHERE id = ?1",
+        [workspace_id],
+        |row| row.get::<_, Option<String>>(0),
+    )
+    .optional()
+    .map_err(|e| format!("Failed to get workspace host: {}", e))?
+    .ok_or_else(|| format!("Workspace not found: {}", workspace_id))
+}
+
+/// Create a workspace for a directory on an SSH host
+pub fn create_remote_workspace(
+    conn: &Connection,
+    ssh_host_id: &str,
+    name: &str,
+    path: &str,
+) -> Result<Workspace, String> {
+    let workspace = Workspace {
+        id: format!("ws_{}", uuid::Uuid::new_v4()),
+        name: name.to_string(),
+        path: path.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    conn.execute(
+        "INSERT INTO workspaces (id, name, path, created_at, ssh_host_id)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![workspace.id, workspace.name, workspace.path, workspace.created_at, ssh_host_id],
+    )
+    .map_err(|e| format!("Failed to create workspace: {}", e))?;
+
+    Ok(workspace)
+}
@@ -0,0 +1,155 @@
+// src-tauri/src/diagnostics.rs
+//! Diagnostics bundle for bug reports
+//!
+//! Produces a zip with redacted log files, stored sidecar logs, database schema
+//! version and row counts, provider connection states, and platform info.
+//! Credentials never leave the keychain and every text file is passed through
+//! secret redaction before it is written.
+
+use crate::db::{self, DbState};
+use crate::logging::redact_secrets;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use tauri::AppHandle;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Provider connection state without credentials
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProviderState {
+    provider_id: String,
+    connection_status: String,
+    credentials_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    selected_model_id: Option<String>,
+    last_connected_at: String,
+    available_model_count: usize,
+}
+
+/// Top-level summary written as `diagnostics.json`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DiagnosticsSummary {
+    generated_at: String,
+    app_version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sidecar_version: Option<String>,
+    os: String,
+    os_family: String,
+    arch: String,
+    schema_version: i32,
+    row_counts: BTreeMap<String, i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    log_level: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    active_provider_id: Option<String>,
+    providers: Vec<ProviderState>,
+}
+
+/// Write a diagnostics zip to `path`
+pub fn export_diagnostics(app: &AppHandle, state: &DbState, path: &Path) -> Result<(), String> {
+    // Hold the database lock only while reading from it
+    let (summary, sidecar_logs) = {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        let summary = build_summary(&conn)?;
+        let sidecar_logs = db::logs::query_logs(
+            &conn,
+            &db::logs::LogFilter {
+                target: Some("sidecar".to_string()),
+                limit: Some(i64::MAX),
+                ..Default::default()
+            },
+        )?;
+        (summary, sidecar_logs)
+    };
+
+    let file = File::create(path)
+        .map_err(|e| format!("Failed to create diagnostics file: {}", e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let summary_json = serde_json::to_string_pretty(&summary)
+        .map_err(|e| format!("Failed to serialize diagnostics: {}", e))?;
+    add_file(&mut zip, "diagnostics.json", &summary_json, options)?;
+
+    // Stored records are newest first; write them in chronological order
+    let sidecar_log: String = sidecar_logs
+        .iter()
+        .rev()
+        .map(|r| format!("{} {} {}\n", r.timestamp, r.level.to_uppercase(), r.message))
+        .collect();
+    add_file(&mut zip, "sidecar.log", &redact_secrets(&sidecar_log), options)?;
+
+    let log_dir = crate::logging::log_dir(app)?;
+    if let Ok(entries) = std::fs::read_dir(&log_dir) {
+        for entry in entries.filter_map(|e| e.ok()) {
+            let entry_path = entry.path();
+            if !entry_path.is_file() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            match std::fs::read(&entry_path) {
+                Ok(bytes) => {
+                    let contents = redact_secrets(&String::from_utf8_lossy(&bytes));
+                    add_file(&mut zip, &format!("logs/{}", name), &contents, options)?;
+                }
+                Err(e) => tracing::warn!("[Diagnostics] Skipping {:?}: {}", entry_path, e),
+            }
+        }
+    }
+
+    zip.finish()
+        .map_err(|e| format!("Failed to finalize diagnostics file: {}", e))?;
+
+    tracing::info!("[Diagnostics] Exported diagnostics to {:?}", path);
+    Ok(())
+}
+
+/// Collect version, schema, and provider information
+fn build_summary(conn: &rusqlite::Connection) -> Result<DiagnosticsSummary, String> {
+    let provider_settings = db::providers::get_provider_settings(conn);
+    let mut providers: Vec<ProviderState> = provider_settings
+        .connected_providers
+        .into_values()
+        .map(|p| ProviderState {
+            provider_id: p.provider_id,
+            connection_status: p.connection_status,
+            credentials_type: p.credentials.credentials_type,
+            selected_model_id: p.selected_model_id,
+            last_connected_at: p.last_connected_at,
+            available_model_count: p.available_models.map(|m| m.len()).unwrap_or(0),
+        })
+        .collect();
+    providers.sort_by(|a, b| a.provider_id.cmp(&b.provider_id));
+
+    Ok(DiagnosticsSummary {
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        sidecar_version: crate::crash::sidecar_version(),
+        os: std::env::consts::OS.to_string(),
+        os_family: std::env::consts::FAMILY.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        schema_version: db::migrations::get_stored_version(conn),
+        row_counts: db::get_table_row_counts(conn)?.into_iter().collect(),
+        log_level: db::settings::get_log_level(conn),
+        active_provider_id: provider_settings.active_provider_id,
+        providers,
+    })
+}
+
+/// Add a text file to the archive
+fn add_file(
+    zip: &mut ZipWriter<File>,
+    name: &str,
+    contents: &str,
+    options: SimpleFileOptions,
+) -> Result<(), String> {
+    zip.start_file(name, options)
+        .map_err(|e| format!("Failed to add {} to diagnostics: {}", name, e))?;
+    zip.write_all(contents.as_bytes())
+        .map_err(|e| format!("Failed to write {} to diagnostics: {}", name, e))
+}
@@ -0,0 +1,189 @@
+// src-tauri/src/diff.rs
+//! Structured diffs for file-editing tool calls
+//!
+//! OpenCode's `edit`, `multiedit`, and `write` tools carry the old and new text
+//! in their input. This module turns that input into per-file hunks with line
+//! numbers and a language hint, so the UI can render diffs without parsing raw
+//! tool input itself.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use similar::{ChangeTag, TextDiff};
+
+/// Unchanged lines kept around each change
+const CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffLine {
+    /// `context`, `add`, or `remove`
+    pub kind: String,
+    pub content: String,
+    /// 1-based line number in the old text, unset for added lines
+    pub old_line: Option<usize>,
+    /// 1-based line number in the new text, unset for removed lines
+    pub new_line: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffHunk {
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileDiff {
+    pub path: String,
+    pub language: Option<String>,
+    /// Replaced text; unset when the tool wrote the whole file
+    pub old_text: Option<String>,
+    pub new_text: String,
+    pub hunks: Vec<DiffHunk>,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+/// Diff attached to a tool message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageDiff {
+    pub tool: String,
+    pub files: Vec<FileDiff>,
+}
+
+/// Language hint from a file extension, using the names code highlighters expect
+fn language_for_path(path: &str) -> Option<String> {
+    let extension = std::path::Path::new(path).extension()?.to_str()?.to_lowercase();
+    let language = match extension.as_str() {
+        "rs" => "rust",
+        "ts" | "mts" | "cts" => "typescript",
+        "tsx" => "tsx",
+        "js" | "mjs" | "cjs" => "javascript",
+        "jsx" => "jsx",
+        "py" => "python",
+        "go" => "go",
+        "java" => "java",
+        "kt" | "kts" => "kotlin",
+        "swift" => "swift",
+        "rb" => "ruby",
+        "php" => "php",
+        "c" | "h" => "c",
+        "cc" | "cpp" | "cxx" | "hpp" => "cpp",
+        "cs" => "csharp",
+        "sh" | "bash" | "zsh" => "bash",
+        "json" => "json",
+        "yaml" | "yml" => "yaml",
+        "toml" => "toml",
+        "md" | "markdown" => "markdown",
+        "html" | "htm" => "html",
+        "css" => "css",
+        "scss" => "scss",
+        "sql" => "sql",
+        "xml" => "xml",
+        _ => return None,
+    };
+    Some(language.to_string())
+}
+
+/// Line-level hunks between two texts
+pub fn hunks(old: &str, new: &str) -> Vec<DiffHunk> {
+    let diff = TextDiff::from_lines(old, new);
+    let mut hunks = Vec::new();
+
+    for group in diff.grouped_ops(CONTEXT_LINES) {
+        let (Some(first), Some(last)) = (group.first(), group.last()) else {
+            continue;
+        };
+        let old_range = first.old_range().start..last.old_range().end;
+        let new_range = first.new_range().start..last.new_range().end;
+
+        let lines = group
+            .iter()
+            .flat_map(|op| diff.iter_changes(op))
+            .map(|change| DiffLine {
+                kind: match change.tag() {
+                    ChangeTag::Equal => "context",
+                    ChangeTag::Insert => "add",
+                    ChangeTag::Delete => "remove",
+                }
+                .to_string(),
+                content: change.value().trim_end_matches(['\r', '\n']).to_string(),
+                old_line: change.old_index().map(|i| i + 1),
+                new_line: change.new_index().map(|i| i + 1),
+            })
+            .collect();
+
+        hunks.push(DiffHunk {
+            old_start: old_range.start + 1,
+            old_lines: old_range.len(),
+            new_start: new_range.start + 1,
+            new_lines: new_range.len(),
+            lines,
+        });
+    }
+
+    hunks
+}
+
+fn file_diff(path: &str, old_text: Option<String>, new_text: String) -> FileDiff {
+    let hunks = hunks(old_text.as_deref().unwrap_or(""), &new_text);
+    let count = |kind: &str| {
+        hunks
+            .iter()
+            .flat_map(|h| &h.lines)
+            .filter(|l| l.kind == kind)
+            .count()
+    };
+    FileDiff {
+        path: path.to_string(),
+        language: language_for_path(path),
+        additions: count("add"),
+        deletions: count("remove"),
+        old_text,
+        new_text,
+        hunks,
+    }
+}
+
+fn str_field<'a>(value: &'a Value, keys: &[&str]) -> Option<&'a str> {
+    keys.iter().find_map(|k| value.get(*k).and_then(|v| v.as_str()))
+}
+
+/// Build a diff from a tool call, or `None` if the tool does not edit files
+pub fn from_tool(tool_name: &str, input: &Value) -> Option<MessageDiff> {
+    let tool = tool_name.to_lowercase();
+    let path = str_field(input, &["filePath", "file_path", "path"])?;
+
+    let files = match tool.as_str() {
+        "edit" => {
+            let old = str_field(input, &["oldString", "old_string"])?;
+            let new = str_field(input, &["newString", "new_string"])?;
+            vec![file_diff(path, Some(old.to_string()), new.to_string())]
+        }
+        "multiedit" => input
+            .get("edits")?
+            .as_array()?
+            .iter()
+            .filter_map(|edit| {
+                let old = str_field(edit, &["oldString", "old_string"])?;
+                let new = str_field(edit, &["newString", "new_string"])?;
+                Some(file_diff(path, Some(old.to_string()), new.to_string()))
+            })
+            .collect(),
+        "write" => {
+            let content = str_field(input, &["content"])?;
+            vec![file_diff(path, None, content.to_string())]
+        }
+        _ => return None,
+    };
+
+    if files.is_empty() {
+        return None;
+    }
+    Some(MessageDiff { tool, files })
+}
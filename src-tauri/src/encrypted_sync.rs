@@ -0,0 +1,560 @@
+// src-tauri/src/encrypted_sync.rs
+//! End-to-end encrypted multi-device sync
+//!
+//! Each device has an X25519 keypair whose secret lives in the keychain.
+//! Paired devices share a symmetric group key, handed over during pairing as
+//! an envelope encrypted to the new device's public key. Tasks are uploaded
+//! as ChaCha20-Poly1305 encrypted bundles addressed by a keyed hash of their
+//! content, and each device publishes an encrypted manifest mapping task IDs
+//! to bundle hashes plus the IDs it has deleted. The relay only ever sees
+//! opaque blobs under random-looking names. Every blob is bound to its name as
+//! associated data, and a bundle must hash to the address it was fetched
+//! under, so the relay can't swap one blob for another.
+//!
+//! Relays are pluggable through [`Relay`]. Two are built in:
+//! - `file:///path` stores blobs in a local (possibly cloud-synced) directory
+//! - `https://host/prefix` speaks a minimal blob protocol: `PUT {url}/{key}`
+//!   stores bytes, `GET {url}/{key}` returns them (404 if missing), and
+//!   `GET {url}/{prefix}/` returns a JSON array of key names under a prefix.
+
+use crate::db::tasks::{self, StoredTask};
+use crate::secure_storage;
+use crate::sync::{self, SyncReport};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use hkdf::Hkdf;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+const NONCE_LEN: usize = 12;
+
+/// Serializes sync runs so two `sync_now` calls cannot interleave uploads
+static SYNC_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Identity of this device
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceIdentity {
+    pub device_id: String,
+    /// Base64 X25519 public key, shared with a device to pair with it
+    pub public_key: String,
+}
+
+/// Outcome of pairing with another device
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairResult {
+    pub peer_device_id: String,
+    /// True when this device joined the peer's sync group, false when it
+    /// published its own group key for the peer to pick up
+    pub joined: bool,
+}
+
+/// Per-device list of task bundles
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    device_id: String,
+    updated_at: String,
+    /// Task ID to bundle hash
+    tasks: HashMap<String, String>,
+    deleted: Vec<String>,
+}
+
+// ============================================================================
+// Relays
+// ============================================================================
+
+/// Blob storage the encrypted data is exchanged through
+pub(crate) trait Relay {
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<(), String>;
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String>;
+    /// Names of the keys directly under `prefix`
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, String>;
+}
+
+/// Relay backed by a directory
+pub(crate) struct FolderRelay {
+    root: PathBuf,
+}
+
+impl Relay for FolderRelay {
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<(), String> {
+        let path = self.root.join(key);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create relay folder: {}", e))?;
+        }
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, bytes).map_err(|e| format!("Failed to write {}: {}", key, e))?;
+        std::fs::rename(&tmp, &path).map_err(|e| format!("Failed to write {}: {}", key, e))
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        match std::fs::read(self.root.join(key)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Failed to read {}: {}", key, e)),
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, String> {
+        match std::fs::read_dir(self.root.join(prefix)) {
+            Ok(entries) => Ok(entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .filter(|name| !name.ends_with(".tmp"))
+                .collect()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(format!("Failed to list {}: {}", prefix, e)),
+        }
+    }
+}
+
+/// Relay speaking the minimal HTTP blob protocol
+pub(crate) struct HttpRelay {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl Relay for HttpRelay {
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<(), String> {
+        self.client
+            .put(format!("{}/{}", self.base_url, key))
+            .body(bytes)
+            .send()
+            .await
+            .map_err(|e| format!("Relay request failed: {}", e))?
+            .error_for_status()
+            .map_err(|e| format!("Relay rejected {}: {}", key, e))?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        let response = self
+            .client
+            .get(format!("{}/{}", self.base_url, key))
+            .send()
+            .await
+            .map_err(|e| format!("Relay request failed: {}", e))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let bytes = response
+            .error_for_status()
+            .map_err(|e| format!("Relay rejected {}: {}", key, e))?
+            .bytes()
+            .await
+            .map_err(|e| format!("Failed to read {} from relay: {}", key, e))?;
+        Ok(Some(bytes.to_vec()))
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, String> {
+        let response = self
+            .client
+            .get(format!("{}/{}/", self.base_url, prefix))
+            .send()
+            .await
+            .map_err(|e| format!("Relay request failed: {}", e))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(Vec::new());
+        }
+        response
+            .error_for_status()
+            .map_err(|e| format!("Relay rejected listing {}: {}", prefix, e))?
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse relay listing: {}", e))
+    }
+}
+
+/// Configured relay
+pub(crate) enum RelayKind {
+    Folder(FolderRelay),
+    Http(HttpRelay),
+}
+
+impl RelayKind {
+    pub fn from_url(url: &str) -> Result<Self, String> {
+        if let Some(path) = url.strip_prefix("file://") {
+            return Ok(Self::Folder(FolderRelay {
+                root: PathBuf::from(path),
+            }));
+        }
+        if url.starts_with("https://") || url.starts_with("http://") {
+            let client = reqwest::Client::builder()
+                .user_agent(concat!("cowork-z/", env!("CARGO_PKG_VERSION")))
+                .build()
+                .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+            return Ok(Self::Http(HttpRelay {
+                base_url: url.trim_end_matches('/').to_string(),
+                client,
+            }));
+        }
+        Err(format!("Unsupported relay URL: {}", url))
+    }
+}
+
+impl Relay for RelayKind {
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<(), String> {
+        match self {
+            Self::Folder(relay) => relay.put(key, bytes).await,
+            Self::Http(relay) => relay.put(key, bytes).await,
+        }
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        match self {
+            Self::Folder(relay) => relay.get(key).await,
+            Self::Http(relay) => relay.get(key).await,
+        }
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>, String> {
+        match self {
+            Self::Folder(relay) => relay.list(prefix).await,
+            Self::Http(relay) => relay.list(prefix).await,
+        }
+    }
+}
+
+// ============================================================================
+// Keys
+// ============================================================================
+
+fn decode_key(encoded: &str) -> Result<[u8; 32], String> {
+    BASE64
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| "Invalid sync key".to_string())
+}
+
+fn device_id(public_key: &PublicKey) -> String {
+    Sha256::digest(public_key.as_bytes())[..8]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// This device's secret, created on first use
+fn device_secret() -> Result<StaticSecret, String> {
+    if let Some(stored) = secure_storage::get_sync_device_key()? {
+        return Ok(StaticSecret::from(decode_key(&stored)?));
+    }
+    let bytes: [u8; 32] = ChaCha20Poly1305::generate_key(&mut OsRng).into();
+    secure_storage::store_sync_device_key(&BASE64.encode(bytes))?;
+    Ok(StaticSecret::from(bytes))
+}
+
+/// This device's ID and public key
+pub fn device_identity() -> Result<DeviceIdentity, String> {
+    let public_key = PublicKey::from(&device_secret()?);
+    Ok(DeviceIdentity {
+        device_id: device_id(&public_key),
+        public_key: BASE64.encode(public_key.as_bytes()),
+    })
+}
+
+/// Create a group key if this device does not belong to a group yet
+pub fn ensure_group_key() -> Result<(), String> {
+    if secure_storage::get_sync_group_key()?.is_none() {
+        let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+        secure_storage::store_sync_group_key(&BASE64.encode(key))?;
+    }
+    Ok(())
+}
+
+pub fn has_group_key() -> bool {
+    matches!(secure_storage::get_sync_group_key(), Ok(Some(_)))
+}
+
+fn derive(ikm: &[u8], info: &str) -> [u8; 32] {
+    let mut okm = [0u8; 32];
+    Hkdf::<Sha256>::new(None, ikm)
+        .expand(info.as_bytes(), &mut okm)
+        .expect("32 bytes is a valid HKDF output length");
+    okm
+}
+
+/// Keys derived from the group key
+struct GroupKeys {
+    /// Relay namespace shared by the group
+    group_id: String,
+    cipher: ChaCha20Poly1305,
+    address_key: [u8; 32],
+}
+
+impl GroupKeys {
+    fn load() -> Result<Self, String> {
+        let stored = secure_storage::get_sync_group_key()?
+            .ok_or("This device is not part of a sync group yet")?;
+        let group_key = decode_key(&stored)?;
+        let group_id: String = derive(&group_key, "cowork-z sync group id")[..16]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        let encryption_key = derive(&group_key, "cowork-z sync encryption");
+        Ok(Self {
+            group_id,
+            cipher: ChaCha20Poly1305::new(Key::from_slice(&encryption_key)),
+            address_key: derive(&group_key, "cowork-z sync address"),
+        })
+    }
+
+    fn key(&self, path: &str) -> String {
+        format!("groups/{}/{}", self.group_id, path)
+    }
+
+    /// Content address of a bundle; keyed so the relay cannot confirm guesses
+    fn address(&self, plaintext: &[u8]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.address_key);
+        hasher.update(plaintext);
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+}
+
+/// Encrypt data stored under relay key `name`, which it is bound to
+fn seal(cipher: &ChaCha20Poly1305, name: &str, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let mut sealed = nonce.to_vec();
+    let payload = Payload {
+        msg: plaintext,
+        aad: name.as_bytes(),
+    };
+    sealed.extend(
+        cipher
+            .encrypt(&nonce, payload)
+            .map_err(|_| "Failed to encrypt sync data".to_string())?,
+    );
+    Ok(sealed)
+}
+
+/// Decrypt data fetched from relay key `name`, failing if it was sealed for another
+fn open(cipher: &ChaCha20Poly1305, name: &str, sealed: &[u8]) -> Result<Vec<u8>, String> {
+    if sealed.len() < NONCE_LEN {
+        return Err("Sync data is truncated".to_string());
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let payload = Payload {
+        msg: ciphertext,
+        aad: name.as_bytes(),
+    };
+    cipher
+        .decrypt(Nonce::from_slice(nonce), payload)
+        .map_err(|_| "Failed to decrypt sync data".to_string())
+}
+
+/// Cipher for pairing envelopes between this device and a peer
+fn pairing_cipher(secret: &StaticSecret, peer: &PublicKey) -> ChaCha20Poly1305 {
+    let shared = secret.diffie_hellman(peer);
+    let key = derive(shared.as_bytes(), "cowork-z sync pairing");
+    ChaCha20Poly1305::new(Key::from_slice(&key))
+}
+
+// ============================================================================
+// Pairing and sync
+// ============================================================================
+
+/// Pair with another device given its public key
+///
+/// Run on both devices with each other's public key. If the peer already left
+/// an envelope for this device, its group key is adopted; otherwise this
+/// device's group key is published for the peer to pick up.
+pub async fn pair_device<R: Relay>(relay: &R, peer_public_key: &str) -> Result<PairResult, String> {
+    let secret = device_secret()?;
+    let own_id = device_id(&PublicKey::from(&secret));
+    let peer = PublicKey::from(decode_key(peer_public_key)?);
+    let peer_id = device_id(&peer);
+    if peer_id == own_id {
+        return Err("Cannot pair a device with itself".to_string());
+    }
+    let cipher = pairing_cipher(&secret, &peer);
+
+    let envelope_name = format!("pairing/{}/{}", own_id, peer_id);
+    if let Some(envelope) = relay.get(&envelope_name).await? {
+        let group_key = open(&cipher, &envelope_name, &envelope)?;
+        if group_key.len() != 32 {
+            return Err("Pairing envelope is invalid".to_string());
+        }
+        secure_storage::store_sync_group_key(&BASE64.encode(&group_key))?;
+        tracing::info!("[EncryptedSync] Joined sync group of device {}", peer_id);
+        return Ok(PairResult {
+            peer_device_id: peer_id,
+            joined: true,
+        });
+    }
+
+    ensure_group_key()?;
+    let group_key = decode_key(&secure_storage::get_sync_group_key()?.unwrap_or_default())?;
+    let envelope_name = format!("pairing/{}/{}", peer_id, own_id);
+    relay
+        .put(&envelope_name, seal(&cipher, &envelope_name, &group_key)?)
+        .await?;
+    tracing::info!("[EncryptedSync] Published group key for device {}", peer_id);
+    Ok(PairResult {
+        peer_device_id: peer_id,
+        joined: false,
+    })
+}
+
+/// Lock used by callers to keep sync runs from overlapping
+pub async fn lock() -> tokio::sync::MutexGuard<'static, ()> {
+    SYNC_LOCK.lock().await
+}
+
+fn bundle(task: &StoredTask) -> Result<Vec<u8>, String> {
+    serde_json::to_vec(task).map_err(|e| format!("Failed to serialize task: {}", e))
+}
+
+/// Pull other devices' changes into the database, then publish this device's state
+///
+/// The database lock is only held between network phases.
+pub async fn sync_now<R: Relay>(relay: &R, conn: &Mutex<Connection>) -> Result<SyncReport, String> {
+    let keys = GroupKeys::load()?;
+    let own_id = device_identity()?.device_id;
+    let mut report = SyncReport::default();
+
+    let local_hashes: HashMap<String, String> = {
+        let conn = conn.lock().map_err(|e| e.to_string())?;
+        tasks::get_tasks(&conn)
+            .iter()
+            .map(|t| Ok((t.id.clone(), keys.address(&bundle(t)?))))
+            .collect::<Result<_, String>>()?
+    };
+
+    // Download everything other devices have that differs from ours
+    let mut deleted: HashSet<String> = HashSet::new();
+    let mut incoming: Vec<StoredTask> = Vec::new();
+    for device in relay.list(&keys.key("manifests")).await? {
+        if device == own_id {
+            continue;
+        }
+        let manifest_name = keys.key(&format!("manifests/{}", device));
+        let Some(sealed) = relay.get(&manifest_name).await? else {
+            continue;
+        };
+        let manifest: Manifest = match open(&keys.cipher, &manifest_name, &sealed)
+            .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|e| e.to_string()))
+        {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                tracing::warn!("[EncryptedSync] Skipping manifest from {}: {}", device, e);
+                continue;
+            }
+        };
+        deleted.extend(manifest.deleted);
+
+        for (task_id, hash) in manifest.tasks {
+            if local_hashes.get(&task_id) == Some(&hash) {
+                continue;
+            }
+            let blob_name = keys.key(&format!("blobs/{}", hash));
+            let Some(sealed) = relay.get(&blob_name).await? else {
+                continue;
+            };
+            let task = open(&keys.cipher, &blob_name, &sealed)
+                .and_then(|bytes| {
+                    if keys.address(&bytes) != hash {
+                        return Err("content does not match its address".to_string());
+                    }
+                    serde_json::from_slice::<StoredTask>(&bytes).map_err(|e| e.to_string())
+                })
+                .and_then(|task| {
+                    if task.id != task_id {
+                        return Err(format!("bundle is of task {}, not {}", task.id, task_id));
+                    }
+                    Ok(task)
+                });
+            match task {
+                Ok(task) => incoming.push(task),
+                Err(e) => tracing::warn!("[EncryptedSync] Skipping bundle {}: {}", hash, e),
+            }
+        }
+    }
+
+    // Apply deletions and merges, then snapshot what to publish
+    let (manifest, bundles) = {
+        let conn = conn.lock().map_err(|e| e.to_string())?;
+        for task_id in &deleted {
+            tasks::record_tombstone(&conn, task_id)?;
+            if tasks::get_task(&conn, task_id).is_some() {
+                tasks::delete_task(&conn, task_id)?;
+                report.deleted += 1;
+            }
+        }
+        let tombstones: HashSet<String> = tasks::get_tombstones(&conn)?.into_iter().collect();
+
+        for remote in incoming.iter().filter(|t| !tombstones.contains(&t.id)) {
+            match tasks::get_task(&conn, &remote.id) {
+                Some(local) => {
+                    let merged = sync::merge_tasks(&local, remote);
+                    if keys.address(&bundle(&merged)?) != keys.address(&bundle(&local)?) {
+                        tasks::save_stored_task(&conn, &merged)?;
+                        report.merged += 1;
+                    }
+                }
+                None => {
+                    tasks::save_stored_task(&conn, remote)?;
+                    report.imported += 1;
+                }
+            }
+        }
+
+        let mut manifest = Manifest {
+            device_id: own_id.clone(),
+            updated_at: chrono::Utc::now().to_rfc3339(),
+            tasks: HashMap::new(),
+            deleted: tombstones.into_iter().collect(),
+        };
+        let mut bundles: Vec<(String, Vec<u8>)> = Vec::new();
+        for task in tasks::get_tasks(&conn) {
+            let plaintext = bundle(&task)?;
+            let hash = keys.address(&plaintext);
+            manifest.tasks.insert(task.id.clone(), hash.clone());
+            bundles.push((hash, plaintext));
+        }
+        (manifest, bundles)
+    };
+
+    // Upload bundles the relay does not have yet, then the manifest
+    let existing: HashSet<String> = relay.list(&keys.key("blobs")).await?.into_iter().collect();
+    for (hash, plaintext) in bundles {
+        if existing.contains(&hash) {
+            continue;
+        }
+        let blob_name = keys.key(&format!("blobs/{}", hash));
+        relay
+            .put(&blob_name, seal(&keys.cipher, &blob_name, &plaintext)?)
+            .await?;
+        report.exported += 1;
+    }
+
+    let manifest_json =
+        serde_json::to_vec(&manifest).map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    let manifest_name = keys.key(&format!("manifests/{}", own_id));
+    relay
+        .put(&manifest_name, seal(&keys.cipher, &manifest_name, &manifest_json)?)
+        .await?;
+
+    tracing::info!(
+        "[EncryptedSync] Synced: {} imported, {} uploaded, {} merged, {} deleted",
+        report.imported,
+        report.exported,
+        report.merged,
+        report.deleted
+    );
+    Ok(report)
+}
@@ -0,0 +1,206 @@
+// src-tauri/src/events.rs
+//! Contract of the events forwarded from the sidecar
+//!
+//! `handle_sidecar_event` turns each sidecar message into an app event named
+//! here, emitted as `{ taskId?, payload }`. `AppEvent` pairs every name with
+//! the shape of its payload, and ts-rs writes it as TypeScript to
+//! `src/lib/bindings` when the tests run (`pnpm bindings`), so the
+//! frontend's listeners are typed from the same definitions. Fields the
+//! frontend narrows itself (OpenCode messages, results) stay `unknown`.
+//!
+//! Payloads are forwarded as the sidecar sent them, so fields the contract
+//! doesn't name still reach the frontend. Debug builds check each against
+//! its contract and warn on a mismatch, so drift shows up in the logs rather
+//! than as a stuck UI.
+
+use crate::sidecar::ForwardedEvent;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// An app event's payload: the task it is about and what the sidecar sent
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct Envelope<P> {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub task_id: Option<String>,
+    pub payload: P,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ReadyPayload {
+    pub version: String,
+    #[serde(default)]
+    pub cli_available: bool,
+    #[serde(default)]
+    pub cli_version: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct PongPayload {
+    /// Milliseconds since the epoch
+    pub timestamp: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CliStatusPayload {
+    pub available: bool,
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct LogPayload {
+    /// "info", "warn" or "error"
+    pub level: String,
+    pub message: String,
+}
+
+/// A sidecar command failed outside a task run
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ErrorPayload {
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct TaskStartedPayload {
+    pub task_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TaskMessagePayload {
+    /// `OpenCodeMessage` from the sidecar, or a `TaskMessage`
+    #[ts(type = "unknown")]
+    pub message: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TaskProgressPayload {
+    /// `TaskProgress`
+    #[ts(type = "unknown")]
+    pub progress: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct PermissionRequestPayload {
+    /// `PermissionRequest`
+    #[ts(type = "unknown")]
+    pub request: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TaskUsagePayload {
+    /// `TaskUsage`
+    #[ts(type = "unknown")]
+    pub usage: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TaskCompletePayload {
+    /// `TaskResult`, with `structuredOutput` for tasks with an output schema
+    #[ts(type = "unknown")]
+    pub result: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TaskErrorPayload {
+    /// Usually a message; OpenCode errors can arrive as objects
+    #[ts(type = "unknown")]
+    pub error: serde_json::Value,
+}
+
+/// An event forwarded from the sidecar, as emitted and as batched in `task:batch`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[serde(tag = "event", content = "payload")]
+#[ts(export)]
+pub enum AppEvent {
+    #[serde(rename = "sidecar:ready")]
+    Ready(Envelope<ReadyPayload>),
+    #[serde(rename = "sidecar:pong")]
+    Pong(Envelope<PongPayload>),
+    #[serde(rename = "sidecar:cli_status")]
+    CliStatus(Envelope<CliStatusPayload>),
+    #[serde(rename = "sidecar:log")]
+    Log(Envelope<LogPayload>),
+    #[serde(rename = "sidecar:error")]
+    Error(Envelope<ErrorPayload>),
+    #[serde(rename = "task:started")]
+    TaskStarted(Envelope<TaskStartedPayload>),
+    #[serde(rename = "task:message")]
+    TaskMessage(Envelope<TaskMessagePayload>),
+    #[serde(rename = "task:progress")]
+    TaskProgress(Envelope<TaskProgressPayload>),
+    #[serde(rename = "task:permission_request")]
+    PermissionRequest(Envelope<PermissionRequestPayload>),
+    #[serde(rename = "task:usage")]
+    TaskUsage(Envelope<TaskUsagePayload>),
+    #[serde(rename = "task:complete")]
+    TaskComplete(Envelope<TaskCompletePayload>),
+    #[serde(rename = "task:error")]
+    TaskError(Envelope<TaskErrorPayload>),
+}
+
+/// App event a sidecar message type is forwarded as
+pub fn app_event_name(sidecar_type: &str) -> Option<&'static str> {
+    let name = match sidecar_type {
+        "ready" => "sidecar:ready",
+        "pong" => "sidecar:pong",
+        "cli_status" => "sidecar:cli_status",
+        "task_started" => "task:started",
+        "task_message" => "task:message",
+        "task_progress" => "task:progress",
+        "permission_request" => "task:permission_request",
+        "task_usage" => "task:usage",
+        "task_complete" => "task:complete",
+        "task_error" => "task:error",
+        "log" => "sidecar:log",
+        "error" => "sidecar:error",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Build the app event for a sidecar message, passing its payload through unchanged
+pub fn forwarded(
+    name: &'static str,
+    task_id: Option<String>,
+    payload: Option<serde_json::Value>,
+) -> ForwardedEvent {
+    let mut envelope = serde_json::json!({});
+    if let Some(task_id) = task_id {
+        envelope["taskId"] = serde_json::json!(task_id);
+    }
+    if let Some(payload) = payload {
+        envelope["payload"] = payload;
+    }
+    if cfg!(debug_assertions) {
+        check_contract(name, &envelope);
+    }
+    ForwardedEvent {
+        event: name.to_string(),
+        payload: envelope,
+    }
+}
+
+/// Warn when an event's payload doesn't match its contract
+fn check_contract(name: &str, envelope: &serde_json::Value) {
+    let event = serde_json::json!({ "event": name, "payload": envelope });
+    if let Err(e) = serde_json::from_value::<AppEvent>(event) {
+        tracing::warn!("[Events] {} payload doesn't match its contract: {}", name, e);
+    }
+}
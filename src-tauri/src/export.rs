@@ -0,0 +1,885 @@
+// src-tauri/src/export.rs
+//! Transcript export
+//!
+//! Renders a stored task as a self-contained HTML document (markdown rendered,
+//! code blocks syntax-highlighted, screenshots embedded as data URLs) and can
+//! print that document to PDF with a locally installed Chromium-based browser.
+//! A shareable variant strips secrets, file paths, and attachments first.
+//! Assistant messages are followed by the web sources they had in context
+//! and by the user's rating of them.
+//!
+//! `export_task` writes a Markdown transcript, with screenshots saved in a
+//! folder beside it, or a JSON bundle of everything stored about the task.
+//! `export_task_metrics_csv` writes one row per task for spreadsheets.
+
+use crate::db::citations::{MessageSource, TaskSources};
+use crate::db::ratings::{MessageRating, TaskRatings};
+use crate::db::tasks::{StoredAttachment, StoredTask, StoredTaskMessage};
+use crate::db::usage::TaskMetrics;
+use crate::logging::redact_secrets;
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::parsing::SyntaxSet;
+
+/// Environment variable overriding the browser used for PDF rendering
+const PDF_BROWSER_ENV: &str = "COWORK_Z_PDF_BROWSER";
+
+/// Layout version of JSON bundles, raised when it changes incompatibly
+const BUNDLE_VERSION: u32 = 1;
+
+static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+static THEME: LazyLock<Theme> = LazyLock::new(|| {
+    ThemeSet::load_defaults()
+        .themes
+        .remove("InspiredGitHub")
+        .unwrap_or_default()
+});
+
+/// Absolute Unix or Windows paths; the directory part is dropped, the file name kept
+static ABSOLUTE_PATH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(^|[\s"'`(\[=,:])(?:[A-Za-z]:\\|/)(?:[\w.\-@+~]+[/\\])+([\w.\-@+~]*)"#).unwrap()
+});
+
+const STYLES: &str = r#"
+body { font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif; color: #1f2328; max-width: 860px; margin: 2rem auto; padding: 0 1.5rem; line-height: 1.55; }
+header { border-bottom: 1px solid #d0d7de; margin-bottom: 1.5rem; padding-bottom: 1rem; }
+header h1 { font-size: 1.5rem; margin: 0 0 .5rem; }
+header dl { display: grid; grid-template-columns: max-content 1fr; gap: .25rem 1rem; margin: 0; font-size: .85rem; color: #59636e; }
+header dd { margin: 0; }
+.message { margin: 0 0 1.25rem; padding: .75rem 1rem; border-radius: 8px; border: 1px solid #d0d7de; page-break-inside: avoid; }
+.message .meta { font-size: .75rem; color: #59636e; margin-bottom: .35rem; text-transform: uppercase; letter-spacing: .03em; }
+.message.user { background: #f6f8fa; }
+.message.tool { background: #fbfbfc; font-size: .9rem; }
+.message.system { color: #59636e; font-size: .9rem; }
+pre { padding: .75rem; border-radius: 6px; overflow-x: auto; font-size: .85rem; white-space: pre-wrap; word-break: break-word; }
+code { font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; }
+:not(pre) > code { background: #eff1f3; padding: .1rem .3rem; border-radius: 4px; font-size: .85em; }
+figure { margin: .75rem 0 0; }
+figure img { max-width: 100%; border: 1px solid #d0d7de; border-radius: 6px; }
+figcaption { font-size: .8rem; color: #59636e; }
+.sources { margin: .75rem 0 0; font-size: .85rem; }
+.sources ol { margin: .25rem 0 0; padding-left: 1.5rem; }
+.sources a { color: #0969da; word-break: break-all; }
+.rating { margin: .75rem 0 0; font-size: .85rem; }
+.rating p { margin: .25rem 0 0; color: #59636e; }
+"#;
+
+/// Escape text for inclusion in HTML
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escape the characters that would end a Markdown link label early
+fn escape_link_label(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('[', "\\[").replace(']', "\\]")
+}
+
+/// Highlight a code snippet, falling back to plain escaped text
+fn highlight_code(code: &str, language: &str) -> String {
+    let syntax = SYNTAX_SET
+        .find_syntax_by_token(language)
+        .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
+    syntect::html::highlighted_html_for_string(code, &SYNTAX_SET, syntax, &THEME)
+        .unwrap_or_else(|_| format!("<pre><code>{}</code></pre>", escape_html(code)))
+}
+
+/// Schemes a link in rendered markdown may use; relative links have none
+const LINK_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// Schemes an image in rendered markdown may load from
+const IMAGE_SCHEMES: &[&str] = &["http", "https"];
+
+/// Whether a link target is relative or uses one of `schemes`
+///
+/// Browsers ignore whitespace and control characters inside a scheme, so
+/// `java\tscript:` is checked as `javascript:`.
+fn allowed_url(url: &str, schemes: &[&str]) -> bool {
+    let url: String = url.chars().filter(|c| !c.is_ascii_whitespace() && !c.is_control()).collect();
+    match url.split_once(':') {
+        Some((scheme, _)) if !scheme.contains(['/', '?', '#']) => {
+            schemes.iter().any(|allowed| scheme.eq_ignore_ascii_case(allowed))
+        }
+        _ => true,
+    }
+}
+
+/// Render markdown to HTML with highlighted code blocks
+///
+/// Raw HTML in the source is escaped rather than passed through, and links
+/// and images with a scheme outside `LINK_SCHEMES`/`IMAGE_SCHEMES` (such as
+/// `javascript:`) are reduced to their text, so a transcript can never inject
+/// markup or script into the exported document.
+fn render_markdown(markdown: &str) -> String {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+
+    let mut events: Vec<Event> = Vec::new();
+    let mut code_block: Option<(String, String)> = None;
+    // Links and images can't nest in themselves, so one flag each is enough
+    let mut dropped_link = false;
+    let mut dropped_image = false;
+
+    for event in Parser::new_ext(markdown, options) {
+        match event {
+            Event::Start(Tag::Link { ref dest_url, .. })
+                if !allowed_url(dest_url, LINK_SCHEMES) =>
+            {
+                dropped_link = true;
+            }
+            Event::End(TagEnd::Link) if dropped_link => dropped_link = false,
+            Event::Start(Tag::Image { ref dest_url, .. })
+                if !allowed_url(dest_url, IMAGE_SCHEMES) =>
+            {
+                dropped_image = true;
+            }
+            Event::End(TagEnd::Image) if dropped_image => dropped_image = false,
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let language = match kind {
+                    CodeBlockKind::Fenced(lang) => lang.split_whitespace().next().unwrap_or("").to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                code_block = Some((language, String::new()));
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some((language, code)) = code_block.take() {
+                    // Blocks without an info string are highlighted as what they look like
+                    let language = if language.is_empty() {
+                        crate::language::detect(None, &code).unwrap_or_default()
+                    } else {
+                        language
+                    };
+                    events.push(Event::Html(highlight_code(&code, &language).into()));
+                }
+            }
+            Event::Text(text) if code_block.is_some() => {
+                if let Some((_, code)) = code_block.as_mut() {
+                    code.push_str(&text);
+                }
+            }
+            Event::Html(html) | Event::InlineHtml(html) => events.push(Event::Text(html)),
+            other => events.push(other),
+        }
+    }
+
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, events.into_iter());
+    html
+}
+
+/// Render an attachment as an embedded image or highlighted JSON
+fn render_attachment(attachment: &StoredAttachment) -> String {
+    let caption = attachment
+        .label
+        .as_deref()
+        .map(|label| format!("<figcaption>{}</figcaption>", escape_html(label)))
+        .unwrap_or_default();
+
+    match attachment.att_type.as_str() {
+        "screenshot" => {
+            let src = if attachment.data.starts_with("data:") {
+                attachment.data.clone()
+            } else {
+                format!("data:image/png;base64,{}", attachment.data)
+            };
+            format!(
+                "<figure><img src=\"{}\" alt=\"{}\">{}</figure>",
+                escape_html(&src),
+                escape_html(attachment.label.as_deref().unwrap_or("Screenshot")),
+                caption
+            )
+        }
+        _ => {
+            let pretty = serde_json::from_str::<serde_json::Value>(&attachment.data)
+                .and_then(|v| serde_json::to_string_pretty(&v))
+                .unwrap_or_else(|_| attachment.data.clone());
+            format!("<figure>{}{}</figure>", highlight_code(&pretty, "json"), caption)
+        }
+    }
+}
+
+/// Normalized role of a message, used as its CSS class
+fn message_role(message: &StoredTaskMessage) -> &str {
+    match message.msg_type.as_str() {
+        "user" | "assistant" | "tool" | "system" => message.msg_type.as_str(),
+        _ => "system",
+    }
+}
+
+/// Longest tool name shown in a transcript
+const MAX_TOOL_NAME_LEN: usize = 64;
+
+/// A tool name as reported by the agent, reduced to characters tool names use
+///
+/// The name comes from the model's tool call, so it is kept from carrying
+/// markup into Markdown headings or path separators into anything built from it.
+fn sanitize_tool_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        .take(MAX_TOOL_NAME_LEN)
+        .collect()
+}
+
+/// Display label for a message, including its time when known
+fn message_label(message: &StoredTaskMessage) -> String {
+    let tool_name = message.tool_name.as_deref().map(sanitize_tool_name);
+    let label = match (message_role(message), tool_name.as_deref()) {
+        ("tool", Some(tool)) if !tool.is_empty() => format!("Tool · {}", tool),
+        ("user", _) => "You".to_string(),
+        ("assistant", _) => "Assistant".to_string(),
+        ("tool", _) => "Tool".to_string(),
+        _ => "System".to_string(),
+    };
+    let time = chrono::DateTime::parse_from_rfc3339(&message.timestamp)
+        .map(|t| t.format(" · %H:%M:%S").to_string())
+        .unwrap_or_default();
+    format!("{}{}", label, time)
+}
+
+/// Numbered list of a message's sources
+fn render_sources(sources: &[MessageSource]) -> String {
+    let items: String = sources
+        .iter()
+        .map(|source| {
+            let url = escape_html(&source.url);
+            let label = source.title.as_deref().map(escape_html).unwrap_or_else(|| url.clone());
+            if crate::citations::is_web_url(&source.url) {
+                format!("<li><a href=\"{}\">{}</a></li>", url, label)
+            } else {
+                format!("<li>{}</li>", label)
+            }
+        })
+        .collect();
+    format!("<div class=\"sources\"><div class=\"meta\">Sources</div><ol>{}</ol></div>", items)
+}
+
+/// A rating and the model it was given to, on one line
+fn rating_summary(rating: &MessageRating) -> String {
+    let verdict = if rating.rating == "up" { "👍 Helpful" } else { "👎 Not helpful" };
+    match &rating.model_id {
+        Some(model_id) => format!("{} · {}", verdict, model_id),
+        None => verdict.to_string(),
+    }
+}
+
+/// A message's rating and its comment
+fn render_rating(rating: &MessageRating) -> String {
+    let comment = rating
+        .comment
+        .as_deref()
+        .map(|c| format!("<p>{}</p>", escape_html(c)))
+        .unwrap_or_default();
+    format!(
+        "<div class=\"rating\"><div class=\"meta\">Rating</div>{}{}</div>",
+        escape_html(&rating_summary(rating)),
+        comment
+    )
+}
+
+/// Render one transcript message
+fn render_message(
+    message: &StoredTaskMessage,
+    sources: &[MessageSource],
+    rating: Option<&MessageRating>,
+) -> String {
+    let mut body = String::new();
+    if !message.content.trim().is_empty() {
+        body.push_str(&render_markdown(&message.content));
+    }
+    if let Some(input) = &message.tool_input {
+        let pretty = serde_json::to_string_pretty(input).unwrap_or_default();
+        body.push_str(&highlight_code(&pretty, "json"));
+    }
+    for attachment in message.attachments.iter().flatten() {
+        body.push_str(&render_attachment(attachment));
+    }
+    if !sources.is_empty() {
+        body.push_str(&render_sources(sources));
+    }
+    if let Some(rating) = rating {
+        body.push_str(&render_rating(rating));
+    }
+
+    format!(
+        "<section class=\"message {}\"><div class=\"meta\">{}</div>{}</section>\n",
+        message_role(message),
+        escape_html(&message_label(message)),
+        body
+    )
+}
+
+/// Title for a task: its summary, or the first line of the prompt
+pub(crate) fn task_title(task: &StoredTask) -> String {
+    task.summary
+        .as_deref()
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or(&task.prompt)
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+/// Render a task transcript as a standalone HTML document
+pub fn render_task_html(task: &StoredTask, sources: &TaskSources, ratings: &TaskRatings) -> String {
+    let title = escape_html(&task_title(task));
+
+    let mut details = format!(
+        "<dt>Status</dt><dd>{}</dd><dt>Created</dt><dd>{}</dd>",
+        escape_html(&task.status),
+        escape_html(&task.created_at)
+    );
+    if let Some(completed_at) = &task.completed_at {
+        details.push_str(&format!(
+            "<dt>Completed</dt><dd>{}</dd>",
+            escape_html(completed_at)
+        ));
+    }
+
+    let mut messages = String::new();
+    if !first_message_is_prompt(task) {
+        messages.push_str(&format!(
+            "<section class=\"message user\"><div class=\"meta\">You</div>{}</section>\n",
+            render_markdown(&task.prompt)
+        ));
+    }
+    for message in &task.messages {
+        let message_sources = sources.get(&message.id).map(Vec::as_slice).unwrap_or_default();
+        messages.push_str(&render_message(message, message_sources, ratings.get(&message.id)));
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>{STYLES}</style>\n</head>\n<body>\n<header><h1>{title}</h1><dl>{details}</dl></header>\n<main>\n{messages}</main>\n</body>\n</html>\n"
+    )
+}
+
+/// Write a task transcript as HTML
+pub fn export_task_html(
+    task: &StoredTask,
+    sources: &TaskSources,
+    ratings: &TaskRatings,
+    path: &Path,
+) -> Result<(), String> {
+    std::fs::write(path, render_task_html(task, sources, ratings))
+        .map_err(|e| format!("Failed to write HTML export: {}", e))
+}
+
+/// Whether the first message simply repeats the task prompt
+fn first_message_is_prompt(task: &StoredTask) -> bool {
+    task.messages
+        .first()
+        .is_some_and(|m| m.msg_type == "user" && m.content.trim() == task.prompt.trim())
+}
+
+/// Screenshots of a task in transcript order
+fn screenshots(task: &StoredTask) -> impl Iterator<Item = &StoredAttachment> {
+    task.messages
+        .iter()
+        .flat_map(|m| m.attachments.iter().flatten())
+        .filter(|a| a.att_type == "screenshot")
+}
+
+/// File name of the `n`th screenshot written beside a Markdown transcript
+fn screenshot_file(n: usize, attachment: &StoredAttachment) -> String {
+    let extension = attachment
+        .data
+        .strip_prefix("data:image/")
+        .and_then(|rest| rest.split_once(';'))
+        .map(|(subtype, _)| if subtype == "jpeg" { "jpg" } else { subtype })
+        .filter(|subtype| subtype.chars().all(|c| c.is_ascii_alphanumeric()))
+        .unwrap_or("png");
+    format!("screenshot-{}.{}", n, extension)
+}
+
+/// Render a task transcript as Markdown
+pub fn render_task_markdown(
+    task: &StoredTask,
+    sources: &TaskSources,
+    ratings: &TaskRatings,
+) -> String {
+    markdown_transcript(task, sources, ratings, None)
+}
+
+/// Markdown transcript; screenshots link into `screenshot_dir` when given,
+/// named by `screenshot_file`, and are only labeled otherwise
+fn markdown_transcript(
+    task: &StoredTask,
+    sources: &TaskSources,
+    ratings: &TaskRatings,
+    screenshot_dir: Option<&str>,
+) -> String {
+    let mut screenshot_count = 0;
+    let mut markdown = format!(
+        "# {}\n\n- **Status:** {}\n- **Created:** {}\n",
+        task_title(task),
+        task.status,
+        task.created_at
+    );
+    if let Some(completed_at) = &task.completed_at {
+        markdown.push_str(&format!("- **Completed:** {}\n", completed_at));
+    }
+
+    if !first_message_is_prompt(task) {
+        markdown.push_str(&format!("\n## You\n\n{}\n", task.prompt.trim()));
+    }
+
+    for message in &task.messages {
+        markdown.push_str(&format!("\n## {}\n", message_label(message)));
+        if !message.content.trim().is_empty() {
+            markdown.push_str(&format!("\n{}\n", message.content.trim()));
+        }
+        if let Some(input) = &message.tool_input {
+            let pretty = serde_json::to_string_pretty(input).unwrap_or_default();
+            markdown.push_str(&format!("\n```json\n{}\n```\n", pretty));
+        }
+        for attachment in message.attachments.iter().flatten() {
+            let label = attachment.label.as_deref().unwrap_or("Attachment");
+            if attachment.att_type == "screenshot" {
+                screenshot_count += 1;
+                match screenshot_dir {
+                    Some(dir) => markdown.push_str(&format!(
+                        "\n![{}](<{}/{}>)\n",
+                        label,
+                        dir,
+                        screenshot_file(screenshot_count, attachment)
+                    )),
+                    None => markdown.push_str(&format!("\n_[Screenshot: {}]_\n", label)),
+                }
+            } else {
+                markdown.push_str(&format!("\n{}:\n\n```json\n{}\n```\n", label, attachment.data));
+            }
+        }
+        if let Some(message_sources) = sources.get(&message.id) {
+            markdown.push_str("\n**Sources**\n\n");
+            for (i, source) in message_sources.iter().enumerate() {
+                let label = escape_link_label(source.title.as_deref().unwrap_or(&source.url));
+                if crate::citations::is_web_url(&source.url) && !source.url.contains(['<', '>']) {
+                    markdown.push_str(&format!("{}. [{}](<{}>)\n", i + 1, label, source.url));
+                } else {
+                    markdown.push_str(&format!("{}. {}\n", i + 1, label));
+                }
+            }
+        }
+        if let Some(rating) = ratings.get(&message.id) {
+            markdown.push_str(&format!("\n**Rating:** {}\n", rating_summary(rating)));
+            if let Some(comment) = &rating.comment {
+                markdown.push_str(&format!("\n> {}\n", comment.replace('\n', "\n> ")));
+            }
+        }
+    }
+
+    markdown
+}
+
+/// Write a Markdown transcript, saving its screenshots to a `<name>_files` folder beside it
+fn export_task_markdown(
+    task: &StoredTask,
+    sources: &TaskSources,
+    ratings: &TaskRatings,
+    path: &Path,
+) -> Result<(), String> {
+    use base64::Engine;
+
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "transcript".to_string());
+    let dir_name = format!("{}_files", stem);
+    let has_screenshots = screenshots(task).next().is_some();
+    let markdown =
+        markdown_transcript(task, sources, ratings, has_screenshots.then_some(dir_name.as_str()));
+    std::fs::write(path, markdown)
+        .map_err(|e| format!("Failed to write Markdown export: {}", e))?;
+
+    if has_screenshots {
+        let dir = path.with_file_name(&dir_name);
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create screenshot folder: {}", e))?;
+        for (i, attachment) in screenshots(task).enumerate() {
+            let encoded = attachment
+                .data
+                .split_once(";base64,")
+                .map(|(_, d)| d)
+                .unwrap_or(&attachment.data);
+            let bytes = match base64::engine::general_purpose::STANDARD.decode(encoded.trim()) {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    tracing::warn!("[Export] Skipping unreadable screenshot: {}", e);
+                    continue;
+                }
+            };
+            std::fs::write(dir.join(screenshot_file(i + 1, attachment)), bytes)
+                .map_err(|e| format!("Failed to write screenshot: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Everything stored about a task, as written by a JSON export
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TaskBundle<'a> {
+    version: u32,
+    exported_at: String,
+    task: &'a StoredTask,
+    tags: &'a [String],
+    /// Web sources of assistant messages, by message ID
+    sources: &'a TaskSources,
+    /// Ratings of assistant messages, by message ID
+    ratings: &'a TaskRatings,
+}
+
+/// Write a task as a Markdown transcript ("markdown") or a JSON bundle ("json")
+pub fn export_task(
+    task: &StoredTask,
+    sources: &TaskSources,
+    ratings: &TaskRatings,
+    tags: &[String],
+    format: &str,
+    path: &Path,
+) -> Result<(), String> {
+    match format {
+        "markdown" | "md" => export_task_markdown(task, sources, ratings, path)?,
+        "json" => {
+            let bundle = TaskBundle {
+                version: BUNDLE_VERSION,
+                exported_at: chrono::Utc::now().to_rfc3339(),
+                task,
+                tags,
+                sources,
+                ratings,
+            };
+            let json = serde_json::to_string_pretty(&bundle)
+                .map_err(|e| format!("Failed to serialize task: {}", e))?;
+            std::fs::write(path, json)
+                .map_err(|e| format!("Failed to write JSON export: {}", e))?;
+        }
+        other => return Err(format!("Unsupported export format: {}", other)),
+    }
+
+    tracing::info!("[Export] Wrote {} export of task {} to {:?}", format, task.id, path);
+    Ok(())
+}
+
+/// Columns of the task metrics CSV
+const METRICS_COLUMNS: &[&str] = &[
+    "task_id",
+    "created_at",
+    "title",
+    "status",
+    "workspace",
+    "model",
+    "duration_seconds",
+    "input_tokens",
+    "output_tokens",
+    "reasoning_tokens",
+    "cache_read_tokens",
+    "cache_write_tokens",
+    "cost_usd",
+    "rated_up",
+    "rated_down",
+    "tags",
+];
+
+/// Quote a CSV field when it holds a separator, quote, or line break
+///
+/// A field a spreadsheet would read as a formula gets a leading `'`, so a
+/// title like `=HYPERLINK(...)` stays text.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+/// Write task metrics as CSV with a header row; tags are separated by `;`
+pub fn export_task_metrics_csv(tasks: &[TaskMetrics], path: &Path) -> Result<(), String> {
+    let mut csv = METRICS_COLUMNS.join(",");
+    csv.push_str("\r\n");
+    for task in tasks {
+        let fields = [
+            task.task_id.clone(),
+            task.created_at.clone(),
+            task.title.clone().unwrap_or_default(),
+            task.status.clone(),
+            task.workspace.clone().unwrap_or_default(),
+            task.model_id.clone().unwrap_or_default(),
+            task.duration_seconds.map(|d| format!("{:.1}", d)).unwrap_or_default(),
+            task.input_tokens.to_string(),
+            task.output_tokens.to_string(),
+            task.reasoning_tokens.to_string(),
+            task.cache_read_tokens.to_string(),
+            task.cache_write_tokens.to_string(),
+            format!("{:.6}", task.cost),
+            task.rated_up.to_string(),
+            task.rated_down.to_string(),
+            task.tags.join(";"),
+        ];
+        let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&row.join(","));
+        csv.push_str("\r\n");
+    }
+    std::fs::write(path, csv).map_err(|e| format!("Failed to write CSV export: {}", e))?;
+
+    tracing::info!("[Export] Wrote metrics of {} tasks to {:?}", tasks.len(), path);
+    Ok(())
+}
+
+/// Options for a shareable export
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ShareableOptions {
+    /// "markdown" (default) or "html"
+    pub format: String,
+    pub strip_paths: bool,
+    pub strip_attachments: bool,
+    pub include_tool_calls: bool,
+}
+
+impl Default for ShareableOptions {
+    fn default() -> Self {
+        Self {
+            format: "markdown".to_string(),
+            strip_paths: true,
+            strip_attachments: true,
+            include_tool_calls: true,
+        }
+    }
+}
+
+/// Redact secrets and, optionally, absolute paths from a piece of text
+fn sanitize_text(text: &str, options: &ShareableOptions) -> String {
+    let redacted = redact_secrets(text);
+    if options.strip_paths {
+        ABSOLUTE_PATH.replace_all(&redacted, "${1}…/${2}").into_owned()
+    } else {
+        redacted
+    }
+}
+
+/// Sanitize a JSON value by sanitizing every string inside it
+fn sanitize_value(value: &serde_json::Value, options: &ShareableOptions) -> serde_json::Value {
+    match value {
+        serde_json::Value::String(s) => serde_json::Value::String(sanitize_text(s, options)),
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.iter().map(|v| sanitize_value(v, options)).collect())
+        }
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), sanitize_value(v, options)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Copy of a task with secrets, paths, and attachments removed per `options`
+pub fn sanitize_task(task: &StoredTask, options: &ShareableOptions) -> StoredTask {
+    let messages = task
+        .messages
+        .iter()
+        .filter(|m| options.include_tool_calls || m.msg_type != "tool")
+        .map(|m| StoredTaskMessage {
+            id: m.id.clone(),
+            msg_type: m.msg_type.clone(),
+            content: sanitize_text(&m.content, options),
+            timestamp: m.timestamp.clone(),
+            tool_name: m.tool_name.as_deref().map(sanitize_tool_name),
+            tool_input: m.tool_input.as_ref().map(|v| sanitize_value(v, options)),
+            attachments: if options.strip_attachments {
+                None
+            } else {
+                m.attachments.as_ref().map(|atts| {
+                    atts.iter()
+                        .map(|a| StoredAttachment {
+                            id: None,
+                            att_type: a.att_type.clone(),
+                            data: if a.att_type == "screenshot" {
+                                a.data.clone()
+                            } else {
+                                sanitize_text(&a.data, options)
+                            },
+                            label: a.label.as_deref().map(|l| sanitize_text(l, options)),
+                            size: None,
+                        })
+                        .collect()
+                })
+            },
+            note: m.note.as_deref().map(|n| sanitize_text(n, options)),
+        })
+        .collect();
+
+    StoredTask {
+        id: task.id.clone(),
+        prompt: sanitize_text(&task.prompt, options),
+        summary: task.summary.as_deref().map(|s| sanitize_text(s, options)),
+        title: task.title.as_deref().map(|t| sanitize_text(t, options)),
+        status: task.status.clone(),
+        messages,
+        session_id: None,
+        created_at: task.created_at.clone(),
+        started_at: task.started_at.clone(),
+        completed_at: task.completed_at.clone(),
+        updated_at: task.updated_at.clone(),
+        workspace_id: None,
+        allow_web_search: task.allow_web_search,
+    }
+}
+
+/// Sources with secrets redacted; URLs keep their paths
+fn sanitize_sources(sources: &TaskSources, options: &ShareableOptions) -> TaskSources {
+    sources
+        .iter()
+        .map(|(message_id, message_sources)| {
+            let sanitized = message_sources
+                .iter()
+                .map(|source| MessageSource {
+                    url: redact_secrets(&source.url),
+                    title: source.title.as_deref().map(|t| sanitize_text(t, options)),
+                    kind: source.kind.clone(),
+                    chunk: source.chunk,
+                })
+                .collect();
+            (message_id.clone(), sanitized)
+        })
+        .collect()
+}
+
+/// Ratings with their comments sanitized
+fn sanitize_ratings(ratings: &TaskRatings, options: &ShareableOptions) -> TaskRatings {
+    ratings
+        .iter()
+        .map(|(message_id, rating)| {
+            let sanitized = MessageRating {
+                comment: rating.comment.as_deref().map(|c| sanitize_text(c, options)),
+                ..rating.clone()
+            };
+            (message_id.clone(), sanitized)
+        })
+        .collect()
+}
+
+/// Render a sanitized transcript in the requested format
+pub fn render_shareable(
+    task: &StoredTask,
+    sources: &TaskSources,
+    ratings: &TaskRatings,
+    options: &ShareableOptions,
+) -> Result<String, String> {
+    let sanitized = sanitize_task(task, options);
+    let sources = sanitize_sources(sources, options);
+    let ratings = sanitize_ratings(ratings, options);
+    match options.format.as_str() {
+        "markdown" | "md" => Ok(render_task_markdown(&sanitized, &sources, &ratings)),
+        "html" => Ok(render_task_html(&sanitized, &sources, &ratings)),
+        other => Err(format!("Unsupported export format: {}", other)),
+    }
+}
+
+/// Find a Chromium-based browser able to print to PDF
+fn find_pdf_browser() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var(PDF_BROWSER_ENV) {
+        return Some(PathBuf::from(path));
+    }
+
+    #[cfg(target_os = "macos")]
+    let candidates: Vec<PathBuf> = [
+        "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+        "/Applications/Chromium.app/Contents/MacOS/Chromium",
+        "/Applications/Microsoft Edge.app/Contents/MacOS/Microsoft Edge",
+        "/Applications/Brave Browser.app/Contents/MacOS/Brave Browser",
+    ]
+    .iter()
+    .map(PathBuf::from)
+    .collect();
+
+    #[cfg(target_os = "windows")]
+    let candidates: Vec<PathBuf> = ["ProgramFiles", "ProgramFiles(x86)", "LOCALAPPDATA"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .flat_map(|base| {
+            [
+                PathBuf::from(&base).join(r"Google\Chrome\Application\chrome.exe"),
+                PathBuf::from(&base).join(r"Microsoft\Edge\Application\msedge.exe"),
+            ]
+        })
+        .collect();
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let candidates: Vec<PathBuf> = std::env::var_os("PATH")
+        .map(|paths| {
+            std::env::split_paths(&paths)
+                .flat_map(|dir| {
+                    ["google-chrome", "chromium", "chromium-browser", "microsoft-edge"]
+                        .iter()
+                        .map(move |name| dir.join(name))
+                        .collect::<Vec<_>>()
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    candidates.into_iter().find(|path| path.is_file())
+}
+
+/// Render a task transcript to PDF via a headless browser
+pub fn export_task_pdf(
+    task: &StoredTask,
+    sources: &TaskSources,
+    ratings: &TaskRatings,
+    path: &Path,
+) -> Result<(), String> {
+    let browser = find_pdf_browser().ok_or_else(|| {
+        format!(
+            "PDF export needs Google Chrome, Chromium, or Microsoft Edge (or set {})",
+            PDF_BROWSER_ENV
+        )
+    })?;
+
+    let html_path = std::env::temp_dir().join(format!("cowork-export-{}.html", uuid::Uuid::new_v4()));
+    export_task_html(task, sources, ratings, &html_path)?;
+    let html_url = format!(
+        "file:///{}",
+        html_path.display().to_string().replace('\\', "/").trim_start_matches('/')
+    );
+
+    let output = std::process::Command::new(&browser)
+        .arg("--headless=new")
+        .arg("--disable-gpu")
+        .arg("--no-pdf-header-footer")
+        .arg(format!("--print-to-pdf={}", path.display()))
+        .arg(html_url)
+        .output();
+    let _ = std::fs::remove_file(&html_path);
+
+    let output = output.map_err(|e| format!("Failed to launch {:?}: {}", browser, e))?;
+    if !output.status.success() || !path.exists() {
+        return Err(format!(
+            "PDF rendering failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    tracing::info!("[Export] Wrote PDF transcript for task {} to {:?}", task.id, path);
+    Ok(())
+}
@@ -0,0 +1,207 @@
+// src-tauri/src/fixtures.rs
+//! Test data for E2E runs
+//!
+//! `seed_test_data` fills the database with a named fixture so E2E tests start
+//! past onboarding with known workspaces, tasks, and providers. IDs and
+//! timestamps are fixed, and seeding replaces the task history, so every run
+//! sees the same data. Only compiled into debug builds and builds with the
+//! `e2e` feature.
+
+use crate::db;
+use crate::db::providers::{AvailableModel, ConnectedProvider, ProviderCredentials};
+use crate::db::tasks::{TaskInput, TaskMessageInput};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Fixtures `seed` accepts
+pub const FIXTURES: &[&str] = &["empty", "basic", "history"];
+
+/// Time all fixture timestamps count from
+const BASE_TIME: &str = "2025-01-01T09:00:00+00:00";
+
+/// Provider and model the fixtures connect
+const PROVIDER_ID: &str = "anthropic";
+const MODEL_ID: &str = "anthropic/claude-sonnet-4-5";
+
+/// Tasks in the "history" fixture
+const HISTORY_TASKS: usize = 40;
+
+/// What a fixture put in the database
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeedSummary {
+    pub fixture: String,
+    pub workspaces: usize,
+    pub tasks: usize,
+    pub messages: usize,
+    pub providers: usize,
+}
+
+/// `BASE_TIME` plus some minutes, as RFC 3339
+fn timestamp(minutes: i64) -> String {
+    let base = chrono::DateTime::parse_from_rfc3339(BASE_TIME).expect("valid base time");
+    (base + chrono::Duration::minutes(minutes)).to_rfc3339()
+}
+
+fn message(id: String, msg_type: &str, content: &str, minutes: i64) -> TaskMessageInput {
+    TaskMessageInput {
+        id,
+        msg_type: msg_type.to_string(),
+        content: content.to_string(),
+        timestamp: timestamp(minutes),
+        tool_name: None,
+        tool_input: None,
+        attachments: None,
+        note: None,
+        origin_id: None,
+    }
+}
+
+/// A completed task with a prompt and an answer
+fn task(
+    id: &str,
+    prompt: &str,
+    answer: &str,
+    minutes: i64,
+    workspace_id: Option<&str>,
+) -> TaskInput {
+    TaskInput {
+        id: id.to_string(),
+        prompt: prompt.to_string(),
+        status: "completed".to_string(),
+        messages: vec![
+            message(format!("{}_msg_1", id), "user", prompt, minutes),
+            message(format!("{}_msg_2", id), "assistant", answer, minutes + 1),
+        ],
+        session_id: Some(format!("{}_session", id)),
+        summary: None,
+        title: None,
+        created_at: timestamp(minutes),
+        started_at: Some(timestamp(minutes)),
+        completed_at: Some(timestamp(minutes + 2)),
+        workspace_id: workspace_id.map(str::to_string),
+        allow_web_search: Some(false),
+        updated_at: Some(timestamp(minutes + 2)),
+    }
+}
+
+/// Insert or replace a workspace with a fixed ID; the directory is created
+fn seed_workspace(conn: &Connection, id: &str, name: &str) -> Result<(), String> {
+    let dir = std::env::temp_dir().join("cowork-e2e").join(name);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    let path = dir.to_string_lossy().to_string();
+
+    conn.execute(
+        "DELETE FROM workspaces WHERE path = ?1 AND id != ?2",
+        params![path, id],
+    )
+    .map_err(|e| format!("Failed to replace workspace: {}", e))?;
+    conn.execute(
+        "INSERT OR REPLACE INTO workspaces (id, name, path, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![id, name, path, timestamp(0)],
+    )
+    .map_err(|e| format!("Failed to seed workspace: {}", e))?;
+    Ok(())
+}
+
+/// Connect the fixture provider, select its model, and finish onboarding
+fn seed_provider(conn: &Connection) -> Result<(), String> {
+    let provider = ConnectedProvider {
+        provider_id: PROVIDER_ID.to_string(),
+        connection_status: "connected".to_string(),
+        selected_model_id: Some(MODEL_ID.to_string()),
+        credentials: ProviderCredentials {
+            credentials_type: "api_key".to_string(),
+            key_prefix: Some("sk-ant-e2e...".to_string()),
+            server_url: None,
+            api_key: None,
+            extra: HashMap::new(),
+        },
+        last_connected_at: timestamp(0),
+        available_models: Some(vec![AvailableModel {
+            id: MODEL_ID.to_string(),
+            name: "Claude Sonnet 4.5".to_string(),
+        }]),
+    };
+    db::providers::set_connected_provider(conn, PROVIDER_ID, &provider)?;
+    db::providers::set_active_provider(conn, Some(PROVIDER_ID))?;
+    db::settings::set_selected_model(
+        conn,
+        Some(&db::settings::SelectedModel {
+            provider: PROVIDER_ID.to_string(),
+            model: MODEL_ID.to_string(),
+            base_url: None,
+            deployment_name: None,
+        }),
+    )?;
+    db::settings::set_onboarding_complete(conn, true)
+}
+
+/// Replace the task history and seed a fixture
+pub fn seed(conn: &Connection, fixture: &str) -> Result<SeedSummary, String> {
+    if !FIXTURES.contains(&fixture) {
+        return Err(format!(
+            "Unknown fixture: {} (expected one of {})",
+            fixture,
+            FIXTURES.join(", ")
+        ));
+    }
+    db::tasks::clear_history(conn)?;
+
+    let mut summary = SeedSummary {
+        fixture: fixture.to_string(),
+        ..Default::default()
+    };
+    if fixture == "empty" {
+        return Ok(summary);
+    }
+
+    seed_provider(conn)?;
+    summary.providers = 1;
+    seed_workspace(conn, "ws_fixture_app", "app")?;
+    summary.workspaces = 1;
+
+    let tasks = match fixture {
+        "basic" => vec![
+            task(
+                "task_fixture_1",
+                "Fix the login race condition",
+                "Moved the session check behind the token refresh.",
+                0,
+                Some("ws_fixture_app"),
+            ),
+            task(
+                "task_fixture_2",
+                "Summarize the README",
+                "The README describes setup, development, and release steps.",
+                10,
+                None,
+            ),
+        ],
+        _ => (0..HISTORY_TASKS)
+            .map(|i| {
+                task(
+                    &format!("task_fixture_{}", i + 1),
+                    &format!("History task {}", i + 1),
+                    &format!("Answer to history task {}", i + 1),
+                    i as i64 * 10,
+                    (i % 2 == 0).then_some("ws_fixture_app"),
+                )
+            })
+            .collect(),
+    };
+    for task in &tasks {
+        db::tasks::save_task(conn, task)?;
+        summary.messages += task.messages.len();
+    }
+    summary.tasks = tasks.len();
+
+    tracing::info!(
+        "[Fixtures] Seeded {} ({} tasks, {} messages)",
+        fixture,
+        summary.tasks,
+        summary.messages
+    );
+    Ok(summary)
+}
@@ -0,0 +1,368 @@
+// src-tauri/src/github.rs
+//! GitHub integration for task outcomes
+//!
+//! Creates an issue or a draft pull request from a completed task. The
+//! repository is detected from the `origin` remote of the working directory,
+//! and the task's changes are staged into a throwaway index so neither the
+//! user's working tree nor their real index is touched.
+
+use crate::db::tasks::StoredTask;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::LazyLock;
+
+const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// Tools whose `filePath` input marks a file the agent changed
+const FILE_CHANGING_TOOLS: &[&str] = &["write", "edit", "multiedit", "patch"];
+
+/// Maximum characters of diff included in an issue or PR body
+const MAX_DIFF_CHARS: usize = 40_000;
+
+/// Number of trailing assistant messages included in the transcript excerpt
+const EXCERPT_MESSAGES: usize = 5;
+
+/// Maximum characters kept from each excerpted message
+const EXCERPT_MESSAGE_CHARS: usize = 1_500;
+
+static GITHUB_REMOTE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"github\.com[:/]([^/\s]+)/([^/\s]+?)(?:\.git)?/?$").unwrap()
+});
+
+/// A GitHub repository detected from a git remote
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitHubRepo {
+    pub owner: String,
+    pub name: String,
+    /// Local repository root
+    pub root: String,
+}
+
+/// A created issue or pull request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitHubLink {
+    pub number: u64,
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreatedItem {
+    number: u64,
+    html_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RepoInfo {
+    default_branch: String,
+}
+
+/// Run git in `dir` and return trimmed stdout
+pub(crate) fn git(dir: &Path, args: &[&str], index_file: Option<&Path>) -> Result<String, String> {
+    let mut command = Command::new("git");
+    command.args(args).current_dir(dir);
+    if let Some(index_file) = index_file {
+        command.env("GIT_INDEX_FILE", index_file);
+    }
+
+    let output = command
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim_end().to_string())
+}
+
+/// Detect the GitHub repository for a working directory
+pub fn detect_repo(working_directory: &str) -> Result<GitHubRepo, String> {
+    let dir = Path::new(working_directory);
+    let root = git(dir, &["rev-parse", "--show-toplevel"], None)?;
+    let remote = git(dir, &["remote", "get-url", "origin"], None)?;
+
+    let captures = GITHUB_REMOTE
+        .captures(&remote)
+        .ok_or_else(|| format!("Remote is not a GitHub repository: {}", remote))?;
+
+    Ok(GitHubRepo {
+        owner: captures[1].to_string(),
+        name: captures[2].to_string(),
+        root,
+    })
+}
+
+/// Files the task wrote or edited, deduplicated in first-touched order
+pub(crate) fn changed_files(task: &StoredTask) -> Vec<String> {
+    let mut files: Vec<String> = Vec::new();
+    for message in &task.messages {
+        let is_file_tool = message
+            .tool_name
+            .as_deref()
+            .is_some_and(|name| FILE_CHANGING_TOOLS.contains(&name.to_ascii_lowercase().as_str()));
+        if !is_file_tool {
+            continue;
+        }
+        let path = message.tool_input.as_ref().and_then(|input| {
+            input
+                .get("filePath")
+                .or_else(|| input.get("file_path"))
+                .and_then(|p| p.as_str())
+        });
+        if let Some(path) = path {
+            if !files.iter().any(|f| f == path) {
+                files.push(path.to_string());
+            }
+        }
+    }
+    files
+}
+
+/// Stage the task's changed files into a throwaway index and return the tree ID
+///
+/// Relative paths are resolved against the task's working directory.
+fn write_task_tree(
+    repo: &GitHubRepo,
+    working_directory: &str,
+    files: &[String],
+) -> Result<Option<String>, String> {
+    let root = PathBuf::from(&repo.root);
+    let in_repo: Vec<&str> = files
+        .iter()
+        .filter(|f| {
+            let path = Path::new(f.as_str());
+            path.is_relative() || path.starts_with(&root)
+        })
+        .map(|f| f.as_str())
+        .collect();
+    if in_repo.is_empty() {
+        return Ok(None);
+    }
+
+    let index_file = std::env::temp_dir().join(format!("cowork-index-{}", uuid::Uuid::new_v4()));
+    let result = (|| {
+        git(&root, &["read-tree", "HEAD"], Some(&index_file))?;
+        let mut args = vec!["add", "-A", "--"];
+        args.extend(in_repo.iter().copied());
+        git(Path::new(working_directory), &args, Some(&index_file))?;
+        git(&root, &["write-tree"], Some(&index_file))
+    })();
+    let _ = std::fs::remove_file(&index_file);
+
+    result.map(Some)
+}
+
+/// Diff of the task's changes against HEAD
+fn task_diff(repo: &GitHubRepo, tree: &str) -> Result<String, String> {
+    let diff = git(Path::new(&repo.root), &["diff", "HEAD", tree], None)?;
+    if diff.chars().count() > MAX_DIFF_CHARS {
+        let truncated: String = diff.chars().take(MAX_DIFF_CHARS).collect();
+        return Ok(format!("{}\n... (diff truncated)", truncated));
+    }
+    Ok(diff)
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let truncated: String = text.chars().take(max_chars).collect();
+    format!("{}…", truncated.trim_end())
+}
+
+/// Issue/PR title, kept short enough for GitHub's list views
+fn task_title(task: &StoredTask) -> String {
+    truncate(&crate::export::task_title(task), 80)
+}
+
+/// Markdown body with the prompt, a transcript excerpt, and the diff
+fn task_body(task: &StoredTask, diff: Option<&str>) -> String {
+    let mut body = format!("## Task\n\n{}\n", task.prompt.trim());
+
+    if let Some(summary) = task.summary.as_deref().filter(|s| !s.trim().is_empty()) {
+        body.push_str(&format!("\n## Summary\n\n{}\n", summary.trim()));
+    }
+
+    let excerpt: Vec<&str> = task
+        .messages
+        .iter()
+        .filter(|m| m.msg_type == "assistant" && !m.content.trim().is_empty())
+        .map(|m| m.content.trim())
+        .collect();
+    if !excerpt.is_empty() {
+        body.push_str("\n## Transcript excerpt\n");
+        let start = excerpt.len().saturating_sub(EXCERPT_MESSAGES);
+        for content in &excerpt[start..] {
+            let quoted: Vec<String> = truncate(content, EXCERPT_MESSAGE_CHARS)
+                .lines()
+                .map(|line| format!("> {}", line))
+                .collect();
+            body.push_str(&format!("\n{}\n", quoted.join("\n")));
+        }
+    }
+
+    if let Some(diff) = diff.filter(|d| !d.is_empty()) {
+        body.push_str(&format!("\n## Changes\n\n```diff\n{}\n```\n", diff));
+    }
+
+    body.push_str("\n<sub>Created with Cowork Z</sub>\n");
+    body
+}
+
+fn github_client(token: &str) -> Result<reqwest::Client, String> {
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert(
+        reqwest::header::AUTHORIZATION,
+        format!("Bearer {}", token)
+            .parse()
+            .map_err(|_| "Invalid GitHub token".to_string())?,
+    );
+    headers.insert(
+        reqwest::header::ACCEPT,
+        reqwest::header::HeaderValue::from_static("application/vnd.github+json"),
+    );
+    reqwest::Client::builder()
+        .user_agent(concat!("cowork-z/", env!("CARGO_PKG_VERSION")))
+        .default_headers(headers)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+fn github_token() -> Result<String, String> {
+    crate::secure_storage::get_github_token()?
+        .ok_or_else(|| "GitHub token is not configured".to_string())
+}
+
+async fn post_item(
+    client: &reqwest::Client,
+    url: String,
+    body: serde_json::Value,
+) -> Result<GitHubLink, String> {
+    let response = client
+        .post(&url)
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| format!("GitHub request failed: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        return Err(format!("GitHub returned {}: {}", status, text));
+    }
+
+    let item: CreatedItem = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse GitHub response: {}", e))?;
+    Ok(GitHubLink {
+        number: item.number,
+        url: item.html_url,
+    })
+}
+
+/// Create a GitHub issue from a task
+pub async fn create_issue(task: &StoredTask, working_directory: &str) -> Result<GitHubLink, String> {
+    let token = github_token()?;
+    let repo = detect_repo(working_directory)?;
+
+    let diff = match write_task_tree(&repo, working_directory, &changed_files(task))? {
+        Some(tree) => Some(task_diff(&repo, &tree)?),
+        None => None,
+    };
+
+    let client = github_client(&token)?;
+    let link = post_item(
+        &client,
+        format!("{}/repos/{}/{}/issues", GITHUB_API_URL, repo.owner, repo.name),
+        serde_json::json!({
+            "title": task_title(task),
+            "body": task_body(task, diff.as_deref()),
+        }),
+    )
+    .await?;
+
+    tracing::info!("[GitHub] Created issue #{} for task {}", link.number, task.id);
+    Ok(link)
+}
+
+/// Push the task's changes to a new branch and open a draft pull request
+pub async fn create_draft_pr(
+    task: &StoredTask,
+    working_directory: &str,
+    base_branch: Option<String>,
+) -> Result<GitHubLink, String> {
+    let token = github_token()?;
+    let repo = detect_repo(working_directory)?;
+    let root = PathBuf::from(&repo.root);
+
+    let tree = write_task_tree(&repo, working_directory, &changed_files(task))?
+        .ok_or("Task has no file changes in this repository")?;
+    let diff = task_diff(&repo, &tree)?;
+    if diff.is_empty() {
+        return Err("Task changes are already committed".to_string());
+    }
+
+    let title = task_title(task);
+    let commit = git(&root, &["commit-tree", &tree, "-p", "HEAD", "-m", &title], None)?;
+    let short_id: String = task.id.trim_start_matches("task_").chars().take(8).collect();
+    let branch = format!("cowork/{}", short_id);
+    git(
+        &root,
+        &["push", "origin", &format!("{}:refs/heads/{}", commit, branch)],
+        None,
+    )?;
+
+    let client = github_client(&token)?;
+    let base = match base_branch {
+        Some(base) => base,
+        None => default_base_branch(&client, &repo).await?,
+    };
+
+    let link = post_item(
+        &client,
+        format!("{}/repos/{}/{}/pulls", GITHUB_API_URL, repo.owner, repo.name),
+        serde_json::json!({
+            "title": title,
+            "head": branch,
+            "base": base,
+            "body": task_body(task, None),
+            "draft": true,
+        }),
+    )
+    .await?;
+
+    tracing::info!("[GitHub] Opened draft PR #{} for task {}", link.number, task.id);
+    Ok(link)
+}
+
+/// Upstream of the current branch, or the repository's default branch
+async fn default_base_branch(client: &reqwest::Client, repo: &GitHubRepo) -> Result<String, String> {
+    if let Ok(upstream) = git(
+        Path::new(&repo.root),
+        &["rev-parse", "--abbrev-ref", "@{upstream}"],
+        None,
+    ) {
+        if let Some(branch) = upstream.strip_prefix("origin/") {
+            return Ok(branch.to_string());
+        }
+    }
+
+    let info: RepoInfo = client
+        .get(format!("{}/repos/{}/{}", GITHUB_API_URL, repo.owner, repo.name))
+        .send()
+        .await
+        .map_err(|e| format!("GitHub request failed: {}", e))?
+        .error_for_status()
+        .map_err(|e| format!("Failed to read repository info: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse repository info: {}", e))?;
+    Ok(info.default_branch)
+}
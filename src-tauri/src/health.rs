@@ -0,0 +1,256 @@
+// src-tauri/src/health.rs
+//! Startup health check
+//!
+//! Problems such as a missing CLI or a provider without a key otherwise only
+//! show when a task starts and fails. At launch `start` runs every check in
+//! the background, keeps the report for `get_startup_health`, and emits it as
+//! `startup:health`, so the UI can list what needs fixing up front. A failed
+//! check carries a fix: a short instruction, and for fixes the app can do
+//! itself an `action` the UI maps to a button.
+
+use crate::db::{self, DbState};
+use crate::{cli, secure_storage};
+use serde::Serialize;
+use std::sync::{LazyLock, Mutex};
+use tauri::{AppHandle, Manager};
+
+/// Name of the sidecar binary, as in `bundle.externalBin`
+const SIDECAR_BINARY: &str = "cowork-sidecar";
+
+/// Outcome of one check
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthCheck {
+    /// `database`, `keychain`, `sidecar`, `cli`, or `provider`
+    pub id: String,
+    pub label: String,
+    /// `ok`, `warning`, or `error`
+    pub status: String,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+    /// `install_cli`, `upgrade_cli`, `restore_backup`, or `open_provider_settings`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+}
+
+impl HealthCheck {
+    fn new(id: &str, label: &str, status: &str, detail: impl Into<String>) -> Self {
+        Self {
+            id: id.to_string(),
+            label: label.to_string(),
+            status: status.to_string(),
+            detail: detail.into(),
+            fix: None,
+            action: None,
+        }
+    }
+
+    fn ok(id: &str, label: &str, detail: impl Into<String>) -> Self {
+        Self::new(id, label, "ok", detail)
+    }
+
+    fn error(id: &str, label: &str, detail: impl Into<String>) -> Self {
+        Self::new(id, label, "error", detail)
+    }
+
+    fn fix(mut self, fix: impl Into<String>, action: Option<&str>) -> Self {
+        self.fix = Some(fix.into());
+        self.action = action.map(|a| a.to_string());
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupHealth {
+    /// Whether no check failed; warnings don't count
+    pub healthy: bool,
+    pub checks: Vec<HealthCheck>,
+    pub checked_at: String,
+}
+
+/// Report of the last run
+static REPORT: LazyLock<Mutex<Option<StartupHealth>>> = LazyLock::new(|| Mutex::new(None));
+
+fn check_database(state: &DbState) -> HealthCheck {
+    const LABEL: &str = "Database";
+    let result = state.reader.lock().map_err(|e| e.to_string()).and_then(|conn| {
+        let integrity: String = conn
+            .query_row("PRAGMA quick_check", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to check database: {}", e))?;
+        Ok((integrity, db::migrations::get_stored_version(&conn)))
+    });
+    match result {
+        Ok((integrity, _)) if integrity != "ok" => {
+            HealthCheck::error("database", LABEL, format!("Integrity check failed: {}", integrity))
+                .fix("Restore the database from a backup.", Some("restore_backup"))
+        }
+        Ok((_, version)) if version != db::migrations::CURRENT_VERSION => HealthCheck::error(
+            "database",
+            LABEL,
+            format!(
+                "Schema version {} doesn't match this app's {}",
+                version,
+                db::migrations::CURRENT_VERSION
+            ),
+        )
+        .fix("Install the latest version of the app.", None),
+        Ok((_, version)) => HealthCheck::ok("database", LABEL, format!("Schema version {}", version)),
+        Err(e) => HealthCheck::error("database", LABEL, e)
+            .fix("Restore the database from a backup.", Some("restore_backup")),
+    }
+}
+
+fn check_keychain() -> HealthCheck {
+    const LABEL: &str = "Keychain";
+    match secure_storage::check_access() {
+        Ok(()) => HealthCheck::ok("keychain", LABEL, "Available"),
+        Err(e) => HealthCheck::error("keychain", LABEL, e).fix(
+            if cfg!(target_os = "linux") {
+                "Start a Secret Service provider such as GNOME Keyring, then restart the app."
+            } else {
+                "Unlock the system keychain, then restart the app."
+            },
+            None,
+        ),
+    }
+}
+
+fn check_sidecar() -> HealthCheck {
+    const LABEL: &str = "Sidecar";
+    // Sidecars are resolved next to the app's executable
+    let path = std::env::current_exe().ok().and_then(|exe| {
+        let name = format!("{}{}", SIDECAR_BINARY, std::env::consts::EXE_SUFFIX);
+        Some(exe.parent()?.join(name))
+    });
+    match path {
+        Some(path) if path.is_file() => {
+            HealthCheck::ok("sidecar", LABEL, path.to_string_lossy().to_string())
+        }
+        Some(path) => {
+            HealthCheck::error("sidecar", LABEL, format!("Not found at {}", path.to_string_lossy()))
+                .fix("Reinstall the app.", None)
+        }
+        None => HealthCheck::error("sidecar", LABEL, "Couldn't locate the app's executable")
+            .fix("Reinstall the app.", None),
+    }
+}
+
+fn check_cli(app: &AppHandle) -> HealthCheck {
+    const LABEL: &str = "OpenCode CLI";
+    let Some(resolved) = cli::resolve(app) else {
+        return HealthCheck::error("cli", LABEL, "Not installed")
+            .fix(format!("Run `{}`.", cli::INSTALL_COMMAND), Some("install_cli"));
+    };
+    let source = if resolved.bundled { "bundled" } else { "system" };
+    match resolved.version {
+        Some(version) if cli::is_outdated(&version) => HealthCheck::error(
+            "cli",
+            LABEL,
+            format!("Version {} is older than {}", version, cli::MIN_VERSION),
+        )
+        .fix(format!("Run `{}`.", cli::UPGRADE_COMMAND), Some("upgrade_cli")),
+        Some(version) => HealthCheck::ok("cli", LABEL, format!("Version {} ({})", version, source)),
+        None => HealthCheck::new(
+            "cli",
+            LABEL,
+            "warning",
+            format!("{} doesn't report its version", resolved.path.to_string_lossy()),
+        ),
+    }
+}
+
+fn check_provider(state: &DbState) -> HealthCheck {
+    const LABEL: &str = "Provider";
+    let settings = match state.reader.lock() {
+        Ok(conn) => db::providers::get_provider_settings(&conn),
+        Err(e) => return HealthCheck::error("provider", LABEL, e.to_string()),
+    };
+    let open_settings = |check: HealthCheck, fix: &str| {
+        check.fix(fix, Some("open_provider_settings"))
+    };
+    let Some(provider_id) = settings.active_provider_id else {
+        return open_settings(
+            HealthCheck::error("provider", LABEL, "No provider selected"),
+            "Connect a provider and select a model.",
+        );
+    };
+    let Some(provider) = settings.connected_providers.get(&provider_id) else {
+        return open_settings(
+            HealthCheck::error("provider", LABEL, format!("{} is not connected", provider_id)),
+            "Connect the provider again.",
+        );
+    };
+    if provider.connection_status != "connected" {
+        return open_settings(
+            HealthCheck::error(
+                "provider",
+                LABEL,
+                format!("{} is {}", provider_id, provider.connection_status),
+            ),
+            "Connect the provider again.",
+        );
+    }
+    if provider.credentials.credentials_type == "api_key" {
+        match secure_storage::has_api_key(&provider_id) {
+            Ok(true) => {}
+            Ok(false) => {
+                return open_settings(
+                    HealthCheck::error(
+                        "provider",
+                        LABEL,
+                        format!("No API key stored for {}", provider_id),
+                    ),
+                    "Enter the provider's API key again.",
+                )
+            }
+            // Reported by the keychain check
+            Err(e) => return HealthCheck::error("provider", LABEL, e),
+        }
+    }
+    match &provider.selected_model_id {
+        Some(model) => HealthCheck::ok("provider", LABEL, format!("{} ({})", provider_id, model)),
+        None => open_settings(
+            HealthCheck::error("provider", LABEL, format!("No model selected for {}", provider_id)),
+            "Select a model.",
+        ),
+    }
+}
+
+/// Run every check, keeping the report for `get_startup_health`
+pub fn check(app: &AppHandle) -> StartupHealth {
+    let db_state = app.state::<DbState>();
+    let checks = vec![
+        check_database(&db_state),
+        check_keychain(),
+        check_sidecar(),
+        check_cli(app),
+        check_provider(&db_state),
+    ];
+    let report = StartupHealth {
+        healthy: checks.iter().all(|check| check.status != "error"),
+        checks,
+        checked_at: chrono::Utc::now().to_rfc3339(),
+    };
+    for check in report.checks.iter().filter(|check| check.status != "ok") {
+        tracing::warn!("[Health] {} {}: {}", check.id, check.status, check.detail);
+    }
+    if let Ok(mut last) = REPORT.lock() {
+        *last = Some(report.clone());
+    }
+    report
+}
+
+/// Report of the last run, if it has finished
+pub fn last_report() -> Option<StartupHealth> {
+    REPORT.lock().ok()?.clone()
+}
+
+/// Run the checks in the background and emit the report as `startup:health`
+pub fn start(app: AppHandle) {
+    std::thread::spawn(move || {
+        let report = check(&app);
+        crate::sidecar::forward_event(&app, "startup:health", serde_json::json!(report));
+    });
+}
@@ -0,0 +1,311 @@
+// src-tauri/src/hooks.rs
+//! Per-workspace task hooks
+//!
+//! Workspaces can configure shell commands that run in the workspace directory
+//! before a task is sent to the agent (e.g. `git stash`, environment setup) and
+//! after it completes successfully (e.g. tests, formatters). Each command's
+//! output is stored as a system message on the task and emitted as `task:hook`.
+//! A failing hook is logged and reported with `success: false`; it never stops
+//! the task. Hooks of remote workspaces run on the SSH host.
+
+use crate::db::tasks::{self, TaskMessageInput};
+use crate::db::workspaces::{self, WorkspaceHooks};
+use crate::db::DbState;
+use crate::remote::{self, RemotePayload};
+use serde::Serialize;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+/// Longest a single hook may run before it is killed
+const HOOK_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// How long output is still read after a hook is killed, in case something it
+/// started outlived it and holds its output open
+const KILLED_OUTPUT_GRACE: Duration = Duration::from_secs(2);
+
+/// Output beyond this many characters is truncated in the task message
+const MAX_OUTPUT_CHARS: usize = 20_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookStage {
+    PreTask,
+    PostTask,
+}
+
+impl HookStage {
+    fn as_str(self) -> &'static str {
+        match self {
+            HookStage::PreTask => "pre-task",
+            HookStage::PostTask => "post-task",
+        }
+    }
+
+    fn commands(self, hooks: &WorkspaceHooks) -> &[String] {
+        match self {
+            HookStage::PreTask => &hooks.pre_task,
+            HookStage::PostTask => &hooks.post_task,
+        }
+    }
+}
+
+/// Result of running one hook command
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookResult {
+    pub stage: &'static str,
+    pub command: String,
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub output: String,
+}
+
+fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
+fn read_pipe<R: Read + Send + 'static>(pipe: Option<R>) -> mpsc::Receiver<String> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        let _ = tx.send(String::from_utf8_lossy(&buf).to_string());
+    });
+    rx
+}
+
+/// Kill a hook and the processes it started
+///
+/// On Unix the hook leads its own process group, so the whole group is killed.
+fn kill_tree(child: &mut Child) {
+    let pid = child.id().to_string();
+    let killed = if cfg!(windows) {
+        Command::new("taskkill").args(["/T", "/F", "/PID", &pid]).status()
+    } else {
+        Command::new("kill").args(["-KILL", &format!("-{}", pid)]).status()
+    };
+    if !killed.is_ok_and(|status| status.success()) {
+        let _ = child.kill();
+    }
+    let _ = child.wait();
+}
+
+/// Run a command in `dir`, or in the remote target's directory, capturing stdout and stderr
+fn run_command(
+    stage: HookStage,
+    command: &str,
+    dir: &Path,
+    remote: Option<&RemotePayload>,
+) -> HookResult {
+    let mut result = HookResult {
+        stage: stage.as_str(),
+        command: command.to_string(),
+        success: false,
+        exit_code: None,
+        timed_out: false,
+        output: String::new(),
+    };
+
+    let mut cmd = match remote {
+        Some(target) => remote::command(target, command),
+        None => {
+            let mut cmd = shell_command(command);
+            cmd.current_dir(dir);
+            cmd
+        }
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+    let mut child = match cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            result.output = format!("Failed to run hook: {}", e);
+            return result;
+        }
+    };
+
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let deadline = Instant::now() + HOOK_TIMEOUT;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) if Instant::now() >= deadline => {
+                kill_tree(&mut child);
+                result.timed_out = true;
+                break None;
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(100)),
+            Err(e) => {
+                result.output = format!("Failed to wait for hook: {}", e);
+                return result;
+            }
+        }
+    };
+
+    // Something a killed hook started may still hold its output open
+    let collect = |rx: mpsc::Receiver<String>| {
+        if result.timed_out {
+            rx.recv_timeout(KILLED_OUTPUT_GRACE).unwrap_or_default()
+        } else {
+            rx.recv().unwrap_or_default()
+        }
+    };
+    let mut output = collect(stdout);
+    let stderr = collect(stderr);
+    if !stderr.is_empty() {
+        if !output.is_empty() && !output.ends_with('\n') {
+            output.push('\n');
+        }
+        output.push_str(&stderr);
+    }
+    if output.chars().count() > MAX_OUTPUT_CHARS {
+        output = output.chars().take(MAX_OUTPUT_CHARS).collect();
+        output.push_str("\n… (output truncated)");
+    }
+
+    result.output = output;
+    result.exit_code = status.and_then(|s| s.code());
+    result.success = status.is_some_and(|s| s.success());
+    result
+}
+
+fn hook_message(result: &HookResult) -> String {
+    let outcome = if result.success {
+        "succeeded".to_string()
+    } else if result.timed_out {
+        format!("timed out after {}s", HOOK_TIMEOUT.as_secs())
+    } else {
+        match result.exit_code {
+            Some(code) => format!("failed with exit code {}", code),
+            None => "failed".to_string(),
+        }
+    };
+
+    let mut content = format!("Ran {} hook `{}`: {}", result.stage, result.command, outcome);
+    let output = result.output.trim_end();
+    if !output.is_empty() {
+        content.push_str(&format!("\n\n```\n{}\n```", output));
+    }
+    content
+}
+
+/// Hooks, ID, and directory of the task's workspace, if it has one
+fn workspace_hooks(
+    conn: &rusqlite::Connection,
+    task_id: &str,
+) -> Option<(WorkspaceHooks, String, String)> {
+    let workspace_id = tasks::get_task(conn, task_id)?.workspace_id?;
+    let dir = tasks::get_working_directory(conn, task_id).ok()??;
+    let hooks = workspaces::get_hooks(conn, &workspace_id).ok()?;
+    Some((hooks, workspace_id, dir))
+}
+
+/// Run the task's workspace hooks for `stage`, blocking until all have finished
+///
+/// Hooks don't run in safe mode.
+pub fn run_hooks(app: &AppHandle, task_id: &str, stage: HookStage) {
+    if crate::safe_mode::enabled() {
+        return;
+    }
+    let Some(db_state) = app.try_state::<DbState>() else {
+        return;
+    };
+    let Some((hooks, workspace_id, dir)) = db_state
+        .conn
+        .lock()
+        .ok()
+        .and_then(|conn| workspace_hooks(&conn, task_id))
+    else {
+        return;
+    };
+
+    let commands: Vec<&String> = stage
+        .commands(&hooks)
+        .iter()
+        .filter(|c| !c.trim().is_empty())
+        .collect();
+    if commands.is_empty() {
+        return;
+    }
+    let remote = match remote::workspace_target(app, Some(&workspace_id)) {
+        Ok(remote) => remote,
+        Err(e) => {
+            tracing::warn!("[Hooks] Skipping {} hooks for task {}: {}", stage.as_str(), task_id, e);
+            return;
+        }
+    };
+
+    for command in commands {
+        tracing::info!("[Hooks] Running {} hook for task {}: {}", stage.as_str(), task_id, command);
+        let result = run_command(stage, command, Path::new(&dir), remote.as_ref());
+        if !result.success {
+            tracing::warn!(
+                "[Hooks] {} hook `{}` failed for task {}",
+                stage.as_str(),
+                command,
+                task_id
+            );
+        }
+
+        let message = TaskMessageInput {
+            id: format!("hook_{}", uuid::Uuid::new_v4()),
+            msg_type: "system".to_string(),
+            content: hook_message(&result),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            tool_name: None,
+            tool_input: None,
+            attachments: None,
+            note: None,
+            origin_id: None,
+        };
+        let saved = db_state
+            .conn
+            .lock()
+            .map_err(|e| e.to_string())
+            .and_then(|conn| tasks::add_task_message(&conn, task_id, &message));
+        if let Err(e) = saved {
+            tracing::warn!("[Hooks] {}", e);
+        }
+
+        crate::sidecar::forward_event(
+            app,
+            "task:hook",
+            serde_json::json!({
+                "taskId": task_id,
+                "payload": {
+                    "result": result,
+                    "message": {
+                        "id": message.id,
+                        "type": message.msg_type,
+                        "content": message.content,
+                        "timestamp": message.timestamp,
+                    },
+                },
+            }),
+        );
+    }
+}
@@ -0,0 +1,185 @@
+// src-tauri/src/importers/claude_code.rs
+//! Claude Code session importer
+//!
+//! Claude Code keeps one JSONL file per session under
+//! `~/.claude/projects/<encoded-project-path>/`. Each line is an event with a
+//! `type` (`user`, `assistant`, `summary`, ...), the session's `cwd`, and for
+//! conversation turns a `message` whose `content` is either a string or a list
+//! of blocks (`text`, `tool_use`, `tool_result`, `thinking`).
+
+use super::{ImportReport, ImportedSession};
+use crate::db::tasks::{StoredTask, StoredTaskMessage};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Prefix of task IDs created by this importer
+const TASK_ID_PREFIX: &str = "task_claude_";
+
+/// Collect every session file below `root`
+fn session_files(root: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            session_files(&path, files);
+        } else if path.extension().and_then(|e| e.to_str()) == Some("jsonl") {
+            files.push(path);
+        }
+    }
+}
+
+fn str_field(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(|v| v.as_str()).map(|s| s.to_string())
+}
+
+/// Convert one session file, or `None` if it holds no user prompt
+fn convert_session(path: &Path) -> Result<Option<ImportedSession>, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+
+    let mut session_id = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut cwd: Option<String> = None;
+    let mut summary: Option<String> = None;
+    let mut messages: Vec<StoredTaskMessage> = Vec::new();
+
+    for line in contents.lines().filter(|l| !l.trim().is_empty()) {
+        let Ok(event) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        if let Some(id) = str_field(&event, "sessionId") {
+            session_id = id;
+        }
+        if cwd.is_none() {
+            cwd = str_field(&event, "cwd");
+        }
+
+        let event_type = event.get("type").and_then(|t| t.as_str()).unwrap_or("");
+        if event_type == "summary" {
+            summary = str_field(&event, "summary").or(summary);
+            continue;
+        }
+        if event_type != "user" && event_type != "assistant" {
+            continue;
+        }
+        // Sub-agent turns and injected meta messages are not part of the conversation
+        let is_flagged = |key: &str| event.get(key).and_then(|v| v.as_bool()).unwrap_or(false);
+        if is_flagged("isSidechain") || is_flagged("isMeta") {
+            continue;
+        }
+
+        let uuid = str_field(&event, "uuid").unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let timestamp = str_field(&event, "timestamp").unwrap_or_default();
+        let content = event.get("message").and_then(|m| m.get("content"));
+
+        match content {
+            Some(Value::String(text)) if !text.trim().is_empty() => {
+                messages.push(StoredTaskMessage {
+                    id: uuid,
+                    msg_type: event_type.to_string(),
+                    content: text.clone(),
+                    timestamp,
+                    tool_name: None,
+                    tool_input: None,
+                    attachments: None,
+                    note: None,
+                });
+            }
+            Some(Value::Array(blocks)) => {
+                for (index, block) in blocks.iter().enumerate() {
+                    let id = format!("{}-{}", uuid, index);
+                    match block.get("type").and_then(|t| t.as_str()) {
+                        Some("text") => {
+                            let text = str_field(block, "text").unwrap_or_default();
+                            if text.trim().is_empty() {
+                                continue;
+                            }
+                            messages.push(StoredTaskMessage {
+                                id,
+                                msg_type: event_type.to_string(),
+                                content: text,
+                                timestamp: timestamp.clone(),
+                                tool_name: None,
+                                tool_input: None,
+                                attachments: None,
+                                note: None,
+                            });
+                        }
+                        Some("tool_use") => messages.push(StoredTaskMessage {
+                            id,
+                            msg_type: "tool".to_string(),
+                            content: String::new(),
+                            timestamp: timestamp.clone(),
+                            tool_name: str_field(block, "name"),
+                            tool_input: block.get("input").cloned(),
+                            attachments: None,
+                            note: None,
+                        }),
+                        // Tool results and thinking are not stored as messages in the app
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let Some(prompt) = messages
+        .iter()
+        .find(|m| m.msg_type == "user")
+        .map(|m| m.content.clone())
+    else {
+        return Ok(None);
+    };
+    let created_at = messages
+        .first()
+        .map(|m| m.timestamp.clone())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+    let completed_at = messages.last().map(|m| m.timestamp.clone()).filter(|t| !t.is_empty());
+
+    Ok(Some(ImportedSession {
+        task: StoredTask {
+            id: format!("{}{}", TASK_ID_PREFIX, session_id),
+            prompt,
+            summary,
+            title: None,
+            status: "completed".to_string(),
+            messages,
+            // Claude Code sessions cannot be resumed through OpenCode
+            session_id: None,
+            started_at: Some(created_at.clone()),
+            created_at,
+            updated_at: completed_at.clone(),
+            completed_at,
+            workspace_id: None,
+            allow_web_search: None,
+        },
+        working_directory: cwd,
+    }))
+}
+
+/// Read every session below `root` (normally `~/.claude/projects`)
+pub fn read_sessions(root: &Path, report: &mut ImportReport) -> Vec<ImportedSession> {
+    let mut files = Vec::new();
+    session_files(root, &mut files);
+
+    let mut sessions = Vec::new();
+    for file in files {
+        match convert_session(&file) {
+            Ok(Some(session)) => sessions.push(session),
+            Ok(None) => report.skipped += 1,
+            Err(e) => {
+                tracing::warn!("[Import] {}", e);
+                report.failed += 1;
+            }
+        }
+    }
+
+    tracing::info!("[Import] Read {} Claude Code sessions from {:?}", sessions.len(), root);
+    sessions
+}
@@ -0,0 +1,89 @@
+// src-tauri/src/importers/mod.rs
+//! Importers for history created by other coding agents
+//!
+//! Each importer converts another tool's on-disk sessions into `StoredTask`s
+//! with deterministic IDs, so importing the same history twice is a no-op.
+
+pub mod claude_code;
+pub mod opencode;
+
+use crate::db::tasks::{self, StoredTask};
+use crate::workspace_detect::{self, DetectedRepo};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Outcome of an import run
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub imported: usize,
+    /// Sessions already imported or without any prompt
+    pub skipped: usize,
+    /// Sessions that could not be read or saved
+    pub failed: usize,
+}
+
+/// A converted session and the directory it ran in
+pub struct ImportedSession {
+    pub task: StoredTask,
+    pub working_directory: Option<String>,
+}
+
+/// Git repositories of the sessions' directories, found before `save_sessions` takes the lock
+pub async fn detect_repos(sessions: &[ImportedSession]) -> HashMap<String, DetectedRepo> {
+    let mut repos = HashMap::new();
+    for dir in sessions.iter().filter_map(|s| s.working_directory.as_deref()) {
+        if repos.contains_key(dir) {
+            continue;
+        }
+        if let Some(detected) = workspace_detect::detect(dir).await {
+            repos.insert(dir.to_string(), detected);
+        }
+    }
+    repos
+}
+
+/// Save converted sessions, attaching each to the workspace for its directory
+pub fn save_sessions(
+    conn: &Connection,
+    sessions: Vec<ImportedSession>,
+    repos: &HashMap<String, DetectedRepo>,
+    report: &mut ImportReport,
+) {
+    for session in sessions {
+        // Sessions started from the app are already in history under their own task ID
+        let known_session = session
+            .task
+            .session_id
+            .as_deref()
+            .is_some_and(|sid| tasks::has_task_for_session(conn, sid));
+        if known_session || tasks::get_task(conn, &session.task.id).is_some() {
+            report.skipped += 1;
+            continue;
+        }
+
+        let mut task = session.task;
+        let dir = session.working_directory.as_deref();
+        if let Some(dir) = dir {
+            match workspace_detect::resolve(conn, dir, repos.get(dir)) {
+                Ok(workspace) => task.workspace_id = Some(workspace.id),
+                Err(e) => tracing::warn!("[Import] {}", e),
+            }
+        }
+
+        let saved = tasks::save_stored_task(conn, &task).and_then(|()| match dir {
+            Some(dir) if task.workspace_id.is_some() => {
+                tasks::set_working_directory(conn, &task.id, dir)
+            }
+            _ => Ok(()),
+        });
+        match saved {
+            Ok(()) => report.imported += 1,
+            Err(e) => {
+                tracing::warn!("[Import] Failed to save {}: {}", task.id, e);
+                report.failed += 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,178 @@
+// src-tauri/src/importers/opencode.rs
+//! OpenCode CLI history importer
+//!
+//! OpenCode stores sessions as individual JSON files under
+//! `~/.local/share/opencode/storage`:
+//! - `session/<projectID>/<sessionID>.json` - title, directory, timestamps
+//! - `message/<sessionID>/<messageID>.json` - role and timestamps
+//! - `part/<messageID>/<partID>.json` - `text` and `tool` content
+//!
+//! Imported tasks keep the OpenCode session ID, so they can be continued
+//! with `resume_session` like tasks started from the app.
+
+use super::{ImportReport, ImportedSession};
+use crate::db::tasks::{StoredTask, StoredTaskMessage};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Prefix of task IDs created by this importer
+const TASK_ID_PREFIX: &str = "task_opencode_";
+
+/// Parse every JSON file directly inside `dir`
+fn read_json_files(dir: &Path) -> Vec<Value> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("json"))
+        .filter_map(|p| std::fs::read_to_string(p).ok())
+        .filter_map(|s| serde_json::from_str(&s).ok())
+        .collect()
+}
+
+fn str_field(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(|v| v.as_str()).map(|s| s.to_string())
+}
+
+/// OpenCode timestamps are milliseconds since the epoch
+fn time_field(value: &Value, key: &str) -> Option<String> {
+    let millis = value.get("time")?.get(key)?.as_i64()?;
+    chrono::DateTime::from_timestamp_millis(millis).map(|t| t.to_rfc3339())
+}
+
+/// Session files across all projects
+fn session_files(root: &Path) -> Vec<PathBuf> {
+    let Ok(projects) = std::fs::read_dir(root.join("session")) else {
+        return Vec::new();
+    };
+    projects
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .filter_map(|p| std::fs::read_dir(p).ok())
+        .flat_map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()))
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("json"))
+        .collect()
+}
+
+/// Messages of a session with their parts, in chronological order
+fn session_messages(root: &Path, session_id: &str) -> Vec<StoredTaskMessage> {
+    let mut infos = read_json_files(&root.join("message").join(session_id));
+    infos.sort_by_key(|m| m.get("time").and_then(|t| t.get("created")).and_then(|c| c.as_i64()));
+
+    let mut messages = Vec::new();
+    for info in infos {
+        let Some(message_id) = str_field(&info, "id") else {
+            continue;
+        };
+        let role = str_field(&info, "role").unwrap_or_default();
+        let timestamp = time_field(&info, "created").unwrap_or_default();
+
+        let mut parts = read_json_files(&root.join("part").join(&message_id));
+        parts.sort_by_key(|p| str_field(p, "id"));
+
+        for part in parts {
+            let Some(part_id) = str_field(&part, "id") else {
+                continue;
+            };
+            // Synthetic parts are injected context, not something the user typed
+            if part.get("synthetic").and_then(|s| s.as_bool()).unwrap_or(false) {
+                continue;
+            }
+            match part.get("type").and_then(|t| t.as_str()) {
+                Some("text") => {
+                    let text = str_field(&part, "text").unwrap_or_default();
+                    if text.trim().is_empty() || (role != "user" && role != "assistant") {
+                        continue;
+                    }
+                    messages.push(StoredTaskMessage {
+                        id: part_id,
+                        msg_type: role.clone(),
+                        content: text,
+                        timestamp: timestamp.clone(),
+                        tool_name: None,
+                        tool_input: None,
+                        attachments: None,
+                        note: None,
+                    });
+                }
+                Some("tool") => messages.push(StoredTaskMessage {
+                    id: part_id,
+                    msg_type: "tool".to_string(),
+                    content: String::new(),
+                    timestamp: timestamp.clone(),
+                    tool_name: str_field(&part, "tool"),
+                    tool_input: part.get("state").and_then(|s| s.get("input")).cloned(),
+                    attachments: None,
+                    note: None,
+                }),
+                _ => {}
+            }
+        }
+    }
+    messages
+}
+
+/// Convert one session, or `None` if it is a sub-agent session or has no prompt
+fn convert_session(root: &Path, path: &Path) -> Result<Option<ImportedSession>, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    let session: Value = serde_json::from_str(&contents)
+        .map_err(|e| format!("Failed to parse {:?}: {}", path, e))?;
+
+    let session_id = str_field(&session, "id").ok_or_else(|| format!("Session without ID: {:?}", path))?;
+    if session.get("parentID").is_some_and(|p| !p.is_null()) {
+        return Ok(None);
+    }
+
+    let messages = session_messages(root, &session_id);
+    let Some(prompt) = messages
+        .iter()
+        .find(|m| m.msg_type == "user")
+        .map(|m| m.content.clone())
+    else {
+        return Ok(None);
+    };
+
+    let created_at = time_field(&session, "created").unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+    let completed_at = time_field(&session, "updated");
+
+    Ok(Some(ImportedSession {
+        task: StoredTask {
+            id: format!("{}{}", TASK_ID_PREFIX, session_id),
+            prompt,
+            summary: str_field(&session, "title").filter(|t| !t.trim().is_empty()),
+            title: str_field(&session, "title").filter(|t| !t.trim().is_empty()),
+            status: "completed".to_string(),
+            messages,
+            session_id: Some(session_id),
+            started_at: Some(created_at.clone()),
+            created_at,
+            updated_at: completed_at.clone(),
+            completed_at,
+            workspace_id: None,
+            allow_web_search: None,
+        },
+        working_directory: str_field(&session, "directory"),
+    }))
+}
+
+/// Read every top-level session below `root` (normally `~/.local/share/opencode/storage`)
+pub fn read_sessions(root: &Path, report: &mut ImportReport) -> Vec<ImportedSession> {
+    let mut sessions = Vec::new();
+    for file in session_files(root) {
+        match convert_session(root, &file) {
+            Ok(Some(session)) => sessions.push(session),
+            Ok(None) => report.skipped += 1,
+            Err(e) => {
+                tracing::warn!("[Import] {}", e);
+                report.failed += 1;
+            }
+        }
+    }
+
+    tracing::info!("[Import] Read {} OpenCode sessions from {:?}", sessions.len(), root);
+    sessions
+}
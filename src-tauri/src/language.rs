@@ -0,0 +1,246 @@
+// src-tauri/src/language.rs
+//! Language detection for code
+//!
+//! A fence's info string names the language in many spellings ("js", "py",
+//! "main.rs"), and agents often leave it out. `detect` turns the info string
+//! into one canonical lowercase name, e.g. "javascript", and only when there
+//! is none looks at the code itself: a shebang or telltale first line, then
+//! the language whose markers match the most lines.
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Spellings of info strings and file extensions, with their canonical name
+const ALIASES: &[(&str, &str)] = &[
+    ("js", "javascript"),
+    ("mjs", "javascript"),
+    ("cjs", "javascript"),
+    ("node", "javascript"),
+    ("ts", "typescript"),
+    ("mts", "typescript"),
+    ("cts", "typescript"),
+    ("py", "python"),
+    ("py3", "python"),
+    ("python3", "python"),
+    ("rs", "rust"),
+    ("sh", "bash"),
+    ("shell", "bash"),
+    ("zsh", "bash"),
+    ("console", "bash"),
+    ("shellscript", "bash"),
+    ("yml", "yaml"),
+    ("md", "markdown"),
+    ("golang", "go"),
+    ("c++", "cpp"),
+    ("cc", "cpp"),
+    ("cxx", "cpp"),
+    ("hpp", "cpp"),
+    ("h", "c"),
+    ("cs", "csharp"),
+    ("c#", "csharp"),
+    ("rb", "ruby"),
+    ("kt", "kotlin"),
+    ("kts", "kotlin"),
+    ("patch", "diff"),
+    ("docker", "dockerfile"),
+    ("htm", "html"),
+    ("jsonc", "json"),
+    ("json5", "json"),
+    ("ps1", "powershell"),
+    ("pwsh", "powershell"),
+];
+
+/// Info strings that mean the block is not code
+const PLAIN: &[&str] = &["text", "txt", "plain", "plaintext", "output"];
+
+/// Lines that count towards a language, and how many matching lines it needs
+struct Markers {
+    language: &'static str,
+    /// A line counts if any of these match it
+    patterns: &'static [&'static str],
+    min_lines: usize,
+}
+
+/// Candidates in order of preference when they match as many lines
+const MARKERS: &[Markers] = &[
+    Markers {
+        language: "rust",
+        patterns: &[
+            r"^\s*(pub(\(crate\))? )?(fn|impl|struct|enum|trait|mod|use) \w",
+            r"\blet mut ",
+            r"&mut ",
+            r"\)\s*->\s*[\w&(]",
+        ],
+        min_lines: 2,
+    },
+    Markers {
+        language: "python",
+        patterns: &[
+            r"^\s*def \w+\(.*\):",
+            r"^\s*class \w+.*:$",
+            r"^\s*from [\w.]+ import ",
+            r"^\s*(elif |if __name__)",
+            r"\bself\.",
+        ],
+        min_lines: 2,
+    },
+    Markers {
+        language: "typescript",
+        patterns: &[
+            r"^\s*(export )?(interface|type) \w+",
+            r": (string|number|boolean)\b",
+            r"^\s*import .* from '",
+        ],
+        min_lines: 2,
+    },
+    Markers {
+        language: "javascript",
+        patterns: &[
+            r"^\s*(const|let|var) \w+ = ",
+            r"=> ",
+            r"require\(|module\.exports|console\.log\(",
+            r"^\s*function \w+\(",
+        ],
+        min_lines: 2,
+    },
+    Markers {
+        language: "go",
+        patterns: &[
+            r"^\s*package \w+$",
+            r"^\s*func (\(\w+ \*?\w+\) )?\w+\(",
+            r":= ",
+            r"\bfmt\.",
+        ],
+        min_lines: 2,
+    },
+    Markers {
+        language: "java",
+        patterns: &[
+            r"^\s*(public|private|protected) (static )?(class|void|final|[A-Z]\w*)\b",
+            r"System\.out\.",
+            r"^\s*package [\w.]+;",
+        ],
+        min_lines: 2,
+    },
+    Markers {
+        language: "sql",
+        patterns: &[
+            r"(?i:^\s*(select .* from|insert into|update \w+ set|delete from)\b)",
+            r"(?i:^\s*(create|alter|drop) (table|index|view)\b)",
+        ],
+        min_lines: 1,
+    },
+    Markers {
+        language: "bash",
+        patterns: &[
+            r"^\s*(\$ |sudo |cd |export \w+=|echo |mkdir |curl )",
+            r"^\s*(npm|pnpm|yarn|cargo|git|pip|brew) \w",
+        ],
+        min_lines: 1,
+    },
+    Markers {
+        language: "css",
+        patterns: &[
+            r"^\s*[.#]?[\w-]+( [\w.#-]+)* \{$",
+            r"^\s*[\w-]+: [^;]+;$",
+        ],
+        min_lines: 2,
+    },
+    Markers {
+        language: "yaml",
+        patterns: &[r"^\s*(- )?[\w.-]+:( |$)"],
+        min_lines: 3,
+    },
+];
+
+static MARKER_PATTERNS: LazyLock<Vec<(&'static Markers, Regex)>> = LazyLock::new(|| {
+    MARKERS
+        .iter()
+        .map(|markers| {
+            let pattern = markers.patterns.join("|");
+            (markers, Regex::new(&pattern).expect("valid marker pattern"))
+        })
+        .collect()
+});
+
+/// Canonical name of a fence's info string, `None` for plain text
+///
+/// Pandoc attributes (`{.python}`) and file names (`src/main.rs`) are
+/// understood; names that aren't aliases are kept as written, in lowercase.
+pub fn normalize(info: &str) -> Option<String> {
+    let token = info
+        .split([' ', ',', '{'])
+        .find(|part| !part.is_empty())
+        .unwrap_or(info)
+        .trim_matches(['{', '}', '.'])
+        .to_ascii_lowercase();
+    if token.is_empty() || PLAIN.contains(&token.as_str()) {
+        return None;
+    }
+    // A file name: go by its extension
+    let token = match token.rsplit_once('.') {
+        Some((_, extension)) if !extension.is_empty() => extension.to_string(),
+        _ => token,
+    };
+    let canonical = ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == token)
+        .map_or(token.as_str(), |(_, canonical)| canonical);
+    Some(canonical.to_string())
+}
+
+/// Language named by the first line alone
+fn first_line_language(code: &str) -> Option<&'static str> {
+    let first = code.lines().find(|line| !line.trim().is_empty())?.trim();
+    if let Some(interpreter) = first.strip_prefix("#!") {
+        let name = interpreter.rsplit('/').next().unwrap_or(interpreter);
+        let name = name.strip_prefix("env ").unwrap_or(name).trim();
+        return match name.split_whitespace().next()? {
+            "bash" | "sh" | "zsh" => Some("bash"),
+            "node" => Some("javascript"),
+            name if name.starts_with("python") => Some("python"),
+            "ruby" => Some("ruby"),
+            _ => None,
+        };
+    }
+    let lower = first.to_ascii_lowercase();
+    match () {
+        _ if lower.starts_with("<?php") => Some("php"),
+        _ if lower.starts_with("<?xml") => Some("xml"),
+        _ if lower.starts_with("<!doctype html") || lower.starts_with("<html") => Some("html"),
+        _ if first.starts_with("diff --git") || first.starts_with("--- a/") => Some("diff"),
+        _ if first.starts_with("FROM ") => Some("dockerfile"),
+        _ => None,
+    }
+}
+
+/// Guess the language of code without an info string
+fn guess(code: &str) -> Option<&'static str> {
+    if let Some(language) = first_line_language(code) {
+        return Some(language);
+    }
+    let trimmed = code.trim();
+    let is_json = || serde_json::from_str::<serde_json::Value>(trimmed).is_ok();
+    if trimmed.starts_with(['{', '[']) && is_json() {
+        return Some("json");
+    }
+    MARKER_PATTERNS
+        .iter()
+        .map(|(markers, pattern)| {
+            let lines = code.lines().filter(|line| pattern.is_match(line)).count();
+            (markers, lines)
+        })
+        .filter(|(markers, lines)| *lines >= markers.min_lines)
+        // The last of equal maxima is kept, so the preferred one has to come last
+        .rev()
+        .max_by_key(|(_, lines)| *lines)
+        .map(|(markers, _)| markers.language)
+}
+
+/// Language of a code block from its info string, or from its content without one
+pub fn detect(info: Option<&str>, code: &str) -> Option<String> {
+    match info.map(str::trim).filter(|info| !info.is_empty()) {
+        Some(info) => normalize(info),
+        None => guess(code).map(str::to_string),
+    }
+}
@@ -1,12 +1,64 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use tauri::{Manager, State};
-
+use tauri::{Emitter, Manager, State};
+
+mod abort;
+mod api_server;
+mod attachments;
+mod backup;
+mod budget;
+mod checkpoint;
+mod citations;
+mod cli;
+mod crash;
 mod db;
+mod diff;
+mod diagnostics;
+mod encrypted_sync;
+mod events;
+mod export;
+#[cfg(any(debug_assertions, feature = "e2e"))]
+mod fixtures;
+mod github;
+mod health;
+mod hooks;
+mod importers;
+mod language;
+mod remote;
+mod logging;
+mod message_buffer;
+mod metrics;
+mod observer;
+mod postprocess;
+mod profile;
+mod project_index;
+mod provider_check;
+mod provider_detect;
+mod recovery;
+mod reminders;
+mod safe_mode;
 mod secure_storage;
+mod session_context;
+mod session_lock;
+mod session_sync;
+mod sandbox;
 mod sidecar;
-
+mod slash_commands;
+mod structured_output;
+mod summaries;
+mod sync;
+mod task_env;
+mod task_progress;
+mod task_wait;
+mod templates;
+mod terminal;
+mod window_state;
+mod workspace_detect;
+
+use api_server::ApiServerState;
 use db::DbState;
+use logging::LoggingState;
+use message_buffer::MessageBufferState;
 use sidecar::SidecarState;
 
 // ============================================================================
@@ -26,6 +78,8 @@ pub struct Task {
     pub session_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
     pub created_at: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub updated_at: Option<String>,
@@ -33,12 +87,18 @@ pub struct Task {
     pub completed_at: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub started_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskMessage {
     pub id: String,
+    /// ID of the sidecar event the message came from; a message with an origin
+    /// already stored for the task is not inserted again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin_id: Option<String>,
     #[serde(rename = "type")]
     pub msg_type: String,
     pub content: String,
@@ -49,16 +109,74 @@ pub struct TaskMessage {
     pub tool_input: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub attachments: Option<Vec<TaskAttachment>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TaskAttachment {
+    /// Row ID of a stored attachment, for `get_message_attachment`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
     #[serde(rename = "type")]
     pub att_type: String,
-    pub data: String,
+    /// Left out when tasks are loaded without attachment data
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<i64>,
+    /// Screenshot thumbnail built when the message is buffered, for storing with it
+    #[serde(skip)]
+    pub thumbnail: Option<String>,
+}
+
+impl TaskMessage {
+    /// Message for the UI, its attachments carrying their data only if `with_data`
+    fn from_stored(message: db::tasks::StoredTaskMessage, with_data: bool) -> Self {
+        TaskMessage {
+            id: message.id,
+            msg_type: message.msg_type,
+            content: message.content,
+            timestamp: message.timestamp,
+            tool_name: message.tool_name,
+            tool_input: message.tool_input,
+            attachments: message.attachments.map(|atts| {
+                atts.into_iter()
+                    .map(|a| TaskAttachment::from_stored(a, with_data))
+                    .collect()
+            }),
+            note: message.note,
+            origin_id: None,
+        }
+    }
+}
+
+/// A window of a task's messages, from `get_task_messages`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskMessagePage {
+    pub messages: Vec<TaskMessage>,
+    /// Position of the first message in the conversation
+    pub offset: u32,
+    /// Number of messages in the conversation
+    pub total: u32,
+}
+
+impl TaskAttachment {
+    /// Attachment for the UI, carrying its data only if `with_data`
+    fn from_stored(attachment: db::tasks::StoredAttachment, with_data: bool) -> Self {
+        TaskAttachment {
+            id: attachment.id,
+            att_type: attachment.att_type,
+            data: with_data.then_some(attachment.data),
+            label: attachment.label,
+            size: attachment.size,
+            thumbnail: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -69,6 +187,40 @@ pub struct TaskResult {
     pub error: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub session_id: Option<String>,
+    /// Final reply parsed and validated against the task's output schema
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub structured_output: Option<serde_json::Value>,
+}
+
+impl TaskResult {
+    /// Result carried by a sidecar `task_complete` payload
+    pub fn from_complete(payload: &serde_json::Value) -> Option<Self> {
+        serde_json::from_value(payload.get("result")?.clone()).ok()
+    }
+
+    /// Result of a run that ended with a sidecar `task_error` payload
+    pub fn from_error(payload: &serde_json::Value) -> Self {
+        let error = match payload.get("error") {
+            Some(serde_json::Value::String(message)) => message.clone(),
+            Some(other) => other.to_string(),
+            None => "Task failed".to_string(),
+        };
+        TaskResult {
+            status: "error".to_string(),
+            error: Some(error),
+            session_id: None,
+            structured_output: None,
+        }
+    }
+
+    /// Status a task is stored with after its run ended with this result
+    pub fn task_status(&self) -> &'static str {
+        match self.status.as_str() {
+            "success" => "completed",
+            "interrupted" => "interrupted",
+            _ => "failed",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -77,6 +229,34 @@ pub struct TaskConfig {
     pub prompt: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub task_id: Option<String>,
+    /// Start even if a budget limit has been reached
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub override_budget: Option<bool>,
+    /// Workspace the task runs in; its directory and hooks are used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<String>,
+    /// Local directory to run in when no workspace is given; its repository's
+    /// workspace, created if there is none, supplies the settings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub working_directory: Option<String>,
+    /// Propose a plan first and only execute after `approve_task_plan`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan_first: Option<bool>,
+    /// IDs of attachments to send with the prompt
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attachments: Option<Vec<String>>,
+    /// Let the agent search and fetch the web; defaults to the web search setting
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_web_search: Option<bool>,
+    /// JSON schema the final reply must match; the parsed reply is returned in the result
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<serde_json::Value>,
+    /// Environment variables for this task, on top of its workspace's
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<Vec<db::env_vars::EnvVar>>,
+    /// Project to file the task under
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -222,6 +402,17 @@ pub struct ClaudeCliStatus {
     pub installed: bool,
     pub version: Option<String>,
     pub install_command: String,
+    /// Resolved binary the sidecar runs
+    pub path: Option<String>,
+    /// Oldest supported version
+    pub required_version: String,
+    /// Installed, but older than `required_version`; tasks won't start
+    pub outdated: bool,
+    pub upgrade_command: String,
+    /// `path` is the CLI from the bundled runtime
+    pub bundled: bool,
+    /// This build ships a runtime
+    pub bundled_available: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -282,38 +473,53 @@ pub struct ConnectedProviderInput {
 // ============================================================================
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all)]
 fn get_version() -> String {
     env!("CARGO_PKG_VERSION").to_string()
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all)]
 fn get_platform() -> String {
     std::env::consts::OS.to_string()
 }
 
 // ============================================================================
-// Task Commands
+// Profile Commands
 // ============================================================================
 
 #[tauri::command]
-async fn start_task(
-    config: TaskConfig,
-    app: tauri::AppHandle,
-    sidecar_state: State<'_, SidecarState>,
-    db_state: State<'_, DbState>,
-) -> Result<Task, String> {
-    // Resolve model ID from provider settings to avoid interactive CLI prompts
-    let resolved_model_id = {
-        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
-        let active_id = db::providers::get_active_provider_id(&conn);
-        if let Some(active_id) = active_id {
-            if let Some(provider) = db::providers::get_connected_provider(&conn, &active_id) {
-                if provider.connection_status == "connected" {
-                    if let Some(model_id) = provider.selected_model_id {
-                        Some(model_id)
-                    } else {
-                        None
-                    }
+#[tracing::instrument(target = "command", skip_all, err)]
+fn list_profiles(app: tauri::AppHandle) -> Result<Vec<profile::ProfileInfo>, String> {
+    profile::list(&app)
+}
+
+/// Make a profile (created if new) the active one and relaunch the app in it
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+fn switch_profile(name: String, app: tauri::AppHandle) -> Result<(), String> {
+    if name == profile::current() {
+        return Ok(());
+    }
+    profile::select(&app, &name)?;
+    profile::relaunch(&name)?;
+    tracing::info!("[Profile] Switching from {} to {}", profile::current(), name);
+    app.exit(0);
+    Ok(())
+}
+
+// ============================================================================
+// Task Commands
+// ============================================================================
+
+/// Model of the active connected provider, falling back to any connected provider
+fn resolve_model_id(conn: &rusqlite::Connection) -> Option<String> {
+    let active_id = db::providers::get_active_provider_id(conn);
+    if let Some(active_id) = active_id {
+        if let Some(provider) = db::providers::get_connected_provider(conn, &active_id) {
+            if provider.connection_status == "connected" {
+                if let Some(model_id) = provider.selected_model_id {
+                    Some(model_id)
                 } else {
                     None
                 }
@@ -323,67 +529,424 @@ async fn start_task(
         } else {
             None
         }
-        .or_else(|| {
-            let settings = db::providers::get_provider_settings(&conn);
-            settings
-                .connected_providers
-                .values()
-                .find_map(|provider| {
-                    if provider.connection_status == "connected" {
-                        provider.selected_model_id.clone()
-                    } else {
-                        None
-                    }
-                })
+    } else {
+        None
+    }
+    .or_else(|| {
+        let settings = db::providers::get_provider_settings(conn);
+        settings
+            .connected_providers
+            .values()
+            .find_map(|provider| {
+                if provider.connection_status == "connected" {
+                    provider.selected_model_id.clone()
+                } else {
+                    None
+                }
+            })
+    })
+}
+
+/// Workspace default model, falling back to the global selection
+fn resolve_task_model_id(
+    conn: &rusqlite::Connection,
+    workspace_id: Option<&str>,
+) -> Option<String> {
+    workspace_id
+        .and_then(|id| db::workspaces::get_defaults(conn, id).ok())
+        .and_then(|defaults| defaults.model_id)
+        .or_else(|| resolve_model_id(conn))
+}
+
+/// Model and agent to continue a task with: the ones it was started with
+///
+/// Tasks stored without a model continue on their workspace's default.
+fn stored_task_model(
+    conn: &rusqlite::Connection,
+    task: &db::tasks::StoredTask,
+) -> Result<db::tasks::TaskModel, String> {
+    let mut model = db::tasks::get_task_model(conn, &task.id)?;
+    if model.model_id.is_none() {
+        model.model_id = resolve_task_model_id(conn, task.workspace_id.as_deref());
+    }
+    Ok(model)
+}
+
+/// Parameter overrides of the model a task runs on
+fn resolve_model_parameters(
+    db_state: &DbState,
+    model_id: Option<&str>,
+) -> Option<db::providers::ModelParameters> {
+    let model_id = model_id?;
+    let conn = db_state.reader.lock().ok()?;
+    db::providers::get_model_parameters(&conn, model_id)
+        .unwrap_or_else(|e| {
+            tracing::warn!("[Models] {}", e);
+            None
         })
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn start_task(
+    config: TaskConfig,
+    app: tauri::AppHandle,
+    sidecar_state: State<'_, SidecarState>,
+    db_state: State<'_, DbState>,
+) -> Result<Task, String> {
+    // Find the directory's repository before taking the lock; it runs git
+    let detected = match (&config.workspace_id, config.working_directory.as_deref()) {
+        (None, Some(directory)) => workspace_detect::detect(directory).await,
+        _ => None,
+    };
+    let (config, resolved_model_id, agent) = {
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        // A directory picked without a workspace gets its repository's, so its hooks and
+        // settings apply; the task still runs in the directory picked
+        let config = match (&config.workspace_id, config.working_directory.as_deref()) {
+            (None, Some(directory)) => {
+                if !std::path::Path::new(directory).is_dir() {
+                    return Err(format!("Not a directory: {}", directory));
+                }
+                let workspace = workspace_detect::resolve(&conn, directory, detected.as_ref())?;
+                TaskConfig {
+                    workspace_id: Some(workspace.id),
+                    ..config
+                }
+            }
+            _ => TaskConfig {
+                working_directory: None,
+                ..config
+            },
+        };
+        // Expand `/name args` prompts from the slash command registry
+        let expansion = slash_commands::expand(&conn, &config.prompt)?;
+        let (config, command_model_id, agent) = match expansion {
+            Some(expansion) => {
+                tracing::info!("[Commands] Expanded /{}", expansion.command.name);
+                let model_id = expansion.command.model_id;
+                let agent = expansion.command.agent;
+                (TaskConfig { prompt: expansion.prompt, ..config }, model_id, agent)
+            }
+            None => (config, None, None),
+        };
+        // Resolve model ID up front to avoid interactive CLI prompts; a command's
+        // model wins over the workspace default, which wins over the global one
+        let workspace_defaults = match &config.workspace_id {
+            Some(workspace_id) => db::workspaces::get_defaults(&conn, workspace_id)?,
+            None => db::workspaces::WorkspaceDefaults::default(),
+        };
+        let model_id = command_model_id
+            .or(workspace_defaults.model_id)
+            .or_else(|| resolve_model_id(&conn));
+        (config, model_id, agent.or(workspace_defaults.agent))
+    };
+    launch_task(config, resolved_model_id, agent, &app, &sidecar_state, &db_state).await
+}
+
+/// Start a task's sandbox container if its workspace runs tasks in Docker
+async fn start_sandbox(
+    app: &tauri::AppHandle,
+    task_id: &str,
+    workspace_id: Option<&str>,
+) -> Result<Option<sandbox::SandboxPayload>, String> {
+    let Some(workspace_id) = workspace_id else {
+        return Ok(None);
+    };
+    let (image, path) = {
+        let db_state = app.state::<DbState>();
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        let Some(image) = db::workspaces::get_sandbox_image(&conn, workspace_id)? else {
+            return Ok(None);
+        };
+        let workspace = db::workspaces::get_workspace(&conn, workspace_id)?
+            .ok_or_else(|| format!("Workspace not found: {}", workspace_id))?;
+        let path = db::tasks::get_working_directory(&conn, task_id)?.unwrap_or(workspace.path);
+        (image, path)
+    };
+
+    // Starting may pull the image, so keep it off the async runtime
+    let app = app.clone();
+    let task_id = task_id.to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        sandbox::start(&app.state::<sandbox::SandboxState>(), &task_id, &image, &path)
+    })
+    .await
+    .map_err(|e| format!("Failed to start sandbox: {}", e))?
+    .map(Some)
+}
+
+/// Refuse to run a task over its provider's spend limit unless `overridden`
+///
+/// Tells the UI when a limit is reached or close.
+fn check_budget(
+    app: &tauri::AppHandle,
+    db_state: &DbState,
+    task_id: &str,
+    model_id: Option<&str>,
+    overridden: bool,
+) -> Result<(), String> {
+    let budget = {
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        budget::get_status(&conn, model_id.and_then(db::usage::provider_of_model))?
     };
+    if let Some(exceeded) = budget.iter().find(|s| s.is_exceeded()) {
+        sidecar::forward_event(
+            app,
+            "budget:exceeded",
+            serde_json::json!({ "taskId": task_id, "budget": exceeded, "overridden": overridden }),
+        );
+        if !overridden {
+            return Err(format!(
+                "{} budget of ${:.2} reached (${:.2} spent)",
+                if exceeded.period == "day" { "Daily" } else { "Monthly" },
+                exceeded.amount,
+                exceeded.spent
+            ));
+        }
+        tracing::warn!("[Budget] Starting task {} over budget by user override", task_id);
+    } else if let Some(near) = budget.iter().find(|s| s.is_near_limit()) {
+        sidecar::forward_event(
+            app,
+            "budget:warning",
+            serde_json::json!({ "taskId": task_id, "budget": near }),
+        );
+    }
+    Ok(())
+}
+
+/// Run the pre-task hooks of a task's workspace
+async fn run_pre_task_hooks(app: &tauri::AppHandle, task_id: &str) -> Result<(), String> {
+    let app = app.clone();
+    let task_id = task_id.to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        hooks::run_hooks(&app, &task_id, hooks::HookStage::PreTask)
+    })
+    .await
+    .map_err(|e| format!("Failed to run pre-task hooks: {}", e))
+}
+
+/// `dir`, resolved against a workspace's directory, if it is that directory or inside it
+///
+/// A remote workspace's directories can't be resolved here, so they are only
+/// checked by path and may not contain `..`.
+fn workspace_subdirectory(root: &str, dir: &str, remote: bool) -> Result<String, String> {
+    let outside = || format!("Working directory is outside the workspace: {}", dir);
+    let path = std::path::Path::new(root).join(dir);
+    if remote {
+        let parent_dir = path.components().any(|c| c == std::path::Component::ParentDir);
+        if parent_dir || !path.starts_with(root) {
+            return Err(outside());
+        }
+        return Ok(path.to_string_lossy().to_string());
+    }
+    let root = std::path::Path::new(root)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace directory {}: {}", root, e))?;
+    let resolved = path
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve working directory {}: {}", dir, e))?;
+    if !resolved.starts_with(&root) {
+        return Err(outside());
+    }
+    Ok(resolved.to_string_lossy().to_string())
+}
+
+/// Record a new task and send it to the sidecar to run on `resolved_model_id`
+async fn launch_task(
+    config: TaskConfig,
+    resolved_model_id: Option<String>,
+    agent: Option<String>,
+    app: &tauri::AppHandle,
+    sidecar_state: &SidecarState,
+    db_state: &DbState,
+) -> Result<Task, String> {
+    safe_mode::check("Running tasks")?;
+
     // Generate task ID
     let task_id = config.task_id.clone().unwrap_or_else(|| {
         format!("task_{}", uuid::Uuid::new_v4())
     });
 
+    // Enforce spend limits for the provider the task will run on
+    let overridden = config.override_budget.unwrap_or(false);
+    check_budget(app, db_state, &task_id, resolved_model_id.as_deref(), overridden)?;
+
+    let (working_directory, agent_settings) = match &config.workspace_id {
+        Some(workspace_id) => {
+            let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+            let workspace = db::workspaces::get_workspace(&conn, workspace_id)?
+                .ok_or_else(|| format!("Workspace not found: {}", workspace_id))?;
+            let agent_settings = db::workspaces::task_agent_settings(&conn, Some(workspace_id))?;
+            let directory = match config.working_directory.as_deref() {
+                Some(dir) => {
+                    let remote = db::workspaces::get_ssh_host_id(&conn, workspace_id)?.is_some();
+                    workspace_subdirectory(&workspace.path, dir, remote)?
+                }
+                None => workspace.path,
+            };
+            (Some(directory), agent_settings)
+        }
+        None => (None, None),
+    };
+
+    // Images and PDFs go to the agent as files; extracted text joins the prompt
+    let mut attachment_files = Vec::new();
+    let mut context_attachments = Vec::new();
+    if let Some(ids) = config.attachments.as_deref() {
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        for id in ids {
+            let attachment = db::attachments::get_attachment(&conn, id)?
+                .ok_or_else(|| format!("Attachment not found: {}", id))?;
+            let is_file = attachment.kind == "image"
+                || (attachment.text.is_none() && attachment.mime_type == "application/pdf");
+            if is_file {
+                attachment_files.push(attachment);
+            } else if attachment.text.is_some() {
+                context_attachments.push(attachment);
+            } else {
+                return Err(format!("Attachment has no text to include: {}", attachment.file_name));
+            }
+        }
+    }
+    // Images are only forwarded to models that can read them
+    if let Some(model_id) = resolved_model_id.as_deref() {
+        if attachment_files.iter().any(|a| a.kind == "image") && !attachments::supports_vision(model_id) {
+            return Err(format!("{} does not accept images; choose a vision-capable model", model_id));
+        }
+    }
+    let agent_prompt = attachments::prompt_with_context(&config.prompt, &context_attachments);
+    citations::note_attachments(&task_id, &context_attachments);
+    let attachment_files = (!attachment_files.is_empty()).then(|| {
+        attachment_files
+            .into_iter()
+            .map(|a| sidecar::AttachmentFile {
+                path: a.path,
+                mime_type: a.mime_type,
+            })
+            .collect()
+    });
+
+    if let Some(schema) = &config.output_schema {
+        structured_output::check_schema(schema)?;
+    }
+
+    let web_search = {
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        let settings = db::settings::get_web_search_settings(&conn);
+        sidecar::WebSearchPayload {
+            enabled: config.allow_web_search.unwrap_or(settings.enabled_by_default),
+            backend: settings.backend,
+        }
+    };
+
     let created_at = chrono::Utc::now().to_rfc3339();
     let started_at = chrono::Utc::now().to_rfc3339();
 
     // Create initial task record in database
-    {
-        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+    let env = {
+        let mut conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        if let Some(project_id) = config.project_id.as_deref() {
+            if !db::projects::project_exists(&conn, project_id) {
+                return Err(format!("Project not found: {}", project_id));
+            }
+        }
+        if let Some(vars) = config.env.as_deref() {
+            task_env::set(&mut conn, &task_id, vars)?;
+        }
         db::tasks::save_task(&conn, &db::tasks::TaskInput {
             id: task_id.clone(),
             prompt: config.prompt.clone(),
             status: "starting".to_string(),
             session_id: None,
             summary: None,
+            title: None,
             messages: vec![],
             created_at: created_at.clone(),
             started_at: Some(started_at.clone()),
             completed_at: None,
+            workspace_id: config.workspace_id.clone(),
+            allow_web_search: Some(web_search.enabled),
+            updated_at: Some(started_at.clone()),
         })?;
+        let model = db::tasks::TaskModel {
+            model_id: resolved_model_id.clone(),
+            agent: agent.clone(),
+        };
+        db::tasks::set_task_model(&conn, &task_id, &model)?;
+        if let Some(directory) = config.working_directory.as_ref().and(working_directory.as_ref()) {
+            db::tasks::set_working_directory(&conn, &task_id, directory)?;
+        }
+        if let Some(project_id) = config.project_id.as_deref() {
+            db::projects::move_tasks(&conn, std::slice::from_ref(&task_id), Some(project_id))?;
+        }
+        if let Some(schema) = &config.output_schema {
+            db::structured::save_schema(&conn, &task_id, schema)?;
+        }
+        task_env::resolve(&conn, config.workspace_id.as_deref(), Some(&task_id))?
+    };
+
+    // Run the workspace's pre-task hooks before the agent touches the directory;
+    // a plan-first task only reads it until its plan is approved, so they run then
+    if config.workspace_id.is_some() && !config.plan_first.unwrap_or(false) {
+        run_pre_task_hooks(app, &task_id).await?;
     }
 
+    // Start the workspace's sandbox container; the agent's commands run inside it
+    let sandbox = start_sandbox(app, &task_id, config.workspace_id.as_deref()).await?;
+    let sandbox_guard = sandbox::SandboxGuard::new(app, &task_id);
+    let remote = remote::workspace_target(app, config.workspace_id.as_deref())?;
+
     // Get API keys from secure storage
-    let api_keys = sidecar::get_all_api_keys()?;
+    let api_keys = sidecar_state.api_keys()?;
 
     // Ensure sidecar is running
+    let model_parameters = resolve_model_parameters(db_state, resolved_model_id.as_deref());
     let mut manager = sidecar_state.manager.lock().await;
     if !manager.is_running() {
-        manager.spawn(&app).await?;
+        manager.spawn(app).await?;
     }
 
+    let title_request = {
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        summaries::title_command(
+            &conn,
+            &task_id,
+            &config.prompt,
+            resolved_model_id.clone(),
+            api_keys.clone(),
+        )
+    };
+
     // Send start task command
     manager
         .send_command(sidecar::SidecarCommand::StartTask {
             task_id: task_id.clone(),
             payload: sidecar::StartTaskPayload {
                 task_id: task_id.clone(),
-                prompt: config.prompt.clone(),
+                prompt: agent_prompt,
                 session_id: None,
                 api_keys: Some(api_keys),
-                working_directory: None,
+                working_directory,
                 model_id: resolved_model_id,
+                model_parameters,
+                plan_only: config.plan_first,
+                agent,
+                attachments: attachment_files,
+                web_search: Some(web_search),
+                output_schema: config.output_schema,
+                env,
+                sandbox,
+                remote,
+                agent_settings,
             },
         })
         .await?;
+    sandbox_guard.keep();
+    // The title is generated alongside the task, from the prompt alone
+    if let Err(e) = manager.send_command(title_request).await {
+        tracing::warn!("[Summary] Failed to request title of {}: {}", task_id, e);
+    }
 
     // Return task object (status will be updated via events)
     Ok(Task {
@@ -394,28 +957,40 @@ async fn start_task(
         result: None,
         session_id: None,
         summary: None,
+        title: None,
         created_at,
         updated_at: None,
         completed_at: None,
         started_at: Some(started_at),
+        tags: Vec::new(),
     })
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
 async fn cancel_task(
     task_id: String,
+    app: tauri::AppHandle,
     sidecar_state: State<'_, SidecarState>,
 ) -> Result<(), String> {
     let mut manager = sidecar_state.manager.lock().await;
     if manager.is_running() {
         manager
-            .send_command(sidecar::SidecarCommand::CancelTask { task_id })
+            .send_command(sidecar::SidecarCommand::CancelTask {
+                task_id: task_id.clone(),
+            })
             .await?;
     }
+    drop(manager);
+    // A cancelled run reports neither completion nor an error, so release what
+    // the run held here: its progress, session lease, remote key and sandbox
+    sidecar::finish_task(&app, &task_id, false);
+    sandbox::stop_in_background(&app, &task_id);
     Ok(())
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
 async fn interrupt_task(
     task_id: String,
     sidecar_state: State<'_, SidecarState>,
@@ -429,10 +1004,65 @@ async fn interrupt_task(
     Ok(())
 }
 
+/// Kill every running task's process tree and cancel the queued ones
 #[tauri::command]
-async fn get_task(task_id: String, state: State<'_, DbState>) -> Result<Option<Task>, String> {
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    let stored = db::tasks::get_task(&conn, &task_id);
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn abort_all_tasks(app: tauri::AppHandle) -> Result<abort::AbortSummary, String> {
+    abort::abort_all(&app).await
+}
+
+/// Wait for a task to end, up to `timeout_ms` (default 60s, at most 10 minutes)
+///
+/// Returns `None` when the task doesn't exist, and an outcome with `finished`
+/// false when the wait timed out first.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn await_task(
+    task_id: String,
+    timeout_ms: Option<u64>,
+    db_state: State<'_, DbState>,
+    sidecar_state: State<'_, SidecarState>,
+) -> Result<Option<task_wait::TaskOutcome>, String> {
+    let timeout = timeout_ms
+        .map(std::time::Duration::from_millis)
+        .unwrap_or(task_wait::DEFAULT_TIMEOUT);
+    task_wait::wait(&task_id, timeout, &db_state, &sidecar_state).await
+}
+
+/// Get a task; attachments carry only metadata unless `include_attachment_data`
+///
+/// With `include_messages` false the task comes without messages, which long
+/// tasks can then load in windows with `get_task_messages`.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn get_task(
+    task_id: String,
+    include_attachment_data: Option<bool>,
+    include_messages: Option<bool>,
+    state: State<'_, DbState>,
+    messages: State<'_, MessageBufferState>,
+) -> Result<Option<Task>, String> {
+    let with_data = include_attachment_data.unwrap_or(false);
+    {
+        let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+        messages.flush_task(&mut conn, &task_id);
+    }
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    let stored = if !include_messages.unwrap_or(true) {
+        db::tasks::get_task_without_messages(&conn, &task_id)
+    } else if with_data {
+        db::tasks::get_task(&conn, &task_id)
+    } else {
+        db::tasks::get_task_without_attachment_data(&conn, &task_id)
+    };
+    // Structured tasks report their validated output in the result
+    let structured_output = db::structured::get(&conn, &task_id)?.and_then(|r| r.output);
+    // A task running again has no result until that run ends
+    let finished = stored
+        .as_ref()
+        .is_some_and(|t| task_wait::FINISHED_STATUSES.contains(&t.status.as_str()));
+    let stored_result = db::tasks::get_task_result(&conn, &task_id)?.filter(|_| finished);
+    let tags = db::tags::get_task_tags(&conn, &task_id)?;
 
     Ok(stored.map(|t| Task {
         id: t.id,
@@ -441,135 +1071,346 @@ async fn get_task(task_id: String, state: State<'_, DbState>) -> Result<Option<T
         messages: t
             .messages
             .into_iter()
-            .map(|m| TaskMessage {
-                id: m.id,
-                msg_type: m.msg_type,
-                content: m.content,
-                timestamp: m.timestamp,
-                tool_name: m.tool_name,
-                tool_input: m.tool_input,
-                attachments: m.attachments.map(|atts| {
-                    atts.into_iter()
-                        .map(|a| TaskAttachment {
-                            att_type: a.att_type,
-                            data: a.data,
-                            label: a.label,
-                        })
-                        .collect()
-                }),
-            })
+            .map(|m| TaskMessage::from_stored(m, with_data))
             .collect(),
-        result: None,
+        result: match stored_result {
+            Some(r) => Some(TaskResult {
+                status: r.status,
+                error: r.error,
+                session_id: r.session_id,
+                structured_output,
+            }),
+            // Structured tasks that ended before results were stored
+            None => structured_output.map(|output| TaskResult {
+                status: "success".to_string(),
+                error: None,
+                session_id: t.session_id.clone(),
+                structured_output: Some(output),
+            }),
+        },
         session_id: t.session_id,
         summary: t.summary,
+        title: t.title,
         created_at: t.created_at.clone(),
-        updated_at: None,
+        updated_at: t.updated_at,
         completed_at: t.completed_at,
         started_at: t.started_at,
+        tags,
     }))
 }
 
+/// Open a read-only window that follows one task
 #[tauri::command]
-async fn list_tasks(state: State<'_, DbState>) -> Result<Vec<Task>, String> {
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    let tasks = db::tasks::get_tasks(&conn);
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn open_observer_window(task_id: String, app: tauri::AppHandle) -> Result<String, String> {
+    observer::open(&app, &task_id)
+}
+
+/// Send a task's events to the calling window's channel, returning the task as stored
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn watch_task(
+    task_id: String,
+    on_event: tauri::ipc::Channel<sidecar::ForwardedEvent>,
+    window: tauri::WebviewWindow,
+    state: State<'_, DbState>,
+    messages: State<'_, MessageBufferState>,
+) -> Result<Option<Task>, String> {
+    // Watch first so nothing emitted while the task is read is missed
+    observer::watch(window.label(), &task_id, on_event);
+    get_task(task_id, None, None, state, messages).await
+}
 
-    Ok(tasks
+/// Up to `limit` of a task's messages from the `offset`th, oldest first, with
+/// attachment metadata only; `total` tells how many there are
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn get_task_messages(
+    task_id: String,
+    offset: u32,
+    limit: u32,
+    state: State<'_, DbState>,
+    messages: State<'_, MessageBufferState>,
+) -> Result<TaskMessagePage, String> {
+    {
+        let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+        messages.flush_task(&mut conn, &task_id);
+    }
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    if !db::tasks::task_exists(&conn, &task_id) {
+        return Err(format!("Task not found: {}", task_id));
+    }
+    let total = db::tasks::count_messages(&conn, &task_id)?;
+    let messages = db::tasks::get_message_window(&conn, &task_id, offset, limit)
         .into_iter()
-        .map(|t| Task {
-            id: t.id,
-            prompt: t.prompt,
-            status: t.status,
-            messages: t
-                .messages
-                .into_iter()
-                .map(|m| TaskMessage {
-                    id: m.id,
-                    msg_type: m.msg_type,
-                    content: m.content,
-                    timestamp: m.timestamp,
-                    tool_name: m.tool_name,
-                    tool_input: m.tool_input,
-                    attachments: m.attachments.map(|atts| {
-                        atts.into_iter()
-                            .map(|a| TaskAttachment {
-                                att_type: a.att_type,
-                                data: a.data,
-                                label: a.label,
-                            })
-                            .collect()
-                    }),
-                })
-                .collect(),
-            result: None,
-            session_id: t.session_id,
-            summary: t.summary,
-            created_at: t.created_at.clone(),
-            updated_at: None,
-            completed_at: t.completed_at,
-            started_at: t.started_at,
-        })
-        .collect())
+        .map(|m| TaskMessage::from_stored(m, false))
+        .collect();
+    Ok(TaskMessagePage {
+        messages,
+        offset,
+        total,
+    })
+}
+
+/// A stored task as the task list shows it: attachment metadata only, no result
+fn listed_task(t: db::tasks::StoredTask, tags: &mut HashMap<String, Vec<String>>) -> Task {
+    Task {
+        tags: tags.remove(&t.id).unwrap_or_default(),
+        id: t.id,
+        prompt: t.prompt,
+        status: t.status,
+        messages: t
+            .messages
+            .into_iter()
+            .map(|m| TaskMessage::from_stored(m, false))
+            .collect(),
+        result: None,
+        session_id: t.session_id,
+        summary: t.summary,
+        title: t.title,
+        created_at: t.created_at.clone(),
+        updated_at: t.updated_at,
+        completed_at: t.completed_at,
+        started_at: t.started_at,
+    }
 }
 
 #[tauri::command]
-async fn delete_task(task_id: String, state: State<'_, DbState>) -> Result<(), String> {
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    db::tasks::delete_task(&conn, &task_id)
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn list_tasks(
+    sort: Option<db::tasks::TaskSort>,
+    state: State<'_, DbState>,
+    messages: State<'_, MessageBufferState>,
+) -> Result<Vec<Task>, String> {
+    // Buffered messages count towards the listed tasks' activity
+    {
+        let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+        messages.flush(&mut conn);
+    }
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    let tasks = db::tasks::get_tasks_without_attachment_data(&conn, sort.unwrap_or_default());
+    let mut tags = db::tags::get_all_task_tags(&conn)?;
+
+    Ok(tasks.into_iter().map(|t| listed_task(t, &mut tags)).collect())
 }
 
+/// Tasks with all of the given tags, any of the given statuses, and created in the date range
 #[tauri::command]
-async fn clear_task_history(state: State<'_, DbState>) -> Result<(), String> {
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    db::tasks::clear_history(&conn)
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn list_tasks_filtered(
+    filter: db::tasks::TaskFilter,
+    state: State<'_, DbState>,
+) -> Result<Vec<Task>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    let tasks = db::tasks::get_tasks_filtered(&conn, &filter)?;
+    let mut tags = db::tags::get_all_task_tags(&conn)?;
+
+    Ok(tasks.into_iter().map(|t| listed_task(t, &mut tags)).collect())
 }
 
-// ============================================================================
-// Task Persistence Commands (for saving task updates from frontend events)
-// ============================================================================
+/// Search task prompts, summaries, titles, and messages
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn search_tasks(
+    query: String,
+    limit: Option<u32>,
+    state: State<'_, DbState>,
+    messages: State<'_, MessageBufferState>,
+) -> Result<Vec<db::search::SearchHit>, String> {
+    {
+        let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+        messages.flush(&mut conn);
+    }
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::search::search_tasks(&conn, &query, limit)
+}
 
+/// Get a message attachment with its data
 #[tauri::command]
-async fn save_task_message(
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_message_attachment(
+    attachment_id: i64,
+    state: State<'_, DbState>,
+) -> Result<Option<TaskAttachment>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    Ok(db::tasks::get_message_attachment(&conn, attachment_id)?
+        .map(|a| TaskAttachment::from_stored(a, true)))
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn delete_task(
     task_id: String,
-    message: TaskMessage,
     state: State<'_, DbState>,
+    messages: State<'_, MessageBufferState>,
 ) -> Result<(), String> {
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    messages.discard_task(&task_id);
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::tasks::delete_task(&conn, &task_id)?;
+    if let Err(e) = task_env::set(&mut conn, &task_id, &[]) {
+        tracing::warn!("[Env] Failed to remove variables of task {}: {}", task_id, e);
+    }
 
-    db::tasks::add_task_message(
-        &conn,
-        &task_id,
-        &db::tasks::TaskMessageInput {
-            id: message.id,
-            msg_type: message.msg_type,
-            content: message.content,
-            timestamp: message.timestamp,
-            tool_name: message.tool_name,
-            tool_input: message.tool_input,
-            attachments: message.attachments.map(|atts| {
-                atts.into_iter()
-                    .map(|a| db::tasks::AttachmentInput {
-                        att_type: a.att_type,
-                        data: a.data,
-                        label: a.label,
-                    })
-                    .collect()
-            }),
-        },
-    )
+    if db::settings::get_e2e_sync_settings(&conn).enabled {
+        db::tasks::record_tombstone(&conn, &task_id)?;
+    }
+    let folder = db::settings::get_sync_folder(&conn);
+    drop(conn);
+    if let Some(folder) = folder {
+        if let Err(e) = sync::record_deletion(std::path::Path::new(&folder), &task_id) {
+            tracing::warn!("[Sync] {}", e);
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn clear_task_history(
+    state: State<'_, DbState>,
+    messages: State<'_, MessageBufferState>,
+) -> Result<(), String> {
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    messages.flush(&mut conn);
+
+    let e2e_enabled = db::settings::get_e2e_sync_settings(&conn).enabled;
+    let folder = db::settings::get_sync_folder(&conn);
+    let archived = db::tasks::get_archived_tasks(&conn)?;
+    let task_ids: Vec<String> = db::tasks::get_tasks(&conn)
+        .into_iter()
+        .map(|t| t.id)
+        .chain(archived.into_iter().map(|t| t.task_id))
+        .collect();
+    if e2e_enabled {
+        for task_id in &task_ids {
+            db::tasks::record_tombstone(&conn, task_id)?;
+        }
+    }
+    db::tasks::clear_history(&conn)?;
+    drop(conn);
+
+    if let Some(folder) = &folder {
+        for task_id in &task_ids {
+            if let Err(e) = sync::record_deletion(std::path::Path::new(folder), task_id) {
+                tracing::warn!("[Sync] {}", e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Tasks archived once the history went over its limit, most recently archived first
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn list_archived_tasks(
+    state: State<'_, DbState>,
+) -> Result<Vec<db::tasks::ArchivedTask>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::tasks::get_archived_tasks(&conn)
+}
+
+/// Move an archived task back into the task list, returning it as the list shows it
+///
+/// When the list is full its oldest task is archived in its place.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn restore_task(task_id: String, state: State<'_, DbState>) -> Result<Task, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    if !db::tasks::restore_task(&conn, &task_id)? {
+        return Err(format!("Task is not archived: {}", task_id));
+    }
+    let task = db::tasks::get_task_without_attachment_data(&conn, &task_id)
+        .ok_or_else(|| format!("Task not found: {}", task_id))?;
+    let mut tags = HashMap::from([(task_id.clone(), db::tags::get_task_tags(&conn, &task_id)?)]);
+    tracing::info!("[Tasks] Restored {} from the archive", task_id);
+    Ok(listed_task(task, &mut tags))
+}
+
+// ============================================================================
+// Task Persistence Commands (for saving task updates from frontend events)
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn save_task_message(
+    task_id: String,
+    message: TaskMessage,
+    messages: State<'_, MessageBufferState>,
+) -> Result<(), String> {
+    // Written by the next flush, see `message_buffer`
+    messages.push(&task_id, message)
+}
+
+/// Get the structured diff of a file-editing tool message
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_message_diff(
+    message_id: String,
+    state: State<'_, DbState>,
+) -> Result<Option<diff::MessageDiff>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::diffs::get_diff(&conn, &message_id)
+}
+
+/// Web pages an assistant message had in context, in the order they were added
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_message_sources(
+    message_id: String,
+    state: State<'_, DbState>,
+) -> Result<Vec<db::citations::MessageSource>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::citations::get_message_sources(&conn, &message_id)
+}
+
+/// Latest progress the sidecar reported for a task
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn get_task_progress(
+    task_id: String,
+    state: State<'_, DbState>,
+) -> Result<Option<db::progress::TaskProgress>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::progress::get_progress(&conn, &task_id)
+}
+
+/// Code blocks saved from a task's messages
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn list_task_artifacts(
+    task_id: String,
+    state: State<'_, DbState>,
+) -> Result<Vec<db::artifacts::Artifact>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::artifacts::get_task_artifacts(&conn, &task_id)
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
 async fn save_task_status(
     task_id: String,
     status: String,
     state: State<'_, DbState>,
+    messages: State<'_, MessageBufferState>,
 ) -> Result<(), String> {
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    db::tasks::update_task_status(&conn, &task_id, &status, None)
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    messages.flush_task(&mut conn, &task_id);
+    db::tasks::update_task_status(&conn, &task_id, &status, None, db::task_events::SOURCE_UI, None)
 }
 
+/// A task's status changes, oldest first, with what made each and why
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn get_task_timeline(
+    task_id: String,
+    state: State<'_, DbState>,
+) -> Result<Vec<db::task_events::TaskEvent>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::task_events::get_timeline(&conn, &task_id)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
 async fn save_task_session(
     task_id: String,
     session_id: String,
@@ -580,6 +1421,7 @@ async fn save_task_session(
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
 async fn save_task_summary(
     task_id: String,
     summary: String,
@@ -590,28 +1432,97 @@ async fn save_task_summary(
 }
 
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
 async fn complete_task(
     task_id: String,
     status: String,
     session_id: Option<String>,
     state: State<'_, DbState>,
+    messages: State<'_, MessageBufferState>,
 ) -> Result<(), String> {
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let result = TaskResult {
+        status: match status.as_str() {
+            "completed" => "success",
+            "interrupted" => "interrupted",
+            _ => "error",
+        }
+        .to_string(),
+        error: None,
+        session_id,
+        structured_output: None,
+    };
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    store_outcome(&mut conn, &messages, &task_id, &status, &result, db::task_events::SOURCE_UI)
+}
+
+/// Store how a task's run ended: its buffered messages, final status, result and session
+///
+/// Called by the sidecar event handler when a task completes or fails, and by
+/// `complete_task`; storing the same outcome again only moves `completed_at`.
+pub(crate) fn store_outcome(
+    conn: &mut rusqlite::Connection,
+    messages: &MessageBufferState,
+    task_id: &str,
+    status: &str,
+    result: &TaskResult,
+    source: &str,
+) -> Result<(), String> {
+    messages.flush_task(conn, task_id);
 
     let completed_at = chrono::Utc::now().to_rfc3339();
 
     // Update status with completion time
-    db::tasks::update_task_status(&conn, &task_id, &status, Some(&completed_at))?;
+    db::tasks::update_task_status(
+        conn,
+        task_id,
+        status,
+        Some(&completed_at),
+        source,
+        result.error.as_deref(),
+    )?;
+    db::tasks::set_task_result(
+        conn,
+        task_id,
+        &db::tasks::StoredResult {
+            status: result.status.clone(),
+            error: result.error.clone(),
+            session_id: result.session_id.clone(),
+        },
+    )?;
 
     // Update session ID if provided
-    if let Some(sid) = session_id {
-        db::tasks::update_task_session_id(&conn, &task_id, &sid)?;
+    if let Some(sid) = &result.session_id {
+        db::tasks::update_task_session_id(conn, task_id, sid)?;
     }
 
+    // Mirror the finished task into the sync folder
+    mirror_to_sync_folder(conn, task_id);
+
     Ok(())
 }
 
+/// Write a task's stored state to the sync folder, if one is set
+///
+/// The file is written on another thread, so the caller's database lock isn't
+/// held for folder I/O.
+fn mirror_to_sync_folder(conn: &rusqlite::Connection, task_id: &str) {
+    static MIRROR_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    let Some(folder) = db::settings::get_sync_folder(conn) else {
+        return;
+    };
+    let Some(task) = db::tasks::get_task(conn, task_id) else {
+        return;
+    };
+    std::thread::spawn(move || {
+        let _guard = MIRROR_LOCK.lock();
+        if let Err(e) = sync::mirror_task(std::path::Path::new(&folder), &task) {
+            tracing::warn!("[Sync] {}", e);
+        }
+    });
+}
+
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
 async fn respond_to_permission(
     response: PermissionResponse,
     sidecar_state: State<'_, SidecarState>,
@@ -632,23 +1543,85 @@ async fn respond_to_permission(
     Ok(())
 }
 
+/// Add messages of a session that advanced outside the app to its task
+///
+/// Returns the ID of the task being synced; the result arrives as
+/// `task:session_synced`.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn sync_session(session_id: String, app: tauri::AppHandle) -> Result<String, String> {
+    session_sync::request(&app, &session_id).await
+}
+
+/// Message count, token estimate, and tasks of a session, i.e. what resuming it starts with
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_session_context(
+    session_id: String,
+    app: tauri::AppHandle,
+) -> Result<session_context::SessionContext, String> {
+    session_context::get(&app, &session_id).await
+}
+
+/// Tasks the previous run left unfinished, interrupted at startup; empty after the first call
 #[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_interrupted_tasks() -> Result<Vec<db::tasks::UnfinishedTask>, String> {
+    Ok(recovery::take_interrupted())
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = task_id.as_deref()), err)]
 async fn resume_session(
     session_id: String,
     prompt: String,
     task_id: Option<String>,
     app: tauri::AppHandle,
     sidecar_state: State<'_, SidecarState>,
+    db_state: State<'_, DbState>,
 ) -> Result<Task, String> {
     // Generate task ID
     let task_id = task_id.unwrap_or_else(|| {
         format!("task_{}", uuid::Uuid::new_v4())
     });
 
+    // OpenCode sessions belong to a project, so resume in the session's directory
+    let (working_directory, workspace_id, env, agent_settings, web_search, model) = {
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        let (working_directory, workspace_id) =
+            db::workspaces::get_session_directory(&conn, &session_id)?.unzip();
+        let env = task_env::resolve(&conn, workspace_id.as_deref(), None)?;
+        let agent_settings =
+            db::workspaces::task_agent_settings(&conn, workspace_id.as_deref())?;
+        // The session's last task decides the model and whether the agent keeps web access
+        let session_task = db::tasks::get_task_id_for_session(&conn, &session_id)
+            .and_then(|id| db::tasks::get_task_without_messages(&conn, &id));
+        let web_search = session_task
+            .as_ref()
+            .and_then(|task| task.allow_web_search)
+            .map(|enabled| sidecar::WebSearchPayload {
+                enabled,
+                backend: db::settings::get_web_search_settings(&conn).backend,
+            });
+        let model = match &session_task {
+            Some(task) => stored_task_model(&conn, task)?,
+            None => db::tasks::TaskModel {
+                model_id: resolve_task_model_id(&conn, workspace_id.as_deref()),
+                agent: None,
+            },
+        };
+        (working_directory, workspace_id, env, agent_settings, web_search, model)
+    };
+    let lease = session_lock::acquire(&db_state, &session_id, &task_id)?;
+    let sandbox = start_sandbox(&app, &task_id, workspace_id.as_deref()).await?;
+    let sandbox_guard = sandbox::SandboxGuard::new(&app, &task_id);
+    let remote = remote::workspace_target(&app, workspace_id.as_deref())?;
+
     // Get API keys from secure storage
-    let api_keys = sidecar::get_all_api_keys()?;
+    let api_keys = sidecar_state.api_keys()?;
 
     // Ensure sidecar is running
+    let model_parameters = resolve_model_parameters(&db_state, model.model_id.as_deref());
     let mut manager = sidecar_state.manager.lock().await;
     if !manager.is_running() {
         manager.spawn(&app).await?;
@@ -663,11 +1636,23 @@ async fn resume_session(
                 prompt: prompt.clone(),
                 session_id: Some(session_id.clone()),
                 api_keys: Some(api_keys),
-                working_directory: None,
-                model_id: None,
+                working_directory,
+                model_id: model.model_id,
+                model_parameters,
+                plan_only: None,
+                agent: model.agent,
+                attachments: None,
+                web_search,
+                output_schema: None,
+                env,
+                sandbox,
+                remote,
+                agent_settings,
             },
         })
         .await?;
+    lease.keep();
+    sandbox_guard.keep();
 
     // Return task object
     Ok(Task {
@@ -678,120 +1663,1576 @@ async fn resume_session(
         result: None,
         session_id: Some(session_id),
         summary: None,
+        title: None,
         created_at: chrono::Utc::now().to_rfc3339(),
         updated_at: None,
         completed_at: None,
         started_at: Some(chrono::Utc::now().to_rfc3339()),
+        tags: Vec::new(),
     })
 }
 
-// ============================================================================
-// Settings Commands
-// ============================================================================
-
+/// Continue a finished task's session with a new message, in the same task
+///
+/// The message is added to the task's transcript and the task runs again under
+/// its own ID, so a conversation stays one task. Rejects with an error starting
+/// with `session:busy` while another task is continuing the same session.
 #[tauri::command]
-async fn get_api_keys() -> Result<Vec<ApiKeyConfig>, String> {
-    let status = secure_storage::get_all_api_key_status()?;
-    let mut keys = Vec::new();
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn send_follow_up(
+    task_id: String,
+    prompt: String,
+    app: tauri::AppHandle,
+    sidecar_state: State<'_, SidecarState>,
+    db_state: State<'_, DbState>,
+    messages: State<'_, MessageBufferState>,
+) -> Result<Task, String> {
+    if prompt.trim().is_empty() {
+        return Err("Message cannot be empty".to_string());
+    }
 
-    for (provider, key_status) in status {
-        if key_status.exists {
-            keys.push(ApiKeyConfig {
-                id: format!("apikey-{}", provider),
-                provider: provider.clone(),
-                label: Some(provider),
-                created_at: chrono::Utc::now().to_rfc3339(),
-            });
+    let (task, session_id, options, model) = {
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        let task = db::tasks::get_task_without_messages(&conn, &task_id)
+            .ok_or_else(|| format!("Task not found: {}", task_id))?;
+        if !task_wait::FINISHED_STATUSES.contains(&task.status.as_str()) {
+            return Err(format!("Task is still {}", task.status));
         }
+        // The last run's session, which is only stored on the task once it completes
+        let session_id = db::tasks::get_task_result(&conn, &task_id)?
+            .and_then(|r| r.session_id)
+            .or_else(|| task.session_id.clone())
+            .ok_or_else(|| "The task has no session to continue".to_string())?;
+        let options = stored_run_options(&conn, &task)?;
+        let model = stored_task_model(&conn, &task)?;
+        (task, session_id, options, model)
+    };
+    let lease = session_lock::acquire(&db_state, &session_id, &task_id)?;
+
+    let sandbox = start_sandbox(&app, &task_id, task.workspace_id.as_deref()).await?;
+    let sandbox_guard = sandbox::SandboxGuard::new(&app, &task_id);
+    let remote = remote::workspace_target(&app, task.workspace_id.as_deref())?;
+    let api_keys = sidecar_state.api_keys()?;
+
+    // Buffered with the replies streamed after it, so it is stored ahead of them
+    let message = TaskMessage {
+        id: format!("msg_{}", uuid::Uuid::new_v4()),
+        msg_type: "user".to_string(),
+        content: prompt.clone(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        tool_name: None,
+        tool_input: None,
+        attachments: None,
+        note: None,
+        origin_id: None,
+    };
+    messages.push(&task_id, message.clone())?;
+    {
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        db::tasks::update_task_status(
+            &conn,
+            &task_id,
+            "running",
+            None,
+            db::task_events::SOURCE_USER,
+            Some("Follow-up sent"),
+        )?;
     }
 
-    Ok(keys)
-}
+    let model_parameters = resolve_model_parameters(&db_state, model.model_id.as_deref());
+    let mut manager = sidecar_state.manager.lock().await;
+    let mut sent = Ok(());
+    if !manager.is_running() {
+        sent = manager.spawn(&app).await;
+    }
+    if sent.is_ok() {
+        sent = manager
+            .send_command(sidecar::SidecarCommand::StartTask {
+                task_id: task_id.clone(),
+                payload: sidecar::StartTaskPayload {
+                    task_id: task_id.clone(),
+                    prompt,
+                    session_id: Some(session_id.clone()),
+                    api_keys: Some(api_keys),
+                    working_directory: options.working_directory,
+                    model_id: model.model_id,
+                    model_parameters,
+                    plan_only: None,
+                    agent: model.agent,
+                    attachments: None,
+                    web_search: options.web_search,
+                    output_schema: None,
+                    env: options.env,
+                    sandbox,
+                    remote,
+                    agent_settings: options.agent_settings,
+                },
+            })
+            .await;
+    }
+    if let Err(e) = sent {
+        // The run never started, so the task is left as it was, without the
+        // follow-up, which may have been flushed already
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        if !messages.discard_message(&task_id, &message.id) {
+            db::tasks::delete_message(&conn, &task_id, &message.id)?;
+        }
+        db::tasks::update_task_status(
+            &conn,
+            &task_id,
+            &task.status,
+            task.completed_at.as_deref(),
+            db::task_events::SOURCE_USER,
+            Some(&format!("Follow-up failed to start: {}", e)),
+        )?;
+        return Err(e);
+    }
+    lease.keep();
+    sandbox_guard.keep();
 
-#[tauri::command]
-async fn add_api_key(
-    provider: String,
-    key: String,
-    label: Option<String>,
-) -> Result<ApiKeyConfig, String> {
-    secure_storage::store_api_key(&provider, &key)?;
+    tracing::info!("[Tasks] Follow-up on task {} in session {}", task_id, session_id);
 
-    Ok(ApiKeyConfig {
-        id: format!("apikey-{}", provider),
-        provider: provider.clone(),
-        label,
-        created_at: chrono::Utc::now().to_rfc3339(),
+    Ok(Task {
+        id: task_id,
+        prompt: task.prompt,
+        status: "running".to_string(),
+        messages: vec![message],
+        result: None,
+        session_id: Some(session_id),
+        summary: task.summary,
+        title: task.title,
+        created_at: task.created_at,
+        updated_at: Some(chrono::Utc::now().to_rfc3339()),
+        completed_at: None,
+        started_at: task.started_at,
+        tags: Vec::new(),
     })
 }
 
-#[tauri::command]
-async fn remove_api_key(id: String) -> Result<(), String> {
-    // Extract provider from id (format: "apikey-{provider}")
-    let provider = id.strip_prefix("apikey-").unwrap_or(&id);
-    secure_storage::delete_api_key(provider)?;
-    Ok(())
+/// Settings a stored task ran with, for continuing or rerunning it
+struct StoredRunOptions {
+    working_directory: Option<String>,
+    web_search: Option<sidecar::WebSearchPayload>,
+    env: Option<HashMap<String, String>>,
+    agent_settings: Option<db::workspaces::AgentSettings>,
 }
 
-#[tauri::command]
-async fn get_debug_mode(state: State<'_, DbState>) -> Result<bool, String> {
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    Ok(db::settings::get_debug_mode(&conn))
+fn stored_run_options(
+    conn: &rusqlite::Connection,
+    task: &db::tasks::StoredTask,
+) -> Result<StoredRunOptions, String> {
+    let working_directory = db::tasks::get_working_directory(conn, &task.id)?;
+    let web_search = task.allow_web_search.map(|enabled| sidecar::WebSearchPayload {
+        enabled,
+        backend: db::settings::get_web_search_settings(conn).backend,
+    });
+    let env = task_env::resolve(conn, task.workspace_id.as_deref(), Some(&task.id))?;
+    let agent_settings = db::workspaces::task_agent_settings(conn, task.workspace_id.as_deref())?;
+    Ok(StoredRunOptions {
+        working_directory,
+        web_search,
+        env,
+        agent_settings,
+    })
 }
 
+/// Prompt that continues a plan-first task once its plan is approved
+const APPROVED_PLAN_PROMPT: &str =
+    "The plan above has been approved. Carry it out now, then summarize what you changed.";
+
+/// Get the proposed plan of a plan-first task
 #[tauri::command]
-async fn set_debug_mode(enabled: bool, state: State<'_, DbState>) -> Result<(), String> {
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    db::settings::set_debug_mode(&conn, enabled)
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn get_task_plan(
+    task_id: String,
+    state: State<'_, DbState>,
+) -> Result<Option<db::plans::TaskPlan>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::plans::get_plan(&conn, &task_id)
 }
 
+/// Approve a task's pending plan and continue its session with execution
+///
+/// Checked against spend limits like a new task; `override_budget` runs it over them.
 #[tauri::command]
-async fn get_app_settings(state: State<'_, DbState>) -> Result<AppSettingsResponse, String> {
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    let settings = db::settings::get_app_settings(&conn);
-    Ok(AppSettingsResponse {
-        debug_mode: settings.debug_mode,
-        onboarding_complete: settings.onboarding_complete,
-    })
-}
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn approve_task_plan(
+    task_id: String,
+    override_budget: Option<bool>,
+    app: tauri::AppHandle,
+    sidecar_state: State<'_, SidecarState>,
+    db_state: State<'_, DbState>,
+) -> Result<Task, String> {
+    let (task, plan, options, model) = {
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        let task = db::tasks::get_task(&conn, &task_id)
+            .ok_or_else(|| format!("Task not found: {}", task_id))?;
+        let plan = db::plans::get_plan(&conn, &task_id)?
+            .filter(|p| p.status == "pending")
+            .ok_or_else(|| format!("No pending plan for task: {}", task_id))?;
+        let options = stored_run_options(&conn, &task)?;
+        // The plan is carried out by the model and agent that wrote it
+        let model = stored_task_model(&conn, &task)?;
+        (task, plan, options, model)
+    };
+    let overridden = override_budget.unwrap_or(false);
+    check_budget(&app, &db_state, &task_id, model.model_id.as_deref(), overridden)?;
+    let lease = match &plan.session_id {
+        Some(session_id) => Some(session_lock::acquire(&db_state, session_id, &task_id)?),
+        None => None,
+    };
 
-// ============================================================================
-// API Key Management Commands
-// ============================================================================
+    // Planning only read the workspace, so its hooks run before the plan is carried out
+    if task.workspace_id.is_some() {
+        run_pre_task_hooks(&app, &task_id).await?;
+    }
+    let sandbox = start_sandbox(&app, &task_id, task.workspace_id.as_deref()).await?;
+    let sandbox_guard = sandbox::SandboxGuard::new(&app, &task_id);
+    let remote = remote::workspace_target(&app, task.workspace_id.as_deref())?;
+    let api_keys = sidecar_state.api_keys()?;
 
-#[tauri::command]
-async fn has_api_key() -> Result<bool, String> {
-    // Check for default provider (anthropic)
-    secure_storage::has_api_key("anthropic")
-}
+    let model_parameters = resolve_model_parameters(&db_state, model.model_id.as_deref());
+    let mut manager = sidecar_state.manager.lock().await;
+    if !manager.is_running() {
+        manager.spawn(&app).await?;
+    }
 
-#[tauri::command]
-async fn set_api_key(key: String) -> Result<(), String> {
-    // Set default provider key (anthropic)
-    secure_storage::store_api_key("anthropic", &key)
+    manager
+        .send_command(sidecar::SidecarCommand::StartTask {
+            task_id: task_id.clone(),
+            payload: sidecar::StartTaskPayload {
+                task_id: task_id.clone(),
+                prompt: APPROVED_PLAN_PROMPT.to_string(),
+                session_id: plan.session_id.clone(),
+                api_keys: Some(api_keys),
+                working_directory: options.working_directory,
+                model_id: model.model_id,
+                model_parameters,
+                plan_only: None,
+                agent: model.agent,
+                attachments: None,
+                web_search: options.web_search,
+                output_schema: None,
+                env: options.env,
+                sandbox,
+                remote,
+                agent_settings: options.agent_settings,
+            },
+        })
+        .await?;
+    if let Some(lease) = lease {
+        lease.keep();
+    }
+    sandbox_guard.keep();
+
+    {
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        db::plans::approve_plan(&conn, &task_id)?;
+        db::tasks::update_task_status(
+            &conn,
+            &task_id,
+            "running",
+            None,
+            db::task_events::SOURCE_USER,
+            Some("Plan approved"),
+        )?;
+    }
+
+    Ok(Task {
+        id: task_id,
+        prompt: task.prompt,
+        status: "running".to_string(),
+        messages: vec![],
+        result: None,
+        session_id: plan.session_id,
+        summary: task.summary,
+        title: task.title,
+        created_at: task.created_at,
+        updated_at: Some(chrono::Utc::now().to_rfc3339()),
+        completed_at: None,
+        started_at: task.started_at,
+        tags: Vec::new(),
+    })
 }
 
-#[tauri::command]
-async fn get_api_key() -> Result<Option<String>, String> {
-    // Get default provider key (anthropic)
-    secure_storage::get_api_key("anthropic")
+/// Prompt that continues an interrupted task's session from its checkpoint
+fn checkpoint_resume_prompt(checkpoint: &db::checkpoints::Checkpoint) -> String {
+    let mut prompt = String::from(
+        "This task was interrupted before it finished. Check the current state of the work, \
+then continue where you left off and finish the original request.",
+    );
+    if !checkpoint.files_touched.is_empty() {
+        prompt.push_str("\n\nFiles changed so far:\n");
+        for file in &checkpoint.files_touched {
+            prompt.push_str(&format!("- {}\n", file));
+        }
+    }
+    prompt
 }
 
+/// Get the latest checkpoint of a task
 #[tauri::command]
-async fn validate_api_key(_key: String) -> Result<ValidationResult, String> {
-    // Basic validation - check key format
-    Ok(ValidationResult {
-        valid: true,
-        error: None,
-    })
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn get_task_checkpoint(
+    task_id: String,
+    state: State<'_, DbState>,
+) -> Result<Option<db::checkpoints::Checkpoint>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::checkpoints::get_checkpoint(&conn, &task_id)
 }
 
+/// Continue a failed or interrupted task's session from its last checkpoint
 #[tauri::command]
-async fn validate_api_key_for_provider(
-    provider: String,
-    key: String,
-    _options: Option<HashMap<String, serde_json::Value>>,
-) -> Result<ValidationResult, String> {
-    // Validate API key format based on provider
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn resume_from_checkpoint(
+    task_id: String,
+    app: tauri::AppHandle,
+    sidecar_state: State<'_, SidecarState>,
+    db_state: State<'_, DbState>,
+) -> Result<Task, String> {
+    let (task, checkpoint, options, model) = {
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        let task = db::tasks::get_task(&conn, &task_id)
+            .ok_or_else(|| format!("Task not found: {}", task_id))?;
+        if matches!(task.status.as_str(), "starting" | "running" | "waiting_permission") {
+            return Err(format!("Task is still {}", task.status));
+        }
+        let checkpoint = db::checkpoints::get_checkpoint(&conn, &task_id)?
+            .ok_or_else(|| format!("No checkpoint for task: {}", task_id))?;
+        let options = stored_run_options(&conn, &task)?;
+        let model = stored_task_model(&conn, &task)?;
+        (task, checkpoint, options, model)
+    };
+    let session_id = checkpoint
+        .session_id
+        .clone()
+        .ok_or_else(|| "The task was checkpointed before it started a session".to_string())?;
+    let lease = session_lock::acquire(&db_state, &session_id, &task_id)?;
+
+    let sandbox = start_sandbox(&app, &task_id, task.workspace_id.as_deref()).await?;
+    let sandbox_guard = sandbox::SandboxGuard::new(&app, &task_id);
+    let remote = remote::workspace_target(&app, task.workspace_id.as_deref())?;
+    let api_keys = sidecar_state.api_keys()?;
+
+    let model_parameters = resolve_model_parameters(&db_state, model.model_id.as_deref());
+    let mut manager = sidecar_state.manager.lock().await;
+    if !manager.is_running() {
+        manager.spawn(&app).await?;
+    }
+
+    manager
+        .send_command(sidecar::SidecarCommand::StartTask {
+            task_id: task_id.clone(),
+            payload: sidecar::StartTaskPayload {
+                task_id: task_id.clone(),
+                prompt: checkpoint_resume_prompt(&checkpoint),
+                session_id: Some(session_id.clone()),
+                api_keys: Some(api_keys),
+                working_directory: options.working_directory,
+                model_id: model.model_id,
+                model_parameters,
+                plan_only: None,
+                agent: model.agent,
+                attachments: None,
+                web_search: options.web_search,
+                output_schema: None,
+                env: options.env,
+                sandbox,
+                remote,
+                agent_settings: options.agent_settings,
+            },
+        })
+        .await?;
+    lease.keep();
+    sandbox_guard.keep();
+
+    {
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        db::tasks::update_task_session_id(&conn, &task_id, &session_id)?;
+        db::tasks::update_task_status(
+            &conn,
+            &task_id,
+            "running",
+            None,
+            db::task_events::SOURCE_USER,
+            Some("Resumed from checkpoint"),
+        )?;
+    }
+    tracing::info!(
+        "[Checkpoint] Resuming {} from {} messages in session {}",
+        task_id,
+        checkpoint.message_count,
+        session_id
+    );
+
+    Ok(Task {
+        id: task_id,
+        prompt: task.prompt,
+        status: "running".to_string(),
+        messages: vec![],
+        result: None,
+        session_id: Some(session_id),
+        summary: task.summary,
+        title: task.title,
+        created_at: task.created_at,
+        updated_at: Some(chrono::Utc::now().to_rfc3339()),
+        completed_at: None,
+        started_at: task.started_at,
+        tags: Vec::new(),
+    })
+}
+
+/// Save a labelled checkpoint of a task at its current message, for `restore_checkpoint`
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn create_checkpoint(
+    task_id: String,
+    label: String,
+    state: State<'_, DbState>,
+    messages: State<'_, MessageBufferState>,
+) -> Result<db::checkpoints::SavedCheckpoint, String> {
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    messages.flush_task(&mut conn, &task_id);
+    let task = db::tasks::get_task_without_messages(&conn, &task_id)
+        .ok_or_else(|| format!("Task not found: {}", task_id))?;
+    let message_count = db::tasks::count_messages(&conn, &task_id)?;
+    if message_count == 0 {
+        return Err("The task has no messages to checkpoint yet".to_string());
+    }
+    // A running task's session is only stored once the run ends
+    let session_id = checkpoint::session_id(&task_id).or(task.session_id);
+    db::checkpoints::add_saved_checkpoint(
+        &conn,
+        &task_id,
+        &label,
+        session_id.as_deref(),
+        message_count.into(),
+    )
+}
+
+/// A task's saved checkpoints, earliest in the conversation first
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn list_checkpoints(
+    task_id: String,
+    state: State<'_, DbState>,
+) -> Result<Vec<db::checkpoints::SavedCheckpoint>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::checkpoints::list_saved_checkpoints(&conn, &task_id)
+}
+
+/// Delete a saved checkpoint
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn delete_checkpoint(checkpoint_id: String, state: State<'_, DbState>) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::checkpoints::delete_saved_checkpoint(&conn, &checkpoint_id)
+}
+
+/// Instruction sent after the kept transcript when a task is rewound to a checkpoint
+const RESTORE_SEED_PROMPT: &str = "The conversation above was rewound to this point; anything after it no longer applies. \
+Do not take any action yet; reply with one sentence summarizing where things stand and wait for the next instruction.";
+
+/// Rewind a task to a saved checkpoint
+///
+/// Messages after the checkpoint are archived and later checkpoints deleted.
+/// OpenCode sessions cannot be rewound, so when the task went on past the
+/// checkpoint a new session is started seeded with the kept messages, and the
+/// history is only rewound once it has started; otherwise the task goes back to
+/// the checkpoint's session as it was.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn restore_checkpoint(
+    task_id: String,
+    checkpoint_id: String,
+    app: tauri::AppHandle,
+    sidecar_state: State<'_, SidecarState>,
+    db_state: State<'_, DbState>,
+    messages: State<'_, MessageBufferState>,
+) -> Result<Task, String> {
+    let (task, saved, kept, current_session, options, model) = {
+        let mut conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        let saved = db::checkpoints::get_saved_checkpoint(&conn, &checkpoint_id)?
+            .filter(|c| c.task_id == task_id)
+            .ok_or_else(|| format!("Checkpoint not found: {}", checkpoint_id))?;
+        messages.flush_task(&mut conn, &task_id);
+        let mut task = db::tasks::get_task(&conn, &task_id)
+            .ok_or_else(|| format!("Task not found: {}", task_id))?;
+        if matches!(
+            task.status.as_str(),
+            "starting" | "running" | "queued" | "waiting_permission"
+        ) {
+            return Err("Stop the task before restoring a checkpoint".to_string());
+        }
+
+        let keep = usize::try_from(saved.message_count).unwrap_or(0);
+        if task.messages.len() <= keep {
+            db::checkpoints::delete_saved_checkpoints_after(&conn, &task_id, saved.message_count)?;
+            // The automatic checkpoint is past this point too
+            db::checkpoints::delete_checkpoint(&conn, &task_id)?;
+            if let Some(session_id) = &saved.session_id {
+                db::tasks::update_task_session_id(&conn, &task_id, session_id)?;
+                task.session_id = Some(session_id.clone());
+            }
+            tracing::info!("[Checkpoint] Restored {} to {}", task_id, saved.label);
+            return Ok(listed_task(task, &mut HashMap::new()));
+        }
+
+        let current_session = db::tasks::get_task_result(&conn, &task_id)?
+            .and_then(|r| r.session_id)
+            .or_else(|| task.session_id.clone());
+        let options = stored_run_options(&conn, &task)?;
+        let model = stored_task_model(&conn, &task)?;
+        task.messages.truncate(keep);
+        let kept = std::mem::take(&mut task.messages);
+        (task, saved, kept, current_session, options, model)
+    };
+    // Nothing may continue the session being left behind while the task is rewound
+    let lease = match &current_session {
+        Some(session_id) => Some(session_lock::acquire(&db_state, session_id, &task_id)?),
+        None => None,
+    };
+
+    let sandbox = start_sandbox(&app, &task_id, task.workspace_id.as_deref()).await?;
+    let sandbox_guard = sandbox::SandboxGuard::new(&app, &task_id);
+    let remote = remote::workspace_target(&app, task.workspace_id.as_deref())?;
+    let api_keys = sidecar_state.api_keys()?;
+
+    let model_parameters = resolve_model_parameters(&db_state, model.model_id.as_deref());
+    let mut manager = sidecar_state.manager.lock().await;
+    if !manager.is_running() {
+        manager.spawn(&app).await?;
+    }
+
+    manager
+        .send_command(sidecar::SidecarCommand::StartTask {
+            task_id: task_id.clone(),
+            payload: sidecar::StartTaskPayload {
+                task_id: task_id.clone(),
+                prompt: conversation_transcript(&kept) + RESTORE_SEED_PROMPT,
+                session_id: None,
+                api_keys: Some(api_keys),
+                working_directory: options.working_directory,
+                model_id: model.model_id,
+                model_parameters,
+                plan_only: None,
+                agent: model.agent,
+                attachments: None,
+                web_search: options.web_search,
+                output_schema: None,
+                env: options.env,
+                sandbox,
+                remote,
+                agent_settings: options.agent_settings,
+            },
+        })
+        .await?;
+    drop(manager);
+    if let Some(lease) = lease {
+        lease.keep();
+    }
+    sandbox_guard.keep();
+
+    // The history is only rewound once the new session has started, all at once
+    {
+        let mut conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to begin restore transaction: {}", e))?;
+        db::tasks::archive_messages_after(&tx, &task_id, kept.len() as u32)?;
+        db::checkpoints::delete_saved_checkpoints_after(&tx, &task_id, saved.message_count)?;
+        db::checkpoints::delete_checkpoint(&tx, &task_id)?;
+        db::tasks::clear_task_session(&tx, &task_id)?;
+        db::tasks::update_task_status(
+            &tx,
+            &task_id,
+            "running",
+            None,
+            db::task_events::SOURCE_USER,
+            Some(&format!("Restored to checkpoint {}", saved.label)),
+        )?;
+        tx.commit().map_err(|e| format!("Failed to commit checkpoint restore: {}", e))?;
+    }
+
+    tracing::info!("[Checkpoint] Rewound {} to {} messages", task_id, kept.len());
+
+    Ok(Task {
+        id: task_id,
+        prompt: task.prompt,
+        status: "running".to_string(),
+        messages: kept
+            .into_iter()
+            .map(|m| TaskMessage::from_stored(m, false))
+            .collect(),
+        result: None,
+        session_id: None,
+        summary: task.summary,
+        title: task.title,
+        created_at: task.created_at,
+        updated_at: Some(chrono::Utc::now().to_rfc3339()),
+        completed_at: None,
+        started_at: task.started_at,
+        tags: Vec::new(),
+    })
+}
+
+/// Instruction sent after the copied transcript when a fork's session is created
+const FORK_SEED_PROMPT: &str = "The conversation above was copied from another thread and continues here. \
+Do not take any action yet; reply with one sentence summarizing where things stand and wait for the next instruction.";
+
+/// Messages as a `<conversation>` block, for seeding a new session with history
+fn conversation_transcript(messages: &[db::tasks::StoredTaskMessage]) -> String {
+    let mut prompt = String::from("<conversation>\n");
+    for message in messages {
+        match (&message.tool_name, &message.tool_input) {
+            (Some(tool), Some(input)) => prompt.push_str(&format!("[tool: {}] {}\n\n", tool, input)),
+            (Some(tool), None) => prompt.push_str(&format!("[tool: {}]\n\n", tool)),
+            _ => prompt.push_str(&format!("[{}] {}\n\n", message.msg_type, message.content)),
+        }
+    }
+    prompt.push_str("</conversation>\n\n");
+    prompt
+}
+
+/// Branch a task at a message into a new task with its own session
+///
+/// Messages up to and including `message_id` are copied into the new task, and
+/// a new session is started with that transcript so follow-ups continue from
+/// there. The original task is left untouched, and may still be running.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn fork_task(
+    task_id: String,
+    message_id: String,
+    app: tauri::AppHandle,
+    sidecar_state: State<'_, SidecarState>,
+    db_state: State<'_, DbState>,
+    messages: State<'_, MessageBufferState>,
+) -> Result<Task, String> {
+    let fork_id = format!("task_{}", uuid::Uuid::new_v4());
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let (fork, options, model) = {
+        let mut conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        // A running parent's latest messages may not be stored yet
+        messages.flush_task(&mut conn, &task_id);
+        let parent = db::tasks::get_task(&conn, &task_id)
+            .ok_or_else(|| format!("Task not found: {}", task_id))?;
+        let position = parent
+            .messages
+            .iter()
+            .position(|m| m.id == message_id)
+            .ok_or_else(|| format!("Message not found: {}", message_id))?;
+        let options = stored_run_options(&conn, &parent)?;
+        // The fork carries on with the model and agent the parent ran on
+        let model = stored_task_model(&conn, &parent)?;
+
+        let fork = db::tasks::StoredTask {
+            id: fork_id.clone(),
+            prompt: parent.prompt.clone(),
+            summary: parent.summary.clone(),
+            title: parent.title.clone(),
+            status: "starting".to_string(),
+            messages: parent.messages[..=position]
+                .iter()
+                .map(|m| db::tasks::StoredTaskMessage {
+                    id: format!("msg_{}", uuid::Uuid::new_v4()),
+                    ..m.clone()
+                })
+                .collect(),
+            session_id: None,
+            created_at: now.clone(),
+            started_at: Some(now.clone()),
+            completed_at: None,
+            updated_at: Some(now.clone()),
+            workspace_id: parent.workspace_id.clone(),
+            allow_web_search: parent.allow_web_search,
+        };
+        (fork, options, model)
+    };
+
+    check_budget(&app, &db_state, &fork_id, model.model_id.as_deref(), false)?;
+
+    {
+        let mut conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        db::tasks::save_stored_task(&conn, &fork)?;
+        if let Some(directory) = options.working_directory.as_deref() {
+            db::tasks::set_working_directory(&conn, &fork_id, directory)?;
+        }
+        db::forks::record_fork(&conn, &fork_id, &task_id, &message_id)?;
+        if let Some(project_id) = db::projects::get_task_project(&conn, &task_id)? {
+            db::projects::move_tasks(&conn, std::slice::from_ref(&fork_id), Some(&project_id))?;
+        }
+        task_env::copy(&mut conn, &task_id, &fork_id)?;
+        db::tasks::set_task_model(&conn, &fork_id, &model)?;
+    }
+
+    let started: Result<(), String> = async {
+        let sandbox = start_sandbox(&app, &fork_id, fork.workspace_id.as_deref()).await?;
+        let sandbox_guard = sandbox::SandboxGuard::new(&app, &fork_id);
+        let remote = remote::workspace_target(&app, fork.workspace_id.as_deref())?;
+        let api_keys = sidecar_state.api_keys()?;
+
+        let model_parameters = resolve_model_parameters(&db_state, model.model_id.as_deref());
+        let mut manager = sidecar_state.manager.lock().await;
+        if !manager.is_running() {
+            manager.spawn(&app).await?;
+        }
+
+        manager
+            .send_command(sidecar::SidecarCommand::StartTask {
+                task_id: fork_id.clone(),
+                payload: sidecar::StartTaskPayload {
+                    task_id: fork_id.clone(),
+                    prompt: conversation_transcript(&fork.messages) + FORK_SEED_PROMPT,
+                    session_id: None,
+                    api_keys: Some(api_keys),
+                    working_directory: options.working_directory,
+                    model_id: model.model_id,
+                    model_parameters,
+                    plan_only: None,
+                    agent: model.agent,
+                    attachments: None,
+                    web_search: options.web_search,
+                    output_schema: None,
+                    env: options.env,
+                    sandbox,
+                    remote,
+                    agent_settings: options.agent_settings,
+                },
+            })
+            .await?;
+        sandbox_guard.keep();
+        Ok(())
+    }
+    .await;
+
+    if let Err(e) = started {
+        // The fork never ran, so it isn't left in the history
+        messages.discard_task(&fork_id);
+        let mut conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        if let Err(e) = db::tasks::delete_task(&conn, &fork_id) {
+            tracing::warn!("[Fork] Failed to remove fork {}: {}", fork_id, e);
+        }
+        if let Err(e) = task_env::set(&mut conn, &fork_id, &[]) {
+            tracing::warn!("[Env] Failed to remove variables of task {}: {}", fork_id, e);
+        }
+        return Err(e);
+    }
+
+    tracing::info!("[Fork] Forked task {} at {} into {}", task_id, message_id, fork_id);
+
+    Ok(Task {
+        id: fork_id,
+        prompt: fork.prompt,
+        status: "starting".to_string(),
+        messages: fork
+            .messages
+            .into_iter()
+            .map(|m| TaskMessage::from_stored(m, false))
+            .collect(),
+        result: None,
+        session_id: None,
+        summary: fork.summary,
+        title: fork.title,
+        created_at: now.clone(),
+        updated_at: None,
+        completed_at: None,
+        started_at: Some(now),
+        tags: Vec::new(),
+    })
+}
+
+/// Parent and child fork links of a task
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn get_task_forks(
+    task_id: String,
+    state: State<'_, DbState>,
+) -> Result<db::forks::TaskForks, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::forks::get_forks(&conn, &task_id)
+}
+
+/// Start a fresh task with another task's prompt, directory, model and settings
+///
+/// The model and agent are the ones the original started with, or the current
+/// defaults for tasks from before those were recorded. Its environment
+/// variables and output schema are copied too, and the new task records the
+/// one it re-runs.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn rerun_task(
+    task_id: String,
+    app: tauri::AppHandle,
+    sidecar_state: State<'_, SidecarState>,
+    db_state: State<'_, DbState>,
+) -> Result<Task, String> {
+    let rerun_id = format!("task_{}", uuid::Uuid::new_v4());
+
+    let (config, model) = {
+        let mut conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        let parent = db::tasks::get_task_without_messages(&conn, &task_id)
+            .ok_or_else(|| format!("Task not found: {}", task_id))?;
+        let mut model = db::tasks::get_task_model(&conn, &task_id)?;
+        if model.model_id.is_none() {
+            model.model_id = resolve_task_model_id(&conn, parent.workspace_id.as_deref());
+        }
+        let output_schema = db::structured::get(&conn, &task_id)?.map(|s| s.schema);
+        let project_id = db::projects::get_task_project(&conn, &task_id)?;
+        // The new task's variables are in place before it starts, as for a fork
+        task_env::copy(&mut conn, &task_id, &rerun_id)?;
+
+        let config = TaskConfig {
+            prompt: parent.prompt,
+            task_id: Some(rerun_id.clone()),
+            override_budget: None,
+            workspace_id: parent.workspace_id,
+            working_directory: None,
+            plan_first: None,
+            attachments: None,
+            allow_web_search: parent.allow_web_search,
+            output_schema,
+            env: None,
+            project_id,
+        };
+        (config, model)
+    };
+
+    let launched =
+        launch_task(config, model.model_id, model.agent, &app, &sidecar_state, &db_state).await;
+    let mut conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+    let task = match launched {
+        Ok(task) => task,
+        Err(e) => {
+            // Don't leave copied secrets behind for a task that was never created
+            if !db::tasks::task_exists(&conn, &rerun_id) {
+                if let Err(e) = task_env::set(&mut conn, &rerun_id, &[]) {
+                    tracing::warn!("[Env] Failed to remove variables of task {}: {}", rerun_id, e);
+                }
+            }
+            return Err(e);
+        }
+    };
+    db::reruns::record_rerun(&conn, &rerun_id, &task_id)?;
+
+    tracing::info!("[Tasks] Re-running task {} as {}", task_id, rerun_id);
+    Ok(task)
+}
+
+/// The task a task re-runs and the re-runs made of it
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn get_task_reruns(
+    task_id: String,
+    state: State<'_, DbState>,
+) -> Result<db::reruns::TaskReruns, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::reruns::get_reruns(&conn, &task_id)
+}
+
+/// Remove a message from a saved transcript (e.g. noisy tool output)
+///
+/// Only the stored transcript loses it: the agent session still holds the
+/// message, and follow-ups continuing that session still see it. Syncing the
+/// session doesn't add it back.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn delete_task_message(
+    task_id: String,
+    message_id: String,
+    state: State<'_, DbState>,
+    messages: State<'_, MessageBufferState>,
+) -> Result<(), String> {
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    // The message may still be waiting in the buffer
+    messages.flush_task(&mut conn, &task_id);
+    db::tasks::delete_message(&conn, &task_id, &message_id)?;
+    mirror_to_sync_folder(&conn, &task_id);
+    Ok(())
+}
+
+/// Blank a message that shouldn't be kept (e.g. a secret in tool output)
+///
+/// Follow-ups and exports build on the stored transcript, so they no longer
+/// see it; an agent session that already holds the message still does.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn redact_task_message(
+    task_id: String,
+    message_id: String,
+    state: State<'_, DbState>,
+    messages: State<'_, MessageBufferState>,
+) -> Result<(), String> {
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    messages.flush_task(&mut conn, &task_id);
+    db::tasks::redact_message(&conn, &task_id, &message_id)?;
+    mirror_to_sync_folder(&conn, &task_id);
+    Ok(())
+}
+
+/// Attach a note to a message; an empty note removes it
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn annotate_message(
+    message_id: String,
+    note: String,
+    state: State<'_, DbState>,
+) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let note = note.trim();
+    db::tasks::set_message_note(&conn, &message_id, (!note.is_empty()).then_some(note))
+}
+
+/// Rate an assistant message "up" or "down" with an optional comment; no rating removes it
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn rate_message(
+    message_id: String,
+    rating: Option<String>,
+    comment: Option<String>,
+    state: State<'_, DbState>,
+    messages: State<'_, MessageBufferState>,
+) -> Result<Option<db::ratings::MessageRating>, String> {
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    // The message may still be waiting in the buffer
+    messages.flush(&mut conn);
+    db::ratings::rate_message(&conn, &message_id, rating.as_deref(), comment.as_deref())
+}
+
+/// Rated messages with their tasks, newest first; only "up" or "down" ones if given
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn list_message_ratings(
+    rating: Option<String>,
+    state: State<'_, DbState>,
+) -> Result<Vec<db::ratings::RatedMessage>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::ratings::list_ratings(&conn, rating.as_deref())
+}
+
+/// A task's notes, oldest first
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_task_notes(
+    task_id: String,
+    state: State<'_, DbState>,
+) -> Result<Vec<db::notes::TaskNote>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::notes::get_task_notes(&conn, &task_id)
+}
+
+/// Add a note to a task, optionally with an RFC 3339 follow-up time
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn add_task_note(
+    task_id: String,
+    content: String,
+    follow_up_at: Option<String>,
+    state: State<'_, DbState>,
+) -> Result<db::notes::TaskNote, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::notes::add_note(&conn, &task_id, &content, follow_up_at.as_deref())
+}
+
+/// Change a note's text and follow-up time; no time removes the follow-up
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn update_task_note(
+    note_id: String,
+    content: String,
+    follow_up_at: Option<String>,
+    state: State<'_, DbState>,
+) -> Result<db::notes::TaskNote, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::notes::update_note(&conn, &note_id, &content, follow_up_at.as_deref())
+}
+
+/// Delete a note
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn delete_task_note(note_id: String, state: State<'_, DbState>) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::notes::delete_note(&conn, &note_id)
+}
+
+/// Follow-ups across tasks that haven't been reminded yet, soonest first
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn list_follow_ups(state: State<'_, DbState>) -> Result<Vec<db::notes::FollowUp>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::notes::get_pending_follow_ups(&conn, None)
+}
+
+/// Projects by name, with how many tasks each has
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn list_projects(state: State<'_, DbState>) -> Result<Vec<db::projects::Project>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::projects::list_projects(&conn)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn create_project(
+    name: String,
+    state: State<'_, DbState>,
+) -> Result<db::projects::Project, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::projects::create_project(&conn, &name)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn rename_project(
+    project_id: String,
+    name: String,
+    state: State<'_, DbState>,
+) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::projects::rename_project(&conn, &project_id, &name)
+}
+
+/// Delete a project, leaving its tasks unfiled
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn delete_project(project_id: String, state: State<'_, DbState>) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::projects::delete_project(&conn, &project_id)
+}
+
+/// File tasks under a project, or unfile them without one; returns how many moved
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn move_tasks_to_project(
+    task_ids: Vec<String>,
+    project_id: Option<String>,
+    state: State<'_, DbState>,
+) -> Result<usize, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::projects::move_tasks(&conn, &task_ids, project_id.as_deref())
+}
+
+/// Tag a task, returning its tags
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn add_task_tag(
+    task_id: String,
+    tag: String,
+    state: State<'_, DbState>,
+) -> Result<Vec<String>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::tags::add_tag(&conn, &task_id, &tag)
+}
+
+/// Take a tag off a task, returning its remaining tags
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn remove_task_tag(
+    task_id: String,
+    tag: String,
+    state: State<'_, DbState>,
+) -> Result<Vec<String>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::tags::remove_tag(&conn, &task_id, &tag)
+}
+
+/// Every tag in use, with how many tasks carry it
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn list_tags(state: State<'_, DbState>) -> Result<Vec<db::tags::TagCount>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::tags::list_tags(&conn)
+}
+
+/// Edit a user message and rerun the task from it
+///
+/// Later messages are archived and the edited message's previous content is
+/// kept as a version. OpenCode sessions cannot be rewound, so the rerun starts
+/// a new session seeded with the messages before the edit and the old one is
+/// forgotten. Nothing is changed unless the rerun starts.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn edit_message_and_rerun(
+    task_id: String,
+    message_id: String,
+    new_content: String,
+    app: tauri::AppHandle,
+    sidecar_state: State<'_, SidecarState>,
+    db_state: State<'_, DbState>,
+) -> Result<Task, String> {
+    if new_content.trim().is_empty() {
+        return Err("Message cannot be empty".to_string());
+    }
+
+    // The history is truncated and the task claimed before the rerun starts,
+    // under one lock, so no other run can start or save messages in between
+    let (task, earlier, edited, edited_at, options, model) = {
+        let mut conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        let task = db::tasks::get_task(&conn, &task_id)
+            .ok_or_else(|| format!("Task not found: {}", task_id))?;
+        if matches!(
+            task.status.as_str(),
+            "starting" | "running" | "queued" | "waiting_permission"
+        ) {
+            return Err("Stop the task before editing its messages".to_string());
+        }
+        let position = task
+            .messages
+            .iter()
+            .position(|m| m.id == message_id)
+            .ok_or_else(|| format!("Message not found: {}", message_id))?;
+        if task.messages[position].msg_type != "user" {
+            return Err("Only user messages can be edited".to_string());
+        }
+
+        let options = stored_run_options(&conn, &task)?;
+        let earlier = task.messages[..position].to_vec();
+        let edited = task.messages[position].clone();
+        let model = stored_task_model(&conn, &task)?;
+
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to begin edit transaction: {}", e))?;
+        let edited_at = db::tasks::edit_message(&tx, &task_id, &message_id, &new_content)?;
+        db::tasks::update_task_status(
+            &tx,
+            &task_id,
+            "starting",
+            None,
+            db::task_events::SOURCE_USER,
+            Some("Message edited and rerun"),
+        )?;
+        tx.commit().map_err(|e| format!("Failed to commit message edit: {}", e))?;
+        (task, earlier, edited, edited_at, options, model)
+    };
+
+    let prompt = if earlier.is_empty() {
+        new_content.clone()
+    } else {
+        conversation_transcript(&earlier) + &new_content
+    };
+    let started: Result<(), String> = async {
+        let sandbox = start_sandbox(&app, &task_id, task.workspace_id.as_deref()).await?;
+        let sandbox_guard = sandbox::SandboxGuard::new(&app, &task_id);
+        let remote = remote::workspace_target(&app, task.workspace_id.as_deref())?;
+        let api_keys = sidecar_state.api_keys()?;
+
+        let model_parameters = resolve_model_parameters(&db_state, model.model_id.as_deref());
+        let mut manager = sidecar_state.manager.lock().await;
+        if !manager.is_running() {
+            manager.spawn(&app).await?;
+        }
+
+        // A new session, seeded with the transcript up to the edit
+        manager
+            .send_command(sidecar::SidecarCommand::StartTask {
+                task_id: task_id.clone(),
+                payload: sidecar::StartTaskPayload {
+                    task_id: task_id.clone(),
+                    prompt,
+                    session_id: None,
+                    api_keys: Some(api_keys),
+                    working_directory: options.working_directory,
+                    model_id: model.model_id,
+                    model_parameters,
+                    plan_only: None,
+                    agent: model.agent,
+                    attachments: None,
+                    web_search: options.web_search,
+                    output_schema: None,
+                    env: options.env,
+                    sandbox,
+                    remote,
+                    agent_settings: options.agent_settings,
+                },
+            })
+            .await?;
+        sandbox_guard.keep();
+        Ok(())
+    }
+    .await;
+
+    if let Err(e) = started {
+        // Nothing ran, so the history and status go back to how they were
+        let mut conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        let restored = conn
+            .transaction()
+            .map_err(|e| format!("Failed to begin undo transaction: {}", e))
+            .and_then(|tx| {
+                db::tasks::undo_message_edit(
+                    &tx,
+                    &task_id,
+                    &message_id,
+                    &edited.timestamp,
+                    &edited_at,
+                )?;
+                db::tasks::update_task_status(
+                    &tx,
+                    &task_id,
+                    &task.status,
+                    None,
+                    db::task_events::SOURCE_USER,
+                    Some("Rerun failed to start"),
+                )?;
+                tx.commit().map_err(|e| format!("Failed to commit undo: {}", e))
+            });
+        if let Err(undo) = restored {
+            tracing::error!("[Tasks] Failed to undo edit of {}: {}", message_id, undo);
+        }
+        return Err(e);
+    }
+
+    {
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        db::tasks::clear_task_session(&conn, &task_id)?;
+        db::tasks::update_task_status(
+            &conn,
+            &task_id,
+            "running",
+            None,
+            db::task_events::SOURCE_USER,
+            Some("Message edited and rerun"),
+        )?;
+    }
+
+    tracing::info!("[Tasks] Rerunning task {} from edited message {}", task_id, message_id);
+
+    let edited = TaskMessage {
+        id: message_id,
+        msg_type: "user".to_string(),
+        content: new_content,
+        timestamp: edited_at,
+        tool_name: None,
+        tool_input: None,
+        attachments: None,
+        note: edited.note,
+        origin_id: None,
+    };
+    Ok(Task {
+        id: task_id,
+        prompt: task.prompt,
+        status: "running".to_string(),
+        messages: earlier
+            .into_iter()
+            .map(|m| TaskMessage::from_stored(m, false))
+            .chain(std::iter::once(edited))
+            .collect(),
+        result: None,
+        session_id: None,
+        summary: task.summary,
+        title: task.title,
+        created_at: task.created_at,
+        updated_at: Some(chrono::Utc::now().to_rfc3339()),
+        completed_at: None,
+        started_at: task.started_at,
+        tags: Vec::new(),
+    })
+}
+
+/// Earlier contents of an edited message, oldest first
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_message_versions(
+    message_id: String,
+    state: State<'_, DbState>,
+) -> Result<Vec<db::tasks::MessageVersion>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::tasks::get_message_versions(&conn, &message_id)
+}
+
+// ============================================================================
+// Comparison Commands
+// ============================================================================
+
+/// Start the same prompt on several models as linked tasks
+///
+/// Models that fail to start are skipped; the command only fails if none start.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn start_comparison_task(
+    prompt: String,
+    models: Vec<String>,
+    app: tauri::AppHandle,
+    sidecar_state: State<'_, SidecarState>,
+    db_state: State<'_, DbState>,
+) -> Result<db::comparisons::Comparison, String> {
+    let mut unique_models: Vec<String> = Vec::new();
+    for model in models {
+        if db::usage::provider_of_model(&model).is_none() {
+            return Err(format!("Expected a provider/model ID: {}", model));
+        }
+        if !unique_models.contains(&model) {
+            unique_models.push(model);
+        }
+    }
+    if unique_models.len() < 2 {
+        return Err("A comparison needs at least two models".to_string());
+    }
+
+    let comparison_id = format!("cmp_{}", uuid::Uuid::new_v4());
+    let mut first_error = None;
+    let mut started = 0;
+    for (position, model_id) in unique_models.into_iter().enumerate() {
+        let config = TaskConfig {
+            prompt: prompt.clone(),
+            task_id: None,
+            override_budget: None,
+            workspace_id: None,
+            working_directory: None,
+            plan_first: None,
+            attachments: None,
+            allow_web_search: None,
+            output_schema: None,
+            env: None,
+            project_id: None,
+        };
+        let launched =
+            launch_task(config, Some(model_id.clone()), None, &app, &sidecar_state, &db_state).await;
+        match launched {
+            Ok(task) => {
+                let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+                db::comparisons::add_task(&conn, &comparison_id, &task.id, &model_id, position)?;
+                started += 1;
+            }
+            Err(e) => {
+                tracing::warn!("[Comparison] Failed to start {} on {}: {}", comparison_id, model_id, e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    if started == 0 {
+        return Err(first_error.unwrap_or_else(|| "No comparison tasks started".to_string()));
+    }
+
+    let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+    db::comparisons::get_comparison(&conn, &comparison_id)?
+        .ok_or_else(|| format!("Comparison not found: {}", comparison_id))
+}
+
+/// Results of a comparison side by side, with timing and cost per model
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_comparison(
+    comparison_id: String,
+    state: State<'_, DbState>,
+) -> Result<Option<db::comparisons::Comparison>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::comparisons::get_comparison(&conn, &comparison_id)
+}
+
+/// Compare two finished runs, e.g. before and after a prompt or model change
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn diff_tasks(
+    task_a: String,
+    task_b: String,
+    state: State<'_, DbState>,
+) -> Result<db::comparisons::TaskDiff, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::comparisons::diff_tasks(&conn, &task_a, &task_b)
+}
+
+// ============================================================================
+// Settings Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_api_keys() -> Result<Vec<ApiKeyConfig>, String> {
+    let status = secure_storage::get_all_api_key_status()?;
+    let mut keys = Vec::new();
+
+    for (provider, key_status) in status {
+        if key_status.exists {
+            keys.push(ApiKeyConfig {
+                id: format!("apikey-{}", provider),
+                provider: provider.clone(),
+                label: Some(provider),
+                created_at: chrono::Utc::now().to_rfc3339(),
+            });
+        }
+    }
+
+    Ok(keys)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn add_api_key(
+    provider: String,
+    key: String,
+    label: Option<String>,
+    sidecar_state: State<'_, SidecarState>,
+) -> Result<ApiKeyConfig, String> {
+    secure_storage::store_api_key(&provider, &key)?;
+    sidecar_state.invalidate_api_keys();
+
+    Ok(ApiKeyConfig {
+        id: format!("apikey-{}", provider),
+        provider: provider.clone(),
+        label,
+        created_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn remove_api_key(id: String, sidecar_state: State<'_, SidecarState>) -> Result<(), String> {
+    // Extract provider from id (format: "apikey-{provider}")
+    let provider = id.strip_prefix("apikey-").unwrap_or(&id);
+    secure_storage::delete_api_key(provider)?;
+    sidecar_state.invalidate_api_keys();
+    Ok(())
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_debug_mode(state: State<'_, DbState>) -> Result<bool, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    Ok(db::settings::get_debug_mode(&conn))
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_debug_mode(enabled: bool, state: State<'_, DbState>) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::settings::set_debug_mode(&conn, enabled)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_app_settings(state: State<'_, DbState>) -> Result<AppSettingsResponse, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    let settings = db::settings::get_app_settings(&conn);
+    Ok(AppSettingsResponse {
+        debug_mode: settings.debug_mode,
+        onboarding_complete: settings.onboarding_complete,
+    })
+}
+
+/// The workspace open when the app last ran, if it still exists
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_last_workspace(
+    state: State<'_, DbState>,
+) -> Result<Option<db::workspaces::Workspace>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    match db::settings::get_last_workspace_id(&conn) {
+        Some(id) => db::workspaces::get_workspace(&conn, &id),
+        None => Ok(None),
+    }
+}
+
+/// Workspaces to pick from, by name
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn list_workspaces(
+    state: State<'_, DbState>,
+) -> Result<Vec<db::workspaces::Workspace>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::workspaces::list_workspaces(&conn)
+}
+
+/// Create a workspace for a local directory, named after the folder unless a name is given
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn create_workspace(
+    path: String,
+    name: Option<String>,
+    state: State<'_, DbState>,
+) -> Result<db::workspaces::Workspace, String> {
+    let detected = workspace_detect::detect(&path).await;
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let workspace = db::workspaces::create_local_workspace(&conn, &path, name.as_deref())?;
+    if let Some(detected) = &detected {
+        workspace_detect::remember(&conn, &workspace, detected)?;
+    }
+    tracing::info!("[Workspaces] Created {} for {}", workspace.id, workspace.path);
+    Ok(workspace)
+}
+
+/// The workspace tasks in a directory would run in, from its git repository
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn detect_workspace(
+    path: String,
+    state: State<'_, DbState>,
+) -> Result<workspace_detect::WorkspaceSuggestion, String> {
+    let detected = workspace_detect::detect(&path).await;
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    workspace_detect::suggest(&conn, &path, detected)
+}
+
+/// Make a workspace the one new tasks run in, remembered across restarts
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn select_workspace(
+    workspace_id: String,
+    state: State<'_, DbState>,
+) -> Result<db::workspaces::Workspace, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let workspace = db::workspaces::get_workspace(&conn, &workspace_id)?
+        .ok_or_else(|| format!("Workspace not found: {}", workspace_id))?;
+    db::settings::set_last_workspace_id(&conn, Some(&workspace.id))?;
+    Ok(workspace)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_last_workspace(
+    workspace_id: Option<String>,
+    state: State<'_, DbState>,
+) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::settings::set_last_workspace_id(&conn, workspace_id.as_deref())
+}
+
+// ============================================================================
+// API Key Management Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn has_api_key() -> Result<bool, String> {
+    // Check for default provider (anthropic)
+    secure_storage::has_api_key("anthropic")
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_api_key(key: String, sidecar_state: State<'_, SidecarState>) -> Result<(), String> {
+    // Set default provider key (anthropic)
+    secure_storage::store_api_key("anthropic", &key)?;
+    sidecar_state.invalidate_api_keys();
+    Ok(())
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_api_key() -> Result<Option<String>, String> {
+    // Get default provider key (anthropic)
+    secure_storage::get_api_key("anthropic")
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn validate_api_key(_key: String) -> Result<ValidationResult, String> {
+    // Basic validation - check key format
+    Ok(ValidationResult {
+        valid: true,
+        error: None,
+    })
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn validate_api_key_for_provider(
+    provider: String,
+    key: String,
+    _options: Option<HashMap<String, serde_json::Value>>,
+) -> Result<ValidationResult, String> {
+    // Validate API key format based on provider
     let valid = match provider.as_str() {
         "anthropic" => key.starts_with("sk-ant-"),
         "openai" => key.starts_with("sk-"),
@@ -800,660 +3241,2359 @@ async fn validate_api_key_for_provider(
         _ => !key.is_empty(),
     };
 
-    if valid {
-        Ok(ValidationResult {
-            valid: true,
-            error: None,
-        })
+    if valid {
+        Ok(ValidationResult {
+            valid: true,
+            error: None,
+        })
+    } else {
+        Ok(ValidationResult {
+            valid: false,
+            error: Some(format!("Invalid API key format for provider: {}", provider)),
+        })
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn clear_api_key(sidecar_state: State<'_, SidecarState>) -> Result<(), String> {
+    // Clear default provider key (anthropic)
+    secure_storage::delete_api_key("anthropic")?;
+    sidecar_state.invalidate_api_keys();
+    Ok(())
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_all_api_keys() -> Result<HashMap<String, ApiKeyStatus>, String> {
+    let status = secure_storage::get_all_api_key_status()?;
+    Ok(status
+        .into_iter()
+        .map(|(k, v)| {
+            (
+                k,
+                ApiKeyStatus {
+                    exists: v.exists,
+                    prefix: v.prefix,
+                },
+            )
+        })
+        .collect())
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn has_any_api_key() -> Result<bool, String> {
+    secure_storage::has_any_api_key()
+}
+
+/// Check a provider step by step: stored key, network, model list, and a tiny completion
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(provider_id = %provider_id), err)]
+async fn run_provider_diagnostics(
+    provider_id: String,
+    state: State<'_, DbState>,
+) -> Result<provider_check::ProviderDiagnostics, String> {
+    provider_check::run(&provider_id, &state).await
+}
+
+/// Providers found on this machine (OpenCode logins, environment keys, a running Ollama
+/// or LM Studio, AWS credentials), most likely to work first, for onboarding to offer
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn detect_available_providers(
+    app: tauri::AppHandle,
+    state: State<'_, DbState>,
+) -> Result<Vec<provider_detect::DetectedProvider>, String> {
+    let home = app.path().home_dir().ok();
+    provider_detect::detect(home.as_deref(), &state).await
+}
+
+// ============================================================================
+// Onboarding Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_onboarding_complete(state: State<'_, DbState>) -> Result<bool, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    Ok(db::settings::get_onboarding_complete(&conn))
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_onboarding_complete(complete: bool, state: State<'_, DbState>) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::settings::set_onboarding_complete(&conn, complete)
+}
+
+/// Checklist of what tasks need (database, keychain, sidecar, CLI, provider)
+///
+/// Returns the report of the checks run at launch unless `refresh` is set or
+/// they haven't finished yet.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_startup_health(
+    refresh: Option<bool>,
+    app: tauri::AppHandle,
+) -> Result<health::StartupHealth, String> {
+    if !refresh.unwrap_or(false) {
+        if let Some(report) = health::last_report() {
+            return Ok(report);
+        }
+    }
+    tauri::async_runtime::spawn_blocking(move || health::check(&app))
+        .await
+        .map_err(|e| format!("Failed to check startup health: {}", e))
+}
+
+// ============================================================================
+// Claude CLI Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn check_claude_cli(app: tauri::AppHandle) -> Result<ClaudeCliStatus, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let resolved = cli::resolve(&app);
+        ClaudeCliStatus {
+            installed: resolved.is_some(),
+            version: resolved.as_ref().and_then(|cli| cli.version.clone()),
+            install_command: cli::INSTALL_COMMAND.to_string(),
+            path: resolved.as_ref().map(|cli| cli.path.to_string_lossy().to_string()),
+            required_version: cli::MIN_VERSION.to_string(),
+            outdated: resolved
+                .as_ref()
+                .and_then(|cli| cli.version.as_deref())
+                .is_some_and(cli::is_outdated),
+            upgrade_command: cli::UPGRADE_COMMAND.to_string(),
+            bundled: resolved.as_ref().is_some_and(|cli| cli.bundled),
+            bundled_available: cli::bundled_bin_dir(&app).is_some(),
+        }
+    })
+    .await
+    .map_err(|e| format!("Failed to check OpenCode CLI: {}", e))
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_claude_version(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    tauri::async_runtime::spawn_blocking(move || cli::resolve(&app).and_then(|cli| cli.version))
+        .await
+        .map_err(|e| format!("Failed to get OpenCode version: {}", e))
+}
+
+/// "system" or "bundled": which OpenCode CLI runs tasks when both are available
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_cli_runtime(state: State<'_, DbState>) -> Result<String, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    Ok(db::settings::get_cli_runtime(&conn))
+}
+
+/// Takes effect the next time the sidecar starts
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_cli_runtime(runtime: String, state: State<'_, DbState>) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::settings::set_cli_runtime(&conn, &runtime)
+}
+
+/// Install the OpenCode CLI, emitting `cli:install_progress`, and return the new status
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn install_opencode_cli(app: tauri::AppHandle) -> Result<ClaudeCliStatus, String> {
+    let home = app.path().home_dir().ok();
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || cli::install(&handle, home.as_deref()))
+        .await
+        .map_err(|e| format!("Failed to install OpenCode CLI: {}", e))??;
+
+    let status = check_claude_cli(app).await?;
+    if !status.installed {
+        return Err("Installation finished but the OpenCode CLI was not found".to_string());
+    }
+    tracing::info!(
+        "[CLI] Installed OpenCode {}",
+        status.version.as_deref().unwrap_or("(unknown version)")
+    );
+    Ok(status)
+}
+
+// ============================================================================
+// Model Selection Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_selected_model(state: State<'_, DbState>) -> Result<Option<SelectedModel>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    let model = db::settings::get_selected_model(&conn);
+    Ok(model.map(|m| SelectedModel {
+        provider: m.provider,
+        model: m.model,
+        base_url: m.base_url,
+        deployment_name: m.deployment_name,
+    }))
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_selected_model(model: SelectedModel, state: State<'_, DbState>) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let db_model = db::settings::SelectedModel {
+        provider: model.provider,
+        model: model.model,
+        base_url: model.base_url,
+        deployment_name: model.deployment_name,
+    };
+    db::settings::set_selected_model(&conn, Some(&db_model))
+}
+
+/// Model ID ("provider/model") used to summarize finished tasks; `None` uses the task's model
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_summary_model(state: State<'_, DbState>) -> Result<Option<String>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    Ok(db::settings::get_summary_model_id(&conn))
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_summary_model(
+    model_id: Option<String>,
+    state: State<'_, DbState>,
+) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let model_id = model_id.as_deref().map(str::trim).filter(|id| !id.is_empty());
+    db::settings::set_summary_model_id(&conn, model_id)
+}
+
+// ============================================================================
+// Ollama Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn test_ollama_connection(url: String) -> Result<ConnectionResult, String> {
+    // Try to connect to Ollama and list models
+    let client = reqwest::Client::new();
+    let tags_url = format!("{}/api/tags", url.trim_end_matches('/'));
+
+    match client.get(&tags_url).send().await {
+        Ok(response) => {
+            if response.status().is_success() {
+                // Parse models from response
+                #[derive(Deserialize)]
+                struct OllamaTagsResponse {
+                    models: Vec<OllamaModelInfo>,
+                }
+                #[derive(Deserialize)]
+                struct OllamaModelInfo {
+                    name: String,
+                    size: u64,
+                }
+
+                match response.json::<OllamaTagsResponse>().await {
+                    Ok(tags) => {
+                        let models: Vec<OllamaModel> = tags
+                            .models
+                            .into_iter()
+                            .map(|m| OllamaModel {
+                                id: m.name.clone(),
+                                display_name: m.name,
+                                size: m.size,
+                            })
+                            .collect();
+
+                        Ok(ConnectionResult {
+                            success: true,
+                            models: Some(models),
+                            error: None,
+                        })
+                    }
+                    Err(e) => Ok(ConnectionResult {
+                        success: false,
+                        models: None,
+                        error: Some(format!("Failed to parse Ollama response: {}", e)),
+                    }),
+                }
+            } else {
+                Ok(ConnectionResult {
+                    success: false,
+                    models: None,
+                    error: Some(format!("Ollama returned status: {}", response.status())),
+                })
+            }
+        }
+        Err(e) => Ok(ConnectionResult {
+            success: false,
+            models: None,
+            error: Some(format!("Failed to connect to Ollama: {}", e)),
+        }),
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_ollama_config(state: State<'_, DbState>) -> Result<Option<OllamaConfig>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let config = db::settings::get_ollama_config(&conn);
+    Ok(config.map(|c| OllamaConfig {
+        base_url: c.base_url,
+        enabled: c.enabled,
+        last_validated: c.last_validated,
+        models: c.models.map(|models| {
+            models
+                .into_iter()
+                .map(|m| OllamaModel {
+                    id: m.id,
+                    display_name: m.display_name,
+                    size: m.size,
+                })
+                .collect()
+        }),
+    }))
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_ollama_config(
+    config: Option<OllamaConfig>,
+    state: State<'_, DbState>,
+) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let db_config = config.map(|c| db::settings::OllamaConfig {
+        base_url: c.base_url,
+        enabled: c.enabled,
+        last_validated: c.last_validated,
+        models: c.models.map(|models| {
+            models
+                .into_iter()
+                .map(|m| db::settings::OllamaModel {
+                    id: m.id,
+                    display_name: m.display_name,
+                    size: m.size,
+                })
+                .collect()
+        }),
+    });
+    db::settings::set_ollama_config(&conn, db_config.as_ref())
+}
+
+// ============================================================================
+// Azure Foundry Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_azure_foundry_config(
+    state: State<'_, DbState>,
+) -> Result<Option<AzureFoundryConfig>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let config = db::settings::get_azure_foundry_config(&conn);
+    Ok(config.map(|c| AzureFoundryConfig {
+        base_url: c.base_url,
+        deployment_name: c.deployment_name,
+        auth_type: c.auth_type,
+        enabled: c.enabled,
+        last_validated: c.last_validated,
+    }))
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_azure_foundry_config(
+    config: Option<AzureFoundryConfig>,
+    state: State<'_, DbState>,
+) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let db_config = config.map(|c| db::settings::AzureFoundryConfig {
+        base_url: c.base_url,
+        deployment_name: c.deployment_name,
+        auth_type: c.auth_type,
+        enabled: c.enabled,
+        last_validated: c.last_validated,
+    });
+    db::settings::set_azure_foundry_config(&conn, db_config.as_ref())
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn test_azure_foundry_connection(
+    _config: AzureFoundryTestConfig,
+) -> Result<ValidationResult, String> {
+    // TODO: Implement Azure Foundry connection test
+    Ok(ValidationResult {
+        valid: false,
+        error: Some("Azure Foundry connection test not yet implemented".to_string()),
+    })
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn save_azure_foundry_config(
+    config: AzureFoundryTestConfig,
+    state: State<'_, DbState>,
+    sidecar_state: State<'_, SidecarState>,
+) -> Result<(), String> {
+    // Store API key securely if present
+    if let Some(api_key) = &config.api_key {
+        secure_storage::store_api_key("azureFoundry", api_key)?;
+        sidecar_state.invalidate_api_keys();
+    }
+
+    // Store rest of config (without API key) in database
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::settings::set_azure_foundry_config(
+        &conn,
+        Some(&db::settings::AzureFoundryConfig {
+            base_url: config.endpoint,
+            deployment_name: config.deployment_name,
+            auth_type: config.auth_type,
+            enabled: true,
+            last_validated: None,
+        }),
+    )
+}
+
+// ============================================================================
+// OpenRouter Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn fetch_openrouter_models() -> Result<OpenRouterModelsResult, String> {
+    // TODO: Requires API key from secure storage
+    Ok(OpenRouterModelsResult {
+        success: false,
+        models: None,
+        error: Some("OpenRouter not yet implemented".to_string()),
+    })
+}
+
+// ============================================================================
+// LiteLLM Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn test_litellm_connection(
+    url: String,
+    _api_key: Option<String>,
+) -> Result<OpenRouterModelsResult, String> {
+    let client = reqwest::Client::new();
+    let models_url = format!("{}/models", url.trim_end_matches('/'));
+
+    match client.get(&models_url).send().await {
+        Ok(response) => {
+            if response.status().is_success() {
+                #[derive(Deserialize)]
+                struct LiteLLMModelsResponse {
+                    data: Vec<LiteLLMModelInfo>,
+                }
+                #[derive(Deserialize)]
+                struct LiteLLMModelInfo {
+                    id: String,
+                    #[serde(default)]
+                    owned_by: String,
+                }
+
+                match response.json::<LiteLLMModelsResponse>().await {
+                    Ok(resp) => {
+                        let models: Vec<OpenRouterModel> = resp
+                            .data
+                            .into_iter()
+                            .map(|m| OpenRouterModel {
+                                id: m.id.clone(),
+                                name: m.id,
+                                provider: m.owned_by,
+                                context_length: 0,
+                            })
+                            .collect();
+
+                        Ok(OpenRouterModelsResult {
+                            success: true,
+                            models: Some(models),
+                            error: None,
+                        })
+                    }
+                    Err(e) => Ok(OpenRouterModelsResult {
+                        success: false,
+                        models: None,
+                        error: Some(format!("Failed to parse LiteLLM response: {}", e)),
+                    }),
+                }
+            } else {
+                Ok(OpenRouterModelsResult {
+                    success: false,
+                    models: None,
+                    error: Some(format!("LiteLLM returned status: {}", response.status())),
+                })
+            }
+        }
+        Err(e) => Ok(OpenRouterModelsResult {
+            success: false,
+            models: None,
+            error: Some(format!("Failed to connect to LiteLLM: {}", e)),
+        }),
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn fetch_litellm_models() -> Result<OpenRouterModelsResult, String> {
+    Ok(OpenRouterModelsResult {
+        success: false,
+        models: None,
+        error: Some("LiteLLM not yet implemented".to_string()),
+    })
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_litellm_config(state: State<'_, DbState>) -> Result<Option<LiteLLMConfig>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let config = db::settings::get_litellm_config(&conn);
+    Ok(config.map(|c| LiteLLMConfig {
+        base_url: c.base_url,
+        enabled: c.enabled,
+        last_validated: c.last_validated,
+        models: c.models.map(|models| {
+            models
+                .into_iter()
+                .map(|m| OpenRouterModel {
+                    id: m.id,
+                    name: m.name,
+                    provider: m.provider,
+                    context_length: m.context_length,
+                })
+                .collect()
+        }),
+    }))
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_litellm_config(
+    config: Option<LiteLLMConfig>,
+    state: State<'_, DbState>,
+) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let db_config = config.map(|c| db::settings::LiteLLMConfig {
+        base_url: c.base_url,
+        enabled: c.enabled,
+        last_validated: c.last_validated,
+        models: c.models.map(|models| {
+            models
+                .into_iter()
+                .map(|m| db::settings::LiteLLMModel {
+                    id: m.id,
+                    name: m.name,
+                    provider: m.provider,
+                    context_length: m.context_length,
+                })
+                .collect()
+        }),
+    });
+    db::settings::set_litellm_config(&conn, db_config.as_ref())
+}
+
+// ============================================================================
+// Bedrock Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn validate_bedrock_credentials(credentials: String) -> Result<ValidationResult, String> {
+    // Parse and validate the credentials format
+    match serde_json::from_str::<BedrockCredentials>(&credentials) {
+        Ok(creds) => {
+            if creds.access_key_id.is_empty()
+                || creds.secret_access_key.is_empty()
+                || creds.region.is_empty()
+            {
+                Ok(ValidationResult {
+                    valid: false,
+                    error: Some("All credential fields are required".to_string()),
+                })
+            } else {
+                Ok(ValidationResult {
+                    valid: true,
+                    error: None,
+                })
+            }
+        }
+        Err(e) => Ok(ValidationResult {
+            valid: false,
+            error: Some(format!("Invalid credentials format: {}", e)),
+        }),
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn save_bedrock_credentials(
+    credentials: String,
+    sidecar_state: State<'_, SidecarState>,
+) -> Result<ApiKeyConfig, String> {
+    secure_storage::store_bedrock_credentials(&credentials)?;
+    sidecar_state.invalidate_api_keys();
+
+    Ok(ApiKeyConfig {
+        id: "apikey-bedrock".to_string(),
+        provider: "bedrock".to_string(),
+        label: Some("AWS Bedrock".to_string()),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_bedrock_credentials() -> Result<Option<BedrockCredentials>, String> {
+    match secure_storage::get_bedrock_credentials()? {
+        Some(creds) => Ok(Some(BedrockCredentials {
+            access_key_id: creds.access_key_id,
+            secret_access_key: creds.secret_access_key,
+            region: creds.region,
+        })),
+        None => Ok(None),
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn fetch_bedrock_models(_credentials: String) -> Result<BedrockModelsResult, String> {
+    // TODO: Implement AWS Bedrock model listing
+    Ok(BedrockModelsResult {
+        success: false,
+        models: vec![],
+        error: Some("Bedrock not yet implemented".to_string()),
+    })
+}
+
+// ============================================================================
+// E2E Testing Command
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn is_e2e_mode() -> Result<bool, String> {
+    Ok(std::env::var("E2E_MODE").is_ok())
+}
+
+/// Whether the app was started in safe mode (`--safe-mode` or `COWORK_Z_SAFE_MODE`)
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all)]
+fn is_safe_mode() -> bool {
+    safe_mode::enabled()
+}
+
+/// Relaunch the app outside safe mode
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+fn exit_safe_mode(app: tauri::AppHandle) -> Result<(), String> {
+    if !safe_mode::enabled() {
+        return Ok(());
+    }
+    safe_mode::relaunch_normally()?;
+    tracing::info!("[SafeMode] Relaunching outside safe mode");
+    app.exit(0);
+    Ok(())
+}
+
+/// Replace the task history with a named fixture and skip onboarding
+///
+/// Only compiled into debug builds and builds with the `e2e` feature, and only
+/// runs with `E2E_MODE` set.
+#[cfg(any(debug_assertions, feature = "e2e"))]
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn seed_test_data(
+    fixture_name: String,
+    state: State<'_, DbState>,
+) -> Result<fixtures::SeedSummary, String> {
+    if std::env::var("E2E_MODE").is_err() {
+        return Err("Test data can only be seeded with E2E_MODE set".to_string());
+    }
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    fixtures::seed(&conn, &fixture_name)
+}
+
+// ============================================================================
+// Provider Settings Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_provider_settings(state: State<'_, DbState>) -> Result<ProviderSettings, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    let settings = db::providers::get_provider_settings(&conn);
+
+    let connected_providers: HashMap<String, ConnectedProviderResponse> = settings
+        .connected_providers
+        .into_iter()
+        .map(|(k, v)| {
+            (
+                k,
+                ConnectedProviderResponse {
+                    id: v.provider_id,
+                    selected_model: v.selected_model_id,
+                    config: serde_json::to_value(&v.credentials).ok(),
+                },
+            )
+        })
+        .collect();
+
+    Ok(ProviderSettings {
+        active_provider: settings.active_provider_id,
+        connected_providers,
+        debug_mode: settings.debug_mode,
+    })
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_active_provider(
+    provider_id: Option<String>,
+    state: State<'_, DbState>,
+) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::providers::set_active_provider(&conn, provider_id.as_deref())
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_connected_provider(
+    provider_id: String,
+    state: State<'_, DbState>,
+) -> Result<Option<ConnectedProviderResponse>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let provider = db::providers::get_connected_provider(&conn, &provider_id);
+
+    Ok(provider.map(|p| ConnectedProviderResponse {
+        id: p.provider_id,
+        selected_model: p.selected_model_id,
+        config: serde_json::to_value(&p.credentials).ok(),
+    }))
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_connected_provider(
+    provider_id: String,
+    provider: ConnectedProviderInput,
+    state: State<'_, DbState>,
+) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+
+    // Convert input to db type
+    let db_provider = db::providers::ConnectedProvider {
+        provider_id: provider.id,
+        connection_status: "connected".to_string(),
+        selected_model_id: provider.selected_model,
+        credentials: db::providers::ProviderCredentials {
+            credentials_type: "api_key".to_string(),
+            key_prefix: None,
+            server_url: None,
+            api_key: None,
+            extra: HashMap::new(),
+        },
+        last_connected_at: chrono::Utc::now().to_rfc3339(),
+        available_models: None,
+    };
+
+    db::providers::set_connected_provider(&conn, &provider_id, &db_provider)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn remove_connected_provider(
+    provider_id: String,
+    state: State<'_, DbState>,
+) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::providers::remove_connected_provider(&conn, &provider_id)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn update_provider_model(
+    provider_id: String,
+    model_id: Option<String>,
+    state: State<'_, DbState>,
+) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::providers::update_provider_model(&conn, &provider_id, model_id.as_deref())
+}
+
+/// Parameter overrides of every model that has some, keyed by model ID
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn list_model_parameters(
+    state: State<'_, DbState>,
+) -> Result<HashMap<String, db::providers::ModelParameters>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::providers::list_model_parameters(&conn)
+}
+
+/// Set a model's parameter overrides; unset fields keep the model's defaults
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(model_id = %model_id), err)]
+async fn set_model_parameters(
+    model_id: String,
+    parameters: db::providers::ModelParameters,
+    state: State<'_, DbState>,
+) -> Result<(), String> {
+    parameters.validate()?;
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::providers::set_model_parameters(&conn, &model_id, &parameters)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_provider_debug_mode(enabled: bool, state: State<'_, DbState>) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::providers::set_provider_debug_mode(&conn, enabled)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_provider_debug_mode(state: State<'_, DbState>) -> Result<bool, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    Ok(db::providers::get_provider_debug_mode(&conn))
+}
+
+// ============================================================================
+// Logging Command
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn log_event(payload: LogPayload) -> Result<(), String> {
+    let context = payload
+        .context
+        .map(|c| serde_json::to_string(&c).unwrap_or_default())
+        .unwrap_or_default();
+    let message = payload.message;
+
+    match payload.level.as_deref().unwrap_or("info") {
+        "error" => tracing::error!(target: "frontend", context, "{}", message),
+        "warn" | "warning" => tracing::warn!(target: "frontend", context, "{}", message),
+        "debug" => tracing::debug!(target: "frontend", context, "{}", message),
+        "trace" => tracing::trace!(target: "frontend", context, "{}", message),
+        _ => tracing::info!(target: "frontend", context, "{}", message),
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_log_level(logging_state: State<'_, LoggingState>) -> Result<String, String> {
+    Ok(logging_state.level())
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_log_level(
+    level: String,
+    logging_state: State<'_, LoggingState>,
+    db_state: State<'_, DbState>,
+) -> Result<(), String> {
+    logging_state.set_level(&level)?;
+    let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+    db::settings::set_log_level(&conn, &level.to_ascii_lowercase())
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn query_logs(
+    filter: Option<db::logs::LogFilter>,
+    state: State<'_, DbState>,
+) -> Result<Vec<db::logs::LogRecord>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::logs::query_logs(&conn, &filter.unwrap_or_default())
+}
+
+/// Commands and sidecar operations that took longer than the slow threshold, newest first
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_slow_commands() -> Result<Vec<logging::SlowCommand>, String> {
+    Ok(logging::slow_commands())
+}
+
+// ============================================================================
+// Crash Reporting Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn list_crash_reports() -> Result<Vec<crash::CrashReportSummary>, String> {
+    crash::list_crash_reports()
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn submit_crash_report(report_id: String) -> Result<(), String> {
+    crash::submit_crash_report(&report_id).await
+}
+
+// ============================================================================
+// Diagnostics Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn export_diagnostics(
+    path: String,
+    app: tauri::AppHandle,
+    state: State<'_, DbState>,
+) -> Result<(), String> {
+    diagnostics::export_diagnostics(&app, &state, std::path::Path::new(&path))
+}
+
+/// Database file sizes, row counts, and the largest tasks, for storage settings
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_db_stats(
+    app: tauri::AppHandle,
+    state: State<'_, DbState>,
+) -> Result<db::stats::DbStats, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::stats::get_db_stats(&conn, &db::get_database_path(&app))
+}
+
+/// Bytes saved by storing identical message attachments once
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_storage_savings(
+    state: State<'_, DbState>,
+) -> Result<db::blobs::StorageSavings, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::blobs::get_storage_savings(&conn)
+}
+
+/// Move attachment data still stored per attachment into shared blobs
+///
+/// Runs at startup too; the freed pages are reclaimed by the next VACUUM.
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn dedup_attachments(
+    state: State<'_, DbState>,
+) -> Result<db::blobs::StorageSavings, String> {
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let moved = db::blobs::dedup_inline(&mut conn)?;
+    tracing::info!("[DB] Deduplicated {} attachments", moved);
+    db::blobs::get_storage_savings(&conn)
+}
+
+// ============================================================================
+// Backup Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_backup_settings(
+    state: State<'_, DbState>,
+) -> Result<db::settings::BackupSettings, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    Ok(db::settings::get_backup_settings(&conn))
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_backup_settings(
+    settings: db::settings::BackupSettings,
+    state: State<'_, DbState>,
+) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::settings::set_backup_settings(&conn, &settings)
+}
+
+/// Back up the database now; manual backups aren't pruned by the schedule
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn create_backup(app: tauri::AppHandle) -> Result<backup::BackupInfo, String> {
+    tauri::async_runtime::spawn_blocking(move || backup::create(&app, "manual"))
+        .await
+        .map_err(|e| format!("Failed to back up database: {}", e))?
+}
+
+/// Backups, newest first
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn list_backups(app: tauri::AppHandle) -> Result<Vec<backup::BackupInfo>, String> {
+    backup::list(&app)
+}
+
+/// Restore a backup, restarting the app; the current database is kept as a backup
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn restore_backup(backup_id: String, app: tauri::AppHandle) -> Result<(), String> {
+    backup::stage_restore(&app, &backup_id)?;
+    app.restart();
+}
+
+/// The database from a newer app version that was set aside at startup, if not yet handled
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_downgrade_notice(
+    state: State<'_, DbState>,
+) -> Result<Option<db::settings::DowngradeNotice>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    Ok(db::settings::get_downgrade_notice(&conn))
+}
+
+/// Import the tasks of the database set aside at startup; returns how many were imported
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn import_downgraded_tasks(app: tauri::AppHandle) -> Result<usize, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let db_state = app.state::<DbState>();
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        let notice = db::settings::get_downgrade_notice(&conn)
+            .ok_or_else(|| "No database was set aside".to_string())?;
+        let imported = backup::import_tasks(&app, &conn, &notice.backup_id)?;
+        db::settings::set_downgrade_notice(&conn, None)?;
+        Ok(imported)
+    })
+    .await
+    .map_err(|e| format!("Failed to import tasks: {}", e))?
+}
+
+/// Stop offering to import the database set aside at startup; its backup is kept
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn dismiss_downgrade_notice(state: State<'_, DbState>) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::settings::set_downgrade_notice(&conn, None)
+}
+
+// ============================================================================
+// Export Commands
+// ============================================================================
+
+/// Load a stored task or fail with a not-found error
+fn load_stored_task(state: &DbState, task_id: &str) -> Result<db::tasks::StoredTask, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::tasks::get_task(&conn, task_id).ok_or_else(|| format!("Task not found: {}", task_id))
+}
+
+fn load_task_sources(
+    state: &DbState,
+    task_id: &str,
+) -> Result<db::citations::TaskSources, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::citations::get_task_sources(&conn, task_id)
+}
+
+fn load_task_ratings(state: &DbState, task_id: &str) -> Result<db::ratings::TaskRatings, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::ratings::get_task_ratings(&conn, task_id)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn export_task_html(
+    task_id: String,
+    path: String,
+    state: State<'_, DbState>,
+    messages: State<'_, MessageBufferState>,
+) -> Result<(), String> {
+    {
+        let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+        messages.flush_task(&mut conn, &task_id);
+    }
+    let task = load_stored_task(&state, &task_id)?;
+    let sources = load_task_sources(&state, &task_id)?;
+    let ratings = load_task_ratings(&state, &task_id)?;
+    export::export_task_html(&task, &sources, &ratings, std::path::Path::new(&path))
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn export_task_pdf(
+    task_id: String,
+    path: String,
+    state: State<'_, DbState>,
+    messages: State<'_, MessageBufferState>,
+) -> Result<(), String> {
+    {
+        let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+        messages.flush_task(&mut conn, &task_id);
+    }
+    let task = load_stored_task(&state, &task_id)?;
+    let sources = load_task_sources(&state, &task_id)?;
+    let ratings = load_task_ratings(&state, &task_id)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        export::export_task_pdf(&task, &sources, &ratings, std::path::Path::new(&path))
+    })
+    .await
+    .map_err(|e| format!("PDF export task failed: {}", e))?
+}
+
+/// Write a task to `path` as a Markdown transcript ("markdown") or a JSON bundle ("json")
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn export_task(
+    task_id: String,
+    format: String,
+    path: String,
+    state: State<'_, DbState>,
+    messages: State<'_, MessageBufferState>,
+) -> Result<(), String> {
+    {
+        let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+        messages.flush_task(&mut conn, &task_id);
+    }
+    let task = load_stored_task(&state, &task_id)?;
+    let sources = load_task_sources(&state, &task_id)?;
+    let ratings = load_task_ratings(&state, &task_id)?;
+    let tags = {
+        let conn = state.reader.lock().map_err(|e| e.to_string())?;
+        db::tags::get_task_tags(&conn, &task_id)?
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        export::export_task(&task, &sources, &ratings, &tags, &format, std::path::Path::new(&path))
+    })
+    .await
+    .map_err(|e| format!("Export task failed: {}", e))?
+}
+
+/// Produce a sanitized transcript; written to `path` when given, and always returned
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn export_task_shareable(
+    task_id: String,
+    options: Option<export::ShareableOptions>,
+    path: Option<String>,
+    state: State<'_, DbState>,
+    messages: State<'_, MessageBufferState>,
+) -> Result<String, String> {
+    {
+        let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+        messages.flush_task(&mut conn, &task_id);
+    }
+    let task = load_stored_task(&state, &task_id)?;
+    let sources = load_task_sources(&state, &task_id)?;
+    let ratings = load_task_ratings(&state, &task_id)?;
+    let options = options.unwrap_or_default();
+    let content = export::render_shareable(&task, &sources, &ratings, &options)?;
+    if let Some(path) = path {
+        std::fs::write(&path, &content)
+            .map_err(|e| format!("Failed to write shareable export: {}", e))?;
+    }
+    Ok(content)
+}
+
+// ============================================================================
+// GitHub Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_github_token(token: String) -> Result<(), String> {
+    secure_storage::store_github_token(token.trim())
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn has_github_token() -> Result<bool, String> {
+    Ok(secure_storage::get_github_token()?.is_some())
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn remove_github_token() -> Result<(), String> {
+    secure_storage::delete_github_token()?;
+    Ok(())
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn detect_github_repo(working_directory: String) -> Result<github::GitHubRepo, String> {
+    github::detect_repo(&working_directory)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn create_github_issue(
+    task_id: String,
+    working_directory: String,
+    state: State<'_, DbState>,
+) -> Result<github::GitHubLink, String> {
+    let task = load_stored_task(&state, &task_id)?;
+    github::create_issue(&task, &working_directory).await
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn create_github_draft_pr(
+    task_id: String,
+    working_directory: String,
+    base_branch: Option<String>,
+    state: State<'_, DbState>,
+) -> Result<github::GitHubLink, String> {
+    let task = load_stored_task(&state, &task_id)?;
+    github::create_draft_pr(&task, &working_directory, base_branch).await
+}
+
+// ============================================================================
+// Local API Server Commands
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiServerStatus {
+    pub enabled: bool,
+    pub running: bool,
+    pub port: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    pub websocket_enabled: bool,
+    pub metrics_enabled: bool,
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_api_server_status(
+    db_state: State<'_, DbState>,
+    api_state: State<'_, ApiServerState>,
+) -> Result<ApiServerStatus, String> {
+    let settings = {
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        db::settings::get_api_server_settings(&conn)
+    };
+    let running_port = api_state.running_port().await;
+
+    Ok(ApiServerStatus {
+        enabled: settings.enabled,
+        running: running_port.is_some(),
+        port: running_port
+            .or(settings.port)
+            .unwrap_or(api_server::DEFAULT_PORT),
+        url: running_port.map(|port| format!("http://127.0.0.1:{}", port)),
+        websocket_enabled: settings.websocket_enabled,
+        metrics_enabled: settings.metrics_enabled,
+    })
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_api_server_enabled(
+    enabled: bool,
+    port: Option<u16>,
+    app: tauri::AppHandle,
+    db_state: State<'_, DbState>,
+    api_state: State<'_, ApiServerState>,
+) -> Result<ApiServerStatus, String> {
+    let settings = {
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        let current = db::settings::get_api_server_settings(&conn);
+        let settings = db::settings::ApiServerSettings {
+            enabled,
+            port: port.or(current.port),
+            websocket_enabled: current.websocket_enabled,
+            metrics_enabled: current.metrics_enabled,
+        };
+        db::settings::set_api_server_settings(&conn, &settings)?;
+        settings
+    };
+
+    if enabled {
+        api_state
+            .start(&app, settings.port.unwrap_or(api_server::DEFAULT_PORT))
+            .await?;
     } else {
-        Ok(ValidationResult {
-            valid: false,
-            error: Some(format!("Invalid API key format for provider: {}", provider)),
-        })
+        api_state.stop().await;
     }
+
+    get_api_server_status(db_state, api_state).await
 }
 
 #[tauri::command]
-async fn clear_api_key() -> Result<(), String> {
-    // Clear default provider key (anthropic)
-    secure_storage::delete_api_key("anthropic")?;
-    Ok(())
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_api_server_websocket_enabled(
+    enabled: bool,
+    db_state: State<'_, DbState>,
+    api_state: State<'_, ApiServerState>,
+) -> Result<ApiServerStatus, String> {
+    {
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        let mut settings = db::settings::get_api_server_settings(&conn);
+        settings.websocket_enabled = enabled;
+        db::settings::set_api_server_settings(&conn, &settings)?;
+    }
+    api_state.set_websocket_enabled(enabled);
+
+    get_api_server_status(db_state, api_state).await
 }
 
+/// Serve Prometheus metrics at `/metrics` on the local API server
 #[tauri::command]
-async fn get_all_api_keys() -> Result<HashMap<String, ApiKeyStatus>, String> {
-    let status = secure_storage::get_all_api_key_status()?;
-    Ok(status
-        .into_iter()
-        .map(|(k, v)| {
-            (
-                k,
-                ApiKeyStatus {
-                    exists: v.exists,
-                    prefix: v.prefix,
-                },
-            )
-        })
-        .collect())
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_api_server_metrics_enabled(
+    enabled: bool,
+    db_state: State<'_, DbState>,
+    api_state: State<'_, ApiServerState>,
+) -> Result<ApiServerStatus, String> {
+    {
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        let mut settings = db::settings::get_api_server_settings(&conn);
+        settings.metrics_enabled = enabled;
+        db::settings::set_api_server_settings(&conn, &settings)?;
+    }
+    api_state.set_metrics_enabled(enabled);
+
+    get_api_server_status(db_state, api_state).await
 }
 
 #[tauri::command]
-async fn has_any_api_key() -> Result<bool, String> {
-    secure_storage::has_any_api_key()
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_api_server_token() -> Result<String, String> {
+    secure_storage::get_or_create_api_server_token()
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn regenerate_api_server_token(
+    api_state: State<'_, ApiServerState>,
+) -> Result<String, String> {
+    let token = secure_storage::regenerate_api_server_token()?;
+    api_state.update_token(&token).await?;
+    Ok(token)
 }
 
 // ============================================================================
-// Onboarding Commands
+// Sync Commands
 // ============================================================================
 
 #[tauri::command]
-async fn get_onboarding_complete(state: State<'_, DbState>) -> Result<bool, String> {
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_sync_folder(state: State<'_, DbState>) -> Result<Option<String>, String> {
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    Ok(db::settings::get_onboarding_complete(&conn))
+    Ok(db::settings::get_sync_folder(&conn))
 }
 
+/// Set the sync folder and merge it immediately; `None` turns folder sync off
 #[tauri::command]
-async fn set_onboarding_complete(complete: bool, state: State<'_, DbState>) -> Result<(), String> {
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_sync_folder(
+    path: Option<String>,
+    app: tauri::AppHandle,
+    state: State<'_, DbState>,
+    messages: State<'_, MessageBufferState>,
+) -> Result<Option<sync::SyncReport>, String> {
+    let Some(path) = path else {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        db::settings::set_sync_folder(&conn, None)?;
+        return Ok(None);
+    };
+
+    {
+        let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+        messages.flush(&mut conn);
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let folder = std::path::Path::new(&path);
+        if !folder.is_dir() {
+            return Err(format!("Sync folder does not exist: {}", path));
+        }
+        let db_state = app.state::<DbState>();
+        let report = sync::sync_folder(&db_state, folder)?;
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        db::settings::set_sync_folder(&conn, Some(&path))?;
+        Ok(Some(report))
+    })
+    .await
+    .map_err(|e| format!("Failed to sync folder: {}", e))?
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn sync_history_folder(
+    app: tauri::AppHandle,
+    state: State<'_, DbState>,
+    messages: State<'_, MessageBufferState>,
+) -> Result<sync::SyncReport, String> {
+    let folder = {
+        let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+        messages.flush(&mut conn);
+        db::settings::get_sync_folder(&conn).ok_or("Sync folder is not configured")?
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        sync::sync_folder(&app.state::<DbState>(), std::path::Path::new(&folder))
+    })
+    .await
+    .map_err(|e| format!("Failed to sync folder: {}", e))?
+}
+
+/// Encrypted sync state returned to the frontend
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EncryptedSyncStatus {
+    enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    relay_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    device: Option<encrypted_sync::DeviceIdentity>,
+    has_group_key: bool,
+}
+
+/// Relay for encrypted sync, failing when the feature is off
+fn encrypted_sync_relay(state: &DbState) -> Result<encrypted_sync::RelayKind, String> {
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    db::settings::set_onboarding_complete(&conn, complete)
+    let settings = db::settings::get_e2e_sync_settings(&conn);
+    if !settings.enabled {
+        return Err("Encrypted sync is not enabled".to_string());
+    }
+    let url = settings.relay_url.ok_or("Encrypted sync relay is not configured")?;
+    encrypted_sync::RelayKind::from_url(&url)
 }
 
-// ============================================================================
-// Claude CLI Commands
-// ============================================================================
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_encrypted_sync_status(
+    state: State<'_, DbState>,
+) -> Result<EncryptedSyncStatus, String> {
+    let settings = {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        db::settings::get_e2e_sync_settings(&conn)
+    };
+    // Only touch the keychain once the user has opted in
+    let device = if settings.enabled {
+        Some(encrypted_sync::device_identity()?)
+    } else {
+        None
+    };
+
+    Ok(EncryptedSyncStatus {
+        enabled: settings.enabled,
+        relay_url: settings.relay_url,
+        device,
+        has_group_key: settings.enabled && encrypted_sync::has_group_key(),
+    })
+}
 
 #[tauri::command]
-async fn check_claude_cli() -> Result<ClaudeCliStatus, String> {
-    // Check if opencode CLI is installed
-    let output = std::process::Command::new("which").arg("opencode").output();
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_encrypted_sync_enabled(
+    enabled: bool,
+    relay_url: Option<String>,
+    state: State<'_, DbState>,
+) -> Result<EncryptedSyncStatus, String> {
+    {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        let mut settings = db::settings::get_e2e_sync_settings(&conn);
+        if relay_url.is_some() {
+            settings.relay_url = relay_url;
+        }
+        if enabled {
+            let url = settings
+                .relay_url
+                .as_deref()
+                .ok_or("A relay URL is required to enable encrypted sync")?;
+            encrypted_sync::RelayKind::from_url(url)?;
+            encrypted_sync::device_identity()?;
+            encrypted_sync::ensure_group_key()?;
+        }
+        settings.enabled = enabled;
+        db::settings::set_e2e_sync_settings(&conn, &settings)?;
+    }
 
-    match output {
-        Ok(out) if out.status.success() => {
-            // Try to get version
-            let version_output = std::process::Command::new("opencode")
-                .arg("--version")
-                .output();
+    get_encrypted_sync_status(state).await
+}
 
-            let version = version_output.ok().and_then(|v| {
-                if v.status.success() {
-                    String::from_utf8(v.stdout).ok().map(|s| s.trim().to_string())
-                } else {
-                    None
-                }
-            });
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn pair_device(
+    public_key: String,
+    state: State<'_, DbState>,
+) -> Result<encrypted_sync::PairResult, String> {
+    let relay = encrypted_sync_relay(&state)?;
+    let _guard = encrypted_sync::lock().await;
+    encrypted_sync::pair_device(&relay, &public_key).await
+}
 
-            Ok(ClaudeCliStatus {
-                installed: true,
-                version,
-                install_command: "npm install -g opencode-ai".to_string(),
-            })
-        }
-        _ => Ok(ClaudeCliStatus {
-            installed: false,
-            version: None,
-            install_command: "npm install -g opencode-ai".to_string(),
-        }),
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn sync_now(
+    state: State<'_, DbState>,
+    messages: State<'_, MessageBufferState>,
+) -> Result<sync::SyncReport, String> {
+    let relay = encrypted_sync_relay(&state)?;
+    let _guard = encrypted_sync::lock().await;
+    {
+        let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+        messages.flush(&mut conn);
     }
+    encrypted_sync::sync_now(&relay, &state.conn).await
 }
 
+// ============================================================================
+// Web Search Commands
+// ============================================================================
+
 #[tauri::command]
-async fn get_claude_version() -> Result<Option<String>, String> {
-    let output = std::process::Command::new("opencode")
-        .arg("--version")
-        .output();
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_web_search_settings(
+    state: State<'_, DbState>,
+) -> Result<db::settings::WebSearchSettings, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    Ok(db::settings::get_web_search_settings(&conn))
+}
 
-    Ok(output.ok().and_then(|v| {
-        if v.status.success() {
-            String::from_utf8(v.stdout).ok().map(|s| s.trim().to_string())
-        } else {
-            None
-        }
-    }))
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_web_search_settings(
+    settings: db::settings::WebSearchSettings,
+    state: State<'_, DbState>,
+) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::settings::set_web_search_settings(&conn, &settings)
 }
 
 // ============================================================================
-// Model Selection Commands
+// Budget Commands
 // ============================================================================
 
 #[tauri::command]
-async fn get_selected_model(state: State<'_, DbState>) -> Result<Option<SelectedModel>, String> {
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_budget_limits(
+    state: State<'_, DbState>,
+) -> Result<Vec<db::settings::BudgetLimit>, String> {
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    let model = db::settings::get_selected_model(&conn);
-    Ok(model.map(|m| SelectedModel {
-        provider: m.provider,
-        model: m.model,
-        base_url: m.base_url,
-        deployment_name: m.deployment_name,
-    }))
+    Ok(db::settings::get_budget_limits(&conn))
 }
 
 #[tauri::command]
-async fn set_selected_model(model: SelectedModel, state: State<'_, DbState>) -> Result<(), String> {
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_budget_limits(
+    limits: Vec<db::settings::BudgetLimit>,
+    state: State<'_, DbState>,
+) -> Result<(), String> {
+    budget::validate_limits(&limits)?;
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    let db_model = db::settings::SelectedModel {
-        provider: model.provider,
-        model: model.model,
-        base_url: model.base_url,
-        deployment_name: model.deployment_name,
+    db::settings::set_budget_limits(&conn, &limits)
+}
+
+/// Current spend against every configured limit
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_budget_status(state: State<'_, DbState>) -> Result<Vec<budget::BudgetStatus>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    budget::get_all_status(&conn)
+}
+
+// ============================================================================
+// Usage Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_usage_dashboard(
+    range: Option<db::usage::DateRange>,
+    state: State<'_, DbState>,
+) -> Result<db::usage::UsageDashboard, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::usage::get_usage_dashboard(&conn, &range.unwrap_or_default())
+}
+
+/// Tokens and estimated cost recorded for a task, overall and by model
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn get_task_usage(
+    task_id: String,
+    state: State<'_, DbState>,
+) -> Result<db::usage::TaskUsage, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::usage::get_task_usage(&conn, &task_id)
+}
+
+/// Tokens and estimated cost recorded in a range (all time without one), overall and by model
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_usage_summary(
+    range: Option<db::usage::DateRange>,
+    state: State<'_, DbState>,
+) -> Result<db::usage::UsageSummary, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::usage::get_usage_summary(&conn, &range.unwrap_or_default())
+}
+
+/// Write one CSV row per task created in the range (status, model, duration, tokens,
+/// cost, workspace, ratings, tags); returns the number of tasks
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn export_task_metrics_csv(
+    range: Option<db::usage::DateRange>,
+    path: String,
+    state: State<'_, DbState>,
+) -> Result<usize, String> {
+    let tasks = {
+        let conn = state.reader.lock().map_err(|e| e.to_string())?;
+        db::usage::get_task_metrics(&conn, &range.unwrap_or_default())?
     };
-    db::settings::set_selected_model(&conn, Some(&db_model))
+    export::export_task_metrics_csv(&tasks, std::path::Path::new(&path))?;
+    Ok(tasks.len())
 }
 
 // ============================================================================
-// Ollama Commands
+// Import Commands
 // ============================================================================
 
+/// Import Claude Code sessions from `path` (defaults to `~/.claude/projects`)
 #[tauri::command]
-async fn test_ollama_connection(url: String) -> Result<ConnectionResult, String> {
-    // Try to connect to Ollama and list models
-    let client = reqwest::Client::new();
-    let tags_url = format!("{}/api/tags", url.trim_end_matches('/'));
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn import_claude_code_sessions(
+    path: Option<String>,
+    app: tauri::AppHandle,
+    state: State<'_, DbState>,
+) -> Result<importers::ImportReport, String> {
+    let root = match path {
+        Some(path) => std::path::PathBuf::from(path),
+        None => app
+            .path()
+            .home_dir()
+            .map_err(|e| format!("Failed to get home directory: {}", e))?
+            .join(".claude")
+            .join("projects"),
+    };
+    if !root.is_dir() {
+        return Err(format!("Claude Code projects folder not found: {}", root.display()));
+    }
 
-    match client.get(&tags_url).send().await {
-        Ok(response) => {
-            if response.status().is_success() {
-                // Parse models from response
-                #[derive(Deserialize)]
-                struct OllamaTagsResponse {
-                    models: Vec<OllamaModelInfo>,
-                }
-                #[derive(Deserialize)]
-                struct OllamaModelInfo {
-                    name: String,
-                    size: u64,
-                }
+    let mut report = importers::ImportReport::default();
+    let sessions = importers::claude_code::read_sessions(&root, &mut report);
+    let repos = importers::detect_repos(&sessions).await;
 
-                match response.json::<OllamaTagsResponse>().await {
-                    Ok(tags) => {
-                        let models: Vec<OllamaModel> = tags
-                            .models
-                            .into_iter()
-                            .map(|m| OllamaModel {
-                                id: m.name.clone(),
-                                display_name: m.name,
-                                size: m.size,
-                            })
-                            .collect();
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    importers::save_sessions(&conn, sessions, &repos, &mut report);
+    Ok(report)
+}
 
-                        Ok(ConnectionResult {
-                            success: true,
-                            models: Some(models),
-                            error: None,
-                        })
-                    }
-                    Err(e) => Ok(ConnectionResult {
-                        success: false,
-                        models: None,
-                        error: Some(format!("Failed to parse Ollama response: {}", e)),
-                    }),
-                }
-            } else {
-                Ok(ConnectionResult {
-                    success: false,
-                    models: None,
-                    error: Some(format!("Ollama returned status: {}", response.status())),
-                })
-            }
+/// Import OpenCode CLI sessions from `path` (defaults to `~/.local/share/opencode/storage`)
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn import_opencode_sessions(
+    path: Option<String>,
+    app: tauri::AppHandle,
+    state: State<'_, DbState>,
+) -> Result<importers::ImportReport, String> {
+    let root = match path {
+        Some(path) => std::path::PathBuf::from(path),
+        None => match std::env::var_os("XDG_DATA_HOME").filter(|d| !d.is_empty()) {
+            Some(data_home) => std::path::PathBuf::from(data_home),
+            None => app
+                .path()
+                .home_dir()
+                .map_err(|e| format!("Failed to get home directory: {}", e))?
+                .join(".local")
+                .join("share"),
         }
-        Err(e) => Ok(ConnectionResult {
-            success: false,
-            models: None,
-            error: Some(format!("Failed to connect to Ollama: {}", e)),
-        }),
+        .join("opencode")
+        .join("storage"),
+    };
+    if !root.is_dir() {
+        return Err(format!("OpenCode storage folder not found: {}", root.display()));
     }
+
+    let mut report = importers::ImportReport::default();
+    let sessions = importers::opencode::read_sessions(&root, &mut report);
+    let repos = importers::detect_repos(&sessions).await;
+
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    importers::save_sessions(&conn, sessions, &repos, &mut report);
+    Ok(report)
+}
+
+// ============================================================================
+// Slash Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn list_slash_commands(
+    state: State<'_, DbState>,
+) -> Result<Vec<db::commands::SlashCommand>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::commands::list_commands(&conn)
 }
 
 #[tauri::command]
-async fn get_ollama_config(state: State<'_, DbState>) -> Result<Option<OllamaConfig>, String> {
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn create_slash_command(
+    command: db::commands::SlashCommandInput,
+    state: State<'_, DbState>,
+) -> Result<db::commands::SlashCommand, String> {
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    let config = db::settings::get_ollama_config(&conn);
-    Ok(config.map(|c| OllamaConfig {
-        base_url: c.base_url,
-        enabled: c.enabled,
-        last_validated: c.last_validated,
-        models: c.models.map(|models| {
-            models
-                .into_iter()
-                .map(|m| OllamaModel {
-                    id: m.id,
-                    display_name: m.display_name,
-                    size: m.size,
-                })
-                .collect()
-        }),
-    }))
+    db::commands::create_command(&conn, &command)
 }
 
 #[tauri::command]
-async fn set_ollama_config(
-    config: Option<OllamaConfig>,
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn update_slash_command(
+    id: String,
+    command: db::commands::SlashCommandInput,
+    state: State<'_, DbState>,
+) -> Result<db::commands::SlashCommand, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::commands::update_command(&conn, &id, &command)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn delete_slash_command(id: String, state: State<'_, DbState>) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::commands::delete_command(&conn, &id)
+}
+
+// ============================================================================
+// Workspace Hook Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_workspace_hooks(
+    workspace_id: String,
+    state: State<'_, DbState>,
+) -> Result<db::workspaces::WorkspaceHooks, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::workspaces::get_hooks(&conn, &workspace_id)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_workspace_hooks(
+    workspace_id: String,
+    hooks: db::workspaces::WorkspaceHooks,
     state: State<'_, DbState>,
 ) -> Result<(), String> {
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    let db_config = config.map(|c| db::settings::OllamaConfig {
-        base_url: c.base_url,
-        enabled: c.enabled,
-        last_validated: c.last_validated,
-        models: c.models.map(|models| {
-            models
-                .into_iter()
-                .map(|m| db::settings::OllamaModel {
-                    id: m.id,
-                    display_name: m.display_name,
-                    size: m.size,
-                })
-                .collect()
-        }),
-    });
-    db::settings::set_ollama_config(&conn, db_config.as_ref())
+    db::workspaces::set_hooks(&conn, &workspace_id, &hooks)
 }
 
 // ============================================================================
-// Azure Foundry Commands
+// Workspace Post-processing Commands
 // ============================================================================
 
 #[tauri::command]
-async fn get_azure_foundry_config(
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_workspace_postprocess(
+    workspace_id: String,
     state: State<'_, DbState>,
-) -> Result<Option<AzureFoundryConfig>, String> {
+) -> Result<db::workspaces::PostprocessSettings, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::workspaces::get_postprocess(&conn, &workspace_id)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_workspace_postprocess(
+    workspace_id: String,
+    settings: db::workspaces::PostprocessSettings,
+    state: State<'_, DbState>,
+) -> Result<(), String> {
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    let config = db::settings::get_azure_foundry_config(&conn);
-    Ok(config.map(|c| AzureFoundryConfig {
-        base_url: c.base_url,
-        deployment_name: c.deployment_name,
-        auth_type: c.auth_type,
-        enabled: c.enabled,
-        last_validated: c.last_validated,
-    }))
+    db::workspaces::set_postprocess(&conn, &workspace_id, &settings)?;
+    // Running tasks pick up the change with their next message
+    postprocess::invalidate();
+    Ok(())
+}
+
+// ============================================================================
+// Workspace Default Commands
+// ============================================================================
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_workspace_defaults(
+    workspace_id: String,
+    state: State<'_, DbState>,
+) -> Result<db::workspaces::WorkspaceDefaults, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::workspaces::get_defaults(&conn, &workspace_id)
 }
 
+/// Set the model and agent used by the workspace's tasks; empty values clear them
 #[tauri::command]
-async fn set_azure_foundry_config(
-    config: Option<AzureFoundryConfig>,
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_workspace_defaults(
+    workspace_id: String,
+    defaults: db::workspaces::WorkspaceDefaults,
     state: State<'_, DbState>,
 ) -> Result<(), String> {
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    let db_config = config.map(|c| db::settings::AzureFoundryConfig {
-        base_url: c.base_url,
-        deployment_name: c.deployment_name,
-        auth_type: c.auth_type,
-        enabled: c.enabled,
-        last_validated: c.last_validated,
-    });
-    db::settings::set_azure_foundry_config(&conn, db_config.as_ref())
+    db::workspaces::set_defaults(&conn, &workspace_id, &defaults)
 }
 
+/// Instructions, permission rules, pinned files, and MCP servers of the workspace
 #[tauri::command]
-async fn test_azure_foundry_connection(
-    _config: AzureFoundryTestConfig,
-) -> Result<ValidationResult, String> {
-    // TODO: Implement Azure Foundry connection test
-    Ok(ValidationResult {
-        valid: false,
-        error: Some("Azure Foundry connection test not yet implemented".to_string()),
-    })
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_workspace_agent_settings(
+    workspace_id: String,
+    state: State<'_, DbState>,
+) -> Result<db::workspaces::AgentSettings, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::workspaces::get_agent_settings(&conn, &workspace_id)
 }
 
+/// Replace the workspace's agent settings; tasks started afterwards get them
 #[tauri::command]
-async fn save_azure_foundry_config(
-    config: AzureFoundryTestConfig,
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_workspace_agent_settings(
+    workspace_id: String,
+    settings: db::workspaces::AgentSettings,
     state: State<'_, DbState>,
 ) -> Result<(), String> {
-    // Store API key securely if present
-    if let Some(api_key) = &config.api_key {
-        secure_storage::store_api_key("azureFoundry", api_key)?;
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::workspaces::set_agent_settings(&conn, &workspace_id, &settings)
+}
+
+// ============================================================================
+// Workspace Template Commands
+// ============================================================================
+
+/// Built-in and imported workspace templates
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn list_workspace_templates(
+    state: State<'_, DbState>,
+) -> Result<Vec<db::templates::WorkspaceTemplate>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    templates::list(&conn)
+}
+
+/// Create a workspace for a local directory with a template's settings
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, fields(template_id = %template_id), err)]
+async fn create_workspace_from_template(
+    template_id: String,
+    path: String,
+    name: Option<String>,
+    state: State<'_, DbState>,
+) -> Result<db::workspaces::Workspace, String> {
+    let detected = workspace_detect::detect(&path).await;
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let workspace = templates::create_workspace(&mut conn, &template_id, &path, name.as_deref())?;
+    if let Some(detected) = &detected {
+        workspace_detect::remember(&conn, &workspace, detected)?;
     }
+    Ok(workspace)
+}
 
-    // Store rest of config (without API key) in database
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    db::settings::set_azure_foundry_config(
+/// Write a workspace's settings to a template file to share
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn export_workspace_template(
+    workspace_id: String,
+    name: String,
+    description: Option<String>,
+    path: String,
+    state: State<'_, DbState>,
+) -> Result<(), String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    templates::export(
         &conn,
-        Some(&db::settings::AzureFoundryConfig {
-            base_url: config.endpoint,
-            deployment_name: config.deployment_name,
-            auth_type: config.auth_type,
-            enabled: true,
-            last_validated: None,
-        }),
+        &workspace_id,
+        &name,
+        description.as_deref(),
+        std::path::Path::new(&path),
     )
 }
 
+/// Read a template file to show what importing it adds, MCP server commands included
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn preview_workspace_template(path: String) -> Result<templates::TemplatePreview, String> {
+    templates::preview(std::path::Path::new(&path))
+}
+
+/// Add the template in an exported file to the template list, once the user
+/// has approved the MCP servers `preview_workspace_template` showed
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn import_workspace_template(
+    path: String,
+    approved_mcp_servers: std::collections::BTreeMap<String, db::workspaces::McpServer>,
+    state: State<'_, DbState>,
+) -> Result<db::templates::WorkspaceTemplate, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    templates::import(&conn, std::path::Path::new(&path), &approved_mcp_servers)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn delete_workspace_template(
+    template_id: String,
+    state: State<'_, DbState>,
+) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    templates::delete(&conn, &template_id)
+}
+
 // ============================================================================
-// OpenRouter Commands
+// Sandbox Commands
 // ============================================================================
 
+/// Docker image the workspace's tasks run in, or `None` when they run on the host
 #[tauri::command]
-async fn fetch_openrouter_models() -> Result<OpenRouterModelsResult, String> {
-    // TODO: Requires API key from secure storage
-    Ok(OpenRouterModelsResult {
-        success: false,
-        models: None,
-        error: Some("OpenRouter not yet implemented".to_string()),
-    })
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_workspace_sandbox(
+    workspace_id: String,
+    state: State<'_, DbState>,
+) -> Result<Option<String>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::workspaces::get_sandbox_image(&conn, &workspace_id)
+}
+
+/// Run the workspace's tasks in a container from `image`; `None` runs them on the host
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_workspace_sandbox(
+    workspace_id: String,
+    image: Option<String>,
+    state: State<'_, DbState>,
+) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    if image.is_some() && db::workspaces::get_ssh_host_id(&conn, &workspace_id)?.is_some() {
+        return Err("Remote workspaces cannot run in a Docker sandbox".to_string());
+    }
+    db::workspaces::set_sandbox_image(&conn, &workspace_id, image.as_deref())
 }
 
 // ============================================================================
-// LiteLLM Commands
+// Project Index Commands
 // ============================================================================
 
+/// Walk a workspace and update its file index, rereading only changed files
 #[tauri::command]
-async fn test_litellm_connection(
-    url: String,
-    _api_key: Option<String>,
-) -> Result<OpenRouterModelsResult, String> {
-    let client = reqwest::Client::new();
-    let models_url = format!("{}/models", url.trim_end_matches('/'));
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn index_project(
+    workspace_id: String,
+    app: tauri::AppHandle,
+) -> Result<project_index::IndexSummary, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        project_index::index(&app.state::<DbState>(), &workspace_id)
+    })
+    .await
+    .map_err(|e| format!("Failed to index project: {}", e))?
+}
 
-    match client.get(&models_url).send().await {
-        Ok(response) => {
-            if response.status().is_success() {
-                #[derive(Deserialize)]
-                struct LiteLLMModelsResponse {
-                    data: Vec<LiteLLMModelInfo>,
-                }
-                #[derive(Deserialize)]
-                struct LiteLLMModelInfo {
-                    id: String,
-                    #[serde(default)]
-                    owned_by: String,
-                }
+/// Files of a workspace whose path or declared symbols match the query, for @-mentions
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn search_project_files(
+    workspace_id: String,
+    query: String,
+    limit: Option<i64>,
+    app: tauri::AppHandle,
+) -> Result<Vec<db::project_files::ProjectFileMatch>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let limit = limit.unwrap_or(50).clamp(1, 500);
+        project_index::search(&app.state::<DbState>(), &workspace_id, &query, limit)
+    })
+    .await
+    .map_err(|e| format!("Failed to search project files: {}", e))?
+}
 
-                match response.json::<LiteLLMModelsResponse>().await {
-                    Ok(resp) => {
-                        let models: Vec<OpenRouterModel> = resp
-                            .data
-                            .into_iter()
-                            .map(|m| OpenRouterModel {
-                                id: m.id.clone(),
-                                name: m.id,
-                                provider: m.owned_by,
-                                context_length: 0,
-                            })
-                            .collect();
+// ============================================================================
+// SSH Host Commands
+// ============================================================================
 
-                        Ok(OpenRouterModelsResult {
-                            success: true,
-                            models: Some(models),
-                            error: None,
-                        })
-                    }
-                    Err(e) => Ok(OpenRouterModelsResult {
-                        success: false,
-                        models: None,
-                        error: Some(format!("Failed to parse LiteLLM response: {}", e)),
-                    }),
-                }
-            } else {
-                Ok(OpenRouterModelsResult {
-                    success: false,
-                    models: None,
-                    error: Some(format!("LiteLLM returned status: {}", response.status())),
-                })
-            }
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn list_ssh_hosts(state: State<'_, DbState>) -> Result<Vec<db::ssh_hosts::SshHost>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::ssh_hosts::list_hosts(&conn)
+}
+
+/// Add an SSH host; `private_key` is required for, and only used by, "key" authentication
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn create_ssh_host(
+    input: db::ssh_hosts::SshHostInput,
+    private_key: Option<String>,
+    state: State<'_, DbState>,
+) -> Result<db::ssh_hosts::SshHost, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let host = db::ssh_hosts::create_host(&conn, &input)?;
+    if host.auth == "key" {
+        let stored = match private_key.as_deref().map(str::trim) {
+            Some(key) if !key.is_empty() => secure_storage::store_ssh_key(&host.id, key),
+            _ => Err("A private key is required for key authentication".to_string()),
+        };
+        if let Err(e) = stored {
+            db::ssh_hosts::delete_host(&conn, &host.id)?;
+            return Err(e);
         }
-        Err(e) => Ok(OpenRouterModelsResult {
-            success: false,
-            models: None,
-            error: Some(format!("Failed to connect to LiteLLM: {}", e)),
-        }),
     }
+    Ok(host)
+}
+
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn delete_ssh_host(
+    id: String,
+    state: State<'_, DbState>,
+) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::ssh_hosts::delete_host(&conn, &id)?;
+    secure_storage::delete_ssh_key(&id)?;
+    Ok(())
+}
+
+/// Connect to an SSH host and return its `uname -a`
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn test_ssh_host(
+    id: String,
+    state: State<'_, DbState>,
+) -> Result<String, String> {
+    let host = {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        db::ssh_hosts::get_host(&conn, &id)?.ok_or_else(|| format!("SSH host not found: {}", id))?
+    };
+    let target = remote::host_target(&host, String::new())?;
+    tauri::async_runtime::spawn_blocking(move || remote::test_connection(&target))
+        .await
+        .map_err(|e| format!("Failed to test SSH host: {}", e))?
+}
+
+/// Create a workspace for a directory on an SSH host
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn create_remote_workspace(
+    ssh_host_id: String,
+    path: String,
+    name: Option<String>,
+    state: State<'_, DbState>,
+) -> Result<db::workspaces::Workspace, String> {
+    let host = {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        db::ssh_hosts::get_host(&conn, &ssh_host_id)?
+            .ok_or_else(|| format!("SSH host not found: {}", ssh_host_id))?
+    };
+    let path = path.trim().trim_end_matches('/').to_string();
+    if !path.starts_with('/') {
+        return Err(format!("Expected an absolute remote path: {}", path));
+    }
+
+    let target = remote::host_target(&host, path.clone())?;
+    tauri::async_runtime::spawn_blocking(move || remote::check_directory(&target))
+        .await
+        .map_err(|e| format!("Failed to check remote directory: {}", e))??;
+
+    let name = name
+        .filter(|n| !n.trim().is_empty())
+        .unwrap_or_else(|| format!("{}:{}", host.name, path.rsplit('/').next().unwrap_or(&path)));
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::workspaces::create_remote_workspace(&conn, &ssh_host_id, &name, &path)
+}
+
+/// Read a file in a remote workspace; relative paths start at the workspace directory
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn read_remote_file(
+    workspace_id: String,
+    path: String,
+    app: tauri::AppHandle,
+) -> Result<String, String> {
+    let target = remote::workspace_target(&app, Some(&workspace_id))?
+        .ok_or_else(|| format!("Workspace is not remote: {}", workspace_id))?;
+    tauri::async_runtime::spawn_blocking(move || remote::read_file(&target, &path))
+        .await
+        .map_err(|e| format!("Failed to read remote file: {}", e))?
+}
+
+// ============================================================================
+// Environment Variable Commands
+// ============================================================================
+
+/// Get a workspace's environment variables; secret values are not returned
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_workspace_env(
+    workspace_id: String,
+    state: State<'_, DbState>,
+) -> Result<Vec<db::env_vars::EnvVar>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::env_vars::list(&conn, &workspace_id)
 }
 
+/// Replace a workspace's environment variables
 #[tauri::command]
-async fn fetch_litellm_models() -> Result<OpenRouterModelsResult, String> {
-    Ok(OpenRouterModelsResult {
-        success: false,
-        models: None,
-        error: Some("LiteLLM not yet implemented".to_string()),
-    })
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_workspace_env(
+    workspace_id: String,
+    vars: Vec<db::env_vars::EnvVar>,
+    state: State<'_, DbState>,
+) -> Result<(), String> {
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::workspaces::get_workspace(&conn, &workspace_id)?
+        .ok_or_else(|| format!("Workspace not found: {}", workspace_id))?;
+    task_env::set(&mut conn, &workspace_id, &vars)
 }
 
+/// Get a task's own environment variables; secret values are not returned
 #[tauri::command]
-async fn get_litellm_config(state: State<'_, DbState>) -> Result<Option<LiteLLMConfig>, String> {
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn get_task_env(
+    task_id: String,
+    state: State<'_, DbState>,
+) -> Result<Vec<db::env_vars::EnvVar>, String> {
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    let config = db::settings::get_litellm_config(&conn);
-    Ok(config.map(|c| LiteLLMConfig {
-        base_url: c.base_url,
-        enabled: c.enabled,
-        last_validated: c.last_validated,
-        models: c.models.map(|models| {
-            models
-                .into_iter()
-                .map(|m| OpenRouterModel {
-                    id: m.id,
-                    name: m.name,
-                    provider: m.provider,
-                    context_length: m.context_length,
-                })
-                .collect()
-        }),
-    }))
+    db::env_vars::list(&conn, &task_id)
 }
 
+/// Replace a task's environment variables, used from its next run on
 #[tauri::command]
-async fn set_litellm_config(
-    config: Option<LiteLLMConfig>,
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn set_task_env(
+    task_id: String,
+    vars: Vec<db::env_vars::EnvVar>,
     state: State<'_, DbState>,
 ) -> Result<(), String> {
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    let db_config = config.map(|c| db::settings::LiteLLMConfig {
-        base_url: c.base_url,
-        enabled: c.enabled,
-        last_validated: c.last_validated,
-        models: c.models.map(|models| {
-            models
-                .into_iter()
-                .map(|m| db::settings::LiteLLMModel {
-                    id: m.id,
-                    name: m.name,
-                    provider: m.provider,
-                    context_length: m.context_length,
-                })
-                .collect()
-        }),
-    });
-    db::settings::set_litellm_config(&conn, db_config.as_ref())
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::tasks::get_task(&conn, &task_id)
+        .ok_or_else(|| format!("Task not found: {}", task_id))?;
+    task_env::set(&mut conn, &task_id, &vars)
 }
 
 // ============================================================================
-// Bedrock Commands
+// Terminal Commands
 // ============================================================================
 
+/// Open a shell in the task's working directory and return the terminal ID
 #[tauri::command]
-async fn validate_bedrock_credentials(credentials: String) -> Result<ValidationResult, String> {
-    // Parse and validate the credentials format
-    match serde_json::from_str::<BedrockCredentials>(&credentials) {
-        Ok(creds) => {
-            if creds.access_key_id.is_empty()
-                || creds.secret_access_key.is_empty()
-                || creds.region.is_empty()
-            {
-                Ok(ValidationResult {
-                    valid: false,
-                    error: Some("All credential fields are required".to_string()),
-                })
-            } else {
-                Ok(ValidationResult {
-                    valid: true,
-                    error: None,
-                })
+#[tracing::instrument(target = "command", skip_all, fields(task_id = %task_id), err)]
+async fn open_terminal(
+    task_id: String,
+    rows: u16,
+    cols: u16,
+    app: tauri::AppHandle,
+    db_state: State<'_, DbState>,
+    terminal_state: State<'_, terminal::TerminalState>,
+) -> Result<String, String> {
+    let cwd = {
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        let task = db::tasks::get_task(&conn, &task_id)
+            .ok_or_else(|| format!("Task not found: {}", task_id))?;
+        match (&task.workspace_id, &task.session_id) {
+            (Some(_), _) => db::tasks::get_working_directory(&conn, &task_id)?,
+            (None, Some(session_id)) => {
+                db::workspaces::get_session_directory(&conn, session_id)?.map(|(path, _)| path)
             }
+            (None, None) => None,
         }
-        Err(e) => Ok(ValidationResult {
-            valid: false,
-            error: Some(format!("Invalid credentials format: {}", e)),
-        }),
     }
-}
-
-#[tauri::command]
-async fn save_bedrock_credentials(credentials: String) -> Result<ApiKeyConfig, String> {
-    secure_storage::store_bedrock_credentials(&credentials)?;
+    .ok_or_else(|| format!("Task {} has no working directory", task_id))?;
 
-    Ok(ApiKeyConfig {
-        id: "apikey-bedrock".to_string(),
-        provider: "bedrock".to_string(),
-        label: Some("AWS Bedrock".to_string()),
-        created_at: chrono::Utc::now().to_rfc3339(),
-    })
+    terminal_state.open(&app, &task_id, &cwd, rows, cols)
 }
 
 #[tauri::command]
-async fn get_bedrock_credentials() -> Result<Option<BedrockCredentials>, String> {
-    match secure_storage::get_bedrock_credentials()? {
-        Some(creds) => Ok(Some(BedrockCredentials {
-            access_key_id: creds.access_key_id,
-            secret_access_key: creds.secret_access_key,
-            region: creds.region,
-        })),
-        None => Ok(None),
-    }
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn write_terminal(
+    terminal_id: String,
+    data: String,
+    terminal_state: State<'_, terminal::TerminalState>,
+) -> Result<(), String> {
+    terminal_state.write(&terminal_id, &data)
 }
 
 #[tauri::command]
-async fn fetch_bedrock_models(_credentials: String) -> Result<BedrockModelsResult, String> {
-    // TODO: Implement AWS Bedrock model listing
-    Ok(BedrockModelsResult {
-        success: false,
-        models: vec![],
-        error: Some("Bedrock not yet implemented".to_string()),
-    })
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn resize_terminal(
+    terminal_id: String,
+    rows: u16,
+    cols: u16,
+    terminal_state: State<'_, terminal::TerminalState>,
+) -> Result<(), String> {
+    terminal_state.resize(&terminal_id, rows, cols)
 }
 
-// ============================================================================
-// E2E Testing Command
-// ============================================================================
-
 #[tauri::command]
-async fn is_e2e_mode() -> Result<bool, String> {
-    Ok(std::env::var("E2E_MODE").is_ok())
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn close_terminal(
+    terminal_id: String,
+    terminal_state: State<'_, terminal::TerminalState>,
+) -> Result<(), String> {
+    terminal_state.close(&terminal_id)
 }
 
 // ============================================================================
-// Provider Settings Commands
+// Attachment Commands
 // ============================================================================
 
-#[tauri::command]
-async fn get_provider_settings(state: State<'_, DbState>) -> Result<ProviderSettings, String> {
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    let settings = db::providers::get_provider_settings(&conn);
-
-    let connected_providers: HashMap<String, ConnectedProviderResponse> = settings
-        .connected_providers
-        .into_iter()
-        .map(|(k, v)| {
-            (
-                k,
-                ConnectedProviderResponse {
-                    id: v.provider_id,
-                    selected_model: v.selected_model_id,
-                    config: serde_json::to_value(&v.credentials).ok(),
-                },
-            )
-        })
-        .collect();
-
-    Ok(ProviderSettings {
-        active_provider: settings.active_provider_id,
-        connected_providers,
-        debug_mode: settings.debug_mode,
-    })
+/// Largest file that can be attached, from the setting or the default
+fn max_attachment_bytes(state: &DbState) -> Result<u64, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    Ok(db::settings::get_max_attachment_bytes(&conn).unwrap_or(attachments::DEFAULT_MAX_BYTES))
 }
 
+/// Copy a file into app storage as an attachment
 #[tauri::command]
-async fn set_active_provider(
-    provider_id: Option<String>,
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn add_attachment(
+    path: String,
+    app: tauri::AppHandle,
     state: State<'_, DbState>,
-) -> Result<(), String> {
+) -> Result<db::attachments::Attachment, String> {
+    let max_bytes = max_attachment_bytes(&state)?;
+    // Copying and thumbnailing are kept off the async runtime
+    let attachment = tauri::async_runtime::spawn_blocking(move || {
+        attachments::store_file(&app, std::path::Path::new(&path), max_bytes)
+    })
+    .await
+    .map_err(|e| format!("Failed to store attachment: {}", e))??;
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    db::providers::set_active_provider(&conn, provider_id.as_deref())
+    db::attachments::insert_attachment(&conn, &attachment)?;
+    Ok(attachment)
 }
 
+/// Store base64 data (e.g. a pasted screenshot) as an attachment
 #[tauri::command]
-async fn get_connected_provider(
-    provider_id: String,
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn add_attachment_data(
+    file_name: String,
+    data: String,
+    app: tauri::AppHandle,
     state: State<'_, DbState>,
-) -> Result<Option<ConnectedProviderResponse>, String> {
+) -> Result<db::attachments::Attachment, String> {
+    let max_bytes = max_attachment_bytes(&state)?;
+    let attachment = tauri::async_runtime::spawn_blocking(move || {
+        attachments::store_base64(&app, &file_name, &data, max_bytes)
+    })
+    .await
+    .map_err(|e| format!("Failed to store attachment: {}", e))??;
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    let provider = db::providers::get_connected_provider(&conn, &provider_id);
-
-    Ok(provider.map(|p| ConnectedProviderResponse {
-        id: p.provider_id,
-        selected_model: p.selected_model_id,
-        config: serde_json::to_value(&p.credentials).ok(),
-    }))
+    db::attachments::insert_attachment(&conn, &attachment)?;
+    Ok(attachment)
 }
 
+/// Thumbnail of an image attachment as a JPEG data URL; `None` for other attachments
 #[tauri::command]
-async fn set_connected_provider(
-    provider_id: String,
-    provider: ConnectedProviderInput,
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_attachment_thumbnail(
+    attachment_id: String,
     state: State<'_, DbState>,
-) -> Result<(), String> {
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
-
-    // Convert input to db type
-    let db_provider = db::providers::ConnectedProvider {
-        provider_id: provider.id,
-        connection_status: "connected".to_string(),
-        selected_model_id: provider.selected_model,
-        credentials: db::providers::ProviderCredentials {
-            credentials_type: "api_key".to_string(),
-            key_prefix: None,
-            server_url: None,
-            api_key: None,
-            extra: HashMap::new(),
-        },
-        last_connected_at: chrono::Utc::now().to_rfc3339(),
-        available_models: None,
+) -> Result<Option<String>, String> {
+    let attachment = {
+        let conn = state.reader.lock().map_err(|e| e.to_string())?;
+        db::attachments::get_attachment(&conn, &attachment_id)?
     };
-
-    db::providers::set_connected_provider(&conn, &provider_id, &db_provider)
+    let attachment =
+        attachment.ok_or_else(|| format!("Attachment not found: {}", attachment_id))?;
+    tauri::async_runtime::spawn_blocking(move || attachments::thumbnail(&attachment))
+        .await
+        .map_err(|e| format!("Failed to get thumbnail: {}", e))?
 }
 
+/// Thumbnail of a screenshot attached to a message as a JPEG data URL; `None` for other
+/// attachments
 #[tauri::command]
-async fn remove_connected_provider(
-    provider_id: String,
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_message_attachment_thumbnail(
+    attachment_id: i64,
     state: State<'_, DbState>,
-) -> Result<(), String> {
+) -> Result<Option<String>, String> {
+    let attachment = {
+        let conn = state.reader.lock().map_err(|e| e.to_string())?;
+        if let Some(thumbnail) = db::tasks::get_message_thumbnail(&conn, attachment_id)? {
+            return Ok(Some(thumbnail));
+        }
+        db::tasks::get_message_attachment(&conn, attachment_id)?
+    };
+    let Some(attachment) = attachment.filter(|a| a.att_type == "screenshot") else {
+        return Ok(None);
+    };
+    // Screenshots stored before thumbnails existed get theirs now
+    let thumbnail = tauri::async_runtime::spawn_blocking(move || {
+        attachments::screenshot_thumbnail(&attachment.data)
+    })
+    .await
+    .map_err(|e| format!("Failed to get thumbnail: {}", e))??;
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    db::providers::remove_connected_provider(&conn, &provider_id)
+    db::tasks::set_message_thumbnail(&conn, attachment_id, &thumbnail)?;
+    Ok(Some(thumbnail))
 }
 
+/// Largest file that can be attached, in bytes
 #[tauri::command]
-async fn update_provider_model(
-    provider_id: String,
-    model_id: Option<String>,
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_max_attachment_size(state: State<'_, DbState>) -> Result<u64, String> {
+    max_attachment_bytes(&state)
+}
+
+/// Set the largest file that can be attached; `None` restores the default
+#[tauri::command]
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn set_max_attachment_size(
+    bytes: Option<u64>,
     state: State<'_, DbState>,
 ) -> Result<(), String> {
+    if bytes == Some(0) {
+        return Err("The attachment size limit must be above zero".to_string());
+    }
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    db::providers::update_provider_model(&conn, &provider_id, model_id.as_deref())
+    db::settings::set_max_attachment_bytes(&conn, bytes)
 }
 
 #[tauri::command]
-async fn set_provider_debug_mode(enabled: bool, state: State<'_, DbState>) -> Result<(), String> {
-    let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    db::providers::set_provider_debug_mode(&conn, enabled)
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn get_attachment(
+    attachment_id: String,
+    state: State<'_, DbState>,
+) -> Result<Option<db::attachments::Attachment>, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    db::attachments::get_attachment(&conn, &attachment_id)
 }
 
+/// Fetch a web page or PDF as an attachment, reusing a recent fetch of the same URL
 #[tauri::command]
-async fn get_provider_debug_mode(state: State<'_, DbState>) -> Result<bool, String> {
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn attach_url(
+    url: String,
+    app: tauri::AppHandle,
+    state: State<'_, DbState>,
+) -> Result<db::attachments::Attachment, String> {
+    {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        let since = (chrono::Utc::now() - chrono::Duration::hours(attachments::URL_CACHE_HOURS)).to_rfc3339();
+        if let Some(cached) = db::attachments::get_cached_url(&conn, &url, &since)? {
+            if std::path::Path::new(&cached.path).is_file() {
+                return Ok(cached);
+            }
+        }
+    }
+
+    let attachment = attachments::fetch_url(&app, &url).await?;
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    Ok(db::providers::get_provider_debug_mode(&conn))
+    db::attachments::insert_attachment(&conn, &attachment)?;
+    Ok(attachment)
 }
 
-// ============================================================================
-// Logging Command
-// ============================================================================
-
+/// Transcribe an audio attachment and store the transcript on it
 #[tauri::command]
-async fn log_event(payload: LogPayload) -> Result<(), String> {
-    println!(
-        "[{}] {}",
-        payload.level.unwrap_or_else(|| "info".to_string()),
-        payload.message
-    );
-    Ok(())
+#[tracing::instrument(target = "command", skip_all, err)]
+async fn transcribe_audio(
+    attachment_id: String,
+    state: State<'_, DbState>,
+) -> Result<String, String> {
+    let attachment = {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        db::attachments::get_attachment(&conn, &attachment_id)?
+            .ok_or_else(|| format!("Attachment not found: {}", attachment_id))?
+    };
+    if let Some(text) = attachment.text.clone().filter(|_| attachment.kind == "audio") {
+        return Ok(text);
+    }
+
+    let text = attachments::transcribe(&attachment).await?;
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::attachments::set_text(&conn, &attachment_id, &text)?;
+    Ok(text)
 }
 
 // ============================================================================
@@ -1466,13 +5606,160 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_shell::init())
         .setup(|app| {
+            // Safe mode decides what runs below, so it's settled before anything else
+            let safe_mode = safe_mode::init();
+
+            // Pick the profile next, since it decides where everything below is stored;
+            // an invalid one leaves the default profile in use
+            let profile_error = profile::init(app.handle()).err();
+
+            // Initialize logging first so everything below is captured
+            let logging_state = logging::init(app.handle())
+                .expect("Failed to initialize logging");
+            if let Some(e) = profile_error {
+                tracing::error!("[Profile] {}; using the default profile", e);
+            }
+            if safe_mode {
+                tracing::warn!(
+                    "[SafeMode] Started in safe mode: sidecar, hooks and startup jobs are off"
+                );
+            }
+
+            // Install the panic hook before anything else can crash
+            if let Err(e) = crash::init(app.handle()) {
+                tracing::warn!("[Crash] Failed to initialize crash reporting: {}", e);
+            }
+
+            // A restore chosen before the restart replaces the database before it's opened
+            if let Err(e) = backup::apply_pending_restore(app.handle()) {
+                tracing::error!("[Backup] {}", e);
+            }
+
             // Initialize database
             let db_state = db::init_database(app.handle())
                 .expect("Failed to initialize database");
+
+            // Apply the persisted log level
+            if let Ok(conn) = db_state.conn.lock() {
+                if let Some(level) = db::settings::get_log_level(&conn) {
+                    if let Err(e) = logging_state.set_level(&level) {
+                        tracing::warn!("[Logging] {}", e);
+                    }
+                }
+            }
+
+            // Start persisting log records now that the schema exists
+            if let Err(e) = logging_state.attach_log_store(db::get_database_path(app.handle())) {
+                tracing::warn!("[Logging] {}", e);
+            }
+
+            // Write messages buffered by a previous run that didn't shut down cleanly;
+            // safe mode leaves them for the next normal run
+            let mut unwritten = Vec::new();
+            if !safe_mode {
+                if let Ok(mut conn) = db_state.conn.lock() {
+                    unwritten = message_buffer::replay_journal(app.handle(), &mut conn);
+                }
+            }
+
+            app.manage(logging_state);
             app.manage(db_state);
+            app.manage(MessageBufferState::new(app.handle(), unwritten));
+            message_buffer::start_flushing(app.handle().clone());
+            checkpoint::start(app.handle().clone());
+            if !safe_mode {
+                backup::start_schedule(app.handle().clone());
+                session_lock::start(app.handle().clone());
+                reminders::start(app.handle().clone());
+
+                // Tasks still running when the previous run ended can't be running now
+                recovery::recover(app.handle());
+                health::start(app.handle().clone());
+            }
 
             // Initialize sidecar state
             app.manage(SidecarState::new());
+            app.manage(terminal::TerminalState::default());
+            app.manage(sandbox::SandboxState::default());
+
+            // Reopen the main window where it was left
+            window_state::init(app.handle());
+
+            // Remove SSH key files a previous session didn't get to
+            remote::remove_stale_keys(app.handle());
+
+            // Remove sandbox containers left running by a previous session
+            let sandboxed = app
+                .state::<DbState>()
+                .conn
+                .lock()
+                .map(|conn| db::workspaces::has_sandboxes(&conn))
+                .unwrap_or(false);
+            if sandboxed && !safe_mode {
+                std::thread::spawn(sandbox::remove_stale);
+            }
+
+            // Start the local API server if the user opted in
+            let api_settings = app
+                .state::<DbState>()
+                .conn
+                .lock()
+                .map(|conn| db::settings::get_api_server_settings(&conn))
+                .unwrap_or_default();
+            let api_state = ApiServerState::new();
+            api_state.set_websocket_enabled(api_settings.websocket_enabled);
+            api_state.set_metrics_enabled(api_settings.metrics_enabled);
+            app.manage(api_state);
+            if api_settings.enabled && !safe_mode {
+                let handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    let port = api_settings.port.unwrap_or(api_server::DEFAULT_PORT);
+                    if let Err(e) = handle.state::<ApiServerState>().start(&handle, port).await {
+                        tracing::warn!("[ApiServer] {}", e);
+                    }
+                });
+            }
+
+            // Everything below reaches outside the app or rewrites stored data
+            if safe_mode {
+                return Ok(());
+            }
+
+            // Merge history from the sync folder without blocking startup
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                let state = handle.state::<DbState>();
+                let folder = match state.conn.lock() {
+                    Ok(conn) => db::settings::get_sync_folder(&conn),
+                    Err(_) => return,
+                };
+                let Some(folder) = folder else {
+                    return;
+                };
+                match sync::sync_folder(&state, std::path::Path::new(&folder)) {
+                    Ok(report) if report.changed_local() => {
+                        if let Err(e) = handle.emit("history:synced", &report) {
+                            tracing::warn!("[Sync] Failed to emit history:synced: {}", e);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("[Sync] {}", e),
+                }
+            });
+
+            // Store attachment data written before it was deduplicated once
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                let state = handle.state::<DbState>();
+                let Ok(mut conn) = state.conn.lock() else {
+                    return;
+                };
+                match db::blobs::dedup_inline(&mut conn) {
+                    Ok(0) => {}
+                    Ok(moved) => tracing::info!("[DB] Deduplicated {} attachments", moved),
+                    Err(e) => tracing::warn!("[DB] {}", e),
+                }
+            });
 
             Ok(())
         })
@@ -1480,21 +5767,81 @@ pub fn run() {
             // App Info
             get_version,
             get_platform,
+            // Profiles
+            list_profiles,
+            switch_profile,
             // Task operations
             start_task,
             cancel_task,
             interrupt_task,
+            abort_all_tasks,
+            await_task,
             get_task,
+            open_observer_window,
+            watch_task,
+            get_task_messages,
+            get_message_attachment,
             list_tasks,
+            list_tasks_filtered,
+            search_tasks,
             delete_task,
             clear_task_history,
+            list_archived_tasks,
+            restore_task,
             save_task_message,
+            get_message_diff,
+            get_message_sources,
+            get_task_progress,
+            list_task_artifacts,
             save_task_status,
+            get_task_timeline,
             save_task_session,
             save_task_summary,
             complete_task,
             respond_to_permission,
             resume_session,
+            send_follow_up,
+            sync_session,
+            get_session_context,
+            get_interrupted_tasks,
+            get_task_checkpoint,
+            resume_from_checkpoint,
+            create_checkpoint,
+            list_checkpoints,
+            delete_checkpoint,
+            restore_checkpoint,
+            get_task_plan,
+            approve_task_plan,
+            fork_task,
+            get_task_forks,
+            rerun_task,
+            get_task_reruns,
+            edit_message_and_rerun,
+            get_message_versions,
+            delete_task_message,
+            redact_task_message,
+            annotate_message,
+            rate_message,
+            list_message_ratings,
+            // Task notes
+            get_task_notes,
+            add_task_note,
+            update_task_note,
+            delete_task_note,
+            list_follow_ups,
+            // Task tags
+            list_projects,
+            create_project,
+            rename_project,
+            delete_project,
+            move_tasks_to_project,
+            add_task_tag,
+            remove_task_tag,
+            list_tags,
+            // Comparisons
+            start_comparison_task,
+            get_comparison,
+            diff_tasks,
             // Settings
             get_api_keys,
             add_api_key,
@@ -1502,6 +5849,12 @@ pub fn run() {
             get_debug_mode,
             set_debug_mode,
             get_app_settings,
+            get_last_workspace,
+            set_last_workspace,
+            list_workspaces,
+            create_workspace,
+            select_workspace,
+            detect_workspace,
             // API Key management
             has_api_key,
             set_api_key,
@@ -1511,15 +5864,23 @@ pub fn run() {
             clear_api_key,
             get_all_api_keys,
             has_any_api_key,
+            run_provider_diagnostics,
+            detect_available_providers,
             // Onboarding
             get_onboarding_complete,
             set_onboarding_complete,
+            get_startup_health,
             // Claude CLI
             check_claude_cli,
             get_claude_version,
+            install_opencode_cli,
+            get_cli_runtime,
+            set_cli_runtime,
             // Model selection
             get_selected_model,
             set_selected_model,
+            get_summary_model,
+            set_summary_model,
             // Ollama
             test_ollama_connection,
             get_ollama_config,
@@ -1543,6 +5904,10 @@ pub fn run() {
             fetch_bedrock_models,
             // E2E
             is_e2e_mode,
+            is_safe_mode,
+            exit_safe_mode,
+            #[cfg(any(debug_assertions, feature = "e2e"))]
+            seed_test_data,
             // Provider Settings
             get_provider_settings,
             set_active_provider,
@@ -1550,11 +5915,147 @@ pub fn run() {
             set_connected_provider,
             remove_connected_provider,
             update_provider_model,
+            list_model_parameters,
+            set_model_parameters,
             set_provider_debug_mode,
             get_provider_debug_mode,
             // Logging
             log_event,
+            get_log_level,
+            set_log_level,
+            query_logs,
+            get_slow_commands,
+            // Crash reporting
+            list_crash_reports,
+            submit_crash_report,
+            // Diagnostics
+            export_diagnostics,
+            get_db_stats,
+            get_storage_savings,
+            dedup_attachments,
+            get_backup_settings,
+            set_backup_settings,
+            create_backup,
+            list_backups,
+            restore_backup,
+            get_downgrade_notice,
+            import_downgraded_tasks,
+            dismiss_downgrade_notice,
+            // Export
+            export_task_html,
+            export_task_pdf,
+            export_task_shareable,
+            export_task,
+            // GitHub
+            set_github_token,
+            has_github_token,
+            remove_github_token,
+            detect_github_repo,
+            create_github_issue,
+            create_github_draft_pr,
+            // Local API server
+            get_api_server_status,
+            set_api_server_enabled,
+            set_api_server_websocket_enabled,
+            set_api_server_metrics_enabled,
+            get_api_server_token,
+            regenerate_api_server_token,
+            // Sync
+            get_sync_folder,
+            set_sync_folder,
+            sync_history_folder,
+            get_encrypted_sync_status,
+            set_encrypted_sync_enabled,
+            pair_device,
+            sync_now,
+            // Web search
+            get_web_search_settings,
+            set_web_search_settings,
+            // Budget
+            get_budget_limits,
+            set_budget_limits,
+            get_budget_status,
+            // Usage
+            get_usage_dashboard,
+            get_task_usage,
+            get_usage_summary,
+            export_task_metrics_csv,
+            // Import
+            import_claude_code_sessions,
+            import_opencode_sessions,
+            // Slash commands
+            list_slash_commands,
+            create_slash_command,
+            update_slash_command,
+            delete_slash_command,
+            // Workspace hooks
+            get_workspace_hooks,
+            set_workspace_hooks,
+            get_workspace_defaults,
+            set_workspace_defaults,
+            get_workspace_agent_settings,
+            set_workspace_agent_settings,
+            // Workspace templates
+            list_workspace_templates,
+            create_workspace_from_template,
+            export_workspace_template,
+            preview_workspace_template,
+            import_workspace_template,
+            delete_workspace_template,
+            // Response post-processing
+            get_workspace_postprocess,
+            set_workspace_postprocess,
+            // Sandbox
+            get_workspace_sandbox,
+            set_workspace_sandbox,
+            // Project index
+            index_project,
+            search_project_files,
+            // SSH hosts
+            list_ssh_hosts,
+            create_ssh_host,
+            delete_ssh_host,
+            test_ssh_host,
+            create_remote_workspace,
+            read_remote_file,
+            // Environment variables
+            get_workspace_env,
+            set_workspace_env,
+            get_task_env,
+            set_task_env,
+            // Terminal
+            open_terminal,
+            write_terminal,
+            resize_terminal,
+            close_terminal,
+            // Attachments
+            add_attachment,
+            add_attachment_data,
+            attach_url,
+            get_attachment,
+            get_attachment_thumbnail,
+            get_message_attachment_thumbnail,
+            get_max_attachment_size,
+            set_max_attachment_size,
+            transcribe_audio,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| match event {
+            // Quitting without closing the window, e.g. with Cmd+Q
+            tauri::RunEvent::ExitRequested { .. } => window_state::save(app),
+            // Write buffered messages before the process exits
+            tauri::RunEvent::Exit => {
+                let db_state = app.state::<DbState>();
+                let Ok(mut conn) = db_state.conn.lock() else {
+                    return;
+                };
+                app.state::<MessageBufferState>().flush(&mut conn);
+                drop(conn);
+                checkpoint::save_all(app);
+                session_lock::release_all(app);
+                remote::release_all();
+            }
+            _ => {}
+        });
 }
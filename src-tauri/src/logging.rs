@@ -0,0 +1,510 @@
+// src-tauri/src/logging.rs
+//! Structured logging built on `tracing`
+//!
+//! Events go to stdout and to a daily-rotated file under `app_data/logs`.
+//! Both outputs pass through secret redaction, and the level can be changed
+//! at runtime through the reload handle kept in `LoggingState`. Records are
+//! also persisted to the `logs` table so the debug panel can query them.
+//!
+//! Tauri commands and sidecar operations run in spans with the `command` and
+//! `sidecar` targets. Events inside them carry the span's `task_id`, and ones
+//! slower than `SLOW_COMMAND_THRESHOLD` are logged and kept for
+//! `get_slow_commands`.
+
+use crate::db::logs::LogRecord;
+use regex::Regex;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{Receiver, SyncSender};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+use tracing::level_filters::LevelFilter;
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, Layer, Registry};
+
+/// Number of rotated log files kept on disk
+const MAX_LOG_FILES: usize = 7;
+
+/// Maximum number of records written to the log store per transaction
+const LOG_STORE_BATCH_SIZE: usize = 200;
+
+/// Records waiting for the log store writer; more are dropped rather than
+/// letting a slow disk hold up logging
+const LOG_STORE_CAPACITY: usize = 10_000;
+
+/// Number of inserted records between log store pruning passes
+const LOG_STORE_PRUNE_INTERVAL: usize = 1_000;
+
+/// Commands and sidecar operations taking longer are logged and kept
+const SLOW_COMMAND_THRESHOLD: Duration = Duration::from_millis(250);
+
+/// Number of slow commands kept for `get_slow_commands`
+const MAX_SLOW_COMMANDS: usize = 100;
+
+/// Default level when nothing is stored in settings
+#[cfg(debug_assertions)]
+pub const DEFAULT_LOG_LEVEL: &str = "debug";
+#[cfg(not(debug_assertions))]
+pub const DEFAULT_LOG_LEVEL: &str = "info";
+
+/// Patterns for secrets that must never reach a log line
+static SECRET_PATTERNS: LazyLock<Vec<(Regex, &'static str)>> = LazyLock::new(|| {
+    vec![
+        // Provider API keys (Anthropic, OpenAI, OpenRouter)
+        (
+            Regex::new(r"\b(sk-(?:ant-|or-|proj-)?)[A-Za-z0-9_\-]{8,}").unwrap(),
+            "${1}[REDACTED]",
+        ),
+        // Google API keys
+        (Regex::new(r"\bAIza[0-9A-Za-z_\-]{20,}").unwrap(), "[REDACTED]"),
+        // AWS access key IDs
+        (Regex::new(r"\b(AKIA|ASIA)[0-9A-Z]{16}\b").unwrap(), "[REDACTED]"),
+        // GitHub tokens
+        (
+            Regex::new(r"\b(gh[pousr]_[A-Za-z0-9]{20,}|github_pat_[A-Za-z0-9_]{20,})").unwrap(),
+            "[REDACTED]",
+        ),
+        // Bearer tokens
+        (
+            Regex::new(r"(?i)(bearer\s+)[A-Za-z0-9._\-~+/]+=*").unwrap(),
+            "${1}[REDACTED]",
+        ),
+        // key=value / "key": "value" pairs with secret-looking names
+        (
+            Regex::new(
+                r#"(?i)("?(?:api[_-]?key|apikey|secret[_-]?access[_-]?key|secretaccesskey|password|token|authorization)"?\s*[:=]\s*"?)[^"\s,}]+"#,
+            )
+            .unwrap(),
+            "${1}[REDACTED]",
+        ),
+    ]
+});
+
+/// Records dropped because the log store writer fell behind
+static DROPPED_RECORDS: AtomicUsize = AtomicUsize::new(0);
+
+/// Queue a record for the log store, dropping it if the writer is behind
+fn send_record(sender: &SyncSender<LogRecord>, record: LogRecord) {
+    // A disconnected receiver only happens on shutdown
+    if let Err(std::sync::mpsc::TrySendError::Full(_)) = sender.try_send(record) {
+        DROPPED_RECORDS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Most recent slow commands, oldest first
+static SLOW_COMMANDS: LazyLock<Mutex<VecDeque<SlowCommand>>> =
+    LazyLock::new(|| Mutex::new(VecDeque::new()));
+
+/// A command or sidecar operation that took longer than `SLOW_COMMAND_THRESHOLD`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlowCommand {
+    pub name: String,
+    /// "command" or "sidecar"
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<String>,
+    pub duration_ms: u64,
+    /// "ok", or "error" if it logged an error (commands log the error they return)
+    pub outcome: String,
+    pub finished_at: String,
+}
+
+/// Slow commands, most recent first
+pub fn slow_commands() -> Vec<SlowCommand> {
+    SLOW_COMMANDS
+        .lock()
+        .map(|commands| commands.iter().rev().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Replace anything that looks like a secret with a redaction marker
+pub fn redact_secrets(input: &str) -> String {
+    let mut output = input.to_string();
+    for (pattern, replacement) in SECRET_PATTERNS.iter() {
+        if pattern.is_match(&output) {
+            output = pattern.replace_all(&output, *replacement).into_owned();
+        }
+    }
+    output
+}
+
+/// Parse a level name into a filter
+pub fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    match level.to_ascii_lowercase().as_str() {
+        "off" => Ok(LevelFilter::OFF),
+        "error" => Ok(LevelFilter::ERROR),
+        "warn" | "warning" => Ok(LevelFilter::WARN),
+        "info" => Ok(LevelFilter::INFO),
+        "debug" => Ok(LevelFilter::DEBUG),
+        "trace" => Ok(LevelFilter::TRACE),
+        _ => Err(format!("Invalid log level: {}", level)),
+    }
+}
+
+/// Logging state kept alive for the lifetime of the app
+pub struct LoggingState {
+    level_handle: reload::Handle<LevelFilter, Registry>,
+    log_store_rx: Mutex<Option<Receiver<LogRecord>>>,
+    _guard: WorkerGuard,
+}
+
+impl LoggingState {
+    /// Current level filter as a lowercase string
+    pub fn level(&self) -> String {
+        self.level_handle
+            .with_current(|level| level.to_string().to_ascii_lowercase())
+            .unwrap_or_else(|_| DEFAULT_LOG_LEVEL.to_string())
+    }
+
+    /// Change the level filter at runtime
+    pub fn set_level(&self, level: &str) -> Result<(), String> {
+        let filter = parse_level(level)?;
+        self.level_handle
+            .modify(|current| *current = filter)
+            .map_err(|e| format!("Failed to update log level: {}", e))
+    }
+
+    /// Start persisting log records into the database at `db_path`
+    ///
+    /// Records emitted before this call are buffered, up to `LOG_STORE_CAPACITY`,
+    /// and written once the store is attached. The writer uses its own connection so logging never
+    /// contends with the main database lock.
+    pub fn attach_log_store(&self, db_path: PathBuf) -> Result<(), String> {
+        let rx = self
+            .log_store_rx
+            .lock()
+            .map_err(|e| e.to_string())?
+            .take()
+            .ok_or("Log store is already attached")?;
+
+        let mut conn = Connection::open(&db_path)
+            .map_err(|e| format!("Failed to open log store: {}", e))?;
+        conn.busy_timeout(Duration::from_secs(5))
+            .map_err(|e| format!("Failed to configure log store: {}", e))?;
+
+        std::thread::Builder::new()
+            .name("log-store".to_string())
+            .spawn(move || run_log_store(&mut conn, rx))
+            .map_err(|e| format!("Failed to start log store: {}", e))?;
+
+        Ok(())
+    }
+}
+
+/// Drain the record channel into the `logs` table until the sender is dropped
+///
+/// Failures are reported with `eprintln!` since logging them through tracing
+/// would feed straight back into this writer.
+fn run_log_store(conn: &mut Connection, rx: Receiver<LogRecord>) {
+    let mut since_prune = 0;
+
+    while let Ok(first) = rx.recv() {
+        let mut batch = vec![first];
+        while batch.len() < LOG_STORE_BATCH_SIZE {
+            match rx.try_recv() {
+                Ok(record) => batch.push(record),
+                Err(_) => break,
+            }
+        }
+
+        if let Err(e) = crate::db::logs::insert_logs(conn, &batch) {
+            eprintln!("[Logging] {}", e);
+        }
+        let dropped = DROPPED_RECORDS.swap(0, Ordering::Relaxed);
+        if dropped > 0 {
+            eprintln!("[Logging] Log store fell behind, dropped {} records", dropped);
+        }
+
+        since_prune += batch.len();
+        if since_prune >= LOG_STORE_PRUNE_INTERVAL {
+            if let Err(e) = crate::db::logs::prune_logs(conn) {
+                eprintln!("[Logging] {}", e);
+            }
+            since_prune = 0;
+        }
+    }
+}
+
+/// Directory holding the rotated log files
+pub fn log_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(crate::profile::data_dir(app)?.join("logs"))
+}
+
+/// Initialize the global tracing subscriber
+pub fn init(app: &AppHandle) -> Result<LoggingState, String> {
+    let log_dir = log_dir(app)?;
+
+    std::fs::create_dir_all(&log_dir)
+        .map_err(|e| format!("Failed to create log directory: {}", e))?;
+
+    let file_appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix("cowork")
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(&log_dir)
+        .map_err(|e| format!("Failed to create log file appender: {}", e))?;
+    let (file_writer, guard) = tracing_appender::non_blocking(file_appender);
+
+    let (level_layer, level_handle) = reload::Layer::new(parse_level(DEFAULT_LOG_LEVEL)?);
+    let (log_store_tx, log_store_rx) = std::sync::mpsc::sync_channel(LOG_STORE_CAPACITY);
+
+    tracing_subscriber::registry()
+        .with(level_layer)
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(RedactingMakeWriter(std::io::stdout)),
+        )
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(RedactingMakeWriter(file_writer)),
+        )
+        .with(CrashBufferLayer)
+        .with(CommandTimingLayer {
+            sender: log_store_tx.clone(),
+        })
+        .with(LogStoreLayer {
+            sender: log_store_tx,
+        })
+        .try_init()
+        .map_err(|e| format!("Failed to initialize logging: {}", e))?;
+
+    Ok(LoggingState {
+        level_handle,
+        log_store_rx: Mutex::new(Some(log_store_rx)),
+        _guard: guard,
+    })
+}
+
+/// Writer factory that redacts secrets from every formatted line
+struct RedactingMakeWriter<M>(M);
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for RedactingMakeWriter<M> {
+    type Writer = RedactingWriter<M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter(self.0.make_writer())
+    }
+}
+
+struct RedactingWriter<W>(W);
+
+impl<W: Write> Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let redacted = redact_secrets(&String::from_utf8_lossy(buf));
+        self.0.write_all(redacted.as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Collects the formatted fields of an event into a single line
+#[derive(Default)]
+pub(crate) struct EventFields {
+    pub message: String,
+    pub fields: Vec<(String, String)>,
+}
+
+impl tracing::field::Visit for EventFields {
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields
+                .push((field.name().to_string(), value.to_string()));
+        }
+    }
+
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields
+                .push((field.name().to_string(), format!("{:?}", value)));
+        }
+    }
+}
+
+/// Feeds recent log lines into the crash report buffer
+struct CrashBufferLayer;
+
+impl<S: Subscriber> Layer<S> for CrashBufferLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut fields = EventFields::default();
+        event.record(&mut fields);
+
+        let metadata = event.metadata();
+        let mut line = format!(
+            "{} {} {}: {}",
+            chrono::Utc::now().to_rfc3339(),
+            metadata.level(),
+            metadata.target(),
+            fields.message
+        );
+        for (name, value) in &fields.fields {
+            line.push_str(&format!(" {}={}", name, value));
+        }
+        crate::crash::record_log_line(redact_secrets(&line));
+    }
+}
+
+/// Whether spans with this metadata wrap a command or sidecar operation
+fn is_timed(metadata: &Metadata<'_>) -> bool {
+    matches!(metadata.target(), "command" | "sidecar")
+}
+
+/// Kept in the extensions of a command or sidecar span
+struct SpanTiming {
+    started: Instant,
+    task_id: Option<String>,
+    failed: bool,
+}
+
+/// Times command and sidecar spans and records the slow ones
+struct CommandTimingLayer {
+    sender: SyncSender<LogRecord>,
+}
+
+impl<S> Layer<S> for CommandTimingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if !is_timed(attrs.metadata()) {
+            return;
+        }
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = EventFields::default();
+        attrs.record(&mut fields);
+        let task_id = fields
+            .fields
+            .into_iter()
+            .find(|(name, value)| name == "task_id" && !value.is_empty())
+            .map(|(_, value)| value);
+        span.extensions_mut().insert(SpanTiming {
+            started: Instant::now(),
+            task_id,
+            failed: false,
+        });
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if *event.metadata().level() != Level::ERROR {
+            return;
+        }
+        if let Some(span) = ctx.event_span(event) {
+            if let Some(timing) = span.extensions_mut().get_mut::<SpanTiming>() {
+                timing.failed = true;
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(timing) = span.extensions_mut().remove::<SpanTiming>() else {
+            return;
+        };
+        let duration = timing.started.elapsed();
+        if duration < SLOW_COMMAND_THRESHOLD {
+            return;
+        }
+
+        let slow = SlowCommand {
+            name: span.name().to_string(),
+            kind: span.metadata().target().to_string(),
+            task_id: timing.task_id,
+            duration_ms: duration.as_millis() as u64,
+            outcome: if timing.failed { "error" } else { "ok" }.to_string(),
+            finished_at: chrono::Utc::now().to_rfc3339(),
+        };
+        // Events emitted from inside the subscriber are dropped, so this goes
+        // straight to the log store
+        send_record(&self.sender, LogRecord {
+            id: None,
+            level: "warn".to_string(),
+            target: slow.kind.clone(),
+            message: format!(
+                "[Timing] {} took {}ms ({})",
+                slow.name, slow.duration_ms, slow.outcome
+            ),
+            task_id: slow.task_id.clone(),
+            timestamp: slow.finished_at.clone(),
+        });
+        if let Ok(mut commands) = SLOW_COMMANDS.lock() {
+            if commands.len() == MAX_SLOW_COMMANDS {
+                commands.pop_front();
+            }
+            commands.push_back(slow);
+        }
+    }
+}
+
+/// Sends structured records to the log store writer thread
+struct LogStoreLayer {
+    sender: SyncSender<LogRecord>,
+}
+
+impl<S> Layer<S> for LogStoreLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = EventFields::default();
+        event.record(&mut fields);
+
+        let mut task_id = None;
+        let mut message = fields.message;
+        for (name, value) in fields.fields {
+            if name == "task_id" {
+                if !value.is_empty() {
+                    task_id = Some(value);
+                }
+            } else {
+                message.push_str(&format!(" {}={}", name, value));
+            }
+        }
+        // Events inside a command are linked to the command's task
+        if task_id.is_none() {
+            task_id = ctx.event_scope(event).and_then(|scope| {
+                scope.into_iter().find_map(|span| {
+                    span.extensions()
+                        .get::<SpanTiming>()
+                        .and_then(|timing| timing.task_id.clone())
+                })
+            });
+        }
+
+        let metadata = event.metadata();
+        let record = LogRecord {
+            id: None,
+            level: metadata.level().as_str().to_ascii_lowercase(),
+            target: metadata.target().to_string(),
+            message: redact_secrets(&message),
+            task_id,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        };
+
+        send_record(&self.sender, record);
+    }
+}
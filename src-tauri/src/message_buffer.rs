@@ -0,0 +1,521 @@
+// src-tauri/src/message_buffer.rs
+//! Write-behind buffer for task messages
+//!
+//! Every message the sidecar streams is saved as it arrives, by the sidecar
+//! event handler rather than the UI so history survives a reload, and writing
+//! each one to SQLite hammers the disk during long tool output. Instead
+//! messages are buffered per task (a later save of the same message ID
+//! replaces the buffered one), and they are written in one
+//! transaction when the task finishes, when the task is read, every
+//! `FLUSH_INTERVAL`, and on shutdown.
+//!
+//! Buffered messages are also appended to a journal in the app data
+//! directory. The journal is rewritten after each flush, and replayed at
+//! startup so messages buffered before a crash aren't lost. A message that
+//! fails to write stays buffered, and in the journal, for the next flush;
+//! after `MAX_WRITE_ATTEMPTS` it is only kept in the journal, for the next
+//! start to try again.
+
+use crate::db::{self, DbState};
+use crate::TaskMessage;
+use rusqlite::{Connection, TransactionBehavior};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// Longest a buffered message waits to be written
+const FLUSH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Journal file in the app data directory
+const JOURNAL_FILE: &str = "message-journal.jsonl";
+
+/// Flushes a message may fail before it's left for the next start
+const MAX_WRITE_ATTEMPTS: u32 = 20;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JournalEntry {
+    task_id: String,
+    message: TaskMessage,
+}
+
+/// Messages per task, tasks in the order they were first buffered
+type Batches = Vec<(String, Vec<TaskMessage>)>;
+
+#[derive(Default)]
+struct Pending {
+    tasks: Batches,
+    /// Failed writes of buffered messages, by message ID
+    failures: HashMap<String, u32>,
+    /// Messages that failed `MAX_WRITE_ATTEMPTS` times, kept only in the journal
+    stranded: Batches,
+    journal: Option<File>,
+}
+
+/// Messages saved but not yet written to the database
+pub struct MessageBufferState {
+    pending: Mutex<Pending>,
+    journal_path: Option<PathBuf>,
+}
+
+fn journal_path(app: &AppHandle) -> Option<PathBuf> {
+    crate::profile::data_dir(app)
+        .ok()
+        .map(|dir| dir.join(JOURNAL_FILE))
+}
+
+/// Write a message and its structured diff
+fn write_message(conn: &Connection, task_id: &str, message: TaskMessage) -> Result<(), String> {
+    let message_id = message.id.clone();
+    let diff_source = message.tool_name.clone().zip(message.tool_input.clone());
+
+    let inserted = db::tasks::add_task_message(
+        conn,
+        task_id,
+        &db::tasks::TaskMessageInput {
+            id: message.id,
+            msg_type: message.msg_type,
+            content: message.content,
+            timestamp: message.timestamp,
+            tool_name: message.tool_name,
+            tool_input: message.tool_input,
+            // Attachments sent back without their data can't be stored
+            attachments: message.attachments.map(|atts| {
+                atts.into_iter()
+                    .filter_map(|a| {
+                        Some(db::tasks::AttachmentInput {
+                            att_type: a.att_type,
+                            data: a.data?,
+                            label: a.label,
+                            thumbnail: a.thumbnail,
+                        })
+                    })
+                    .collect()
+            }),
+            note: message.note,
+            origin_id: message.origin_id,
+        },
+    )?;
+    // Replayed or re-emitted, and its origin already stored
+    if !inserted {
+        return Ok(());
+    }
+
+    // File edits get a structured diff stored next to the message
+    if let Some((tool_name, tool_input)) = diff_source {
+        if let Err(e) = db::diffs::save_for_message(conn, &message_id, &tool_name, &tool_input) {
+            tracing::warn!("[Diff] {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Build a message's screenshot thumbnails, so writing it doesn't decode
+/// images while it holds the database
+fn build_thumbnails(message: &mut TaskMessage) {
+    for attachment in message.attachments.iter_mut().flatten() {
+        if attachment.att_type != "screenshot" || attachment.thumbnail.is_some() {
+            continue;
+        }
+        if let Some(data) = &attachment.data {
+            attachment.thumbnail = crate::attachments::screenshot_thumbnail(data)
+                .map_err(|e| tracing::warn!("[Messages] No thumbnail for {}: {}", message.id, e))
+                .ok();
+        }
+    }
+}
+
+/// Write messages in one transaction, returning those that weren't written
+fn write_all(conn: &mut Connection, batches: Batches) -> Batches {
+    // Take the write lock up front so the busy timeout applies, rather than failing at commit
+    let tx = match conn.transaction_with_behavior(TransactionBehavior::Immediate) {
+        Ok(tx) => tx,
+        Err(e) => {
+            tracing::error!("[Messages] Failed to start transaction: {}", e);
+            return batches;
+        }
+    };
+    let mut results = Vec::new();
+    for (task_id, messages) in batches {
+        let written: Vec<(TaskMessage, bool)> = messages
+            .into_iter()
+            .map(|message| match write_message(&tx, &task_id, message.clone()) {
+                Ok(()) => (message, true),
+                Err(e) => {
+                    tracing::warn!("[Messages] {} (task {})", e, task_id);
+                    (message, false)
+                }
+            })
+            .collect();
+        results.push((task_id, written));
+    }
+    let committed = match tx.commit() {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::error!("[Messages] Failed to commit messages: {}", e);
+            false
+        }
+    };
+
+    results
+        .into_iter()
+        .filter_map(|(task_id, written)| {
+            let failed: Vec<TaskMessage> = written
+                .into_iter()
+                .filter(|(_, ok)| !committed || !ok)
+                .map(|(message, _)| message)
+                .collect();
+            (!failed.is_empty()).then_some((task_id, failed))
+        })
+        .collect()
+}
+
+/// Message a streamed OpenCode event is shown and stored as
+///
+/// Matches `normalizeOpenCodeMessage` in the frontend, so a message saved
+/// again by the UI has the same ID: text parts are assistant messages, tool
+/// calls are tool messages, and other events aren't stored.
+pub fn streamed_message(event: &serde_json::Value) -> Option<TaskMessage> {
+    let part = event.get("part");
+    let field = |name: &str| part.and_then(|p| p.get(name));
+    let text = |name: &str| field(name).and_then(|v| v.as_str()).map(|s| s.to_string());
+
+    let (msg_type, content, tool_input) = match event.get("type")?.as_str()? {
+        "text" => {
+            let content = text("text").filter(|t| !t.trim().is_empty())?;
+            ("assistant", content, None)
+        }
+        "tool_call" => ("tool", String::new(), field("input").cloned()),
+        "tool_use" => ("tool", String::new(), part?.pointer("/state/input").cloned()),
+        _ => return None,
+    };
+    let timestamp = event
+        .get("timestamp")
+        .and_then(|t| t.as_f64())
+        .and_then(|t| chrono::DateTime::from_timestamp_millis(t as i64))
+        .unwrap_or_else(chrono::Utc::now);
+    let origin_id = text("id");
+    let id = text("messageID").or_else(|| origin_id.clone()).unwrap_or_else(|| {
+        format!("opencode_{}_{}", timestamp.timestamp_millis(), uuid::Uuid::new_v4().simple())
+    });
+
+    Some(TaskMessage {
+        id,
+        origin_id,
+        msg_type: msg_type.to_string(),
+        content,
+        timestamp: timestamp.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        tool_name: if msg_type == "tool" { text("tool") } else { None },
+        tool_input,
+        attachments: None,
+        note: None,
+    })
+}
+
+impl MessageBufferState {
+    /// A buffer holding the messages `replay_journal` couldn't write
+    pub fn new(app: &AppHandle, unwritten: Vec<(String, Vec<TaskMessage>)>) -> Self {
+        Self {
+            pending: Mutex::new(Pending {
+                tasks: unwritten,
+                ..Pending::default()
+            }),
+            journal_path: journal_path(app),
+        }
+    }
+
+    /// Buffer a message of a task
+    pub fn push(&self, task_id: &str, mut message: TaskMessage) -> Result<(), String> {
+        build_thumbnails(&mut message);
+        let mut pending = self.pending.lock().map_err(|e| e.to_string())?;
+
+        if pending.journal.is_none() {
+            if let Some(path) = &self.journal_path {
+                pending.journal = std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|e| tracing::warn!("[Messages] Failed to open journal: {}", e))
+                    .ok();
+            }
+        }
+        if let Some(journal) = pending.journal.as_mut() {
+            let entry = JournalEntry {
+                task_id: task_id.to_string(),
+                message: message.clone(),
+            };
+            if let Ok(line) = serde_json::to_string(&entry) {
+                if let Err(e) = writeln!(journal, "{}", line) {
+                    tracing::warn!("[Messages] Failed to write journal: {}", e);
+                }
+            }
+        }
+
+        match pending.tasks.iter_mut().find(|(id, _)| id == task_id) {
+            Some((_, messages)) => match messages.iter_mut().find(|m| m.id == message.id) {
+                Some(buffered) => *buffered = message,
+                None => messages.push(message),
+            },
+            None => pending.tasks.push((task_id.to_string(), vec![message])),
+        }
+        Ok(())
+    }
+
+    /// Take buffered messages, of all tasks or of one
+    fn take(&self, task_id: Option<&str>) -> Batches {
+        let Ok(mut pending) = self.pending.lock() else {
+            return Vec::new();
+        };
+        match task_id {
+            Some(task_id) => {
+                let (taken, rest) = std::mem::take(&mut pending.tasks)
+                    .into_iter()
+                    .partition(|(id, _)| id == task_id);
+                pending.tasks = rest;
+                taken
+            }
+            None => std::mem::take(&mut pending.tasks),
+        }
+    }
+
+    /// Rewrite the journal with the messages still buffered
+    fn rewrite_journal(&self) {
+        let (Some(path), Ok(mut pending)) = (&self.journal_path, self.pending.lock()) else {
+            return;
+        };
+        let rewrite = File::create(path).and_then(|mut journal| {
+            for (task_id, messages) in pending.stranded.iter().chain(&pending.tasks) {
+                for message in messages {
+                    let entry = JournalEntry {
+                        task_id: task_id.clone(),
+                        message: message.clone(),
+                    };
+                    let line = serde_json::to_string(&entry).unwrap_or_default();
+                    writeln!(journal, "{}", line)?;
+                }
+            }
+            Ok(journal)
+        });
+        match rewrite {
+            Ok(journal) => pending.journal = Some(journal),
+            Err(e) => {
+                tracing::warn!("[Messages] Failed to rewrite journal: {}", e);
+                pending.journal = None;
+            }
+        }
+    }
+
+    /// Put messages that failed to write back ahead of those buffered since
+    ///
+    /// A message buffered again meanwhile keeps the newer version, and one that
+    /// has failed `MAX_WRITE_ATTEMPTS` times is left for the next start.
+    fn requeue(&self, failed: Batches) {
+        let Ok(mut pending) = self.pending.lock() else {
+            return;
+        };
+        let failed_ids: HashSet<String> = failed
+            .iter()
+            .flat_map(|(_, messages)| messages.iter().map(|m| m.id.clone()))
+            .collect();
+        let pending = &mut *pending;
+        for (task_id, messages) in failed.into_iter().rev() {
+            let index = match pending.tasks.iter().position(|(id, _)| *id == task_id) {
+                Some(index) => index,
+                None => {
+                    pending.tasks.insert(0, (task_id.clone(), Vec::new()));
+                    0
+                }
+            };
+            let mut retried = Vec::new();
+            for message in messages {
+                if pending.tasks[index].1.iter().any(|m| m.id == message.id) {
+                    continue;
+                }
+                let attempts = pending.failures.entry(message.id.clone()).or_insert(0);
+                *attempts += 1;
+                if *attempts < MAX_WRITE_ATTEMPTS {
+                    retried.push(message);
+                    continue;
+                }
+                tracing::error!(
+                    "[Messages] Giving up on message {} of task {} until the next start",
+                    message.id,
+                    task_id
+                );
+                match pending.stranded.iter_mut().find(|(id, _)| *id == task_id) {
+                    Some((_, stranded)) => stranded.push(message),
+                    None => pending.stranded.push((task_id.clone(), vec![message])),
+                }
+            }
+            retried.append(&mut pending.tasks[index].1);
+            pending.tasks[index].1 = retried;
+        }
+        pending.tasks.retain(|(_, messages)| !messages.is_empty());
+        let tasks = &pending.tasks;
+        pending.failures.retain(|id, _| {
+            failed_ids.contains(id) || tasks.iter().any(|(_, m)| m.iter().any(|m| m.id == *id))
+        });
+    }
+
+    /// Write one task's buffered messages
+    pub fn flush_task(&self, conn: &mut Connection, task_id: &str) {
+        let taken = self.take(Some(task_id));
+        if !taken.is_empty() {
+            self.requeue(write_all(conn, taken));
+            self.rewrite_journal();
+        }
+    }
+
+    /// Write every buffered message
+    pub fn flush(&self, conn: &mut Connection) {
+        let taken = self.take(None);
+        if !taken.is_empty() {
+            self.requeue(write_all(conn, taken));
+            self.rewrite_journal();
+        }
+    }
+
+    /// Drop a deleted task's buffered messages
+    pub fn discard_task(&self, task_id: &str) {
+        if !self.take(Some(task_id)).is_empty() {
+            self.rewrite_journal();
+        }
+    }
+
+    /// Drop one buffered message, returning whether it was still buffered
+    pub fn discard_message(&self, task_id: &str, message_id: &str) -> bool {
+        let discarded = {
+            let Ok(mut pending) = self.pending.lock() else {
+                return false;
+            };
+            let Some((_, messages)) = pending.tasks.iter_mut().find(|(id, _)| id == task_id)
+            else {
+                return false;
+            };
+            let before = messages.len();
+            messages.retain(|m| m.id != message_id);
+            let discarded = messages.len() < before;
+            pending.tasks.retain(|(_, messages)| !messages.is_empty());
+            discarded
+        };
+        if discarded {
+            self.rewrite_journal();
+        }
+        discarded
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pending.lock().map(|p| p.tasks.is_empty()).unwrap_or(true)
+    }
+}
+
+/// Write messages left in the journal by a previous run that didn't flush them
+///
+/// Returns the messages that still couldn't be written, for the buffer to retry;
+/// the journal is kept until they are.
+pub fn replay_journal(app: &AppHandle, conn: &mut Connection) -> Vec<(String, Vec<TaskMessage>)> {
+    let Some(path) = journal_path(app) else {
+        return Vec::new();
+    };
+    let Ok(file) = File::open(&path) else {
+        return Vec::new();
+    };
+
+    let mut batches: Batches = Vec::new();
+    let mut replayed = 0;
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        // A crash can leave a partial last line
+        let Ok(entry) = serde_json::from_str::<JournalEntry>(&line) else {
+            continue;
+        };
+        if db::tasks::message_exists(conn, &entry.message.id) {
+            continue;
+        }
+        replayed += 1;
+        match batches.iter_mut().find(|(id, _)| *id == entry.task_id) {
+            Some((_, messages)) => match messages.iter_mut().find(|m| m.id == entry.message.id) {
+                Some(buffered) => *buffered = entry.message,
+                None => messages.push(entry.message),
+            },
+            None => batches.push((entry.task_id, vec![entry.message])),
+        }
+    }
+
+    let mut unwritten = Vec::new();
+    if !batches.is_empty() {
+        tracing::info!("[Messages] Replaying {} journaled message(s)", replayed);
+        unwritten = write_all(conn, batches);
+    }
+    if unwritten.is_empty() {
+        if let Err(e) = std::fs::remove_file(&path) {
+            tracing::warn!("[Messages] Failed to remove journal: {}", e);
+        }
+    }
+    unwritten
+}
+
+/// Flush buffered messages every `FLUSH_INTERVAL`
+pub fn start_flushing(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(FLUSH_INTERVAL);
+        let buffer = app.state::<MessageBufferState>();
+        if buffer.is_empty() {
+            continue;
+        }
+        let db_state = app.state::<DbState>();
+        let Ok(mut conn) = db_state.conn.lock() else {
+            continue;
+        };
+        buffer.flush(&mut conn);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::tasks::tests::{save_test_task, TestDb};
+
+    fn buffer() -> MessageBufferState {
+        MessageBufferState {
+            pending: Mutex::new(Pending::default()),
+            journal_path: None,
+        }
+    }
+
+    fn message(id: &str, content: &str) -> TaskMessage {
+        TaskMessage {
+            id: id.to_string(),
+            origin_id: Some("part_1".to_string()),
+            msg_type: "assistant".to_string(),
+            content: content.to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            tool_name: None,
+            tool_input: None,
+            attachments: None,
+            note: None,
+        }
+    }
+
+    #[test]
+    fn message_flushed_again_is_updated() {
+        let db = TestDb::new();
+        let mut conn = db.open();
+        save_test_task(&conn, "task_a");
+        let buffer = buffer();
+
+        buffer.push("task_a", message("m1", "Hello")).unwrap();
+        buffer.flush(&mut conn);
+        buffer.push("task_a", message("m1", "Hello, world")).unwrap();
+        buffer.flush(&mut conn);
+
+        let messages = db::tasks::get_task(&conn, "task_a").unwrap().messages;
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].content, "Hello, world");
+        assert!(buffer.is_empty());
+    }
+}
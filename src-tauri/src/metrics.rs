@@ -0,0 +1,113 @@
+// src-tauri/src/metrics.rs
+//! Prometheus metrics
+//!
+//! With the metrics endpoint switched on, the local API server serves
+//! `/metrics` in the Prometheus text exposition format, so agent activity on a
+//! shared machine can be scraped into existing dashboards. Task and token
+//! totals come from the database and cover the whole history; sidecar restarts
+//! are counted by this process since it started.
+
+use crate::db::usage;
+use rusqlite::Connection;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Content type of the text exposition format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Times the sidecar was spawned by this process
+static SIDECAR_STARTS: AtomicU64 = AtomicU64::new(0);
+
+/// Count a sidecar spawn; every one after the first is a restart
+pub fn record_sidecar_start() {
+    SIDECAR_STARTS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Escape a label value
+fn label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Write the `# HELP` and `# TYPE` lines of a metric
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Render every metric
+pub fn render(conn: &Connection) -> Result<String, String> {
+    let statuses = usage::get_status_totals(conn)?;
+    let models = usage::get_model_totals(conn)?;
+    let mut out = String::new();
+
+    header(&mut out, "cowork_tasks", "gauge", "Tasks by status.");
+    for status in &statuses {
+        let _ = writeln!(
+            out,
+            "cowork_tasks{{status=\"{}\"}} {}",
+            label(&status.status),
+            status.tasks
+        );
+    }
+
+    header(
+        &mut out,
+        "cowork_task_duration_seconds",
+        "summary",
+        "Time from start to completion of finished tasks, by status.",
+    );
+    for status in statuses.iter().filter(|s| s.timed > 0) {
+        let status_label = label(&status.status);
+        let _ = writeln!(
+            out,
+            "cowork_task_duration_seconds_sum{{status=\"{}\"}} {}",
+            status_label, status.duration_seconds
+        );
+        let _ = writeln!(
+            out,
+            "cowork_task_duration_seconds_count{{status=\"{}\"}} {}",
+            status_label, status.timed
+        );
+    }
+
+    header(
+        &mut out,
+        "cowork_sidecar_restarts_total",
+        "counter",
+        "Times the sidecar was started again since the app started.",
+    );
+    let starts = SIDECAR_STARTS.load(Ordering::Relaxed);
+    let _ = writeln!(out, "cowork_sidecar_restarts_total {}", starts.saturating_sub(1));
+
+    header(&mut out, "cowork_tokens_total", "counter", "Tokens used, by model and kind.");
+    for model in &models {
+        let model_label = label(&model.model_id);
+        for (kind, tokens) in [
+            ("input", model.input_tokens),
+            ("output", model.output_tokens),
+            ("reasoning", model.reasoning_tokens),
+            ("cache_read", model.cache_read_tokens),
+            ("cache_write", model.cache_write_tokens),
+        ] {
+            let _ = writeln!(
+                out,
+                "cowork_tokens_total{{model=\"{}\",kind=\"{}\"}} {}",
+                model_label, kind, tokens
+            );
+        }
+    }
+
+    header(&mut out, "cowork_cost_usd_total", "counter", "Cost in USD, by model.");
+    for model in &models {
+        let _ = writeln!(
+            out,
+            "cowork_cost_usd_total{{model=\"{}\"}} {}",
+            label(&model.model_id),
+            model.cost
+        );
+    }
+    Ok(out)
+}
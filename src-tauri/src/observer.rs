@@ -0,0 +1,83 @@
+// src-tauri/src/observer.rs
+//! Read-only observer windows
+//!
+//! An observer window follows a single task, e.g. on a second monitor or a
+//! screen share. App events are emitted to every window, so observer windows
+//! (labels starting with `LABEL_PREFIX`) get no event permissions in their
+//! capability and can't listen to them. Instead the window calls `watch_task`
+//! with a channel, and the events of that task alone are sent down it as they
+//! are forwarded. A window's watch ends when it watches another task or is
+//! closed.
+
+use crate::sidecar::ForwardedEvent;
+use std::sync::{LazyLock, Mutex};
+use tauri::ipc::Channel;
+use tauri::{AppHandle, WebviewUrl, WebviewWindowBuilder, WindowEvent};
+
+/// Label prefix of observer windows, matched by `capabilities/observer.json`
+pub const LABEL_PREFIX: &str = "observer-";
+
+/// A window following a task
+struct Watch {
+    label: String,
+    task_id: String,
+    channel: Channel<ForwardedEvent>,
+}
+
+static WATCHES: LazyLock<Mutex<Vec<Watch>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+
+/// Open an observer window for a task, returning its label
+pub fn open(app: &AppHandle, task_id: &str) -> Result<String, String> {
+    // The task ID goes into the window's URL as is
+    let valid = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+    if task_id.is_empty() || !task_id.chars().all(valid) {
+        return Err(format!("Invalid task ID: {}", task_id));
+    }
+    let label = format!("{}{}", LABEL_PREFIX, uuid::Uuid::new_v4().simple());
+    let url = WebviewUrl::App(format!("index.html?observe={}", task_id).into());
+    let window = WebviewWindowBuilder::new(app, &label, url)
+        .title("Cowork Z Observer")
+        .inner_size(900.0, 700.0)
+        .min_inner_size(480.0, 360.0)
+        .build()
+        .map_err(|e| format!("Failed to open observer window: {}", e))?;
+
+    let closed = label.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Destroyed = event {
+            unwatch(&closed);
+        }
+    });
+    tracing::info!("[Observer] Opened {} for task {}", label, task_id);
+    Ok(label)
+}
+
+/// Send a task's events to a window's channel, replacing what the window watched
+pub fn watch(label: &str, task_id: &str, channel: Channel<ForwardedEvent>) {
+    if let Ok(mut watches) = WATCHES.lock() {
+        watches.retain(|w| w.label != label);
+        watches.push(Watch {
+            label: label.to_string(),
+            task_id: task_id.to_string(),
+            channel,
+        });
+    }
+}
+
+fn unwatch(label: &str) {
+    if let Ok(mut watches) = WATCHES.lock() {
+        watches.retain(|w| w.label != label);
+    }
+}
+
+/// Send a forwarded event to the windows watching its task
+pub fn publish(event: &ForwardedEvent) {
+    let Some(task_id) = event.task_id() else {
+        return;
+    };
+    let Ok(mut watches) = WATCHES.lock() else {
+        return;
+    };
+    // A channel that can't be sent to belongs to a window that went away
+    watches.retain(|w| w.task_id != task_id || w.channel.send(event.clone()).is_ok());
+}
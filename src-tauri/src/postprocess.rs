@@ -0,0 +1,352 @@
+// src-tauri/src/postprocess.rs
+//! Post-processing of agent messages
+//!
+//! Each `task_message` from the sidecar runs through `PROCESSORS` before it is
+//! emitted to the frontend, which persists it as emitted. A processor applies
+//! to some message types and can be switched off per workspace
+//! (`PostprocessSettings`): ANSI escape codes are stripped from tool output,
+//! fenced code blocks in assistant text are saved as task artifacts with their
+//! language (emitted as `task:artifacts`), and paths in assistant text that
+//! exist under the task's working directory become `file://` links. Code blocks
+//! are left as they are.
+//!
+//! The working directory and settings are looked up once per task and kept
+//! until the task completes or fails.
+
+use crate::db::artifacts::Artifact;
+use crate::db::workspaces::PostprocessSettings;
+use crate::db::{self, DbState};
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use tauri::{AppHandle, Manager};
+
+/// Code blocks shorter than this many lines are not saved as artifacts
+const MIN_ARTIFACT_LINES: usize = 5;
+
+static ANSI_ESCAPE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\x1b(?:\[[0-?]*[ -/]*[@-~]|\][^\x07\x1b]*(?:\x07|\x1b\\)|[@-Z\\-_])")
+        .expect("valid ANSI pattern")
+});
+
+/// A relative path with at least one directory, optionally in backticks and with a line
+static RELATIVE_PATH: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(`?)((?:\./)?[\w.-]+(?:/[\w.-]+)+)(?::(\d+))?(`?)").expect("valid path pattern")
+});
+
+/// What processors know about a message's task
+struct Context {
+    task_id: String,
+    /// Working directory, `None` for tasks outside a workspace
+    root: Option<PathBuf>,
+    settings: PostprocessSettings,
+}
+
+/// A message's text as it goes through the processors
+struct Processed {
+    text: String,
+    origin_id: Option<String>,
+    artifacts: Vec<Artifact>,
+}
+
+/// A step of the pipeline
+struct Processor {
+    name: &'static str,
+    /// OpenCode message types it runs on
+    types: &'static [&'static str],
+    enabled: fn(&PostprocessSettings) -> bool,
+    run: fn(&Context, &mut Processed),
+}
+
+/// The pipeline, in order
+const PROCESSORS: &[Processor] = &[
+    Processor {
+        name: "strip_ansi",
+        types: &["tool_use", "tool_result"],
+        enabled: |settings| settings.strip_ansi,
+        run: strip_ansi,
+    },
+    Processor {
+        name: "extract_code_blocks",
+        types: &["text"],
+        enabled: |settings| settings.extract_code_blocks,
+        run: extract_code_blocks,
+    },
+    Processor {
+        name: "linkify_paths",
+        types: &["text"],
+        enabled: |settings| settings.linkify_paths,
+        run: linkify_paths,
+    },
+];
+
+/// Context of each task with messages in flight
+static CONTEXTS: LazyLock<Mutex<HashMap<String, Arc<Context>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// JSON pointer to the text of each message type in a `task_message` payload
+fn text_pointer(msg_type: &str) -> Option<&'static str> {
+    match msg_type {
+        "text" => Some("/message/part/text"),
+        "tool_use" => Some("/message/part/state/output"),
+        "tool_result" => Some("/message/part/output"),
+        _ => None,
+    }
+}
+
+/// A fenced code block: the whole block, its info string, and its body
+struct Fence {
+    range: Range<usize>,
+    language: Option<String>,
+    body: Range<usize>,
+}
+
+/// Fenced code blocks in markdown; an unclosed fence runs to the end
+fn fences(text: &str) -> Vec<Fence> {
+    let mut fences = Vec::new();
+    let mut open: Option<(usize, &str, Option<String>, usize)> = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let trimmed = line.trim_start();
+        let marker = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m));
+        // A closing fence has nothing after its backticks or tildes
+        let bare = trimmed.trim_end().trim_matches(['`', '~']).is_empty();
+        match (&open, marker) {
+            (None, Some(marker)) => {
+                let info = trimmed[marker.len()..].trim().trim_start_matches(['`', '~']);
+                let language = info.split_whitespace().next().map(|l| l.to_string());
+                open = Some((start, marker, language, offset));
+            }
+            (Some((_, open_marker, _, _)), Some(marker)) if marker == *open_marker && bare => {
+                if let Some((block_start, _, language, body_start)) = open.take() {
+                    fences.push(Fence {
+                        range: block_start..offset,
+                        language,
+                        body: body_start..start,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some((block_start, _, language, body_start)) = open {
+        fences.push(Fence {
+            range: block_start..text.len(),
+            language,
+            body: body_start..text.len(),
+        });
+    }
+    fences
+}
+
+fn strip_ansi(_: &Context, message: &mut Processed) {
+    if message.text.contains('\x1b') {
+        message.text = ANSI_ESCAPE.replace_all(&message.text, "").into_owned();
+    }
+}
+
+fn extract_code_blocks(context: &Context, message: &mut Processed) {
+    let created_at = chrono::Utc::now().to_rfc3339();
+    for fence in fences(&message.text) {
+        let body = message.text[fence.body].trim_end_matches('\n');
+        if body.lines().count() < MIN_ARTIFACT_LINES {
+            continue;
+        }
+        message.artifacts.push(Artifact {
+            id: format!("art_{}", uuid::Uuid::new_v4()),
+            task_id: context.task_id.clone(),
+            origin_id: message.origin_id.clone(),
+            language: crate::language::detect(fence.language.as_deref(), body),
+            content: body.to_string(),
+            created_at: created_at.clone(),
+        });
+    }
+}
+
+/// The file a relative path names under a directory, if it exists there
+fn resolve(root: &Path, path: &str) -> Option<PathBuf> {
+    let root = root.canonicalize().ok()?;
+    let resolved = root.join(path).canonicalize().ok()?;
+    resolved.starts_with(&root).then_some(resolved)
+}
+
+/// Link a path match, or `None` to leave it as written
+fn link(root: &Path, before: &str, captures: &Captures) -> Option<String> {
+    let (open, close) = (&captures[1], &captures[4]);
+    // Already a link, or part of a URL or an absolute path
+    if open != close || before.ends_with(['[', '(', '<', '/', '\\']) {
+        return None;
+    }
+    let matched = &captures[2];
+    let line = captures.get(3).map(|l| l.as_str());
+    // A full stop ending the sentence isn't part of the path
+    let path = match (open, line) {
+        ("", None) => matched.trim_end_matches('.'),
+        _ => matched,
+    };
+    let resolved = resolve(root, path)?;
+    let line_suffix = line.map(|l| format!(":{}", l)).unwrap_or_default();
+    let fragment = line.map(|l| format!("#L{}", l)).unwrap_or_default();
+    Some(format!(
+        "[{}{}{}{}](<file://{}{}>){}",
+        open,
+        path,
+        line_suffix,
+        close,
+        resolved.to_string_lossy(),
+        fragment,
+        &matched[path.len()..]
+    ))
+}
+
+fn linkify_paths(context: &Context, message: &mut Processed) {
+    let Some(root) = context.root.as_deref() else {
+        return;
+    };
+    let text = &message.text;
+    let mut linked = String::with_capacity(text.len());
+    let mut last = 0;
+    // Only prose is linked, code blocks stay as written
+    let mut gaps: Vec<Range<usize>> = Vec::new();
+    for fence in fences(text) {
+        gaps.push(last..fence.range.start);
+        last = fence.range.end;
+    }
+    gaps.push(last..text.len());
+
+    last = 0;
+    for gap in gaps {
+        for captures in RELATIVE_PATH.captures_iter(&text[gap.clone()]) {
+            let Some(whole) = captures.get(0) else {
+                continue;
+            };
+            let (start, end) = (gap.start + whole.start(), gap.start + whole.end());
+            if let Some(replacement) = link(root, &text[..start], &captures) {
+                linked.push_str(&text[last..start]);
+                linked.push_str(&replacement);
+                last = end;
+            }
+        }
+    }
+    linked.push_str(&text[last..]);
+    message.text = linked;
+}
+
+/// Look up the working directory and settings of a task
+fn load_context(app: &AppHandle, task_id: &str) -> Option<Context> {
+    let db_state = app.try_state::<DbState>()?;
+    let conn = db_state.reader.lock().ok()?;
+    let task = db::tasks::get_task(&conn, task_id)?;
+    let (root, settings) = match task.workspace_id.as_deref() {
+        Some(workspace_id) => {
+            let workspace = db::workspaces::get_workspace(&conn, workspace_id).ok()??;
+            let settings = db::workspaces::get_postprocess(&conn, workspace_id).ok()?;
+            // Remote workspaces aren't on this machine
+            let remote = db::workspaces::get_ssh_host_id(&conn, workspace_id)
+                .ok()
+                .flatten()
+                .is_some();
+            ((!remote).then(|| PathBuf::from(workspace.path)), settings)
+        }
+        None => (None, PostprocessSettings::default()),
+    };
+    Some(Context {
+        task_id: task_id.to_string(),
+        root,
+        settings,
+    })
+}
+
+fn context(app: &AppHandle, task_id: &str) -> Arc<Context> {
+    if let Some(context) = CONTEXTS.lock().ok().and_then(|c| c.get(task_id).cloned()) {
+        return context;
+    }
+    match load_context(app, task_id) {
+        Some(context) => {
+            let context = Arc::new(context);
+            if let Ok(mut contexts) = CONTEXTS.lock() {
+                contexts.insert(task_id.to_string(), context.clone());
+            }
+            context
+        }
+        // Not stored yet; look again with the next message
+        None => Arc::new(Context {
+            task_id: task_id.to_string(),
+            root: None,
+            settings: PostprocessSettings::default(),
+        }),
+    }
+}
+
+/// Run a `task_message` payload through the pipeline
+pub fn apply(app: &AppHandle, task_id: &str, payload: &mut serde_json::Value) {
+    let msg_type = payload
+        .pointer("/message/type")
+        .and_then(|t| t.as_str())
+        .unwrap_or_default()
+        .to_string();
+    let Some(pointer) = text_pointer(&msg_type) else {
+        return;
+    };
+    let Some(text) = payload.pointer(pointer).and_then(|t| t.as_str()) else {
+        return;
+    };
+
+    let context = context(app, task_id);
+    let mut message = Processed {
+        text: text.to_string(),
+        origin_id: payload
+            .pointer("/message/part/id")
+            .and_then(|id| id.as_str())
+            .map(|id| id.to_string()),
+        artifacts: Vec::new(),
+    };
+    for processor in PROCESSORS {
+        if processor.types.contains(&msg_type.as_str()) && (processor.enabled)(&context.settings) {
+            (processor.run)(&context, &mut message);
+            tracing::trace!("[Postprocess] Ran {} on a {} message", processor.name, msg_type);
+        }
+    }
+
+    if let Some(text) = payload.pointer_mut(pointer) {
+        *text = serde_json::Value::String(message.text);
+    }
+    if !message.artifacts.is_empty() {
+        save_artifacts(app, task_id, message.artifacts);
+    }
+}
+
+fn save_artifacts(app: &AppHandle, task_id: &str, artifacts: Vec<Artifact>) {
+    let db_state = app.state::<DbState>();
+    let saved = db_state.conn.lock().map_err(|e| e.to_string()).and_then(|conn| {
+        artifacts
+            .iter()
+            .try_for_each(|artifact| db::artifacts::add_artifact(&conn, artifact))
+    });
+    match saved {
+        Ok(()) => crate::sidecar::forward_event(
+            app,
+            "task:artifacts",
+            serde_json::json!({ "taskId": task_id, "artifacts": artifacts }),
+        ),
+        Err(e) => tracing::warn!("[Postprocess] {}", e),
+    }
+}
+
+/// Drop a finished task's context
+pub fn finish(task_id: &str) {
+    if let Ok(mut contexts) = CONTEXTS.lock() {
+        contexts.remove(task_id);
+    }
+}
+
+/// Drop all contexts, e.g. after a workspace's settings change
+pub fn invalidate() {
+    if let Ok(mut contexts) = CONTEXTS.lock() {
+        contexts.clear();
+    }
+}
@@ -0,0 +1,176 @@
+// src-tauri/src/profile.rs
+//! Profiles with separate data
+//!
+//! Each profile has its own database, attachments, logs, and keychain entries,
+//! so work can be kept apart from experiments. The default profile uses the app
+//! data directory itself, as before profiles existed; any other profile lives
+//! under `profiles/<name>` there and suffixes the keychain service with its name.
+//!
+//! The profile is picked once at startup: `--profile <name>` wins, then the
+//! profile last switched to, then the default. `switch_profile` records the new
+//! profile and relaunches the app in it.
+
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use tauri::{AppHandle, Manager};
+
+/// Profile used when none is chosen
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Longest profile name
+const MAX_NAME_CHARS: usize = 32;
+
+/// Directory under the app data directory holding the other profiles
+const PROFILES_DIR: &str = "profiles";
+
+/// File in the app data directory naming the profile last switched to
+const ACTIVE_PROFILE_FILE: &str = "active-profile";
+
+/// Profile of this run, set by `init`
+static PROFILE: OnceLock<String> = OnceLock::new();
+
+/// A profile and whether this run uses it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileInfo {
+    pub name: String,
+    pub active: bool,
+}
+
+/// Check that a profile name is usable as a directory and keychain suffix
+pub fn validate_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name.chars().count() <= MAX_NAME_CHARS
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid profile name: {} (use up to {} lowercase letters, digits, '-' or '_')",
+            name, MAX_NAME_CHARS
+        ))
+    }
+}
+
+/// Value of `--profile <name>` or `--profile=<name>` on the command line
+fn arg_profile() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--profile" {
+            return args.next();
+        }
+        if let Some(name) = arg.strip_prefix("--profile=") {
+            return Some(name.to_string());
+        }
+    }
+    None
+}
+
+fn base_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))
+}
+
+/// Pick this run's profile; called once at startup, before anything opens its data
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    let stored = base_dir(app)
+        .ok()
+        .and_then(|dir| std::fs::read_to_string(dir.join(ACTIVE_PROFILE_FILE)).ok())
+        .map(|name| name.trim().to_string());
+    let name = arg_profile()
+        .or(stored)
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+    validate_name(&name)?;
+    PROFILE
+        .set(name)
+        .map_err(|_| "Profile already initialized".to_string())
+}
+
+/// Profile of this run
+pub fn current() -> &'static str {
+    PROFILE.get().map(String::as_str).unwrap_or(DEFAULT_PROFILE)
+}
+
+fn profile_dir(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    let base = base_dir(app)?;
+    Ok(if name == DEFAULT_PROFILE {
+        base
+    } else {
+        base.join(PROFILES_DIR).join(name)
+    })
+}
+
+/// Data directory of this run's profile, created if missing
+pub fn data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = profile_dir(app, current())?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create profile directory: {}", e))?;
+    Ok(dir)
+}
+
+/// Keychain service of this run's profile, suffixed for all but the default
+pub fn keychain_service(service: &str) -> String {
+    match current() {
+        DEFAULT_PROFILE => service.to_string(),
+        name => format!("{}.{}", service, name),
+    }
+}
+
+/// The default profile and every profile with a data directory, by name
+pub fn list(app: &AppHandle) -> Result<Vec<ProfileInfo>, String> {
+    let mut names = vec![DEFAULT_PROFILE.to_string()];
+    if let Ok(entries) = std::fs::read_dir(base_dir(app)?.join(PROFILES_DIR)) {
+        let mut others: Vec<String> = entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_dir())
+            .filter_map(|e| e.file_name().into_string().ok())
+            .filter(|name| name != DEFAULT_PROFILE && validate_name(name).is_ok())
+            .collect();
+        others.sort();
+        names.extend(others);
+    }
+    Ok(names
+        .into_iter()
+        .map(|name| ProfileInfo {
+            active: name == current(),
+            name,
+        })
+        .collect())
+}
+
+/// Create the profile if needed and make it the one used on next launch
+pub fn select(app: &AppHandle, name: &str) -> Result<(), String> {
+    validate_name(name)?;
+    let dir = profile_dir(app, name)?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create profile directory: {}", e))?;
+    let marker = base_dir(app)?.join(ACTIVE_PROFILE_FILE);
+    std::fs::write(&marker, name).map_err(|e| format!("Failed to save active profile: {}", e))
+}
+
+/// Start a new instance of the app in a profile; the caller exits this one
+pub fn relaunch(name: &str) -> Result<(), String> {
+    let exe = std::env::current_exe()
+        .map_err(|e| format!("Failed to find the app executable: {}", e))?;
+    // Drop this run's `--profile` so the new one applies
+    let mut args = Vec::new();
+    let mut rest = std::env::args().skip(1);
+    while let Some(arg) = rest.next() {
+        if arg == "--profile" {
+            rest.next();
+        } else if !arg.starts_with("--profile=") {
+            args.push(arg);
+        }
+    }
+    std::process::Command::new(exe)
+        .args(args)
+        .arg("--profile")
+        .arg(name)
+        .spawn()
+        .map_err(|e| format!("Failed to relaunch in profile {}: {}", name, e))?;
+    Ok(())
+}
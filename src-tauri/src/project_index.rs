@@ -0,0 +1,266 @@
+// src-tauri/src/project_index.rs
+//! Workspace file index for @-mentions and symbol lookups
+//!
+//! `index` walks a workspace with git's ignore rules (`.gitignore`, `.ignore`,
+//! and the global excludes file; hidden files are skipped) and stores each
+//! file's path and the symbols it declares in `project_files`. Reindexing only
+//! reads files whose size or modification time changed. Symbols come from
+//! per-language declaration patterns rather than a parser, which is enough to
+//! find the file defining a name. A search refreshes an index older than
+//! `STALE_AFTER_SECS` first, so results follow the agent's edits.
+
+use crate::db::project_files::{self, ProjectFileInput, ProjectFileMatch};
+use crate::db::{self, DbState};
+use ignore::WalkBuilder;
+use regex::Regex;
+use rusqlite::TransactionBehavior;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::{Instant, UNIX_EPOCH};
+
+/// Files indexed per workspace; the walk stops here
+const MAX_FILES: usize = 50_000;
+
+/// Larger files are indexed by path only
+const MAX_SYMBOL_FILE_BYTES: i64 = 512 * 1024;
+
+const MAX_SYMBOLS_PER_FILE: usize = 200;
+
+/// Age after which a search refreshes the index first
+const STALE_AFTER_SECS: i64 = 5 * 60;
+
+/// Declaration patterns by file extension; the first group is the symbol name
+static SYMBOL_PATTERNS: LazyLock<Vec<(&'static [&'static str], Regex)>> = LazyLock::new(|| {
+    let pattern = |re: &str| Regex::new(re).expect("valid symbol pattern");
+    vec![
+        (
+            &["rs"][..],
+            pattern(concat!(
+                r"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?(?:(?:async|const|unsafe|extern)\s+)*",
+                r"(?:fn|struct|enum|trait|type|mod|const|static|macro_rules!)\s+",
+                r"([A-Za-z_][A-Za-z0-9_]*)",
+            )),
+        ),
+        (
+            &["ts", "tsx", "mts", "cts", "js", "jsx", "mjs", "cjs"][..],
+            pattern(concat!(
+                r"(?m)^\s*(?:export\s+)?(?:default\s+)?(?:declare\s+)?(?:abstract\s+)?",
+                r"(?:async\s+)?(?:function\*?|class|interface|type|enum|const|let|var)\s+",
+                r"([A-Za-z_$][A-Za-z0-9_$]*)",
+            )),
+        ),
+        (
+            &["py"][..],
+            pattern(r"(?m)^\s*(?:async\s+)?(?:def|class)\s+([A-Za-z_][A-Za-z0-9_]*)"),
+        ),
+        (
+            &["go"][..],
+            pattern(r"(?m)^(?:func(?:\s*\([^)]*\))?|type)\s+([A-Za-z_][A-Za-z0-9_]*)"),
+        ),
+        (
+            &["java", "kt", "kts", "cs", "swift", "scala"][..],
+            pattern(concat!(
+                r"(?m)^\s*(?:(?:public|private|protected|internal|static|final|abstract|open|",
+                r"data|sealed|override)\s+)*",
+                r"(?:class|interface|enum|object|struct|protocol|record|fun|func)\s+",
+                r"([A-Za-z_][A-Za-z0-9_]*)",
+            )),
+        ),
+        (
+            &["rb"][..],
+            pattern(r"(?m)^\s*(?:def|class|module)\s+(?:self\.)?([A-Za-z_][A-Za-z0-9_?!]*)"),
+        ),
+    ]
+});
+
+/// Outcome of indexing a workspace
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexSummary {
+    pub workspace_id: String,
+    pub file_count: usize,
+    /// Files read because they were new or changed
+    pub updated: usize,
+    /// Files dropped because they no longer exist or are now ignored
+    pub removed: usize,
+    pub duration_ms: u64,
+}
+
+/// A file found by the walk
+struct WalkedFile {
+    path: String,
+    absolute: PathBuf,
+    size: i64,
+    modified_at: i64,
+}
+
+/// Symbols declared in a file, in order of appearance
+fn extract_symbols(path: &Path) -> Vec<String> {
+    let Some(extension) = path.extension().and_then(|e| e.to_str()) else {
+        return Vec::new();
+    };
+    let extension = extension.to_lowercase();
+    let Some((_, pattern)) = SYMBOL_PATTERNS
+        .iter()
+        .find(|(extensions, _)| extensions.contains(&extension.as_str()))
+    else {
+        return Vec::new();
+    };
+    let Ok(text) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+
+    let mut symbols: Vec<String> = Vec::new();
+    for name in pattern.captures_iter(&text).filter_map(|c| c.get(1)) {
+        if !symbols.iter().any(|s| s == name.as_str()) {
+            symbols.push(name.as_str().to_string());
+        }
+        if symbols.len() >= MAX_SYMBOLS_PER_FILE {
+            break;
+        }
+    }
+    symbols
+}
+
+/// Files under a directory that git wouldn't ignore, with `/`-separated relative paths
+fn walk(root: &Path) -> Vec<WalkedFile> {
+    let mut files = Vec::new();
+    // Apply .gitignore files even in directories that aren't git repositories
+    for entry in WalkBuilder::new(root).require_git(false).build() {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                tracing::debug!("[Index] Skipping entry: {}", e);
+                continue;
+            }
+        };
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let (Ok(metadata), Ok(relative)) = (entry.metadata(), entry.path().strip_prefix(root))
+        else {
+            continue;
+        };
+        let path = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let modified_at = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_secs() as i64);
+        files.push(WalkedFile {
+            path,
+            absolute: entry.path().to_path_buf(),
+            size: metadata.len() as i64,
+            modified_at,
+        });
+        if files.len() >= MAX_FILES {
+            tracing::warn!("[Index] Stopped at {} files in {}", MAX_FILES, root.display());
+            break;
+        }
+    }
+    files
+}
+
+/// Index a workspace's files, reading only the ones that changed since the last run
+pub fn index(state: &DbState, workspace_id: &str) -> Result<IndexSummary, String> {
+    let started = Instant::now();
+    let (root, mut stamps) = {
+        let conn = state.reader.lock().map_err(|e| e.to_string())?;
+        let workspace = db::workspaces::get_workspace(&conn, workspace_id)?
+            .ok_or_else(|| format!("Workspace not found: {}", workspace_id))?;
+        if db::workspaces::get_ssh_host_id(&conn, workspace_id)?.is_some() {
+            return Err("Remote workspaces can't be indexed".to_string());
+        }
+        (
+            PathBuf::from(workspace.path),
+            project_files::get_file_stamps(&conn, workspace_id)?,
+        )
+    };
+    if !root.is_dir() {
+        return Err(format!("Workspace directory not found: {}", root.display()));
+    }
+
+    let walked = walk(&root);
+    let file_count = walked.len();
+    let changed: Vec<ProjectFileInput> = walked
+        .into_iter()
+        .filter(|file| stamps.remove(&file.path) != Some((file.size, file.modified_at)))
+        .map(|file| ProjectFileInput {
+            symbols: if file.size <= MAX_SYMBOL_FILE_BYTES {
+                extract_symbols(&file.absolute)
+            } else {
+                Vec::new()
+            },
+            path: file.path,
+            size: file.size,
+            modified_at: file.modified_at,
+        })
+        .collect();
+    // Whatever the walk didn't find again is gone
+    let removed: Vec<String> = stamps.into_keys().collect();
+
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    for file in &changed {
+        project_files::upsert_file(&tx, workspace_id, file)?;
+    }
+    for path in &removed {
+        project_files::delete_file(&tx, workspace_id, path)?;
+    }
+    project_files::set_indexed(
+        &tx,
+        workspace_id,
+        &chrono::Utc::now().to_rfc3339(),
+        file_count as i64,
+    )?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit project index: {}", e))?;
+
+    let summary = IndexSummary {
+        workspace_id: workspace_id.to_string(),
+        file_count,
+        updated: changed.len(),
+        removed: removed.len(),
+        duration_ms: started.elapsed().as_millis() as u64,
+    };
+    tracing::info!(
+        "[Index] Indexed {} files of {} ({} updated, {} removed) in {} ms",
+        summary.file_count,
+        workspace_id,
+        summary.updated,
+        summary.removed,
+        summary.duration_ms
+    );
+    Ok(summary)
+}
+
+/// Search a workspace's files by path and declared symbols, refreshing a stale index first
+pub fn search(
+    state: &DbState,
+    workspace_id: &str,
+    query: &str,
+    limit: i64,
+) -> Result<Vec<ProjectFileMatch>, String> {
+    let info = {
+        let conn = state.reader.lock().map_err(|e| e.to_string())?;
+        project_files::get_index_info(&conn, workspace_id)?
+    };
+    let stale = info
+        .and_then(|info| chrono::DateTime::parse_from_rfc3339(&info.indexed_at).ok())
+        .is_none_or(|indexed_at| {
+            chrono::Utc::now().signed_duration_since(indexed_at).num_seconds() >= STALE_AFTER_SECS
+        });
+    if stale {
+        index(state, workspace_id)?;
+    }
+
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    project_files::search(&conn, workspace_id, query, limit)
+}
@@ -0,0 +1,382 @@
+// src-tauri/src/provider_check.rs
+//! Step-by-step provider diagnostics
+//!
+//! `run_provider_diagnostics` checks a provider one layer at a time: the API
+//! key is in the keychain, the API host answers, the model list can be fetched
+//! with the key, and a tiny completion succeeds. Each step reports what it saw,
+//! and the steps after a failed one are reported as not run, so the report
+//! shows which layer is broken instead of a single pass/fail.
+
+use crate::db::{self, DbState};
+use crate::secure_storage;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// Time allowed for each request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Prompt of the test completion
+const PROBE_PROMPT: &str = "Reply with OK.";
+
+/// Output tokens allowed for the test completion
+const PROBE_MAX_TOKENS: u32 = 8;
+
+/// Steps in the order they run, with their labels
+const STEPS: &[(&str, &str)] = &[
+    ("key", "API key"),
+    ("network", "Network"),
+    ("models", "Model list"),
+    ("completion", "Test completion"),
+];
+
+/// Outcome of one step
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticStep {
+    /// `key`, `network`, `models`, or `completion`
+    pub id: String,
+    pub label: String,
+    /// `passed`, `failed`, or `skipped`
+    pub status: String,
+    pub detail: String,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderDiagnostics {
+    pub provider_id: String,
+    /// Whether every step passed
+    pub passed: bool,
+    pub steps: Vec<DiagnosticStep>,
+}
+
+/// Request format of a provider's API
+#[derive(Debug, Clone, Copy)]
+enum Api {
+    Anthropic,
+    OpenAiCompatible,
+    Google,
+}
+
+/// Where and how to reach a provider
+struct Target {
+    provider_id: String,
+    api: Option<Api>,
+    base_url: String,
+    key_required: bool,
+    /// Model selected for the provider, tried before the first listed one
+    model: Option<String>,
+}
+
+fn target(provider_id: &str, state: &DbState) -> Result<Target, String> {
+    let conn = state.reader.lock().map_err(|e| e.to_string())?;
+    let configured = |base_url: Option<String>, default: &str| {
+        base_url
+            .filter(|url| !url.trim().is_empty())
+            .unwrap_or_else(|| default.to_string())
+            .trim_end_matches('/')
+            .to_string()
+    };
+    let (api, base_url, key_required) = match provider_id {
+        "anthropic" => (Some(Api::Anthropic), "https://api.anthropic.com/v1".to_string(), true),
+        "google" => (
+            Some(Api::Google),
+            "https://generativelanguage.googleapis.com/v1beta".to_string(),
+            true,
+        ),
+        "openai" => (Some(Api::OpenAiCompatible), "https://api.openai.com/v1".to_string(), true),
+        "xai" => (Some(Api::OpenAiCompatible), "https://api.x.ai/v1".to_string(), true),
+        "deepseek" => (
+            Some(Api::OpenAiCompatible),
+            "https://api.deepseek.com/v1".to_string(),
+            true,
+        ),
+        "zai" => (Some(Api::OpenAiCompatible), "https://api.z.ai/api/paas/v4".to_string(), true),
+        "openrouter" => (
+            Some(Api::OpenAiCompatible),
+            "https://openrouter.ai/api/v1".to_string(),
+            true,
+        ),
+        // Ollama serves an OpenAI-compatible API under /v1
+        "ollama" => {
+            let base_url = db::settings::get_ollama_config(&conn).map(|c| c.base_url);
+            let base_url = configured(base_url, "http://localhost:11434");
+            (Some(Api::OpenAiCompatible), format!("{}/v1", base_url), false)
+        }
+        "litellm" => {
+            let base_url = db::settings::get_litellm_config(&conn).map(|c| c.base_url);
+            (Some(Api::OpenAiCompatible), configured(base_url, "http://localhost:4000"), false)
+        }
+        // Signed or deployment-specific requests; only the key is checked
+        "bedrock" | "azure-foundry" | "custom" => (None, String::new(), true),
+        _ => return Err(format!("Unknown provider: {}", provider_id)),
+    };
+
+    let model = db::settings::get_selected_model(&conn)
+        .filter(|selected| selected.provider == provider_id)
+        .map(|selected| {
+            let prefix = format!("{}/", provider_id);
+            selected.model.strip_prefix(&prefix).unwrap_or(&selected.model).to_string()
+        });
+    Ok(Target {
+        provider_id: provider_id.to_string(),
+        api,
+        base_url,
+        key_required,
+        model,
+    })
+}
+
+impl ProviderDiagnostics {
+    /// Record a step's outcome, returning its value if it passed
+    fn record<T>(
+        &mut self,
+        id: &str,
+        started: Instant,
+        outcome: Result<(T, String), String>,
+    ) -> Option<T> {
+        let label = STEPS
+            .iter()
+            .find(|(step, _)| *step == id)
+            .map(|(_, label)| label.to_string())
+            .unwrap_or_else(|| id.to_string());
+        let (status, detail, value) = match outcome {
+            Ok((value, detail)) => ("passed", detail, Some(value)),
+            Err(e) => ("failed", e, None),
+        };
+        self.steps.push(DiagnosticStep {
+            id: id.to_string(),
+            label,
+            status: status.to_string(),
+            detail,
+            duration_ms: started.elapsed().as_millis() as u64,
+        });
+        value
+    }
+
+    /// Report the steps that didn't run as skipped
+    fn finish(mut self) -> Self {
+        let reason = if self.steps.iter().any(|step| step.status == "failed") {
+            "Not run because an earlier step failed".to_string()
+        } else {
+            format!("Not checked for {}", self.provider_id)
+        };
+        for (id, label) in &STEPS[self.steps.len()..] {
+            self.steps.push(DiagnosticStep {
+                id: id.to_string(),
+                label: label.to_string(),
+                status: "skipped".to_string(),
+                detail: reason.clone(),
+                duration_ms: 0,
+            });
+        }
+        self.passed = self.steps.iter().all(|step| step.status == "passed");
+        self
+    }
+}
+
+fn check_key(target: &Target) -> Result<(Option<String>, String), String> {
+    let key = secure_storage::get_api_key(&target.provider_id)?;
+    match key {
+        Some(key) => {
+            let prefix: String = key.chars().take(8).collect();
+            Ok((Some(key), format!("Found in the keychain ({}...)", prefix)))
+        }
+        None if target.key_required => Err("No API key is stored for this provider".to_string()),
+        None => Ok((None, "Not required for this provider".to_string())),
+    }
+}
+
+/// Describe a failed request by the URL given, since the error's own URL may
+/// carry the API key in its query
+fn request_error(url: &str, e: reqwest::Error) -> String {
+    let e = e.without_url();
+    if e.is_timeout() {
+        format!("{} did not answer within {} seconds", url, REQUEST_TIMEOUT.as_secs())
+    } else if e.is_connect() {
+        format!("Could not connect to {}: {}", url, e)
+    } else {
+        format!("Request to {} failed: {}", url, e)
+    }
+}
+
+/// Turn an error status into a message naming the likely cause
+async fn status_error(response: reqwest::Response) -> String {
+    let status = response.status();
+    let body: String = response.text().await.unwrap_or_default().chars().take(300).collect();
+    match status.as_u16() {
+        401 | 403 => format!("The provider rejected the API key (HTTP {}): {}", status, body),
+        404 => format!("Endpoint or model not found (HTTP {}): {}", status, body),
+        429 => format!("Rate limited or out of quota (HTTP {}): {}", status, body),
+        _ => format!("HTTP {}: {}", status, body),
+    }
+}
+
+async fn check_network(client: &reqwest::Client, target: &Target) -> Result<((), String), String> {
+    let response = client
+        .get(&target.base_url)
+        .send()
+        .await
+        .map_err(|e| request_error(&target.base_url, e))?;
+    // Any answer means the host is reachable; authentication is checked next
+    Ok(((), format!("{} answered with HTTP {}", target.base_url, response.status())))
+}
+
+fn authorize(
+    request: reqwest::RequestBuilder,
+    api: Api,
+    key: Option<&str>,
+) -> reqwest::RequestBuilder {
+    match (api, key) {
+        (Api::Anthropic, Some(key)) => request
+            .header("x-api-key", key)
+            .header("anthropic-version", "2023-06-01"),
+        (Api::Google, Some(key)) => request.query(&[("key", key)]),
+        (Api::OpenAiCompatible, Some(key)) => request.bearer_auth(key),
+        (_, None) => request,
+    }
+}
+
+async fn list_models(
+    client: &reqwest::Client,
+    target: &Target,
+    api: Api,
+    key: Option<&str>,
+) -> Result<(Vec<String>, String), String> {
+    let url = format!("{}/models", target.base_url);
+    let response = authorize(client.get(&url), api, key)
+        .send()
+        .await
+        .map_err(|e| request_error(&url, e))?;
+    if !response.status().is_success() {
+        return Err(status_error(response).await);
+    }
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse model list: {}", e))?;
+
+    let models: Vec<String> = match api {
+        Api::Google => body["models"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|m| m["name"].as_str())
+            .map(|name| name.strip_prefix("models/").unwrap_or(name).to_string())
+            .collect(),
+        Api::Anthropic | Api::OpenAiCompatible => body["data"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|m| m["id"].as_str())
+            .map(str::to_string)
+            .collect(),
+    };
+    if models.is_empty() {
+        return Err("The provider listed no models".to_string());
+    }
+    let detail = format!("{} model(s) available", models.len());
+    Ok((models, detail))
+}
+
+async fn complete(
+    client: &reqwest::Client,
+    target: &Target,
+    api: Api,
+    key: Option<&str>,
+    model: &str,
+) -> Result<((), String), String> {
+    let (url, body) = match api {
+        Api::Anthropic => (
+            format!("{}/messages", target.base_url),
+            serde_json::json!({
+                "model": model,
+                "max_tokens": PROBE_MAX_TOKENS,
+                "messages": [{ "role": "user", "content": PROBE_PROMPT }],
+            }),
+        ),
+        Api::OpenAiCompatible => (
+            format!("{}/chat/completions", target.base_url),
+            serde_json::json!({
+                "model": model,
+                "max_tokens": PROBE_MAX_TOKENS,
+                "messages": [{ "role": "user", "content": PROBE_PROMPT }],
+            }),
+        ),
+        Api::Google => (
+            format!("{}/models/{}:generateContent", target.base_url, model),
+            serde_json::json!({
+                "contents": [{ "parts": [{ "text": PROBE_PROMPT }] }],
+                "generationConfig": { "maxOutputTokens": PROBE_MAX_TOKENS },
+            }),
+        ),
+    };
+
+    let started = Instant::now();
+    let response = authorize(client.post(&url).json(&body), api, key)
+        .send()
+        .await
+        .map_err(|e| request_error(&url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("{} failed: {}", model, status_error(response).await));
+    }
+    Ok(((), format!("{} answered in {} ms", model, started.elapsed().as_millis())))
+}
+
+/// Run the steps in order, stopping at the first that fails
+async fn run_steps(
+    report: &mut ProviderDiagnostics,
+    client: &reqwest::Client,
+    target: &Target,
+) -> Option<()> {
+    let started = Instant::now();
+    let key = report.record("key", started, check_key(target))?;
+    let api = target.api?;
+
+    let started = Instant::now();
+    report.record("network", started, check_network(client, target).await)?;
+
+    let started = Instant::now();
+    let models = report.record(
+        "models",
+        started,
+        list_models(client, target, api, key.as_deref()).await,
+    )?;
+
+    let model = target.model.clone().unwrap_or_else(|| models[0].clone());
+    let started = Instant::now();
+    report.record(
+        "completion",
+        started,
+        complete(client, target, api, key.as_deref(), &model).await,
+    )
+}
+
+/// Check a provider layer by layer
+pub async fn run(provider_id: &str, state: &DbState) -> Result<ProviderDiagnostics, String> {
+    let target = target(provider_id, state)?;
+    let client = reqwest::Client::builder()
+        .user_agent(concat!("cowork-z/", env!("CARGO_PKG_VERSION")))
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let mut report = ProviderDiagnostics {
+        provider_id: provider_id.to_string(),
+        passed: false,
+        steps: Vec::new(),
+    };
+    run_steps(&mut report, &client, &target).await;
+    let report = report.finish();
+    tracing::info!(
+        "[Providers] Diagnostics for {}: {}",
+        provider_id,
+        report
+            .steps
+            .iter()
+            .map(|step| format!("{} {}", step.id, step.status))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    Ok(report)
+}
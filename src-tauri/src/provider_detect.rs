@@ -0,0 +1,258 @@
+// src-tauri/src/provider_detect.rs
+//! Providers found on this machine, for onboarding
+//!
+//! `detect_available_providers` looks for what the user already has instead
+//! of starting from a blank provider picker: logins made with `opencode auth
+//! login`, API keys in the environment, a running Ollama or LM Studio, and
+//! AWS credentials for Bedrock. Nothing is set up here; the list is ranked
+//! so the first entry is the one most likely to work without further steps.
+
+use crate::db::{self, DbState};
+use crate::secure_storage;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Time a local server gets to answer; it runs on this machine or not at all
+const PROBE_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// Where LM Studio serves its OpenAI-compatible API by default
+const LM_STUDIO_URL: &str = "http://localhost:1234";
+
+/// Where Ollama listens unless configured or `OLLAMA_HOST` says otherwise
+const OLLAMA_URL: &str = "http://localhost:11434";
+
+/// Environment variables holding provider API keys, as OpenCode reads them
+const KEY_ENV_VARS: &[(&str, &str)] = &[
+    ("anthropic", "ANTHROPIC_API_KEY"),
+    ("openai", "OPENAI_API_KEY"),
+    ("google", "GOOGLE_GENERATIVE_AI_API_KEY"),
+    ("google", "GEMINI_API_KEY"),
+    ("openrouter", "OPENROUTER_API_KEY"),
+    ("xai", "XAI_API_KEY"),
+    ("deepseek", "DEEPSEEK_API_KEY"),
+];
+
+/// A provider that looks usable, and what it was found through
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DetectedProvider {
+    /// Provider to set up; LM Studio is a `custom` OpenAI-compatible provider
+    pub provider_id: String,
+    /// `opencode-login`, `env`, `ollama`, `lm-studio`, or `aws`
+    pub source: String,
+    /// What was found, for showing to the user
+    pub detail: String,
+    /// Server URL for local providers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// Models a local server has available
+    pub models: Vec<String>,
+    /// The provider is already set up in the app
+    pub configured: bool,
+    /// Lower is more likely to work as is
+    #[serde(skip)]
+    rank: u8,
+}
+
+impl DetectedProvider {
+    fn new(provider_id: &str, source: &str, detail: String, rank: u8) -> Self {
+        DetectedProvider {
+            provider_id: provider_id.to_string(),
+            source: source.to_string(),
+            detail,
+            base_url: None,
+            models: Vec::new(),
+            configured: false,
+            rank,
+        }
+    }
+}
+
+/// OpenCode's data directory, which holds `auth.json`
+fn opencode_data_dir(home: &Path) -> PathBuf {
+    std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".local").join("share"))
+        .join("opencode")
+}
+
+/// Providers logged in with `opencode auth login`, such as a Claude subscription
+fn opencode_logins(home: &Path) -> Vec<DetectedProvider> {
+    let path = opencode_data_dir(home).join("auth.json");
+    let Ok(contents) = std::fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    let Ok(logins) = serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&contents)
+    else {
+        tracing::warn!("[Providers] Can't read {:?}", path);
+        return Vec::new();
+    };
+    logins
+        .iter()
+        .filter(|(provider_id, _)| secure_storage::PROVIDERS.contains(&provider_id.as_str()))
+        .map(|(provider_id, login)| {
+            let kind = match login.get("type").and_then(|t| t.as_str()) {
+                Some("oauth") => "account login",
+                _ => "API key",
+            };
+            DetectedProvider::new(
+                provider_id,
+                "opencode-login",
+                format!("OpenCode CLI {} for {}", kind, provider_id),
+                0,
+            )
+        })
+        .collect()
+}
+
+/// Providers with an API key in the environment
+fn env_keys() -> Vec<DetectedProvider> {
+    let mut found: Vec<DetectedProvider> = Vec::new();
+    for (provider_id, var) in KEY_ENV_VARS {
+        let set = std::env::var(var).is_ok_and(|key| !key.trim().is_empty());
+        if set && !found.iter().any(|p| p.provider_id == *provider_id) {
+            found.push(DetectedProvider::new(provider_id, "env", format!("{} is set", var), 1));
+        }
+    }
+    found
+}
+
+/// AWS credentials Bedrock can use, from the environment or `~/.aws`
+fn aws_credentials(home: Option<&Path>) -> Option<DetectedProvider> {
+    let detail = if std::env::var_os("AWS_ACCESS_KEY_ID").is_some() {
+        "AWS_ACCESS_KEY_ID is set".to_string()
+    } else if let Some(profile) = std::env::var("AWS_PROFILE").ok().filter(|p| !p.is_empty()) {
+        format!("AWS profile {}", profile)
+    } else {
+        let credentials = std::env::var_os("AWS_SHARED_CREDENTIALS_FILE")
+            .map(PathBuf::from)
+            .or_else(|| home.map(|home| home.join(".aws").join("credentials")))?;
+        if !credentials.is_file() {
+            return None;
+        }
+        format!("AWS credentials in {}", credentials.display())
+    };
+    Some(DetectedProvider::new("bedrock", "aws", detail, 3))
+}
+
+#[derive(Deserialize)]
+struct OllamaTags {
+    models: Vec<OllamaTag>,
+}
+
+#[derive(Deserialize)]
+struct OllamaTag {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct OpenAiModels {
+    data: Vec<OpenAiModel>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiModel {
+    id: String,
+}
+
+/// Rank of a local server: ready with models, or running without any
+fn server_rank(models: &[String]) -> u8 {
+    if models.is_empty() {
+        4
+    } else {
+        2
+    }
+}
+
+/// Detail of a local server, naming how many models it has
+fn count_models(server: &str, models: &[String]) -> String {
+    match models.len() {
+        0 => format!("{} is running without models", server),
+        1 => format!("{} is running with 1 model", server),
+        n => format!("{} is running with {} models", server, n),
+    }
+}
+
+/// A running Ollama and its models
+async fn ollama(client: &reqwest::Client, base_url: &str) -> Option<DetectedProvider> {
+    let response = client.get(format!("{}/api/tags", base_url)).send().await.ok()?;
+    let tags: OllamaTags = response.error_for_status().ok()?.json().await.ok()?;
+    let models: Vec<String> = tags.models.into_iter().map(|m| m.name).collect();
+    let rank = server_rank(&models);
+    let mut provider =
+        DetectedProvider::new("ollama", "ollama", count_models("Ollama", &models), rank);
+    provider.base_url = Some(base_url.to_string());
+    provider.models = models;
+    Some(provider)
+}
+
+/// A running LM Studio server and its models
+async fn lm_studio(client: &reqwest::Client) -> Option<DetectedProvider> {
+    let response = client.get(format!("{}/v1/models", LM_STUDIO_URL)).send().await.ok()?;
+    let list: OpenAiModels = response.error_for_status().ok()?.json().await.ok()?;
+    let models: Vec<String> = list.data.into_iter().map(|m| m.id).collect();
+    let rank = server_rank(&models);
+    let mut provider =
+        DetectedProvider::new("custom", "lm-studio", count_models("LM Studio", &models), rank);
+    provider.base_url = Some(format!("{}/v1", LM_STUDIO_URL));
+    provider.models = models;
+    Some(provider)
+}
+
+/// Ollama's URL: the configured one, `OLLAMA_HOST`, or the default
+fn ollama_url(configured: Option<String>) -> String {
+    let url = configured
+        .filter(|url| !url.trim().is_empty())
+        .or_else(|| std::env::var("OLLAMA_HOST").ok().filter(|h| !h.trim().is_empty()))
+        .unwrap_or_else(|| OLLAMA_URL.to_string());
+    let url = url.trim().trim_end_matches('/');
+    if url.contains("://") {
+        url.to_string()
+    } else {
+        format!("http://{}", url)
+    }
+}
+
+/// Look for usable providers, most likely to work first
+pub async fn detect(home: Option<&Path>, state: &DbState) -> Result<Vec<DetectedProvider>, String> {
+    let ollama_config = {
+        let conn = state.reader.lock().map_err(|e| e.to_string())?;
+        db::settings::get_ollama_config(&conn)
+    };
+    let ollama_configured = ollama_config.as_ref().is_some_and(|c| c.enabled);
+    let ollama_url = ollama_url(ollama_config.map(|c| c.base_url));
+
+    let client = reqwest::Client::builder()
+        .timeout(PROBE_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let (ollama, lm_studio) = tokio::join!(ollama(&client, &ollama_url), lm_studio(&client));
+
+    let mut found = Vec::new();
+    if let Some(home) = home {
+        found.extend(opencode_logins(home));
+    }
+    found.extend(env_keys());
+    found.extend(lm_studio);
+    found.extend(aws_credentials(home));
+    for provider in &mut found {
+        provider.configured = secure_storage::has_api_key(&provider.provider_id).unwrap_or(false);
+    }
+    found.extend(ollama.map(|p| DetectedProvider {
+        configured: ollama_configured,
+        ..p
+    }));
+    // Stable, so a provider's login comes before its environment key
+    found.sort_by_key(|p| p.rank);
+
+    tracing::info!(
+        "[Providers] Detected {}",
+        found
+            .iter()
+            .map(|p| format!("{} ({})", p.provider_id, p.source))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    Ok(found)
+}
@@ -0,0 +1,123 @@
+// src-tauri/src/recovery.rs
+//! Recovering tasks a previous run left in progress
+//!
+//! Tasks are only moved to a final status by sidecar events, so when the app
+//! crashes or is killed mid-task they stay `running` (or `starting`, ...)
+//! forever. The sidecar is a child of the app and is started fresh by each
+//! run, so at startup no task of an earlier run can still be executing: every
+//! unfinished task is moved to `interrupted` with a system message saying so.
+//! The recovered tasks are kept for `get_interrupted_tasks`, so the UI can
+//! offer to resume them once it has loaded, and `task:interrupted` is emitted
+//! for listeners that are already up.
+
+use crate::db::{session_locks, task_events};
+use crate::db::tasks::{self, TaskMessageInput, UnfinishedTask};
+use crate::db::DbState;
+use crate::sidecar;
+use rusqlite::{Connection, TransactionBehavior};
+use std::sync::{LazyLock, Mutex};
+use tauri::{AppHandle, Manager};
+
+/// Tasks interrupted at startup, until the UI reads them
+static INTERRUPTED: LazyLock<Mutex<Vec<UnfinishedTask>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+
+fn recovery_note(task: &UnfinishedTask) -> String {
+    let resume = if task.session_id.is_some() {
+        "It can be resumed from its session."
+    } else {
+        "It had not started a session, so it has to be run again."
+    };
+    format!(
+        "Interrupted: the app closed while this task was {}. {}",
+        task.status.replace('_', " "),
+        resume
+    )
+}
+
+/// Move unfinished tasks to `interrupted`, returning them
+fn interrupt(conn: &mut Connection) -> Result<Vec<UnfinishedTask>, String> {
+    let unfinished = tasks::get_unfinished_tasks(conn)?;
+    if unfinished.is_empty() {
+        return Ok(unfinished);
+    }
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    let now = chrono::Utc::now().to_rfc3339();
+    for task in &unfinished {
+        let content = recovery_note(task);
+        tasks::update_task_status(
+            &tx,
+            &task.task_id,
+            "interrupted",
+            Some(&now),
+            task_events::SOURCE_RECOVERY,
+            Some(&content),
+        )?;
+        let note = TaskMessageInput {
+            id: format!("msg_{}", uuid::Uuid::new_v4()),
+            msg_type: "system".to_string(),
+            content,
+            timestamp: now.clone(),
+            tool_name: None,
+            tool_input: None,
+            attachments: None,
+            note: None,
+            origin_id: None,
+        };
+        tasks::add_task_message(&tx, &task.task_id, &note)?;
+        // Its run is gone, so its session can be resumed right away
+        session_locks::release_task(&tx, &task.task_id)?;
+    }
+    tx.commit()
+        .map_err(|e| format!("Failed to commit interrupted tasks: {}", e))?;
+    Ok(unfinished)
+}
+
+/// Interrupt the tasks a previous run left unfinished
+pub fn recover(app: &AppHandle) {
+    let db_state = app.state::<DbState>();
+    let interrupted = match db_state.conn.lock() {
+        Ok(mut conn) => interrupt(&mut conn),
+        Err(e) => Err(e.to_string()),
+    };
+    let interrupted = match interrupted {
+        Ok(interrupted) => interrupted,
+        Err(e) => {
+            tracing::error!("[Recovery] Failed to interrupt unfinished tasks: {}", e);
+            return;
+        }
+    };
+
+    for task in &interrupted {
+        tracing::info!(
+            "[Recovery] Task {} was left {}, marked interrupted",
+            task.task_id,
+            task.status
+        );
+        sidecar::forward_event(
+            app,
+            "task:status-change",
+            serde_json::json!({ "taskId": task.task_id, "status": "interrupted" }),
+        );
+    }
+    if !interrupted.is_empty() {
+        sidecar::forward_event(
+            app,
+            "task:interrupted",
+            serde_json::json!({ "tasks": interrupted }),
+        );
+    }
+    if let Ok(mut recovered) = INTERRUPTED.lock() {
+        *recovered = interrupted;
+    }
+}
+
+/// Tasks interrupted at startup; each is returned once
+pub fn take_interrupted() -> Vec<UnfinishedTask> {
+    INTERRUPTED
+        .lock()
+        .map(|mut recovered| std::mem::take(&mut *recovered))
+        .unwrap_or_default()
+}
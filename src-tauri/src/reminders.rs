@@ -0,0 +1,60 @@
+// src-tauri/src/reminders.rs
+//! Follow-up reminders for task notes
+//!
+//! A background thread looks for notes whose follow-up time has passed and
+//! emits each one once as `task:reminder`, with the note and the task's title,
+//! for the UI to show ("re-check the migration task"). Follow-ups that came
+//! due while the app was closed go out at the first check after it starts.
+
+use crate::db::notes::{self, FollowUp};
+use crate::db::DbState;
+use crate::sidecar;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// How often follow-ups are checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Claim the follow-ups that are due, so another instance doesn't remind them too
+fn take_due(app: &AppHandle) -> Result<Vec<FollowUp>, String> {
+    let now = chrono::Utc::now().to_rfc3339();
+    let db_state = app.state::<DbState>();
+    let due = {
+        let conn = db_state.reader.lock().map_err(|e| e.to_string())?;
+        notes::get_pending_follow_ups(&conn, Some(&now))?
+    };
+    if due.is_empty() {
+        return Ok(due);
+    }
+
+    let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+    let mut claimed = Vec::with_capacity(due.len());
+    for mut follow_up in due {
+        if notes::mark_reminded(&conn, &follow_up.note.id, &now)? {
+            follow_up.note.reminded_at = Some(now.clone());
+            claimed.push(follow_up);
+        }
+    }
+    Ok(claimed)
+}
+
+/// Emit reminders for due follow-ups in the background
+pub fn start(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        // The first check waits for the UI to be listening
+        std::thread::sleep(CHECK_INTERVAL);
+        match take_due(&app) {
+            Ok(due) => {
+                for follow_up in due {
+                    tracing::info!(
+                        "[Reminders] Follow-up {} on {}",
+                        follow_up.note.id,
+                        follow_up.note.task_id
+                    );
+                    sidecar::forward_event(&app, "task:reminder", serde_json::json!(follow_up));
+                }
+            }
+            Err(e) => tracing::warn!("[Reminders] {}", e),
+        }
+    });
+}
@@ -0,0 +1,268 @@
+// src-tauri/src/remote.rs
+//! Remote SSH execution targets
+//!
+//! A remote workspace's directory lives on an SSH host. OpenCode still runs
+//! locally, but its shell commands go over `ssh` into that directory: the
+//! sidecar points OpenCode's shell at an ssh wrapper and turns off the local
+//! file tools, so the agent reads and edits files through commands. Hooks run
+//! on the host the same way, and `read_remote_file` brokers file reads for the
+//! UI. The system `ssh` client is used, so `~/.ssh/config`, known hosts, and
+//! the SSH agent apply. A key kept in the keychain is written to a private
+//! temporary file only while something runs with it: the file goes when the
+//! target is dropped, or for a task when the task finishes.
+
+use crate::db::ssh_hosts::{self, SshHost};
+use crate::db::{self, DbState};
+use crate::secure_storage;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// Largest file `read_file` returns
+const MAX_FILE_BYTES: usize = 5 * 1024 * 1024;
+
+/// Prefix of the temporary directories key files are written to
+const KEY_DIR_PREFIX: &str = "cowork-ssh-";
+
+/// Age past which a key directory was left by a run that didn't shut down
+/// cleanly, rather than being in use by another instance
+const STALE_KEY_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Key files of running tasks, removed when the task finishes
+static TASK_KEYS: LazyLock<Mutex<HashMap<String, Arc<IdentityFile>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// ssh arguments and directory of a remote workspace, sent to the sidecar
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemotePayload {
+    /// Options and destination for `ssh`
+    pub ssh_args: Vec<String>,
+    pub directory: String,
+    /// Key file the options point at, kept while the target is in use
+    #[serde(skip)]
+    identity: Option<Arc<IdentityFile>>,
+}
+
+/// A host's key in a temporary directory only the user can open, removed on drop
+#[derive(Debug)]
+struct IdentityFile {
+    dir: PathBuf,
+}
+
+impl IdentityFile {
+    fn path(&self) -> PathBuf {
+        self.dir.join("id")
+    }
+}
+
+impl Drop for IdentityFile {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_dir_all(&self.dir) {
+            tracing::warn!("[Remote] Failed to remove SSH key file {:?}: {}", self.dir, e);
+        }
+    }
+}
+
+/// Quote a string for a POSIX shell
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Write the host's keychain key to a new temporary directory
+///
+/// The directory's random name means it can't have been created ahead of us;
+/// it is only open to the user, and the file only readable by them.
+fn identity_file(host: &SshHost) -> Result<IdentityFile, String> {
+    let key = secure_storage::get_ssh_key(&host.id)?
+        .ok_or_else(|| format!("No private key stored for {}", host.name))?;
+    let dir = std::env::temp_dir().join(format!("{}{}", KEY_DIR_PREFIX, uuid::Uuid::new_v4()));
+    let mut builder = std::fs::DirBuilder::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder
+        .create(&dir)
+        .map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    let identity = IdentityFile { dir };
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(identity.path())
+        .map_err(|e| format!("Failed to write SSH key: {}", e))?;
+    std::io::Write::write_all(&mut file, key.trim_end().as_bytes())
+        .and_then(|_| std::io::Write::write_all(&mut file, b"\n"))
+        .map_err(|e| format!("Failed to write SSH key: {}", e))?;
+    Ok(identity)
+}
+
+/// Remove key files a previous run left behind, and the ones older versions
+/// kept in the app data directory
+pub fn remove_stale_keys(app: &AppHandle) {
+    if let Ok(dir) = crate::profile::data_dir(app) {
+        let legacy = dir.join("ssh");
+        if legacy.exists() {
+            if let Err(e) = std::fs::remove_dir_all(&legacy) {
+                tracing::warn!("[Remote] Failed to remove {:?}: {}", legacy, e);
+            }
+        }
+    }
+    let Ok(entries) = std::fs::read_dir(std::env::temp_dir()) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let stale = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > STALE_KEY_AGE);
+        if stale && entry.file_name().to_string_lossy().starts_with(KEY_DIR_PREFIX) {
+            let _ = std::fs::remove_dir_all(entry.path());
+        }
+    }
+}
+
+/// Keep a task's key file until `release` is called for the task
+pub fn hold(task_id: &str, target: &RemotePayload) {
+    if let (Some(identity), Ok(mut keys)) = (&target.identity, TASK_KEYS.lock()) {
+        keys.insert(task_id.to_string(), identity.clone());
+    }
+}
+
+/// Remove a finished task's key file
+pub fn release(task_id: &str) {
+    let identity = TASK_KEYS.lock().ok().and_then(|mut keys| keys.remove(task_id));
+    drop(identity);
+}
+
+/// Remove every task's key file, e.g. when the sidecar goes away
+pub fn release_all() {
+    let keys = TASK_KEYS.lock().map(|mut keys| std::mem::take(&mut *keys));
+    drop(keys);
+}
+
+/// Whether a user or host name would be read by ssh as an option
+fn is_option_like(value: &str) -> bool {
+    value.starts_with('-') || value.contains(char::is_whitespace)
+}
+
+/// ssh options and destination for a host, with the key file they point at
+fn ssh_args(host: &SshHost) -> Result<(Vec<String>, Option<IdentityFile>), String> {
+    if is_option_like(&host.host) || host.user.as_deref().is_some_and(is_option_like) {
+        return Err(format!("Invalid SSH destination for {}", host.name));
+    }
+    let mut args = vec![
+        "-o".to_string(),
+        "BatchMode=yes".to_string(),
+        "-o".to_string(),
+        "ConnectTimeout=15".to_string(),
+    ];
+    if let Some(port) = host.port {
+        args.push("-p".to_string());
+        args.push(port.to_string());
+    }
+    let identity = match host.auth.as_str() {
+        "key" => Some(identity_file(host)?),
+        _ => None,
+    };
+    if let Some(identity) = &identity {
+        args.push("-i".to_string());
+        args.push(identity.path().to_string_lossy().to_string());
+        args.push("-o".to_string());
+        args.push("IdentitiesOnly=yes".to_string());
+    }
+    // Nothing after this is read as an option
+    args.push("--".to_string());
+    args.push(match &host.user {
+        Some(user) => format!("{}@{}", user, host.host),
+        None => host.host.clone(),
+    });
+    Ok((args, identity))
+}
+
+/// Target for running commands in `directory` on a host
+pub fn host_target(host: &SshHost, directory: String) -> Result<RemotePayload, String> {
+    let (ssh_args, identity) = ssh_args(host)?;
+    Ok(RemotePayload {
+        ssh_args,
+        directory,
+        identity: identity.map(Arc::new),
+    })
+}
+
+/// Command that runs a shell command in `dir` on the host
+pub fn command(target: &RemotePayload, command: &str) -> Command {
+    let mut cmd = Command::new("ssh");
+    cmd.args(&target.ssh_args)
+        .arg(format!("cd {} && {}", quote(&target.directory), command));
+    cmd
+}
+
+fn run(mut cmd: Command) -> Result<Vec<u8>, String> {
+    let output = cmd
+        .output()
+        .map_err(|e| format!("Failed to run ssh: {}", e))?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// Remote target of a workspace, or `None` if the workspace is local
+pub fn workspace_target(
+    app: &AppHandle,
+    workspace_id: Option<&str>,
+) -> Result<Option<RemotePayload>, String> {
+    let Some(workspace_id) = workspace_id else {
+        return Ok(None);
+    };
+    let (host, directory) = {
+        let db_state = app.state::<DbState>();
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        let Some(host_id) = db::workspaces::get_ssh_host_id(&conn, workspace_id)? else {
+            return Ok(None);
+        };
+        let host = ssh_hosts::get_host(&conn, &host_id)?
+            .ok_or_else(|| format!("SSH host not found: {}", host_id))?;
+        let workspace = db::workspaces::get_workspace(&conn, workspace_id)?
+            .ok_or_else(|| format!("Workspace not found: {}", workspace_id))?;
+        (host, workspace.path)
+    };
+    host_target(&host, directory).map(Some)
+}
+
+/// Connect to a target's host and return its `uname -a`
+pub fn test_connection(target: &RemotePayload) -> Result<String, String> {
+    let mut cmd = Command::new("ssh");
+    cmd.args(&target.ssh_args).arg("uname -a");
+    let output = run(cmd)?;
+    Ok(String::from_utf8_lossy(&output).trim().to_string())
+}
+
+/// Check that the target's directory exists on the host
+pub fn check_directory(target: &RemotePayload) -> Result<(), String> {
+    run(command(target, "true"))
+        .map(|_| ())
+        .map_err(|e| format!("Cannot open {} on the remote host: {}", target.directory, e))
+}
+
+/// Read a file on the host; relative paths are resolved in the target's directory
+pub fn read_file(target: &RemotePayload, path: &str) -> Result<String, String> {
+    let cmd = command(target, &format!("head -c {} {}", MAX_FILE_BYTES, quote(path)));
+    let output = run(cmd).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    Ok(String::from_utf8_lossy(&output).to_string())
+}
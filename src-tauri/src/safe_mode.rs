@@ -0,0 +1,62 @@
+// src-tauri/src/safe_mode.rs
+//! Safe mode, for recovering from a configuration that crashes the app at launch
+//!
+//! Started with `--safe-mode` or `COWORK_Z_SAFE_MODE=1`, the app opens its
+//! database and window but runs nothing on the user's behalf: the sidecar is
+//! never spawned, so no task, title or summary reaches a provider, workspace
+//! hooks don't run, and the startup jobs that reach outside the app or
+//! rewrite stored data (API server, folder sync, sandbox cleanup, attachment
+//! deduplication, task recovery, the message journal replay, backups,
+//! reminders, health checks and the session lease sweeper) are skipped.
+//! Provider settings and keys are left as they are. History, settings and the
+//! diagnostics tools still work, so whatever is at fault can be changed before
+//! `exit_safe_mode` relaunches the app normally.
+
+use std::sync::OnceLock;
+
+/// Command-line flag that starts the app in safe mode
+const SAFE_MODE_ARG: &str = "--safe-mode";
+
+/// Environment variable that starts the app in safe mode when set to anything but `0`/`false`
+const SAFE_MODE_ENV: &str = "COWORK_Z_SAFE_MODE";
+
+static SAFE_MODE: OnceLock<bool> = OnceLock::new();
+
+fn requested() -> bool {
+    std::env::args().skip(1).any(|arg| arg == SAFE_MODE_ARG)
+        || std::env::var(SAFE_MODE_ENV)
+            .is_ok_and(|value| !matches!(value.trim(), "" | "0" | "false"))
+}
+
+/// Decide whether this run is in safe mode; called once at startup, before logging
+pub fn init() -> bool {
+    *SAFE_MODE.get_or_init(requested)
+}
+
+/// Whether this run is in safe mode
+pub fn enabled() -> bool {
+    SAFE_MODE.get().copied().unwrap_or(false)
+}
+
+/// Fail with an explanation when `what` is off in safe mode
+pub fn check(what: &str) -> Result<(), String> {
+    if enabled() {
+        return Err(format!(
+            "{} is off in safe mode; restart the app normally to use it",
+            what
+        ));
+    }
+    Ok(())
+}
+
+/// Start a new instance of the app outside safe mode; the caller exits this one
+pub fn relaunch_normally() -> Result<(), String> {
+    let exe = std::env::current_exe()
+        .map_err(|e| format!("Failed to find the app executable: {}", e))?;
+    std::process::Command::new(exe)
+        .args(std::env::args().skip(1).filter(|arg| arg != SAFE_MODE_ARG))
+        .env_remove(SAFE_MODE_ENV)
+        .spawn()
+        .map_err(|e| format!("Failed to relaunch the app: {}", e))?;
+    Ok(())
+}
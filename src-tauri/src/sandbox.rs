@@ -0,0 +1,184 @@
+// src-tauri/src/sandbox.rs
+//! Docker sandbox execution mode
+//!
+//! Workspaces with a sandbox image run their tasks' shell commands in a Docker
+//! container instead of on the host. The container is started from the image
+//! before the task runs, with the workspace directory mounted at the same path
+//! so file tools and shell commands see the same files. The sidecar points
+//! OpenCode's shell at `docker exec` into it, and the container is removed
+//! once the task completes or fails, or its run fails to start.
+//!
+//! Containers are labelled with the profile that started them, so one left by
+//! a crash is only cleared out by the next start of the same profile.
+
+use serde::Serialize;
+use std::collections::HashSet;
+use std::process::Command;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+/// Label set on every sandbox container, naming its task
+const SANDBOX_LABEL: &str = "com.kevinlin.cowork-z.sandbox";
+
+/// Label naming the profile a sandbox container belongs to
+const PROFILE_LABEL: &str = "com.kevinlin.cowork-z.profile";
+
+/// Container a task's commands run in
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxPayload {
+    pub container: String,
+}
+
+/// Tasks whose sandbox container is running
+#[derive(Default)]
+pub struct SandboxState {
+    tasks: Mutex<HashSet<String>>,
+}
+
+fn container_name(task_id: &str) -> String {
+    format!("cowork-z-{}", task_id)
+}
+
+fn docker(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("docker")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run docker (is Docker installed?): {}", e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// Start a task's container, or reuse it if it is still running
+pub fn start(
+    state: &SandboxState,
+    task_id: &str,
+    image: &str,
+    working_directory: &str,
+) -> Result<SandboxPayload, String> {
+    if cfg!(windows) {
+        return Err("Docker sandbox mode is not supported on Windows".to_string());
+    }
+
+    let name = container_name(task_id);
+    if docker(&["inspect", "-f", "{{.State.Running}}", &name]).as_deref() == Ok("true") {
+        return Ok(SandboxPayload { container: name });
+    }
+    // Clear out a stopped container left by an earlier run
+    let _ = docker(&["rm", "-f", &name]);
+
+    let mount = format!("{}:{}", working_directory, working_directory);
+    let label = format!("{}={}", SANDBOX_LABEL, task_id);
+    let profile_label = format!("{}={}", PROFILE_LABEL, crate::profile::current());
+    docker(&[
+        "run",
+        "-d",
+        "--rm",
+        "--name",
+        &name,
+        "--label",
+        &label,
+        "--label",
+        &profile_label,
+        "-v",
+        &mount,
+        "-w",
+        working_directory,
+        "--entrypoint",
+        "sleep",
+        image,
+        "infinity",
+    ])
+    .map_err(|e| format!("Failed to start sandbox from {}: {}", image, e))?;
+
+    tracing::info!("[Sandbox] Started {} from {} for task {}", name, image, task_id);
+    if let Ok(mut tasks) = state.tasks.lock() {
+        tasks.insert(task_id.to_string());
+    }
+    Ok(SandboxPayload { container: name })
+}
+
+/// Remove a task's container once the task has finished, if it has one
+pub fn stop(state: &SandboxState, task_id: &str) {
+    let started = state
+        .tasks
+        .lock()
+        .map(|mut tasks| tasks.remove(task_id))
+        .unwrap_or(false);
+    if !started {
+        return;
+    }
+    let name = container_name(task_id);
+    match docker(&["rm", "-f", &name]) {
+        Ok(_) => tracing::info!("[Sandbox] Removed {}", name),
+        Err(e) => tracing::warn!("[Sandbox] Failed to remove {}: {}", name, e),
+    }
+}
+
+/// Remove a task's container in the background, if it has one
+pub fn stop_in_background(app: &AppHandle, task_id: &str) {
+    let started = app
+        .state::<SandboxState>()
+        .tasks
+        .lock()
+        .map(|tasks| tasks.contains(task_id))
+        .unwrap_or(false);
+    if !started {
+        return;
+    }
+    let app = app.clone();
+    let task_id = task_id.to_string();
+    std::thread::spawn(move || stop(&app.state::<SandboxState>(), &task_id));
+}
+
+/// A sandbox started for a run that is being started
+///
+/// The container is removed when this is dropped, i.e. when starting the run
+/// fails, unless `keep` was called once the run started.
+pub struct SandboxGuard {
+    app: AppHandle,
+    task_id: String,
+    kept: bool,
+}
+
+impl SandboxGuard {
+    pub fn new(app: &AppHandle, task_id: &str) -> Self {
+        Self {
+            app: app.clone(),
+            task_id: task_id.to_string(),
+            kept: false,
+        }
+    }
+
+    /// Keep the container until the run finishes
+    pub fn keep(mut self) {
+        self.kept = true;
+    }
+}
+
+impl Drop for SandboxGuard {
+    fn drop(&mut self) {
+        if !self.kept {
+            stop_in_background(&self.app, &self.task_id);
+        }
+    }
+}
+
+/// Remove this profile's containers left behind by tasks that never finished
+/// (e.g. after a crash)
+pub fn remove_stale() {
+    let filter = format!("label={}", SANDBOX_LABEL);
+    let profile_filter = format!("label={}={}", PROFILE_LABEL, crate::profile::current());
+    let Ok(ids) = docker(&["ps", "-aq", "--filter", &filter, "--filter", &profile_filter])
+    else {
+        return;
+    };
+    for id in ids.lines().filter(|id| !id.is_empty()) {
+        if let Err(e) = docker(&["rm", "-f", id]) {
+            tracing::warn!("[Sandbox] Failed to remove stale container {}: {}", id, e);
+        }
+    }
+}
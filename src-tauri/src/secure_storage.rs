@@ -1,12 +1,38 @@
 // src-tauri/src/secure_storage.rs
 //! Secure storage using OS Keychain (macOS Keychain, Windows Credential Manager, Linux Secret Service)
+//!
+//! Reading an entry can prompt the user (the Secret Service on Linux asks per
+//! read), so whether each account holds a key is cached after the first read
+//! and kept current by `store_api_key` and `delete_api_key`. Status checks are
+//! answered from the cache, and secrets are only read for accounts that hold one.
 
+use crate::profile;
 use keyring::Entry;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
 
 const SERVICE_NAME: &str = "com.kevinlin.cowork-z";
 
+/// Keychain account holding the local API server token
+const API_SERVER_TOKEN_ACCOUNT: &str = "api-server-token";
+
+/// Keychain account holding the GitHub personal access token
+const GITHUB_TOKEN_ACCOUNT: &str = "github-token";
+
+/// Keychain account holding this device's X25519 secret for encrypted sync
+const SYNC_DEVICE_KEY_ACCOUNT: &str = "sync-device-key";
+
+/// Keychain account holding the symmetric key shared by paired devices
+const SYNC_GROUP_KEY_ACCOUNT: &str = "sync-group-key";
+
+/// Keychain account read to check the keychain can be reached; never written
+const PROBE_ACCOUNT: &str = "health-probe";
+
+/// Status of each account read so far
+static KEY_STATUS: LazyLock<Mutex<HashMap<String, ApiKeyStatus>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
 /// API key providers
 pub const PROVIDERS: &[&str] = &[
     "anthropic",
@@ -21,6 +47,8 @@ pub const PROVIDERS: &[&str] = &[
     "litellm",
     "openrouter",
     "custom",
+    // Brave Search API key for the "brave" web search backend
+    "brave",
 ];
 
 /// Stored API key metadata
@@ -37,59 +65,89 @@ pub struct ApiKeyInfo {
     pub key_prefix: Option<String>,
 }
 
+/// Record whether an account holds a key, with its first few characters for display
+fn cache_status(provider: &str, api_key: Option<&str>) -> ApiKeyStatus {
+    let status = ApiKeyStatus {
+        exists: api_key.is_some(),
+        prefix: api_key.map(|key| {
+            let prefix: String = key.chars().take(8).collect();
+            format!("{}...", prefix)
+        }),
+    };
+    if let Ok(mut cache) = KEY_STATUS.lock() {
+        cache.insert(provider.to_string(), status.clone());
+    }
+    status
+}
+
+fn cached_status(provider: &str) -> Option<ApiKeyStatus> {
+    KEY_STATUS.lock().ok()?.get(provider).cloned()
+}
+
+/// Keychain entry of an account, under the current profile's service
+fn entry(account: &str) -> Result<Entry, String> {
+    Entry::new(&profile::keychain_service(SERVICE_NAME), account)
+        .map_err(|e| format!("Keychain error: {}", e))
+}
+
+/// Read an entry from the keychain, bypassing the status cache
+fn read_api_key(provider: &str) -> Result<Option<String>, String> {
+    let entry = entry(provider)?;
+
+    match entry.get_password() {
+        Ok(password) => Ok(Some(password)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to get API key: {}", e)),
+    }
+}
+
 /// Store an API key in the OS keychain
 pub fn store_api_key(provider: &str, api_key: &str) -> Result<(), String> {
-    let entry = Entry::new(SERVICE_NAME, provider).map_err(|e| format!("Keychain error: {}", e))?;
+    let entry = entry(provider)?;
 
     entry
         .set_password(api_key)
         .map_err(|e| format!("Failed to store API key: {}", e))?;
 
+    cache_status(provider, Some(api_key));
     Ok(())
 }
 
-/// Retrieve an API key from the OS keychain
+/// Retrieve an API key from the OS keychain; accounts known to be empty aren't read
 pub fn get_api_key(provider: &str) -> Result<Option<String>, String> {
-    let entry = Entry::new(SERVICE_NAME, provider).map_err(|e| format!("Keychain error: {}", e))?;
-
-    match entry.get_password() {
-        Ok(password) => Ok(Some(password)),
-        Err(keyring::Error::NoEntry) => Ok(None),
-        Err(e) => Err(format!("Failed to get API key: {}", e)),
+    if cached_status(provider).is_some_and(|status| !status.exists) {
+        return Ok(None);
     }
+    let api_key = read_api_key(provider)?;
+    cache_status(provider, api_key.as_deref());
+    Ok(api_key)
 }
 
 /// Delete an API key from the OS keychain
 pub fn delete_api_key(provider: &str) -> Result<bool, String> {
-    let entry = Entry::new(SERVICE_NAME, provider).map_err(|e| format!("Keychain error: {}", e))?;
+    let entry = entry(provider)?;
 
-    match entry.delete_password() {
-        Ok(()) => Ok(true),
-        Err(keyring::Error::NoEntry) => Ok(false),
-        Err(e) => Err(format!("Failed to delete API key: {}", e)),
-    }
+    let deleted = match entry.delete_password() {
+        Ok(()) => true,
+        Err(keyring::Error::NoEntry) => false,
+        Err(e) => return Err(format!("Failed to delete API key: {}", e)),
+    };
+    cache_status(provider, None);
+    Ok(deleted)
 }
 
-/// Check if an API key exists for a provider
-pub fn has_api_key(provider: &str) -> Result<bool, String> {
-    let entry = Entry::new(SERVICE_NAME, provider).map_err(|e| format!("Keychain error: {}", e))?;
-
-    match entry.get_password() {
-        Ok(_) => Ok(true),
-        Err(keyring::Error::NoEntry) => Ok(false),
-        Err(e) => Err(format!("Failed to check API key: {}", e)),
+/// Status of a provider's key, reading the keychain only the first time
+fn key_status(provider: &str) -> Result<ApiKeyStatus, String> {
+    if let Some(status) = cached_status(provider) {
+        return Ok(status);
     }
+    let api_key = read_api_key(provider)?;
+    Ok(cache_status(provider, api_key.as_deref()))
 }
 
-/// Get key prefix (first few characters) for display
-pub fn get_key_prefix(provider: &str) -> Result<Option<String>, String> {
-    match get_api_key(provider)? {
-        Some(key) => {
-            let prefix_len = std::cmp::min(8, key.len());
-            Ok(Some(format!("{}...", &key[..prefix_len])))
-        }
-        None => Ok(None),
-    }
+/// Check if an API key exists for a provider
+pub fn has_api_key(provider: &str) -> Result<bool, String> {
+    Ok(key_status(provider)?.exists)
 }
 
 /// Get status of all API keys
@@ -97,20 +155,7 @@ pub fn get_all_api_key_status() -> Result<HashMap<String, ApiKeyStatus>, String>
     let mut result = HashMap::new();
 
     for provider in PROVIDERS {
-        let exists = has_api_key(provider)?;
-        let prefix = if exists {
-            get_key_prefix(provider)?
-        } else {
-            None
-        };
-
-        result.insert(
-            provider.to_string(),
-            ApiKeyStatus {
-                exists,
-                prefix,
-            },
-        );
+        result.insert(provider.to_string(), key_status(provider)?);
     }
 
     Ok(result)
@@ -159,6 +204,11 @@ pub struct BedrockCredentials {
     pub region: String,
 }
 
+/// Check the keychain can be read, e.g. that the Secret Service is running
+pub fn check_access() -> Result<(), String> {
+    read_api_key(PROBE_ACCOUNT).map(|_| ())
+}
+
 /// Clear all stored API keys
 pub fn clear_all_api_keys() -> Result<(), String> {
     for provider in PROVIDERS {
@@ -166,3 +216,97 @@ pub fn clear_all_api_keys() -> Result<(), String> {
     }
     Ok(())
 }
+
+/// Get the local API server token, generating one on first use
+pub fn get_or_create_api_server_token() -> Result<String, String> {
+    match get_api_key(API_SERVER_TOKEN_ACCOUNT)? {
+        Some(token) => Ok(token),
+        None => regenerate_api_server_token(),
+    }
+}
+
+/// Replace the local API server token with a freshly generated one
+pub fn regenerate_api_server_token() -> Result<String, String> {
+    let token = format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    );
+    store_api_key(API_SERVER_TOKEN_ACCOUNT, &token)?;
+    Ok(token)
+}
+
+/// Store the GitHub personal access token
+pub fn store_github_token(token: &str) -> Result<(), String> {
+    store_api_key(GITHUB_TOKEN_ACCOUNT, token)
+}
+
+/// Get the GitHub personal access token
+pub fn get_github_token() -> Result<Option<String>, String> {
+    get_api_key(GITHUB_TOKEN_ACCOUNT)
+}
+
+/// Delete the GitHub personal access token
+pub fn delete_github_token() -> Result<bool, String> {
+    delete_api_key(GITHUB_TOKEN_ACCOUNT)
+}
+
+/// Get this device's encrypted sync secret (base64)
+pub fn get_sync_device_key() -> Result<Option<String>, String> {
+    get_api_key(SYNC_DEVICE_KEY_ACCOUNT)
+}
+
+/// Store this device's encrypted sync secret (base64)
+pub fn store_sync_device_key(key: &str) -> Result<(), String> {
+    store_api_key(SYNC_DEVICE_KEY_ACCOUNT, key)
+}
+
+/// Get the key shared by paired devices (base64)
+pub fn get_sync_group_key() -> Result<Option<String>, String> {
+    get_api_key(SYNC_GROUP_KEY_ACCOUNT)
+}
+
+/// Store the key shared by paired devices (base64)
+pub fn store_sync_group_key(key: &str) -> Result<(), String> {
+    store_api_key(SYNC_GROUP_KEY_ACCOUNT, key)
+}
+
+/// Keychain account holding a secret environment variable of a workspace or task
+fn env_secret_account(owner_id: &str, name: &str) -> String {
+    format!("env:{}:{}", owner_id, name)
+}
+
+/// Store a secret environment variable
+pub fn store_env_secret(owner_id: &str, name: &str, value: &str) -> Result<(), String> {
+    store_api_key(&env_secret_account(owner_id, name), value)
+}
+
+/// Get a secret environment variable
+pub fn get_env_secret(owner_id: &str, name: &str) -> Result<Option<String>, String> {
+    get_api_key(&env_secret_account(owner_id, name))
+}
+
+/// Delete a secret environment variable
+pub fn delete_env_secret(owner_id: &str, name: &str) -> Result<bool, String> {
+    delete_api_key(&env_secret_account(owner_id, name))
+}
+
+/// Keychain account holding the private key of an SSH host
+fn ssh_key_account(host_id: &str) -> String {
+    format!("ssh-key:{}", host_id)
+}
+
+/// Store the private key of an SSH host
+pub fn store_ssh_key(host_id: &str, key: &str) -> Result<(), String> {
+    store_api_key(&ssh_key_account(host_id), key)
+}
+
+/// Get the private key of an SSH host
+pub fn get_ssh_key(host_id: &str) -> Result<Option<String>, String> {
+    get_api_key(&ssh_key_account(host_id))
+}
+
+/// Delete the private key of an SSH host
+pub fn delete_ssh_key(host_id: &str) -> Result<bool, String> {
+    delete_api_key(&ssh_key_account(host_id))
+}
@@ -0,0 +1,130 @@
+// src-tauri/src/session_context.rs
+//! What a resumed session starts with
+//!
+//! `get_session_context` shows the UI what a task resuming a session will
+//! see. The sidecar reads the session with `opencode export` and measures it
+//! (`get_session_context`): how many messages it holds and roughly how many
+//! tokens they take, from the provider's count where one was reported. The
+//! tasks that ran in the session come from the database. Sidecar replies are
+//! matched to the waiting command by request ID.
+
+use crate::db::tasks::{self, SessionTask};
+use crate::db::DbState;
+use crate::sidecar::{self, SidecarState};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::oneshot;
+
+/// How long to wait for the sidecar to export a session
+const REPLY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The session's size as the sidecar measured it
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Measured {
+    message_count: u64,
+    token_estimate: u64,
+    reported: bool,
+}
+
+/// A session as a task resuming it would start
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionContext {
+    pub session_id: String,
+    pub message_count: u64,
+    pub token_estimate: u64,
+    /// Whether the estimate builds on a token count the provider reported
+    pub token_count_reported: bool,
+    /// Tasks that ran in the session, oldest first
+    pub tasks: Vec<SessionTask>,
+}
+
+type Reply = Result<Measured, String>;
+
+static PENDING: LazyLock<Mutex<HashMap<String, oneshot::Sender<Reply>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Measure a session and list the tasks that ran in it
+pub async fn get(app: &AppHandle, session_id: &str) -> Result<SessionContext, String> {
+    let tasks = {
+        let db_state = app.state::<DbState>();
+        let conn = db_state.reader.lock().map_err(|e| e.to_string())?;
+        tasks::get_session_tasks(&conn, session_id)?
+    };
+
+    let request_id = uuid::Uuid::new_v4().to_string();
+    let (tx, rx) = oneshot::channel();
+    PENDING
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(request_id.clone(), tx);
+
+    let sent = send_request(app, &request_id, session_id).await;
+    let reply = match sent {
+        Ok(()) => tokio::time::timeout(REPLY_TIMEOUT, rx).await,
+        Err(e) => {
+            forget(&request_id);
+            return Err(e);
+        }
+    };
+    let measured = match reply {
+        Ok(Ok(reply)) => reply?,
+        Ok(Err(_)) => return Err("Sidecar stopped before measuring the session".to_string()),
+        Err(_) => {
+            forget(&request_id);
+            return Err(format!("Timed out measuring session {}", session_id));
+        }
+    };
+
+    Ok(SessionContext {
+        session_id: session_id.to_string(),
+        message_count: measured.message_count,
+        token_estimate: measured.token_estimate,
+        token_count_reported: measured.reported,
+        tasks,
+    })
+}
+
+async fn send_request(app: &AppHandle, request_id: &str, session_id: &str) -> Result<(), String> {
+    let sidecar_state = app.state::<SidecarState>();
+    let mut manager = sidecar_state.manager.lock().await;
+    if !manager.is_running() {
+        manager.spawn(app).await?;
+    }
+    manager
+        .send_command(sidecar::SidecarCommand::GetSessionContext {
+            payload: sidecar::SessionContextRequestPayload {
+                request_id: request_id.to_string(),
+                session_id: session_id.to_string(),
+            },
+        })
+        .await
+}
+
+fn forget(request_id: &str) {
+    if let Ok(mut pending) = PENDING.lock() {
+        pending.remove(request_id);
+    }
+}
+
+/// Hand a `session_context` or `session_context_error` reply to the waiting command
+pub fn apply(payload: serde_json::Value) {
+    let Some(request_id) = payload.get("requestId").and_then(|id| id.as_str()) else {
+        tracing::warn!("[SessionContext] Reply without a request ID");
+        return;
+    };
+    let Some(tx) = PENDING.lock().ok().and_then(|mut p| p.remove(request_id)) else {
+        // The command timed out
+        return;
+    };
+    let reply = match payload.get("error").and_then(|e| e.as_str()) {
+        Some(error) => Err(format!("Failed to read session: {}", error)),
+        None => serde_json::from_value(payload)
+            .map_err(|e| format!("Invalid session context: {}", e)),
+    };
+    let _ = tx.send(reply);
+}
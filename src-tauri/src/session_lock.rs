@@ -0,0 +1,216 @@
+// src-tauri/src/session_lock.rs
+//! Locking OpenCode sessions while a run continues them
+//!
+//! Two runs continuing one session (a follow-up sent twice, or a resume while
+//! the session is still going) would interleave their turns, so commands that
+//! continue a session take a lease on it first and fail fast with a
+//! `session:busy` error when another task holds one. Leases belong to this app
+//! instance (`HOLDER`) and are renewed while it runs; the sidecar's
+//! `task_complete`/`task_error` or cancelling the task releases a task's
+//! lease, and a sidecar that terminates releases them all. Leases of an
+//! instance that crashed expire after `LEASE`.
+//!
+//! Runs that start a new session (new tasks, forks, and reruns from an edit or
+//! a checkpoint) can't name it up front, so `observe` leases it once the
+//! run's streamed messages do.
+
+use crate::db::session_locks;
+use crate::db::DbState;
+use std::collections::HashSet;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// How long a lease lasts without being renewed
+const LEASE: Duration = Duration::from_secs(90);
+
+/// How often this instance renews its leases
+const RENEW_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Prefix of the error returned when a session is locked
+pub const BUSY_ERROR: &str = "session:busy";
+
+/// This app instance, as the holder of its leases
+static HOLDER: LazyLock<String> = LazyLock::new(|| uuid::Uuid::new_v4().to_string());
+
+/// Tasks whose current run has a lease, or has tried to take one in `observe`
+static CLAIMED: LazyLock<Mutex<HashSet<String>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+fn claim(task_id: &str) -> bool {
+    CLAIMED.lock().map(|mut claimed| claimed.insert(task_id.to_string())).unwrap_or(false)
+}
+
+fn expires_at(now: chrono::DateTime<chrono::Utc>) -> String {
+    let lease = chrono::Duration::from_std(LEASE).unwrap_or_default();
+    (now + lease).to_rfc3339()
+}
+
+/// A lease taken for a run that is being started
+///
+/// The lease is released when this is dropped, i.e. when starting the run
+/// fails, unless `keep` was called once the run started.
+pub struct SessionLease<'a> {
+    state: &'a DbState,
+    task_id: String,
+    kept: bool,
+}
+
+impl SessionLease<'_> {
+    /// Hold the lease until the run finishes
+    pub fn keep(mut self) {
+        claim(&self.task_id);
+        self.kept = true;
+    }
+}
+
+impl Drop for SessionLease<'_> {
+    fn drop(&mut self) {
+        if !self.kept {
+            release_in(self.state, &self.task_id);
+        }
+    }
+}
+
+/// Lock a session for a task, or fail with `BUSY_ERROR` if another task has it
+pub fn acquire<'a>(
+    state: &'a DbState,
+    session_id: &str,
+    task_id: &str,
+) -> Result<SessionLease<'a>, String> {
+    let now = chrono::Utc::now();
+    let blocking = {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        session_locks::try_acquire(
+            &conn,
+            session_id,
+            task_id,
+            &HOLDER,
+            &now.to_rfc3339(),
+            &expires_at(now),
+        )?
+    };
+    if let Some(lock) = blocking {
+        tracing::info!(
+            "[SessionLock] {} refused for {}: held by {}",
+            session_id,
+            task_id,
+            lock.task_id
+        );
+        return Err(format!(
+            "{}: Session {} is in use by task {}",
+            BUSY_ERROR, session_id, lock.task_id
+        ));
+    }
+    Ok(SessionLease {
+        state,
+        task_id: task_id.to_string(),
+        kept: false,
+    })
+}
+
+/// Lease the session a run's streamed messages name, if the run has none yet
+///
+/// The run is already going, so a session another task holds is only logged.
+pub fn observe(app: &AppHandle, task_id: &str, session_id: &str) {
+    if !claim(task_id) {
+        return;
+    }
+    let now = chrono::Utc::now();
+    let db_state = app.state::<DbState>();
+    let blocking = db_state.conn.lock().map_err(|e| e.to_string()).and_then(|conn| {
+        session_locks::try_acquire(
+            &conn,
+            session_id,
+            task_id,
+            &HOLDER,
+            &now.to_rfc3339(),
+            &expires_at(now),
+        )
+    });
+    match blocking {
+        Ok(Some(lock)) if lock.task_id != task_id => tracing::warn!(
+            "[SessionLock] {} runs in {} while task {} holds it",
+            task_id,
+            session_id,
+            lock.task_id
+        ),
+        Ok(_) => {}
+        Err(e) => tracing::warn!("[SessionLock] {}", e),
+    }
+}
+
+fn release_in(state: &DbState, task_id: &str) {
+    if let Ok(mut claimed) = CLAIMED.lock() {
+        claimed.remove(task_id);
+    }
+    let result = state
+        .conn
+        .lock()
+        .map_err(|e| e.to_string())
+        .and_then(|conn| session_locks::release_task(&conn, task_id));
+    if let Err(e) = result {
+        tracing::warn!("[SessionLock] {}", e);
+    }
+}
+
+/// Release a finished task's lease
+pub fn release(app: &AppHandle, task_id: &str) {
+    release_in(&app.state::<DbState>(), task_id);
+}
+
+/// Release every lease of this instance, e.g. when the sidecar goes away
+pub fn release_all(app: &AppHandle) {
+    if let Ok(mut claimed) = CLAIMED.lock() {
+        claimed.clear();
+    }
+    let db_state = app.state::<DbState>();
+    let result = db_state
+        .conn
+        .lock()
+        .map_err(|e| e.to_string())
+        .and_then(|conn| session_locks::release_holder(&conn, &HOLDER));
+    if let Err(e) = result {
+        tracing::warn!("[SessionLock] {}", e);
+    }
+}
+
+/// Renew this instance's leases in the background
+pub fn start(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(RENEW_INTERVAL);
+        let db_state = app.state::<DbState>();
+        let result = db_state
+            .conn
+            .lock()
+            .map_err(|e| e.to_string())
+            .and_then(|conn| {
+                session_locks::renew(&conn, &HOLDER, &expires_at(chrono::Utc::now()))
+            });
+        if let Err(e) = result {
+            tracing::warn!("[SessionLock] {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::tasks::tests::TestDb;
+
+    #[test]
+    fn cancelled_task_lease_can_be_reacquired() {
+        let db = TestDb::new();
+        let state = DbState {
+            conn: Mutex::new(db.open()),
+            reader: Mutex::new(db.open()),
+        };
+
+        acquire(&state, "ses_1", "task_a").unwrap().keep();
+        let busy = acquire(&state, "ses_1", "task_b").err().unwrap();
+        assert!(busy.starts_with(BUSY_ERROR));
+
+        // What `cancel_task` does through `sidecar::finish_task`
+        release_in(&state, "task_a");
+        acquire(&state, "ses_1", "task_b").unwrap().keep();
+    }
+}
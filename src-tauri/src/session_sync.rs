@@ -0,0 +1,194 @@
+// src-tauri/src/session_sync.rs
+//! Reconciling stored messages with OpenCode's session
+//!
+//! A session can advance outside the app, e.g. when it is resumed with the
+//! OpenCode CLI. `sync_session` asks the sidecar for the session as OpenCode
+//! stored it (`get_transcript`), and the reply is reconciled into the task
+//! using the session: transcript messages are keyed by OpenCode part ID, and
+//! the ones missing from the task are appended in transcript order with that
+//! ID as their origin. The user messages the app stored when it sent them have
+//! no part ID, so transcript user turns are matched to them in order by
+//! content and the match is recorded as their origin. Stored messages are
+//! otherwise left as they are. The outcome is emitted as `task:session_synced`.
+
+use crate::db::{self, DbState};
+use crate::message_buffer::MessageBufferState;
+use crate::sidecar::{self, SidecarState};
+use rusqlite::{Connection, TransactionBehavior};
+use serde::Deserialize;
+use tauri::{AppHandle, Manager};
+
+/// A message of the transcript, see `TranscriptMessage` in the sidecar
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TranscriptMessage {
+    /// OpenCode part ID
+    id: String,
+    #[serde(rename = "type")]
+    msg_type: String,
+    content: String,
+    timestamp: String,
+    tool_name: Option<String>,
+    tool_input: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TranscriptPayload {
+    session_id: String,
+    #[serde(default)]
+    messages: Option<Vec<TranscriptMessage>>,
+    error: Option<String>,
+}
+
+/// Ask the sidecar for the transcript of the task using a session, returning the task ID
+pub async fn request(app: &AppHandle, session_id: &str) -> Result<String, String> {
+    let task_id = {
+        let db_state = app.state::<DbState>();
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        db::tasks::get_task_id_for_session(&conn, session_id)
+            .ok_or_else(|| format!("No task uses session {}", session_id))?
+    };
+
+    let sidecar_state = app.state::<SidecarState>();
+    let mut manager = sidecar_state.manager.lock().await;
+    if !manager.is_running() {
+        manager.spawn(app).await?;
+    }
+    manager
+        .send_command(sidecar::SidecarCommand::GetTranscript {
+            task_id: task_id.clone(),
+            payload: sidecar::TranscriptRequestPayload {
+                session_id: session_id.to_string(),
+            },
+        })
+        .await?;
+    Ok(task_id)
+}
+
+/// Whether a transcript user turn is the prompt a stored user message was sent as
+///
+/// The agent may have been sent more than the user typed (attachment text, or
+/// the transcript seeding a new session), so the turn only has to contain it.
+fn is_sent_prompt(turn: &str, stored: &str) -> bool {
+    let stored = stored.trim();
+    !stored.is_empty() && turn.contains(stored)
+}
+
+/// Append the transcript messages the task doesn't have, returning how many were added
+fn reconcile(
+    conn: &mut Connection,
+    task_id: &str,
+    messages: Vec<TranscriptMessage>,
+) -> Result<usize, String> {
+    let known = db::tasks::get_message_keys(conn, task_id)?;
+    let missing: Vec<TranscriptMessage> =
+        messages.into_iter().filter(|m| !known.contains(&m.id)).collect();
+    if missing.is_empty() {
+        return Ok(0);
+    }
+    let unmatched = db::tasks::get_unmatched_user_messages(conn, task_id)?;
+    let mut next_unmatched = 0;
+
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    let mut added = 0;
+    for message in missing {
+        if message.msg_type == "user" {
+            let matched = unmatched[next_unmatched..]
+                .iter()
+                .position(|(_, content)| is_sent_prompt(&message.content, content));
+            if let Some(offset) = matched {
+                let (stored_id, _) = &unmatched[next_unmatched + offset];
+                db::tasks::set_message_origin(&tx, stored_id, &message.id)?;
+                next_unmatched += offset + 1;
+                continue;
+            }
+        }
+
+        // Part IDs are unique, but another task may hold a copy of the message
+        let id = if db::tasks::message_exists(&tx, &message.id) {
+            format!("msg_{}", uuid::Uuid::new_v4())
+        } else {
+            message.id.clone()
+        };
+        let diff_source = message.tool_name.clone().zip(message.tool_input.clone());
+        let input = db::tasks::TaskMessageInput {
+            id: id.clone(),
+            msg_type: message.msg_type,
+            content: message.content,
+            timestamp: message.timestamp,
+            tool_name: message.tool_name,
+            tool_input: message.tool_input,
+            attachments: None,
+            note: None,
+            origin_id: Some(message.id),
+        };
+        if !db::tasks::add_task_message(&tx, task_id, &input)? {
+            continue;
+        }
+        added += 1;
+
+        if let Some((tool_name, tool_input)) = diff_source {
+            if let Err(e) = db::diffs::save_for_message(&tx, &id, &tool_name, &tool_input) {
+                tracing::warn!("[Diff] {}", e);
+            }
+        }
+    }
+    tx.commit()
+        .map_err(|e| format!("Failed to commit synced messages: {}", e))?;
+    Ok(added)
+}
+
+/// Reconcile a transcript the sidecar sent for a task
+pub fn apply(app: &AppHandle, task_id: &str, payload: serde_json::Value) {
+    let payload: TranscriptPayload = match serde_json::from_value(payload) {
+        Ok(payload) => payload,
+        Err(e) => {
+            tracing::warn!("[SessionSync] Invalid transcript for {}: {}", task_id, e);
+            return;
+        }
+    };
+
+    let outcome = match (payload.messages, payload.error) {
+        (Some(messages), None) => {
+            let db_state = app.state::<DbState>();
+            db_state
+                .conn
+                .lock()
+                .map_err(|e| e.to_string())
+                .and_then(|mut conn| {
+                    // Buffered messages count as stored
+                    app.state::<MessageBufferState>().flush_task(&mut conn, task_id);
+                    reconcile(&mut conn, task_id, messages)
+                })
+        }
+        (_, error) => Err(error.unwrap_or_else(|| "No transcript".to_string())),
+    };
+
+    let event = match outcome {
+        Ok(added) => {
+            tracing::info!(
+                "[SessionSync] Added {} message(s) from session {} to {}",
+                added,
+                payload.session_id,
+                task_id
+            );
+            serde_json::json!({
+                "taskId": task_id,
+                "sessionId": payload.session_id,
+                "added": added,
+            })
+        }
+        Err(e) => {
+            tracing::warn!("[SessionSync] Failed to sync session {}: {}", payload.session_id, e);
+            serde_json::json!({
+                "taskId": task_id,
+                "sessionId": payload.session_id,
+                "error": e,
+            })
+        }
+    };
+    sidecar::forward_event(app, "task:session_synced", event);
+}
@@ -2,10 +2,16 @@
 //!
 //! The sidecar communicates via JSON-line messages over stdin/stdout.
 
+use crate::db::usage::UsageInput;
+use crate::db::DbState;
+use crate::message_buffer::MessageBufferState;
+use crate::structured_output::Outcome;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::Duration;
 use tauri::async_runtime::Mutex;
 use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::broadcast;
 use tauri_plugin_shell::process::{CommandChild, CommandEvent};
 use tauri_plugin_shell::ShellExt;
 
@@ -33,6 +39,8 @@ pub struct ApiKeys {
     pub azure_foundry: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bedrock: Option<BedrockCredentials>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub brave: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,11 +68,33 @@ pub enum SidecarCommand {
         #[serde(rename = "taskId")]
         task_id: String,
     },
+    /// Kill the process trees of all running tasks; no task events follow
+    AbortAll,
     SendResponse {
         #[serde(rename = "taskId")]
         task_id: String,
         payload: SendResponsePayload,
     },
+    GenerateText {
+        #[serde(rename = "taskId")]
+        task_id: String,
+        payload: GenerateTextPayload,
+    },
+    /// Read a task's session as OpenCode stored it, answered with
+    /// `session_transcript` or `session_transcript_error`
+    GetTranscript {
+        #[serde(rename = "taskId")]
+        task_id: String,
+        payload: TranscriptRequestPayload,
+    },
+    /// Measure a session, answered with `session_context` or `session_context_error`
+    GetSessionContext {
+        payload: SessionContextRequestPayload,
+    },
+    /// Keys that changed since the last update; `null` removes a key
+    UpdateApiKeys {
+        payload: serde_json::Map<String, serde_json::Value>,
+    },
     Ping,
     CheckCli,
 }
@@ -82,6 +112,51 @@ pub struct StartTaskPayload {
     pub working_directory: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub model_id: Option<String>,
+    /// Generation parameters set for the model
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_parameters: Option<crate::db::providers::ModelParameters>,
+    /// Run the read-only planning agent and report the plan instead of executing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan_only: Option<bool>,
+    /// OpenCode agent to run instead of the default one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    /// Files attached to the prompt
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attachments: Option<Vec<AttachmentFile>>,
+    /// Web search access; unset keeps the agent's default tools
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub web_search: Option<WebSearchPayload>,
+    /// JSON schema the final reply must match
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<serde_json::Value>,
+    /// Environment variables of the workspace and task, with secrets resolved
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<std::collections::HashMap<String, String>>,
+    /// Container the agent's shell commands run in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<crate::sandbox::SandboxPayload>,
+    /// SSH host and directory the agent's shell commands run in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote: Option<crate::remote::RemotePayload>,
+    /// Instructions, permissions, pinned files, and MCP servers of the workspace
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_settings: Option<crate::db::workspaces::AgentSettings>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSearchPayload {
+    pub enabled: bool,
+    /// "fetch", "exa" or "brave"
+    pub backend: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentFile {
+    pub path: String,
+    pub mime_type: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -89,6 +164,32 @@ pub struct SendResponsePayload {
     pub response: String,
 }
 
+/// One-off completion for a task, answered with `text_generated` or `text_error`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateTextPayload {
+    /// What the text is for; echoed back with the result
+    pub purpose: String,
+    pub prompt: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_keys: Option<ApiKeys>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptRequestPayload {
+    pub session_id: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionContextRequestPayload {
+    pub request_id: String,
+    pub session_id: String,
+}
+
 /// Events received from the sidecar
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -100,10 +201,100 @@ pub struct SidecarEvent {
     pub payload: Option<serde_json::Value>,
 }
 
+/// Capacity of the channel mirroring frontend events for external clients
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// An event emitted to the frontend, mirrored for external clients
+#[derive(Debug, Clone, Serialize)]
+pub struct ForwardedEvent {
+    pub event: String,
+    pub payload: serde_json::Value,
+}
+
+impl ForwardedEvent {
+    /// Task ID carried in the payload, if any
+    pub fn task_id(&self) -> Option<&str> {
+        self.payload.get("taskId").and_then(|v| v.as_str())
+    }
+}
+
+/// Emit an event to the frontend and mirror it to external subscribers
+pub(crate) fn forward_event(app: &AppHandle, event: &str, payload: serde_json::Value) {
+    mirror_event(
+        app,
+        ForwardedEvent {
+            event: event.to_string(),
+            payload: payload.clone(),
+        },
+    );
+
+    if let Err(e) = app.emit(event, payload) {
+        tracing::error!("[sidecar] Failed to emit event {}: {}", event, e);
+    }
+}
+
+/// Send an event to external clients
+fn mirror_event(app: &AppHandle, event: ForwardedEvent) {
+    crate::observer::publish(&event);
+    if let Some(state) = app.try_state::<SidecarState>() {
+        // No receivers is the common case and not an error
+        let _ = state.events.send(event);
+    }
+}
+
+/// Drop what is kept for a task while it runs; a completed task's checkpoint goes too
+pub fn finish_task(app: &AppHandle, task_id: &str, completed: bool) {
+    crate::task_progress::finish(app, task_id);
+    crate::postprocess::finish(task_id);
+    crate::citations::finish(task_id);
+    crate::checkpoint::finish(app, task_id, completed);
+    crate::session_lock::release(app, task_id);
+    crate::remote::release(task_id);
+}
+
+/// How long streaming events are held so a burst reaches the webview as one emit
+const EVENT_BATCH_INTERVAL: Duration = Duration::from_millis(30);
+
+/// Events held for the next batch instead of emitted one by one
+const BATCHED_EVENTS: &[&str] = &["task:message", "task:progress"];
+
+/// Streaming task events waiting for the next tick
+///
+/// Each task's pending events are emitted as one `task:batch` event with
+/// `{ taskId, events: [{ event, payload }] }`, in arrival order. External
+/// clients still get them one by one.
+#[derive(Default)]
+struct EventBatch {
+    tasks: Vec<(String, Vec<ForwardedEvent>)>,
+}
+
+impl EventBatch {
+    fn push(&mut self, task_id: String, event: ForwardedEvent) {
+        match self.tasks.iter_mut().find(|(id, _)| *id == task_id) {
+            Some((_, events)) => events.push(event),
+            None => self.tasks.push((task_id, vec![event])),
+        }
+    }
+
+    fn flush(&mut self, app: &AppHandle) {
+        for (task_id, events) in self.tasks.drain(..) {
+            for event in &events {
+                mirror_event(app, event.clone());
+            }
+            let payload = serde_json::json!({ "taskId": task_id, "events": events });
+            if let Err(e) = app.emit("task:batch", payload) {
+                tracing::error!("[sidecar] Failed to emit task batch for {}: {}", task_id, e);
+            }
+        }
+    }
+}
+
 /// Manages the sidecar process lifecycle
 pub struct SidecarManager {
     child: Option<CommandChild>,
     is_ready: bool,
+    /// API keys the running sidecar holds, as sent with `update_api_keys`
+    sent_api_keys: serde_json::Map<String, serde_json::Value>,
 }
 
 impl SidecarManager {
@@ -111,6 +302,7 @@ impl SidecarManager {
         Self {
             child: None,
             is_ready: false,
+            sent_api_keys: serde_json::Map::new(),
         }
     }
 
@@ -120,10 +312,12 @@ impl SidecarManager {
     }
 
     /// Spawn the sidecar process
+    #[tracing::instrument(target = "sidecar", skip_all, err)]
     pub async fn spawn(&mut self, app: &AppHandle) -> Result<(), String> {
         if self.child.is_some() {
             return Ok(());
         }
+        crate::safe_mode::check("The agent sidecar")?;
 
         let resource_dir = app.path().resource_dir().ok();
         let current_exe = std::env::current_exe().ok();
@@ -164,21 +358,62 @@ impl SidecarManager {
             }
         }
 
+        // Refuse to run tasks against a CLI whose output the sidecar can't parse.
+        // Finding it runs npm and the CLI itself, so it stays off the async runtime.
+        let handle = app.clone();
+        let cli = tauri::async_runtime::spawn_blocking(move || crate::cli::resolve(&handle))
+            .await
+            .map_err(|e| format!("Failed to resolve OpenCode CLI: {}", e))?;
+        if let Some(cli) = &cli {
+            crate::cli::ensure_supported(app, cli)?;
+        }
+
         let shell = app.shell();
+        let mut command = shell
+            .sidecar("cowork-sidecar")
+            .map_err(|e| format!("Failed to create sidecar command: {}", e))?;
+
+        // Point the sidecar at the CLI binary that `check_claude_cli` resolves
+        if let Some(cli) = cli {
+            if cli.bundled {
+                tracing::info!("[sidecar] Using bundled OpenCode CLI at {:?}", cli.path);
+                // The agent's commands get the bundled node/npx too, which sit next to it
+                if let Some(bin) = cli.path.parent().map(std::path::Path::to_path_buf) {
+                    let path = std::env::var_os("PATH").unwrap_or_default();
+                    let dirs = std::iter::once(bin).chain(std::env::split_paths(&path));
+                    if let Ok(path) = std::env::join_paths(dirs) {
+                        command = command.env("PATH", path);
+                    }
+                }
+            }
+            command = command.env("OPENCODE_CLI_PATH", cli.path);
+        }
 
         // Spawn the sidecar
-        let (mut rx, child) = shell
-            .sidecar("cowork-sidecar")
-            .map_err(|e| format!("Failed to create sidecar command: {}", e))?
+        let (mut rx, child) = command
             .spawn()
             .map_err(|e| format!("Failed to spawn sidecar: {}", e))?;
+        crate::metrics::record_sidecar_start();
 
         // Clone app handle for event forwarding
         let app_handle = app.clone();
 
         // Spawn stdout reader task
         tauri::async_runtime::spawn(async move {
-            while let Some(event) = rx.recv().await {
+            let mut batch = EventBatch::default();
+            let mut tick = tokio::time::interval(EVENT_BATCH_INTERVAL);
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                let event = tokio::select! {
+                    event = rx.recv() => event,
+                    _ = tick.tick() => {
+                        batch.flush(&app_handle);
+                        continue;
+                    }
+                };
+                let Some(event) = event else {
+                    break;
+                };
                 match event {
                     CommandEvent::Stdout(line) => {
                         let line_str = String::from_utf8_lossy(&line);
@@ -188,54 +423,97 @@ impl SidecarManager {
                             lines += 1;
                             if let Ok(event) = serde_json::from_str::<SidecarEvent>(json_line) {
                                 parsed += 1;
-                                Self::handle_sidecar_event(&app_handle, event);
+                                Self::handle_sidecar_event(&app_handle, event, &mut batch);
                             }
                         }
                     }
                     CommandEvent::Stderr(line) => {
                         let line_str = String::from_utf8_lossy(&line);
-                        eprintln!("[sidecar stderr] {}", line_str);
+                        tracing::warn!(target: "sidecar", "[sidecar stderr] {}", line_str.trim_end());
                     }
                     CommandEvent::Error(err) => {
                         let err_str = err.to_string();
-                        eprintln!("[sidecar error] {}", err);
-                        let _ = app_handle.emit("sidecar:error", &err);
+                        tracing::error!(target: "sidecar", "[sidecar error] {}", err);
+                        forward_event(&app_handle, "sidecar:error", serde_json::json!(err_str));
                     }
                     CommandEvent::Terminated(payload) => {
-                        println!(
+                        batch.flush(&app_handle);
+                        // Keep where running tasks got to before they were cut off
+                        crate::checkpoint::save_all(&app_handle);
+                        // Its runs are over, so are their sessions' leases
+                        crate::session_lock::release_all(&app_handle);
+                        crate::remote::release_all();
+                        crate::task_progress::finish_all(&app_handle);
+                        tracing::info!(
+                            target: "sidecar",
                             "[sidecar] terminated with code: {:?}",
                             payload.code
                         );
-                        let _ = app_handle.emit("sidecar:terminated", payload.code);
+                        forward_event(
+                            &app_handle,
+                            "sidecar:terminated",
+                            serde_json::json!(payload.code),
+                        );
                     }
                     _ => {}
                 }
             }
+            batch.flush(&app_handle);
         });
 
         self.child = Some(child);
         self.is_ready = true;
+        self.sent_api_keys.clear();
 
         Ok(())
     }
 
     /// Send a command to the sidecar
-    pub async fn send_command(&mut self, cmd: SidecarCommand) -> Result<(), String> {
+    ///
+    /// API keys in the command aren't sent with it: the sidecar keeps the keys
+    /// it was given, and only the ones that changed are sent first.
+    #[tracing::instrument(target = "sidecar", skip_all, err)]
+    pub async fn send_command(&mut self, mut cmd: SidecarCommand) -> Result<(), String> {
+        let api_keys = match &mut cmd {
+            SidecarCommand::StartTask { payload, .. } => payload.api_keys.take(),
+            SidecarCommand::GenerateText { payload, .. } => payload.api_keys.take(),
+            _ => None,
+        };
+        if let Some(api_keys) = api_keys {
+            self.update_api_keys(&api_keys)?;
+        }
+
         let (cmd_type, has_task_id) = match &cmd {
             SidecarCommand::StartTask { task_id, .. } => ("start_task", !task_id.is_empty()),
             SidecarCommand::CancelTask { task_id } => ("cancel_task", !task_id.is_empty()),
             SidecarCommand::InterruptTask { task_id } => ("interrupt_task", !task_id.is_empty()),
+            SidecarCommand::AbortAll => ("abort_all", false),
             SidecarCommand::SendResponse { task_id, .. } => ("send_response", !task_id.is_empty()),
+            SidecarCommand::GenerateText { task_id, .. } => ("generate_text", !task_id.is_empty()),
+            SidecarCommand::GetTranscript { task_id, .. } => ("get_transcript", !task_id.is_empty()),
+            SidecarCommand::GetSessionContext { .. } => ("get_session_context", false),
+            SidecarCommand::UpdateApiKeys { .. } => ("update_api_keys", false),
             SidecarCommand::Ping => ("ping", false),
             SidecarCommand::CheckCli => ("check_cli", false),
         };
 
+        self.write(&cmd)?;
+        // The task's ssh commands need its key file until it finishes
+        if let SidecarCommand::StartTask { task_id, payload } = &cmd {
+            if let Some(remote) = &payload.remote {
+                crate::remote::hold(task_id, remote);
+            }
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, cmd: &SidecarCommand) -> Result<(), String> {
         let child = self
             .child
             .as_mut()
             .ok_or("Sidecar not running")?;
 
-        let json = serde_json::to_string(&cmd)
+        let json = serde_json::to_string(cmd)
             .map_err(|e| format!("Failed to serialize command: {}", e))?;
 
         child
@@ -245,46 +523,388 @@ impl SidecarManager {
         Ok(())
     }
 
+    /// Send the keys that differ from those the sidecar holds
+    fn update_api_keys(&mut self, api_keys: &ApiKeys) -> Result<(), String> {
+        let serde_json::Value::Object(keys) = serde_json::to_value(api_keys)
+            .map_err(|e| format!("Failed to serialize API keys: {}", e))?
+        else {
+            return Ok(());
+        };
+
+        let mut changes = serde_json::Map::new();
+        for (name, value) in &keys {
+            if self.sent_api_keys.get(name) != Some(value) {
+                changes.insert(name.clone(), value.clone());
+            }
+        }
+        for name in self.sent_api_keys.keys() {
+            if !keys.contains_key(name) {
+                changes.insert(name.clone(), serde_json::Value::Null);
+            }
+        }
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        self.write(&SidecarCommand::UpdateApiKeys { payload: changes })?;
+        self.sent_api_keys = keys;
+        Ok(())
+    }
+
     /// Handle events from the sidecar and forward to frontend
-    fn handle_sidecar_event(app: &AppHandle, event: SidecarEvent) {
-        if matches!(
-            event.event_type.as_str(),
-            "task_message" | "task_progress" | "task_complete" | "task_error"
+    fn handle_sidecar_event(app: &AppHandle, mut event: SidecarEvent, batch: &mut EventBatch) {
+        // Task events that aren't streamed go out after the ones before them
+        if !matches!(event.event_type.as_str(), "task_message" | "task_progress" | "log") {
+            batch.flush(app);
+        }
+        if let (true, Some(task_id)) = (
+            matches!(event.event_type.as_str(), "task_complete" | "task_error"),
+            &event.task_id,
         ) {
+            finish_task(app, task_id, event.event_type == "task_complete");
         }
-        let event_name = match event.event_type.as_str() {
-            "ready" => "sidecar:ready",
-            "pong" => "sidecar:pong",
-            "cli_status" => "sidecar:cli_status",
-            "task_started" => "task:started",
-            "task_message" => "task:message",
-            "task_progress" => "task:progress",
-            "permission_request" => "task:permission_request",
-            "task_complete" => "task:complete",
-            "task_error" => "task:error",
-            "log" => "sidecar:log",
-            "error" => "sidecar:error",
-            _ => {
-                println!("[sidecar] unknown event type: {}", event.event_type);
+        match event.event_type.as_str() {
+            "ready" => {
+                let version = event
+                    .payload
+                    .as_ref()
+                    .and_then(|p| p.get("version"))
+                    .and_then(|v| v.as_str())
+                    .map(|v| v.to_string());
+                crate::crash::set_sidecar_version(version);
+            }
+            "log" => {
+                let payload = event.payload.as_ref();
+                let level = payload
+                    .and_then(|p| p.get("level"))
+                    .and_then(|l| l.as_str())
+                    .unwrap_or("info");
+                let message = payload
+                    .and_then(|p| p.get("message"))
+                    .and_then(|m| m.as_str())
+                    .unwrap_or_default();
+                let task_id = event.task_id.as_deref().unwrap_or_default();
+                match level {
+                    "error" => tracing::error!(target: "sidecar", task_id, "{}", message),
+                    "warn" => tracing::warn!(target: "sidecar", task_id, "{}", message),
+                    _ => tracing::info!(target: "sidecar", task_id, "{}", message),
+                }
+            }
+            "task_complete" => {
+                let result = event.payload.as_ref().and_then(|p| p.get("result"));
+                let plan = result.and_then(|r| r.get("plan")).and_then(|p| p.as_str());
+                if let (Some(task_id), Some(plan)) = (&event.task_id, plan) {
+                    let session_id = result
+                        .and_then(|r| r.get("sessionId"))
+                        .and_then(|s| s.as_str());
+                    Self::record_plan(app, task_id, plan, session_id);
+                    Self::stop_sandbox(app, task_id);
+                    return;
+                }
+
+                let succeeded = |payload: &Option<serde_json::Value>| {
+                    payload
+                        .as_ref()
+                        .and_then(|p| p.get("result"))
+                        .and_then(|r| r.get("status"))
+                        .and_then(|s| s.as_str())
+                        == Some("success")
+                };
+                if let (true, Some(task_id), Some(payload)) =
+                    (succeeded(&event.payload), &event.task_id, event.payload.as_mut())
+                {
+                    if Self::apply_structured_output(app, task_id, payload) {
+                        return;
+                    }
+                }
+                if let Some(task_id) = &event.task_id {
+                    let result = event
+                        .payload
+                        .as_ref()
+                        .and_then(crate::TaskResult::from_complete)
+                        .unwrap_or_else(|| crate::TaskResult {
+                            status: "error".to_string(),
+                            error: Some("Task ended without a result".to_string()),
+                            session_id: None,
+                            structured_output: None,
+                        });
+                    Self::record_outcome(app, task_id, &result);
+                    Self::stop_sandbox(app, task_id);
+                }
+
+                if let (true, Some(task_id)) = (succeeded(&event.payload), event.task_id.clone()) {
+                    let app = app.clone();
+                    std::thread::spawn(move || {
+                        crate::hooks::run_hooks(&app, &task_id, crate::hooks::HookStage::PostTask)
+                    });
+                }
+                // Summarize the finished task for the history list
+                if let (true, Some(task_id)) = (succeeded(&event.payload), event.task_id.clone()) {
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = crate::summaries::request_summary(&app, &task_id).await {
+                            tracing::warn!("[Summary] {}", e);
+                        }
+                    });
+                }
+            }
+            "task_error" => {
+                if let Some(task_id) = &event.task_id {
+                    let payload = event.payload.as_ref().unwrap_or(&serde_json::Value::Null);
+                    Self::record_outcome(app, task_id, &crate::TaskResult::from_error(payload));
+                    Self::stop_sandbox(app, task_id);
+                }
+            }
+            "text_generated" | "text_error" => {
+                let payload = event.payload.as_ref();
+                let field = |name: &str| payload.and_then(|p| p.get(name)).and_then(|v| v.as_str());
+                match (&event.task_id, field("purpose"), field("text")) {
+                    (Some(task_id), Some(purpose), Some(text)) => {
+                        crate::summaries::apply(app, task_id, purpose, text)
+                    }
+                    _ => tracing::warn!(
+                        "[sidecar] {} for task {:?} failed: {}",
+                        field("purpose").unwrap_or("text generation"),
+                        event.task_id,
+                        field("error").unwrap_or("no text")
+                    ),
+                }
+                return;
+            }
+            "task_message" => {
+                if let (Some(task_id), Some(payload)) = (&event.task_id, event.payload.as_mut()) {
+                    let session_id = payload
+                        .get("message")
+                        .and_then(|m| m.get("part"))
+                        .and_then(|p| p.get("sessionID"))
+                        .and_then(|s| s.as_str());
+                    if let Some(session_id) = session_id {
+                        crate::session_lock::observe(app, task_id, session_id);
+                    }
+                    crate::checkpoint::observe(task_id, payload);
+                    crate::citations::observe(app, task_id, payload);
+                    crate::postprocess::apply(app, task_id, payload);
+                    Self::record_message(app, task_id, payload);
+                }
+            }
+            "task_progress" => {
+                let progress = event.payload.as_ref().and_then(|p| p.get("progress")).cloned();
+                if let (Some(task_id), Some(progress)) = (&event.task_id, progress) {
+                    crate::task_progress::record(app, task_id, progress);
+                }
+            }
+            "session_transcript" | "session_transcript_error" => {
+                match (&event.task_id, event.payload) {
+                    (Some(task_id), Some(payload)) => {
+                        crate::session_sync::apply(app, task_id, payload)
+                    }
+                    _ => tracing::warn!("[sidecar] {} without a task", event.event_type),
+                }
                 return;
             }
+            "session_context" | "session_context_error" => {
+                if let Some(payload) = event.payload {
+                    crate::session_context::apply(payload);
+                }
+                return;
+            }
+            "task_usage" => {
+                let usage = event.payload.as_ref().and_then(|p| p.get("usage")).cloned();
+                if let (Some(task_id), Some(usage)) = (&event.task_id, usage) {
+                    Self::record_usage(app, task_id, usage);
+                }
+            }
+            _ => {}
+        }
+
+        let Some(event_name) = crate::events::app_event_name(&event.event_type) else {
+            tracing::warn!("[sidecar] unknown event type: {}", event.event_type);
+            return;
+        };
+        let forwarded = crate::events::forwarded(event_name, event.task_id.clone(), event.payload);
+
+        if let (true, Some(task_id)) = (BATCHED_EVENTS.contains(&event_name), event.task_id) {
+            batch.push(task_id, forwarded);
+            return;
+        }
+        forward_event(app, event_name, forwarded.payload);
+    }
+
+    /// Save a streamed message to the task's history, processed as the UI shows it
+    fn record_message(app: &AppHandle, task_id: &str, payload: &serde_json::Value) {
+        let Some(message) = payload.get("message").and_then(crate::message_buffer::streamed_message)
+        else {
+            return;
+        };
+        let Some(messages) = app.try_state::<MessageBufferState>() else {
+            return;
+        };
+        if let Err(e) = messages.push(task_id, message) {
+            tracing::warn!("[sidecar] Failed to save message of task {}: {}", task_id, e);
+        }
+    }
+
+    /// Store how a task ended, so its history is complete without the UI
+    fn record_outcome(app: &AppHandle, task_id: &str, result: &crate::TaskResult) {
+        let (Some(db_state), Some(messages)) =
+            (app.try_state::<DbState>(), app.try_state::<MessageBufferState>())
+        else {
+            return;
         };
+        let result = db_state
+            .conn
+            .lock()
+            .map_err(|e| e.to_string())
+            .and_then(|mut conn| {
+                let status = result.task_status();
+                let source = crate::db::task_events::SOURCE_SIDECAR;
+                crate::store_outcome(&mut conn, &messages, task_id, status, result, source)
+            });
+        if let Err(e) = result {
+            tracing::warn!("[sidecar] Failed to store outcome of task {}: {}", task_id, e);
+        }
+    }
 
-        // Build the payload to emit
-        let mut emit_payload = serde_json::json!({});
-        if let Some(task_id) = &event.task_id {
-            emit_payload["taskId"] = serde_json::json!(task_id);
+    /// Persist a step's token usage and cost
+    fn record_usage(app: &AppHandle, task_id: &str, usage: serde_json::Value) {
+        let usage: UsageInput = match serde_json::from_value(usage) {
+            Ok(usage) => usage,
+            Err(e) => {
+                tracing::warn!("[sidecar] invalid usage payload: {}", e);
+                return;
+            }
+        };
+        let Some(db_state) = app.try_state::<DbState>() else {
+            return;
+        };
+        let result = db_state
+            .conn
+            .lock()
+            .map_err(|e| e.to_string())
+            .and_then(|conn| crate::db::usage::insert_usage(&conn, task_id, &usage));
+        if let Err(e) = result {
+            tracing::warn!("[sidecar] {}", e);
         }
-        if let Some(payload) = event.payload {
-            emit_payload["payload"] = payload;
+    }
+
+    /// Remove a finished task's sandbox container in the background
+    fn stop_sandbox(app: &AppHandle, task_id: &str) {
+        let app = app.clone();
+        let task_id = task_id.to_string();
+        std::thread::spawn(move || {
+            crate::sandbox::stop(&app.state::<crate::sandbox::SandboxState>(), &task_id)
+        });
+    }
+
+    /// Validate a structured task's final reply into `result.structuredOutput`
+    ///
+    /// Returns true when the reply was invalid and a retry was sent, in which
+    /// case the task is still running and the completion is not forwarded.
+    fn apply_structured_output(app: &AppHandle, task_id: &str, payload: &mut serde_json::Value) -> bool {
+        let Some(db_state) = app.try_state::<DbState>() else {
+            return false;
+        };
+        let reply = payload
+            .get("result")
+            .and_then(|r| r.get("output"))
+            .and_then(|o| o.as_str())
+            .map(|o| o.to_string());
+        let outcome = db_state
+            .conn
+            .lock()
+            .map_err(|e| e.to_string())
+            .and_then(|conn| crate::structured_output::check(&conn, task_id, reply.as_deref()));
+
+        match outcome {
+            Ok(Some(Outcome::Valid(value))) => {
+                payload["result"]["structuredOutput"] = value;
+                false
+            }
+            Ok(Some(Outcome::Invalid(error))) => {
+                tracing::warn!("[Structured] Task {} output is invalid: {}", task_id, error);
+                payload["result"]["status"] = serde_json::json!("error");
+                payload["result"]["error"] =
+                    serde_json::json!(format!("Output did not match the schema: {}", error));
+                false
+            }
+            Ok(Some(Outcome::Retry(prompt))) => {
+                tracing::info!("[Structured] Retrying task {} after invalid output", task_id);
+                let session_id = payload
+                    .get("result")
+                    .and_then(|r| r.get("sessionId"))
+                    .and_then(|s| s.as_str())
+                    .map(|s| s.to_string());
+                let (app, task_id) = (app.clone(), task_id.to_string());
+                tauri::async_runtime::spawn(async move {
+                    let retried =
+                        crate::structured_output::retry(&app, &task_id, session_id.clone(), prompt)
+                            .await;
+                    // The task ends here when the retry can't start
+                    if let Err(e) = retried {
+                        tracing::warn!("[Structured] Failed to retry task {}: {}", task_id, e);
+                        let error = format!("Failed to retry after invalid output: {}", e);
+                        let result = crate::TaskResult {
+                            status: "error".to_string(),
+                            error: Some(error.clone()),
+                            session_id,
+                            structured_output: None,
+                        };
+                        Self::record_outcome(&app, &task_id, &result);
+                        Self::stop_sandbox(&app, &task_id);
+                        forward_event(
+                            &app,
+                            "task:error",
+                            serde_json::json!({ "taskId": task_id, "payload": { "error": error } }),
+                        );
+                    }
+                });
+                true
+            }
+            Ok(None) => false,
+            Err(e) => {
+                tracing::warn!("[sidecar] {}", e);
+                false
+            }
         }
+    }
 
-        if let Err(e) = app.emit(event_name, emit_payload) {
-            eprintln!("[sidecar] Failed to emit event {}: {}", event_name, e);
+    /// Store a plan-only run's plan and hold the task for approval
+    fn record_plan(app: &AppHandle, task_id: &str, plan: &str, session_id: Option<&str>) {
+        let Some(db_state) = app.try_state::<DbState>() else {
+            return;
+        };
+        let result = db_state.conn.lock().map_err(|e| e.to_string()).and_then(|mut conn| {
+            // The planning turn's messages are stored before the task waits for approval
+            if let Some(messages) = app.try_state::<MessageBufferState>() {
+                messages.flush_task(&mut conn, task_id);
+            }
+            crate::db::plans::save_plan(&conn, task_id, plan, session_id)?;
+            crate::db::tasks::update_task_status(
+                &conn,
+                task_id,
+                "awaiting_approval",
+                None,
+                crate::db::task_events::SOURCE_SIDECAR,
+                Some("Plan ready for approval"),
+            )
+        });
+        if let Err(e) = result {
+            tracing::warn!("[sidecar] {}", e);
+            return;
         }
+
+        forward_event(
+            app,
+            "task:plan_ready",
+            serde_json::json!({
+                "taskId": task_id,
+                "payload": { "plan": plan, "sessionId": session_id },
+            }),
+        );
     }
 
     /// Stop the sidecar process
+    #[tracing::instrument(target = "sidecar", skip_all, err)]
     pub async fn stop(&mut self) -> Result<(), String> {
         if let Some(child) = self.child.take() {
             child.kill().map_err(|e| format!("Failed to kill sidecar: {}", e))?;
@@ -303,13 +923,42 @@ impl Default for SidecarManager {
 /// State for sidecar manager
 pub struct SidecarState {
     pub manager: Arc<Mutex<SidecarManager>>,
+    pub events: broadcast::Sender<ForwardedEvent>,
+    /// Keys read from the keychain, until a key is added or removed
+    api_keys: std::sync::Mutex<Option<ApiKeys>>,
 }
 
 impl SidecarState {
     pub fn new() -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
         Self {
             manager: Arc::new(Mutex::new(SidecarManager::new())),
+            events,
+            api_keys: std::sync::Mutex::new(None),
+        }
+    }
+
+    /// API keys for the sidecar, read from the keychain only after a change
+    pub fn api_keys(&self) -> Result<ApiKeys, String> {
+        let mut cached = self.api_keys.lock().map_err(|e| e.to_string())?;
+        if let Some(api_keys) = cached.as_ref() {
+            return Ok(api_keys.clone());
         }
+        let api_keys = get_all_api_keys()?;
+        *cached = Some(api_keys.clone());
+        Ok(api_keys)
+    }
+
+    /// Re-read the keys on the next `api_keys`, after a key is stored or removed
+    pub fn invalidate_api_keys(&self) {
+        if let Ok(mut cached) = self.api_keys.lock() {
+            *cached = None;
+        }
+    }
+
+    /// Subscribe to the events forwarded to the frontend
+    pub fn subscribe(&self) -> broadcast::Receiver<ForwardedEvent> {
+        self.events.subscribe()
     }
 }
 
@@ -353,6 +1002,9 @@ pub fn get_all_api_keys() -> Result<ApiKeys, String> {
     if let Ok(Some(key)) = secure_storage::get_api_key("azureFoundry") {
         keys.azure_foundry = Some(key);
     }
+    if let Ok(Some(key)) = secure_storage::get_api_key("brave") {
+        keys.brave = Some(key);
+    }
 
     // Get Bedrock credentials
     if let Ok(Some(creds)) = secure_storage::get_bedrock_credentials() {
@@ -0,0 +1,53 @@
+// src-tauri/src/slash_commands.rs
+//! Slash command expansion
+//!
+//! A prompt starting with `/name` is replaced with the template of the stored
+//! command of that name. `$ARGUMENTS` in the template becomes the rest of the
+//! prompt; templates without it get the arguments appended. Prompts naming an
+//! unknown command (e.g. an absolute path) are left as typed.
+
+use crate::db::commands::{self, SlashCommand};
+use rusqlite::Connection;
+
+/// Placeholder for the text after the command name
+const ARGUMENTS_PLACEHOLDER: &str = "$ARGUMENTS";
+
+/// Prompt after expansion, with the command's defaults
+pub struct Expansion {
+    pub prompt: String,
+    pub command: SlashCommand,
+}
+
+/// Command name and arguments of a `/name args` prompt
+fn parse(prompt: &str) -> Option<(&str, &str)> {
+    let rest = prompt.trim_start().strip_prefix('/')?;
+    let (name, args) = match rest.find(char::is_whitespace) {
+        Some(i) => (&rest[..i], rest[i..].trim()),
+        None => (rest, ""),
+    };
+    (!name.is_empty()).then_some((name, args))
+}
+
+fn fill(template: &str, args: &str) -> String {
+    if template.contains(ARGUMENTS_PLACEHOLDER) {
+        template.replace(ARGUMENTS_PLACEHOLDER, args)
+    } else if args.is_empty() {
+        template.to_string()
+    } else {
+        format!("{}\n\n{}", template.trim_end(), args)
+    }
+}
+
+/// Expand a prompt that invokes a stored command, or `None` if it does not
+pub fn expand(conn: &Connection, prompt: &str) -> Result<Option<Expansion>, String> {
+    let Some((name, args)) = parse(prompt) else {
+        return Ok(None);
+    };
+    let Some(command) = commands::get_command_by_name(conn, name)? else {
+        return Ok(None);
+    };
+    Ok(Some(Expansion {
+        prompt: fill(&command.template, args),
+        command,
+    }))
+}
@@ -0,0 +1,184 @@
+// src-tauri/src/structured_output.rs
+//! Structured output tasks
+//!
+//! A task started with an `outputSchema` must end with a JSON reply matching
+//! the schema. The sidecar asks the agent for JSON and reports its final reply;
+//! here it is parsed and validated. A valid reply is stored and added to the
+//! result as `structuredOutput`. An invalid one gets a single retry in the same
+//! session with the validation error as a hint, after which the task fails.
+
+use crate::db::{self, DbState};
+use crate::sidecar::{self, SidecarState};
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+
+/// Validation attempts before the task fails: the first reply plus one retry
+const MAX_ATTEMPTS: i64 = 2;
+
+/// Validation errors beyond this many are left out of messages
+const MAX_REPORTED_ERRORS: usize = 5;
+
+/// What to do with a structured task's final reply
+pub enum Outcome {
+    Valid(Value),
+    Retry(String),
+    Invalid(String),
+}
+
+/// Check that a schema compiles before a task is started with it
+pub fn check_schema(schema: &Value) -> Result<(), String> {
+    jsonschema::validator_for(schema)
+        .map(|_| ())
+        .map_err(|e| format!("Invalid output schema: {}", e))
+}
+
+/// Parse a reply that is JSON, possibly wrapped in a code fence or prose
+fn parse_reply(reply: &str) -> Result<Value, String> {
+    let reply = reply.trim();
+    if let Ok(value) = serde_json::from_str(reply) {
+        return Ok(value);
+    }
+
+    // Fenced block (```json ... ```)
+    if let Some((_, rest)) = reply.split_once("```") {
+        let body = rest.split_once('\n').map(|(_, b)| b).unwrap_or(rest);
+        if let Some((block, _)) = body.split_once("```") {
+            if let Ok(value) = serde_json::from_str(block.trim()) {
+                return Ok(value);
+            }
+        }
+    }
+
+    // Outermost object or array within surrounding text
+    let start = reply.find(['{', '[']);
+    let end = reply.rfind(['}', ']']);
+    if let (Some(start), Some(end)) = (start, end) {
+        if start < end {
+            if let Ok(value) = serde_json::from_str(&reply[start..=end]) {
+                return Ok(value);
+            }
+        }
+    }
+
+    Err("The reply did not contain valid JSON".to_string())
+}
+
+fn validate(schema: &Value, value: &Value) -> Result<(), String> {
+    let validator = jsonschema::validator_for(schema)
+        .map_err(|e| format!("Invalid output schema: {}", e))?;
+    let errors: Vec<String> = validator
+        .iter_errors(value)
+        .take(MAX_REPORTED_ERRORS)
+        .map(|e| {
+            let path = e.instance_path.to_string();
+            if path.is_empty() {
+                e.to_string()
+            } else {
+                format!("{}: {}", path, e)
+            }
+        })
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("; "))
+    }
+}
+
+fn retry_prompt(error: &str) -> String {
+    format!(
+        "Your final reply did not match the required JSON schema: {}\n\
+         Reply again with only the corrected JSON object, without any other text.",
+        error
+    )
+}
+
+/// Validate a task's final reply, or `None` if the task has no schema
+pub fn check(
+    conn: &rusqlite::Connection,
+    task_id: &str,
+    reply: Option<&str>,
+) -> Result<Option<Outcome>, String> {
+    let Some(record) = db::structured::get(conn, task_id)? else {
+        return Ok(None);
+    };
+
+    let result = reply
+        .ok_or_else(|| "The task finished without a reply".to_string())
+        .and_then(parse_reply)
+        .and_then(|value| validate(&record.schema, &value).map(|_| value));
+
+    match result {
+        Ok(value) => {
+            db::structured::record_attempt(conn, task_id, Ok(&value))?;
+            Ok(Some(Outcome::Valid(value)))
+        }
+        Err(error) => {
+            db::structured::record_attempt(conn, task_id, Err(&error))?;
+            if record.attempts + 1 < MAX_ATTEMPTS {
+                Ok(Some(Outcome::Retry(retry_prompt(&error))))
+            } else {
+                Ok(Some(Outcome::Invalid(error)))
+            }
+        }
+    }
+}
+
+/// Continue a task's session with a retry prompt
+pub async fn retry(
+    app: &AppHandle,
+    task_id: &str,
+    session_id: Option<String>,
+    prompt: String,
+) -> Result<(), String> {
+    let db_state = app.state::<DbState>();
+    let (options, model, workspace_id, schema) = {
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        let task = db::tasks::get_task_without_messages(&conn, task_id)
+            .ok_or_else(|| format!("Task not found: {}", task_id))?;
+        let schema = db::structured::get(&conn, task_id)?.map(|r| r.schema);
+        let options = crate::stored_run_options(&conn, &task)?;
+        let model = crate::stored_task_model(&conn, &task)?;
+        (options, model, task.workspace_id, schema)
+    };
+    let lease = match &session_id {
+        Some(session_id) => Some(crate::session_lock::acquire(&db_state, session_id, task_id)?),
+        None => None,
+    };
+    let sandbox = crate::start_sandbox(app, task_id, workspace_id.as_deref()).await?;
+    let remote = crate::remote::workspace_target(app, workspace_id.as_deref())?;
+    let sidecar_state = app.state::<SidecarState>();
+    let api_keys = sidecar_state.api_keys()?;
+    let model_parameters = crate::resolve_model_parameters(&db_state, model.model_id.as_deref());
+    let mut manager = sidecar_state.manager.lock().await;
+    if !manager.is_running() {
+        manager.spawn(app).await?;
+    }
+    manager
+        .send_command(sidecar::SidecarCommand::StartTask {
+            task_id: task_id.to_string(),
+            payload: sidecar::StartTaskPayload {
+                task_id: task_id.to_string(),
+                prompt,
+                session_id,
+                api_keys: Some(api_keys),
+                working_directory: options.working_directory,
+                model_id: model.model_id,
+                model_parameters,
+                plan_only: None,
+                agent: model.agent,
+                attachments: None,
+                web_search: options.web_search,
+                output_schema: schema,
+                env: options.env,
+                sandbox,
+                remote,
+                agent_settings: options.agent_settings,
+            },
+        })
+        .await?;
+    if let Some(lease) = lease {
+        lease.keep();
+    }
+    Ok(())
+}
@@ -0,0 +1,162 @@
+// src-tauri/src/summaries.rs
+//! Generated task summaries and titles
+//!
+//! When a task completes, its prompt and replies are sent to the sidecar's
+//! `generate_text` with a request for a one-line summary. The reply is stored
+//! with `update_task_summary` and emitted as `task:summary`, so the history
+//! list can show it instead of the truncated prompt. The `summary_model_id`
+//! setting picks a cheaper model; unset, the task's own model is used.
+//!
+//! A new task also asks for a short title from its prompt alone, sent right
+//! after the task starts so it usually arrives with the first response. It
+//! is stored with `update_task_title` and emitted as `task:title`.
+
+use crate::db::{self, DbState};
+use crate::message_buffer::MessageBufferState;
+use crate::sidecar::{self, SidecarState};
+use rusqlite::Connection;
+use tauri::{AppHandle, Manager};
+
+/// `purpose` of summary requests
+const SUMMARY_PURPOSE: &str = "summary";
+
+/// `purpose` of title requests
+const TITLE_PURPOSE: &str = "title";
+
+/// Most characters of the conversation sent to the model
+const MAX_TRANSCRIPT_CHARS: usize = 8000;
+
+/// Longest summary stored
+const MAX_SUMMARY_CHARS: usize = 120;
+
+/// Longest title stored
+const MAX_TITLE_CHARS: usize = 60;
+
+const SUMMARY_PROMPT: &str = "Summarize the task below in one line of at most 80 characters, \
+saying what was done (e.g. \"Fixed the race condition in the login flow\"). \
+Reply with the summary only, without quotes or punctuation at the end.";
+
+const TITLE_PROMPT: &str = "Write a title of at most 6 words for the request below, \
+naming the work asked for (e.g. \"Fix login race condition\"). \
+Reply with the title only, without quotes or punctuation at the end.";
+
+/// The task's prompt and text replies, keeping the end when it is too long
+fn transcript(task: &db::tasks::StoredTask) -> String {
+    let mut text = format!("[user] {}\n\n", task.prompt);
+    for message in &task.messages {
+        let is_text = matches!(message.msg_type.as_str(), "user" | "assistant");
+        if is_text && !message.content.is_empty() && message.content != task.prompt {
+            text.push_str(&format!("[{}] {}\n\n", message.msg_type, message.content));
+        }
+    }
+    let chars = text.chars().count();
+    if chars > MAX_TRANSCRIPT_CHARS {
+        text = text.chars().skip(chars - MAX_TRANSCRIPT_CHARS).collect();
+    }
+    text
+}
+
+/// Ask the sidecar for a summary of a completed task
+pub async fn request_summary(app: &AppHandle, task_id: &str) -> Result<(), String> {
+    let (prompt, model_id) = {
+        let db_state = app.state::<DbState>();
+        let mut conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        // Include replies the UI saved but that are still buffered
+        app.state::<MessageBufferState>().flush_task(&mut conn, task_id);
+        let task = db::tasks::get_task(&conn, task_id)
+            .ok_or_else(|| format!("Task not found: {}", task_id))?;
+        let model_id = db::settings::get_summary_model_id(&conn)
+            .or_else(|| crate::resolve_task_model_id(&conn, task.workspace_id.as_deref()));
+        let prompt = format!(
+            "{}\n\n<task>\n{}</task>",
+            SUMMARY_PROMPT,
+            transcript(&task)
+        );
+        (prompt, model_id)
+    };
+    let sidecar_state = app.state::<SidecarState>();
+    let api_keys = sidecar_state.api_keys()?;
+    let mut manager = sidecar_state.manager.lock().await;
+    if !manager.is_running() {
+        manager.spawn(app).await?;
+    }
+    manager
+        .send_command(sidecar::SidecarCommand::GenerateText {
+            task_id: task_id.to_string(),
+            payload: sidecar::GenerateTextPayload {
+                purpose: SUMMARY_PURPOSE.to_string(),
+                prompt,
+                model_id,
+                api_keys: Some(api_keys),
+            },
+        })
+        .await
+}
+
+/// Title request for a new task, run on the summary model or else `task_model_id`
+pub fn title_command(
+    conn: &Connection,
+    task_id: &str,
+    prompt: &str,
+    task_model_id: Option<String>,
+    api_keys: sidecar::ApiKeys,
+) -> sidecar::SidecarCommand {
+    let prompt: String = prompt.chars().take(MAX_TRANSCRIPT_CHARS).collect();
+    sidecar::SidecarCommand::GenerateText {
+        task_id: task_id.to_string(),
+        payload: sidecar::GenerateTextPayload {
+            purpose: TITLE_PURPOSE.to_string(),
+            prompt: format!("{}\n\n<request>\n{}\n</request>", TITLE_PROMPT, prompt),
+            model_id: db::settings::get_summary_model_id(conn).or(task_model_id),
+            api_keys: Some(api_keys),
+        },
+    }
+}
+
+/// First non-empty line, without wrapping quotes, capped at `max_chars`
+fn one_line(text: &str, max_chars: usize) -> Option<String> {
+    let line = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())?
+        .trim_matches(|c| c == '"' || c == '\'' || c == '`')
+        .trim();
+    if line.is_empty() {
+        return None;
+    }
+    Some(line.chars().take(max_chars).collect())
+}
+
+/// Store text generated for a task
+pub fn apply(app: &AppHandle, task_id: &str, purpose: &str, text: &str) {
+    let (max_chars, event) = match purpose {
+        SUMMARY_PURPOSE => (MAX_SUMMARY_CHARS, "task:summary"),
+        TITLE_PURPOSE => (MAX_TITLE_CHARS, "task:title"),
+        _ => {
+            tracing::warn!("[Summary] Unexpected generated text for {}", purpose);
+            return;
+        }
+    };
+    let Some(text) = one_line(text, max_chars) else {
+        return;
+    };
+
+    let db_state = app.state::<DbState>();
+    let stored = db_state
+        .conn
+        .lock()
+        .map_err(|e| e.to_string())
+        .and_then(|conn| match purpose {
+            TITLE_PURPOSE => db::tasks::update_task_title(&conn, task_id, &text),
+            _ => db::tasks::update_task_summary(&conn, task_id, &text),
+        });
+    if let Err(e) = stored {
+        tracing::warn!("[Summary] Failed to store {} of {}: {}", purpose, task_id, e);
+        return;
+    }
+    sidecar::forward_event(
+        app,
+        event,
+        serde_json::json!({ "taskId": task_id, purpose: text }),
+    );
+}
@@ -0,0 +1,288 @@
+// src-tauri/src/sync.rs
+//! Cloud folder sync of task history
+//!
+//! Mirrors every task as a JSON file into a user-chosen folder (typically one
+//! managed by iCloud Drive, Dropbox, or OneDrive) and merges the folder back
+//! into the local database on startup. Copies of the same task are merged by
+//! message ID, so conflicted copies created by the sync client are folded in
+//! rather than lost. Deleted tasks leave a tombstone so they are not revived
+//! by another device.
+//!
+//! Task IDs read from the folder name files, so one that isn't a plain ID is
+//! skipped. The database is only locked while merging, not for folder I/O.
+
+use crate::db::tasks::{self, StoredTask};
+use crate::db::DbState;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Directory created inside the chosen folder
+const SYNC_DIR: &str = "cowork-z";
+
+/// Outcome of a folder sync
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncReport {
+    /// Tasks found only in the folder
+    pub imported: usize,
+    /// Tasks written to the folder
+    pub exported: usize,
+    /// Tasks present on both sides that needed merging
+    pub merged: usize,
+    /// Local tasks removed because another device deleted them
+    pub deleted: usize,
+}
+
+impl SyncReport {
+    pub fn changed_local(&self) -> bool {
+        self.imported > 0 || self.merged > 0 || self.deleted > 0
+    }
+}
+
+fn tasks_dir(folder: &Path) -> PathBuf {
+    folder.join(SYNC_DIR).join("tasks")
+}
+
+fn deleted_dir(folder: &Path) -> PathBuf {
+    folder.join(SYNC_DIR).join("deleted")
+}
+
+/// Write through a temp file so the sync client never uploads a partial file
+fn write_atomic(path: &Path, contents: &str) -> Result<(), String> {
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, contents)
+        .map_err(|e| format!("Failed to write {:?}: {}", tmp, e))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("Failed to replace {:?}: {}", path, e))
+}
+
+/// Whether a task ID is safe to use as a file name in the folder
+fn valid_task_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn write_task(folder: &Path, task: &StoredTask) -> Result<(), String> {
+    if !valid_task_id(&task.id) {
+        return Err(format!("Invalid task ID: {:?}", task.id));
+    }
+    let json = serde_json::to_string_pretty(task)
+        .map_err(|e| format!("Failed to serialize task: {}", e))?;
+    write_atomic(&tasks_dir(folder).join(format!("{}.json", task.id)), &json)
+}
+
+/// Task IDs deleted on any device
+fn read_tombstones(folder: &Path) -> HashSet<String> {
+    std::fs::read_dir(deleted_dir(folder))
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .filter(|id| valid_task_id(id))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Every task file in the folder, grouped by task ID
+///
+/// Sync clients write conflicting edits next to the original under a different
+/// name, so the file name is ignored and the ID inside the file is used.
+fn read_remote_tasks(folder: &Path) -> Result<HashMap<String, Vec<(PathBuf, StoredTask)>>, String> {
+    let mut remote: HashMap<String, Vec<(PathBuf, StoredTask)>> = HashMap::new();
+    let entries = std::fs::read_dir(tasks_dir(folder))
+        .map_err(|e| format!("Failed to read sync folder: {}", e))?;
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|s| serde_json::from_str::<StoredTask>(&s).map_err(|e| e.to_string()));
+        match parsed {
+            Ok(task) if !valid_task_id(&task.id) => {
+                tracing::warn!("[Sync] Skipping {:?}: invalid task ID {:?}", path, task.id)
+            }
+            Ok(task) => remote.entry(task.id.clone()).or_default().push((path, task)),
+            Err(e) => tracing::warn!("[Sync] Skipping {:?}: {}", path, e),
+        }
+    }
+
+    Ok(remote)
+}
+
+/// Whether `b` is further along than `a`
+fn is_newer(a: &StoredTask, b: &StoredTask) -> bool {
+    let key = |t: &StoredTask| (t.completed_at.is_some(), t.completed_at.clone(), t.messages.len());
+    key(b) > key(a)
+}
+
+/// Merge two copies of a task
+///
+/// Messages are unioned by ID and ordered by timestamp; scalar fields come from
+/// the copy that is further along, falling back to the other copy when unset.
+pub fn merge_tasks(a: &StoredTask, b: &StoredTask) -> StoredTask {
+    let (primary, other) = if is_newer(a, b) { (b, a) } else { (a, b) };
+
+    let mut messages = primary.messages.clone();
+    let known: HashSet<&str> = primary.messages.iter().map(|m| m.id.as_str()).collect();
+    messages.extend(
+        other
+            .messages
+            .iter()
+            .filter(|m| !known.contains(m.id.as_str()))
+            .cloned(),
+    );
+    messages.sort_by(|x, y| x.timestamp.cmp(&y.timestamp));
+
+    StoredTask {
+        id: primary.id.clone(),
+        prompt: primary.prompt.clone(),
+        summary: primary.summary.clone().or_else(|| other.summary.clone()),
+        title: primary.title.clone().or_else(|| other.title.clone()),
+        status: primary.status.clone(),
+        messages,
+        session_id: primary.session_id.clone().or_else(|| other.session_id.clone()),
+        created_at: primary.created_at.clone(),
+        started_at: primary.started_at.clone().or_else(|| other.started_at.clone()),
+        completed_at: primary.completed_at.clone().or_else(|| other.completed_at.clone()),
+        updated_at: primary.updated_at.clone().max(other.updated_at.clone()),
+        workspace_id: primary.workspace_id.clone().or_else(|| other.workspace_id.clone()),
+        allow_web_search: primary.allow_web_search.or(other.allow_web_search),
+    }
+}
+
+fn same(a: &StoredTask, b: &StoredTask) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+/// What merging leaves to be done in the folder
+#[derive(Default)]
+struct FolderChanges {
+    write: Vec<StoredTask>,
+    remove: Vec<PathBuf>,
+}
+
+/// Merge the folder into the database and mirror the result back out
+pub fn sync_folder(db_state: &DbState, folder: &Path) -> Result<SyncReport, String> {
+    std::fs::create_dir_all(tasks_dir(folder))
+        .map_err(|e| format!("Failed to create sync folder: {}", e))?;
+    std::fs::create_dir_all(deleted_dir(folder))
+        .map_err(|e| format!("Failed to create sync folder: {}", e))?;
+
+    let tombstones = read_tombstones(folder);
+    let remote = read_remote_tasks(folder)?;
+    let (report, changes) = {
+        let conn = db_state.conn.lock().map_err(|e| e.to_string())?;
+        merge_folder(&conn, folder, &tombstones, remote)?
+    };
+
+    // Merged copies are written before the conflicted copies they replace are removed
+    for task in &changes.write {
+        write_task(folder, task)?;
+    }
+    for path in &changes.remove {
+        let _ = std::fs::remove_file(path);
+    }
+
+    tracing::info!(
+        "[Sync] Synced {:?}: {} imported, {} exported, {} merged, {} deleted",
+        folder,
+        report.imported,
+        report.exported,
+        report.merged,
+        report.deleted
+    );
+    Ok(report)
+}
+
+/// Merge the folder's tasks into the database, returning what to change in the folder
+fn merge_folder(
+    conn: &Connection,
+    folder: &Path,
+    tombstones: &HashSet<String>,
+    mut remote: HashMap<String, Vec<(PathBuf, StoredTask)>>,
+) -> Result<(SyncReport, FolderChanges), String> {
+    let mut report = SyncReport::default();
+    let mut changes = FolderChanges::default();
+    let mut local: HashMap<String, StoredTask> = tasks::get_tasks(conn)
+        .into_iter()
+        .map(|t| (t.id.clone(), t))
+        .collect();
+
+    // Apply deletions from other devices first
+    for id in tombstones {
+        if local.remove(id).is_some() {
+            tasks::delete_task(conn, id)?;
+            report.deleted += 1;
+        }
+        changes
+            .remove
+            .extend(remote.remove(id).unwrap_or_default().into_iter().map(|(path, _)| path));
+    }
+
+    for (id, copies) in remote {
+        let canonical = tasks_dir(folder).join(format!("{}.json", id));
+        let mut remote_merged = copies[0].1.clone();
+        for (_, copy) in &copies[1..] {
+            remote_merged = merge_tasks(&remote_merged, copy);
+        }
+
+        let merged = match local.remove(&id) {
+            Some(local_task) => {
+                let merged = merge_tasks(&local_task, &remote_merged);
+                if !same(&merged, &local_task) {
+                    tasks::save_stored_task(conn, &merged)?;
+                    report.merged += 1;
+                }
+                merged
+            }
+            // Archived here; the copy in the folder stays as it is
+            None if tasks::is_archived(conn, &id) => continue,
+            None => {
+                tasks::save_stored_task(conn, &remote_merged)?;
+                report.imported += 1;
+                remote_merged.clone()
+            }
+        };
+
+        let has_conflict_copies = copies.iter().any(|(path, _)| *path != canonical);
+        if has_conflict_copies || !same(&merged, &remote_merged) {
+            changes.remove.extend(
+                copies
+                    .into_iter()
+                    .map(|(path, _)| path)
+                    .filter(|path| *path != canonical),
+            );
+            changes.write.push(merged);
+        }
+    }
+
+    // Whatever is left exists only on this device
+    report.exported = local.len();
+    changes.write.extend(local.into_values());
+
+    Ok((report, changes))
+}
+
+/// Write a single task to the folder
+pub fn mirror_task(folder: &Path, task: &StoredTask) -> Result<(), String> {
+    std::fs::create_dir_all(tasks_dir(folder))
+        .map_err(|e| format!("Failed to create sync folder: {}", e))?;
+    write_task(folder, task)
+}
+
+/// Remove a task from the folder and leave a tombstone for other devices
+pub fn record_deletion(folder: &Path, task_id: &str) -> Result<(), String> {
+    if !valid_task_id(task_id) {
+        return Err(format!("Invalid task ID: {:?}", task_id));
+    }
+    std::fs::create_dir_all(deleted_dir(folder))
+        .map_err(|e| format!("Failed to create sync folder: {}", e))?;
+    std::fs::write(deleted_dir(folder).join(task_id), chrono::Utc::now().to_rfc3339())
+        .map_err(|e| format!("Failed to record deletion: {}", e))?;
+    let _ = std::fs::remove_file(tasks_dir(folder).join(format!("{}.json", task_id)));
+    Ok(())
+}
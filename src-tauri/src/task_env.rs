@@ -0,0 +1,106 @@
+// src-tauri/src/task_env.rs
+//! Environment variables for agent runs
+//!
+//! Workspaces and tasks can define variables (`DATABASE_URL`, toolchain
+//! paths, ...) that are set for OpenCode, so the commands the agent runs see
+//! them. A task's variables override its workspace's. Non-secret values are
+//! stored in the database and secret ones in the keychain.
+
+use crate::db::env_vars::{self, EnvVar};
+use crate::secure_storage;
+use rusqlite::Connection;
+use std::collections::HashMap;
+
+fn validate_name(name: &str) -> Result<(), String> {
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid environment variable name '{}': use letters, digits and '_'",
+            name
+        ))
+    }
+}
+
+/// Replace the variables of a workspace or task
+///
+/// A secret without a value keeps its stored value. Secrets that are removed
+/// or no longer marked secret are deleted from the keychain.
+pub fn set(conn: &mut Connection, owner_id: &str, vars: &[EnvVar]) -> Result<(), String> {
+    let existing = env_vars::list(conn, owner_id)?;
+    let mut stored: Vec<EnvVar> = Vec::with_capacity(vars.len());
+    for var in vars {
+        let name = var.name.trim();
+        validate_name(name)?;
+        if stored.iter().any(|v| v.name == name) {
+            return Err(format!("Duplicate environment variable: {}", name));
+        }
+        if var.secret {
+            match var.value.as_deref() {
+                Some(value) => secure_storage::store_env_secret(owner_id, name, value)?,
+                None if existing.iter().any(|v| v.name == name && v.secret) => {}
+                None => return Err(format!("No value for secret {}", name)),
+            }
+        }
+        stored.push(EnvVar {
+            name: name.to_string(),
+            value: (!var.secret).then(|| var.value.clone().unwrap_or_default()),
+            secret: var.secret,
+        });
+    }
+
+    env_vars::replace(conn, owner_id, &stored)?;
+
+    for old in existing.iter().filter(|v| v.secret) {
+        if !stored.iter().any(|v| v.name == old.name && v.secret) {
+            secure_storage::delete_env_secret(owner_id, &old.name)?;
+        }
+    }
+    Ok(())
+}
+
+/// Variables with their values, secrets included
+fn values(conn: &Connection, owner_id: &str) -> Result<Vec<EnvVar>, String> {
+    let mut vars = env_vars::list(conn, owner_id)?;
+    for var in vars.iter_mut().filter(|v| v.secret) {
+        var.value = secure_storage::get_env_secret(owner_id, &var.name)?;
+        if var.value.is_none() {
+            tracing::warn!("[Env] Secret {} of {} is missing from the keychain", var.name, owner_id);
+        }
+    }
+    Ok(vars)
+}
+
+/// Environment for a run: the workspace's variables overridden by the task's
+pub fn resolve(
+    conn: &Connection,
+    workspace_id: Option<&str>,
+    task_id: Option<&str>,
+) -> Result<Option<HashMap<String, String>>, String> {
+    let mut env = HashMap::new();
+    for owner_id in [workspace_id, task_id].into_iter().flatten() {
+        for var in values(conn, owner_id)? {
+            if let Some(value) = var.value {
+                env.insert(var.name, value);
+            }
+        }
+    }
+    Ok((!env.is_empty()).then_some(env))
+}
+
+/// Copy a task's variables to another task, e.g. a fork
+pub fn copy(conn: &mut Connection, from_task_id: &str, to_task_id: &str) -> Result<(), String> {
+    let vars: Vec<EnvVar> = values(conn, from_task_id)?
+        .into_iter()
+        .filter(|v| v.value.is_some())
+        .collect();
+    if vars.is_empty() {
+        return Ok(());
+    }
+    set(conn, to_task_id, &vars)
+}
@@ -0,0 +1,96 @@
+// src-tauri/src/task_progress.rs
+//! Task progress and the dock/taskbar indicator
+//!
+//! Each `task_progress` event from the sidecar is stored as the task's latest
+//! progress, and the main window's progress bar (dock icon on macOS, taskbar
+//! button on Windows) follows the running tasks: the average share of steps
+//! done when any task knows its total, indeterminate otherwise, and hidden
+//! once no task is running. A task leaves it when it completes, fails, or is
+//! cancelled, and all do when the sidecar terminates.
+
+use crate::db::progress::TaskProgress;
+use crate::db::{self, DbState};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use tauri::window::{ProgressBarState, ProgressBarStatus};
+use tauri::{AppHandle, Manager};
+
+/// Percent done of each running task, `None` while its total is unknown
+static RUNNING: LazyLock<Mutex<HashMap<String, Option<u64>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Point the main window's progress bar at the running tasks
+fn refresh_indicator(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let state = {
+        let Ok(running) = RUNNING.lock() else {
+            return;
+        };
+        let known: Vec<u64> = running.values().filter_map(|p| *p).collect();
+        if running.is_empty() {
+            ProgressBarState {
+                status: Some(ProgressBarStatus::None),
+                progress: None,
+            }
+        } else if known.is_empty() {
+            ProgressBarState {
+                status: Some(ProgressBarStatus::Indeterminate),
+                progress: None,
+            }
+        } else {
+            ProgressBarState {
+                status: Some(ProgressBarStatus::Normal),
+                progress: Some(known.iter().sum::<u64>() / known.len() as u64),
+            }
+        }
+    };
+    if let Err(e) = window.set_progress_bar(state) {
+        tracing::debug!("[Progress] Failed to set progress bar: {}", e);
+    }
+}
+
+/// Store a `task_progress` payload and update the indicator
+pub fn record(app: &AppHandle, task_id: &str, progress: serde_json::Value) {
+    let progress: TaskProgress = match serde_json::from_value(progress) {
+        Ok(progress) => progress,
+        Err(e) => {
+            tracing::warn!("[Progress] Invalid progress for {}: {}", task_id, e);
+            return;
+        }
+    };
+    if let Some(db_state) = app.try_state::<DbState>() {
+        let stored = db_state
+            .conn
+            .lock()
+            .map_err(|e| e.to_string())
+            .and_then(|conn| db::progress::set_progress(&conn, task_id, &progress));
+        if let Err(e) = stored {
+            tracing::warn!("[Progress] {}", e);
+        }
+    }
+    if let Ok(mut running) = RUNNING.lock() {
+        running.insert(task_id.to_string(), progress.percent());
+    }
+    refresh_indicator(app);
+}
+
+/// Take a finished, failed or cancelled task off the indicator
+pub fn finish(app: &AppHandle, task_id: &str) {
+    let removed = RUNNING
+        .lock()
+        .map(|mut running| running.remove(task_id).is_some())
+        .unwrap_or(false);
+    if removed {
+        refresh_indicator(app);
+    }
+}
+
+/// Clear the indicator when the sidecar goes away with its tasks
+pub fn finish_all(app: &AppHandle) {
+    if let Ok(mut running) = RUNNING.lock() {
+        running.clear();
+    }
+    refresh_indicator(app);
+}
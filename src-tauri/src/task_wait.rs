@@ -0,0 +1,169 @@
+// src-tauri/src/task_wait.rs
+//! Waiting for a task to finish
+//!
+//! `await_task` and `GET /api/tasks/:id/await` block until a task ends, for
+//! scripts that would otherwise subscribe to events. The wait follows the
+//! forwarded `task:complete` and `task:error` events, and checks the stored
+//! status too, for tasks that already ended or were cancelled.
+
+use crate::api_server::event_stream;
+use crate::db::{self, DbState};
+use crate::sidecar::SidecarState;
+use crate::TaskResult;
+use futures_util::StreamExt;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Wait used when none is given
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Longest wait allowed; clients poll again after it
+pub const MAX_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// How often the stored status is checked while waiting
+const STATUS_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Statuses a task doesn't leave without being started again
+pub const FINISHED_STATUSES: &[&str] = &["completed", "failed", "cancelled", "interrupted"];
+
+/// How a wait for a task ended
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskOutcome {
+    pub task_id: String,
+    /// Whether the task ended; false when the wait timed out first
+    pub finished: bool,
+    /// The task's status when the wait ended
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<TaskResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+/// Result of a run from a task's stored result, or its status for tasks from before results
+fn stored_result(
+    task: &db::tasks::StoredTask,
+    stored: Option<db::tasks::StoredResult>,
+    structured: Option<serde_json::Value>,
+) -> TaskResult {
+    if let Some(stored) = stored {
+        return TaskResult {
+            status: stored.status,
+            error: stored.error,
+            session_id: stored.session_id,
+            structured_output: structured,
+        };
+    }
+    let status = match task.status.as_str() {
+        "completed" => "success",
+        "interrupted" | "cancelled" => "interrupted",
+        _ => "error",
+    };
+    TaskResult {
+        status: status.to_string(),
+        error: None,
+        session_id: task.session_id.clone(),
+        structured_output: structured,
+    }
+}
+
+/// Result carried by a `task:complete` or `task:error` event
+fn event_result(event: &str, payload: &serde_json::Value) -> Option<TaskResult> {
+    match event {
+        "task:complete" => TaskResult::from_complete(payload),
+        "task:error" => Some(TaskResult::from_error(payload)),
+        _ => None,
+    }
+}
+
+/// Outcome of a task whose stored status says it ended
+fn stored_outcome(db_state: &DbState, task: db::tasks::StoredTask) -> Result<TaskOutcome, String> {
+    let (stored, structured) = {
+        let conn = db_state.reader.lock().map_err(|e| e.to_string())?;
+        (
+            db::tasks::get_task_result(&conn, &task.id)?,
+            db::structured::get(&conn, &task.id)?.and_then(|r| r.output),
+        )
+    };
+    Ok(TaskOutcome {
+        result: Some(stored_result(&task, stored, structured)),
+        task_id: task.id,
+        finished: true,
+        status: task.status,
+        summary: task.summary,
+    })
+}
+
+/// Wait up to `timeout` (capped at `MAX_TIMEOUT`) for a task to end
+///
+/// Returns `None` when the task doesn't exist.
+pub async fn wait(
+    task_id: &str,
+    timeout: Duration,
+    db_state: &DbState,
+    sidecar_state: &SidecarState,
+) -> Result<Option<TaskOutcome>, String> {
+    let load = || -> Result<db::tasks::StoredTask, String> {
+        let conn = db_state.reader.lock().map_err(|e| e.to_string())?;
+        db::tasks::get_task_without_messages(&conn, task_id)
+            .ok_or_else(|| format!("Task not found: {}", task_id))
+    };
+
+    // Subscribe before the first status check so an ending in between isn't missed
+    let mut events = Box::pin(event_stream(sidecar_state.subscribe(), Some(task_id.to_string())));
+    let task = {
+        let conn = db_state.reader.lock().map_err(|e| e.to_string())?;
+        db::tasks::get_task_without_messages(&conn, task_id)
+    };
+    let Some(task) = task else {
+        return Ok(None);
+    };
+    if FINISHED_STATUSES.contains(&task.status.as_str()) {
+        return stored_outcome(db_state, task).map(Some);
+    }
+
+    let deadline = tokio::time::sleep(timeout.min(MAX_TIMEOUT));
+    tokio::pin!(deadline);
+    // Cancelling or aborting a task only changes its stored status
+    let start = tokio::time::Instant::now() + STATUS_CHECK_INTERVAL;
+    let mut status_check = tokio::time::interval_at(start, STATUS_CHECK_INTERVAL);
+
+    loop {
+        tokio::select! {
+            event = events.next() => {
+                let Some(event) = event else { break };
+                let payload = event.payload.get("payload").unwrap_or(&serde_json::Value::Null);
+                if let Some(result) = event_result(&event.event, payload) {
+                    let task = load()?;
+                    return Ok(Some(TaskOutcome {
+                        task_id: task.id,
+                        finished: true,
+                        status: result.task_status().to_string(),
+                        result: Some(result),
+                        summary: task.summary,
+                    }));
+                }
+            }
+            _ = status_check.tick() => {
+                let task = load()?;
+                if FINISHED_STATUSES.contains(&task.status.as_str()) {
+                    return stored_outcome(db_state, task).map(Some);
+                }
+            }
+            _ = &mut deadline => break,
+        }
+    }
+
+    let task = load()?;
+    if FINISHED_STATUSES.contains(&task.status.as_str()) {
+        return stored_outcome(db_state, task).map(Some);
+    }
+    Ok(Some(TaskOutcome {
+        task_id: task.id,
+        finished: false,
+        status: task.status,
+        result: None,
+        summary: task.summary,
+    }))
+}
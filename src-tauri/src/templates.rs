@@ -0,0 +1,268 @@
+// src-tauri/src/templates.rs
+//! Workspace templates
+//!
+//! A template bundles what a workspace starts with: the default model and
+//! agent and the agent settings (instructions, permission rules, pinned files,
+//! and MCP servers). A few templates ship with the app; others are exported
+//! from a workspace to a JSON file and imported by teammates.
+//!
+//! MCP server environment values are left out of exported files since they
+//! often hold API keys; the names stay so the importer knows what to fill in.
+//! An imported file's MCP servers run commands on the importer's machine, so
+//! `preview` shows them first and `import` only stores the servers approved.
+
+use crate::db::templates::{self, WorkspaceTemplate};
+use crate::db::workspaces::{
+    self, AgentSettings, McpServer, PermissionRule, Workspace, WorkspaceDefaults,
+};
+use rusqlite::{Connection, TransactionBehavior};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Version of the exported file format
+const FILE_VERSION: u32 = 1;
+
+/// A template as exported for sharing
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TemplateFile {
+    version: u32,
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    defaults: WorkspaceDefaults,
+    #[serde(default)]
+    settings: AgentSettings,
+}
+
+fn rule(tool: &str, pattern: Option<&str>, action: &str) -> PermissionRule {
+    PermissionRule {
+        tool: tool.to_string(),
+        pattern: pattern.map(str::to_string),
+        action: action.to_string(),
+    }
+}
+
+fn builtin(
+    id: &str,
+    name: &str,
+    description: &str,
+    instructions: &str,
+    permission_rules: Vec<PermissionRule>,
+    pinned_files: &[&str],
+    mcp_servers: BTreeMap<String, McpServer>,
+) -> WorkspaceTemplate {
+    WorkspaceTemplate {
+        id: format!("builtin:{}", id),
+        name: name.to_string(),
+        description: Some(description.to_string()),
+        builtin: true,
+        // The model is left to the user's connected providers
+        defaults: WorkspaceDefaults::default(),
+        settings: AgentSettings {
+            instructions: Some(instructions.to_string()),
+            permission_rules,
+            pinned_files: pinned_files.iter().map(|f| f.to_string()).collect(),
+            mcp_servers,
+        },
+    }
+}
+
+/// Templates shipped with the app
+fn builtins() -> Vec<WorkspaceTemplate> {
+    let playwright = McpServer {
+        kind: "local".to_string(),
+        command: vec!["npx".into(), "-y".into(), "@playwright/mcp@latest".into()],
+        url: None,
+        environment: BTreeMap::new(),
+        enabled: true,
+    };
+    vec![
+        builtin(
+            "code-review",
+            "Code review",
+            "Reviews changes without editing files",
+            "Review the changes you are asked about for bugs, missing tests, and unclear \
+             code. Report findings with file and line references, most severe first. \
+             Do not modify files.",
+            vec![
+                rule("edit", None, "deny"),
+                rule("bash", None, "deny"),
+                rule("bash", Some("git diff*"), "allow"),
+                rule("bash", Some("git log*"), "allow"),
+                rule("bash", Some("git show*"), "allow"),
+                rule("bash", Some("git status*"), "allow"),
+            ],
+            &["CONTRIBUTING.md"],
+            BTreeMap::new(),
+        ),
+        builtin(
+            "web-app",
+            "Web app",
+            "Builds and checks web front ends in a browser",
+            "Follow the conventions of the existing code and package scripts. After \
+             changing the UI, check it in the browser before reporting back.",
+            vec![rule("bash", Some("git push*"), "deny")],
+            &["README.md", "package.json"],
+            BTreeMap::from([("playwright".to_string(), playwright)]),
+        ),
+        builtin(
+            "docs",
+            "Documentation",
+            "Writes and edits project documentation",
+            "Write plain, concise documentation in the style of the existing docs. Check \
+             that commands and code samples match the code.",
+            vec![rule("bash", Some("git push*"), "deny")],
+            &["README.md"],
+            BTreeMap::new(),
+        ),
+        builtin(
+            "data-analysis",
+            "Data analysis",
+            "Explores local data files without going online",
+            "Analyze the data files in this folder with scripts you write here. State the \
+             assumptions behind each result and save charts and tables as files.",
+            vec![rule("webfetch", None, "deny")],
+            &[],
+            BTreeMap::new(),
+        ),
+    ]
+}
+
+/// Built-in templates followed by the imported ones
+pub fn list(conn: &Connection) -> Result<Vec<WorkspaceTemplate>, String> {
+    let mut all = builtins();
+    all.extend(templates::list_templates(conn)?);
+    Ok(all)
+}
+
+fn find(conn: &Connection, template_id: &str) -> Result<WorkspaceTemplate, String> {
+    if let Some(template) = builtins().into_iter().find(|t| t.id == template_id) {
+        return Ok(template);
+    }
+    templates::get_template(conn, template_id)?
+        .ok_or_else(|| format!("Template not found: {}", template_id))
+}
+
+/// Create a workspace for a local directory with a template's settings
+pub fn create_workspace(
+    conn: &mut Connection,
+    template_id: &str,
+    path: &str,
+    name: Option<&str>,
+) -> Result<Workspace, String> {
+    let template = find(conn, template_id)?;
+    let tx = conn
+        .transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    let workspace = workspaces::create_local_workspace(&tx, path, name)?;
+    workspaces::set_defaults(&tx, &workspace.id, &template.defaults)?;
+    workspaces::set_agent_settings(&tx, &workspace.id, &template.settings)?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit workspace: {}", e))?;
+
+    tracing::info!("[Templates] Created {} from {}", workspace.id, template.id);
+    Ok(workspace)
+}
+
+/// Write a workspace's settings to a template file
+pub fn export(
+    conn: &Connection,
+    workspace_id: &str,
+    name: &str,
+    description: Option<&str>,
+    path: &Path,
+) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Template name cannot be empty".to_string());
+    }
+    let mut settings = workspaces::get_agent_settings(conn, workspace_id)?;
+    for server in settings.mcp_servers.values_mut() {
+        server.environment.values_mut().for_each(String::clear);
+    }
+    let file = TemplateFile {
+        version: FILE_VERSION,
+        name: name.to_string(),
+        description: description
+            .map(str::trim)
+            .filter(|d| !d.is_empty())
+            .map(str::to_string),
+        defaults: workspaces::get_defaults(conn, workspace_id)?,
+        settings,
+    };
+    let json = serde_json::to_string_pretty(&file)
+        .map_err(|e| format!("Failed to serialize template: {}", e))?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write template: {}", e))?;
+    tracing::info!("[Templates] Exported {} to {}", workspace_id, path.display());
+    Ok(())
+}
+
+/// What importing a template file would add, shown for approval
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplatePreview {
+    pub name: String,
+    pub description: Option<String>,
+    pub defaults: WorkspaceDefaults,
+    /// Includes the MCP servers and the commands they run
+    pub settings: AgentSettings,
+}
+
+fn read_file(path: &Path) -> Result<TemplateFile, String> {
+    let json =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read template: {}", e))?;
+    let file: TemplateFile =
+        serde_json::from_str(&json).map_err(|e| format!("Not a workspace template: {}", e))?;
+    if file.version > FILE_VERSION {
+        return Err(format!(
+            "Template file version {} is newer than this app supports",
+            file.version
+        ));
+    }
+    Ok(file)
+}
+
+/// Read an exported file without storing it
+pub fn preview(path: &Path) -> Result<TemplatePreview, String> {
+    let file = read_file(path)?;
+    Ok(TemplatePreview {
+        name: file.name,
+        description: file.description,
+        defaults: file.defaults,
+        settings: file.settings,
+    })
+}
+
+/// Store the template in an exported file
+///
+/// `approved_mcp_servers` are the servers the user accepted from `preview`;
+/// the file's servers must match them, so a file changed since can't slip
+/// in another command.
+pub fn import(
+    conn: &Connection,
+    path: &Path,
+    approved_mcp_servers: &BTreeMap<String, McpServer>,
+) -> Result<WorkspaceTemplate, String> {
+    let file = read_file(path)?;
+    if &file.settings.mcp_servers != approved_mcp_servers {
+        return Err("The template's MCP servers haven't been approved".to_string());
+    }
+    templates::save_template(
+        conn,
+        &file.name,
+        file.description.as_deref(),
+        &file.defaults,
+        &file.settings,
+    )
+}
+
+/// Delete an imported template
+pub fn delete(conn: &Connection, template_id: &str) -> Result<(), String> {
+    if builtins().iter().any(|t| t.id == template_id) {
+        return Err("Built-in templates can't be deleted".to_string());
+    }
+    templates::delete_template(conn, template_id)
+}
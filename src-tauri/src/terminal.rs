@@ -0,0 +1,175 @@
+// src-tauri/src/terminal.rs
+//! Embedded terminal sessions
+//!
+//! Opens the user's default shell in a pseudo-terminal rooted at a task's
+//! working directory, so the repo the agent is working on can be inspected
+//! without leaving the app. Output is streamed to the frontend as
+//! `terminal:output` events and `terminal:exit` is emitted when the shell ends.
+
+use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+struct TerminalSession {
+    master: Box<dyn MasterPty + Send>,
+    writer: Box<dyn Write + Send>,
+    child: Box<dyn Child + Send + Sync>,
+}
+
+/// Open terminal sessions keyed by terminal ID
+#[derive(Default)]
+pub struct TerminalState {
+    sessions: Mutex<HashMap<String, TerminalSession>>,
+}
+
+/// Wait for an ended shell so it doesn't linger as a zombie
+fn reap(terminal_id: &str, mut child: Box<dyn Child + Send + Sync>) {
+    if let Err(e) = child.wait() {
+        tracing::warn!("[Terminal] Failed to reap {}: {}", terminal_id, e);
+    }
+}
+
+fn pty_size(rows: u16, cols: u16) -> PtySize {
+    PtySize {
+        rows,
+        cols,
+        pixel_width: 0,
+        pixel_height: 0,
+    }
+}
+
+/// Stream PTY output until the shell exits
+///
+/// Reads can split a multi-byte character, so incomplete UTF-8 at the end of a
+/// chunk is carried over to the next one.
+fn stream_output(app: AppHandle, terminal_id: String, task_id: String, mut reader: Box<dyn Read + Send>) {
+    let mut buf = [0u8; 8192];
+    let mut pending: Vec<u8> = Vec::new();
+
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                pending.extend_from_slice(&buf[..n]);
+                let valid = match std::str::from_utf8(&pending) {
+                    Ok(_) => pending.len(),
+                    Err(e) if e.error_len().is_none() => e.valid_up_to(),
+                    Err(_) => pending.len(),
+                };
+                let data = String::from_utf8_lossy(&pending[..valid]).to_string();
+                pending.drain(..valid);
+                let _ = app.emit(
+                    "terminal:output",
+                    serde_json::json!({ "terminalId": terminal_id, "taskId": task_id, "data": data }),
+                );
+            }
+        }
+    }
+
+    // Closed terminals are reaped by `close`; a shell that exited on its own is reaped here
+    let session = app
+        .try_state::<TerminalState>()
+        .and_then(|state| state.sessions.lock().ok()?.remove(&terminal_id));
+    if let Some(session) = session {
+        reap(&terminal_id, session.child);
+    }
+    let _ = app.emit(
+        "terminal:exit",
+        serde_json::json!({ "terminalId": terminal_id, "taskId": task_id }),
+    );
+}
+
+impl TerminalState {
+    /// Start a shell in `cwd` and return the terminal ID
+    pub fn open(
+        &self,
+        app: &AppHandle,
+        task_id: &str,
+        cwd: &str,
+        rows: u16,
+        cols: u16,
+    ) -> Result<String, String> {
+        let pair = native_pty_system()
+            .openpty(pty_size(rows, cols))
+            .map_err(|e| format!("Failed to open terminal: {}", e))?;
+
+        let mut command = CommandBuilder::new_default_prog();
+        command.cwd(cwd);
+        let child = pair
+            .slave
+            .spawn_command(command)
+            .map_err(|e| format!("Failed to start shell: {}", e))?;
+        drop(pair.slave);
+
+        let reader = pair
+            .master
+            .try_clone_reader()
+            .map_err(|e| format!("Failed to read terminal: {}", e))?;
+        let writer = pair
+            .master
+            .take_writer()
+            .map_err(|e| format!("Failed to write terminal: {}", e))?;
+
+        let terminal_id = format!("term_{}", uuid::Uuid::new_v4());
+        self.sessions.lock().map_err(|e| e.to_string())?.insert(
+            terminal_id.clone(),
+            TerminalSession {
+                master: pair.master,
+                writer,
+                child,
+            },
+        );
+
+        let (app, id, task_id) = (app.clone(), terminal_id.clone(), task_id.to_string());
+        std::thread::spawn(move || stream_output(app, id, task_id, reader));
+
+        tracing::info!("[Terminal] Opened {} in {}", terminal_id, cwd);
+        Ok(terminal_id)
+    }
+
+    /// Send input (keystrokes or pasted text) to a terminal
+    pub fn write(&self, terminal_id: &str, data: &str) -> Result<(), String> {
+        let mut sessions = self.sessions.lock().map_err(|e| e.to_string())?;
+        let session = sessions
+            .get_mut(terminal_id)
+            .ok_or_else(|| format!("Terminal not found: {}", terminal_id))?;
+        session
+            .writer
+            .write_all(data.as_bytes())
+            .and_then(|_| session.writer.flush())
+            .map_err(|e| format!("Failed to write to terminal: {}", e))
+    }
+
+    pub fn resize(&self, terminal_id: &str, rows: u16, cols: u16) -> Result<(), String> {
+        let sessions = self.sessions.lock().map_err(|e| e.to_string())?;
+        let session = sessions
+            .get(terminal_id)
+            .ok_or_else(|| format!("Terminal not found: {}", terminal_id))?;
+        session
+            .master
+            .resize(pty_size(rows, cols))
+            .map_err(|e| format!("Failed to resize terminal: {}", e))
+    }
+
+    /// Kill the shell; `terminal:exit` follows once its output is drained
+    ///
+    /// The shell is waited for on its own thread, so closing doesn't block.
+    pub fn close(&self, terminal_id: &str) -> Result<(), String> {
+        let session = self
+            .sessions
+            .lock()
+            .map_err(|e| e.to_string())?
+            .remove(terminal_id);
+        if let Some(mut session) = session {
+            // A shell that already exited fails to be killed, and still needs reaping
+            if let Err(e) = session.child.kill() {
+                tracing::warn!("[Terminal] Failed to kill {}: {}", terminal_id, e);
+            }
+            let terminal_id = terminal_id.to_string();
+            std::thread::spawn(move || reap(&terminal_id, session.child));
+        }
+        Ok(())
+    }
+}
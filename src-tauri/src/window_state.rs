@@ -0,0 +1,164 @@
+// src-tauri/src/window_state.rs
+//! Main window size and position across runs
+//!
+//! The window's bounds are saved to `app_settings` when it closes and applied
+//! at startup. Saved bounds are checked against the connected monitors first:
+//! the size is capped to the monitor the window lands on, and a position that
+//! would leave the window mostly off-screen (e.g. on a display that has since
+//! been unplugged) is dropped, so the window opens centered instead.
+
+use crate::db::settings::WindowState;
+use crate::db::{self, DbState};
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize, WebviewWindow, WindowEvent};
+
+/// Label of the window whose state is kept
+const MAIN_WINDOW: &str = "main";
+
+/// Smallest size restored, matching the window's configured minimum
+const MIN_WIDTH: u32 = 800;
+const MIN_HEIGHT: u32 = 600;
+
+/// Part of the window that must be on a monitor for its position to be kept
+const MIN_VISIBLE: i64 = 100;
+
+/// Monitor bounds as (x, y, width, height)
+type Bounds = (i64, i64, i64, i64);
+
+/// Visible width and height of a window on a monitor
+fn overlap(window: Bounds, monitor: Bounds) -> (i64, i64) {
+    let width = (window.0 + window.2).min(monitor.0 + monitor.2) - window.0.max(monitor.0);
+    let height = (window.1 + window.3).min(monitor.1 + monitor.3) - window.1.max(monitor.1);
+    (width.max(0), height.max(0))
+}
+
+/// Saved bounds fitted to the connected monitors; the position is `None` when off-screen
+fn fit(
+    state: &WindowState,
+    monitors: &[Bounds],
+) -> (PhysicalSize<u32>, Option<PhysicalPosition<i32>>) {
+    let window = (
+        state.x as i64,
+        state.y as i64,
+        state.width as i64,
+        state.height as i64,
+    );
+    let monitor = monitors
+        .iter()
+        .copied()
+        .map(|monitor| (monitor, overlap(window, monitor)))
+        .filter(|(_, (w, h))| *w >= MIN_VISIBLE && *h >= MIN_VISIBLE)
+        .max_by_key(|(_, (w, h))| w * h)
+        .map(|(monitor, _)| monitor);
+
+    let (max_width, max_height) = match monitor.or_else(|| monitors.first().copied()) {
+        Some((_, _, w, h)) => (w as u32, h as u32),
+        None => (state.width, state.height),
+    };
+    let size = PhysicalSize {
+        width: state.width.min(max_width).max(MIN_WIDTH),
+        height: state.height.min(max_height).max(MIN_HEIGHT),
+    };
+    let position = monitor.map(|_| PhysicalPosition {
+        x: state.x,
+        y: state.y,
+    });
+    (size, position)
+}
+
+fn monitor_bounds(window: &WebviewWindow) -> Vec<Bounds> {
+    window
+        .available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .map(|m| {
+            let (position, size) = (m.position(), m.size());
+            (
+                position.x as i64,
+                position.y as i64,
+                size.width as i64,
+                size.height as i64,
+            )
+        })
+        .collect()
+}
+
+/// Apply the saved bounds to the main window
+pub fn restore(app: &AppHandle) {
+    let Some(window) = app.get_webview_window(MAIN_WINDOW) else {
+        return;
+    };
+    let saved = app
+        .state::<DbState>()
+        .conn
+        .lock()
+        .ok()
+        .and_then(|conn| db::settings::get_window_state(&conn));
+    let Some(state) = saved else {
+        return;
+    };
+
+    let (size, position) = fit(&state, &monitor_bounds(&window));
+    if let Err(e) = window.set_size(size) {
+        tracing::warn!("[Window] Failed to restore size: {}", e);
+    }
+    match position {
+        Some(position) => {
+            if let Err(e) = window.set_position(position) {
+                tracing::warn!("[Window] Failed to restore position: {}", e);
+            }
+        }
+        None => tracing::info!("[Window] Saved position is off-screen, leaving it centered"),
+    }
+    if state.maximized {
+        if let Err(e) = window.maximize() {
+            tracing::warn!("[Window] Failed to maximize: {}", e);
+        }
+    }
+}
+
+/// Save the main window's bounds; a maximized window keeps its earlier bounds
+pub fn save(app: &AppHandle) {
+    let Some(window) = app.get_webview_window(MAIN_WINDOW) else {
+        return;
+    };
+    let db_state = app.state::<DbState>();
+    let Ok(conn) = db_state.conn.lock() else {
+        return;
+    };
+    let maximized = window.is_maximized().unwrap_or(false);
+    let state = match (maximized, db::settings::get_window_state(&conn)) {
+        (true, Some(previous)) => WindowState {
+            maximized,
+            ..previous
+        },
+        _ => {
+            let (Ok(size), Ok(position)) = (window.inner_size(), window.outer_position()) else {
+                return;
+            };
+            WindowState {
+                width: size.width,
+                height: size.height,
+                x: position.x,
+                y: position.y,
+                maximized,
+            }
+        }
+    };
+    if let Err(e) = db::settings::set_window_state(&conn, &state) {
+        tracing::warn!("[Window] {}", e);
+    }
+}
+
+/// Restore the main window and save its bounds when it closes
+pub fn init(app: &AppHandle) {
+    restore(app);
+    let Some(window) = app.get_webview_window(MAIN_WINDOW) else {
+        return;
+    };
+    let handle = app.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::CloseRequested { .. } = event {
+            save(&handle);
+        }
+    });
+}
@@ -0,0 +1,179 @@
+// src-tauri/src/workspace_detect.rs
+//! Workspaces detected from git repositories
+//!
+//! A directory inside a git repository belongs to the workspace of that
+//! repository: the one at the directory itself if there is one, otherwise the
+//! one recorded for the `origin` remote, otherwise the one at the repository
+//! root. When none exists, one is created at the root and named after the
+//! repository. The remote is stored on the workspace, so other clones and
+//! worktrees of the same repository land in it whichever way a task starts.
+
+use crate::db::workspaces::{self, Workspace};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::path::Path;
+
+/// The git repository a directory is in
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DetectedRepo {
+    /// Repository root
+    pub root: String,
+    /// `origin` remote as `host/owner/name`, lowercased; unset without a network remote
+    pub remote: Option<String>,
+    /// Repository name, from the remote or the root folder
+    pub name: String,
+}
+
+/// The workspace tasks in a directory would run in
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceSuggestion {
+    /// Existing workspace the directory belongs to
+    pub workspace: Option<Workspace>,
+    pub repo: Option<DetectedRepo>,
+    /// Directory and name a new workspace would get
+    pub path: String,
+    pub name: String,
+}
+
+/// Reduce a git remote URL to `host/owner/name`
+///
+/// Accepts `https://`, `ssh://` and scp-style (`git@host:owner/name`) URLs, so
+/// the different ways of cloning a repository compare equal. Local paths and
+/// `file://` remotes have no host and give `None`.
+fn normalize_remote(url: &str) -> Option<String> {
+    let url = url.trim();
+    let (host, path) = match url.split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?,
+        // scp-style; a single letter before the colon is a Windows drive
+        None => url.split_once(':').filter(|(host, _)| host.len() > 1 && !host.contains('/'))?,
+    };
+    let host = host.rsplit('@').next()?.split(':').next()?;
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    if host.is_empty() || path.is_empty() {
+        return None;
+    }
+    Some(format!("{}/{}", host, path).to_lowercase())
+}
+
+/// Whether two paths are the same directory
+fn same_dir(a: &str, b: &str) -> bool {
+    match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Find the git repository a local directory is in
+///
+/// Runs git, so it's kept off the async runtime.
+pub async fn detect(dir: &str) -> Option<DetectedRepo> {
+    let dir = dir.to_string();
+    tauri::async_runtime::spawn_blocking(move || find_repo(&dir))
+        .await
+        .ok()
+        .flatten()
+}
+
+fn find_repo(dir: &str) -> Option<DetectedRepo> {
+    let path = Path::new(dir);
+    let toplevel = crate::github::git(path, &["rev-parse", "--show-toplevel"], None).ok()?;
+    // git prints the root with forward slashes; keep the directory's spelling when it is the root
+    let root = if same_dir(dir, &toplevel) {
+        dir.to_string()
+    } else {
+        toplevel
+    };
+    let remote = crate::github::git(path, &["remote", "get-url", "origin"], None)
+        .ok()
+        .and_then(|url| normalize_remote(&url));
+    let name = remote
+        .as_deref()
+        .and_then(|remote| remote.rsplit('/').next())
+        .map(str::to_string)
+        .unwrap_or_else(|| workspaces::folder_name(&root));
+
+    Some(DetectedRepo { root, remote, name })
+}
+
+/// Existing workspace a directory belongs to
+fn existing(
+    conn: &Connection,
+    dir: &str,
+    detected: Option<&DetectedRepo>,
+) -> Result<Option<Workspace>, String> {
+    if let Some(workspace) = workspaces::get_workspace_by_path(conn, dir)? {
+        return Ok(Some(workspace));
+    }
+    let Some(detected) = detected else {
+        return Ok(None);
+    };
+    if let Some(remote) = &detected.remote {
+        if let Some(workspace) = workspaces::get_workspace_by_repo(conn, remote)? {
+            return Ok(Some(workspace));
+        }
+    }
+    workspaces::get_workspace_by_path(conn, &detected.root)
+}
+
+/// The workspace a directory would use, without creating it
+pub fn suggest(
+    conn: &Connection,
+    dir: &str,
+    detected: Option<DetectedRepo>,
+) -> Result<WorkspaceSuggestion, String> {
+    let workspace = existing(conn, dir, detected.as_ref())?;
+    let (path, name) = match &detected {
+        Some(detected) => (detected.root.clone(), detected.name.clone()),
+        None => (dir.to_string(), workspaces::folder_name(dir)),
+    };
+    Ok(WorkspaceSuggestion {
+        workspace,
+        repo: detected,
+        path,
+        name,
+    })
+}
+
+/// Record a workspace at a repository's root as that repository's workspace
+///
+/// A workspace for a subdirectory isn't, so other clones don't land in it, and
+/// a workspace keeps the first repository it was recorded for.
+pub fn remember(
+    conn: &Connection,
+    workspace: &Workspace,
+    detected: &DetectedRepo,
+) -> Result<(), String> {
+    let Some(remote) = &detected.remote else {
+        return Ok(());
+    };
+    if workspace.path != detected.root || workspaces::get_repo(conn, &workspace.id)?.is_some() {
+        return Ok(());
+    }
+    workspaces::set_repo(conn, &workspace.id, remote)
+}
+
+/// Get the workspace for a directory, creating one for its repository if needed
+///
+/// Only the workspace's settings apply; tasks keep running in the directory.
+pub fn resolve(
+    conn: &Connection,
+    dir: &str,
+    detected: Option<&DetectedRepo>,
+) -> Result<Workspace, String> {
+    let Some(detected) = detected else {
+        return workspaces::ensure_workspace_for_path(conn, dir);
+    };
+    let workspace = match existing(conn, dir, Some(detected))? {
+        Some(workspace) => workspace,
+        None => {
+            let workspace = workspaces::create_workspace(conn, &detected.name, &detected.root)?;
+            tracing::info!("[Workspaces] Created {} for {}", workspace.id, workspace.path);
+            workspace
+        }
+    };
+    remember(conn, &workspace, detected)?;
+    Ok(workspace)
+}
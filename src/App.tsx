@@ -13,6 +13,8 @@ import ExecutionPage from './pages/Execution';
 
 // Components
 import Sidebar from './components/layout/Sidebar';
+import DowngradeDialog from './components/layout/DowngradeDialog';
+import SafeModeBanner from './components/layout/SafeModeBanner';
 import { TaskLauncher } from './components/TaskLauncher';
 import { useTaskStore } from './stores/taskStore';
 import { Loader2, AlertTriangle } from 'lucide-react';
@@ -138,6 +140,8 @@ export default function App() {
         </AnimatePresence>
       </main>
       <TaskLauncher />
+      <DowngradeDialog />
+      <SafeModeBanner />
     </div>
   );
 }
@@ -1,7 +1,9 @@
-import { useEffect } from 'react';
+import { useEffect, useState } from 'react';
 import { Link } from 'react-router-dom';
 import { useTaskStore } from '../../stores/taskStore';
+import { getAccomplish } from '@/lib/accomplish';
 import type { Task } from '@/shared';
+import type { TaskSort } from '@/lib/tauri-api';
 
 interface TaskHistoryProps {
   limit?: number;
@@ -9,7 +11,7 @@ interface TaskHistoryProps {
 }
 
 export default function TaskHistory({ limit, showTitle = true }: TaskHistoryProps) {
-  const { tasks, loadTasks, deleteTask, clearHistory } = useTaskStore();
+  const { tasks, loadTasks, deleteTask, clearHistory, historySort, setHistorySort } = useTaskStore();
 
   useEffect(() => {
     loadTasks();
@@ -31,16 +33,27 @@ export default function TaskHistory({ limit, showTitle = true }: TaskHistoryProp
         <div className="flex items-center justify-between mb-4">
           <h2 className="text-lg font-medium text-text">Recent Tasks</h2>
           {tasks.length > 0 && !limit && (
-            <button
-              onClick={() => {
-                if (confirm('Are you sure you want to clear all task history?')) {
-                  clearHistory();
-                }
-              }}
-              className="text-sm text-text-muted hover:text-danger transition-colors"
-            >
-              Clear all
-            </button>
+            <div className="flex items-center gap-4">
+              <select
+                value={historySort}
+                onChange={(e) => setHistorySort(e.target.value as TaskSort)}
+                className="text-sm text-text-muted bg-transparent"
+                aria-label="Sort tasks"
+              >
+                <option value="created">Newest first</option>
+                <option value="activity">Recently active</option>
+              </select>
+              <button
+                onClick={() => {
+                  if (confirm('Are you sure you want to clear all task history?')) {
+                    clearHistory();
+                  }
+                }}
+                className="text-sm text-text-muted hover:text-danger transition-colors"
+              >
+                Clear all
+              </button>
+            </div>
           )}
         </div>
       )}
@@ -50,6 +63,7 @@ export default function TaskHistory({ limit, showTitle = true }: TaskHistoryProp
           <TaskHistoryItem
             key={task.id}
             task={task}
+            byActivity={historySort === 'activity'}
             onDelete={() => deleteTask(task.id)}
           />
         ))}
@@ -69,9 +83,11 @@ export default function TaskHistory({ limit, showTitle = true }: TaskHistoryProp
 
 function TaskHistoryItem({
   task,
+  byActivity,
   onDelete,
 }: {
   task: Task;
+  byActivity: boolean;
   onDelete: () => void;
 }) {
   const statusConfig: Record<string, { color: string; label: string }> = {
@@ -84,7 +100,28 @@ function TaskHistoryItem({
   };
 
   const config = statusConfig[task.status] || statusConfig.pending;
-  const timeAgo = getTimeAgo(task.createdAt);
+  const timeAgo = getTimeAgo((byActivity && task.updatedAt) || task.createdAt);
+
+  // The latest screenshot, shown as a stored thumbnail rather than the full image
+  const screenshotId = task.messages
+    .flatMap((m) => m.attachments ?? [])
+    .filter((a) => a.type === 'screenshot' && a.id !== undefined)
+    .pop()?.id;
+  const [thumbnail, setThumbnail] = useState<string | null>(null);
+  useEffect(() => {
+    setThumbnail(null);
+    if (screenshotId === undefined) return;
+    let cancelled = false;
+    getAccomplish()
+      .getMessageAttachmentThumbnail?.(screenshotId)
+      .then((url) => {
+        if (!cancelled) setThumbnail(url);
+      })
+      .catch(() => {});
+    return () => {
+      cancelled = true;
+    };
+  }, [screenshotId]);
 
   return (
     <Link
@@ -92,6 +129,9 @@ function TaskHistoryItem({
       className="flex items-center gap-4 p-4 rounded-card border border-border bg-background-card hover:shadow-card-hover transition-all"
     >
       <div className={`w-2 h-2 rounded-full ${config.color}`} />
+      {thumbnail && (
+        <img src={thumbnail} alt="" className="h-10 w-10 rounded object-cover shrink-0" />
+      )}
       <div className="flex-1 min-w-0">
         <p className="text-sm text-text truncate" title={task.summary || task.prompt}>
           {task.summary || task.prompt}
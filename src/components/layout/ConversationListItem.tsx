@@ -65,7 +65,7 @@ export default function ConversationListItem({ task }: ConversationListItemProps
           handleClick();
         }
       }}
-      title={task.summary || task.prompt}
+      title={task.title || task.summary || task.prompt}
       className={cn(
         'w-full text-left px-3 py-2 rounded-md text-sm transition-colors duration-200',
         'text-zinc-700 hover:bg-accent hover:text-accent-foreground',
@@ -74,7 +74,7 @@ export default function ConversationListItem({ task }: ConversationListItemProps
       )}
     >
       {getStatusIcon()}
-      <span className="block truncate flex-1">{task.summary || task.prompt}</span>
+      <span className="block truncate flex-1">{task.title || task.summary || task.prompt}</span>
       <button
         onClick={handleDelete}
         className={cn(
@@ -0,0 +1,83 @@
+'use client';
+
+import { useEffect, useState } from 'react';
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+} from '@/components/ui/dialog';
+import { Button } from '@/components/ui/button';
+import {
+  dismissDowngradeNotice,
+  getDowngradeNotice,
+  importDowngradedTasks,
+  type DowngradeNotice,
+} from '@/lib/tauri-api';
+import { useTaskStore } from '@/stores/taskStore';
+
+/**
+ * Offers to import tasks from a database written by a newer version of the app,
+ * which was kept as a backup so this version could start with a new one.
+ */
+export default function DowngradeDialog() {
+  const [notice, setNotice] = useState<DowngradeNotice | null>(null);
+  const [importing, setImporting] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const loadTasks = useTaskStore((state) => state.loadTasks);
+
+  useEffect(() => {
+    getDowngradeNotice()
+      .then(setNotice)
+      .catch((err) => console.error('Failed to get downgrade notice:', err));
+  }, []);
+
+  const handleImport = async () => {
+    setImporting(true);
+    setError(null);
+    try {
+      await importDowngradedTasks();
+      await loadTasks();
+      setNotice(null);
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+    } finally {
+      setImporting(false);
+    }
+  };
+
+  const handleDismiss = async () => {
+    try {
+      await dismissDowngradeNotice();
+    } catch (err) {
+      console.error('Failed to dismiss downgrade notice:', err);
+    }
+    setNotice(null);
+  };
+
+  return (
+    <Dialog open={notice !== null} onOpenChange={(open) => !open && handleDismiss()}>
+      <DialogContent className="max-w-md">
+        <DialogHeader>
+          <DialogTitle>Database from a newer version</DialogTitle>
+          <DialogDescription>
+            Your data was saved by a newer version of Cowork Z, so this version started with a new
+            database. The old one is kept as the backup {notice?.backupId} and can be restored after
+            upgrading. You can import its tasks now.
+          </DialogDescription>
+        </DialogHeader>
+        {error && <p className="text-sm text-destructive">{error}</p>}
+        <DialogFooter>
+          <Button variant="outline" onClick={handleDismiss} disabled={importing}>
+            Skip
+          </Button>
+          <Button onClick={handleImport} disabled={importing}>
+            {importing ? 'Importing…' : 'Import tasks'}
+          </Button>
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -0,0 +1,52 @@
+'use client';
+
+import { useEffect, useState } from 'react';
+import { ShieldAlert } from 'lucide-react';
+import { Button } from '@/components/ui/button';
+import { exitSafeMode, isSafeMode } from '@/lib/tauri-api';
+
+/**
+ * Tells the user the app started in safe mode, where tasks can't run, and offers to restart
+ * normally once the setting at fault has been changed.
+ */
+export default function SafeModeBanner() {
+  const [safeMode, setSafeMode] = useState(false);
+  const [restarting, setRestarting] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    isSafeMode()
+      .then(setSafeMode)
+      .catch((err) => console.error('Failed to check safe mode:', err));
+  }, []);
+
+  const handleRestart = async () => {
+    setRestarting(true);
+    setError(null);
+    try {
+      await exitSafeMode();
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+      setRestarting(false);
+    }
+  };
+
+  if (!safeMode) {
+    return null;
+  }
+
+  return (
+    <div className="fixed bottom-4 left-1/2 z-50 flex -translate-x-1/2 items-center gap-3 rounded-lg border border-border bg-card px-4 py-2 shadow-lg">
+      <ShieldAlert className="h-4 w-4 text-warning" />
+      <div className="text-sm">
+        <p className="font-medium text-foreground">Safe mode</p>
+        <p className="text-muted-foreground">
+          {error ?? 'Tasks, hooks and background sync are off. History and settings still work.'}
+        </p>
+      </div>
+      <Button size="sm" variant="outline" onClick={handleRestart} disabled={restarting}>
+        {restarting ? 'Restarting…' : 'Restart normally'}
+      </Button>
+    </div>
+  );
+}
@@ -15,12 +15,13 @@ import type {
   TaskProgress,
   ApiKeyConfig,
   TaskMessage,
+  TaskAttachment,
   BedrockCredentials,
   ProviderSettings,
   ProviderId,
   ConnectedProvider,
 } from '@/shared';
-import { getTauriApi, isRunningInTauri } from './tauri-api';
+import { getTauriApi, isRunningInTauri, type ClaudeCliStatus } from './tauri-api';
 
 // Define the API interface
 interface AccomplishAPI {
@@ -35,7 +36,9 @@ interface AccomplishAPI {
   startTask(config: TaskConfig): Promise<Task>;
   cancelTask(taskId: string): Promise<void>;
   interruptTask(taskId: string): Promise<void>;
-  getTask(taskId: string): Promise<Task | null>;
+  getTask(taskId: string, includeAttachmentData?: boolean): Promise<Task | null>;
+  getMessageAttachment?(attachmentId: number): Promise<TaskAttachment | null>;
+  getMessageAttachmentThumbnail?(attachmentId: number): Promise<string | null>;
   listTasks(): Promise<Task[]>;
   deleteTask(taskId: string): Promise<void>;
   clearTaskHistory(): Promise<void>;
@@ -71,7 +74,7 @@ interface AccomplishAPI {
   setOnboardingComplete(complete: boolean): Promise<void>;
 
   // Claude CLI
-  checkClaudeCli(): Promise<{ installed: boolean; version: string | null; installCommand: string }>;
+  checkClaudeCli(): Promise<ClaudeCliStatus>;
   getClaudeVersion(): Promise<string | null>;
 
   // Model selection
@@ -142,6 +145,7 @@ interface AccomplishAPI {
   onDebugModeChange?(callback: (data: { enabled: boolean }) => void): () => void;
   onTaskStatusChange?(callback: (data: { taskId: string; status: TaskStatus }) => void): () => void;
   onTaskSummary?(callback: (data: { taskId: string; summary: string }) => void): () => void;
+  onTaskTitle?(callback: (data: { taskId: string; title: string }) => void): () => void;
 
   // Logging
   logEvent(payload: { level?: string; message: string; context?: Record<string, unknown> }): Promise<unknown>;
@@ -219,6 +223,7 @@ export function getAccomplish() {
       onDebugModeChange: (callback: (data: { enabled: boolean }) => void) => toSyncUnlisten(tauriApi.onDebugModeChange(callback)),
       onTaskStatusChange: (callback: (data: { taskId: string; status: TaskStatus }) => void) => toSyncUnlisten(tauriApi.onTaskStatusChange(callback)),
       onTaskSummary: (callback: (data: { taskId: string; summary: string }) => void) => toSyncUnlisten(tauriApi.onTaskSummary(callback)),
+      onTaskTitle: (callback: (data: { taskId: string; title: string }) => void) => toSyncUnlisten(tauriApi.onTaskTitle(callback)),
     };
   }
 
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CliStatusPayload } from "./CliStatusPayload";
+import type { Envelope } from "./Envelope";
+import type { ErrorPayload } from "./ErrorPayload";
+import type { LogPayload } from "./LogPayload";
+import type { PermissionRequestPayload } from "./PermissionRequestPayload";
+import type { PongPayload } from "./PongPayload";
+import type { ReadyPayload } from "./ReadyPayload";
+import type { TaskCompletePayload } from "./TaskCompletePayload";
+import type { TaskErrorPayload } from "./TaskErrorPayload";
+import type { TaskMessagePayload } from "./TaskMessagePayload";
+import type { TaskProgressPayload } from "./TaskProgressPayload";
+import type { TaskStartedPayload } from "./TaskStartedPayload";
+import type { TaskUsagePayload } from "./TaskUsagePayload";
+
+/**
+ * An event forwarded from the sidecar, as emitted and as batched in `task:batch`
+ */
+export type AppEvent = { "event": "sidecar:ready", "payload": Envelope<ReadyPayload> } | { "event": "sidecar:pong", "payload": Envelope<PongPayload> } | { "event": "sidecar:cli_status", "payload": Envelope<CliStatusPayload> } | { "event": "sidecar:log", "payload": Envelope<LogPayload> } | { "event": "sidecar:error", "payload": Envelope<ErrorPayload> } | { "event": "task:started", "payload": Envelope<TaskStartedPayload> } | { "event": "task:message", "payload": Envelope<TaskMessagePayload> } | { "event": "task:progress", "payload": Envelope<TaskProgressPayload> } | { "event": "task:permission_request", "payload": Envelope<PermissionRequestPayload> } | { "event": "task:usage", "payload": Envelope<TaskUsagePayload> } | { "event": "task:complete", "payload": Envelope<TaskCompletePayload> } | { "event": "task:error", "payload": Envelope<TaskErrorPayload> };
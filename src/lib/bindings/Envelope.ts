@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * An app event's payload: the task it is about and what the sidecar sent
+ */
+export type Envelope<P> = { taskId?: string, payload: P, };
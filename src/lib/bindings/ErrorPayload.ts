@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A sidecar command failed outside a task run
+ */
+export type ErrorPayload = { message: string, };
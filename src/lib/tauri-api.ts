@@ -5,9 +5,10 @@
  * and event system, replacing the Electron preload script.
  */
 
-import { invoke } from '@tauri-apps/api/core';
+import { Channel, invoke } from '@tauri-apps/api/core';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import { openUrl } from '@tauri-apps/plugin-opener';
+import type { AppEvent } from './bindings/AppEvent';
 
 import type {
   Task,
@@ -20,11 +21,13 @@ import type {
   TaskResult,
   ApiKeyConfig,
   TaskMessage,
+  TaskAttachment,
   BedrockCredentials,
   ProviderSettings,
   ProviderId,
   ConnectedProvider,
   OpenCodeMessage,
+  EnvVar,
 } from '@/shared';
 
 // ============================================================================
@@ -39,6 +42,25 @@ export async function getPlatform(): Promise<string> {
   return invoke<string>('get_platform');
 }
 
+// ============================================================================
+// Profiles
+// ============================================================================
+
+export interface ProfileInfo {
+  name: string;
+  /** Whether this run of the app uses the profile */
+  active: boolean;
+}
+
+export async function listProfiles(): Promise<ProfileInfo[]> {
+  return invoke<ProfileInfo[]>('list_profiles');
+}
+
+/** Switch to a profile, creating it if new; the app relaunches in it */
+export async function switchProfile(name: string): Promise<void> {
+  return invoke<void>('switch_profile', { name });
+}
+
 // ============================================================================
 // Shell
 // ============================================================================
@@ -63,12 +85,178 @@ export async function interruptTask(taskId: string): Promise<void> {
   return invoke<void>('interrupt_task', { taskId });
 }
 
-export async function getTask(taskId: string): Promise<Task | null> {
-  return invoke<Task | null>('get_task', { taskId });
+/** Tasks stopped by `abortAllTasks`, all now `cancelled` */
+export interface AbortSummary {
+  taskIds: string[];
+  abortedAt: string;
+}
+
+/** Kill every running task's process tree and cancel the queued ones */
+export async function abortAllTasks(): Promise<AbortSummary> {
+  return invoke<AbortSummary>('abort_all_tasks');
+}
+
+export async function onTasksAborted(callback: (event: AbortSummary) => void): Promise<UnlistenFn> {
+  return listen<AbortSummary>('tasks:aborted', (event) => callback(event.payload));
+}
+
+/** How a wait for a task ended; `finished` is false when the wait timed out first */
+export interface TaskOutcome {
+  taskId: string;
+  finished: boolean;
+  status: string;
+  result?: TaskResult;
+  summary?: string;
+}
+
+/**
+ * Wait for a task to end, up to timeoutMs (default 60s, at most 10 minutes). Null when the task
+ * doesn't exist.
+ */
+export async function awaitTask(taskId: string, timeoutMs?: number): Promise<TaskOutcome | null> {
+  return invoke<TaskOutcome | null>('await_task', { taskId, timeoutMs });
+}
+
+/**
+ * Attachments carry only their metadata unless includeAttachmentData is set. With includeMessages
+ * false the task comes without messages, for loading them in windows with getTaskMessages.
+ */
+export async function getTask(
+  taskId: string,
+  includeAttachmentData?: boolean,
+  includeMessages?: boolean
+): Promise<Task | null> {
+  return invoke<Task | null>('get_task', { taskId, includeAttachmentData, includeMessages });
+}
+
+/** A window of a task's messages; `offset` is the first one's position and `total` the count */
+export interface TaskMessagePage {
+  messages: TaskMessage[];
+  offset: number;
+  total: number;
+}
+
+/** Fetch up to `limit` (at most 500) of a task's messages from the `offset`th, oldest first */
+export async function getTaskMessages(
+  taskId: string,
+  offset: number,
+  limit: number
+): Promise<TaskMessagePage> {
+  return invoke<TaskMessagePage>('get_task_messages', { taskId, offset, limit });
+}
+
+/** Fetch a message attachment's data, left out of getTask and listTasks by default */
+export async function getMessageAttachment(attachmentId: number): Promise<TaskAttachment | null> {
+  return invoke<TaskAttachment | null>('get_message_attachment', { attachmentId });
+}
+
+/** Small JPEG data URL of a screenshot attached to a message; null for other attachments */
+export async function getMessageAttachmentThumbnail(attachmentId: number): Promise<string | null> {
+  return invoke<string | null>('get_message_attachment_thumbnail', { attachmentId });
+}
+
+/** Order of a task list, most recent first: by creation, or by last activity */
+export type TaskSort = 'created' | 'activity';
+
+export async function listTasks(sort?: TaskSort): Promise<Task[]> {
+  return invoke<Task[]>('list_tasks', { sort });
+}
+
+/** Which tasks listTasksFiltered returns; fields left out don't filter */
+export type TaskFilter = {
+  /** Tasks carrying every one of these tags */
+  tags?: string[];
+  /** Tasks with any of these statuses */
+  statuses?: TaskStatus[];
+  /** Created at or after this date (YYYY-MM-DD) or RFC 3339 time */
+  createdFrom?: string;
+  /** Created at or before this RFC 3339 time, or on or before this date */
+  createdTo?: string;
+  /** Tasks filed under this project */
+  projectId?: string;
+  /** Defaults to 'created' */
+  sort?: TaskSort;
+};
+
+/** The latest 100 matching tasks, without their messages */
+export async function listTasksFiltered(filter: TaskFilter): Promise<Task[]> {
+  return invoke<Task[]>('list_tasks_filtered', { filter });
+}
+
+/** A folder of tasks; taskCount leaves out archived tasks */
+export interface Project {
+  id: string;
+  name: string;
+  createdAt: string;
+  taskCount: number;
+}
+
+export async function listProjects(): Promise<Project[]> {
+  return invoke<Project[]>('list_projects');
+}
+
+export async function createProject(name: string): Promise<Project> {
+  return invoke<Project>('create_project', { name });
+}
+
+export async function renameProject(projectId: string, name: string): Promise<void> {
+  return invoke<void>('rename_project', { projectId, name });
+}
+
+/** Delete a project; its tasks are kept, unfiled */
+export async function deleteProject(projectId: string): Promise<void> {
+  return invoke<void>('delete_project', { projectId });
 }
 
-export async function listTasks(): Promise<Task[]> {
-  return invoke<Task[]>('list_tasks');
+/** File tasks under a project, or unfile them with null; resolves to how many moved */
+export async function moveTasksToProject(taskIds: string[], projectId: string | null): Promise<number> {
+  return invoke<number>('move_tasks_to_project', { taskIds, projectId });
+}
+
+/** Tag a task; resolves to its tags */
+export async function addTaskTag(taskId: string, tag: string): Promise<string[]> {
+  return invoke<string[]>('add_task_tag', { taskId, tag });
+}
+
+/** Take a tag off a task; resolves to its remaining tags */
+export async function removeTaskTag(taskId: string, tag: string): Promise<string[]> {
+  return invoke<string[]>('remove_task_tag', { taskId, tag });
+}
+
+/** Every tag in use, with how many tasks carry it */
+export async function listTags(): Promise<{ tag: string; taskCount: number }[]> {
+  return invoke<{ tag: string; taskCount: number }[]>('list_tags');
+}
+
+/** A task or message matching `searchTasks`; `messageId` is null when the task itself matched */
+export type SearchHit = {
+  taskId: string;
+  taskTitle: string | null;
+  taskStatus: TaskStatus;
+  taskCreatedAt: string;
+  messageId: string | null;
+  messageType: string | null;
+  /** Text around the match; matched terms are between \u0002 and \u0003 */
+  snippet: string;
+  /** BM25 score; lower is a better match */
+  rank: number;
+};
+
+/** Search task prompts, summaries, titles, and messages; every word must match as a prefix */
+export async function searchTasks(query: string, limit?: number): Promise<SearchHit[]> {
+  return invoke<SearchHit[]>('search_tasks', { query, limit });
+}
+
+/** Split a search snippet into plain and matched parts for highlighting */
+export function splitSnippet(snippet: string): { text: string; match: boolean }[] {
+  return snippet
+    .split(/(\u0002[^\u0003]*\u0003)/)
+    .filter((part) => part.length > 0)
+    .map((part) =>
+      part.startsWith('\u0002')
+        ? { text: part.slice(1, -1), match: true }
+        : { text: part, match: false }
+    );
 }
 
 export async function deleteTask(taskId: string): Promise<void> {
@@ -79,6 +267,25 @@ export async function clearTaskHistory(): Promise<void> {
   return invoke<void>('clear_task_history');
 }
 
+/** A task moved out of the task list once it went over 100 tasks */
+export interface ArchivedTask {
+  taskId: string;
+  title?: string;
+  prompt: string;
+  status: string;
+  createdAt: string;
+  archivedAt: string;
+}
+
+export async function listArchivedTasks(): Promise<ArchivedTask[]> {
+  return invoke<ArchivedTask[]>('list_archived_tasks');
+}
+
+/** Move an archived task back into the list; the list's oldest task is archived if it is full */
+export async function restoreTask(taskId: string): Promise<Task> {
+  return invoke<Task>('restore_task', { taskId });
+}
+
 // ============================================================================
 // Task Persistence (for saving task updates to database)
 // ============================================================================
@@ -87,10 +294,74 @@ export async function saveTaskMessage(taskId: string, message: TaskMessage): Pro
   return invoke<void>('save_task_message', { taskId, message });
 }
 
+export type DiffLine = {
+  kind: 'context' | 'add' | 'remove';
+  content: string;
+  oldLine?: number;
+  newLine?: number;
+};
+
+export type DiffHunk = {
+  oldStart: number;
+  oldLines: number;
+  newStart: number;
+  newLines: number;
+  lines: DiffLine[];
+};
+
+export type FileDiff = {
+  path: string;
+  language?: string;
+  /** Unset when the tool wrote the whole file */
+  oldText?: string;
+  newText: string;
+  hunks: DiffHunk[];
+  additions: number;
+  deletions: number;
+};
+
+export type MessageDiff = { tool: string; files: FileDiff[] };
+
+/** Structured diff of an edit/multiedit/write tool message, or null for other messages */
+export async function getMessageDiff(messageId: string): Promise<MessageDiff | null> {
+  return invoke<MessageDiff | null>('get_message_diff', { messageId });
+}
+
+/** A web page an assistant message had in context */
+export interface MessageSource {
+  url: string;
+  title: string | null;
+  kind: 'attachment' | 'webfetch' | 'websearch';
+  /** Order of the context chunk the URL came in within its task */
+  chunk: number;
+}
+
+/** Sources of an assistant message, in the order they entered the context */
+export async function getMessageSources(messageId: string): Promise<MessageSource[]> {
+  return invoke<MessageSource[]>('get_message_sources', { messageId });
+}
+
 export async function saveTaskStatus(taskId: string, status: TaskStatus): Promise<void> {
   return invoke<void>('save_task_status', { taskId, status });
 }
 
+/** A change of a task's status, and what made it */
+export type TaskEvent = {
+  id: number;
+  taskId: string;
+  /** Null when the task was created with the status */
+  fromStatus: TaskStatus | null;
+  toStatus: TaskStatus;
+  source: 'user' | 'ui' | 'sidecar' | 'abort' | 'recovery' | 'save';
+  reason: string | null;
+  createdAt: string;
+};
+
+/** A task's status changes, oldest first, for finding out why it stalled or stopped */
+export async function getTaskTimeline(taskId: string): Promise<TaskEvent[]> {
+  return invoke<TaskEvent[]>('get_task_timeline', { taskId });
+}
+
 export async function saveTaskSession(taskId: string, sessionId: string): Promise<void> {
   return invoke<void>('save_task_session', { taskId, sessionId });
 }
@@ -115,10 +386,422 @@ export async function respondToPermission(response: PermissionResponse): Promise
 // Session Management
 // ============================================================================
 
+/**
+ * Continue a session. Rejects with an error starting with `session:busy` (see
+ * `isSessionBusy`) while another task is continuing the same session.
+ */
 export async function resumeSession(sessionId: string, prompt: string, taskId?: string): Promise<Task> {
   return invoke<Task>('resume_session', { sessionId, prompt, taskId });
 }
 
+/**
+ * Continue a finished task's session with a new message in the same task. The returned task
+ * holds just the stored message. Rejects like `resumeSession` while the session is busy.
+ */
+export async function sendFollowUp(taskId: string, prompt: string): Promise<Task> {
+  return invoke<Task>('send_follow_up', { taskId, prompt });
+}
+
+/** Whether a resume failed because another task holds the session's lock */
+export function isSessionBusy(err: unknown): boolean {
+  return String(err).startsWith('session:busy');
+}
+
+export type SessionSyncResult = {
+  taskId: string;
+  sessionId: string;
+  /** Messages added from the session; unset when the sync failed */
+  added?: number;
+  error?: string;
+};
+
+/** Add messages of a session that advanced outside the app to its task; returns the task ID */
+export async function syncSession(sessionId: string): Promise<string> {
+  return invoke<string>('sync_session', { sessionId });
+}
+
+export async function onSessionSynced(callback: (result: SessionSyncResult) => void): Promise<UnlistenFn> {
+  return listen<SessionSyncResult>('task:session_synced', (event) => callback(event.payload));
+}
+
+/** A task that ran in an agent session */
+export type SessionTask = {
+  taskId: string;
+  title: string | null;
+  prompt: string;
+  status: string;
+  createdAt: string;
+};
+
+/** What a task resuming a session starts with */
+export type SessionContext = {
+  sessionId: string;
+  messageCount: number;
+  tokenEstimate: number;
+  /** Whether the estimate builds on a token count the provider reported */
+  tokenCountReported: boolean;
+  /** Tasks that ran in the session, oldest first */
+  tasks: SessionTask[];
+};
+
+/** Message count, token estimate, and tasks of a session */
+export async function getSessionContext(sessionId: string): Promise<SessionContext> {
+  return invoke<SessionContext>('get_session_context', { sessionId });
+}
+
+/** A task the previous run left in progress, now `interrupted` */
+export type InterruptedTask = {
+  taskId: string;
+  prompt: string;
+  /** Status the task was left in */
+  status: string;
+  /**
+   * Resume with `resumeFromCheckpoint` (or `resumeSession`) when set; otherwise
+   * the task has to be rerun
+   */
+  sessionId?: string;
+};
+
+/** Tasks interrupted at startup; empty after the first call */
+export async function getInterruptedTasks(): Promise<InterruptedTask[]> {
+  return invoke<InterruptedTask[]>('get_interrupted_tasks');
+}
+
+export async function onTasksInterrupted(callback: (tasks: InterruptedTask[]) => void): Promise<UnlistenFn> {
+  return listen<{ tasks: InterruptedTask[] }>('task:interrupted', (event) => callback(event.payload.tasks));
+}
+
+/** Where a running task had got to, kept until it completes */
+export type TaskCheckpoint = {
+  taskId: string;
+  sessionId?: string;
+  /** Messages stored for the task when the checkpoint was taken */
+  messageCount: number;
+  /** Files the agent wrote or edited */
+  filesTouched: string[];
+  updatedAt: string;
+};
+
+export async function getTaskCheckpoint(taskId: string): Promise<TaskCheckpoint | null> {
+  return invoke<TaskCheckpoint | null>('get_task_checkpoint', { taskId });
+}
+
+/** Continue a failed or interrupted task's session from its last checkpoint */
+export async function resumeFromCheckpoint(taskId: string): Promise<Task> {
+  return invoke<Task>('resume_from_checkpoint', { taskId });
+}
+
+/** A point in a task's conversation the user saved to come back to */
+export type SavedCheckpoint = {
+  id: string;
+  taskId: string;
+  label: string;
+  sessionId?: string;
+  /** Messages the task had when the checkpoint was saved */
+  messageCount: number;
+  createdAt: string;
+};
+
+/** Save a labelled checkpoint of a task at its current message */
+export async function createCheckpoint(taskId: string, label: string): Promise<SavedCheckpoint> {
+  return invoke<SavedCheckpoint>('create_checkpoint', { taskId, label });
+}
+
+/** A task's saved checkpoints, earliest in the conversation first */
+export async function listCheckpoints(taskId: string): Promise<SavedCheckpoint[]> {
+  return invoke<SavedCheckpoint[]>('list_checkpoints', { taskId });
+}
+
+export async function deleteCheckpoint(checkpointId: string): Promise<void> {
+  return invoke<void>('delete_checkpoint', { checkpointId });
+}
+
+/**
+ * Rewind a task to a saved checkpoint, archiving later messages; if the task went on past it,
+ * a new session is started from the kept messages
+ */
+export async function restoreCheckpoint(taskId: string, checkpointId: string): Promise<Task> {
+  return invoke<Task>('restore_checkpoint', { taskId, checkpointId });
+}
+
+export type TaskPlan = {
+  taskId: string;
+  plan: string;
+  sessionId?: string;
+  status: 'pending' | 'approved' | 'discarded';
+  createdAt: string;
+  approvedAt?: string;
+};
+
+export type PlanReadyEvent = { taskId: string; payload: { plan: string; sessionId?: string } };
+
+/** Latest progress stored for a task */
+export type StoredTaskProgress = {
+  stage: string;
+  step?: number;
+  totalSteps?: number;
+  label?: string;
+  updatedAt?: string;
+};
+
+export async function getTaskProgress(taskId: string): Promise<StoredTaskProgress | null> {
+  return invoke<StoredTaskProgress | null>('get_task_progress', { taskId });
+}
+
+/** Fenced code block saved from an assistant message */
+export type TaskArtifact = {
+  id: string;
+  taskId: string;
+  /** OpenCode part ID of the message it came from */
+  originId?: string;
+  /** Canonical lowercase name ("javascript", not "js"), from the fence or detected from the code */
+  language?: string;
+  content: string;
+  createdAt: string;
+};
+
+export type ArtifactsEvent = { taskId: string; artifacts: TaskArtifact[] };
+
+export async function listTaskArtifacts(taskId: string): Promise<TaskArtifact[]> {
+  return invoke<TaskArtifact[]>('list_task_artifacts', { taskId });
+}
+
+export async function onTaskArtifacts(callback: (event: ArtifactsEvent) => void): Promise<UnlistenFn> {
+  return listen<ArtifactsEvent>('task:artifacts', (event) => callback(event.payload));
+}
+
+export async function getTaskPlan(taskId: string): Promise<TaskPlan | null> {
+  return invoke<TaskPlan | null>('get_task_plan', { taskId });
+}
+
+/** Approve a plan-first task's plan and continue with execution; `overrideBudget` runs it over spend limits */
+export async function approveTaskPlan(taskId: string, overrideBudget?: boolean): Promise<Task> {
+  return invoke<Task>('approve_task_plan', { taskId, overrideBudget });
+}
+
+export async function onTaskPlanReady(callback: (event: PlanReadyEvent) => void): Promise<UnlistenFn> {
+  return listen<PlanReadyEvent>('task:plan_ready', (event) => callback(event.payload));
+}
+
+export type TaskFork = {
+  taskId: string;
+  /** Unset once the parent task is deleted */
+  parentTaskId?: string;
+  parentMessageId: string;
+  createdAt: string;
+};
+
+export type TaskForks = { parent?: TaskFork; children: TaskFork[] };
+
+/** Branch a task at a message into a new task that continues from there */
+export async function forkTask(taskId: string, messageId: string): Promise<Task> {
+  return invoke<Task>('fork_task', { taskId, messageId });
+}
+
+export async function getTaskForks(taskId: string): Promise<TaskForks> {
+  return invoke<TaskForks>('get_task_forks', { taskId });
+}
+
+export type TaskRerun = {
+  taskId: string;
+  /** Unset once the parent task is deleted */
+  parentTaskId?: string;
+  createdAt: string;
+};
+
+export type TaskReruns = { parent?: TaskRerun; children: TaskRerun[] };
+
+/** Start a fresh task with a task's prompt, directory, model and settings */
+export async function rerunTask(taskId: string): Promise<Task> {
+  return invoke<Task>('rerun_task', { taskId });
+}
+
+export async function getTaskReruns(taskId: string): Promise<TaskReruns> {
+  return invoke<TaskReruns>('get_task_reruns', { taskId });
+}
+
+/** Remove a message from a saved transcript */
+export async function deleteTaskMessage(taskId: string, messageId: string): Promise<void> {
+  return invoke<void>('delete_task_message', { taskId, messageId });
+}
+
+/** Blank a message (e.g. a secret in tool output), dropping its attachments and versions */
+export async function redactTaskMessage(taskId: string, messageId: string): Promise<void> {
+  return invoke<void>('redact_task_message', { taskId, messageId });
+}
+
+/** Attach a note to a message; an empty note removes it */
+export async function annotateMessage(messageId: string, note: string): Promise<void> {
+  return invoke<void>('annotate_message', { messageId, note });
+}
+
+/** The user's rating of an assistant message, with the model of the task's latest step */
+export type MessageRating = {
+  messageId: string;
+  rating: 'up' | 'down';
+  comment: string | null;
+  providerId: string | null;
+  modelId: string | null;
+  ratedAt: string;
+};
+
+/** A rating with the task and content of the rated message */
+export type RatedMessage = MessageRating & {
+  taskId: string;
+  taskPrompt: string;
+  content: string;
+};
+
+/** Rate an assistant message; a null rating removes it */
+export async function rateMessage(
+  messageId: string,
+  rating: 'up' | 'down' | null,
+  comment?: string
+): Promise<MessageRating | null> {
+  return invoke<MessageRating | null>('rate_message', { messageId, rating, comment });
+}
+
+/** Rated messages, newest first; only thumbs-up or thumbs-down ones if `rating` is given */
+export async function listMessageRatings(rating?: 'up' | 'down'): Promise<RatedMessage[]> {
+  return invoke<RatedMessage[]>('list_message_ratings', { rating });
+}
+
+/** The user's note on a task */
+export type TaskNote = {
+  id: string;
+  taskId: string;
+  content: string;
+  followUpAt: string | null;
+  /** When the follow-up reminder went out */
+  remindedAt: string | null;
+  createdAt: string;
+  updatedAt: string;
+};
+
+/** A note with a follow-up, and the title (or prompt) of its task */
+export type FollowUp = TaskNote & { taskTitle: string };
+
+export async function getTaskNotes(taskId: string): Promise<TaskNote[]> {
+  return invoke<TaskNote[]>('get_task_notes', { taskId });
+}
+
+/** Add a note to a task; `followUpAt` is an RFC 3339 time to be reminded at */
+export async function addTaskNote(
+  taskId: string,
+  content: string,
+  followUpAt?: string
+): Promise<TaskNote> {
+  return invoke<TaskNote>('add_task_note', { taskId, content, followUpAt: followUpAt ?? null });
+}
+
+/** Change a note; leaving out `followUpAt` removes the follow-up */
+export async function updateTaskNote(
+  noteId: string,
+  content: string,
+  followUpAt?: string
+): Promise<TaskNote> {
+  return invoke<TaskNote>('update_task_note', { noteId, content, followUpAt: followUpAt ?? null });
+}
+
+export async function deleteTaskNote(noteId: string): Promise<void> {
+  return invoke<void>('delete_task_note', { noteId });
+}
+
+/** Follow-ups not reminded yet, soonest first */
+export async function listFollowUps(): Promise<FollowUp[]> {
+  return invoke<FollowUp[]>('list_follow_ups');
+}
+
+/** A follow-up came due */
+export async function onTaskReminder(callback: (followUp: FollowUp) => void): Promise<UnlistenFn> {
+  return listen<FollowUp>('task:reminder', (event) => callback(event.payload));
+}
+
+export type MessageVersion = { version: number; content: string; replacedAt: string };
+
+/** Replace a user message, archive the messages after it, and rerun from there */
+export async function editMessageAndRerun(
+  taskId: string,
+  messageId: string,
+  newContent: string
+): Promise<Task> {
+  return invoke<Task>('edit_message_and_rerun', { taskId, messageId, newContent });
+}
+
+/** Earlier contents of an edited message, oldest first */
+export async function getMessageVersions(messageId: string): Promise<MessageVersion[]> {
+  return invoke<MessageVersion[]>('get_message_versions', { messageId });
+}
+
+// ============================================================================
+// Model Comparison
+// ============================================================================
+
+export type ComparisonEntry = {
+  taskId: string;
+  modelId: string;
+  status: string;
+  startedAt?: string;
+  completedAt?: string;
+  durationMs?: number;
+  /** Cost in USD */
+  cost: number;
+  inputTokens: number;
+  outputTokens: number;
+  /** Last assistant message */
+  result?: string;
+};
+
+export type Comparison = {
+  id: string;
+  prompt: string;
+  entries: ComparisonEntry[];
+};
+
+/** Run the same prompt on each model (provider/model IDs) as linked tasks */
+export async function startComparisonTask(prompt: string, models: string[]): Promise<Comparison> {
+  return invoke<Comparison>('start_comparison_task', { prompt, models });
+}
+
+export async function getComparison(comparisonId: string): Promise<Comparison | null> {
+  return invoke<Comparison | null>('get_comparison', { comparisonId });
+}
+
+export type TaskRun = {
+  taskId: string;
+  prompt: string;
+  status: string;
+  /** Models that reported usage, in order of first use */
+  models: string[];
+  startedAt?: string;
+  completedAt?: string;
+  durationMs?: number;
+  /** Cost in USD */
+  cost: number;
+  inputTokens: number;
+  outputTokens: number;
+  /** Last assistant message */
+  result?: string;
+};
+
+export type TaskDiff = {
+  a: TaskRun;
+  b: TaskRun;
+  /** Empty when the prompts match */
+  promptHunks: DiffHunk[];
+  sameModels: boolean;
+  /** b minus a, when both have finished */
+  durationDeltaMs?: number;
+  /** b minus a, in USD */
+  costDelta: number;
+  resultHunks: DiffHunk[];
+};
+
+/** Compare two runs, from taskA to taskB */
+export async function diffTasks(taskA: string, taskB: string): Promise<TaskDiff> {
+  return invoke<TaskDiff>('diff_tasks', { taskA, taskB });
+}
+
 // ============================================================================
 // Settings - API Keys
 // ============================================================================
@@ -151,6 +834,52 @@ export async function getAppSettings(): Promise<{ debugMode: boolean; onboarding
   return invoke<{ debugMode: boolean; onboardingComplete: boolean }>('get_app_settings');
 }
 
+/** The workspace open when the app last ran, or null if it was removed */
+export async function getLastWorkspace(): Promise<Workspace | null> {
+  return invoke<Workspace | null>('get_last_workspace');
+}
+
+export async function setLastWorkspace(workspaceId: string | null): Promise<void> {
+  return invoke<void>('set_last_workspace', { workspaceId });
+}
+
+/** Workspaces to pick from, by name */
+export async function listWorkspaces(): Promise<Workspace[]> {
+  return invoke<Workspace[]>('list_workspaces');
+}
+
+/** Create a workspace for a local directory; it is named after the folder unless a name is given */
+export async function createWorkspace(path: string, name?: string): Promise<Workspace> {
+  return invoke<Workspace>('create_workspace', { path, name: name ?? null });
+}
+
+/** Git repository a directory is in; `remote` is `host/owner/name`, lowercased */
+export interface DetectedRepo {
+  root: string;
+  remote: string | null;
+  name: string;
+}
+
+/** The workspace tasks in a directory would run in */
+export interface WorkspaceSuggestion {
+  /** Existing workspace the directory belongs to; null if starting a task would create one */
+  workspace: Workspace | null;
+  repo: DetectedRepo | null;
+  /** Directory and name a new workspace would get */
+  path: string;
+  name: string;
+}
+
+/** Find the workspace for a directory from its git repository, without creating one */
+export async function detectWorkspace(path: string): Promise<WorkspaceSuggestion> {
+  return invoke<WorkspaceSuggestion>('detect_workspace', { path });
+}
+
+/** Make a workspace the one new tasks run in; it is also the last workspace from then on */
+export async function selectWorkspace(workspaceId: string): Promise<Workspace> {
+  return invoke<Workspace>('select_workspace', { workspaceId });
+}
+
 // ============================================================================
 // API Key Management
 // ============================================================================
@@ -191,6 +920,43 @@ export async function hasAnyApiKey(): Promise<boolean> {
   return invoke<boolean>('has_any_api_key');
 }
 
+export type DiagnosticStep = {
+  id: 'key' | 'network' | 'models' | 'completion';
+  label: string;
+  status: 'passed' | 'failed' | 'skipped';
+  detail: string;
+  durationMs: number;
+};
+
+export type ProviderDiagnostics = {
+  providerId: string;
+  /** Whether every step passed */
+  passed: boolean;
+  steps: DiagnosticStep[];
+};
+
+/** Check a provider layer by layer: stored key, network, model list, and a tiny completion */
+export async function runProviderDiagnostics(providerId: string): Promise<ProviderDiagnostics> {
+  return invoke<ProviderDiagnostics>('run_provider_diagnostics', { providerId });
+}
+
+/** A provider found on this machine; LM Studio is offered as a `custom` provider */
+export type DetectedProvider = {
+  providerId: string;
+  source: 'opencode-login' | 'env' | 'ollama' | 'lm-studio' | 'aws';
+  /** What was found, e.g. "Ollama is running with 3 models" */
+  detail: string;
+  baseUrl?: string;
+  models: string[];
+  /** Already set up in the app */
+  configured: boolean;
+};
+
+/** Look for OpenCode logins, API keys in the environment, a running Ollama or LM Studio, and AWS credentials, best first */
+export async function detectAvailableProviders(): Promise<DetectedProvider[]> {
+  return invoke<DetectedProvider[]>('detect_available_providers');
+}
+
 // ============================================================================
 // Onboarding
 // ============================================================================
@@ -203,18 +969,100 @@ export async function setOnboardingComplete(complete: boolean): Promise<void> {
   return invoke<void>('set_onboarding_complete', { complete });
 }
 
+export type HealthCheck = {
+  id: 'database' | 'keychain' | 'sidecar' | 'cli' | 'provider';
+  label: string;
+  status: 'ok' | 'warning' | 'error';
+  detail: string;
+  /** What the user should do about a failed check */
+  fix?: string;
+  /** Fix the app can start itself */
+  action?: 'install_cli' | 'upgrade_cli' | 'restore_backup' | 'open_provider_settings';
+};
+
+export type StartupHealth = {
+  /** Whether no check failed; warnings don't count */
+  healthy: boolean;
+  checks: HealthCheck[];
+  checkedAt: string;
+};
+
+/** Checks run at launch; `refresh` runs them again */
+export async function getStartupHealth(refresh?: boolean): Promise<StartupHealth> {
+  return invoke<StartupHealth>('get_startup_health', { refresh });
+}
+
+export async function onStartupHealth(callback: (report: StartupHealth) => void): Promise<UnlistenFn> {
+  return listen<StartupHealth>('startup:health', (event) => callback(event.payload));
+}
+
 // ============================================================================
 // Claude CLI
 // ============================================================================
 
-export async function checkClaudeCli(): Promise<{ installed: boolean; version: string | null; installCommand: string }> {
-  return invoke<{ installed: boolean; version: string | null; installCommand: string }>('check_claude_cli');
+export type ClaudeCliStatus = {
+  installed: boolean;
+  version: string | null;
+  installCommand: string;
+  /** Resolved binary the sidecar runs */
+  path: string | null;
+  /** Oldest supported version */
+  requiredVersion: string;
+  /** Installed, but older than requiredVersion; tasks won't start */
+  outdated: boolean;
+  upgradeCommand: string;
+  /** path is the CLI from the runtime bundled with the app */
+  bundled: boolean;
+  /** This build ships a runtime */
+  bundledAvailable: boolean;
+};
+
+/** Which CLI runs tasks when both are available */
+export type CliRuntime = 'system' | 'bundled';
+
+/** Emitted when a task is blocked because the installed CLI is too old */
+export type CliOutdatedEvent = {
+  version: string;
+  requiredVersion: string;
+  upgradeCommand: string;
+  path: string;
+};
+
+export async function checkClaudeCli(): Promise<ClaudeCliStatus> {
+  return invoke<ClaudeCliStatus>('check_claude_cli');
 }
 
 export async function getClaudeVersion(): Promise<string | null> {
   return invoke<string | null>('get_claude_version');
 }
 
+export async function getCliRuntime(): Promise<CliRuntime> {
+  return invoke<CliRuntime>('get_cli_runtime');
+}
+
+/** Takes effect the next time the sidecar starts */
+export async function setCliRuntime(runtime: CliRuntime): Promise<void> {
+  return invoke<void>('set_cli_runtime', { runtime });
+}
+
+export type CliInstallProgress = {
+  stage: 'installing' | 'output' | 'done' | 'error';
+  message: string;
+};
+
+/** Install the OpenCode CLI with npm (or Homebrew) and return the re-checked status */
+export async function installOpencodeCli(): Promise<ClaudeCliStatus> {
+  return invoke<ClaudeCliStatus>('install_opencode_cli');
+}
+
+export async function onCliOutdated(callback: (event: CliOutdatedEvent) => void): Promise<UnlistenFn> {
+  return listen<CliOutdatedEvent>('cli:outdated', (event) => callback(event.payload));
+}
+
+export async function onCliInstallProgress(callback: (progress: CliInstallProgress) => void): Promise<UnlistenFn> {
+  return listen<CliInstallProgress>('cli:install_progress', (event) => callback(event.payload));
+}
+
 // ============================================================================
 // Model Selection
 // ============================================================================
@@ -227,6 +1075,15 @@ export async function setSelectedModel(model: { provider: string; model: string;
   return invoke<void>('set_selected_model', { model });
 }
 
+/** Model ID used to summarize finished tasks; null uses the task's model */
+export async function getSummaryModel(): Promise<string | null> {
+  return invoke<string | null>('get_summary_model');
+}
+
+export async function setSummaryModel(modelId: string | null): Promise<void> {
+  return invoke<void>('set_summary_model', { modelId });
+}
+
 // ============================================================================
 // Ollama Configuration
 // ============================================================================
@@ -381,6 +1238,33 @@ export async function isE2EMode(): Promise<boolean> {
   return invoke<boolean>('is_e2e_mode');
 }
 
+// ============================================================================
+// Safe Mode
+// ============================================================================
+
+/** Whether the app was started with `--safe-mode` or `COWORK_Z_SAFE_MODE`; tasks can't run in it */
+export async function isSafeMode(): Promise<boolean> {
+  return invoke<boolean>('is_safe_mode');
+}
+
+/** Relaunch the app outside safe mode */
+export async function exitSafeMode(): Promise<void> {
+  return invoke<void>('exit_safe_mode');
+}
+
+export interface SeedSummary {
+  fixture: string;
+  workspaces: number;
+  tasks: number;
+  messages: number;
+  providers: number;
+}
+
+/** Replace the task history with a fixture ('empty', 'basic', or 'history'); E2E only */
+export async function seedTestData(fixtureName: string): Promise<SeedSummary> {
+  return invoke<SeedSummary>('seed_test_data', { fixtureName });
+}
+
 // ============================================================================
 // Provider Settings API
 // ============================================================================
@@ -486,6 +1370,27 @@ export async function updateProviderModel(providerId: ProviderId, modelId: strin
   return invoke<void>('update_provider_model', { providerId, modelId });
 }
 
+/** Generation parameters overriding a model's defaults; unset ones keep the default */
+export type ModelParameters = {
+  /** 0 to 2 */
+  temperature?: number;
+  /** 0 to 1 */
+  topP?: number;
+  /** Most tokens generated per response */
+  maxTokens?: number;
+  reasoningEffort?: 'minimal' | 'low' | 'medium' | 'high';
+};
+
+/** Parameter overrides keyed by model ID, for models that have some */
+export async function listModelParameters(): Promise<Record<string, ModelParameters>> {
+  return invoke<Record<string, ModelParameters>>('list_model_parameters');
+}
+
+/** Replace a model's overrides; an empty object clears them */
+export async function setModelParameters(modelId: string, parameters: ModelParameters): Promise<void> {
+  return invoke<void>('set_model_parameters', { modelId, parameters });
+}
+
 export async function setProviderDebugMode(enabled: boolean): Promise<void> {
   return invoke<void>('set_provider_debug_mode', { enabled });
 }
@@ -528,6 +1433,11 @@ function buildOpenCodeMessageId(message: OpenCodeMessage): string {
   return `opencode_${fallbackTimestamp}_${Math.random().toString(36).slice(2, 10)}`;
 }
 
+/** OpenCode part ID, stable when the same event is emitted again */
+function getOpenCodeOriginId(message: OpenCodeMessage): string | undefined {
+  return (message as { part?: { id?: string } }).part?.id;
+}
+
 function normalizeOpenCodeMessage(message: OpenCodeMessage): TaskMessage | null {
   switch (message.type) {
     case 'text': {
@@ -538,6 +1448,7 @@ function normalizeOpenCodeMessage(message: OpenCodeMessage): TaskMessage | null
       }
       return {
         id: buildOpenCodeMessageId(message),
+        originId: getOpenCodeOriginId(message),
         type: 'assistant',
         content,
         timestamp: normalizeTimestamp(message.timestamp),
@@ -547,6 +1458,7 @@ function normalizeOpenCodeMessage(message: OpenCodeMessage): TaskMessage | null
       const toolMessage = message as OpenCodeMessage & { part?: { tool?: string; input?: unknown } };
       return {
         id: buildOpenCodeMessageId(message),
+        originId: getOpenCodeOriginId(message),
         type: 'tool',
         content: '',
         timestamp: normalizeTimestamp(message.timestamp),
@@ -558,6 +1470,7 @@ function normalizeOpenCodeMessage(message: OpenCodeMessage): TaskMessage | null
       const toolUseMessage = message as OpenCodeMessage & { part?: { tool?: string; state?: { input?: unknown } } };
       return {
         id: buildOpenCodeMessageId(message),
+        originId: getOpenCodeOriginId(message),
         type: 'tool',
         content: '',
         timestamp: normalizeTimestamp(message.timestamp),
@@ -580,11 +1493,42 @@ function normalizeIncomingMessage(message: unknown): TaskMessage | null {
   return null;
 }
 
+/** Name of an event forwarded from the sidecar (see `src-tauri/src/events.rs`) */
+type AppEventName = AppEvent['event'];
+
+/** `{ taskId?, payload }` of a forwarded event */
+type AppEventPayload<E extends AppEventName> = Extract<AppEvent, { event: E }>['payload'];
+
+/** Streaming events the backend coalesces into one `task:batch` emit per task */
+type TaskBatchEvent = {
+  taskId: string;
+  events: AppEvent[];
+};
+
+function listenWithBatches<E extends AppEventName>(
+  event: E,
+  handler: (payload: AppEventPayload<E>) => void
+): Promise<UnlistenFn[]> {
+  return Promise.all([
+    listen<AppEventPayload<E>>(event, (e) => handler(e.payload)),
+    listen<TaskBatchEvent>('task:batch', (e) => {
+      for (const item of e.payload.events) {
+        if (item.event === event) {
+          handler(item.payload as AppEventPayload<E>);
+        }
+      }
+    }),
+  ]);
+}
+
 export async function onTaskUpdate(callback: (event: TaskUpdateEvent) => void): Promise<UnlistenFn> {
   const unlisteners: UnlistenFn[] = [];
   const track = (unlisten: UnlistenFn) => {
     unlisteners.push(unlisten);
   };
+  const trackAll = (unlistenFns: UnlistenFn[]) => {
+    unlisteners.push(...unlistenFns);
+  };
 
   await Promise.all([
     listen<TaskUpdateEvent>('task:update', (event) => {
@@ -597,9 +1541,9 @@ export async function onTaskUpdate(callback: (event: TaskUpdateEvent) => void):
       }
       callback(event.payload);
     }).then(track),
-    listen<{ taskId?: string; payload?: { message?: TaskMessage } }>('task:message', (event) => {
-      const taskId = event.payload?.taskId;
-      const message = event.payload?.payload?.message;
+    listenWithBatches('task:message', (event) => {
+      const taskId = event?.taskId;
+      const message = event?.payload?.message;
       if (taskId && message) {
         const normalized = normalizeIncomingMessage(message);
         if (!normalized) {
@@ -607,22 +1551,22 @@ export async function onTaskUpdate(callback: (event: TaskUpdateEvent) => void):
         }
         callback({ taskId, type: 'message', message: normalized });
       }
-    }).then(track),
-    listen<{ taskId?: string; payload?: { progress?: TaskProgress } }>('task:progress', (event) => {
-      const taskId = event.payload?.taskId;
-      const progress = event.payload?.payload?.progress;
+    }).then(trackAll),
+    listenWithBatches('task:progress', (event) => {
+      const taskId = event?.taskId;
+      const progress = event?.payload?.progress as TaskProgress | undefined;
       if (taskId && progress) {
         callback({ taskId, type: 'progress', progress });
       }
-    }).then(track),
-    listen<{ taskId?: string; payload?: { result?: TaskResult } }>('task:complete', (event) => {
+    }).then(trackAll),
+    listen<AppEventPayload<'task:complete'>>('task:complete', (event) => {
       const taskId = event.payload?.taskId;
-      const result = event.payload?.payload?.result;
+      const result = event.payload?.payload?.result as TaskResult | undefined;
       if (taskId && result) {
         callback({ taskId, type: 'complete', result });
       }
     }).then(track),
-    listen<{ taskId?: string; payload?: { error?: unknown } }>('task:error', (event) => {
+    listen<AppEventPayload<'task:error'>>('task:error', (event) => {
       const taskId = event.payload?.taskId;
       const errorPayload = event.payload?.payload?.error;
       if (taskId && errorPayload !== undefined) {
@@ -637,16 +1581,71 @@ export async function onTaskUpdate(callback: (event: TaskUpdateEvent) => void):
   };
 }
 
+// ============================================================================
+// Observer Windows
+// ============================================================================
+
+/** An app event sent to an observer window's channel */
+type ObservedEvent = {
+  event: string;
+  payload: { taskId?: string; payload?: { message?: unknown; progress?: TaskProgress; result?: TaskResult; error?: unknown } };
+};
+
+/** Open a read-only window that follows one task; resolves to its window label */
+export async function openObserverWindow(taskId: string): Promise<string> {
+  return invoke<string>('open_observer_window', { taskId });
+}
+
+/**
+ * Follow one task from an observer window
+ *
+ * Resolves to the task as stored, then calls back with the task's updates only.
+ * Observer windows can't listen to app events, so this is their only feed.
+ */
+export async function watchTask(taskId: string, callback: (event: TaskUpdateEvent) => void): Promise<Task | null> {
+  const channel = new Channel<ObservedEvent>();
+  channel.onmessage = ({ event, payload }) => {
+    const data = payload.payload;
+    if (event === 'task:message' && data?.message) {
+      const message = normalizeIncomingMessage(data.message);
+      if (message) {
+        callback({ taskId, type: 'message', message });
+      }
+    } else if (event === 'task:progress' && data?.progress) {
+      callback({ taskId, type: 'progress', progress: data.progress });
+    } else if (event === 'task:complete' && data?.result) {
+      callback({ taskId, type: 'complete', result: data.result });
+    } else if (event === 'task:error' && data?.error !== undefined) {
+      const error = typeof data.error === 'string' ? data.error : JSON.stringify(data.error);
+      callback({ taskId, type: 'error', error });
+    }
+  };
+  return invoke<Task | null>('watch_task', { taskId, onEvent: channel });
+}
+
 export async function onTaskUpdateBatch(callback: (event: { taskId: string; messages: TaskMessage[] }) => void): Promise<UnlistenFn> {
   return listen<{ taskId: string; messages: TaskMessage[] }>('task:update-batch', (event) => callback(event.payload));
 }
 
 export async function onPermissionRequest(callback: (request: PermissionRequest) => void): Promise<UnlistenFn> {
-  return listen<PermissionRequest>('permission:request', (event) => callback(event.payload));
+  return listen<AppEventPayload<'task:permission_request'>>('task:permission_request', (event) => {
+    const request = event.payload?.payload?.request as PermissionRequest | undefined;
+    if (request) {
+      callback(request);
+    }
+  });
 }
 
 export async function onTaskProgress(callback: (progress: TaskProgress) => void): Promise<UnlistenFn> {
-  return listen<TaskProgress>('task:progress', (event) => callback(event.payload));
+  const unlisteners = await listenWithBatches('task:progress', (event) => {
+    const progress = event?.payload?.progress as TaskProgress | undefined;
+    if (progress) {
+      callback(progress);
+    }
+  });
+  return () => {
+    unlisteners.forEach((unlisten) => unlisten());
+  };
 }
 
 export async function onDebugLog(callback: (log: unknown) => void): Promise<UnlistenFn> {
@@ -665,6 +1664,10 @@ export async function onTaskSummary(callback: (data: { taskId: string; summary:
   return listen<{ taskId: string; summary: string }>('task:summary', (event) => callback(event.payload));
 }
 
+export async function onTaskTitle(callback: (data: { taskId: string; title: string }) => void): Promise<UnlistenFn> {
+  return listen<{ taskId: string; title: string }>('task:title', (event) => callback(event.payload));
+}
+
 // ============================================================================
 // Logging
 // ============================================================================
@@ -673,6 +1676,867 @@ export async function logEvent(payload: { level?: string; message: string; conte
   return invoke('log_event', { payload });
 }
 
+export type LogLevel = 'off' | 'error' | 'warn' | 'info' | 'debug' | 'trace';
+
+export async function getLogLevel(): Promise<LogLevel> {
+  return invoke<LogLevel>('get_log_level');
+}
+
+export async function setLogLevel(level: LogLevel): Promise<void> {
+  return invoke<void>('set_log_level', { level });
+}
+
+export type LogRecord = {
+  id?: number;
+  level: Exclude<LogLevel, 'off'>;
+  target: string;
+  message: string;
+  taskId?: string;
+  timestamp: string;
+};
+
+export async function queryLogs(filter?: {
+  level?: Exclude<LogLevel, 'off'>;
+  taskId?: string;
+  target?: string;
+  search?: string;
+  since?: string;
+  until?: string;
+  limit?: number;
+}): Promise<LogRecord[]> {
+  return invoke<LogRecord[]>('query_logs', { filter });
+}
+
+export type SlowCommand = {
+  name: string;
+  kind: 'command' | 'sidecar';
+  taskId?: string;
+  durationMs: number;
+  outcome: 'ok' | 'error';
+  finishedAt: string;
+};
+
+/** Commands and sidecar operations slower than the threshold, newest first */
+export async function getSlowCommands(): Promise<SlowCommand[]> {
+  return invoke<SlowCommand[]>('get_slow_commands');
+}
+
+// ============================================================================
+// Crash Reporting
+// ============================================================================
+
+export async function listCrashReports(): Promise<Array<{
+  id: string;
+  createdAt: string;
+  message: string;
+  submitted: boolean;
+}>> {
+  return invoke('list_crash_reports');
+}
+
+export async function submitCrashReport(reportId: string): Promise<void> {
+  return invoke<void>('submit_crash_report', { reportId });
+}
+
+// ============================================================================
+// Diagnostics
+// ============================================================================
+
+export async function exportDiagnostics(path: string): Promise<void> {
+  return invoke<void>('export_diagnostics', { path });
+}
+
+export interface TaskSize {
+  taskId: string;
+  title: string;
+  messageCount: number;
+  messageBytes: number;
+  attachmentBytes: number;
+}
+
+export interface DbStats {
+  path: string;
+  fileBytes: number;
+  walBytes: number;
+  /** Free pages in the file, reclaimable with VACUUM */
+  freeBytes: number;
+  schemaVersion: number;
+  tables: { table: string; rows: number }[];
+  /** Largest first */
+  largestTasks: TaskSize[];
+}
+
+export async function getDbStats(): Promise<DbStats> {
+  return invoke<DbStats>('get_db_stats');
+}
+
+/** Space saved by storing identical message attachments once */
+export type StorageSavings = {
+  attachments: number;
+  /** Attachments still holding their own copy of their data */
+  inlineAttachments: number;
+  /** Distinct stored contents */
+  blobs: number;
+  /** Bytes the attachments would take with a copy each */
+  referencedBytes: number;
+  storedBytes: number;
+  savedBytes: number;
+};
+
+export async function getStorageSavings(): Promise<StorageSavings> {
+  return invoke<StorageSavings>('get_storage_savings');
+}
+
+/** Move per-attachment data into shared storage (also done at startup) */
+export async function dedupAttachments(): Promise<StorageSavings> {
+  return invoke<StorageSavings>('dedup_attachments');
+}
+
+export type BackupSettings = {
+  enabled: boolean;
+  /** Hours between automatic backups */
+  intervalHours: number;
+  /** Automatic backups kept; older ones are deleted */
+  keep: number;
+};
+
+export type BackupInfo = {
+  /** File name, passed to restoreBackup */
+  id: string;
+  kind: 'auto' | 'manual' | 'pre-restore' | 'pre-downgrade';
+  createdAt: string;
+  bytes: number;
+};
+
+export async function getBackupSettings(): Promise<BackupSettings> {
+  return invoke<BackupSettings>('get_backup_settings');
+}
+
+export async function setBackupSettings(settings: BackupSettings): Promise<void> {
+  return invoke<void>('set_backup_settings', { settings });
+}
+
+export async function createBackup(): Promise<BackupInfo> {
+  return invoke<BackupInfo>('create_backup');
+}
+
+/** Backups, newest first */
+export async function listBackups(): Promise<BackupInfo[]> {
+  return invoke<BackupInfo[]>('list_backups');
+}
+
+/** Restore a backup; the app restarts, keeping the current database as a pre-restore backup */
+export async function restoreBackup(backupId: string): Promise<void> {
+  return invoke<void>('restore_backup', { backupId });
+}
+
+/** A database from a newer version of the app, kept as a backup when this version started */
+export type DowngradeNotice = {
+  backupId: string;
+  schemaVersion: number;
+};
+
+/** The newer database set aside at startup, until its tasks are imported or it is dismissed */
+export async function getDowngradeNotice(): Promise<DowngradeNotice | null> {
+  return invoke<DowngradeNotice | null>('get_downgrade_notice');
+}
+
+/** Import the set-aside database's tasks that aren't here yet; resolves to how many */
+export async function importDowngradedTasks(): Promise<number> {
+  return invoke<number>('import_downgraded_tasks');
+}
+
+export async function dismissDowngradeNotice(): Promise<void> {
+  return invoke<void>('dismiss_downgrade_notice');
+}
+
+// ============================================================================
+// Export
+// ============================================================================
+
+export async function exportTaskHtml(taskId: string, path: string): Promise<void> {
+  return invoke<void>('export_task_html', { taskId, path });
+}
+
+export async function exportTaskPdf(taskId: string, path: string): Promise<void> {
+  return invoke<void>('export_task_pdf', { taskId, path });
+}
+
+/**
+ * Write a task as a Markdown transcript (screenshots saved in a `<name>_files` folder beside it)
+ * or as a JSON bundle of the task, its tags, sources, and ratings
+ */
+export async function exportTask(taskId: string, format: 'markdown' | 'json', path: string): Promise<void> {
+  return invoke<void>('export_task', { taskId, format, path });
+}
+
+export type ShareableOptions = {
+  format?: 'markdown' | 'html';
+  stripPaths?: boolean;
+  stripAttachments?: boolean;
+  includeToolCalls?: boolean;
+};
+
+export async function exportTaskShareable(
+  taskId: string,
+  options?: ShareableOptions,
+  path?: string
+): Promise<string> {
+  return invoke<string>('export_task_shareable', { taskId, options, path });
+}
+
+// ============================================================================
+// GitHub
+// ============================================================================
+
+export type GitHubRepo = { owner: string; name: string; root: string };
+export type GitHubLink = { number: number; url: string };
+
+export async function setGithubToken(token: string): Promise<void> {
+  return invoke<void>('set_github_token', { token });
+}
+
+export async function hasGithubToken(): Promise<boolean> {
+  return invoke<boolean>('has_github_token');
+}
+
+export async function removeGithubToken(): Promise<void> {
+  return invoke<void>('remove_github_token');
+}
+
+export async function detectGithubRepo(workingDirectory: string): Promise<GitHubRepo> {
+  return invoke<GitHubRepo>('detect_github_repo', { workingDirectory });
+}
+
+export async function createGithubIssue(taskId: string, workingDirectory: string): Promise<GitHubLink> {
+  return invoke<GitHubLink>('create_github_issue', { taskId, workingDirectory });
+}
+
+export async function createGithubDraftPr(taskId: string, workingDirectory: string, baseBranch?: string): Promise<GitHubLink> {
+  return invoke<GitHubLink>('create_github_draft_pr', { taskId, workingDirectory, baseBranch });
+}
+
+// ============================================================================
+// Local API Server
+// ============================================================================
+
+export type ApiServerStatus = {
+  enabled: boolean;
+  running: boolean;
+  port: number;
+  url?: string;
+  websocketEnabled: boolean;
+  /** Serving Prometheus metrics at `/metrics` */
+  metricsEnabled: boolean;
+};
+
+export async function getApiServerStatus(): Promise<ApiServerStatus> {
+  return invoke<ApiServerStatus>('get_api_server_status');
+}
+
+export async function setApiServerEnabled(enabled: boolean, port?: number): Promise<ApiServerStatus> {
+  return invoke<ApiServerStatus>('set_api_server_enabled', { enabled, port });
+}
+
+export async function setApiServerWebsocketEnabled(enabled: boolean): Promise<ApiServerStatus> {
+  return invoke<ApiServerStatus>('set_api_server_websocket_enabled', { enabled });
+}
+
+export async function setApiServerMetricsEnabled(enabled: boolean): Promise<ApiServerStatus> {
+  return invoke<ApiServerStatus>('set_api_server_metrics_enabled', { enabled });
+}
+
+export async function getApiServerToken(): Promise<string> {
+  return invoke<string>('get_api_server_token');
+}
+
+export async function regenerateApiServerToken(): Promise<string> {
+  return invoke<string>('regenerate_api_server_token');
+}
+
+// ============================================================================
+// Sync
+// ============================================================================
+
+export type SyncReport = {
+  imported: number;
+  exported: number;
+  merged: number;
+  deleted: number;
+};
+
+export async function getSyncFolder(): Promise<string | null> {
+  return invoke<string | null>('get_sync_folder');
+}
+
+export async function setSyncFolder(path: string | null): Promise<SyncReport | null> {
+  return invoke<SyncReport | null>('set_sync_folder', { path });
+}
+
+export async function syncHistoryFolder(): Promise<SyncReport> {
+  return invoke<SyncReport>('sync_history_folder');
+}
+
+export async function onHistorySynced(callback: (report: SyncReport) => void): Promise<UnlistenFn> {
+  return listen<SyncReport>('history:synced', (event) => callback(event.payload));
+}
+
+export type DeviceIdentity = { deviceId: string; publicKey: string };
+
+export type EncryptedSyncStatus = {
+  enabled: boolean;
+  relayUrl?: string;
+  device?: DeviceIdentity;
+  hasGroupKey: boolean;
+};
+
+export type PairResult = { peerDeviceId: string; joined: boolean };
+
+export async function getEncryptedSyncStatus(): Promise<EncryptedSyncStatus> {
+  return invoke<EncryptedSyncStatus>('get_encrypted_sync_status');
+}
+
+export async function setEncryptedSyncEnabled(
+  enabled: boolean,
+  relayUrl?: string
+): Promise<EncryptedSyncStatus> {
+  return invoke<EncryptedSyncStatus>('set_encrypted_sync_enabled', { enabled, relayUrl });
+}
+
+export async function pairDevice(publicKey: string): Promise<PairResult> {
+  return invoke<PairResult>('pair_device', { publicKey });
+}
+
+export async function syncNow(): Promise<SyncReport> {
+  return invoke<SyncReport>('sync_now');
+}
+
+// ============================================================================
+// Budget
+// ============================================================================
+
+export type BudgetLimit = {
+  period: 'day' | 'month';
+  /** Limit in USD */
+  amount: number;
+  /** Model ID prefix (e.g. "anthropic"); omitted for a limit across all providers */
+  providerId?: string;
+};
+
+export type BudgetStatus = BudgetLimit & { spent: number };
+
+export type BudgetEvent = { taskId: string; budget: BudgetStatus; overridden?: boolean };
+
+export async function getBudgetLimits(): Promise<BudgetLimit[]> {
+  return invoke<BudgetLimit[]>('get_budget_limits');
+}
+
+export async function setBudgetLimits(limits: BudgetLimit[]): Promise<void> {
+  return invoke<void>('set_budget_limits', { limits });
+}
+
+export async function getBudgetStatus(): Promise<BudgetStatus[]> {
+  return invoke<BudgetStatus[]>('get_budget_status');
+}
+
+export async function onBudgetWarning(callback: (event: BudgetEvent) => void): Promise<UnlistenFn> {
+  return listen<BudgetEvent>('budget:warning', (event) => callback(event.payload));
+}
+
+export async function onBudgetExceeded(callback: (event: BudgetEvent) => void): Promise<UnlistenFn> {
+  return listen<BudgetEvent>('budget:exceeded', (event) => callback(event.payload));
+}
+
+// ============================================================================
+// Usage
+// ============================================================================
+
+/** Inclusive RFC 3339 bounds; either side may be omitted */
+export type DateRange = { since?: string; until?: string };
+
+export type UsageDashboard = {
+  tasksPerDay: { date: string; total: number; completed: number; failed: number }[];
+  tokensPerDay: { date: string; inputTokens: number; outputTokens: number; cost: number }[];
+  costByModel: { modelId: string; tasks: number; inputTokens: number; outputTokens: number; cost: number }[];
+  totalTasks: number;
+  completedTasks: number;
+  failedTasks: number;
+  successRate?: number;
+};
+
+export async function getUsageDashboard(range?: DateRange): Promise<UsageDashboard> {
+  return invoke<UsageDashboard>('get_usage_dashboard', { range });
+}
+
+/** Token and estimated cost (USD) sums; steps counts the agent steps with usage recorded */
+export type UsageTotals = {
+  steps: number;
+  inputTokens: number;
+  outputTokens: number;
+  reasoningTokens: number;
+  cacheReadTokens: number;
+  cacheWriteTokens: number;
+  cost: number;
+};
+
+export type ModelUsage = Omit<UsageTotals, 'steps'> & { modelId: string };
+
+export type TaskUsage = UsageTotals & { taskId: string; byModel: ModelUsage[] };
+
+/** Usage recorded in a range; tasks counts those with any usage in it */
+export type UsageSummary = UsageTotals & { tasks: number; byModel: ModelUsage[] };
+
+export async function getTaskUsage(taskId: string): Promise<TaskUsage> {
+  return invoke<TaskUsage>('get_task_usage', { taskId });
+}
+
+/** Usage recorded in the range, or all time without one */
+export async function getUsageSummary(range?: DateRange): Promise<UsageSummary> {
+  return invoke<UsageSummary>('get_usage_summary', { range });
+}
+
+/**
+ * Write a CSV with one row per task created in the range: status, model, duration, tokens,
+ * cost, workspace, ratings, and tags. Resolves to the number of tasks written.
+ */
+export async function exportTaskMetricsCsv(range: DateRange | undefined, path: string): Promise<number> {
+  return invoke<number>('export_task_metrics_csv', { range, path });
+}
+
+// ============================================================================
+// Import
+// ============================================================================
+
+export type ImportReport = { imported: number; skipped: number; failed: number };
+
+export async function importClaudeCodeSessions(path?: string): Promise<ImportReport> {
+  return invoke<ImportReport>('import_claude_code_sessions', { path });
+}
+
+export async function importOpencodeSessions(path?: string): Promise<ImportReport> {
+  return invoke<ImportReport>('import_opencode_sessions', { path });
+}
+
+// ============================================================================
+// Workspace Hooks
+// ============================================================================
+
+/** Shell commands run in the workspace directory around each task */
+export type WorkspaceHooks = { preTask: string[]; postTask: string[] };
+
+export type HookEvent = {
+  taskId: string;
+  payload: {
+    result: {
+      stage: 'pre-task' | 'post-task';
+      command: string;
+      success: boolean;
+      exitCode?: number;
+      timedOut: boolean;
+      output: string;
+    };
+    /** System message already saved to the task */
+    message: { id: string; type: 'system'; content: string; timestamp: string };
+  };
+};
+
+export async function getWorkspaceHooks(workspaceId: string): Promise<WorkspaceHooks> {
+  return invoke<WorkspaceHooks>('get_workspace_hooks', { workspaceId });
+}
+
+export async function setWorkspaceHooks(workspaceId: string, hooks: WorkspaceHooks): Promise<void> {
+  return invoke<void>('set_workspace_hooks', { workspaceId, hooks });
+}
+
+/** Processors run over a workspace's agent messages before they are shown and saved */
+export type PostprocessSettings = {
+  /** Remove ANSI escape codes from tool output */
+  stripAnsi: boolean;
+  /** Link file paths that exist in the workspace */
+  linkifyPaths: boolean;
+  /** Save fenced code blocks as task artifacts */
+  extractCodeBlocks: boolean;
+};
+
+export async function getWorkspacePostprocess(workspaceId: string): Promise<PostprocessSettings> {
+  return invoke<PostprocessSettings>('get_workspace_postprocess', { workspaceId });
+}
+
+export async function setWorkspacePostprocess(
+  workspaceId: string,
+  settings: PostprocessSettings
+): Promise<void> {
+  return invoke<void>('set_workspace_postprocess', { workspaceId, settings });
+}
+
+/** Model and agent used by a workspace's tasks instead of the global selection */
+export type WorkspaceDefaults = { modelId?: string | null; agent?: string | null };
+
+export async function getWorkspaceDefaults(workspaceId: string): Promise<WorkspaceDefaults> {
+  return invoke<WorkspaceDefaults>('get_workspace_defaults', { workspaceId });
+}
+
+export async function setWorkspaceDefaults(
+  workspaceId: string,
+  defaults: WorkspaceDefaults
+): Promise<void> {
+  return invoke<void>('set_workspace_defaults', { workspaceId, defaults });
+}
+
+/** Allows or denies an OpenCode tool; only `bash` rules take a command pattern */
+export type PermissionRule = { tool: string; pattern?: string | null; action: 'allow' | 'deny' };
+
+/** MCP server started for a workspace's tasks */
+export type WorkspaceMcpServer = {
+  type: 'local' | 'remote';
+  command: string[];
+  url?: string | null;
+  environment: Record<string, string>;
+  enabled: boolean;
+};
+
+/** What the agent is given in a workspace besides the prompt */
+export type AgentSettings = {
+  instructions?: string | null;
+  /** Applied in order, so later rules override earlier ones */
+  permissionRules: PermissionRule[];
+  /** Relative to the workspace directory */
+  pinnedFiles: string[];
+  mcpServers: Record<string, WorkspaceMcpServer>;
+};
+
+export async function getWorkspaceAgentSettings(workspaceId: string): Promise<AgentSettings> {
+  return invoke<AgentSettings>('get_workspace_agent_settings', { workspaceId });
+}
+
+export async function setWorkspaceAgentSettings(
+  workspaceId: string,
+  settings: AgentSettings
+): Promise<void> {
+  return invoke<void>('set_workspace_agent_settings', { workspaceId, settings });
+}
+
+/** Settings a new workspace starts with; built-in IDs start with `builtin:` */
+export interface WorkspaceTemplate {
+  id: string;
+  name: string;
+  description: string | null;
+  builtin: boolean;
+  defaults: WorkspaceDefaults;
+  settings: AgentSettings;
+}
+
+export async function listWorkspaceTemplates(): Promise<WorkspaceTemplate[]> {
+  return invoke<WorkspaceTemplate[]>('list_workspace_templates');
+}
+
+/** Create a workspace for a local directory with a template's settings */
+export async function createWorkspaceFromTemplate(
+  templateId: string,
+  path: string,
+  name?: string
+): Promise<Workspace> {
+  return invoke<Workspace>('create_workspace_from_template', { templateId, path, name });
+}
+
+/** Write a workspace's settings to a file; MCP server environment values are left out */
+export async function exportWorkspaceTemplate(
+  workspaceId: string,
+  name: string,
+  path: string,
+  description?: string
+): Promise<void> {
+  return invoke<void>('export_workspace_template', { workspaceId, name, description, path });
+}
+
+/** What importing a template file would add; show its MCP servers before importing */
+export interface TemplatePreview {
+  name: string;
+  description: string | null;
+  defaults: WorkspaceDefaults;
+  settings: AgentSettings;
+}
+
+export async function previewWorkspaceTemplate(path: string): Promise<TemplatePreview> {
+  return invoke<TemplatePreview>('preview_workspace_template', { path });
+}
+
+/** Import a template file; `approvedMcpServers` must be the servers its preview showed */
+export async function importWorkspaceTemplate(
+  path: string,
+  approvedMcpServers: Record<string, WorkspaceMcpServer>
+): Promise<WorkspaceTemplate> {
+  return invoke<WorkspaceTemplate>('import_workspace_template', { path, approvedMcpServers });
+}
+
+export async function deleteWorkspaceTemplate(templateId: string): Promise<void> {
+  return invoke<void>('delete_workspace_template', { templateId });
+}
+
+/** Docker image the workspace's tasks run in, or null when they run on the host */
+export async function getWorkspaceSandbox(workspaceId: string): Promise<string | null> {
+  return invoke<string | null>('get_workspace_sandbox', { workspaceId });
+}
+
+export async function setWorkspaceSandbox(workspaceId: string, image: string | null): Promise<void> {
+  return invoke<void>('set_workspace_sandbox', { workspaceId, image });
+}
+
+// ============================================================================
+// Project Index
+// ============================================================================
+
+export type IndexSummary = {
+  workspaceId: string;
+  fileCount: number;
+  /** Files read because they were new or changed */
+  updated: number;
+  removed: number;
+  durationMs: number;
+};
+
+export type ProjectFileMatch = {
+  /** Relative to the workspace */
+  path: string;
+  /** Declared symbols matching the query */
+  symbols: string[];
+};
+
+export async function indexProject(workspaceId: string): Promise<IndexSummary> {
+  return invoke<IndexSummary>('index_project', { workspaceId });
+}
+
+/** Files whose path or declared symbols match every term of the query */
+export async function searchProjectFiles(
+  workspaceId: string,
+  query: string,
+  limit?: number
+): Promise<ProjectFileMatch[]> {
+  return invoke<ProjectFileMatch[]>('search_project_files', { workspaceId, query, limit });
+}
+
+export async function getWorkspaceEnv(workspaceId: string): Promise<EnvVar[]> {
+  return invoke<EnvVar[]>('get_workspace_env', { workspaceId });
+}
+
+export async function setWorkspaceEnv(workspaceId: string, vars: EnvVar[]): Promise<void> {
+  return invoke<void>('set_workspace_env', { workspaceId, vars });
+}
+
+export async function getTaskEnv(taskId: string): Promise<EnvVar[]> {
+  return invoke<EnvVar[]>('get_task_env', { taskId });
+}
+
+export async function setTaskEnv(taskId: string, vars: EnvVar[]): Promise<void> {
+  return invoke<void>('set_task_env', { taskId, vars });
+}
+
+export async function onTaskHook(callback: (event: HookEvent) => void): Promise<UnlistenFn> {
+  return listen<HookEvent>('task:hook', (event) => callback(event.payload));
+}
+
+// ============================================================================
+// Remote Hosts
+// ============================================================================
+
+export type SshHost = {
+  id: string;
+  name: string;
+  host: string;
+  port?: number | null;
+  user?: string | null;
+  /** 'agent' uses the SSH agent and ~/.ssh keys; 'key' a private key kept in the keychain */
+  auth: 'agent' | 'key';
+  createdAt: string;
+};
+
+export type SshHostInput = {
+  name: string;
+  host: string;
+  port?: number;
+  user?: string;
+  auth?: 'agent' | 'key';
+};
+
+export type Workspace = { id: string; name: string; path: string; createdAt: string };
+
+export async function listSshHosts(): Promise<SshHost[]> {
+  return invoke<SshHost[]>('list_ssh_hosts');
+}
+
+export async function createSshHost(input: SshHostInput, privateKey?: string): Promise<SshHost> {
+  return invoke<SshHost>('create_ssh_host', { input, privateKey });
+}
+
+export async function deleteSshHost(id: string): Promise<void> {
+  return invoke<void>('delete_ssh_host', { id });
+}
+
+/** Connect to the host and return its `uname -a` */
+export async function testSshHost(id: string): Promise<string> {
+  return invoke<string>('test_ssh_host', { id });
+}
+
+/** Create a workspace whose directory is on an SSH host; tasks in it run commands there */
+export async function createRemoteWorkspace(
+  sshHostId: string,
+  path: string,
+  name?: string
+): Promise<Workspace> {
+  return invoke<Workspace>('create_remote_workspace', { sshHostId, path, name });
+}
+
+export async function readRemoteFile(workspaceId: string, path: string): Promise<string> {
+  return invoke<string>('read_remote_file', { workspaceId, path });
+}
+
+// ============================================================================
+// Terminal
+// ============================================================================
+
+export type TerminalOutputEvent = { terminalId: string; taskId: string; data: string };
+export type TerminalExitEvent = { terminalId: string; taskId: string };
+
+/** Open a shell in the task's working directory; returns the terminal ID */
+export async function openTerminal(taskId: string, rows: number, cols: number): Promise<string> {
+  return invoke<string>('open_terminal', { taskId, rows, cols });
+}
+
+export async function writeTerminal(terminalId: string, data: string): Promise<void> {
+  return invoke<void>('write_terminal', { terminalId, data });
+}
+
+export async function resizeTerminal(terminalId: string, rows: number, cols: number): Promise<void> {
+  return invoke<void>('resize_terminal', { terminalId, rows, cols });
+}
+
+export async function closeTerminal(terminalId: string): Promise<void> {
+  return invoke<void>('close_terminal', { terminalId });
+}
+
+export async function onTerminalOutput(callback: (event: TerminalOutputEvent) => void): Promise<UnlistenFn> {
+  return listen<TerminalOutputEvent>('terminal:output', (event) => callback(event.payload));
+}
+
+export async function onTerminalExit(callback: (event: TerminalExitEvent) => void): Promise<UnlistenFn> {
+  return listen<TerminalExitEvent>('terminal:exit', (event) => callback(event.payload));
+}
+
+// ============================================================================
+// Attachments
+// ============================================================================
+
+export type Attachment = {
+  id: string;
+  kind: 'audio' | 'image' | 'document';
+  fileName: string;
+  mimeType: string;
+  path: string;
+  /** Transcription or extracted text */
+  text?: string;
+  sourceUrl?: string;
+  createdAt: string;
+};
+
+/** Copy a file into app storage as an attachment */
+export async function addAttachment(path: string): Promise<Attachment> {
+  return invoke<Attachment>('add_attachment', { path });
+}
+
+/** Store base64 data or a data URL (e.g. a pasted screenshot) as an attachment */
+export async function addAttachmentData(fileName: string, data: string): Promise<Attachment> {
+  return invoke<Attachment>('add_attachment_data', { fileName, data });
+}
+
+/** Fetch a web page or PDF as an attachment; recent fetches of the same URL are reused */
+export async function attachUrl(url: string): Promise<Attachment> {
+  return invoke<Attachment>('attach_url', { url });
+}
+
+export async function getAttachment(attachmentId: string): Promise<Attachment | null> {
+  return invoke<Attachment | null>('get_attachment', { attachmentId });
+}
+
+/** Small JPEG data URL of an image attachment; null for other kinds */
+export async function getAttachmentThumbnail(attachmentId: string): Promise<string | null> {
+  return invoke<string | null>('get_attachment_thumbnail', { attachmentId });
+}
+
+/** Largest file that can be attached, in bytes */
+export async function getMaxAttachmentSize(): Promise<number> {
+  return invoke<number>('get_max_attachment_size');
+}
+
+/** Set the largest file that can be attached; null restores the default (50 MB) */
+export async function setMaxAttachmentSize(bytes: number | null): Promise<void> {
+  return invoke<void>('set_max_attachment_size', { bytes });
+}
+
+/** Transcribe an audio attachment (requires an OpenAI API key) */
+export async function transcribeAudio(attachmentId: string): Promise<string> {
+  return invoke<string>('transcribe_audio', { attachmentId });
+}
+
+// ============================================================================
+// Web Search
+// ============================================================================
+
+export type WebSearchSettings = {
+  /** Used for tasks that don't set allowWebSearch */
+  enabledByDefault: boolean;
+  /**
+   * 'fetch' only fetches pages; 'brave' needs a Brave Search API key stored
+   * under the 'brave' provider
+   */
+  backend: 'fetch' | 'exa' | 'brave';
+};
+
+export async function getWebSearchSettings(): Promise<WebSearchSettings> {
+  return invoke<WebSearchSettings>('get_web_search_settings');
+}
+
+export async function setWebSearchSettings(settings: WebSearchSettings): Promise<void> {
+  return invoke('set_web_search_settings', { settings });
+}
+
+// ============================================================================
+// Slash Commands
+// ============================================================================
+
+export type SlashCommandInput = {
+  /** Invoked as /name */
+  name: string;
+  description?: string;
+  /** Prompt template; $ARGUMENTS is replaced with the text after the name */
+  template: string;
+  /** Model used instead of the active provider's model */
+  modelId?: string;
+  /** OpenCode agent used instead of the default agent */
+  agent?: string;
+};
+
+export type SlashCommand = SlashCommandInput & {
+  id: string;
+  createdAt: string;
+  updatedAt: string;
+};
+
+export async function listSlashCommands(): Promise<SlashCommand[]> {
+  return invoke<SlashCommand[]>('list_slash_commands');
+}
+
+export async function createSlashCommand(command: SlashCommandInput): Promise<SlashCommand> {
+  return invoke<SlashCommand>('create_slash_command', { command });
+}
+
+export async function updateSlashCommand(id: string, command: SlashCommandInput): Promise<SlashCommand> {
+  return invoke<SlashCommand>('update_slash_command', { id, command });
+}
+
+export async function deleteSlashCommand(id: string): Promise<void> {
+  return invoke<void>('delete_slash_command', { id });
+}
+
 // ============================================================================
 // Compatibility Helpers
 // ============================================================================
@@ -730,6 +2594,10 @@ export function getTauriApi() {
     getVersion,
     getPlatform,
 
+    // Profiles
+    listProfiles,
+    switchProfile,
+
     // Shell
     openExternal,
 
@@ -737,16 +2605,79 @@ export function getTauriApi() {
     startTask,
     cancelTask,
     interruptTask,
+    awaitTask,
     getTask,
+    getTaskMessages,
+    openObserverWindow,
+    watchTask,
+    getMessageAttachment,
+    getMessageAttachmentThumbnail,
     listTasks,
+    listTasksFiltered,
+    listProjects,
+    createProject,
+    renameProject,
+    deleteProject,
+    moveTasksToProject,
+    addTaskTag,
+    removeTaskTag,
+    listTags,
+    searchTasks,
     deleteTask,
     clearTaskHistory,
+    listArchivedTasks,
+    restoreTask,
+    getMessageDiff,
+    getMessageSources,
+    abortAllTasks,
+    onTasksAborted,
 
     // Permission responses
     respondToPermission,
 
     // Session management
     resumeSession,
+    sendFollowUp,
+    isSessionBusy,
+    syncSession,
+    onSessionSynced,
+    getSessionContext,
+    getInterruptedTasks,
+    getTaskCheckpoint,
+    resumeFromCheckpoint,
+    createCheckpoint,
+    listCheckpoints,
+    deleteCheckpoint,
+    restoreCheckpoint,
+    onTasksInterrupted,
+    getTaskProgress,
+    getTaskTimeline,
+    listTaskArtifacts,
+    getTaskPlan,
+    approveTaskPlan,
+    onTaskPlanReady,
+    forkTask,
+    getTaskForks,
+    rerunTask,
+    getTaskReruns,
+    editMessageAndRerun,
+    getMessageVersions,
+    deleteTaskMessage,
+    redactTaskMessage,
+    annotateMessage,
+    rateMessage,
+    listMessageRatings,
+    getTaskNotes,
+    addTaskNote,
+    updateTaskNote,
+    deleteTaskNote,
+    listFollowUps,
+    onTaskReminder,
+
+    // Model comparison
+    startComparisonTask,
+    getComparison,
+    diffTasks,
 
     // Settings
     getApiKeys,
@@ -755,6 +2686,12 @@ export function getTauriApi() {
     getDebugMode,
     setDebugMode,
     getAppSettings,
+    getLastWorkspace,
+    setLastWorkspace,
+    listWorkspaces,
+    createWorkspace,
+    selectWorkspace,
+    detectWorkspace,
 
     // API Key management
     hasApiKey,
@@ -765,18 +2702,29 @@ export function getTauriApi() {
     clearApiKey,
     getAllApiKeys,
     hasAnyApiKey,
+    runProviderDiagnostics,
+    detectAvailableProviders,
 
     // Onboarding
     getOnboardingComplete,
     setOnboardingComplete,
+    getStartupHealth,
+    onStartupHealth,
 
     // Claude CLI
     checkClaudeCli,
     getClaudeVersion,
+    installOpencodeCli,
+    onCliInstallProgress,
+    onCliOutdated,
+    getCliRuntime,
+    setCliRuntime,
 
     // Model selection
     getSelectedModel,
     setSelectedModel,
+    getSummaryModel,
+    setSummaryModel,
 
     // Ollama configuration
     testOllamaConnection,
@@ -806,6 +2754,9 @@ export function getTauriApi() {
 
     // E2E Testing
     isE2EMode,
+    isSafeMode,
+    exitSafeMode,
+    seedTestData,
 
     // Provider Settings API
     getProviderSettings,
@@ -814,6 +2765,8 @@ export function getTauriApi() {
     setConnectedProvider,
     removeConnectedProvider,
     updateProviderModel,
+    listModelParameters,
+    setModelParameters,
     setProviderDebugMode,
     getProviderDebugMode,
 
@@ -822,13 +2775,119 @@ export function getTauriApi() {
     onTaskUpdateBatch,
     onPermissionRequest,
     onTaskProgress,
+    onTaskArtifacts,
     onDebugLog,
     onDebugModeChange,
     onTaskStatusChange,
     onTaskSummary,
+    onTaskTitle,
 
     // Logging
     logEvent,
+    getLogLevel,
+    setLogLevel,
+    queryLogs,
+    getSlowCommands,
+
+    // Crash reporting
+    listCrashReports,
+    submitCrashReport,
+    exportDiagnostics,
+    getDbStats,
+    getStorageSavings,
+    dedupAttachments,
+    getBackupSettings,
+    setBackupSettings,
+    createBackup,
+    listBackups,
+    restoreBackup,
+    getDowngradeNotice,
+    importDowngradedTasks,
+    dismissDowngradeNotice,
+    exportTaskHtml,
+    exportTaskPdf,
+    exportTaskShareable,
+    exportTask,
+    setGithubToken,
+    hasGithubToken,
+    removeGithubToken,
+    detectGithubRepo,
+    createGithubIssue,
+    createGithubDraftPr,
+    getApiServerStatus,
+    setApiServerEnabled,
+    setApiServerWebsocketEnabled,
+    setApiServerMetricsEnabled,
+    getApiServerToken,
+    regenerateApiServerToken,
+    getSyncFolder,
+    setSyncFolder,
+    syncHistoryFolder,
+    onHistorySynced,
+    getEncryptedSyncStatus,
+    setEncryptedSyncEnabled,
+    pairDevice,
+    syncNow,
+    getBudgetLimits,
+    setBudgetLimits,
+    getBudgetStatus,
+    onBudgetWarning,
+    onBudgetExceeded,
+    getUsageDashboard,
+    getTaskUsage,
+    getUsageSummary,
+    exportTaskMetricsCsv,
+    importClaudeCodeSessions,
+    importOpencodeSessions,
+    getWorkspaceHooks,
+    setWorkspaceHooks,
+    getWorkspaceDefaults,
+    setWorkspaceDefaults,
+    getWorkspaceAgentSettings,
+    setWorkspaceAgentSettings,
+    listWorkspaceTemplates,
+    createWorkspaceFromTemplate,
+    exportWorkspaceTemplate,
+    previewWorkspaceTemplate,
+    importWorkspaceTemplate,
+    deleteWorkspaceTemplate,
+    getWorkspacePostprocess,
+    setWorkspacePostprocess,
+    getWorkspaceSandbox,
+    setWorkspaceSandbox,
+    indexProject,
+    searchProjectFiles,
+    getWorkspaceEnv,
+    setWorkspaceEnv,
+    getTaskEnv,
+    setTaskEnv,
+    onTaskHook,
+    listSshHosts,
+    createSshHost,
+    deleteSshHost,
+    testSshHost,
+    createRemoteWorkspace,
+    readRemoteFile,
+    openTerminal,
+    writeTerminal,
+    resizeTerminal,
+    closeTerminal,
+    onTerminalOutput,
+    onTerminalExit,
+    addAttachment,
+    addAttachmentData,
+    attachUrl,
+    getAttachment,
+    getAttachmentThumbnail,
+    getMaxAttachmentSize,
+    setMaxAttachmentSize,
+    transcribeAudio,
+    getWebSearchSettings,
+    setWebSearchSettings,
+    listSlashCommands,
+    createSlashCommand,
+    updateSlashCommand,
+    deleteSlashCommand,
   };
 }
 
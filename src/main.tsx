@@ -1,7 +1,6 @@
 import { StrictMode } from 'react';
 import { createRoot } from 'react-dom/client';
 import { HashRouter } from 'react-router-dom';
-import App from './App';
 import './styles/globals.css';
 
 const container = document.getElementById('root');
@@ -10,10 +9,25 @@ if (!container) {
 }
 
 const root = createRoot(container);
-root.render(
-  <StrictMode>
-    <HashRouter>
-      <App />
-    </HashRouter>
-  </StrictMode>
-);
+
+// Observer windows only load their page: the app's store subscribes to events they can't receive
+const observedTaskId = new URLSearchParams(window.location.search).get('observe');
+if (observedTaskId) {
+  void import('./pages/Observer').then(({ default: ObserverPage }) => {
+    root.render(
+      <StrictMode>
+        <ObserverPage taskId={observedTaskId} />
+      </StrictMode>
+    );
+  });
+} else {
+  void import('./App').then(({ default: App }) => {
+    root.render(
+      <StrictMode>
+        <HashRouter>
+          <App />
+        </HashRouter>
+      </StrictMode>
+    );
+  });
+}
@@ -120,6 +120,9 @@ export default function ExecutionPage() {
   // Elapsed time for startup indicator
   const [elapsedTime, setElapsedTime] = useState(0);
 
+  // Why approving the pending plan failed, such as a reached budget
+  const [planError, setPlanError] = useState<string | null>(null);
+
   const {
     currentTask,
     loadTaskById,
@@ -133,6 +136,8 @@ export default function ExecutionPage() {
     respondToPermission,
     sendFollowUp,
     interruptTask,
+    pendingPlan,
+    approvePlan,
     setupProgress,
     setupProgressTaskId,
     setupDownloadStep,
@@ -471,6 +476,13 @@ export default function ExecutionPage() {
             Stopped
           </span>
         );
+      case 'awaiting_approval':
+        return (
+          <span className="inline-flex items-center gap-1.5 px-2.5 py-1 rounded-full text-xs font-medium bg-amber-500/10 text-amber-600 shrink-0">
+            <FileText className="h-3 w-3" />
+            Awaiting approval
+          </span>
+        );
       default:
         return (
           <span className="inline-flex items-center gap-1.5 px-2.5 py-1 rounded-full text-xs font-medium bg-muted text-muted-foreground shrink-0">
@@ -1049,6 +1061,39 @@ export default function ExecutionPage() {
         </div>
       )}
 
+      {/* Plan awaiting approval */}
+      {currentTask.status === 'awaiting_approval' && (
+        <div className="flex-shrink-0 border-t border-border bg-card/50 px-6 py-4" data-testid="execution-plan">
+          <div className="max-w-4xl mx-auto">
+            <div className="flex items-center gap-2 text-sm font-medium text-foreground mb-2">
+              <FileText className="h-4 w-4" />
+              Proposed plan
+            </div>
+            {pendingPlan?.taskId === currentTask.id && (
+              <div className="max-h-64 overflow-y-auto rounded-md border border-border bg-background px-4 py-3 mb-3 prose prose-sm max-w-none">
+                <ReactMarkdown>{pendingPlan.plan}</ReactMarkdown>
+              </div>
+            )}
+            <div className="flex items-center justify-end gap-3">
+              <p className={cn('text-xs mr-auto break-words', planError ? 'text-destructive' : 'text-muted-foreground')}>
+                {planError ?? 'Nothing has been changed yet. Approve to carry out the plan.'}
+              </p>
+              <Button
+                onClick={() => {
+                  setPlanError(null);
+                  approvePlan().catch((err) => setPlanError(err instanceof Error ? err.message : String(err)));
+                }}
+                disabled={isLoading}
+                data-testid="execution-approve-plan"
+              >
+                <Check className="h-4 w-4 mr-1.5" />
+                Approve plan
+              </Button>
+            </div>
+          </div>
+        </div>
+      )}
+
       {/* Follow-up input */}
       {canFollowUp && (
         <div className="flex-shrink-0 border-t border-border bg-card/50 px-6 py-4">
@@ -1097,6 +1142,9 @@ export default function ExecutionPage() {
           <p className="text-sm text-muted-foreground mb-3">
             Task {currentTask.status === 'interrupted' ? 'stopped' : currentTask.status}
           </p>
+          {currentTask.result?.error && (
+            <p className="text-sm text-destructive mb-3 break-words">{currentTask.result.error}</p>
+          )}
           <Button onClick={() => navigate('/')}>
             Start New Task
           </Button>
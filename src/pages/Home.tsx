@@ -84,10 +84,15 @@ export default function HomePage() {
   const [prompt, setPrompt] = useState('');
   const [showExamples, setShowExamples] = useState(true);
   const [showSettingsDialog, setShowSettingsDialog] = useState(false);
-  const { startTask, isLoading, addTaskUpdate, setPermissionRequest } = useTaskStore();
+  const { startTask, isLoading, addTaskUpdate, setPermissionRequest, loadWorkspace } = useTaskStore();
   const navigate = useNavigate();
   const accomplish = getAccomplish();
 
+  // New tasks run in the workspace picked last time
+  useEffect(() => {
+    void loadWorkspace();
+  }, [loadWorkspace]);
+
   // Subscribe to task events
   useEffect(() => {
     const unsubscribeTask = accomplish.onTaskUpdate((event) => {
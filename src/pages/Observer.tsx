@@ -0,0 +1,136 @@
+/**
+ * Observer - Read-only view of one task in its own window
+ *
+ * Opened with `openObserverWindow`; the task ID comes from the `observe` query
+ * parameter. Updates arrive through `watchTask`, which only carries this task's
+ * events, so the window never sees other tasks or workspaces.
+ */
+
+import { useEffect, useRef, useState } from 'react';
+import ReactMarkdown from 'react-markdown';
+import { Eye, Loader2, Terminal, Wrench } from 'lucide-react';
+import * as api from '../lib/tauri-api';
+import type { Task, TaskMessage, TaskStatus, TaskUpdateEvent } from '@/shared';
+import { cn } from '@/lib/utils';
+
+const proseClasses = 'prose prose-sm max-w-none dark:prose-invert break-words';
+
+function applyUpdate(task: Task, update: TaskUpdateEvent): Task {
+  switch (update.type) {
+    case 'message': {
+      const message = update.message as TaskMessage;
+      const index = task.messages.findIndex((m) => m.id === message.id);
+      const messages =
+        index === -1
+          ? [...task.messages, message]
+          : task.messages.map((m, i) => (i === index ? message : m));
+      return { ...task, messages };
+    }
+    case 'complete': {
+      const status: TaskStatus =
+        update.result?.status === 'success'
+          ? 'completed'
+          : update.result?.status === 'interrupted'
+            ? 'interrupted'
+            : 'failed';
+      return { ...task, status, result: update.result };
+    }
+    case 'error':
+      return { ...task, status: 'failed' };
+    default:
+      return task;
+  }
+}
+
+function ObservedMessage({ message }: { message: TaskMessage }) {
+  if (message.type === 'tool') {
+    return (
+      <div className="flex items-center gap-2 text-sm text-muted-foreground">
+        <Wrench className="h-4 w-4" />
+        <span>{message.toolName || 'Tool'}</span>
+      </div>
+    );
+  }
+  return (
+    <div
+      className={cn(
+        'rounded-xl px-4 py-3',
+        message.type === 'user' ? 'ml-auto max-w-[80%] bg-primary text-primary-foreground' : 'bg-card'
+      )}
+    >
+      {message.type === 'system' && (
+        <div className="mb-1.5 flex items-center gap-1.5 text-xs font-medium text-muted-foreground">
+          <Terminal className="h-3.5 w-3.5" />
+          System
+        </div>
+      )}
+      {message.type === 'user' ? (
+        <p className="whitespace-pre-wrap break-words text-sm">{message.content}</p>
+      ) : (
+        <div className={proseClasses}>
+          <ReactMarkdown>{message.content}</ReactMarkdown>
+        </div>
+      )}
+    </div>
+  );
+}
+
+export default function ObserverPage({ taskId }: { taskId: string }) {
+  const [task, setTask] = useState<Task | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const endRef = useRef<HTMLDivElement>(null);
+
+  useEffect(() => {
+    // Updates that arrive before the stored task are applied once it loads
+    const pending: TaskUpdateEvent[] = [];
+    let loaded = false;
+    api
+      .watchTask(taskId, (update) => {
+        if (!loaded) {
+          pending.push(update);
+          return;
+        }
+        setTask((current) => (current ? applyUpdate(current, update) : current));
+      })
+      .then((stored) => {
+        loaded = true;
+        if (!stored) {
+          setError('This task no longer exists.');
+          return;
+        }
+        setTask(pending.reduce(applyUpdate, stored));
+      })
+      .catch((err) => setError(String(err)));
+  }, [taskId]);
+
+  useEffect(() => {
+    endRef.current?.scrollIntoView({ behavior: 'smooth' });
+  }, [task?.messages.length]);
+
+  if (error) {
+    return <div className="flex min-h-screen items-center justify-center text-muted-foreground">{error}</div>;
+  }
+  if (!task) {
+    return (
+      <div className="flex min-h-screen items-center justify-center bg-background">
+        <Loader2 className="h-8 w-8 animate-spin text-primary" />
+      </div>
+    );
+  }
+
+  return (
+    <div className="flex h-screen flex-col bg-background">
+      <header className="flex items-center gap-2 border-b px-6 py-3">
+        <Eye className="h-4 w-4 text-muted-foreground" />
+        <h1 className="truncate text-sm font-medium">{task.title || task.prompt}</h1>
+        <span className="ml-auto text-xs capitalize text-muted-foreground">{task.status.replace('_', ' ')}</span>
+      </header>
+      <main className="flex-1 space-y-4 overflow-y-auto px-6 py-4">
+        {task.messages.map((message) => (
+          <ObservedMessage key={message.id} message={message} />
+        ))}
+        <div ref={endRef} />
+      </main>
+    </div>
+  );
+}
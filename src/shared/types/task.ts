@@ -7,6 +7,7 @@ export type TaskStatus =
   | 'queued'
   | 'running'
   | 'waiting_permission'
+  | 'awaiting_approval'
   | 'completed'
   | 'failed'
   | 'cancelled'
@@ -17,7 +18,7 @@ export interface TaskConfig {
   prompt: string;
   /** Optional task ID to correlate events */
   taskId?: string;
-  /** Working directory for Claude Code operations */
+  /** Local directory to run in when no workspace is given; its workspace is created if needed */
   workingDirectory?: string;
   /** List of allowed tools */
   allowedTools?: string[];
@@ -27,6 +28,29 @@ export interface TaskConfig {
   outputSchema?: object;
   /** Session ID for resuming */
   sessionId?: string;
+  /** Start even if a budget limit has been reached */
+  overrideBudget?: boolean;
+  /** Workspace to run in; its directory and pre/post-task hooks are used */
+  workspaceId?: string;
+  /** Propose a read-only plan first; execution waits for approveTaskPlan */
+  planFirst?: boolean;
+  /** Attachment IDs: images and PDFs are sent as files, extracted text is added to the prompt */
+  attachments?: string[];
+  /** Let the agent search and fetch the web; defaults to the web search setting */
+  allowWebSearch?: boolean;
+  /** Environment variables for this task, on top of the workspace's */
+  env?: EnvVar[];
+  /** Project to file the task under */
+  projectId?: string;
+}
+
+/** Environment variable of a workspace or task */
+export interface EnvVar {
+  name: string;
+  /** Never returned for secrets; leave unset to keep a stored secret */
+  value?: string | null;
+  /** Stored in the OS keychain instead of the database */
+  secret?: boolean;
 }
 
 export interface Task {
@@ -34,19 +58,32 @@ export interface Task {
   prompt: string;
   /** AI-generated short summary of the task (displayed in history) */
   summary?: string;
+  /** Short generated title shown in the sidebar */
+  title?: string;
   status: TaskStatus;
   sessionId?: string;
   messages: TaskMessage[];
   createdAt: string;
   startedAt?: string;
   completedAt?: string;
+  /** Last time a message was added or the status changed */
+  updatedAt?: string;
   result?: TaskResult;
+  /** Whether the agent was allowed to search and fetch the web */
+  allowWebSearch?: boolean;
+  /** The user's tags; left out when there are none */
+  tags?: string[];
 }
 
 export interface TaskAttachment {
-  type: 'screenshot' | 'json';
-  data: string; // base64 for images, JSON string for data
+  /** Stored attachment ID, for getMessageAttachment */
+  id?: number;
+  type: 'screenshot' | 'json' | 'audio';
+  /** Left out of loaded tasks unless requested; fetch it with getMessageAttachment */
+  data?: string; // base64 for images, JSON string for data, attachment ID for audio
   label?: string; // e.g., "Screenshot after clicking Submit"
+  /** Length of the stored data */
+  size?: number;
 }
 
 export interface TaskMessage {
@@ -58,6 +95,10 @@ export interface TaskMessage {
   timestamp: string;
   /** Attachments like screenshots captured during browser automation */
   attachments?: TaskAttachment[];
+  /** User note added with annotateMessage */
+  note?: string;
+  /** ID of the sidecar event the message came from, so a replayed event isn't stored twice */
+  originId?: string;
 }
 
 export interface TaskResult {
@@ -65,6 +106,8 @@ export interface TaskResult {
   sessionId?: string;
   durationMs?: number;
   error?: string;
+  /** Final reply parsed and validated against TaskConfig.outputSchema */
+  structuredOutput?: unknown;
 }
 
 /**
@@ -89,6 +132,12 @@ export interface TaskProgress {
   modelName?: string;
   /** Whether this is the first task (cold start) */
   isFirstTask?: boolean;
+  /** Steps done: todos completed when the agent keeps a todo list, else agent steps started */
+  step?: number;
+  /** Steps in the agent's todo list; unset when there is none */
+  totalSteps?: number;
+  /** What the current step is doing */
+  label?: string;
 }
 
 export interface TaskUpdateEvent {
@@ -42,6 +42,7 @@ interface TaskState {
 
   // Task history
   tasks: Task[];
+  historySort: api.TaskSort;
 
   // Permission handling
   permissionRequest: PermissionRequest | null;
@@ -55,6 +56,15 @@ interface TaskState {
   startupStage: StartupStageInfo | null;
   startupStageTaskId: string | null;
 
+  // Tasks the previous run left in progress, to offer resuming
+  interruptedTasks: api.InterruptedTask[];
+
+  // Plan of the current task while it awaits approval
+  pendingPlan: api.TaskPlan | null;
+
+  // Workspace new tasks run in, unless their config names one
+  workspace: api.Workspace | null;
+
   // Task launcher
   isLauncherOpen: boolean;
   openLauncher: () => void;
@@ -62,19 +72,28 @@ interface TaskState {
 
   // Actions
   startTask: (config: TaskConfig) => Promise<Task | null>;
+  loadWorkspace: () => Promise<void>;
+  selectWorkspace: (workspaceId: string | null) => Promise<void>;
   setSetupProgress: (taskId: string | null, message: string | null) => void;
   setStartupStage: (taskId: string | null, stage: string | null, message?: string, modelName?: string, isFirstTask?: boolean) => void;
   clearStartupStage: (taskId: string) => void;
   sendFollowUp: (message: string) => Promise<void>;
   cancelTask: () => Promise<void>;
   interruptTask: () => Promise<void>;
+  abortAllTasks: () => Promise<void>;
   setPermissionRequest: (request: PermissionRequest | null) => void;
   respondToPermission: (response: PermissionResponse) => Promise<void>;
   addTaskUpdate: (event: TaskUpdateEvent) => void;
   addTaskUpdateBatch: (event: TaskUpdateBatchEvent) => void;
   updateTaskStatus: (taskId: string, status: TaskStatus) => void;
   setTaskSummary: (taskId: string, summary: string) => void;
+  setTaskTitle: (taskId: string, title: string) => void;
+  setInterruptedTasks: (tasks: api.InterruptedTask[]) => void;
+  dismissInterruptedTask: (taskId: string) => void;
+  setPendingPlan: (event: api.PlanReadyEvent) => void;
+  approvePlan: () => Promise<void>;
   loadTasks: () => Promise<void>;
+  setHistorySort: (sort: api.TaskSort) => Promise<void>;
   loadTaskById: (taskId: string) => Promise<void>;
   deleteTask: (taskId: string) => Promise<void>;
   clearHistory: () => Promise<void>;
@@ -90,12 +109,16 @@ export const useTaskStore = create<TaskState>((set, get) => ({
   isLoading: false,
   error: null,
   tasks: [],
+  historySort: 'created',
   permissionRequest: null,
   setupProgress: null,
   setupProgressTaskId: null,
   setupDownloadStep: 1,
   startupStage: null,
   startupStageTaskId: null,
+  interruptedTasks: [],
+  pendingPlan: null,
+  workspace: null,
   isLauncherOpen: false,
 
   setSetupProgress: (taskId: string | null, message: string | null) => {
@@ -153,7 +176,12 @@ export const useTaskStore = create<TaskState>((set, get) => ({
         message: 'UI start task',
         context: { prompt: config.prompt, taskId: config.taskId },
       });
-      const task = await api.startTask(config);
+      const { workspace } = get();
+      const task = await api.startTask(
+        workspace && !config.workspaceId && !config.workingDirectory
+          ? { ...config, workspaceId: workspace.id }
+          : config
+      );
       // Task might be 'running' or 'queued' depending on if another task is running
       // Also add to tasks list so sidebar updates immediately
       const currentTasks = get().tasks;
@@ -248,12 +276,19 @@ export const useTaskStore = create<TaskState>((set, get) => ({
         message: 'UI follow-up sent',
         context: { taskId: currentTask.id, message },
       });
-      const task = await api.resumeSession(sessionId, message, currentTask.id);
+      const task = await api.sendFollowUp(taskId, message);
+      const stored = task.messages[0];
 
-      // Update status based on response (could be 'running' or 'queued')
+      // Update status based on response, and take on the stored message's ID
       set((state) => ({
         currentTask: state.currentTask
-          ? { ...state.currentTask, status: task.status }
+          ? {
+              ...state.currentTask,
+              status: task.status,
+              messages: stored
+                ? state.currentTask.messages.map((m) => (m.id === userMessage.id ? stored : m))
+                : state.currentTask.messages,
+            }
           : null,
         isLoading: task.status === 'queued',
         tasks: state.tasks.map((t) =>
@@ -261,6 +296,24 @@ export const useTaskStore = create<TaskState>((set, get) => ({
         ),
       }));
     } catch (err) {
+      if (api.isSessionBusy(err)) {
+        // The session is still going elsewhere, so this task didn't fail
+        set((state) => ({
+          error: 'This session is busy in another task. Try again once it finishes.',
+          isLoading: false,
+          currentTask: state.currentTask
+            ? {
+                ...state.currentTask,
+                status: currentTask.status,
+                messages: state.currentTask.messages.filter((m) => m.id !== userMessage.id),
+              }
+            : null,
+          tasks: state.tasks.map((t) =>
+            t.id === taskId ? { ...t, status: currentTask.status } : t
+          ),
+        }));
+        return;
+      }
       set((state) => ({
         error: err instanceof Error ? err.message : 'Failed to send message',
         isLoading: false,
@@ -312,6 +365,23 @@ export const useTaskStore = create<TaskState>((set, get) => ({
     }
   },
 
+  abortAllTasks: async () => {
+    void api.logEvent({ level: 'warn', message: 'UI abort all tasks' });
+    // The backend stores the cancelled status
+    const { taskIds } = await api.abortAllTasks();
+    const aborted = new Set(taskIds);
+    set((state) => ({
+      currentTask:
+        state.currentTask && aborted.has(state.currentTask.id)
+          ? { ...state.currentTask, status: 'cancelled' }
+          : state.currentTask,
+      tasks: state.tasks.map((t) =>
+        aborted.has(t.id) ? { ...t, status: 'cancelled' as TaskStatus } : t
+      ),
+      isLoading: false,
+    }));
+  },
+
   setPermissionRequest: (request) => {
     set({ permissionRequest: request });
   },
@@ -333,28 +403,7 @@ export const useTaskStore = create<TaskState>((set, get) => ({
       context: { ...event },
     });
 
-    // Persist message to database
-    if (event.type === 'message' && event.message) {
-      api.saveTaskMessage(event.taskId, event.message).catch((err) => {
-        console.error('Failed to save task message:', err);
-      });
-    }
-
-    // Persist complete event to database
-    if (event.type === 'complete' && event.result) {
-      const status = event.result.status === 'success' ? 'completed' :
-                    event.result.status === 'interrupted' ? 'interrupted' : 'failed';
-      api.completeTask(event.taskId, status, event.result.sessionId).catch((err) => {
-        console.error('Failed to save task completion:', err);
-      });
-    }
-
-    // Persist error status to database
-    if (event.type === 'error') {
-      api.saveTaskStatus(event.taskId, 'failed').catch((err) => {
-        console.error('Failed to save task error status:', err);
-      });
-    }
+    // Messages, completion and errors are saved by the backend as the sidecar streams them
 
     set((state) => {
       // Determine if this event is for the currently viewed task
@@ -512,14 +561,114 @@ export const useTaskStore = create<TaskState>((set, get) => ({
     });
   },
 
+  setTaskTitle: (taskId: string, title: string) => {
+    // Already stored by the backend when it was generated
+    set((state) => ({
+      tasks: state.tasks.map((task) => (task.id === taskId ? { ...task, title } : task)),
+      currentTask:
+        state.currentTask?.id === taskId ? { ...state.currentTask, title } : state.currentTask,
+    }));
+  },
+
+  setInterruptedTasks: (tasks: api.InterruptedTask[]) => {
+    set((state) => ({
+      interruptedTasks: [
+        ...state.interruptedTasks,
+        ...tasks.filter((t) => !state.interruptedTasks.some((known) => known.taskId === t.taskId)),
+      ],
+      tasks: state.tasks.map((task) =>
+        tasks.some((t) => t.taskId === task.id) ? { ...task, status: 'interrupted' } : task
+      ),
+    }));
+  },
+
+  dismissInterruptedTask: (taskId: string) => {
+    set((state) => ({
+      interruptedTasks: state.interruptedTasks.filter((t) => t.taskId !== taskId),
+    }));
+  },
+
+  setPendingPlan: (event: api.PlanReadyEvent) => {
+    const { taskId, payload } = event;
+    set((state) => {
+      const isCurrentTask = state.currentTask?.id === taskId;
+      return {
+        currentTask: isCurrentTask && state.currentTask
+          ? { ...state.currentTask, status: 'awaiting_approval', sessionId: payload.sessionId }
+          : state.currentTask,
+        tasks: state.tasks.map((t) =>
+          t.id === taskId ? { ...t, status: 'awaiting_approval' as TaskStatus } : t
+        ),
+        pendingPlan: isCurrentTask
+          ? {
+              taskId,
+              plan: payload.plan,
+              sessionId: payload.sessionId,
+              status: 'pending',
+              createdAt: new Date().toISOString(),
+            }
+          : state.pendingPlan,
+        isLoading: isCurrentTask ? false : state.isLoading,
+      };
+    });
+  },
+
+  approvePlan: async () => {
+    const { currentTask } = get();
+    if (!currentTask || currentTask.status !== 'awaiting_approval') {
+      return;
+    }
+    const taskId = currentTask.id;
+    set({ isLoading: true });
+    try {
+      void api.logEvent({ level: 'info', message: 'UI approve plan', context: { taskId } });
+      const task = await api.approveTaskPlan(taskId);
+      set((state) => ({
+        pendingPlan: null,
+        currentTask: state.currentTask?.id === taskId
+          ? { ...state.currentTask, status: task.status }
+          : state.currentTask,
+        tasks: state.tasks.map((t) => (t.id === taskId ? { ...t, status: task.status } : t)),
+        isLoading: false,
+      }));
+    } catch (err) {
+      // The plan stays pending, so the caller can show why and approving can be retried
+      set({ isLoading: false });
+      throw err;
+    }
+  },
+
+  loadWorkspace: async () => {
+    set({ workspace: await api.getLastWorkspace() });
+  },
+
+  selectWorkspace: async (workspaceId: string | null) => {
+    if (workspaceId) {
+      set({ workspace: await api.selectWorkspace(workspaceId) });
+    } else {
+      await api.setLastWorkspace(null);
+      set({ workspace: null });
+    }
+  },
+
   loadTasks: async () => {
-        const tasks = await api.listTasks();
+        const tasks = await api.listTasks(get().historySort);
     set({ tasks });
   },
 
+  setHistorySort: async (sort: api.TaskSort) => {
+    set({ historySort: sort });
+    await get().loadTasks();
+  },
+
   loadTaskById: async (taskId: string) => {
         const task = await api.getTask(taskId);
-    set({ currentTask: task, error: task ? null : 'Task not found' });
+    const plan = task?.status === 'awaiting_approval' ? await api.getTaskPlan(taskId) : null;
+    set({
+      currentTask: task,
+      pendingPlan: plan?.status === 'pending' ? plan : null,
+      error: task ? null : 'Task not found',
+    });
   },
 
   deleteTask: async (taskId: string) => {
@@ -545,6 +694,7 @@ export const useTaskStore = create<TaskState>((set, get) => ({
       setupDownloadStep: 1,
       startupStage: null,
       startupStageTaskId: null,
+      pendingPlan: null,
       isLauncherOpen: false,
     });
   },
@@ -609,8 +759,32 @@ if (typeof window !== 'undefined' && api.isRunningInTauri()) {
     }
   });
 
+  // Show a plan-first task's plan for approval
+  void api.onTaskPlanReady((event) => {
+    const state = useTaskStore.getState();
+    state.setPendingPlan(event);
+    state.clearStartupStage(event.taskId);
+  });
+
   // Subscribe to task summary updates
   void api.onTaskSummary((data) => {
     useTaskStore.getState().setTaskSummary(data.taskId, data.summary);
   });
+
+  // Subscribe to generated task titles
+  void api.onTaskTitle((data) => {
+    useTaskStore.getState().setTaskTitle(data.taskId, data.title);
+  });
+
+  // Offer to resume tasks the previous run left unfinished
+  void api.getInterruptedTasks().then((tasks) => useTaskStore.getState().setInterruptedTasks(tasks));
+  void api.onTasksInterrupted((tasks) => useTaskStore.getState().setInterruptedTasks(tasks));
+
+  // Reload the open task when messages from its session were added
+  void api.onSessionSynced((result) => {
+    const state = useTaskStore.getState();
+    if (result.added && state.currentTask?.id === result.taskId) {
+      void state.loadTaskById(result.taskId);
+    }
+  });
 }